@@ -0,0 +1,121 @@
+//! `functor fmt`: rewrite `.fun` / `.funi` sources into the canonical layout
+//! (`functor_lang::format`), or with `--check` only report the files that
+//! are not — the CI form, which exits non-zero and writes nothing.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Format `paths` (files, or directories searched recursively), relative to
+/// `root`; with no paths, everything under `root`.
+pub fn execute(root: &Path, paths: &[PathBuf], check: bool) -> io::Result<()> {
+    let mut files = Vec::new();
+    if paths.is_empty() {
+        collect(root, &mut files)?;
+    } else {
+        for path in paths {
+            let path = root.join(path);
+            if path.is_dir() {
+                collect(&path, &mut files)?;
+            } else {
+                files.push(path);
+            }
+        }
+    }
+
+    let mut unformatted = 0;
+    let mut failed = 0;
+    for path in &files {
+        let src = fs::read_to_string(path)?;
+        let interface = path.extension().is_some_and(|ext| ext == "funi");
+        match functor_lang::format::format_source(&src, interface) {
+            Ok(formatted) if formatted == src => {}
+            Ok(formatted) => {
+                unformatted += 1;
+                if check {
+                    println!("{} is not formatted", path.display());
+                } else {
+                    fs::write(path, formatted)?;
+                    println!("formatted {}", path.display());
+                }
+            }
+            Err(err) => {
+                failed += 1;
+                let (line, col) = functor_lang::line_col(&src, err.span.start);
+                eprintln!("{}:{line}:{col}: error: {}", path.display(), err.message);
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(io::Error::other(format!(
+            "{failed} file(s) could not be formatted"
+        )));
+    }
+    if check && unformatted > 0 {
+        return Err(io::Error::other(format!(
+            "{unformatted} file(s) need formatting; run `functor fmt`"
+        )));
+    }
+    Ok(())
+}
+
+/// Every `.fun` / `.funi` under `dir`, skipping hidden directories and build
+/// output (`target`, `node_modules`, `dist`), in a stable order.
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    for path in entries {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+        if path.is_dir() {
+            if !name.starts_with('.') && !matches!(name, "target" | "node_modules" | "dist") {
+                collect(&path, files)?;
+            }
+        } else if name.ends_with(".fun") || name.ends_with(".funi") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::execute;
+    use std::fs;
+
+    #[test]
+    fn check_reports_without_writing_and_fmt_rewrites_in_place() {
+        let dir = std::env::temp_dir().join(format!("functor-fmt-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("target")).unwrap();
+        fs::write(dir.join("game.fun"), "let  x=1\n").unwrap();
+        fs::write(dir.join("target/skipped.fun"), "let  y=2\n").unwrap();
+
+        let err = execute(&dir, &[], true).unwrap_err();
+        assert!(
+            err.to_string().contains("1 file(s) need formatting"),
+            "{err}"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("game.fun")).unwrap(),
+            "let  x=1\n"
+        );
+
+        execute(&dir, &[], false).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("game.fun")).unwrap(),
+            "let x = 1\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("target/skipped.fun")).unwrap(),
+            "let  y=2\n"
+        );
+        execute(&dir, &[], true).unwrap();
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -55,6 +55,20 @@ pub struct FunctorLangProject {
     pub cursor: CursorPolicy,
 }
 
+/// How `functor test` runs: the evaluator's options, which tests to run, and
+/// whether a machine-readable report replaces the diagnostics.
+#[derive(Clone)]
+pub struct TestRun {
+    pub options: functor_lang::ExpectOptions,
+    pub selection: functor_runtime_common::functor_lang_test::Selection,
+    pub reporter: Option<functor_runtime_common::functor_lang_test_report::Reporter>,
+    /// `--coverage`: where the LCOV tracefile goes (relative to the project).
+    pub coverage: Option<PathBuf>,
+    /// `--min-coverage`: the total line coverage, in percent, below which the
+    /// run fails.
+    pub min_coverage: Option<f64>,
+}
+
 /// Shell pointer behavior declared by `functor.json`'s `cursor` field.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum CursorPolicy {
@@ -551,17 +565,28 @@ impl FunctorLangProject {
             .map_err(Error::other)
     }
 
-    /// Run the project's inline `expect` tests headlessly under the ENGINE
-    /// prelude (no GL context, no window, no game loop) — the thin CLI shell
-    /// over [`functor_runtime_common::functor_lang_test::run_expects_in`].
+    /// Run the project's inline `expect` and `property` tests headlessly under
+    /// the ENGINE prelude (no GL context, no window, no game loop) — the thin
+    /// CLI shell over [`functor_runtime_common::functor_lang_test::run_expects_in`].
     ///
     /// Takes the project [`Self::build`] already loaded and typechecked, so
     /// the bytes evaluated are exactly the bytes verified (re-loading would
     /// let an editor save land in between). A failure here is therefore a
     /// *runtime* one, rendered as a positioned diagnostic at the `expect`
-    /// that produced it.
-    pub fn test(&self, project: &functor_lang::project::Project) -> Result<(), Error> {
-        let run = match functor_runtime_common::functor_lang_test::run_expects_in(project) {
+    /// that produced it — or, under `--reporter`, a result in the report
+    /// printed to stdout (paths relative to `working_directory`).
+    pub fn test(
+        &self,
+        project: &functor_lang::project::Project,
+        working_directory: &str,
+        settings: &TestRun,
+    ) -> Result<(), Error> {
+        let run = if settings.coverage.is_some() {
+            functor_runtime_common::functor_lang_test::run_covered
+        } else {
+            functor_runtime_common::functor_lang_test::run_selected
+        };
+        let run = match run(project, &settings.options, &settings.selection) {
             Ok(run) => run,
             Err(e) => {
                 emit(Event::Diagnostic {
@@ -578,7 +603,20 @@ impl FunctorLangProject {
             }
         };
 
-        for case in &run.cases {
+        // A report carries every result itself (the event stream is on
+        // stderr then); otherwise each failure is a diagnostic.
+        let diagnostics = match settings.reporter {
+            Some(reporter) => {
+                use std::io::Write;
+                let mut out = std::io::stdout().lock();
+                let report = reporter.render(&run, Path::new(working_directory));
+                out.write_all(report.as_bytes())?;
+                out.flush()?;
+                &[][..]
+            }
+            None => &run.cases[..],
+        };
+        for case in diagnostics {
             let Some(message) = &case.failure else {
                 continue;
             };
@@ -592,11 +630,39 @@ impl FunctorLangProject {
             });
         }
 
+        let gate = match (&run.coverage, &settings.coverage) {
+            (Some(files), Some(path)) => coverage(files, working_directory, path, settings),
+            _ => Ok(()),
+        };
         if run.total() == 0 {
+            let selection = &settings.selection;
+            // Only `--watch` narrows to the modules a save affected; a save
+            // that reaches no test is routine there, not a finding, and an
+            // empty run has no coverage to gate on.
+            if selection.modules.is_some() {
+                emit(Event::Info {
+                    message: "no affected tests".to_string(),
+                });
+                return Ok(());
+            }
+            let narrowed = selection.filter.is_some() || selection.module.is_some();
+            let message = if narrowed {
+                "no tests match the selection"
+            } else {
+                "no `expect`, `property`, or `expectSnapshot` tests found"
+            };
             emit(Event::Info {
-                message: "no `expect` tests found".to_string(),
+                message: message.to_string(),
+            });
+            return gate;
+        }
+        if run.snapshots_written > 0 {
+            emit(Event::Info {
+                message: format!(
+                    "{} snapshot(s) written to __snapshots__",
+                    run.snapshots_written
+                ),
             });
-            return Ok(());
         }
         let (passed, failed) = (run.passed(), run.failed());
         if failed > 0 {
@@ -608,7 +674,75 @@ impl FunctorLangProject {
         emit(Event::Info {
             message: format!("{} expect(s): {passed} passed", run.total()),
         });
-        Ok(())
+        gate
+    }
+
+    /// `test --watch`: run the selection, then poll the project's files and
+    /// re-run — on each save — the tests of the edited modules and their
+    /// dependents (a project that fails to load or check reports it and
+    /// waits for the next save, after which everything selected re-runs).
+    /// A save no selected test depends on reports "no affected tests".
+    /// Polls contents, not mtimes, like `push --watch`.
+    pub async fn test_watch(
+        &self,
+        working_directory: &str,
+        settings: &TestRun,
+    ) -> Result<(), Error> {
+        use functor_runtime_common::functor_lang_test::{affected_modules, SourceSnapshot};
+        let entry = self.entry_path(working_directory)?;
+        emit(Event::Info {
+            message: format!(
+                "watching {} + siblings — re-running affected tests on save (Ctrl-C to stop)",
+                self.entry
+            ),
+        });
+        // The contents last run against, and whether that run loaded (only
+        // then can the next change be narrowed to what it affects).
+        let mut seen: Option<SourceSnapshot> = None;
+        let mut loaded = false;
+        loop {
+            // Atomic-save editors briefly unlink files mid-save; wait for the
+            // next poll rather than failing the loop.
+            if let Ok(current) = SourceSnapshot::read(&entry) {
+                if seen.as_ref() != Some(&current) {
+                    let changed = (seen.as_ref())
+                        .filter(|_| loaded)
+                        .map(|seen| current.changed_since(seen));
+                    let project = self.build(working_directory, false);
+                    loaded = project.is_ok();
+                    let outcome = project.and_then(|project| {
+                        let mut selection = settings.selection.clone();
+                        selection.modules =
+                            changed.and_then(|paths| affected_modules(&project, &paths));
+                        let settings = TestRun {
+                            selection,
+                            ..settings.clone()
+                        };
+                        self.test(&project, working_directory, &settings)
+                    });
+                    if let Err(e) = outcome {
+                        emit(Event::Warning {
+                            message: e.to_string(),
+                        });
+                    }
+                    seen = Some(current);
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        }
+    }
+
+    /// An interactive REPL over the project under the ENGINE prelude — the
+    /// thin CLI shell over [`functor_runtime_common::functor_lang_repl`].
+    /// Like `functor-lang run`, there is no typecheck gate up front: each
+    /// input is checked as it is entered, and errors elsewhere in the project
+    /// don't keep you from poking at the parts that work.
+    pub fn repl(&self, working_directory: &str) -> Result<(), Error> {
+        let entry = self.entry_path(working_directory)?;
+        let mut repl =
+            functor_runtime_common::functor_lang_repl::repl(&entry).map_err(Error::other)?;
+        let stdin = std::io::stdin();
+        functor_runtime_common::functor_lang_repl::run(&mut repl, stdin.lock(), std::io::stdout())
     }
 
     /// Spawn the runner on the entry (`run` and `develop` — hot reload is
@@ -1624,6 +1758,65 @@ Content-Length: {}\r\nConnection: close\r\n\r\n",
     Ok((status, body))
 }
 
+/// `functor test --coverage`: write the LCOV tracefile to `path` (relative to
+/// the project), emit a summary row per module, and apply `--min-coverage`
+/// to the total line coverage.
+fn coverage(
+    files: &[functor_lang::coverage::FileCoverage],
+    working_directory: &str,
+    path: &Path,
+    settings: &TestRun,
+) -> Result<(), Error> {
+    let root = Path::new(working_directory);
+    let target = root.join(path);
+    if let Some(dir) = target.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&target, functor_lang::coverage::lcov(files, root))?;
+    let (mut hit, mut found) = (0, 0);
+    for file in files {
+        let (lines_hit, lines_found) = file.line_counts();
+        let (branches_hit, branches_found) = file.branch_counts();
+        let (functions_hit, functions_found) = file.function_counts();
+        let missed = file
+            .branches
+            .iter()
+            .filter(|branch| branch.taken.contains(&false));
+        let mut missed_branches: Vec<usize> = missed.map(|branch| branch.line).collect();
+        missed_branches.sort_unstable();
+        missed_branches.dedup();
+        emit(Event::Coverage {
+            module: file.module.clone(),
+            lines_hit,
+            lines_found,
+            branches_hit,
+            branches_found,
+            functions_hit,
+            functions_found,
+            missed_branches,
+        });
+        hit += lines_hit;
+        found += lines_found;
+    }
+    let percent = if found == 0 {
+        100.0
+    } else {
+        hit as f64 * 100.0 / found as f64
+    };
+    emit(Event::Info {
+        message: format!(
+            "line coverage {percent:.1}% ({hit}/{found}), written to {}",
+            path.display()
+        ),
+    });
+    match settings.min_coverage {
+        Some(min) if percent < min => Err(Error::other(format!(
+            "line coverage {percent:.1}% is below --min-coverage {min}%"
+        ))),
+        _ => Ok(()),
+    }
+}
+
 /// The 1-based `line`th line of `src`, without its newline — `None` when the
 /// line is out of range (a defensive fail-soft: the caret is a nicety, never a
 /// hard dependency of surfacing the diagnostic).
@@ -83,6 +83,8 @@ fn print_report(path: &str, report: &ModelReport) {
             p.weight_count,
             p.has_skinning,
         );
+        let name = p.material.name.as_deref().unwrap_or("<no name>");
+        println!("      material {}: {}", name, p.material.summary());
     }
     println!();
 
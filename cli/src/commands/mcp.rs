@@ -942,9 +942,9 @@ pub struct LaunchArgs {
     /// or the sole entry. Launch one session per role to simulate a
     /// multiplayer game.
     pub entry: Option<String>,
-    /// `"hidden"` (default) renders into an invisible window — `capture_frame`
-    /// works. `"headless"` creates no GL context at all — no display or GPU
-    /// needed, but `capture_frame` then fails.
+    /// `"hidden"` (default) renders into an invisible window with the GL
+    /// renderer. `"headless"` creates no GL context at all — no display or GPU
+    /// needed — and `capture_frame` draws with the CPU software renderer.
     pub mode: Option<String>,
     /// Include the runtime's full endpoint index in the response (default
     /// false). The response always carries `protocol_version`, which is the
@@ -1015,8 +1015,8 @@ pub struct LaunchGroupArgs {
     /// entry, with a role named `server` first.
     pub roles: Option<Vec<String>>,
     /// `"headless"` (default here — a group is usually driven, not watched)
-    /// creates no GL context at all; `"hidden"` renders into an invisible
-    /// window so `capture_frame` returns pixels.
+    /// creates no GL context at all (`capture_frame` renders in software);
+    /// `"hidden"` renders into an invisible GL window.
     pub mode: Option<String>,
 }
 
@@ -1204,9 +1204,8 @@ impl FunctorMcp {
     /// and return its session id. The project comes from `dir` (a directory
     /// holding `functor.json`) OR from `files` — the whole project inline, so
     /// a client with no filesystem can run a game it just wrote. Defaults to
-    /// `hidden` mode (an invisible GL window, so `capture_frame` returns
-    /// pixels); `headless` needs no display or GPU at all but has no pixels,
-    /// so `capture_frame` fails there.
+    /// `hidden` mode (an invisible GL window); `headless` needs no display or
+    /// GPU at all, and `capture_frame` there renders in software.
     #[tool]
     async fn launch_game(
         &self,
@@ -1218,8 +1217,8 @@ impl FunctorMcp {
             "headless" => "--headless",
             other => {
                 return tool_error(format!(
-                    "unknown mode {other:?}: expected \"hidden\" (pixels, capture_frame works) \
-or \"headless\" (no GL, no capture)"
+                    "unknown mode {other:?}: expected \"hidden\" (an invisible GL window) \
+or \"headless\" (no GL; software-rendered captures)"
                 ))
             }
         };
@@ -1320,7 +1319,7 @@ files writes the inline project to a scratch directory this server owns",
             other => {
                 return tool_error(format!(
                     "unknown mode {other:?}: expected \"headless\" (default; no GL) or \
-\"hidden\" (an invisible window, so capture_frame works)"
+\"hidden\" (an invisible GL window)"
                 ))
             }
         };
@@ -1724,10 +1723,10 @@ MCP server before reconnecting or reusing that runtime URL.",
         self.proxy_get(&args.session, "/trace").await
     }
 
-    /// Render the next frame and return it as a PNG image. Requires a GL
-    /// context: a session launched in `headless` mode has no pixels and this
-    /// fails — relaunch it in `hidden` mode. Raw capture responses are capped
-    /// at 8 MiB.
+    /// Render the next frame and return it as a PNG image. A session launched
+    /// in `headless` mode renders it with the CPU software renderer (no mip
+    /// filtering, terrain skipped); `hidden` mode reads back the GL frame.
+    /// Raw capture responses are capped at 8 MiB.
     #[tool]
     async fn capture_frame(
         &self,
@@ -2857,16 +2856,10 @@ runtime, restart both the runtime and this MCP server before reconnecting."
             "the captured PNG",
         )
         .await?;
-        // 503 is "no pixels right now", and the runtime says WHICH reason —
-        // headless, a dozing XR session, a capture timeout. Pass its own words
-        // through and only append the hint, so an attached Quest is not told to
-        // relaunch a process this server does not own.
+        // 503 is "no pixels right now", and the runtime says WHICH reason — a
+        // dozing XR session, a capture timeout. Pass its own words through.
         if status.as_u16() == 503 {
-            return Err(format!(
-                "POST /capture -> 503: {}\n\nA session launched with mode \"headless\" has no GL \
-context at all — relaunch it with mode \"hidden\" to capture frames.",
-                String::from_utf8_lossy(&body)
-            ));
+            return Err(format!("POST /capture -> 503: {}", String::from_utf8_lossy(&body)));
         }
         if !status.is_success() {
             return Err(format!(
@@ -1,4 +1,5 @@
 pub mod docs;
+pub mod fmt;
 pub mod functor_lang_project;
 pub mod import;
 pub mod init;
@@ -87,6 +87,27 @@ impl From<&DocsFormat> for functor_docgen::OutputFormat {
     }
 }
 
+/// `functor test --reporter`: a machine-readable report on stdout.
+#[derive(ValueEnum, Clone, Debug)]
+enum TestReporter {
+    /// JUnit XML, one `<testsuite>` per module.
+    Junit,
+    /// TAP version 13.
+    Tap,
+    /// ndjson, one object per test.
+    Json,
+}
+
+impl From<&TestReporter> for functor_runtime_common::functor_lang_test_report::Reporter {
+    fn from(value: &TestReporter) -> Self {
+        match value {
+            TestReporter::Junit => Self::Junit,
+            TestReporter::Tap => Self::Tap,
+            TestReporter::Json => Self::Json,
+        }
+    }
+}
+
 impl Environment {
     fn default(maybe_env: &Option<Environment>) -> Environment {
         maybe_env.clone().unwrap_or(Environment::Native)
@@ -119,6 +140,17 @@ enum Command {
         #[arg(long, value_name = "PATH", conflicts_with = "output")]
         check: Option<PathBuf>,
     },
+    /// Rewrite `.fun` / `.funi` sources into the canonical layout, comments
+    /// kept. Formats every source under the project directory, or just the
+    /// given files and directories. E.g. `functor fmt --check` in CI.
+    Fmt {
+        /// Files or directories to format (default: the project directory).
+        paths: Vec<PathBuf>,
+
+        /// Only report unformatted files, exiting non-zero if there are any.
+        #[arg(long)]
+        check: bool,
+    },
     /// Scaffold a new Functor Lang project (defaults to the 3d template).
     Init {
         #[arg(value_enum, default_value = "3d")]
@@ -134,10 +166,67 @@ enum Command {
     },
     /// Run the project's inline `expect` tests headlessly under the engine
     /// prelude — no GPU, no window. Typechecks first (like `build`), then
-    /// evaluates every `expect` in the entry and its sibling modules,
-    /// reporting each failure at its source location. Exits non-zero if any
-    /// expect fails. E.g. `functor -d examples/platformer test`.
-    Test,
+    /// evaluates every `expect`, `property`, and `expectSnapshot` in the
+    /// entry and its sibling modules, reporting each failure at its source
+    /// location (a falsified property with its shrunk inputs and seed, a
+    /// changed snapshot with a path per difference). Exits non-zero if any
+    /// fails. E.g. `functor -d examples/platformer test`, or `functor test
+    /// --module Physics --reporter junit > junit.xml` in CI.
+    Test {
+        /// Seed for `property` inputs; a failure reports the seed it used,
+        /// and passing it back replays the same cases.
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Generated cases per `property` (default 100).
+        #[arg(long)]
+        cases: Option<u32>,
+
+        /// Accept every `expectSnapshot` value as it is now: changed
+        /// snapshots are rewritten in `__snapshots__` instead of failing.
+        #[arg(long)]
+        update_snapshots: bool,
+
+        /// Run only the tests whose source contains this text (e.g.
+        /// `--filter clamp`).
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Run only one module's tests: a file's (`Physics`, including its
+        /// inline `module` blocks) or one block (`Physics.Broadphase`).
+        #[arg(long)]
+        module: Option<String>,
+
+        /// Print a machine-readable report on stdout, one result per test
+        /// (the event stream moves to stderr).
+        #[arg(long, value_enum)]
+        reporter: Option<TestReporter>,
+
+        /// Keep watching the project and re-run the tests an edit can affect
+        /// (the edited modules and their dependents) on every save.
+        #[arg(long)]
+        watch: bool,
+
+        /// Record which lines, branches (`if` branches and `match` arms) and
+        /// functions the tests reach: an LCOV tracefile (default
+        /// `coverage/lcov.info`) and a per-module summary.
+        #[arg(
+            long,
+            value_name = "PATH",
+            num_args = 0..=1,
+            default_missing_value = "coverage/lcov.info"
+        )]
+        coverage: Option<PathBuf>,
+
+        /// Fail when total line coverage is below this percentage.
+        #[arg(long, value_name = "PERCENT", requires = "coverage")]
+        min_coverage: Option<f64>,
+    },
+    /// Evaluate expressions and `let` bindings interactively against the
+    /// project under the engine prelude, printing each value with its
+    /// inferred type. `:reload` re-reads the project's files; `:help` lists
+    /// the other commands. E.g. `functor -d examples/platformer repl`.
+    Repl,
     /// Run the game (default `native`, an OpenGL window; `wasm` serves a dev
     /// server; `vr` runs it on an adb-attached headset, re-pushing on save).
     /// E.g. `functor -d examples/primitives run native`.
@@ -258,12 +347,22 @@ async fn main() -> tokio::io::Result<()> {
         return finish_inspect(commands::mcp::execute().await);
     }
 
+    // A `test --reporter` report owns stdout (a CI job redirects it to a
+    // file), so the event stream renders to stderr for that command.
+    let report_on_stdout = matches!(
+        args.command,
+        Command::Test {
+            reporter: Some(_),
+            ..
+        }
+    );
     output::init(
         args.json,
         args.quiet,
         args.no_color,
         args.ascii,
         args.verbose,
+        report_on_stdout,
     );
 
     // When the live (ink-style) renderer is up, a Ctrl-C would otherwise kill
@@ -310,6 +409,10 @@ async fn main() -> tokio::io::Result<()> {
         );
         return finish_inspect(res);
     }
+    if let Command::Fmt { paths, check } = &args.command {
+        let res = commands::fmt::execute(&get_working_directory(&args), paths, *check);
+        return finish_inspect(res);
+    }
 
     finish(run(&args).await, started)
 }
@@ -370,7 +473,8 @@ async fn run(args: &Args) -> io::Result<()> {
     let is_routed = matches!(
         &args.command,
         Command::Build { .. }
-            | Command::Test
+            | Command::Test { .. }
+            | Command::Repl
             | Command::Run { .. }
             | Command::Develop { .. }
             | Command::Push { .. }
@@ -400,6 +504,7 @@ async fn run(args: &Args) -> io::Result<()> {
         let project = config.select(args.entry.as_deref().or(trailing_entry.as_deref()))?;
         return match &args.command {
             Command::Docs { .. }
+            | Command::Fmt { .. }
             | Command::Init { .. }
             | Command::Inspect { .. }
             | Command::Mcp
@@ -408,10 +513,42 @@ async fn run(args: &Args) -> io::Result<()> {
             }
             // The typecheck gate runs first so an `expect` failure is
             // unambiguously a RUNTIME failure, never a type error in disguise.
-            Command::Test => {
+            Command::Test {
+                seed,
+                cases,
+                update_snapshots,
+                filter,
+                module,
+                reporter,
+                watch,
+                coverage,
+                min_coverage,
+            } => {
+                let defaults = functor_lang::ExpectOptions::default();
+                let mut options = functor_lang::ExpectOptions {
+                    seed: seed.unwrap_or(defaults.seed),
+                    cases: cases.unwrap_or(defaults.cases).max(1),
+                    ..defaults
+                };
+                options.snapshots.update = *update_snapshots;
+                let run = commands::functor_lang_project::TestRun {
+                    options,
+                    selection: functor_runtime_common::functor_lang_test::Selection {
+                        filter: filter.clone(),
+                        module: module.clone(),
+                        modules: None,
+                    },
+                    reporter: reporter.as_ref().map(Into::into),
+                    coverage: coverage.clone(),
+                    min_coverage: *min_coverage,
+                };
+                if *watch {
+                    return project.test_watch(&working_directory_str, &run).await;
+                }
                 let loaded = project.build(&working_directory_str, false)?;
-                project.test(&loaded)
+                project.test(&loaded, &working_directory_str, &run)
             }
+            Command::Repl => project.repl(&working_directory_str),
             // `build` is the strict typecheck gate — nothing compiles for
             // either target (native interprets the file; wasm ships it as
             // text). `build wasm` then also writes the static web bundle.
@@ -471,11 +608,13 @@ async fn run(args: &Args) -> io::Result<()> {
     match &args.command {
         Command::Init { .. } => unreachable!("init is handled before metadata validation"),
         Command::Docs { .. } => unreachable!("docs is handled before metadata validation"),
+        Command::Fmt { .. } => unreachable!("fmt is handled before metadata validation"),
         // The F#/Fable pipeline was removed in E3: every Functor project is now
         // Functor Lang (functor.json `"language": "functor-lang"`), routed above. A project that
         // isn't Functor Lang has no build/run/develop/push path.
         Command::Build { .. }
-        | Command::Test
+        | Command::Test { .. }
+        | Command::Repl
         | Command::Run { .. }
         | Command::Develop { .. } => {
             Err(io::Error::other(
@@ -526,8 +665,10 @@ fn command_name(command: &Command) -> &'static str {
     match command {
         Command::Init { .. } => "init",
         Command::Docs { .. } => "docs",
+        Command::Fmt { .. } => "fmt",
         Command::Build { .. } => "build",
-        Command::Test => "test",
+        Command::Test { .. } => "test",
+        Command::Repl => "repl",
         Command::Run { .. } => "run",
         Command::Develop { .. } => "develop",
         Command::Mcp => "mcp",
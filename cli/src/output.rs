@@ -103,6 +103,19 @@ pub enum Event {
     Info { message: String },
     /// A non-fatal issue.
     Warning { message: String },
+    /// One module's `functor test --coverage` row: hit/found counts for
+    /// lines, branches (`if` branches, `match` arms) and functions, and the
+    /// lines of the branches no test took.
+    Coverage {
+        module: String,
+        lines_hit: usize,
+        lines_found: usize,
+        branches_hit: usize,
+        branches_found: usize,
+        functions_hit: usize,
+        functions_found: usize,
+        missed_branches: Vec<usize>,
+    },
     /// A fatal error, emitted just before the process exits non-zero.
     Error {
         message: String,
@@ -239,13 +252,22 @@ pub trait Renderer: Send + Sync {
 }
 
 /// ndjson: one compact JSON object per line, flushed. No color, ever.
-pub struct JsonRenderer;
+pub struct JsonRenderer {
+    /// Render to stderr: a command's report owns stdout (`test --reporter`).
+    pub stderr: bool,
+}
 
 impl Renderer for JsonRenderer {
     fn render(&self, event: &Event) {
-        let mut out = std::io::stdout().lock();
-        if serde_json::to_writer(&mut out, event).is_ok() {
-            let _ = out.write_all(b"\n");
+        let Ok(mut line) = serde_json::to_vec(event) else {
+            return;
+        };
+        line.push(b'\n');
+        if self.stderr {
+            let _ = std::io::stderr().lock().write_all(&line);
+        } else {
+            let mut out = std::io::stdout().lock();
+            let _ = out.write_all(&line);
             let _ = out.flush();
         }
     }
@@ -257,6 +279,8 @@ impl Renderer for JsonRenderer {
 /// animation yet — that's the PR-2 ink-style renderer.
 pub struct PlainRenderer {
     pub quiet: bool,
+    /// Render to stderr (see [`JsonRenderer::stderr`]).
+    pub stderr: bool,
 }
 
 impl Renderer for PlainRenderer {
@@ -265,7 +289,11 @@ impl Renderer for PlainRenderer {
             return;
         }
         for line in Self::lines(event) {
-            println!("{line}");
+            if self.stderr {
+                eprintln!("{line}");
+            } else {
+                println!("{line}");
+            }
         }
     }
 }
@@ -353,6 +381,38 @@ impl PlainRenderer {
             Event::Warning { message } => {
                 vec![format!("{}: {message}", "warning".yellow().bold())]
             }
+            Event::Coverage {
+                module,
+                lines_hit,
+                lines_found,
+                branches_hit,
+                branches_found,
+                functions_hit,
+                functions_found,
+                missed_branches,
+            } => {
+                let percent = |hit: usize, found: usize| {
+                    let percent = if found == 0 {
+                        100.0
+                    } else {
+                        hit as f64 * 100.0 / found as f64
+                    };
+                    format!("{percent:>5.1}% ({hit}/{found})")
+                };
+                let mut row = format!(
+                    "{} {module:<20} lines {}  branches {}  functions {functions_hit}/\
+                     {functions_found}",
+                    g_bullet().cyan(),
+                    percent(*lines_hit, *lines_found),
+                    percent(*branches_hit, *branches_found),
+                );
+                if !missed_branches.is_empty() {
+                    let lines: Vec<String> = missed_branches.iter().map(usize::to_string).collect();
+                    let missed = format!("missed: line {}", lines.join(", "));
+                    row.push_str(&format!("  {}", missed.dimmed()));
+                }
+                vec![row]
+            }
             Event::Error { message, hint } => {
                 let mut out = vec![format!("{}: {message}", "error".red().bold())];
                 if let Some(hint) = hint {
@@ -605,12 +665,18 @@ fn log_level(verbose: bool) -> log::LevelFilter {
 
 /// Select and install the process-wide renderer from the global flags +
 /// environment. Called once at startup, before any command logic runs. See
-/// `docs/cli-output.md` for the selection table.
-pub fn init(json: bool, quiet: bool, no_color: bool, ascii: bool, verbose: bool) {
+/// `docs/cli-output.md` for the selection table. `stderr` moves the stream
+/// off stdout for a command whose report is the payload there.
+pub fn init(json: bool, quiet: bool, no_color: bool, ascii: bool, verbose: bool, stderr: bool) {
     // Color only on a real TTY, and never under NO_COLOR / --no-color / CI /
     // --json. Enforced globally so no code path can leak ANSI.
+    let tty = if stderr {
+        std::io::stderr().is_terminal()
+    } else {
+        std::io::stdout().is_terminal()
+    };
     let color = !json
-        && std::io::stdout().is_terminal()
+        && tty
         && std::env::var_os("NO_COLOR").is_none()
         && std::env::var_os("CI").is_none()
         && !no_color;
@@ -625,15 +691,15 @@ pub fn init(json: bool, quiet: bool, no_color: bool, ascii: bool, verbose: bool)
     // output). Every machine-facing path — --json, --quiet, non-TTY, CI,
     // NO_COLOR, --no-color — keeps the plain/json renderer, byte-for-byte.
     let renderer: Box<dyn Renderer> = if json {
-        Box::new(JsonRenderer)
-    } else if quiet {
-        Box::new(PlainRenderer { quiet })
+        Box::new(JsonRenderer { stderr })
+    } else if quiet || stderr {
+        Box::new(PlainRenderer { quiet, stderr })
     } else if color {
         LIVE_ACTIVE.store(true, Ordering::Relaxed);
         install_terminal_guard();
         Box::new(live::LiveRenderer::new())
     } else {
-        Box::new(PlainRenderer { quiet })
+        Box::new(PlainRenderer { quiet, stderr })
     };
     let _ = RENDERER.set(renderer);
 
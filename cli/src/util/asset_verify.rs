@@ -168,6 +168,7 @@ fn walk(expr: &Expr, f: &mut impl FnMut(&Expr)) {
     f(expr);
     match &expr.kind {
         ExprKind::Number(_)
+        | ExprKind::Int(_)
         | ExprKind::String(_)
         | ExprKind::Bool(_)
         | ExprKind::Local { .. }
@@ -237,6 +238,9 @@ fn walk(expr: &Expr, f: &mut impl FnMut(&Expr)) {
         ExprKind::Match { scrutinee, arms } => {
             walk(scrutinee, f);
             for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    walk(guard, f);
+                }
                 walk(&arm.body, f);
             }
         }
@@ -0,0 +1,595 @@
+//! Code actions: quick fixes for the checker's diagnostics and two small
+//! refactors — the language-aware half of the LSP's
+//! `textDocument/codeAction`. Like [`crate::references`] this decides the
+//! edits (project-wide spans) and the editor server only converts positions.
+//!
+//! - **Add missing match arms** on a non-exhaustive `match` over a variant
+//!   type or `bool`: one arm per constructor no unguarded arm fully covers,
+//!   after the last arm and in its layout. A new arm's body is a placeholder
+//!   of the match's type — `0.0`, `""`, `[]`, … or, for other types, the
+//!   body of an existing arm that uses none of its pattern's bindings — so
+//!   the program still checks and the user edits from there.
+//! - **Apply the suggestion** of a "did you mean `X`?" diagnostic (an
+//!   unknown type name, an unknown builtin member).
+//! - **Add a return annotation** to a top-level lambda `let` from its
+//!   inferred return type, when the cursor is on its head.
+//! - **Extract to a top-level `let`**: the selected expression becomes a
+//!   new definition above the enclosing one; locals it uses become its
+//!   parameters (`let extracted = (x) => x * 2.0`, called as
+//!   `extracted(x)`).
+//!
+//! Every candidate is proven before it is offered, the way a rename is:
+//! the edited project is re-linked and checked, a quick fix must leave
+//! fewer diagnostics, and a refactor must not add any.
+
+use crate::complete::owning_module;
+use crate::ir::{Expr, ExprKind, PatternKind};
+use crate::project::{Project, SourceFile};
+use crate::references::Edit;
+use crate::span::Span;
+use crate::types::{ExprTypes, Type};
+use crate::{ast::TypeBody, CheckError};
+
+/// What an action does, for the editor's grouping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionKind {
+    /// Fixes the diagnostic in [`CodeAction::fixes`].
+    QuickFix,
+    /// Rewrites code in place without changing what it means.
+    Rewrite,
+    /// Moves code into a new definition.
+    Extract,
+}
+
+/// One offered action: a title and the edits applying it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeAction {
+    pub title: String,
+    pub kind: ActionKind,
+    /// Project-wide, non-overlapping, in source order.
+    pub edits: Vec<Edit>,
+    /// The diagnostic a quick fix resolves.
+    pub fixes: Option<CheckError>,
+}
+
+/// The actions available for the project-wide `range` (a cursor when
+/// empty): fixes for the diagnostics it touches, then the refactors that
+/// apply there.
+pub fn code_actions(project: &Project, range: Span) -> Vec<CodeAction> {
+    let (errors, types) = project.check_with_types();
+    let mut candidates = Vec::new();
+    for error in &errors {
+        if error.span.start > range.end || range.start > error.span.end {
+            continue;
+        }
+        let fix = if error.message.contains("is not exhaustive") {
+            missing_arms(project, &types, error)
+        } else {
+            suggestion(project, error)
+        };
+        candidates.extend(fix);
+    }
+    candidates.extend(return_annotation(project, &types, range));
+    candidates.extend(extract(project, range));
+    candidates
+        .into_iter()
+        .filter(|action| proven(project, &errors, action))
+        .collect()
+}
+
+/// Whether `action` re-links, and leaves fewer diagnostics (a fix) or no
+/// more (a refactor) than `before`.
+fn proven(project: &Project, before: &[CheckError], action: &CodeAction) -> bool {
+    let files = project.sources.files();
+    let owner = |span: Span| {
+        files
+            .iter()
+            .rposition(|file| file.base <= span.start)
+            .unwrap_or(0)
+    };
+    let Ok(edited) = project.relink(|index, file| {
+        let mut src = file.src.clone();
+        for edit in action
+            .edits
+            .iter()
+            .rev()
+            .filter(|edit| owner(edit.span) == index)
+        {
+            src.replace_range(
+                edit.span.start - file.base..edit.span.end - file.base,
+                &edit.text,
+            );
+        }
+        (file.path.clone(), file.module.clone(), src)
+    }) else {
+        return false;
+    };
+    let after = edited.check().len();
+    match action.kind {
+        ActionKind::QuickFix => after < before.len(),
+        ActionKind::Rewrite | ActionKind::Extract => after <= before.len(),
+    }
+}
+
+/// Every expression of the project's defs and expects, outermost first.
+fn each_expr<'a>(project: &'a Project, f: &mut impl FnMut(&'a Expr)) {
+    fn walk<'a>(expr: &'a Expr, f: &mut impl FnMut(&'a Expr)) {
+        f(expr);
+        crate::rebind::each_child(expr, &mut |child| walk(child, f));
+    }
+    for def in &project.module.defs {
+        walk(&def.value, f);
+    }
+    for expect in &project.module.expects {
+        walk(&expect.expr, f);
+    }
+}
+
+fn text(file: &SourceFile, span: Span) -> &str {
+    &file.src[span.start - file.base..span.end - file.base]
+}
+
+/// `name` as written in `file`: a file module's own names drop their
+/// module prefix (`Utils.Red` is `Red` inside `utils.fun`); the entry's are
+/// already bare.
+fn written_in<'a>(name: &'a str, file: &SourceFile) -> &'a str {
+    name.strip_prefix(&file.module)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .unwrap_or(name)
+}
+
+/// Add an arm for each constructor (or `bool` value) of a non-exhaustive
+/// match that no unguarded arm fully covers.
+fn missing_arms(project: &Project, types: &ExprTypes, error: &CheckError) -> Option<CodeAction> {
+    let mut found = None;
+    each_expr(project, &mut |expr| {
+        if expr.span == error.span && matches!(expr.kind, ExprKind::Match { .. }) {
+            found.get_or_insert(expr);
+        }
+    });
+    let found = found?;
+    let ExprKind::Match { scrutinee, arms } = &found.kind else {
+        return None;
+    };
+    let file = project.sources.file_at(error.span.start);
+    // The constructors an unguarded arm covers outright; `None` once a
+    // catch-all arm covers everything.
+    let mut covered: Vec<String> = Vec::new();
+    let mut catch_all = false;
+    for arm in arms.iter().filter(|arm| arm.guard.is_none()) {
+        let alternatives = match &arm.pattern.kind {
+            PatternKind::Or(alternatives) => alternatives.iter().collect(),
+            _ => vec![&arm.pattern],
+        };
+        for pattern in alternatives {
+            match &pattern.kind {
+                PatternKind::Wildcard | PatternKind::Var { .. } => catch_all = true,
+                PatternKind::Ctor { name, args } if args.iter().all(irrefutable) => {
+                    covered.push(name.clone())
+                }
+                PatternKind::Bool(b) => covered.push(b.to_string()),
+                _ => {}
+            }
+        }
+    }
+    if catch_all {
+        return None;
+    }
+    let patterns: Vec<String> = match types.expr(scrutinee.id)? {
+        Type::Variant(name, _) => {
+            let ty = project.module.types.iter().find(|ty| ty.name == *name)?;
+            let TypeBody::Variants(variants) = &ty.body else {
+                return None;
+            };
+            variants
+                .iter()
+                .filter(|variant| !covered.contains(&variant.name))
+                .map(|variant| {
+                    let name = written_in(&variant.name, file);
+                    match variant.fields.len() {
+                        0 => name.to_string(),
+                        n => format!("{name}({})", vec!["_"; n].join(", ")),
+                    }
+                })
+                .collect()
+        }
+        Type::Bool => ["true", "false"]
+            .into_iter()
+            .filter(|b| !covered.iter().any(|c| c == b))
+            .map(str::to_string)
+            .collect(),
+        _ => return None,
+    };
+    if patterns.is_empty() {
+        return None;
+    }
+    let last = arms.last()?;
+    let body = placeholder(types, found, arms, file)?;
+    // Follow the last arm's layout: its own line (at its indentation) or
+    // inline after it.
+    let line_start = file.src[..last.span.start - file.base]
+        .rfind('\n')
+        .map_or(0, |i| i + 1);
+    let before = &file.src[line_start..last.span.start - file.base];
+    let separator = match before.trim() {
+        "" | "|" => {
+            let indent: String = before.chars().take_while(|c| c.is_whitespace()).collect();
+            format!("\n{indent}")
+        }
+        _ => " ".to_string(),
+    };
+    let arms: String = patterns
+        .iter()
+        .map(|pattern| format!("{separator}| {pattern} => {body}"))
+        .collect();
+    let title = match patterns.len() {
+        1 => format!("Add missing arm `{}`", patterns[0]),
+        _ => format!("Add {} missing arms", patterns.len()),
+    };
+    Some(CodeAction {
+        title,
+        kind: ActionKind::QuickFix,
+        edits: vec![Edit {
+            span: Span::new(last.span.end, last.span.end),
+            text: arms,
+        }],
+        fixes: Some((*error).clone()),
+    })
+}
+
+/// A body for an added arm: the zero value of the match's type, or else
+/// the last arm body that does not use its own pattern's bindings.
+fn placeholder(
+    types: &ExprTypes,
+    expr: &Expr,
+    arms: &[crate::ir::MatchArm],
+    file: &SourceFile,
+) -> Option<String> {
+    let zero = match types.expr(expr.id)? {
+        Type::Float => Some("0.0"),
+        Type::Int => Some("0"),
+        Type::String => Some("\"\""),
+        Type::Bool => Some("false"),
+        Type::List(_) => Some("[]"),
+        _ => None,
+    };
+    if let Some(zero) = zero {
+        return Some(zero.to_string());
+    }
+    arms.iter()
+        .rev()
+        .find(|arm| {
+            let mut bound = Vec::new();
+            crate::rebind::pattern_binders(&arm.pattern, &mut |binding, _| bound.push(binding));
+            let mut free = Vec::new();
+            crate::rebind::free_vars(&arm.body, &mut Vec::new(), &mut free);
+            free.iter().all(|(_, binding)| !bound.contains(binding))
+        })
+        .map(|arm| text(file, arm.body.span).to_string())
+}
+
+fn irrefutable(pattern: &crate::ir::Pattern) -> bool {
+    matches!(
+        pattern.kind,
+        PatternKind::Wildcard | PatternKind::Var { .. }
+    )
+}
+
+/// Replace the name a "did you mean `X`?" diagnostic points at with `X`.
+fn suggestion(project: &Project, error: &CheckError) -> Option<CodeAction> {
+    let (_, rest) = error.message.split_once("did you mean `")?;
+    let (suggested, after) = rest.split_once('`')?;
+    if !after.starts_with('?') {
+        return None;
+    }
+    // Only the written name — never an annotation's type arguments
+    // (`Lsit<float>` keeps its `<float>`).
+    let file = project.sources.file_at(error.span.start);
+    let written = text(file, error.span);
+    let len = written
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+        .unwrap_or(written.len());
+    if len == 0 || written[..len] == *suggested {
+        return None;
+    }
+    Some(CodeAction {
+        title: format!("Change to `{suggested}`"),
+        kind: ActionKind::QuickFix,
+        edits: vec![Edit {
+            span: Span::new(error.span.start, error.span.start + len),
+            text: suggested.to_string(),
+        }],
+        fixes: Some((*error).clone()),
+    })
+}
+
+/// Annotate the top-level lambda `let` whose head (`let f = (…)`) the
+/// cursor is on with its inferred return type.
+fn return_annotation(project: &Project, types: &ExprTypes, range: Span) -> Option<CodeAction> {
+    let def = project.module.defs.iter().find(|def| {
+        matches!(&def.value.kind, ExprKind::Lambda { ret: None, body, .. }
+            if def.span.start <= range.start && range.start < body.span.start)
+    })?;
+    let Some(Type::Fn(_, ret)) = types.expr(def.value.id) else {
+        return None;
+    };
+    if matches!(**ret, Type::Unknown) {
+        return None;
+    }
+    // After the `)` closing the parameter list (annotations may nest
+    // parentheses: `(f: (int) => int)`).
+    let file = project.sources.file_at(def.value.span.start);
+    let lambda = text(file, def.value.span);
+    if !lambda.starts_with('(') {
+        return None;
+    }
+    let mut depth = 0;
+    let close = lambda.char_indices().find_map(|(i, c)| {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        (depth == 0).then_some(i)
+    })?;
+    let at = def.value.span.start + close + 1;
+    let annotation = format!(": {ret}");
+    Some(CodeAction {
+        title: format!("Add return annotation `{annotation}`"),
+        kind: ActionKind::Rewrite,
+        edits: vec![Edit {
+            span: Span::new(at, at),
+            text: annotation,
+        }],
+        fixes: None,
+    })
+}
+
+/// Move the expression selected by `range` into a new top-level `let`
+/// above the item containing it.
+fn extract(project: &Project, range: Span) -> Option<CodeAction> {
+    if range.start == range.end {
+        return None;
+    }
+    let file = project.sources.file_at(range.start);
+    if file.interface || range.end > file.base + file.src.len() {
+        return None;
+    }
+    // The selection, trimmed of surrounding whitespace.
+    let selected = text(file, range);
+    let start = range.start + (selected.len() - selected.trim_start().len());
+    let end = range.end - (selected.len() - selected.trim_end().len());
+    let target = Span::new(start, end);
+
+    // The enclosing item (its whole value is not worth extracting) and the
+    // outermost expression spanning exactly the selection.
+    let mut item = None;
+    for def in &project.module.defs {
+        if def.span.start <= start && end <= def.span.end && def.value.span != target {
+            item = Some((
+                def.span,
+                owning_module(&def.name, &project.entry).to_string(),
+                &def.value,
+            ));
+        }
+    }
+    for expect in &project.module.expects {
+        if expect.span.start <= start && end <= expect.span.end {
+            item = Some((expect.span, expect.module.clone(), &expect.expr));
+        }
+    }
+    let (item_span, module, root) = item?;
+    // A parenthesized expression's span covers its parens, so a selection
+    // just inside them selects it too.
+    let local = |offset: usize| offset - file.base;
+    let parenthesized = local(start) > 0
+        && file.src[..local(start)].ends_with('(')
+        && file.src[local(end)..].starts_with(')');
+    let spans = [
+        Some(target),
+        parenthesized.then(|| Span::new(start - 1, end + 1)),
+    ];
+    let mut found = None;
+    fn find<'a>(expr: &'a Expr, spans: &[Option<Span>], found: &mut Option<&'a Expr>) {
+        if spans.contains(&Some(expr.span)) && found.is_none() {
+            *found = Some(expr);
+        }
+        crate::rebind::each_child(expr, &mut |child| find(child, spans, found));
+    }
+    find(root, &spans, &mut found);
+    let expr = found?;
+
+    // Locals bound outside the selection become parameters.
+    let mut free = Vec::new();
+    crate::rebind::free_vars(expr, &mut Vec::new(), &mut free);
+    let params: Vec<&str> = free.iter().map(|(name, _)| name.as_str()).collect();
+
+    // A fresh name: no def of the module, and no word of the file (so no
+    // local the use site sits under can capture it).
+    let taken = |name: &str| {
+        let canonical = if module == project.entry {
+            name.to_string()
+        } else {
+            format!("{module}.{name}")
+        };
+        project.module.defs.iter().any(|def| def.name == canonical)
+            || file
+                .src
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .any(|word| word == name)
+    };
+    let name = std::iter::once("extracted".to_string())
+        .chain((2..).map(|n| format!("extracted{n}")))
+        .find(|name| !taken(name))?;
+
+    // Above the item and the doc comment attached to it.
+    let mut insert = file.src[..local(item_span.start)]
+        .rfind('\n')
+        .map_or(0, |i| i + 1);
+    while insert > 0 {
+        let previous = file.src[..insert - 1].rfind('\n').map_or(0, |i| i + 1);
+        if !file.src[previous..insert].trim_start().starts_with("//") {
+            break;
+        }
+        insert = previous;
+    }
+    let indent: String = file.src[insert..]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect();
+    let body = text(file, target);
+    let (definition, call) = if params.is_empty() {
+        (format!("let {name} = {body}"), name.clone())
+    } else {
+        let params = params.join(", ");
+        (
+            format!("let {name} = ({params}) => {body}"),
+            format!("{name}({params})"),
+        )
+    };
+    let at = file.base + insert + indent.len();
+    Some(CodeAction {
+        title: format!("Extract to top-level `let {name}`"),
+        kind: ActionKind::Extract,
+        edits: vec![
+            Edit {
+                span: Span::new(at, at),
+                text: format!("{definition}\n\n{indent}"),
+            },
+            Edit {
+                span: target,
+                text: call,
+            },
+        ],
+        fixes: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(src: &str) -> Project {
+        crate::project::load_single_source("Main", src).unwrap_or_else(|e| panic!("{}", e.message))
+    }
+
+    /// `src` with every edit of the action titled `title` (offered for the
+    /// range `[at, at + len)`) applied.
+    fn apply(src: &str, at: usize, len: usize, title: &str) -> String {
+        let project = project(src);
+        let actions = code_actions(&project, Span::new(at, at + len));
+        let action = actions
+            .iter()
+            .find(|action| action.title == title)
+            .unwrap_or_else(|| panic!("no `{title}` in {actions:?}"));
+        let mut out = src.to_string();
+        for edit in action.edits.iter().rev() {
+            out.replace_range(edit.span.start..edit.span.end, &edit.text);
+        }
+        out
+    }
+
+    fn titles(src: &str, at: usize, len: usize) -> Vec<String> {
+        code_actions(&project(src), Span::new(at, at + len))
+            .into_iter()
+            .map(|action| action.title)
+            .collect()
+    }
+
+    #[test]
+    fn adds_an_arm_per_uncovered_constructor() {
+        let src = "type Shape = | Circle(radius: float) | Rect(w: float, h: float) | Point\n\
+let area = (s: Shape): float =>\n  match s with\n  | Circle(r) => r * r\n";
+        let fixed = apply(src, src.find("match").unwrap(), 0, "Add 2 missing arms");
+        assert_eq!(
+            fixed,
+            "type Shape = | Circle(radius: float) | Rect(w: float, h: float) | Point\n\
+let area = (s: Shape): float =>\n  match s with\n  | Circle(r) => r * r\n  | Rect(_, _) => 0.0\n  | Point => 0.0\n"
+        );
+    }
+
+    #[test]
+    fn missing_bool_arms_follow_an_inline_layout() {
+        let src = "let f = (b: bool): int => match b with | true => 1\n";
+        assert_eq!(
+            apply(
+                src,
+                src.find("match").unwrap(),
+                0,
+                "Add missing arm `false`"
+            ),
+            "let f = (b: bool): int => match b with | true => 1 | false => 0\n"
+        );
+    }
+
+    #[test]
+    fn other_types_copy_a_closed_arm_body() {
+        let src = "type Dir = | Up | Down | Left\n\
+let flip = (d: Dir): Dir => match d with | Up => Down | Down => Up\n";
+        assert_eq!(
+            apply(src, src.find("match").unwrap(), 0, "Add missing arm `Left`"),
+            "type Dir = | Up | Down | Left\n\
+let flip = (d: Dir): Dir => match d with | Up => Down | Down => Up | Left => Up\n"
+        );
+    }
+
+    #[test]
+    fn applies_a_did_you_mean_suggestion() {
+        let src = "let f = (x: Float): float => x\n";
+        let at = src.find("Float").unwrap();
+        assert_eq!(
+            apply(src, at, 0, "Change to `float`"),
+            "let f = (x: float): float => x\n"
+        );
+        let src = "let xs = List.mapp((x) => x, [1])\n";
+        assert_eq!(
+            apply(src, src.find("List").unwrap(), 0, "Change to `List.map`"),
+            "let xs = List.map((x) => x, [1])\n"
+        );
+    }
+
+    #[test]
+    fn annotates_the_inferred_return_type() {
+        let src = "let scale = (x: float, f: (float) => float) => f(x) * 2.0\n";
+        assert_eq!(
+            apply(src, 5, 0, "Add return annotation `: float`"),
+            "let scale = (x: float, f: (float) => float): float => f(x) * 2.0\n"
+        );
+        // Only on the head, and never over an existing annotation.
+        assert!(titles(src, src.find("f(x)").unwrap(), 0).is_empty());
+        assert!(titles("let g = (x: float): float => x\n", 5, 0).is_empty());
+    }
+
+    #[test]
+    fn extracts_a_closed_expression_to_a_constant() {
+        let src = "// Doubles.\nlet f = (x: float) => x * (2.0 + 1.0)\n";
+        let at = src.find("2.0 + 1.0").unwrap();
+        assert_eq!(
+            apply(
+                src,
+                at,
+                "2.0 + 1.0".len(),
+                "Extract to top-level `let extracted`"
+            ),
+            "let extracted = 2.0 + 1.0\n\n// Doubles.\nlet f = (x: float) => x * (extracted)\n"
+        );
+    }
+
+    #[test]
+    fn extracted_locals_become_parameters() {
+        let src = "let extracted = 1.0\nlet f = (x: float) => let y = x in y * x + extracted\n";
+        let at = src.find("y * x").unwrap();
+        assert_eq!(
+            apply(
+                src,
+                at,
+                "y * x".len(),
+                "Extract to top-level `let extracted2`"
+            ),
+            "let extracted = 1.0\nlet extracted2 = (y, x) => y * x\n\n\
+let f = (x: float) => let y = x in extracted2(y, x) + extracted\n"
+        );
+        // A selection that is not a whole expression offers nothing.
+        assert!(titles(src, at, "y * x +".len()).is_empty());
+    }
+}
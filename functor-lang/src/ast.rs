@@ -32,9 +32,10 @@ pub enum Item {
 }
 
 /// `unit <suffix> = <name>` — a unit-suffixed literal's meaning: which
-/// `(float) => 't` function or constructor a suffixed numeric literal calls.
-/// Units are project-wide (file = module, like constructors), so a suffix may
-/// be declared exactly once across the whole project. Resolution and
+/// `(float) => 't` (or `(int) => 't`) function or constructor a suffixed
+/// numeric literal calls. Units are project-wide (file = module, like
+/// constructors), so a suffix may be declared exactly once across the whole
+/// project. Resolution and
 /// desugaring happen in lowering: `90deg` becomes exactly the call
 /// `Angle.degrees(90.0)`, so hover, inlay, typechecking, and the host's
 /// teaching errors all see an ordinary call.
@@ -101,10 +102,27 @@ pub struct ModuleDecl {
 /// span (file:line) is their identity.
 #[derive(Debug)]
 pub struct ExpectDecl {
+    pub kind: ExpectKind,
     pub expr: Expr,
     pub span: Span,
 }
 
+/// Which kind of test an [`ExpectDecl`] is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpectKind {
+    /// `expect <expr>` — one fixed example.
+    Example,
+    /// `property (xs: List<int>) => <expr>` — a law over GENERATED inputs:
+    /// the expression is a lambda whose annotated parameter types pick the
+    /// generators, and its body is the bool checked for every seeded case
+    /// (see [`crate::property`]). `property` is contextual like `expect`.
+    Property,
+    /// `expectSnapshot name = <expr>` — the expression's value compared
+    /// against a stored golden dump under `name`, unique per file (see
+    /// [`crate::snapshot`]). Contextual like `expect`.
+    Snapshot(String),
+}
+
 /// `let name : Type` in a `.funi` — a value signature with no body.
 #[derive(Debug)]
 pub struct SigDecl {
@@ -131,6 +149,9 @@ pub struct LetDecl {
     pub name: String,
     pub ty: Option<TypeName>,
     pub value: Expr,
+    /// `private let …` — reachable only from its own file (see
+    /// `crate::project`). `private` is contextual, like `open`.
+    pub private: bool,
     pub span: Span,
 }
 
@@ -144,6 +165,9 @@ pub struct TypeDecl {
     /// declaration is not generic.
     pub params: Vec<String>,
     pub body: TypeBody,
+    /// `private type …` — the type and its constructors are reachable only
+    /// from its own file.
+    pub private: bool,
     pub span: Span,
 }
 
@@ -203,15 +227,50 @@ pub struct Expr {
     pub span: Span,
 }
 
+/// A numeric literal's digits as spelled: bare digits are an [`Numeral::Int`],
+/// digits with a fractional part a [`Numeral::Float`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Numeral {
+    Int(i64),
+    Float(f64),
+}
+
+impl std::ops::Neg for Numeral {
+    type Output = Numeral;
+
+    // A literal's digits are never negative and never `i64::MIN` (the lexer
+    // refuses anything past `i64::MAX`), so the int negation cannot overflow.
+    fn neg(self) -> Numeral {
+        match self {
+            Numeral::Int(n) => Numeral::Int(-n),
+            Numeral::Float(n) => Numeral::Float(-n),
+        }
+    }
+}
+
+impl std::fmt::Display for Numeral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Numeral::Int(n) => write!(f, "{n}"),
+            // `{:?}` keeps the dot (`90.0`), so the spelling round-trips.
+            Numeral::Float(n) => write!(f, "{n:?}"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ExprKind {
-    /// Ints and floats both parse to f64 (Functor Lang has one number type for now).
+    /// `1.5` — a float literal (written with a fractional part).
     Number(f64),
+    /// `42` — an int literal (bare digits).
+    Int(i64),
     /// `90deg` / `0.5s` — a numeric literal with a unit suffix. Lowering
     /// resolves the suffix against the project's `unit` declarations and
     /// desugars it to the plain call (`Angle.degrees(90.0)`); the IR has no
-    /// unit-literal node.
-    NumberUnit { value: f64, suffix: String },
+    /// unit-literal node. The digits are whichever number the unit's target
+    /// takes: `90deg` is `Angle.degrees(90.0)`, while a unit over an `int`
+    /// target refuses `1.5`.
+    NumberUnit { value: Numeral, suffix: String },
     String(String),
     /// `$"score: {score}"` — text and full expression holes, evaluated
     /// left-to-right. `{{` / `}}` produce literal braces.
@@ -333,10 +392,13 @@ pub enum StringPart {
     Expr(Expr),
 }
 
-/// One `| pattern => body` arm of a `match`.
+/// One `| pattern => body` arm of a `match`, optionally guarded:
+/// `| pattern when cond => body` is taken only when `cond` (evaluated with
+/// the pattern's variables in scope) is true.
 #[derive(Debug)]
 pub struct MatchArm {
     pub pattern: Pattern,
+    pub guard: Option<Expr>,
     pub body: Expr,
     pub span: Span,
 }
@@ -347,9 +409,9 @@ pub struct Pattern {
     pub span: Span,
 }
 
-/// The deliberately-minimal B5 pattern language: constructor patterns whose
-/// sub-patterns are variable bindings or `_` (no deeper nesting), bare
-/// variables, `_`, and literal (equality) patterns.
+/// The pattern language. Constructor, tuple, list, and record patterns nest
+/// arbitrarily (`Some(Hit(pos, _))`, `{ state: Playing, lives }`); the
+/// leaves are variables, `_`, and literal (equality) patterns.
 #[derive(Debug)]
 pub enum PatternKind {
     /// `_` — matches anything, binds nothing.
@@ -362,22 +424,38 @@ pub enum PatternKind {
         name: String,
         args: Vec<Pattern>,
     },
-    /// `(x, _)` — element sub-patterns are variable bindings or `_` only
-    /// (like ctor patterns); arity must match the matched tuple exactly.
+    /// `(x, _)` — arity must match the matched tuple exactly.
     Tuple(Vec<Pattern>),
     /// `[]` (empty), `[a, b]` (exact length), `[head, ..rest]` (at least
-    /// `items.len()`, `rest` binds the remainder as a list). Element and
-    /// tail sub-patterns are variable bindings or `_` only (like tuple/ctor
-    /// patterns). `tail: None` means an exact-length match.
+    /// `items.len()`, `rest` matches the remainder as a list). `tail: None`
+    /// means an exact-length match.
     List {
         items: Vec<Pattern>,
         tail: Option<Box<Pattern>>,
     },
+    /// `{ state: Playing, lives }` — matches a record having AT LEAST the
+    /// named fields (unnamed fields are ignored), each against its
+    /// sub-pattern. A bare field name is punned: `{ lives }` is
+    /// `{ lives: lives }`.
+    Record(Vec<FieldPattern>),
+    /// `A | B` — matches when any alternative does (tried left to right).
+    /// Every alternative binds the same names; lowering enforces it.
+    Or(Vec<Pattern>),
     Number(f64),
+    Int(i64),
     Bool(bool),
     String(String),
 }
 
+/// One `name: pattern` (or punned `name`) entry of a record pattern. A
+/// punned entry's pattern is the [`PatternKind::Var`] spanning the name.
+#[derive(Debug)]
+pub struct FieldPattern {
+    pub name: String,
+    pub pattern: Pattern,
+    pub span: Span,
+}
+
 /// One `name: value` entry of a record expression.
 #[derive(Debug)]
 pub struct Field {
@@ -400,6 +478,9 @@ pub enum BinOp {
     Sub,
     Mul,
     Div,
+    /// `%` — the EUCLIDEAN remainder, never negative, like `Math.mod`. Plain
+    /// number arithmetic only: no brand can declare it.
+    Rem,
     Lt,
     Gt,
     Le,
@@ -428,8 +509,8 @@ impl BinOp {
     ];
 
     /// The declarable operator this one is implemented BY — itself for the
-    /// six in [`BinOp::DECLARABLE`], and the base of a derived comparison
-    /// otherwise. `!=` is `==` negated; `>` is `<` with its operands swapped,
+    /// six in [`BinOp::DECLARABLE`] (and for `%`, which has no brand form),
+    /// and the base of a derived comparison otherwise. `!=` is `==` negated; `>` is `<` with its operands swapped,
     /// `<=` is `>` negated, and `>=` is `<` negated. One `('t, 't) => bool`
     /// implementation per brand therefore answers all four orderings, so a
     /// brand's ordering is total and self-consistent by construction.
@@ -441,6 +522,12 @@ impl BinOp {
         }
     }
 
+    /// Can a brand implement this operator, directly or through its base?
+    /// Everything but `%`.
+    pub fn brandable(self) -> bool {
+        BinOp::DECLARABLE.contains(&self.declarable())
+    }
+
     /// Is this a comparison (`==`, `!=`, `<`, `>`, `<=`, `>=`)? Comparisons
     /// answer `bool` whichever brand claims them, where arithmetic answers
     /// the brand — the one place the two families diverge. Matched
@@ -461,6 +548,7 @@ impl BinOp {
             BinOp::Sub => "-",
             BinOp::Mul => "*",
             BinOp::Div => "/",
+            BinOp::Rem => "%",
             BinOp::Lt => "<",
             BinOp::Gt => ">",
             BinOp::Le => "<=",
@@ -478,6 +566,10 @@ impl BinOp {
 /// the checker knows the type, `Angle.t`; the interpreter knows the runtime
 /// tag, `Angle`).
 pub(crate) fn declared_operators_hint(brand: &str, declared: &[&str], op: BinOp) -> String {
+    // No declaration could supply `%`, so there is nothing to teach.
+    if !op.brandable() {
+        return String::new();
+    }
     if declared.is_empty() {
         return format!(
             " — `{brand}` is a branded value with no declared operators; declare one with \
@@ -6,8 +6,8 @@
 //! is an ordinary `.fun` project whose entry defines a zero-arg `let main`; the
 //! harness parses + lowers + loads it **once** (untimed), then times *repeated
 //! evaluation of `main()`*. So the reported number is per-`main()`-evaluation
-//! cost — the tree-walking interpreter's hot path — not startup, parse, or
-//! typecheck. (Each timed call goes through [`functor_lang::Session::call`], which spins
+//! cost — the interpreter's hot path (compiled closure bodies on the VM) —
+//! not startup, parse, or typecheck. (Each timed call goes through [`functor_lang::Session::call`], which spins
 //! up a fresh interpreter over the session's globals exactly as the runtime
 //! does per frame, so the number is representative of live per-frame cost.)
 //!
@@ -0,0 +1,800 @@
+//! Bytecode for closure bodies — the compile step between the core IR and
+//! the interpreter's per-frame hot path (docs/functor-lang.md, "bytecode VM").
+//!
+//! [`compile`] turns every lambda in a module into a [`Code`]: a flat stack
+//! bytecode whose locals live in numbered frame SLOTS (parameters first, then
+//! captured variables, then the body's own `let` / match binders) and whose
+//! top-level references index the session's global table — no environment
+//! chain walk and no name lookup per read. [`crate::eval`] runs a closure's
+//! `Code` whenever the closure's body is the one it was compiled from;
+//! top-level initializers and `expect` expressions run once and stay
+//! tree-walked.
+//!
+//! Observation is compiled in rather than bolted on: every op carries the
+//! span its errors report, each expression's coverage start is attached to
+//! the first op it emits (exactly the expressions the tree-walker `eval`s, so
+//! the coverage set is unchanged), and binder / reference ops keep the name
+//! and span the recorder labels them with. Tracing needs nothing here — it
+//! lives on the call path both evaluators share.
+//!
+//! Compilation never fails. An IR inconsistency lowering should make
+//! unreachable (a local with no slot) compiles to an op that raises the
+//! tree-walker's own "internal:" error when — and only if — it is reached.
+
+use crate::ast::{BinOp, LogicalOp};
+use crate::eval::{builtin, callee_label, Builtin};
+use crate::ir::{
+    BindingId, Expr, ExprId, ExprKind, Module, Param, Pattern, PatternKind, StringPart,
+};
+use crate::span::Span;
+use crate::value::{Closure, Value};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// One instruction. Operands are indices into the owning [`Code`]'s side
+/// tables (`u32`, keeping an op two words wide); jump targets are op indices.
+pub(crate) enum Op {
+    Number(f64),
+    Int(i64),
+    Bool(bool),
+    /// Push `consts[i]` — string literals, constructors, builtins.
+    Const(u32),
+    /// Push a frame slot; `site` indexes `sites` for the recorder.
+    Local {
+        slot: u32,
+        site: u32,
+    },
+    /// Push a global by its slot in the session's table.
+    Global {
+        global: u32,
+        site: u32,
+    },
+    /// A reference with no slot behind it; raises the matching error.
+    Unbound(u32),
+    Undefined(u32),
+    /// A non-builtin external, resolved against the host when reached.
+    External(u32),
+    /// Charge one budget step (container construction).
+    Step,
+    /// Pop `shapes[i].len()` values into a record with those field names.
+    Record(u32),
+    Tuple(u32),
+    List(u32),
+    /// Pop a tail list and `n` items; push the items prepended onto it.
+    Cons(u32),
+    /// `{ base with … }`: check the base is a record, then per field check
+    /// the name exists before its value runs, then set it.
+    UpdateBase,
+    UpdateCheck(u32),
+    UpdateSet(u32),
+    Field(u32),
+    /// Pop into `slot`; `binder` indexes `binders` for the recorder.
+    Bind {
+        slot: u32,
+        binder: u32,
+    },
+    /// Pop into `slot` with no recording (`name := value`).
+    Store(u32),
+    MakeClosure(u32),
+    Call {
+        argc: u32,
+        label: u32,
+    },
+    /// A call in tail position: a saturated compiled closure REPLACES this
+    /// frame (constant host stack); any other callee is an ordinary call
+    /// whose result this frame returns.
+    TailCall {
+        argc: u32,
+        label: u32,
+    },
+    Binary(BinOp),
+    Neg,
+    /// Require the top to be a bool (`&&` / `||` / `not` operands).
+    AsBool,
+    Not,
+    Pop,
+    Jump(u32),
+    /// Pop an `if` condition; jump when false.
+    JumpIfFalse(u32),
+    /// Pop a `when` guard; jump when false.
+    GuardIfFalse(u32),
+    /// Peek a checked bool and jump when it already decides `&&` / `||`.
+    JumpIfFalseKeep(u32),
+    JumpIfTrueKeep(u32),
+    /// Test `arms[arm]` against the scrutinee on top, writing its variables
+    /// into their slots; jump to `next` on a mismatch.
+    Match {
+        arm: u32,
+        next: u32,
+    },
+    /// Commit to `arms[arm]`: record its binders, pop the scrutinee.
+    Take(u32),
+    NoMatch,
+    /// Interpolation: a literal piece (`consts[i]`), an evaluated piece made
+    /// text, and the final join of `n` pieces.
+    Text(u32),
+    Stringify,
+    Concat(u32),
+    Return,
+}
+
+/// A compiled lambda body.
+pub(crate) struct Code {
+    pub(crate) ops: Vec<Op>,
+    /// Per op: the span its errors (and calls) report.
+    pub(crate) spans: Vec<Span>,
+    /// Per op `pc`, `covers[cover_at[pc]..cover_at[pc + 1]]` are the starts
+    /// of the expressions that begin evaluating there.
+    cover_at: Vec<u32>,
+    covers: Vec<usize>,
+    /// Frame size. Slots `0..params` are the parameters, then one per
+    /// [`Self::captures`] entry, then the body's binders.
+    pub(crate) slots: usize,
+    /// The captured bindings, in the order a compiled closure's environment
+    /// stores them.
+    pub(crate) captures: Rc<Vec<BindingId>>,
+    /// The body compiled — the identity a closure must share to run this.
+    pub(crate) body: Rc<Expr>,
+    pub(crate) consts: Vec<Value>,
+    /// Reference sites: the name and span a read is recorded under.
+    pub(crate) sites: Vec<(String, Span)>,
+    /// `let` binder sites: the `let [mut] name =` region, as the recorder
+    /// reports it.
+    pub(crate) binders: Vec<(BindingId, String, Span)>,
+    pub(crate) names: Vec<String>,
+    pub(crate) shapes: Vec<Vec<String>>,
+    pub(crate) labels: Vec<String>,
+    pub(crate) paths: Vec<Vec<String>>,
+    pub(crate) protos: Vec<Proto>,
+    pub(crate) arms: Vec<Arm>,
+}
+
+impl Code {
+    /// The coverage starts attached to op `pc`.
+    pub(crate) fn covers(&self, pc: usize) -> &[usize] {
+        &self.covers[self.cover_at[pc] as usize..self.cover_at[pc + 1] as usize]
+    }
+}
+
+/// What [`Op::MakeClosure`] builds: the lambda's shared IR plus where each of
+/// its captures lives in the creating frame (`None` only for an IR
+/// inconsistency — the capture is left out and the closure falls back to the
+/// tree-walker, which reports it).
+pub(crate) struct Proto {
+    pub(crate) params: Rc<Vec<Param>>,
+    pub(crate) body: Rc<Expr>,
+    pub(crate) expr_id: ExprId,
+    pub(crate) captures: Vec<(BindingId, Option<u32>)>,
+}
+
+/// One match arm: its pattern (matched by the same routine the tree-walker
+/// uses) and the slot each of its variables lands in.
+pub(crate) struct Arm {
+    pub(crate) pattern: Pattern,
+    pub(crate) slots: Vec<(BindingId, u32)>,
+}
+
+impl Arm {
+    pub(crate) fn slot(&self, binding: BindingId) -> Option<u32> {
+        self.slots
+            .iter()
+            .find(|(b, _)| *b == binding)
+            .map(|(_, slot)| *slot)
+    }
+}
+
+/// Every compiled lambda of a module, by the lambda's [`ExprId`].
+#[derive(Default)]
+pub(crate) struct CodeTable {
+    by_expr: Vec<Option<Rc<Code>>>,
+}
+
+impl CodeTable {
+    /// The code for `closure`, when its body is the one compiled here — a
+    /// closure surviving from an older module (held by the host across a
+    /// reload) shares ids, not bodies, and keeps tree-walking.
+    pub(crate) fn get(&self, closure: &Closure) -> Option<&Rc<Code>> {
+        self.by_expr
+            .get(closure.expr_id.0 as usize)?
+            .as_ref()
+            .filter(|code| Rc::ptr_eq(&code.body, &closure.body))
+    }
+}
+
+/// Compile every lambda in `module` — inside defs, expects, and unit
+/// declarations alike. `globals` maps each top-level name to its slot.
+pub(crate) fn compile(module: &Module, globals: &HashMap<String, u32>) -> CodeTable {
+    let mut pending = Vec::new();
+    let roots = module
+        .defs
+        .iter()
+        .map(|def| &def.value)
+        .chain(module.expects.iter().map(|expect| &expect.expr))
+        .chain(module.units.iter().map(|unit| &unit.target))
+        .chain(module.unit_ops.iter().map(|op| &op.target));
+    for root in roots {
+        outermost_lambdas(root, &mut pending);
+    }
+    let mut captures = HashMap::new();
+    let mut table = CodeTable::default();
+    while let Some(lambda) = pending.pop() {
+        let code = Compiler::lambda(lambda, globals, &mut captures, &mut pending);
+        let id = lambda.id.0 as usize;
+        if table.by_expr.len() <= id {
+            table.by_expr.resize(id + 1, None);
+        }
+        table.by_expr[id] = Some(Rc::new(code));
+    }
+    table
+}
+
+struct Compiler<'m, 'c> {
+    globals: &'c HashMap<String, u32>,
+    /// Free-variable lists by lambda id, shared between the parent emitting
+    /// a closure and the child compiling against it.
+    captures: &'c mut HashMap<u32, Rc<Vec<BindingId>>>,
+    pending: &'c mut Vec<&'m Expr>,
+    slots: HashMap<u32, u32>,
+    /// Coverage starts waiting for the next emitted op.
+    cover: Vec<usize>,
+    code: Code,
+}
+
+impl<'m> Compiler<'m, '_> {
+    fn lambda<'c>(
+        lambda: &'m Expr,
+        globals: &'c HashMap<String, u32>,
+        captures: &'c mut HashMap<u32, Rc<Vec<BindingId>>>,
+        pending: &'c mut Vec<&'m Expr>,
+    ) -> Code {
+        let ExprKind::Lambda { params, body, .. } = &lambda.kind else {
+            unreachable!("only lambdas are queued for compilation")
+        };
+        let free = captures
+            .entry(lambda.id.0)
+            .or_insert_with(|| Rc::new(free_locals(params, body)))
+            .clone();
+        let mut compiler = Compiler {
+            globals,
+            captures,
+            pending,
+            slots: HashMap::new(),
+            cover: Vec::new(),
+            code: Code {
+                ops: Vec::new(),
+                spans: Vec::new(),
+                cover_at: vec![0],
+                covers: Vec::new(),
+                slots: 0,
+                captures: free.clone(),
+                body: body.clone(),
+                consts: Vec::new(),
+                sites: Vec::new(),
+                binders: Vec::new(),
+                names: Vec::new(),
+                shapes: Vec::new(),
+                labels: Vec::new(),
+                paths: Vec::new(),
+                protos: Vec::new(),
+                arms: Vec::new(),
+            },
+        };
+        for param in params.iter() {
+            compiler.slot(param.binding);
+        }
+        for &binding in free.iter() {
+            compiler.slot(binding);
+        }
+        compiler.expr_at(body, true);
+        compiler.emit(Op::Return, body.span);
+        compiler.code
+    }
+
+    /// The slot for `binding`, allocating the next one on first sight.
+    fn slot(&mut self, binding: BindingId) -> u32 {
+        let next = self.code.slots as u32;
+        let slot = *self.slots.entry(binding.0).or_insert(next);
+        if slot == next {
+            self.code.slots += 1;
+        }
+        slot
+    }
+
+    fn emit(&mut self, op: Op, span: Span) -> usize {
+        let pc = self.code.ops.len();
+        self.code.ops.push(op);
+        self.code.spans.push(span);
+        self.code.covers.append(&mut self.cover);
+        self.code.cover_at.push(self.code.covers.len() as u32);
+        pc
+    }
+
+    /// Point the jump at `at` to the next op emitted.
+    fn patch(&mut self, at: usize) {
+        let target = self.code.ops.len() as u32;
+        match &mut self.code.ops[at] {
+            Op::Jump(t)
+            | Op::JumpIfFalse(t)
+            | Op::GuardIfFalse(t)
+            | Op::JumpIfFalseKeep(t)
+            | Op::JumpIfTrueKeep(t)
+            | Op::Match { next: t, .. } => *t = target,
+            _ => unreachable!("only jumps are patched"),
+        }
+    }
+
+    fn constant(&mut self, value: Value) -> u32 {
+        self.code.consts.push(value);
+        self.code.consts.len() as u32 - 1
+    }
+
+    fn site(&mut self, name: &str, span: Span) -> u32 {
+        self.code.sites.push((name.to_string(), span));
+        self.code.sites.len() as u32 - 1
+    }
+
+    fn name(&mut self, name: &str) -> u32 {
+        self.code.names.push(name.to_string());
+        self.code.names.len() as u32 - 1
+    }
+
+    fn expr(&mut self, expr: &'m Expr) {
+        self.expr_at(expr, false)
+    }
+
+    /// Compile `expr`, leaving its value on the stack. Mirrors
+    /// `Interp::eval_inner` case for case — including WHICH nodes count as
+    /// evaluated for coverage (the inner links of a `+` / `&&` / `else if`
+    /// spine do not) and the iterative spine walks, so a 2000-term chain
+    /// costs no compiler stack either. `tail` marks an expression whose value
+    /// the frame returns as-is: the body, and from a tail `let` / `:=` /
+    /// `if` / `match`, its continuation, branches, and arm bodies.
+    fn expr_at(&mut self, expr: &'m Expr, tail: bool) {
+        self.cover.push(expr.span.start);
+        let span = expr.span;
+        match &expr.kind {
+            ExprKind::Number(n) => {
+                self.emit(Op::Number(*n), span);
+            }
+            ExprKind::Int(n) => {
+                self.emit(Op::Int(*n), span);
+            }
+            ExprKind::Bool(b) => {
+                self.emit(Op::Bool(*b), span);
+            }
+            ExprKind::String(s) => {
+                let c = self.constant(Value::String(Rc::from(s.as_str())));
+                self.emit(Op::Const(c), span);
+            }
+            ExprKind::InterpolatedString(parts) => {
+                for part in parts {
+                    match part {
+                        StringPart::Text(text) => {
+                            let c = self.constant(Value::String(Rc::from(text.as_str())));
+                            self.emit(Op::Text(c), span);
+                        }
+                        StringPart::Expr(part) => {
+                            self.expr(part);
+                            self.emit(Op::Stringify, span);
+                        }
+                    }
+                }
+                self.emit(Op::Concat(parts.len() as u32), span);
+            }
+            ExprKind::Local { binding, name } | ExprKind::LocalMut { binding, name } => {
+                let site = self.site(name, span);
+                match self.slots.get(&binding.0) {
+                    Some(&slot) => self.emit(Op::Local { slot, site }, span),
+                    None => self.emit(Op::Unbound(site), span),
+                };
+            }
+            ExprKind::Global(name) => {
+                let site = self.site(name, span);
+                match self.globals.get(name) {
+                    Some(&global) => self.emit(Op::Global { global, site }, span),
+                    None => self.emit(Op::Undefined(site), span),
+                };
+            }
+            // Builtins are fixed, so they become constants; anything else
+            // may be the host's, which is asked when the op runs.
+            ExprKind::External(path) => {
+                match builtin(path) {
+                    Some(Builtin::MathPi) => self.emit(Op::Number(std::f64::consts::PI), span),
+                    Some(b) => {
+                        let c = self.constant(Value::Builtin(b));
+                        self.emit(Op::Const(c), span)
+                    }
+                    None => {
+                        self.code.paths.push(path.clone());
+                        let p = self.code.paths.len() as u32 - 1;
+                        self.emit(Op::External(p), span)
+                    }
+                };
+            }
+            ExprKind::Record(fields) => {
+                self.emit(Op::Step, span);
+                for field in fields {
+                    self.expr(&field.value);
+                }
+                self.code
+                    .shapes
+                    .push(fields.iter().map(|field| field.name.clone()).collect());
+                let shape = self.code.shapes.len() as u32 - 1;
+                self.emit(Op::Record(shape), span);
+            }
+            ExprKind::Tuple(items) | ExprKind::List(items) => {
+                self.emit(Op::Step, span);
+                for item in items {
+                    self.expr(item);
+                }
+                let n = items.len() as u32;
+                let op = match &expr.kind {
+                    ExprKind::Tuple(_) => Op::Tuple(n),
+                    _ => Op::List(n),
+                };
+                self.emit(op, span);
+            }
+            ExprKind::ListCons { items, tail } => {
+                self.emit(Op::Step, span);
+                for item in items {
+                    self.expr(item);
+                }
+                self.expr(tail);
+                self.emit(Op::Cons(items.len() as u32), tail.span);
+            }
+            ExprKind::RecordUpdate { base, fields } => {
+                self.emit(Op::Step, span);
+                self.expr(base);
+                self.emit(Op::UpdateBase, span);
+                for field in fields {
+                    let name = self.name(&field.name);
+                    self.emit(Op::UpdateCheck(name), field.span);
+                    self.expr(&field.value);
+                    self.emit(Op::UpdateSet(name), field.span);
+                }
+            }
+            ExprKind::Let {
+                binding,
+                name,
+                value,
+                body,
+                ..
+            } => {
+                self.expr(value);
+                let slot = self.slot(*binding);
+                let binder_span = Span::new(span.start, value.span.start);
+                self.code
+                    .binders
+                    .push((*binding, name.clone(), binder_span));
+                let binder = self.code.binders.len() as u32 - 1;
+                self.emit(Op::Bind { slot, binder }, span);
+                self.expr_at(body, tail);
+            }
+            ExprKind::Assign {
+                binding,
+                name,
+                value,
+                rest,
+            } => {
+                self.expr(value);
+                match self.slots.get(&binding.0) {
+                    Some(&slot) => self.emit(Op::Store(slot), span),
+                    None => {
+                        let site = self.site(name, span);
+                        self.emit(Op::Unbound(site), span)
+                    }
+                };
+                self.expr_at(rest, tail);
+            }
+            ExprKind::FieldAccess { object, field } => {
+                self.expr(object);
+                let name = self.name(field);
+                self.emit(Op::Field(name), span);
+            }
+            ExprKind::Lambda { params, body, .. } => {
+                self.pending.push(expr);
+                let free = self
+                    .captures
+                    .entry(expr.id.0)
+                    .or_insert_with(|| Rc::new(free_locals(params, body)))
+                    .clone();
+                let captures = free
+                    .iter()
+                    .map(|binding| (*binding, self.slots.get(&binding.0).copied()))
+                    .collect();
+                self.code.protos.push(Proto {
+                    params: params.clone(),
+                    body: body.clone(),
+                    expr_id: expr.id,
+                    captures,
+                });
+                let proto = self.code.protos.len() as u32 - 1;
+                self.emit(Op::MakeClosure(proto), span);
+            }
+            ExprKind::Call { callee, args } => {
+                self.expr(callee);
+                for arg in args {
+                    self.expr(arg);
+                }
+                self.code.labels.push(callee_label(callee));
+                let label = self.code.labels.len() as u32 - 1;
+                let argc = args.len() as u32;
+                let op = if tail {
+                    Op::TailCall { argc, label }
+                } else {
+                    Op::Call { argc, label }
+                };
+                self.emit(op, span);
+            }
+            ExprKind::Binary { .. } => {
+                let mut spine = Vec::new();
+                let mut leaf = expr;
+                while let ExprKind::Binary { op, lhs, rhs } = &leaf.kind {
+                    spine.push((*op, rhs.as_ref(), leaf.span));
+                    leaf = lhs;
+                }
+                self.expr(leaf);
+                for (op, rhs, span) in spine.into_iter().rev() {
+                    self.expr(rhs);
+                    self.emit(Op::Binary(op), span);
+                }
+            }
+            ExprKind::Logical { .. } => {
+                let mut spine = Vec::new();
+                let mut leaf = expr;
+                while let ExprKind::Logical { op, lhs, rhs } = &leaf.kind {
+                    spine.push((*op, rhs.as_ref()));
+                    leaf = lhs;
+                }
+                self.expr(leaf);
+                self.emit(Op::AsBool, leaf.span);
+                for (op, rhs) in spine.into_iter().rev() {
+                    let decided = match op {
+                        LogicalOp::And => self.emit(Op::JumpIfFalseKeep(0), rhs.span),
+                        LogicalOp::Or => self.emit(Op::JumpIfTrueKeep(0), rhs.span),
+                    };
+                    self.emit(Op::Pop, rhs.span);
+                    self.expr(rhs);
+                    self.emit(Op::AsBool, rhs.span);
+                    self.patch(decided);
+                }
+            }
+            ExprKind::Neg(inner) => {
+                self.expr(inner);
+                self.emit(Op::Neg, span);
+            }
+            ExprKind::Not(inner) => {
+                self.expr(inner);
+                self.emit(Op::AsBool, inner.span);
+                self.emit(Op::Not, span);
+            }
+            ExprKind::If { .. } => {
+                let mut ends = Vec::new();
+                let mut node = expr;
+                while let ExprKind::If {
+                    cond,
+                    then_branch,
+                    else_branch,
+                } = &node.kind
+                {
+                    self.expr(cond);
+                    let skip = self.emit(Op::JumpIfFalse(0), cond.span);
+                    self.expr_at(then_branch, tail);
+                    ends.push(self.emit(Op::Jump(0), then_branch.span));
+                    self.patch(skip);
+                    node = else_branch;
+                }
+                self.expr_at(node, tail);
+                for end in ends {
+                    self.patch(end);
+                }
+            }
+            ExprKind::Ctor { name, arity } => {
+                let value = if *arity == 0 {
+                    Value::Variant {
+                        ctor: Rc::from(name.as_str()),
+                        args: Rc::new(Vec::new()),
+                    }
+                } else {
+                    Value::Ctor {
+                        name: Rc::from(name.as_str()),
+                        arity: *arity,
+                    }
+                };
+                let c = self.constant(value);
+                self.emit(Op::Const(c), span);
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.expr(scrutinee);
+                let mut ends = Vec::new();
+                for arm in arms {
+                    let mut bindings = Vec::new();
+                    pattern_bindings(&arm.pattern, &mut bindings);
+                    let slots = bindings
+                        .into_iter()
+                        .map(|binding| (binding, self.slot(binding)))
+                        .collect();
+                    self.code.arms.push(Arm {
+                        pattern: arm.pattern.clone(),
+                        slots,
+                    });
+                    let index = self.code.arms.len() as u32 - 1;
+                    let test = self.emit(
+                        Op::Match {
+                            arm: index,
+                            next: 0,
+                        },
+                        arm.pattern.span,
+                    );
+                    let guard = arm.guard.as_ref().map(|guard| {
+                        self.expr(guard);
+                        self.emit(Op::GuardIfFalse(0), guard.span)
+                    });
+                    self.emit(Op::Take(index), arm.span);
+                    self.expr_at(&arm.body, tail);
+                    ends.push(self.emit(Op::Jump(0), arm.span));
+                    self.patch(test);
+                    if let Some(guard) = guard {
+                        self.patch(guard);
+                    }
+                }
+                self.emit(Op::NoMatch, span);
+                for end in ends {
+                    self.patch(end);
+                }
+            }
+        }
+    }
+}
+
+/// Every pattern variable's binding, across all alternatives of an or-pattern
+/// (they share ids, so each appears once).
+fn pattern_bindings(pattern: &Pattern, out: &mut Vec<BindingId>) {
+    match &pattern.kind {
+        PatternKind::Var { binding, .. } => {
+            if !out.contains(binding) {
+                out.push(*binding);
+            }
+        }
+        PatternKind::Ctor { args, .. } | PatternKind::Tuple(args) | PatternKind::Or(args) => {
+            for arg in args {
+                pattern_bindings(arg, out);
+            }
+        }
+        PatternKind::List { items, tail } => {
+            for item in items {
+                pattern_bindings(item, out);
+            }
+            if let Some(tail) = tail {
+                pattern_bindings(tail, out);
+            }
+        }
+        PatternKind::Record(fields) => {
+            for field in fields {
+                pattern_bindings(&field.pattern, out);
+            }
+        }
+        PatternKind::Wildcard
+        | PatternKind::Number(_)
+        | PatternKind::Int(_)
+        | PatternKind::Bool(_)
+        | PatternKind::String(_) => {}
+    }
+}
+
+/// The locals a lambda reads but does not bind — its captures, in first-read
+/// order. Binding ids are unique per site, so "bound anywhere inside" (a
+/// nested lambda's parameter, a `let`, a pattern variable) is exact without
+/// tracking scopes.
+fn free_locals(params: &[Param], body: &Expr) -> Vec<BindingId> {
+    let mut bound: Vec<BindingId> = params.iter().map(|param| param.binding).collect();
+    let mut read = Vec::new();
+    let mut work = vec![body];
+    while let Some(expr) = work.pop() {
+        match &expr.kind {
+            ExprKind::Local { binding, .. } | ExprKind::LocalMut { binding, .. }
+                if !read.contains(binding) =>
+            {
+                read.push(*binding)
+            }
+            ExprKind::Let { binding, .. } => bound.push(*binding),
+            ExprKind::Lambda { params, .. } => {
+                bound.extend(params.iter().map(|param| param.binding))
+            }
+            ExprKind::Match { arms, .. } => {
+                for arm in arms {
+                    pattern_bindings(&arm.pattern, &mut bound);
+                }
+            }
+            _ => {}
+        }
+        // Reversed so the worklist visits children left to right.
+        let start = work.len();
+        children(expr, &mut work);
+        work[start..].reverse();
+    }
+    read.retain(|binding| !bound.contains(binding));
+    read
+}
+
+/// The lambdas in `expr` not nested inside another lambda — the compile
+/// roots; nested ones are queued as their parent compiles.
+fn outermost_lambdas<'m>(expr: &'m Expr, out: &mut Vec<&'m Expr>) {
+    let mut work = vec![expr];
+    while let Some(expr) = work.pop() {
+        if matches!(expr.kind, ExprKind::Lambda { .. }) {
+            out.push(expr);
+        } else {
+            children(expr, &mut work);
+        }
+    }
+}
+
+/// Push `expr`'s direct sub-expressions.
+fn children<'m>(expr: &'m Expr, out: &mut Vec<&'m Expr>) {
+    match &expr.kind {
+        ExprKind::Number(_)
+        | ExprKind::Int(_)
+        | ExprKind::String(_)
+        | ExprKind::Bool(_)
+        | ExprKind::Local { .. }
+        | ExprKind::LocalMut { .. }
+        | ExprKind::Global(_)
+        | ExprKind::External(_)
+        | ExprKind::Ctor { .. } => {}
+        ExprKind::InterpolatedString(parts) => {
+            for part in parts {
+                if let StringPart::Expr(part) = part {
+                    out.push(part);
+                }
+            }
+        }
+        ExprKind::Record(fields) => out.extend(fields.iter().map(|field| &field.value)),
+        ExprKind::RecordUpdate { base, fields } => {
+            out.push(base);
+            out.extend(fields.iter().map(|field| &field.value));
+        }
+        ExprKind::List(items) | ExprKind::Tuple(items) => out.extend(items),
+        ExprKind::ListCons { items, tail } => {
+            out.extend(items);
+            out.push(tail);
+        }
+        ExprKind::Let { value, body, .. } => {
+            out.push(value);
+            out.push(body);
+        }
+        ExprKind::Assign { value, rest, .. } => {
+            out.push(value);
+            out.push(rest);
+        }
+        ExprKind::FieldAccess { object, .. } => out.push(object),
+        ExprKind::Lambda { body, .. } => out.push(body),
+        ExprKind::Call { callee, args } => {
+            out.push(callee);
+            out.extend(args);
+        }
+        ExprKind::Binary { lhs, rhs, .. } | ExprKind::Logical { lhs, rhs, .. } => {
+            out.push(lhs);
+            out.push(rhs);
+        }
+        ExprKind::Neg(inner) | ExprKind::Not(inner) => out.push(inner),
+        ExprKind::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => {
+            out.push(cond);
+            out.push(then_branch);
+            out.push(else_branch);
+        }
+        ExprKind::Match { scrutinee, arms } => {
+            out.push(scrutinee);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    out.push(guard);
+                }
+                out.push(&arm.body);
+            }
+        }
+    }
+}
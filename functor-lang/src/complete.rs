@@ -301,7 +301,7 @@ fn context_at(live_text: &str, offset: usize) -> Context {
 /// that occur while typing inside an interpolation hole. The synthetic suffix
 /// is discarded by span before context classification, so it can only make
 /// the real prefix lexable; it never becomes a completion token itself.
-fn lex_completion_prefix(prefix: &str) -> Option<Vec<Token>> {
+pub(crate) fn lex_completion_prefix(prefix: &str) -> Option<Vec<Token>> {
     match crate::lexer::lex(prefix, 0) {
         Ok(tokens) => return Some(tokens),
         Err(error) if error.message.starts_with("unterminated ") => {}
@@ -342,7 +342,7 @@ fn lex_completion_prefix(prefix: &str) -> Option<Vec<Token>> {
 /// `Ident (Dot Ident)*` chain so `a.b.` yields `"a.b"`. A multi-segment
 /// qualifier names a nested namespace (`Utils.Grid.`); one that names no
 /// module comes back empty — the chained-member boundary.
-fn qualifier_chain(tokens: &[Token], end: usize) -> String {
+pub(crate) fn qualifier_chain(tokens: &[Token], end: usize) -> String {
     let mut names = Vec::new();
     let mut i = end;
     while let TokenKind::Ident(name) = &tokens[i].kind {
@@ -404,7 +404,7 @@ fn member_candidates(
             let fields = if qualifier.contains('.') {
                 Vec::new()
             } else {
-                record_fields_of(project, &ty)
+                record_fields_of(project, current_module, &ty)
             };
             return finish(fields, partial);
         }
@@ -435,8 +435,10 @@ fn member_candidates(
         }
     }
 
-    // Sibling/user-module defs (detail from the checker, hover-identical).
-    for def in &module.defs {
+    // Sibling/user-module defs (detail from the checker, hover-identical) —
+    // not another file's private or sealed-away helpers.
+    let reachable = |name: &str| project.reachable_from(name, current_module);
+    for def in module.defs.iter().filter(|def| reachable(&def.name)) {
         if let Some(label) = direct_member(&def.name, &prefix) {
             let ty = types.expr(def.value.id).cloned().unwrap_or(Type::Unknown);
             items.push(CompletionItem {
@@ -451,7 +453,7 @@ fn member_candidates(
     for ty in &module.types {
         if let TypeBody::Variants(decls) = &ty.body {
             let ret = ctor_return(ty);
-            for variant in decls {
+            for variant in decls.iter().filter(|variant| reachable(&variant.name)) {
                 if let Some(label) = direct_member(&variant.name, &prefix) {
                     items.push(CompletionItem {
                         label: label.to_string(),
@@ -479,7 +481,11 @@ fn member_candidates(
     // The namespaces nested under the qualifier (`Utils.` offers `Grid`).
     // Inline modules are their only source: no builtin or `.funi` name has a
     // second dot, so a member's own name can never imply one.
-    for inline in &project.inline_modules {
+    for inline in project
+        .inline_modules
+        .iter()
+        .filter(|inline| reachable(&inline.path))
+    {
         if let Some(segment) = inline.path.strip_prefix(&prefix) {
             items.push(CompletionItem {
                 label: segment.to_string(),
@@ -513,7 +519,7 @@ fn direct_member<'a>(name: &'a str, prefix: &str) -> Option<&'a str> {
 ///   reachable (`Server.step` there is an unknown external): `None`;
 /// - anything else — a sibling (`Utils`), a sibling's inline module
 ///   (`Utils.Grid`), a builtin namespace (`Scene`) — as written.
-fn canonical_qualifier(
+pub(crate) fn canonical_qualifier(
     project: &Project,
     current_module: &str,
     qualifier: &str,
@@ -547,11 +553,14 @@ fn canonical_qualifier(
 
 /// The fields of a declared record type, or empty. Only a [`Type::Record`]
 /// offers fields (gradual honesty — an unknown/non-record type offers
-/// nothing).
-fn record_fields_of(project: &Project, ty: &Type) -> Vec<CompletionItem> {
+/// nothing), and only where its representation is not sealed away.
+fn record_fields_of(project: &Project, current_module: &str, ty: &Type) -> Vec<CompletionItem> {
     let Type::Record(name, _) = ty else {
         return Vec::new();
     };
+    if !project.reachable_from(name, current_module) {
+        return Vec::new();
+    }
     // The declaration by its EXACT (canonical) name — a sibling's record is
     // `Utils.Vec2` in both the type and the declaration.
     let Some(decl) = project.module.types.iter().find(|decl| &decl.name == name) else {
@@ -718,7 +727,7 @@ fn top_level_candidates(
 
 /// A constructor's return type as declared: the owning type's bare name with
 /// its type parameters (`Shape`, `Option<'a>`).
-fn ctor_return(ty: &crate::ir::TypeDef) -> String {
+pub(crate) fn ctor_return(ty: &crate::ir::TypeDef) -> String {
     let name = bare_name(&ty.name);
     if ty.params.is_empty() {
         name.to_string()
@@ -813,9 +822,17 @@ fn collect_binders_in_scope(expr: &Expr, offset: usize, out: &mut Vec<(String, B
         ExprKind::Match { scrutinee, arms } => {
             collect_binders_in_scope(scrutinee, offset, out);
             for arm in arms {
-                if scope_contains(arm.body.span, offset) {
+                // Pattern variables are in scope in the guard and the body.
+                let in_guard = arm
+                    .guard
+                    .as_ref()
+                    .is_some_and(|guard| scope_contains(guard.span, offset));
+                if in_guard || scope_contains(arm.body.span, offset) {
                     collect_pattern_binders(&arm.pattern, out);
                 }
+                if let Some(guard) = &arm.guard {
+                    collect_binders_in_scope(guard, offset, out);
+                }
                 collect_binders_in_scope(&arm.body, offset, out);
             }
         }
@@ -848,8 +865,16 @@ fn collect_pattern_binders(pattern: &Pattern, out: &mut Vec<(String, BindingId,
                 collect_pattern_binders(tail, out);
             }
         }
+        PatternKind::Record(fields) => {
+            for field in fields {
+                collect_pattern_binders(&field.pattern, out);
+            }
+        }
+        // Every alternative binds the same ids; the first is canonical.
+        PatternKind::Or(alternatives) => collect_pattern_binders(&alternatives[0], out),
         PatternKind::Wildcard
         | PatternKind::Number(_)
+        | PatternKind::Int(_)
         | PatternKind::Bool(_)
         | PatternKind::String(_) => {}
     }
@@ -875,7 +900,7 @@ fn binder_item(name: &str, binding: BindingId, mutable: bool, types: &ExprTypes)
 /// The module a canonical name belongs to: everything before its LAST `.`
 /// (`Utils.clamp` → `Utils`, `Utils.Grid.cell` → the inline module
 /// `Utils.Grid`), or the entry (whose members are bare).
-fn owning_module<'a>(name: &'a str, entry: &'a str) -> &'a str {
+pub(crate) fn owning_module<'a>(name: &'a str, entry: &'a str) -> &'a str {
     name.rsplit_once('.').map_or(entry, |(module, _)| module)
 }
 
@@ -1014,6 +1039,31 @@ mod tests {
             find(&maps, "insert").detail.as_deref(),
             Some("Map.insert : ('a, 'b, Map<'a, 'b>) => Map<'a, 'b>")
         );
+
+        let sets = game(STUB, &[], "let s = Set.");
+        assert_eq!(
+            labels(&sets),
+            vec![
+                "difference",
+                "empty",
+                "fromList",
+                "insert",
+                "intersect",
+                "member",
+                "remove",
+                "toList",
+                "union"
+            ]
+        );
+        let arrays = game(STUB, &[], "let a = Array.");
+        assert_eq!(
+            find(&arrays, "set").detail.as_deref(),
+            Some("Array.set : (int, 'a, Array<'a>) => Array<'a>")
+        );
+        assert_eq!(
+            find(&arrays, "get").detail.as_deref(),
+            Some("Array.get : (int, Array<'a>) => Option.t<'a>")
+        );
     }
 
     // Bundled stdlib modules complete from their ordinary inferred `.fun`
@@ -1176,6 +1226,8 @@ mod tests {
         assert_eq!(find(&items, "Scene").kind, CompletionKind::Module);
         assert_eq!(find(&items, "List").kind, CompletionKind::Module);
         assert_eq!(find(&items, "Map").kind, CompletionKind::Module);
+        assert_eq!(find(&items, "Set").kind, CompletionKind::Module);
+        assert_eq!(find(&items, "Array").kind, CompletionKind::Module);
         assert!(!has(&items, "Utils.clamp"), "qualified label leaked");
         assert!(!has(&items, "Game"), "entry name offered as a module");
     }
@@ -1789,4 +1841,29 @@ mod tests {
         let items = complete(&project, "Game", Some("Server"), src, offset);
         assert_eq!(find(&items, "delta").detail.as_deref(), Some("delta : float"));
     }
+
+    // A `private let` is offered inside its own file but not as `Utils.` from
+    // another; a sealing `.funi` hides everything it leaves out the same way.
+    #[test]
+    fn private_and_sealed_members_stay_inside_their_file() {
+        let utils = "private let helper = 1.0\nlet version = helper\nlet extra = 2.0\n";
+        let project = project_of(&[("game.fun", STUB), ("utils.fun", utils)], &[]);
+        let live = "let x = Utils.";
+        let items = complete(&project, "Game", None, live, live.len());
+        assert!(!has(&items, "helper"), "{:?}", labels(&items));
+        assert!(has(&items, "version") && has(&items, "extra"));
+        let own = complete(&project, "Utils", None, "let x = ", "let x = ".len());
+        assert!(has(&own, "helper"), "{:?}", labels(&own));
+
+        let sealed = project_of(
+            &[
+                ("game.fun", STUB),
+                ("utils.fun", utils),
+                ("utils.funi", "let version : float\n"),
+            ],
+            &[],
+        );
+        let items = complete(&sealed, "Game", None, live, live.len());
+        assert_eq!(labels(&items), ["version"]);
+    }
 }
@@ -9,8 +9,17 @@
 //! which positions COULD run. Mirrors the inlay/hover walk (Lambda bodies
 //! and Match arms special-cased, everything else through
 //! [`crate::hover::children`]) so the three walks stay in lockstep.
+//!
+//! `functor test --coverage` pairs the same set with the positions a test
+//! run evaluated ([`crate::run_expects_covered`]): [`report`] folds them into
+//! per-file line, branch (`if` branches and `match` arms) and function
+//! coverage, and [`lcov`] writes that as an LCOV tracefile.
+
+use std::collections::HashSet;
+use std::path::Path;
 
 use crate::ir::{Expr, ExprKind, Module};
+use crate::project::Project;
 
 /// Every expression span start in the module's defs, sorted and deduped —
 /// the static "could run" set the runtime pairs with per-frame coverage.
@@ -24,6 +33,235 @@ pub fn runnable_offsets(module: &Module) -> Vec<usize> {
     out
 }
 
+/// One file's coverage, in the shape an LCOV record has.
+#[derive(Debug, Clone)]
+pub struct FileCoverage {
+    pub path: std::path::PathBuf,
+    pub module: String,
+    /// Each line a runnable expression starts on, and whether one ran.
+    pub lines: Vec<(usize, bool)>,
+    pub branches: Vec<Branch>,
+    pub functions: Vec<Function>,
+}
+
+/// An `if` (then, else) or a `match` (one branch per arm): which ran.
+#[derive(Debug, Clone)]
+pub struct Branch {
+    /// The line the `if`/`match` starts on.
+    pub line: usize,
+    pub taken: Vec<bool>,
+}
+
+/// A top-level `let` bound to a lambda; hit when its body ran.
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: String,
+    pub line: usize,
+    pub hit: bool,
+}
+
+/// `(hit, found)` counts, LCOV's `LH`/`LF`-style pairs.
+pub type Counts = (usize, usize);
+
+impl FileCoverage {
+    pub fn line_counts(&self) -> Counts {
+        let hit = self.lines.iter().filter(|(_, hit)| *hit).count();
+        (hit, self.lines.len())
+    }
+
+    pub fn branch_counts(&self) -> Counts {
+        let taken = self.branches.iter().flat_map(|branch| &branch.taken);
+        let (hit, found) = taken.fold((0, 0), |(hit, found), taken| {
+            (hit + usize::from(*taken), found + 1)
+        });
+        (hit, found)
+    }
+
+    pub fn function_counts(&self) -> Counts {
+        let hit = self
+            .functions
+            .iter()
+            .filter(|function| function.hit)
+            .count();
+        (hit, self.functions.len())
+    }
+}
+
+/// Fold a run's `covered` set (sorted span starts, as
+/// [`crate::run_expects_covered`] returns it) into per-file coverage of
+/// `project`'s defs, in file order. Only the project's own `.fun` files
+/// count: interface files have nothing to run, bundled modules (`<stdlib>/…`
+/// paths) are not the project's code, and a file with no runnable position
+/// is left out.
+pub fn report(project: &Project, covered: &[usize]) -> Vec<FileCoverage> {
+    let ran = |start: &usize| covered.binary_search(start).is_ok();
+    let own = |file: &&crate::project::SourceFile| {
+        !file.interface && !file.path.to_string_lossy().starts_with('<')
+    };
+    let mut files: Vec<FileCoverage> = project
+        .sources
+        .files()
+        .iter()
+        .filter(own)
+        .map(|file| FileCoverage {
+            path: file.path.clone(),
+            module: file.module.clone(),
+            lines: Vec::new(),
+            branches: Vec::new(),
+            functions: Vec::new(),
+        })
+        .collect();
+    let lines: Vec<Lines> = (project.sources.files().iter())
+        .filter(own)
+        .map(|file| Lines::new(&file.src, file.base))
+        .collect();
+    let file_of = |start: usize| lines.iter().position(|lines| lines.contains(start));
+
+    let mut hit_lines: Vec<HashSet<usize>> = vec![HashSet::new(); files.len()];
+    for start in runnable_offsets(&project.module) {
+        let Some(index) = file_of(start) else {
+            continue;
+        };
+        let line = lines[index].line(start);
+        if ran(&start) {
+            hit_lines[index].insert(line);
+        }
+        if files[index]
+            .lines
+            .last()
+            .is_none_or(|(last, _)| *last != line)
+        {
+            files[index].lines.push((line, false));
+        }
+    }
+    for (file, hit) in files.iter_mut().zip(&hit_lines) {
+        for (line, ran) in &mut file.lines {
+            *ran = hit.contains(line);
+        }
+    }
+
+    let mut points = Vec::new();
+    for def in &project.module.defs {
+        branch_points(&def.value, &mut points);
+        let ExprKind::Lambda { body, .. } = &def.value.kind else {
+            continue;
+        };
+        if let Some(index) = file_of(def.span.start) {
+            files[index].functions.push(Function {
+                name: def.name.clone(),
+                line: lines[index].line(def.span.start),
+                hit: ran(&body.span.start),
+            });
+        }
+    }
+    for (start, arms) in points {
+        if let Some(index) = file_of(start) {
+            files[index].branches.push(Branch {
+                line: lines[index].line(start),
+                taken: arms.iter().map(ran).collect(),
+            });
+        }
+    }
+    files.retain(|file| !file.lines.is_empty());
+    files
+}
+
+/// Every `if`/`match` under `expr`: its start and each branch's body start.
+fn branch_points(expr: &Expr, out: &mut Vec<(usize, Vec<usize>)>) {
+    match &expr.kind {
+        ExprKind::If {
+            then_branch,
+            else_branch,
+            ..
+        } => out.push((
+            expr.span.start,
+            vec![then_branch.span.start, else_branch.span.start],
+        )),
+        ExprKind::Match { arms, .. } => out.push((
+            expr.span.start,
+            arms.iter().map(|arm| arm.body.span.start).collect(),
+        )),
+        _ => {}
+    }
+    match &expr.kind {
+        ExprKind::Lambda { body, .. } => branch_points(body, out),
+        ExprKind::Match { scrutinee, arms } => {
+            branch_points(scrutinee, out);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    branch_points(guard, out);
+                }
+                branch_points(&arm.body, out);
+            }
+        }
+        _ => {
+            for child in crate::hover::children(expr) {
+                branch_points(child, out);
+            }
+        }
+    }
+}
+
+/// A file's line starts in the project-wide span space, for offset → line.
+struct Lines {
+    starts: Vec<usize>,
+    end: usize,
+}
+
+impl Lines {
+    fn new(src: &str, base: usize) -> Lines {
+        let breaks = src.match_indices('\n').map(|(i, _)| base + i + 1);
+        Lines {
+            starts: std::iter::once(base).chain(breaks).collect(),
+            end: base + src.len(),
+        }
+    }
+
+    fn contains(&self, offset: usize) -> bool {
+        self.starts[0] <= offset && offset <= self.end
+    }
+
+    /// The 1-based line of `offset`.
+    fn line(&self, offset: usize) -> usize {
+        self.starts.partition_point(|start| *start <= offset)
+    }
+}
+
+/// `files` as an LCOV tracefile (`SF`/`FN`/`DA`/`BRDA` records), paths
+/// relative to `root`. Counts are 1 or 0: the run records whether a position
+/// ran, not how often.
+pub fn lcov(files: &[FileCoverage], root: &Path) -> String {
+    let mut out = String::new();
+    for file in files {
+        let path = file.path.strip_prefix(root).unwrap_or(&file.path);
+        out.push_str(&format!("TN:\nSF:{}\n", path.display()));
+        for function in &file.functions {
+            out.push_str(&format!("FN:{},{}\n", function.line, function.name));
+        }
+        for function in &file.functions {
+            let hits = usize::from(function.hit);
+            out.push_str(&format!("FNDA:{hits},{}\n", function.name));
+        }
+        let (hit, found) = file.function_counts();
+        out.push_str(&format!("FNF:{found}\nFNH:{hit}\n"));
+        for (block, branch) in file.branches.iter().enumerate() {
+            for (arm, taken) in branch.taken.iter().enumerate() {
+                let line = branch.line;
+                let taken = usize::from(*taken);
+                out.push_str(&format!("BRDA:{line},{block},{arm},{taken}\n"));
+            }
+        }
+        let (hit, found) = file.branch_counts();
+        out.push_str(&format!("BRF:{found}\nBRH:{hit}\n"));
+        for (line, hit) in &file.lines {
+            out.push_str(&format!("DA:{line},{}\n", usize::from(*hit)));
+        }
+        let (hit, found) = file.line_counts();
+        out.push_str(&format!("LF:{found}\nLH:{hit}\nend_of_record\n"));
+    }
+    out
+}
+
 fn visit(expr: &Expr, out: &mut Vec<usize>) {
     out.push(expr.span.start);
     match &expr.kind {
@@ -31,6 +269,9 @@ fn visit(expr: &Expr, out: &mut Vec<usize>) {
         ExprKind::Match { scrutinee, arms } => {
             visit(scrutinee, out);
             for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    visit(guard, out);
+                }
                 visit(&arm.body, out);
             }
         }
@@ -64,6 +305,46 @@ mod tests {
         assert!(offsets.windows(2).all(|w| w[0] < w[1]));
     }
 
+    // The `update` arm no expect reaches is an untaken branch and an unhit
+    // line; the helper nothing calls is an unhit function.
+    #[test]
+    fn a_run_folds_into_lines_branches_and_functions() {
+        let src = "type Msg = | Inc | Reset\n\
+                   let update = (msg, n) =>\n  \
+                   match msg with\n  \
+                   | Inc => if n > 9.0 then 0.0 else n + 1.0\n  \
+                   | Reset => 0.0\n\
+                   let unused = (x) => x\n\
+                   expect update(Inc, 1.0) == 2.0\n";
+        let project = crate::project::load_single_source("Game", src)
+            .unwrap_or_else(|err| panic!("loads: {}", err.message));
+        let options = crate::ExpectOptions::default();
+        let (reports, covered) =
+            crate::run_expects_covered(&project.module, &mut crate::NoHost, &options)
+                .unwrap_or_else(|failure| panic!("{}", failure.error.message));
+        assert!(matches!(reports[0].outcome, crate::ExpectOutcome::Pass));
+        let files = report(&project, &covered);
+        let game = files
+            .iter()
+            .find(|file| file.module == "Game")
+            .expect("Game");
+        let taken: Vec<(usize, Vec<bool>)> = (game.branches.iter())
+            .map(|branch| (branch.line, branch.taken.clone()))
+            .collect();
+        assert_eq!(taken, [(3, vec![true, false]), (4, vec![false, true])]);
+        assert!(game.lines.contains(&(5, false)), "{:?}", game.lines);
+        assert_eq!(game.function_counts(), (1, 2));
+
+        let text = lcov(&files, Path::new(""));
+        assert!(
+            text.contains("SF:Game.fun\nFN:2,update\nFN:6,unused\n"),
+            "{text}"
+        );
+        assert!(text.contains("BRDA:3,0,1,0\n"), "{text}");
+        assert!(text.contains("DA:5,0\n"), "{text}");
+        assert!(text.contains("BRF:4\nBRH:2\n"), "{text}");
+    }
+
     // Lambda bodies (the walk's special case) are included.
     #[test]
     fn lambda_bodies_are_runnable() {
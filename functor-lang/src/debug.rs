@@ -0,0 +1,1009 @@
+//! A source-level debugger over [`Session`]: line and conditional
+//! breakpoints, step in/over/out, stack frames with their locals, variables
+//! rendered from [`Value`], and evaluate-in-frame. The transport-agnostic
+//! engine behind `functor-lang-dap` (a Debug Adapter Protocol server) and a
+//! running game's `POST /debug`.
+//!
+//! ## Replay
+//!
+//! Evaluation is deterministic over immutable values, so the engine never
+//! suspends an interpreter. A debugged RUN is a closure that evaluates
+//! something under a [`DebugHook`] — loading a program ([`run_program`]),
+//! or replaying one journaled `update` — and stopping abandons it. Resuming
+//! replays the same run from its start, passing the pause points it has
+//! already seen (counted by ordinal) before applying the step rule. A step
+//! costs a replay; in exchange the stopped state is plain data, and no
+//! transport needs a second thread.
+//!
+//! A pause point is the innermost frame reaching a new source line (see
+//! [`DebugHook`]). With `d` the frame depth at the stop, [`Step::In`] stops
+//! at the next pause point, [`Step::Over`] at the next one at depth ≤ `d`,
+//! [`Step::Out`] at depth < `d`, and [`Step::Continue`] at the next
+//! breakpoint whose condition holds. A breakpoint also ends a step early.
+//!
+//! ## Evaluate-in-frame
+//!
+//! An expression typed at a stop (or a breakpoint condition) is linked as a
+//! def appended to the frame's file — so file-private names and `open`s
+//! resolve as they do there — taking the frame's locals as parameters, and
+//! is evaluated against the stopped globals.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::eval::{DebugHook, Paused, Resume};
+use crate::ir::{Expr, ExprKind, Module};
+use crate::project::Project;
+use crate::value::Value;
+use crate::{Host, RunError, Session, Span};
+
+/// A line breakpoint: 1-based, with an optional condition (an expression
+/// over the frame's locals that must evaluate to `true`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub line: usize,
+    pub condition: Option<String>,
+}
+
+/// How to resume from a stop (see the module doc).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    Continue,
+    In,
+    Over,
+    Out,
+}
+
+/// Why a run stopped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// The first pause point, when starting with `stop_on_entry`.
+    Entry,
+    Step,
+    Breakpoint,
+    /// A breakpoint whose condition failed to evaluate — it stops, so a
+    /// typo in a condition is not a breakpoint that silently never hits.
+    Condition(String),
+    /// The run failed; its innermost frame is shown as it was.
+    Error(String),
+}
+
+/// One frame of a stopped run.
+pub struct StackFrame {
+    /// The function (`update`, `<lambda>`) or top-level def it evaluates.
+    pub name: String,
+    /// The expression it is at: the next one to run in the innermost frame,
+    /// the call site in a caller. Project-wide; see [`Debugger::locate`].
+    pub span: Span,
+    /// Its locals in scope, outermost first, shadowed ones omitted.
+    pub locals: Vec<(String, Value)>,
+}
+
+/// A stopped run: why, and its frames, innermost first.
+pub struct Stopped {
+    pub reason: StopReason,
+    pub frames: Vec<StackFrame>,
+    /// The pause point's ordinal in the run (see the module doc).
+    ordinal: usize,
+    /// The globals as they stood, for evaluating in a frame.
+    session: Session,
+    /// For an [`StopReason::Error`] stop: the error the run ended with.
+    error: Option<RunError>,
+}
+
+/// The result of starting or resuming a run.
+pub enum Outcome {
+    /// Stopped; the stop is [`Debugger::stopped`].
+    Stopped(StopReason),
+    /// Ran to completion: the run's value, if it has one to report.
+    Finished(Result<Option<Value>, RunError>),
+}
+
+/// A variables scope of a frame (see [`Debugger::scopes`]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scope {
+    pub name: &'static str,
+    pub reference: usize,
+}
+
+/// One rendered variable. `reference` is nonzero when it has children,
+/// listed by [`Debugger::variables`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+    pub reference: usize,
+}
+
+/// A debugged run: evaluate under the hook, answering `None` when the hook
+/// stopped it (see [`Session::call_debugged`]).
+pub trait Run: FnMut(&mut dyn DebugHook) -> Option<Result<Option<Value>, RunError>> {}
+
+impl<F> Run for F where F: FnMut(&mut dyn DebugHook) -> Option<Result<Option<Value>, RunError>> {}
+
+/// Debug a program the way `functor-lang run` runs it: load it, then call
+/// `main` when it defines one — the run `functor-lang-dap` launches.
+pub fn run_program(
+    module: &Module,
+    host: &mut dyn Host,
+    hook: &mut dyn DebugHook,
+) -> Option<Result<Option<Value>, RunError>> {
+    let session = match Session::load_debugged(module, host, hook)? {
+        Ok(session) => session,
+        Err(failure) => return Some(Err(failure.error)),
+    };
+    if session.global("main").is_none() {
+        return Some(Ok(None));
+    }
+    Some(
+        session
+            .call_debugged("main", Vec::new(), host, hook)?
+            .map(Some),
+    )
+}
+
+/// The debugger: a project, its breakpoints, and the current stop.
+pub struct Debugger {
+    index: Index,
+    /// Breakpoints by [`Index::line`] key, with their conditions.
+    breakpoints: HashMap<usize, Option<String>>,
+    /// The keys each [`Self::set_breakpoints`] call installed, by file.
+    files: HashMap<usize, Vec<usize>>,
+    stop_on_errors: bool,
+    evaluations: Evaluations,
+    stopped: Option<Stopped>,
+    /// What the variable references of the current stop point at (a
+    /// reference is an index + 1).
+    nodes: Vec<Node>,
+}
+
+impl Debugger {
+    /// Debug runs of `project`'s module.
+    pub fn new(project: Project) -> Debugger {
+        Debugger {
+            index: Index::new(project),
+            breakpoints: HashMap::new(),
+            files: HashMap::new(),
+            stop_on_errors: true,
+            evaluations: Evaluations::default(),
+            stopped: None,
+            nodes: Vec::new(),
+        }
+    }
+
+    /// The project — shared, so a run can hold its module while the
+    /// debugger drives it.
+    pub fn project(&self) -> Rc<Project> {
+        self.index.project.clone()
+    }
+
+    /// Replace the breakpoints of the file at `path` (matched as
+    /// [`crate::project::SourceMap::file_by_path`] does, then by file name
+    /// alone — a running game knows its files only by name). Answers, per
+    /// breakpoint, whether it is VERIFIED: some expression starts on its
+    /// line. An unknown file verifies none.
+    pub fn set_breakpoints(&mut self, path: &Path, breakpoints: &[Breakpoint]) -> Vec<bool> {
+        let Some(file) = self.index.file(path) else {
+            return vec![false; breakpoints.len()];
+        };
+        for key in self.files.remove(&file).unwrap_or_default() {
+            self.breakpoints.remove(&key);
+        }
+        let mut installed = Vec::new();
+        let verified = breakpoints
+            .iter()
+            .map(|breakpoint| {
+                let Some(key) = self.index.key(file, breakpoint.line) else {
+                    return false;
+                };
+                self.breakpoints.insert(key, breakpoint.condition.clone());
+                installed.push(key);
+                self.index.code.contains(&key)
+            })
+            .collect();
+        self.files.insert(file, installed);
+        verified
+    }
+
+    /// Whether any breakpoint is set — a running game only replays its
+    /// frames when one is.
+    pub fn has_breakpoints(&self) -> bool {
+        !self.breakpoints.is_empty()
+    }
+
+    /// Whether a failing run stops at the failure (the default) rather than
+    /// finishing with its error.
+    pub fn set_stop_on_errors(&mut self, stop: bool) {
+        self.stop_on_errors = stop;
+    }
+
+    /// Start `run`, stopping at its first pause point if `stop_on_entry`,
+    /// else at a breakpoint.
+    pub fn start(&mut self, stop_on_entry: bool, run: impl Run) -> Outcome {
+        let rule = match stop_on_entry {
+            true => Rule::Depth(usize::MAX, StopReason::Entry),
+            false => Rule::Breakpoints,
+        };
+        self.drive(0, rule, run)
+    }
+
+    /// Resume the stopped run — the same `run` [`Self::start`] was given —
+    /// by `step`. Without a stop this starts the run afresh.
+    pub fn resume(&mut self, step: Step, run: impl Run) -> Outcome {
+        let Some(stopped) = self.stopped.take() else {
+            return self.start(false, run);
+        };
+        if let Some(error) = stopped.error {
+            self.nodes.clear();
+            return Outcome::Finished(Err(error));
+        }
+        let depth = stopped.frames.len();
+        let rule = match step {
+            Step::Continue => Rule::Breakpoints,
+            Step::In => Rule::Depth(usize::MAX, StopReason::Step),
+            Step::Over => Rule::Depth(depth, StopReason::Step),
+            Step::Out => Rule::Depth(depth - 1, StopReason::Step),
+        };
+        self.drive(stopped.ordinal + 1, rule, run)
+    }
+
+    /// The current stop, if the run is stopped.
+    pub fn stopped(&self) -> Option<&Stopped> {
+        self.stopped.as_ref()
+    }
+
+    /// Forget the current stop (the client disconnected, or the run it
+    /// belongs to is gone).
+    pub fn clear(&mut self) {
+        self.stopped = None;
+        self.nodes.clear();
+    }
+
+    /// A project-wide offset as (file path, 1-based line, 1-based column).
+    pub fn locate(&self, offset: usize) -> (&Path, usize, usize) {
+        let (file, line, col) = self.index.project.sources.resolve(offset);
+        (&file.path, line, col)
+    }
+
+    /// Frame `frame`'s (innermost first) variable scopes.
+    pub fn scopes(&mut self, frame: usize) -> Vec<Scope> {
+        match self
+            .stopped
+            .as_ref()
+            .and_then(|stopped| stopped.frames.get(frame))
+        {
+            Some(_) => vec![
+                Scope {
+                    name: "Locals",
+                    reference: self.node(Node::Locals(frame)),
+                },
+                Scope {
+                    name: "Globals",
+                    reference: self.node(Node::Globals(frame)),
+                },
+            ],
+            None => Vec::new(),
+        }
+    }
+
+    /// The children of a scope or a variable.
+    pub fn variables(&mut self, reference: usize) -> Vec<Variable> {
+        let Some(stopped) = &self.stopped else {
+            return Vec::new();
+        };
+        let children: Vec<(String, Value)> = match reference
+            .checked_sub(1)
+            .and_then(|index| self.nodes.get(index))
+        {
+            Some(Node::Locals(frame)) => stopped.frames[*frame].locals.clone(),
+            Some(Node::Globals(frame)) => {
+                let file = self
+                    .index
+                    .project
+                    .sources
+                    .file_at(stopped.frames[*frame].span.start);
+                let module = &self.index.project.module;
+                module
+                    .defs
+                    .iter()
+                    .filter(|def| {
+                        self.index.project.sources.file_at(def.span.start).base == file.base
+                    })
+                    .filter_map(|def| Some((def.name.clone(), stopped.session.global(&def.name)?)))
+                    .filter(|(_, value)| !is_callable(value))
+                    .collect()
+            }
+            Some(Node::Value(value)) => children(value),
+            None => Vec::new(),
+        };
+        children
+            .into_iter()
+            .map(|(name, value)| self.variable(name, value))
+            .collect()
+    }
+
+    /// Evaluate `src` in stopped frame `frame` (innermost first).
+    pub fn evaluate(
+        &mut self,
+        frame: usize,
+        src: &str,
+        host: &mut dyn Host,
+    ) -> Result<Variable, String> {
+        let stopped = self.stopped.as_ref().ok_or("not stopped")?;
+        let at = stopped.frames.get(frame).ok_or("no such frame")?;
+        let value = self.evaluations.evaluate(
+            &self.index,
+            &stopped.session,
+            at.span.start,
+            &at.locals,
+            src,
+            host,
+        )?;
+        Ok(self.variable(src.trim().to_string(), value))
+    }
+
+    fn drive(&mut self, skip: usize, rule: Rule, mut run: impl Run) -> Outcome {
+        self.nodes.clear();
+        let mut controller = Controller {
+            index: &self.index,
+            breakpoints: &self.breakpoints,
+            evaluations: &mut self.evaluations,
+            stop_on_errors: self.stop_on_errors,
+            skip,
+            rule,
+            seen: 0,
+            stop: None,
+        };
+        let finished = run(&mut controller);
+        let stop = controller.stop.take();
+        match (finished, stop) {
+            (Some(Err(error)), Some(mut stopped)) => {
+                stopped.error = Some(error);
+                let reason = stopped.reason.clone();
+                self.stopped = Some(stopped);
+                Outcome::Stopped(reason)
+            }
+            (None, Some(stopped)) => {
+                let reason = stopped.reason.clone();
+                self.stopped = Some(stopped);
+                Outcome::Stopped(reason)
+            }
+            (Some(finished), _) => {
+                self.stopped = None;
+                Outcome::Finished(finished)
+            }
+            (None, None) => {
+                self.stopped = None;
+                Outcome::Finished(Err(RunError {
+                    message: "internal: the run stopped without a stop".to_string(),
+                    span: Span::new(0, 0),
+                }))
+            }
+        }
+    }
+
+    fn node(&mut self, node: Node) -> usize {
+        self.nodes.push(node);
+        self.nodes.len()
+    }
+
+    fn variable(&mut self, name: String, value: Value) -> Variable {
+        let rendered = match value.is_primitive() {
+            true => value.debug().to_string(),
+            false => value.preview(),
+        };
+        let reference = match children(&value).is_empty() {
+            true => 0,
+            false => self.node(Node::Value(value)),
+        };
+        Variable {
+            name,
+            value: rendered,
+            reference,
+        }
+    }
+}
+
+/// What a variable reference expands to.
+enum Node {
+    Locals(usize),
+    /// The values defined by the frame's file.
+    Globals(usize),
+    Value(Value),
+}
+
+/// A value's children, as named variables.
+fn children(value: &Value) -> Vec<(String, Value)> {
+    let indexed = |items: &mut dyn Iterator<Item = Value>| {
+        items
+            .enumerate()
+            .map(|(index, item)| (format!("[{index}]"), item))
+            .collect()
+    };
+    match value {
+        Value::List(items) | Value::Array(items) => indexed(&mut items.iter().cloned()),
+        Value::Tuple(items) => indexed(&mut items.iter().cloned()),
+        Value::Variant { args, .. } => indexed(&mut args.iter().cloned()),
+        Value::Record(fields) => fields.as_ref().clone(),
+        Value::Map(entries) => entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect(),
+        Value::Set(keys) => keys
+            .iter()
+            .enumerate()
+            .map(|(index, key)| (format!("[{index}]"), key.to_value()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn is_callable(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Closure(_)
+            | Value::Partial(_)
+            | Value::Builtin(_)
+            | Value::HostFn(_)
+            | Value::Ctor { .. }
+    )
+}
+
+/// How the current run stops (see the module doc).
+enum Rule {
+    /// At the first pause point at most this deep.
+    Depth(usize, StopReason),
+    /// At breakpoints only.
+    Breakpoints,
+}
+
+/// The project, indexed by source line.
+struct Index {
+    project: Rc<Project>,
+    /// The project-wide offset of every line start, ascending: a line's key
+    /// is its position here.
+    lines: Vec<usize>,
+    /// Each file's first line key, in [`crate::project::SourceMap::files`]
+    /// order.
+    first: Vec<usize>,
+    /// Keys of the lines some expression starts on (closures excepted —
+    /// building one is not a pause point).
+    code: HashSet<usize>,
+    /// Every local binding's name.
+    names: HashMap<u32, String>,
+}
+
+impl Index {
+    fn new(project: Project) -> Index {
+        let mut lines = Vec::new();
+        let mut first = Vec::new();
+        for file in project.sources.files() {
+            first.push(lines.len());
+            lines.push(file.base);
+            lines.extend(
+                file.src
+                    .bytes()
+                    .enumerate()
+                    .filter(|(_, byte)| *byte == b'\n')
+                    .map(|(at, _)| file.base + at + 1),
+            );
+        }
+        let mut index = Index {
+            project: Rc::new(project),
+            lines,
+            first,
+            code: HashSet::new(),
+            names: HashMap::new(),
+        };
+        let (mut code, mut names) = (HashSet::new(), HashMap::new());
+        for def in &index.project.module.defs {
+            index.walk(&def.value, &mut code, &mut names);
+        }
+        index.code = code;
+        index.names = names;
+        index
+    }
+
+    fn walk(&self, expr: &Expr, code: &mut HashSet<usize>, names: &mut HashMap<u32, String>) {
+        match &expr.kind {
+            ExprKind::Lambda { params, .. } => {
+                for param in params.iter() {
+                    names.insert(param.binding.0, param.name.clone());
+                }
+            }
+            ExprKind::Let { binding, name, .. }
+            | ExprKind::Local { binding, name }
+            | ExprKind::LocalMut { binding, name } => {
+                names.insert(binding.0, name.clone());
+            }
+            ExprKind::Match { arms, .. } => {
+                for arm in arms {
+                    let mut sites = Vec::new();
+                    crate::eval::pattern_binder_sites(&arm.pattern, &mut sites);
+                    for (binding, name, _) in sites {
+                        names.insert(binding.0, name.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+        if !matches!(expr.kind, ExprKind::Lambda { .. }) {
+            code.insert(self.line(expr.span.start));
+        }
+        crate::rebind::each_child(expr, &mut |child| self.walk(child, code, names));
+    }
+
+    /// The key of the line holding project-wide `offset`.
+    fn line(&self, offset: usize) -> usize {
+        self.lines
+            .partition_point(|start| *start <= offset)
+            .saturating_sub(1)
+    }
+
+    /// The key of 1-based `line` of file `file`, if it has that line.
+    fn key(&self, file: usize, line: usize) -> Option<usize> {
+        let end = self
+            .first
+            .get(file + 1)
+            .copied()
+            .unwrap_or(self.lines.len());
+        let key = self.first[file] + line.checked_sub(1)?;
+        (key < end).then_some(key)
+    }
+
+    /// The index of the file at `path` (see [`Debugger::set_breakpoints`]).
+    fn file(&self, path: &Path) -> Option<usize> {
+        let files = self.project.sources.files();
+        if let Some(file) = self.project.sources.file_by_path(path) {
+            return files
+                .iter()
+                .position(|candidate| candidate.base == file.base);
+        }
+        let name = path.file_name()?;
+        let mut named = files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| file.path.file_name() == Some(name));
+        match (named.next(), named.next()) {
+            (Some((index, _)), None) => Some(index),
+            _ => None,
+        }
+    }
+
+    /// The names of `locals` usable as parameters: the deduplicated,
+    /// identifier-shaped ones (lowering can introduce others).
+    fn named(&self, locals: Vec<(crate::ir::BindingId, Value)>) -> Vec<(String, Value)> {
+        let mut seen = HashSet::new();
+        let mut named: Vec<(String, Value)> = locals
+            .into_iter()
+            .filter_map(|(binding, value)| Some((self.names.get(&binding.0)?.clone(), value)))
+            .filter(|(name, _)| is_identifier(name) && seen.insert(name.clone()))
+            .collect();
+        named.reverse();
+        named
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_lowercase() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Expressions evaluated in frames, linked once per (file, source,
+/// parameters) — a conditional breakpoint in a loop links once.
+#[derive(Default)]
+struct Evaluations {
+    linked: HashMap<(usize, String, Vec<String>), Rc<Linked>>,
+}
+
+impl Evaluations {
+    /// Evaluate `src` as if written at project-wide `at`, over `locals`.
+    fn evaluate(
+        &mut self,
+        index: &Index,
+        session: &Session,
+        at: usize,
+        locals: &[(String, Value)],
+        src: &str,
+        host: &mut dyn Host,
+    ) -> Result<Value, String> {
+        let files = index.project.sources.files();
+        let mut file = files.iter().rposition(|file| file.base <= at).unwrap_or(0);
+        if files[file].interface {
+            file = 0;
+        }
+        let params: Vec<String> = locals.iter().map(|(name, _)| name.clone()).collect();
+        let key = (file, src.trim().to_string(), params);
+        let next = self.linked.len();
+        let linked = self
+            .linked
+            .entry(key)
+            .or_insert_with_key(|(file, src, params)| {
+                Rc::new(link(&index.project, *file, src, params, next))
+            })
+            .clone();
+        let (project, name) = linked.as_ref().as_ref().map_err(Clone::clone)?;
+        let args = locals.iter().map(|(_, value)| value.clone()).collect();
+        session
+            .evaluate(&project.module, name, args, host)
+            .map_err(|error| error.message)
+    }
+}
+
+/// A linked evaluation: the project with the def, and the def's name.
+type Linked = Result<(Project, String), String>;
+
+/// `project` with `src` appended to file `file` as a fresh def — a function
+/// of `params` when there are any — and that def's canonical name.
+fn link(
+    project: &Project,
+    file: usize,
+    src: &str,
+    params: &[String],
+    serial: usize,
+) -> Linked {
+    if src.is_empty() {
+        return Err("nothing to evaluate".to_string());
+    }
+    let fresh = format!("debugEval{serial}");
+    let def = match params.is_empty() {
+        true => format!("\nlet {fresh} = ({src})\n"),
+        false => format!("\nlet {fresh} = ({}) => ({src})\n", params.join(", ")),
+    };
+    let project = project
+        .relink(|index, source| {
+            let mut src = source.src.clone();
+            if index == file {
+                src.push_str(&def);
+            }
+            (source.path.clone(), source.module.clone(), src)
+        })
+        .map_err(|error| error.message)?;
+    let suffix = format!(".{fresh}");
+    let name = project
+        .module
+        .defs
+        .iter()
+        .map(|def| &def.name)
+        .find(|name| **name == fresh || name.ends_with(&suffix))
+        .cloned()
+        .ok_or_else(|| format!("internal: `{fresh}` did not link"))?;
+    Ok((project, name))
+}
+
+/// The [`DebugHook`] of one run: counts pause points, applies the step rule
+/// and the breakpoints, and snapshots the stop.
+struct Controller<'d> {
+    index: &'d Index,
+    breakpoints: &'d HashMap<usize, Option<String>>,
+    evaluations: &'d mut Evaluations,
+    stop_on_errors: bool,
+    /// Pause points before this ordinal were seen by an earlier run.
+    skip: usize,
+    rule: Rule,
+    seen: usize,
+    stop: Option<Stopped>,
+}
+
+impl Controller<'_> {
+    fn snapshot(&self, paused: &Paused, reason: StopReason, ordinal: usize) -> Stopped {
+        let frames = paused
+            .frames()
+            .into_iter()
+            .map(|(name, span, locals)| StackFrame {
+                name: name.to_string(),
+                span,
+                locals: self.index.named(locals),
+            })
+            .collect();
+        Stopped {
+            reason,
+            frames,
+            ordinal,
+            session: paused.session(),
+            error: None,
+        }
+    }
+
+    /// Does the breakpoint at `key` stop here?
+    fn hits(&mut self, paused: &mut Paused, key: usize) -> Option<StopReason> {
+        let condition = self.breakpoints.get(&key)?;
+        let Some(condition) = condition else {
+            return Some(StopReason::Breakpoint);
+        };
+        let locals = match paused.frames().into_iter().next() {
+            Some((_, _, locals)) => self.index.named(locals),
+            None => Vec::new(),
+        };
+        let session = paused.session();
+        let value = self.evaluations.evaluate(
+            self.index,
+            &session,
+            paused.span().start,
+            &locals,
+            condition,
+            paused.host(),
+        );
+        match value {
+            Ok(Value::Bool(true)) => Some(StopReason::Breakpoint),
+            Ok(Value::Bool(false)) => None,
+            Ok(other) => Some(StopReason::Condition(format!(
+                "condition `{condition}` is {}, not a bool",
+                other.kind_name()
+            ))),
+            Err(message) => Some(StopReason::Condition(format!(
+                "condition `{condition}`: {message}"
+            ))),
+        }
+    }
+}
+
+impl DebugHook for Controller<'_> {
+    fn line(&self, offset: usize) -> usize {
+        self.index.line(offset)
+    }
+
+    fn pause(&mut self, paused: &mut Paused<'_, '_>) -> Resume {
+        let ordinal = self.seen;
+        self.seen += 1;
+        if ordinal < self.skip {
+            return Resume::Continue;
+        }
+        let reason = match &self.rule {
+            Rule::Depth(depth, reason) if paused.depth() <= *depth => Some(reason.clone()),
+            _ => self.hits(paused, self.index.line(paused.span().start)),
+        };
+        match reason {
+            Some(reason) => {
+                self.stop = Some(self.snapshot(paused, reason, ordinal));
+                Resume::Stop
+            }
+            None => Resume::Continue,
+        }
+    }
+
+    fn fault(&mut self, paused: &mut Paused<'_, '_>, error: &RunError) {
+        if self.stop_on_errors && self.stop.is_none() {
+            let reason = StopReason::Error(error.message.clone());
+            self.stop = Some(self.snapshot(paused, reason, self.seen));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NoHost;
+    use std::path::PathBuf;
+
+    const GAME: &str = "let double = (n) =>\n\
+\x20 let twice = n * 2.0 in\n\
+\x20 twice\n\
+\n\
+let total = (xs) =>\n\
+\x20 xs |> List.fold((acc, x) =>\n\
+\x20   acc + double(x), 0.0)\n\
+\n\
+let main = () =>\n\
+\x20 let result = total([1.0, 2.0, 3.0]) in\n\
+\x20 result\n";
+
+    fn debugger(src: &str) -> Debugger {
+        let sources = vec![(PathBuf::from("game.fun"), src.to_string())];
+        let project = crate::project::load_sources_with_prelude(sources, &[])
+            .unwrap_or_else(|e| panic!("{}", e.message));
+        Debugger::new(project)
+    }
+
+    /// The launch run: the program, under no host.
+    fn program(debugger: &Debugger) -> impl Run {
+        let project = debugger.project();
+        move |hook: &mut dyn DebugHook| run_program(&project.module, &mut NoHost, hook)
+    }
+
+    fn breakpoint(line: usize, condition: Option<&str>) -> Breakpoint {
+        Breakpoint {
+            line,
+            condition: condition.map(str::to_string),
+        }
+    }
+
+    /// The innermost frame as `name@line`.
+    fn at(debugger: &Debugger) -> String {
+        let frame = &debugger.stopped().expect("stopped").frames[0];
+        let (_, line, _) = debugger.locate(frame.span.start);
+        format!("{}@{line}", frame.name)
+    }
+
+    fn local(debugger: &Debugger, name: &str) -> String {
+        let frame = &debugger.stopped().expect("stopped").frames[0];
+        let (_, value) = frame
+            .locals
+            .iter()
+            .find(|(local, _)| local == name)
+            .unwrap_or_else(|| panic!("no local `{name}` in {:?}", locals(debugger)));
+        value.to_string()
+    }
+
+    fn locals(debugger: &Debugger) -> Vec<String> {
+        let frame = &debugger.stopped().expect("stopped").frames[0];
+        frame.locals.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    fn finished(outcome: Outcome) -> String {
+        match outcome {
+            Outcome::Finished(Ok(Some(value))) => value.to_string(),
+            Outcome::Finished(Ok(None)) => "<none>".to_string(),
+            Outcome::Finished(Err(error)) => format!("error: {}", error.message),
+            Outcome::Stopped(reason) => panic!("stopped: {reason:?}"),
+        }
+    }
+
+    #[test]
+    fn a_breakpoint_stops_with_the_frame_and_its_locals() {
+        let mut debugger = debugger(GAME);
+        assert_eq!(
+            debugger.set_breakpoints(
+                Path::new("game.fun"),
+                &[breakpoint(3, None), breakpoint(4, None)]
+            ),
+            vec![true, false],
+            "line 4 is blank"
+        );
+        let run = program(&debugger);
+        assert!(matches!(
+            debugger.start(false, run),
+            Outcome::Stopped(StopReason::Breakpoint)
+        ));
+        assert_eq!(at(&debugger), "double@3");
+        assert_eq!(locals(&debugger), ["n", "twice"]);
+        assert_eq!(local(&debugger, "twice"), "2");
+        let names: Vec<&str> = debugger
+            .stopped()
+            .unwrap()
+            .frames
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect();
+        assert_eq!(names, ["double", "List.fold[0]", "total", "main"]);
+
+        // The next call hits it again, with the next element.
+        let run = program(&debugger);
+        assert!(matches!(
+            debugger.resume(Step::Continue, run),
+            Outcome::Stopped(_)
+        ));
+        assert_eq!(local(&debugger, "n"), "2");
+        debugger.set_breakpoints(Path::new("game.fun"), &[]);
+        let run = program(&debugger);
+        assert_eq!(finished(debugger.resume(Step::Continue, run)), "12");
+    }
+
+    #[test]
+    fn stepping_in_over_and_out_follow_the_call_depth() {
+        let mut debugger = debugger(GAME);
+        debugger.set_breakpoints(Path::new("game.fun"), &[breakpoint(10, None)]);
+        let run = program(&debugger);
+        debugger.start(false, run);
+        assert_eq!(at(&debugger), "main@10");
+
+        let run = program(&debugger);
+        debugger.resume(Step::In, run);
+        assert_eq!(at(&debugger), "total@6");
+        let run = program(&debugger);
+        debugger.resume(Step::Out, run);
+        assert_eq!(at(&debugger), "main@11");
+        assert_eq!(local(&debugger, "result"), "12");
+
+        // Over from the call line never enters `total`.
+        let run = program(&debugger);
+        debugger.start(false, run);
+        let run = program(&debugger);
+        debugger.resume(Step::Over, run);
+        assert_eq!(at(&debugger), "main@11");
+    }
+
+    #[test]
+    fn stop_on_entry_stops_at_the_first_pause_point() {
+        let mut debugger = debugger("let base = 40.0\nlet main = () => base + 2.0\n");
+        let run = program(&debugger);
+        assert!(matches!(
+            debugger.start(true, run),
+            Outcome::Stopped(StopReason::Entry)
+        ));
+        assert_eq!(at(&debugger), "base@1");
+        let run = program(&debugger);
+        debugger.resume(Step::Over, run);
+        assert_eq!(at(&debugger), "main@2");
+        let run = program(&debugger);
+        assert_eq!(finished(debugger.resume(Step::Over, run)), "42");
+    }
+
+    #[test]
+    fn a_conditional_breakpoint_stops_only_when_its_condition_holds() {
+        let mut debugger = debugger(GAME);
+        debugger.set_breakpoints(Path::new("game.fun"), &[breakpoint(2, Some("n > 2.0"))]);
+        let run = program(&debugger);
+        debugger.start(false, run);
+        assert_eq!(local(&debugger, "n"), "3");
+
+        // A condition that cannot evaluate stops and says why.
+        debugger.set_breakpoints(Path::new("game.fun"), &[breakpoint(2, Some("n + 1.0"))]);
+        let run = program(&debugger);
+        match debugger.start(false, run) {
+            Outcome::Stopped(StopReason::Condition(message)) => {
+                assert!(message.contains("not a bool"), "{message}")
+            }
+            other => panic!("{:?}", matches!(other, Outcome::Finished(_))),
+        }
+    }
+
+    #[test]
+    fn evaluate_sees_the_frame_locals_and_the_globals() {
+        let mut debugger = debugger(GAME);
+        debugger.set_breakpoints(Path::new("game.fun"), &[breakpoint(7, None)]);
+        let run = program(&debugger);
+        debugger.start(false, run);
+        // `total` evaluates the fold's `0.0` on that line before any call.
+        assert_eq!(at(&debugger), "total@7");
+        let run = program(&debugger);
+        debugger.resume(Step::Continue, run);
+        assert_eq!(at(&debugger), "List.fold[0]@7");
+        let value = debugger
+            .evaluate(0, "acc + double(x) + List.length(xs)", &mut NoHost)
+            .expect("evaluates");
+        assert_eq!(value.value, "5");
+        let error = debugger
+            .evaluate(0, "missing + 1.0", &mut NoHost)
+            .unwrap_err();
+        assert!(error.contains("missing"), "{error}");
+    }
+
+    #[test]
+    fn variables_expand_structured_values() {
+        let mut debugger = debugger(
+            "let main = () =>\n  let player = { name: \"p1\", at: (1.0, 2.0) } in\n  player\n",
+        );
+        debugger.set_breakpoints(Path::new("game.fun"), &[breakpoint(3, None)]);
+        let run = program(&debugger);
+        debugger.start(false, run);
+        let scopes = debugger.scopes(0);
+        assert_eq!(scopes[0].name, "Locals");
+        let locals = debugger.variables(scopes[0].reference);
+        assert_eq!(locals[0].name, "player");
+        assert_ne!(locals[0].reference, 0);
+        let fields = debugger.variables(locals[0].reference);
+        let rendered: Vec<(&str, &str)> = fields
+            .iter()
+            .map(|v| (v.name.as_str(), v.value.as_str()))
+            .collect();
+        assert_eq!(rendered, [("name", "\"p1\""), ("at", "(1, 2)")]);
+        let at = debugger.variables(fields[1].reference);
+        assert_eq!(at[1].name, "[1]");
+        assert_eq!(at[1].value, "2");
+    }
+
+    #[test]
+    fn a_runtime_error_stops_at_its_frame_then_finishes_with_it() {
+        let mut debugger = debugger("let main = () =>\n  let xs = [1.0] in\n  xs(2.0)\n");
+        let run = program(&debugger);
+        match debugger.start(false, run) {
+            Outcome::Stopped(StopReason::Error(message)) => {
+                assert!(message.contains("cannot call"), "{message}")
+            }
+            _ => panic!("expected an error stop"),
+        }
+        assert_eq!(at(&debugger), "main@3");
+        assert_eq!(local(&debugger, "xs"), "[1]");
+        let run = program(&debugger);
+        assert!(finished(debugger.resume(Step::Continue, run)).contains("cannot call"));
+
+        debugger.set_stop_on_errors(false);
+        let run = program(&debugger);
+        assert!(finished(debugger.start(false, run)).contains("cannot call"));
+    }
+}
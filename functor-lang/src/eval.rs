@@ -1,4 +1,4 @@
-//! Tree-walking interpreter over the core IR — Track B3 of `docs/functor-lang.md`.
+//! Interpreter over the core IR — Track B3 of `docs/functor-lang.md`.
 //!
 //! ## Program semantics
 //!
@@ -15,6 +15,24 @@
 //! it and reports its result; otherwise the outcome is the list of top-level
 //! bindings.
 //!
+//! ## Compiled closures
+//!
+//! Loading a module compiles every lambda body to slot-resolved bytecode
+//! ([`crate::bytecode`]), and calling a closure runs that code on a small
+//! stack VM ([`Interp::run_code`]) — locals are frame slots, globals are
+//! table slots, and a nested expression costs no host stack. Top-level
+//! initializers and `expect` expressions run once and are tree-walked, as is
+//! a closure whose body is not the one its module compiled (a value kept
+//! across a reload). Both evaluators share the call path, builtins, and every
+//! error message, so the choice is invisible to a program and to tooling:
+//! tracing hooks the shared call path, and the recorder's binder, reference,
+//! and coverage sites are compiled into the code.
+//!
+//! A call in tail position that saturates a compiled closure replaces the
+//! running frame instead of nesting one ([`Op::TailCall`]), so self- and
+//! mutual-tail recursion runs in constant host stack. Each such logical call
+//! still charges a step, records its params, and traces an enter/exit pair.
+//!
 //! ## Externals
 //!
 //! [`ExprKind::External`] names resolve against the builtin registry at
@@ -29,23 +47,32 @@
 //! LLM-readable execution story (`functor-lang trace`). Recording stops (with a
 //! marker event) after [`MAX_TRACE_EVENTS`] so a hot loop can't produce an
 //! unbounded transcript; evaluation itself continues.
+//!
+//! A [`crate::profile::Profiler`] armed on a [`Session`] hears the same
+//! enter/exit pairs — failed calls included — as names and timestamps only.
 
+use crate::ast::ExpectKind;
+use crate::bytecode::{compile, Code, CodeTable, Op};
 use crate::ir::{
     BindingId, Def, ExpectDef, Expr, ExprKind, Module, Pattern, PatternKind, StringPart,
 };
+use crate::profile::{CallKind, Profiler};
 use crate::span::Span;
-use crate::value::{canonicalize_map_entries, Closure, Env, MapKey, Value};
+use crate::value::{canonicalize_map_entries, Closure, Env, List, Map, MapKey, Set, Value};
 use crate::RunError;
-use std::cmp::Ordering;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::Write;
 use std::rc::Rc;
 
 /// Evaluation depth cap: pathological or unboundedly recursive Functor Lang code must
-/// fail as a clean spanned error, not a host stack overflow. Counts *every*
-/// nested `eval` entry (expression nesting and calls alike), so it bounds
-/// host stack usage directly. Deep iteration belongs in the iterative builtins
-/// (`List.map`/`fold`), not user-level recursion.
+/// fail as a clean spanned error, not a host stack overflow. Counts every
+/// nested tree-walked `eval` entry (expression nesting and calls alike) and
+/// every compiled frame (one per call — the VM's expression nesting lives on
+/// its value stack), so it bounds host stack usage directly. A compiled tail
+/// call reuses its caller's frame and consumes no depth; other deep
+/// iteration belongs in the iterative builtins (`List.map`/`fold`), not
+/// non-tail user-level recursion.
 ///
 /// INVARIANT: the cap must trip *before* a default 2 MiB test-thread stack is
 /// exhausted in a debug build, so `cargo test -p mle` needs no `RUST_MIN_STACK`
@@ -275,9 +302,9 @@ impl Recorder {
             slot.count += 1;
             // Numeric loop sites fold their range; a non-numeric value at a
             // previously-numeric site (a union-typed binder) drops it.
-            if let Value::Number(n) = value {
-                slot.min = Some(slot.min.map_or(*n, |m| m.min(*n)));
-                slot.max = Some(slot.max.map_or(*n, |m| m.max(*n)));
+            if let Some(n) = recorded_number(value) {
+                slot.min = Some(slot.min.map_or(n, |m| m.min(n)));
+                slot.max = Some(slot.max.map_or(n, |m| m.max(n)));
             } else {
                 slot.min = None;
                 slot.max = None;
@@ -294,10 +321,7 @@ impl Recorder {
         }
         *sites += 1;
         self.index.insert(key, self.bindings.len());
-        let n = match value {
-            Value::Number(n) => Some(*n),
-            _ => None,
-        };
+        let n = recorded_number(value);
         self.bindings.push(RecordedBinding {
             name: name.to_string(),
             span,
@@ -312,6 +336,16 @@ impl Recorder {
     }
 }
 
+/// The number a loop site's range folds, for either numeric kind (an int's
+/// range is only displayed, so `f64` is exact enough).
+fn recorded_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => Some(*n),
+        Value::Int(n) => Some(*n as f64),
+        _ => None,
+    }
+}
+
 fn recorded_kind(value: &Value) -> RecordedKind {
     if value.is_primitive() {
         RecordedKind::Primitive
@@ -362,8 +396,11 @@ pub fn run_with_host(
     tracing: Tracing,
     host: &mut dyn Host,
 ) -> Result<RunRecord, RunFailure> {
+    let globals = Globals::for_module(module);
     let mut interp = Interp {
-        globals: HashMap::new(),
+        codes: Rc::new(compile(module, &globals.slots)),
+        globals: Rc::new(globals),
+        stack: Vec::new(),
         mut_slots: HashMap::new(),
         trace: Vec::new(),
         tracing,
@@ -373,6 +410,8 @@ pub fn run_with_host(
         fuel: None,
         brand_ops: BrandOps::default(),
         brand_ops_complete: true,
+        debug: None,
+        profiler: None,
         host,
     };
     match interp.run_module(module) {
@@ -394,6 +433,13 @@ pub fn run_with_host(
 pub struct ExpectReport {
     pub span: Span,
     pub outcome: ExpectOutcome,
+    /// An `expectSnapshot`'s fresh dump (`None` for other tests, and for a
+    /// snapshot whose expression errored) — what [`crate::snapshot::save`]
+    /// records.
+    pub snapshot: Option<crate::snapshot::Json>,
+    /// Wall-clock time the test took (a `property`'s spans all its cases and
+    /// shrinking). `None` where there is no clock (wasm32).
+    pub duration: Option<std::time::Duration>,
 }
 
 #[derive(Debug)]
@@ -405,6 +451,12 @@ pub enum ExpectOutcome {
     /// Evaluation failed (a spanned runtime error), or the expression did not
     /// produce a bool (unchecked code — `check` catches this statically).
     Error(RunError),
+    /// A `property` failed for some generated input: the shrunk
+    /// counterexample, with the seed that replays it.
+    Falsified(Box<Counterexample>),
+    /// An `expectSnapshot` value no longer matches its stored dump: where
+    /// the two differ, structurally.
+    Changed(Box<crate::snapshot::SnapshotDiff>),
 }
 
 impl ExpectOutcome {
@@ -432,14 +484,57 @@ impl ExpectOutcome {
                 )),
             ),
             ExpectOutcome::Error(error) => ("error", Some(error.message.clone())),
+            ExpectOutcome::Falsified(counterexample) => ("fail", Some(counterexample.to_string())),
+            ExpectOutcome::Changed(diff) => ("fail", Some(diff.to_string())),
         }
     }
 }
 
+/// A falsified `property`: the simplest failing input found, how it failed,
+/// and what reproduces it — rerunning with the same `seed` (and case count)
+/// generates the same cases.
+#[derive(Debug)]
+pub struct Counterexample {
+    pub seed: u64,
+    /// Which generated case (1-based) failed first.
+    pub case: u32,
+    /// How many simplifications shrinking kept.
+    pub shrinks: u32,
+    /// The shrunk inputs, by parameter name, rendered like [`FailedCompare`]'s
+    /// sides.
+    pub inputs: Vec<(String, String)>,
+    /// How the shrunk inputs fail: [`ExpectOutcome::Fail`] or
+    /// [`ExpectOutcome::Error`] (a runtime error is a failure too).
+    pub failure: ExpectOutcome,
+}
+
+impl std::fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let inputs: Vec<String> = self
+            .inputs
+            .iter()
+            .map(|(name, value)| format!("{name} = {value}"))
+            .collect();
+        let plural = if self.shrinks == 1 { "" } else { "s" };
+        write!(
+            f,
+            "falsified by {} (seed {}, case {}, {} shrink{plural})",
+            inputs.join(", "),
+            self.seed,
+            self.case,
+            self.shrinks
+        )?;
+        if let (_, Some(detail)) = self.failure.status() {
+            write!(f, ": {detail}")?;
+        }
+        Ok(())
+    }
+}
+
 /// The sides of a failed top-level comparison: `expect a == b` reports what
 /// `a` and `b` actually were (rendered with [`Value`]'s deterministic
 /// `Display`).
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct FailedCompare {
     pub op: &'static str,
     pub lhs: String,
@@ -490,17 +585,97 @@ pub fn run_expects_budgeted(
     host: &mut dyn Host,
     budget: Option<u64>,
 ) -> Result<Vec<ExpectReport>, RunFailure> {
+    let options = ExpectOptions {
+        budget,
+        ..ExpectOptions::default()
+    };
+    run_expects_with(module, host, &options)
+}
+
+/// How [`run_expects_with`] runs a module's tests.
+#[derive(Clone, Debug)]
+pub struct ExpectOptions {
+    /// The per-phase step budget (see [`run_expects_budgeted`]). A `property`
+    /// spends it per CASE — every generated case and every shrink attempt
+    /// starts from a full budget, exactly like one `expect`.
+    pub budget: Option<u64>,
+    /// Generated cases per `property`.
+    pub cases: u32,
+    /// The seed `property` inputs are generated from (each property replays
+    /// the same stream). A falsified property reports it.
+    pub seed: u64,
+    /// The stored dumps `expectSnapshot` tests compare against (see
+    /// [`crate::snapshot::load`]). Empty by default: every snapshot passes
+    /// as new.
+    pub snapshots: crate::snapshot::Goldens,
+    /// The tests to run, by their span's start offset (`functor test
+    /// --filter`/`--module`). `None` runs every test; an unselected test
+    /// produces no report.
+    pub selected: Option<std::collections::HashSet<usize>>,
+}
+
+impl Default for ExpectOptions {
+    fn default() -> ExpectOptions {
+        ExpectOptions {
+            budget: None,
+            cases: crate::property::DEFAULT_CASES,
+            seed: crate::property::DEFAULT_SEED,
+            snapshots: crate::snapshot::Goldens::default(),
+            selected: None,
+        }
+    }
+}
+
+/// [`run_expects_budgeted`] with every knob: the budget, and how `property`
+/// tests sample — `options.cases` seeded cases each, growing in size, with
+/// the first failure shrunk to a minimal [`Counterexample`].
+pub fn run_expects_with(
+    module: &Module,
+    host: &mut dyn Host,
+    options: &ExpectOptions,
+) -> Result<Vec<ExpectReport>, RunFailure> {
+    run_expects_recording(module, host, options, None).map(|(reports, _)| reports)
+}
+
+/// [`run_expects_with`], also returning the execution-coverage set: the
+/// sorted span starts of every expression the def load and the tests
+/// evaluated (`functor test --coverage`; pair it with
+/// [`crate::coverage::runnable_offsets`]). Costs a set insert per
+/// evaluation, so only coverage runs arm it.
+pub fn run_expects_covered(
+    module: &Module,
+    host: &mut dyn Host,
+    options: &ExpectOptions,
+) -> Result<(Vec<ExpectReport>, Vec<usize>), RunFailure> {
+    let (reports, recorder) =
+        run_expects_recording(module, host, options, Some(Recorder::new(false)))?;
+    let recorder = recorder.expect("armed above");
+    Ok((reports, recorder.coverage_sorted()))
+}
+
+fn run_expects_recording(
+    module: &Module,
+    host: &mut dyn Host,
+    options: &ExpectOptions,
+    recorder: Option<Recorder>,
+) -> Result<(Vec<ExpectReport>, Option<Recorder>), RunFailure> {
+    let budget = options.budget;
+    let globals = Globals::for_module(module);
     let mut interp = Interp {
-        globals: HashMap::new(),
+        codes: Rc::new(compile(module, &globals.slots)),
+        globals: Rc::new(globals),
+        stack: Vec::new(),
         mut_slots: HashMap::new(),
         trace: Vec::new(),
         tracing: Tracing::Off,
-        recorder: None,
+        recorder,
         depth: 0,
         call_depth: 0,
         fuel: budget.map(Fuel::new),
         brand_ops: BrandOps::default(),
         brand_ops_complete: true,
+        debug: None,
+        profiler: None,
         host,
     };
     if let Err(error) = interp
@@ -513,28 +688,71 @@ pub fn run_expects_budgeted(
             trace: interp.trace,
         });
     }
-    Ok(module
+    let mut generators = None;
+    let reports = module
         .expects
         .iter()
+        .filter(|expect| {
+            let selected = options.selected.as_ref();
+            selected.is_none_or(|starts| starts.contains(&expect.span.start))
+        })
         .map(|expect| {
             interp.fuel = budget.map(Fuel::new);
+            let started = Clock::start();
+            let (outcome, snapshot) = match &expect.kind {
+                ExpectKind::Example => (interp.eval_expect(expect), None),
+                ExpectKind::Property => {
+                    let generators =
+                        generators.get_or_insert_with(|| crate::property::Generators::new(module));
+                    (interp.eval_property(expect, generators, options), None)
+                }
+                ExpectKind::Snapshot(name) => {
+                    interp.eval_snapshot(expect, name, &options.snapshots)
+                }
+            };
             ExpectReport {
                 span: expect.span,
-                outcome: interp.eval_expect(expect),
+                outcome,
+                snapshot,
+                duration: started.elapsed(),
             }
         })
-        .collect())
+        .collect();
+    Ok((reports, interp.recorder))
+}
+
+/// A test's start time for [`ExpectReport::duration`] — `Instant` panics on
+/// wasm32, so there the clock is absent.
+struct Clock(#[cfg(not(target_arch = "wasm32"))] std::time::Instant);
+
+impl Clock {
+    fn start() -> Clock {
+        Clock(
+            #[cfg(not(target_arch = "wasm32"))]
+            std::time::Instant::now(),
+        )
+    }
+
+    fn elapsed(&self) -> Option<std::time::Duration> {
+        #[cfg(not(target_arch = "wasm32"))]
+        return Some(self.0.elapsed());
+        #[cfg(target_arch = "wasm32")]
+        None
+    }
 }
 
 /// A persistent interpreter session for embedding (the C2 producer): load a
 /// module once, then call top-level functions per frame. Globals are
-/// evaluated at load; each `call` runs with a fresh interpreter over the
-/// session's globals (Rc-cheap clones), so per-frame state lives entirely in
+/// evaluated and every lambda compiled at load; each `call` runs with a fresh
+/// interpreter sharing the session's global table and code (Rc-cheap, no
+/// per-frame copy), so per-frame state lives entirely in
 /// the VALUES passed in and returned — the model stays data (hot-reload can
 /// swap the session and keep the model; docs/functor-lang.md C3).
 pub struct Session {
-    globals: HashMap<String, Value>,
+    globals: Rc<Globals>,
     brand_ops: BrandOps,
+    codes: Rc<CodeTable>,
+    profiler: Option<Rc<RefCell<Profiler>>>,
 }
 
 /// Declared unit operators, resolved to callable VALUES and keyed by the
@@ -599,8 +817,11 @@ impl Session {
     /// session. Unlike [`run`], a `main` def is NOT called — loading a game
     /// must not execute anything beyond its initializers.
     pub fn load(module: &Module, host: &mut dyn Host) -> Result<Session, RunFailure> {
+        let globals = Globals::for_module(module);
         let mut interp = Interp {
-            globals: HashMap::new(),
+            codes: Rc::new(compile(module, &globals.slots)),
+            globals: Rc::new(globals),
+            stack: Vec::new(),
             mut_slots: HashMap::new(),
             trace: Vec::new(),
             tracing: Tracing::Off,
@@ -610,6 +831,8 @@ impl Session {
             fuel: None,
             brand_ops: BrandOps::default(),
             brand_ops_complete: true,
+            debug: None,
+            profiler: None,
             host,
         };
         let loaded = interp
@@ -620,6 +843,8 @@ impl Session {
             Ok(()) => Ok(Session {
                 brand_ops: interp.brand_ops.clone(),
                 globals: interp.globals,
+                codes: interp.codes,
+                profiler: None,
             }),
             Err(error) => Err(RunFailure {
                 error,
@@ -630,7 +855,15 @@ impl Session {
 
     /// The value of a top-level def, if any.
     pub fn global(&self, name: &str) -> Option<Value> {
-        self.globals.get(name).cloned()
+        self.globals.get(name)
+    }
+
+    /// Arm (or, with `None`, disarm) the hook profiler: every later
+    /// [`Self::call`] and [`Self::apply`] reports its calls to it (see
+    /// [`crate::profile`]). Recorded and debugged calls are replays of work
+    /// already profiled, so they stay out of it.
+    pub fn set_profiler(&mut self, profiler: Option<Rc<RefCell<Profiler>>>) {
+        self.profiler = profiler;
     }
 
     /// Call the top-level function `name` with `args`. `span` 0..0 is used
@@ -641,12 +874,14 @@ impl Session {
         args: Vec<Value>,
         host: &mut dyn Host,
     ) -> Result<Value, RunError> {
-        let callee = self.globals.get(name).cloned().ok_or_else(|| RunError {
+        let callee = self.globals.get(name).ok_or_else(|| RunError {
             message: format!("no top-level `let {name}` in the module"),
             span: Span::new(0, 0),
         })?;
         let mut interp = Interp {
             globals: self.globals.clone(),
+            codes: self.codes.clone(),
+            stack: Vec::new(),
             mut_slots: HashMap::new(),
             trace: Vec::new(),
             tracing: Tracing::Off,
@@ -656,6 +891,8 @@ impl Session {
             fuel: None,
             brand_ops: self.brand_ops.clone(),
             brand_ops_complete: true,
+            debug: None,
+            profiler: self.profiler.clone(),
             host,
         };
         interp.call(callee, args, name.to_string(), Span::new(0, 0), None)
@@ -673,6 +910,8 @@ impl Session {
     ) -> Result<Value, RunError> {
         let mut interp = Interp {
             globals: self.globals.clone(),
+            codes: self.codes.clone(),
+            stack: Vec::new(),
             mut_slots: HashMap::new(),
             trace: Vec::new(),
             tracing: Tracing::Off,
@@ -682,6 +921,8 @@ impl Session {
             fuel: None,
             brand_ops: self.brand_ops.clone(),
             brand_ops_complete: true,
+            debug: None,
+            profiler: self.profiler.clone(),
             host,
         };
         interp.call(callee, args, label.to_string(), Span::new(0, 0), None)
@@ -700,12 +941,14 @@ impl Session {
         args: Vec<Value>,
         host: &mut dyn Host,
     ) -> Result<(Value, RecordedInvocation), RunError> {
-        let callee = self.globals.get(name).cloned().ok_or_else(|| RunError {
+        let callee = self.globals.get(name).ok_or_else(|| RunError {
             message: format!("no top-level `let {name}` in the module"),
             span: Span::new(0, 0),
         })?;
         let mut interp = Interp {
             globals: self.globals.clone(),
+            codes: self.codes.clone(),
+            stack: Vec::new(),
             mut_slots: HashMap::new(),
             trace: Vec::new(),
             tracing: Tracing::Off,
@@ -715,6 +958,8 @@ impl Session {
             fuel: None,
             brand_ops: self.brand_ops.clone(),
             brand_ops_complete: true,
+            debug: None,
+            profiler: None,
             host,
         };
         let result = interp.call(callee, args, name.to_string(), Span::new(0, 0), None)?;
@@ -731,6 +976,142 @@ impl Session {
         Ok((result, invocation))
     }
 
+    /// [`Self::load`] under a debugger (see [`DebugHook`]): each top-level
+    /// initializer runs in a frame of its own. `None` when the hook stopped
+    /// the load.
+    pub fn load_debugged(
+        module: &Module,
+        host: &mut dyn Host,
+        hook: &mut dyn DebugHook,
+    ) -> Option<Result<Session, RunFailure>> {
+        let globals = Globals::for_module(module);
+        let mut interp = Interp {
+            codes: Rc::new(compile(module, &globals.slots)),
+            globals: Rc::new(globals),
+            stack: Vec::new(),
+            mut_slots: HashMap::new(),
+            trace: Vec::new(),
+            tracing: Tracing::Off,
+            recorder: None,
+            depth: 0,
+            call_depth: 0,
+            fuel: None,
+            brand_ops: BrandOps::default(),
+            brand_ops_complete: true,
+            debug: Some(Debugging::new(hook)),
+            profiler: None,
+            host,
+        };
+        let loaded = interp
+            .load_unit_operators(module)
+            .and_then(|_| interp.eval_defs(module))
+            .and_then(|_| interp.settle_unit_operators(module));
+        if interp.debug.as_ref().is_some_and(|debug| debug.stopped) {
+            return None;
+        }
+        Some(match loaded {
+            Ok(()) => Ok(Session {
+                brand_ops: interp.brand_ops.clone(),
+                globals: interp.globals,
+                codes: interp.codes,
+                profiler: None,
+            }),
+            Err(error) => Err(RunFailure {
+                error,
+                trace: interp.trace,
+            }),
+        })
+    }
+
+    /// [`Self::call`] under a debugger (see [`DebugHook`]). `None` when the
+    /// hook stopped the call.
+    pub fn call_debugged(
+        &self,
+        name: &str,
+        args: Vec<Value>,
+        host: &mut dyn Host,
+        hook: &mut dyn DebugHook,
+    ) -> Option<Result<Value, RunError>> {
+        let Some(callee) = self.globals.get(name) else {
+            return Some(Err(RunError {
+                message: format!("no top-level `let {name}` in the module"),
+                span: Span::new(0, 0),
+            }));
+        };
+        let mut interp = Interp {
+            globals: self.globals.clone(),
+            codes: self.codes.clone(),
+            stack: Vec::new(),
+            mut_slots: HashMap::new(),
+            trace: Vec::new(),
+            tracing: Tracing::Off,
+            recorder: None,
+            depth: 0,
+            call_depth: 0,
+            fuel: None,
+            brand_ops: self.brand_ops.clone(),
+            brand_ops_complete: true,
+            debug: Some(Debugging::new(hook)),
+            profiler: None,
+            host,
+        };
+        let result = interp.call(callee, args, name.to_string(), Span::new(0, 0), None);
+        match interp.debug {
+            Some(debug) if debug.stopped => None,
+            _ => Some(result),
+        }
+    }
+
+    /// Evaluate `module`'s def `name` against this session's globals (by
+    /// name), then apply it to `args` unless there are none. `module` is this
+    /// session's module re-linked with the def added — how [`crate::debug`]
+    /// evaluates an expression in a paused frame.
+    pub(crate) fn evaluate(
+        &self,
+        module: &Module,
+        name: &str,
+        args: Vec<Value>,
+        host: &mut dyn Host,
+    ) -> Result<Value, RunError> {
+        let mut globals = Globals::for_module(module);
+        for (global, &slot) in &self.globals.slots {
+            if let Some(value) = &self.globals.values[slot as usize] {
+                globals.set(global, value.clone());
+            }
+        }
+        let def = module
+            .defs
+            .iter()
+            .find(|def| def.name == name)
+            .ok_or_else(|| RunError {
+                message: format!("internal: no def `{name}` to evaluate"),
+                span: Span::new(0, 0),
+            })?;
+        let mut interp = Interp {
+            codes: Rc::new(compile(module, &globals.slots)),
+            globals: Rc::new(globals),
+            stack: Vec::new(),
+            mut_slots: HashMap::new(),
+            trace: Vec::new(),
+            tracing: Tracing::Off,
+            recorder: None,
+            depth: 0,
+            call_depth: 0,
+            fuel: None,
+            brand_ops: self.brand_ops.clone(),
+            brand_ops_complete: true,
+            debug: None,
+            profiler: None,
+            host,
+        };
+        let value = interp.eval(&def.value, &Env::empty())?;
+        if args.is_empty() {
+            Ok(value)
+        } else {
+            interp.call(value, args, name.to_string(), def.span, None)
+        }
+    }
+
     /// Like [`Self::call_recorded`] but COVERAGE-ONLY: returns the sorted
     /// span starts of every expression the call evaluated, skipping the
     /// values pass entirely (no Display rendering, no site bookkeeping) —
@@ -742,12 +1123,14 @@ impl Session {
         args: Vec<Value>,
         host: &mut dyn Host,
     ) -> Result<(Value, Vec<usize>), RunError> {
-        let callee = self.globals.get(name).cloned().ok_or_else(|| RunError {
+        let callee = self.globals.get(name).ok_or_else(|| RunError {
             message: format!("no top-level `let {name}` in the module"),
             span: Span::new(0, 0),
         })?;
         let mut interp = Interp {
             globals: self.globals.clone(),
+            codes: self.codes.clone(),
+            stack: Vec::new(),
             mut_slots: HashMap::new(),
             trace: Vec::new(),
             tracing: Tracing::Off,
@@ -757,6 +1140,8 @@ impl Session {
             fuel: None,
             brand_ops: self.brand_ops.clone(),
             brand_ops_complete: true,
+            debug: None,
+            profiler: None,
             host,
         };
         let result = interp.call(callee, args, name.to_string(), Span::new(0, 0), None)?;
@@ -765,6 +1150,158 @@ impl Session {
     }
 }
 
+/// A debugger driving an evaluation ([`Session::load_debugged`] /
+/// [`Session::call_debugged`]; the engine is [`crate::debug`]). While a hook
+/// is armed every closure is tree-walked, so each expression passes through
+/// it; the innermost frame reaching a new source line is a PAUSE POINT.
+pub trait DebugHook {
+    /// A key for the source line holding project-wide `offset`: a pause
+    /// point is where the innermost frame's key changes.
+    fn line(&self, offset: usize) -> usize;
+
+    /// A pause point: carry on, or abandon the evaluation here.
+    fn pause(&mut self, paused: &mut Paused<'_, '_>) -> Resume;
+
+    /// The evaluation failed with `error`, seen from the innermost frame
+    /// before the stack unwinds. Called at most once per evaluation.
+    fn fault(&mut self, paused: &mut Paused<'_, '_>, error: &RunError);
+}
+
+/// A [`DebugHook`]'s answer at a pause point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resume {
+    Continue,
+    /// Abandon the evaluation: the debugged call answers `None`.
+    Stop,
+}
+
+/// The interpreter at a pause point, as a [`DebugHook`] sees it. Its
+/// accessors are for [`crate::debug`]; outside the crate it is opaque.
+pub struct Paused<'a, 'h> {
+    span: Span,
+    frames: &'a [DebugFrame],
+    interp: &'a mut Interp<'h>,
+}
+
+impl Paused<'_, '_> {
+    /// The expression about to be evaluated.
+    pub(crate) fn span(&self) -> Span {
+        self.span
+    }
+
+    /// How many frames are live: top-level initializers and closure calls.
+    pub(crate) fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// The live frames, innermost first: each one's name, the expression it
+    /// is at (a caller is at its call site), and its locals, innermost scope
+    /// first (shadowed bindings included).
+    pub(crate) fn frames(&self) -> Vec<PausedFrame<'_>> {
+        (0..self.frames.len())
+            .rev()
+            .map(|index| {
+                let frame = &self.frames[index];
+                let span = match self.frames.get(index + 1) {
+                    Some(callee) => callee.call,
+                    None => self.span,
+                };
+                (frame.name.as_str(), span, self.locals(index))
+            })
+            .collect()
+    }
+
+    /// Frame `index`'s (outermost first) bindings, its live `let mut`
+    /// slots first. A slot is a stack per binding, one entry per frame that
+    /// entered it, so this frame's value is the one its own entry pushed.
+    fn locals(&self, index: usize) -> Vec<(BindingId, Value)> {
+        let frame = &self.frames[index];
+        let mut locals = Vec::new();
+        for binding in frame.muts.iter().rev() {
+            let entered = self.frames[..=index]
+                .iter()
+                .flat_map(|frame| &frame.muts)
+                .filter(|candidate| *candidate == binding)
+                .count();
+            let value = self
+                .interp
+                .mut_slots
+                .get(&binding.0)
+                .and_then(|slots| slots.get(entered - 1));
+            if let Some(value) = value {
+                locals.push((*binding, value.clone()));
+            }
+        }
+        locals.extend(frame.env.bindings());
+        locals
+    }
+
+    /// The globals as they stand, as a session — what an expression
+    /// evaluated "in this frame" sees (see [`Session::evaluate`]).
+    pub(crate) fn session(&self) -> Session {
+        Session {
+            globals: self.interp.globals.clone(),
+            brand_ops: self.interp.brand_ops.clone(),
+            codes: self.interp.codes.clone(),
+            profiler: None,
+        }
+    }
+
+    /// The evaluation's host.
+    pub(crate) fn host(&mut self) -> &mut dyn Host {
+        &mut *self.interp.host
+    }
+}
+
+/// A live frame as [`Paused::frames`] shows it: name, position, locals.
+pub(crate) type PausedFrame<'a> = (&'a str, Span, Vec<(BindingId, Value)>);
+
+/// An armed [`DebugHook`] and the frames it is shown.
+struct Debugging<'h> {
+    hook: &'h mut dyn DebugHook,
+    frames: Vec<DebugFrame>,
+    /// The hook answered [`Resume::Stop`]: unwind without further pauses.
+    stopped: bool,
+    /// [`DebugHook::fault`] has been called.
+    faulted: bool,
+}
+
+impl<'h> Debugging<'h> {
+    fn new(hook: &'h mut dyn DebugHook) -> Box<Debugging<'h>> {
+        Box::new(Debugging {
+            hook,
+            frames: Vec::new(),
+            stopped: false,
+            faulted: false,
+        })
+    }
+}
+
+/// One live frame of a debugged evaluation.
+struct DebugFrame {
+    name: String,
+    /// Where it was entered: the call site, or a top-level def.
+    call: Span,
+    /// The environment of the expression it is evaluating.
+    env: Env,
+    /// The [`DebugHook::line`] key of `span`, once it has paused.
+    line: Option<usize>,
+    /// `let mut` slots entered in this frame, in order.
+    muts: Vec<BindingId>,
+}
+
+impl DebugFrame {
+    fn new(name: String, call: Span, env: Env) -> DebugFrame {
+        DebugFrame {
+            name,
+            call,
+            env,
+            line: None,
+            muts: Vec::new(),
+        }
+    }
+}
+
 /// A step budget for bounded evaluation ([`run_expects_budgeted`] — the live
 /// tooling seam: an editor evaluating on every edit must not hang on a
 /// runaway expect). A step is one function CALL (closures, builtins, ctors —
@@ -823,8 +1360,49 @@ impl std::fmt::Write for FuelWriter<'_> {
     }
 }
 
+/// The module's top-level values, resolved to slots in file order: compiled
+/// code ([`crate::bytecode`]) reads a global by its slot, the tree-walker and
+/// the embedding API by name. A slot stays `None` until its initializer has
+/// run — "used before its definition". Read-only once loaded, so a session
+/// shares one table with every call (`Rc`) instead of copying it per frame.
+#[derive(Clone)]
+struct Globals {
+    slots: HashMap<String, u32>,
+    values: Vec<Option<Value>>,
+}
+
+impl Globals {
+    fn for_module(module: &Module) -> Globals {
+        let mut slots = HashMap::new();
+        for def in &module.defs {
+            let next = slots.len() as u32;
+            slots.entry(def.name.clone()).or_insert(next);
+        }
+        Globals {
+            values: vec![None; slots.len()],
+            slots,
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<Value> {
+        self.values[*self.slots.get(name)? as usize].clone()
+    }
+
+    fn set(&mut self, name: &str, value: Value) {
+        if let Some(&slot) = self.slots.get(name) {
+            self.values[slot as usize] = Some(value);
+        }
+    }
+}
+
 struct Interp<'h> {
-    globals: HashMap<String, Value>,
+    globals: Rc<Globals>,
+    /// Every lambda's compiled body (see [`crate::bytecode`]); a closure
+    /// call runs its code on [`Interp::run_code`] when it has one.
+    codes: Rc<CodeTable>,
+    /// The VM's value stack, shared by every compiled frame on the call
+    /// path: a frame's slots sit at its base, its operands above them.
+    stack: Vec<Value>,
     /// Live `let mut` slots, keyed by binding, as a stack per binding (the
     /// same binding can be re-entered through indirect recursion). Lowering
     /// guarantees a slot is only touched within its `let`'s dynamic extent —
@@ -850,6 +1428,13 @@ struct Interp<'h> {
     /// False only while a unit's own constructor is still an unevaluated def
     /// (see [`Interp::load_brand_ops`]).
     brand_ops_complete: bool,
+    /// The debugger, armed only by [`Session::load_debugged`] /
+    /// [`Session::call_debugged`]; `None` (the norm) costs one branch per
+    /// eval step, like `recorder`.
+    debug: Option<Box<Debugging<'h>>>,
+    /// The hook profiler, armed on a session by [`Session::set_profiler`];
+    /// `None` (the norm) costs one branch per call, like `tracing`.
+    profiler: Option<Rc<RefCell<Profiler>>>,
     host: &'h mut dyn Host,
 }
 
@@ -859,8 +1444,15 @@ impl Interp<'_> {
     fn eval_defs(&mut self, module: &Module) -> Result<Vec<(String, Value)>, RunError> {
         let mut bindings = Vec::new();
         for def in &module.defs {
-            let value = self.eval(&def.value, &Env::empty())?;
-            self.globals.insert(def.name.clone(), value.clone());
+            if let Some(debug) = &mut self.debug {
+                debug.frames.push(DebugFrame::new(def.name.clone(), def.span, Env::empty()));
+            }
+            let value = self.eval(&def.value, &Env::empty());
+            if let Some(debug) = &mut self.debug {
+                debug.frames.pop();
+            }
+            let value = value?;
+            Rc::make_mut(&mut self.globals).set(&def.name, value.clone());
             bindings.push((def.name.clone(), value));
             // A unit whose own constructor is a top-level `let` could not be
             // probed before the defs ran; retry as soon as more globals exist,
@@ -879,8 +1471,43 @@ impl Interp<'_> {
     /// Evaluate one `expect`. A top-level comparison is decomposed — the two
     /// sides evaluate separately — so a failure reports both actual values.
     fn eval_expect(&mut self, expect: &ExpectDef) -> ExpectOutcome {
+        self.eval_check(&expect.expr, &Env::empty())
+    }
+
+    /// Evaluate an `expectSnapshot`: dump its value and compare it with the
+    /// stored dump, if there is one. The dump comes back for
+    /// [`crate::snapshot::save`] whatever the outcome.
+    fn eval_snapshot(
+        &mut self,
+        expect: &ExpectDef,
+        name: &str,
+        goldens: &crate::snapshot::Goldens,
+    ) -> (ExpectOutcome, Option<crate::snapshot::Json>) {
+        let dumped = self
+            .eval(&expect.expr, &Env::empty())
+            .and_then(|value| {
+                crate::snapshot::dump(&value).map_err(|message| RunError {
+                    message: format!("snapshot `{name}`: {message}"),
+                    span: expect.expr.span,
+                })
+            });
+        let fresh = match dumped {
+            Ok(fresh) => fresh,
+            Err(error) => return (ExpectOutcome::Error(error), None),
+        };
+        let outcome = match goldens.stored.get(&expect.span.start) {
+            Some(stored) if *stored != fresh && !goldens.update => ExpectOutcome::Changed(
+                Box::new(crate::snapshot::SnapshotDiff::new(name, stored, &fresh)),
+            ),
+            _ => ExpectOutcome::Pass,
+        };
+        (outcome, Some(fresh))
+    }
+
+    /// Evaluate a test's bool: a top-level comparison is decomposed, so a
+    /// failure reports both sides.
+    fn eval_check(&mut self, check: &Expr, env: &Env) -> ExpectOutcome {
         use crate::ast::BinOp;
-        let env = Env::empty();
         if let ExprKind::Binary {
             op:
                 op @ (BinOp::Eq
@@ -891,29 +1518,39 @@ impl Interp<'_> {
                 | BinOp::Ge),
             lhs,
             rhs,
-        } = &expect.expr.kind
+        } = &check.kind
         {
-            let l = match self.eval(lhs, &env) {
+            let l = match self.eval(lhs, env) {
                 Ok(v) => v,
                 Err(e) => return ExpectOutcome::Error(e),
             };
-            let r = match self.eval(rhs, &env) {
+            let r = match self.eval(rhs, env) {
                 Ok(v) => v,
                 Err(e) => return ExpectOutcome::Error(e),
             };
-            return match self.binary_op(*op, l.clone(), r.clone(), expect.expr.span) {
+            return match self.binary_op(*op, l.clone(), r.clone(), check.span) {
                 Ok(Value::Bool(true)) => ExpectOutcome::Pass,
                 // Comparisons only produce bools, so anything else is false.
-                Ok(_) => ExpectOutcome::Fail(Some(FailedCompare {
-                    op: op.symbol(),
-                    lhs: l.to_string(),
-                    rhs: r.to_string(),
-                })),
+                Ok(_) => {
+                    let (mut lhs, mut rhs) = (l.to_string(), r.to_string());
+                    // `2` (a float) and `2` (an int) render alike but are
+                    // never equal — name the kinds, or the report reads as
+                    // a contradiction.
+                    if lhs == rhs && l.kind_name() != r.kind_name() {
+                        lhs = format!("{lhs} ({})", l.kind_name());
+                        rhs = format!("{rhs} ({})", r.kind_name());
+                    }
+                    ExpectOutcome::Fail(Some(FailedCompare {
+                        op: op.symbol(),
+                        lhs,
+                        rhs,
+                    }))
+                }
                 // e.g. `==` on functions — a runtime error, not a plain fail.
                 Err(e) => ExpectOutcome::Error(e),
             };
         }
-        match self.eval(&expect.expr, &env) {
+        match self.eval(check, env) {
             Ok(Value::Bool(true)) => ExpectOutcome::Pass,
             Ok(Value::Bool(false)) => ExpectOutcome::Fail(None),
             Ok(other) => ExpectOutcome::Error(RunError {
@@ -921,12 +1558,127 @@ impl Interp<'_> {
                     "an `expect` must evaluate to a bool, got {other} — write a comparison \
 (`expect actual == expected`)"
                 ),
-                span: expect.expr.span,
+                span: check.span,
             }),
             Err(e) => ExpectOutcome::Error(e),
         }
     }
 
+    /// Run a `property`: generate `options.cases` seeded inputs of growing
+    /// size and check the lambda's body for each. The first failing case is
+    /// shrunk — simpler inputs that still fail replace it until none does —
+    /// and reported as a [`Counterexample`]. An engine-external call fails
+    /// every input alike, so it is reported as-is (unrunnable), unshrunk; so
+    /// is a runaway case, which would cost a full budget per shrink attempt.
+    fn eval_property(
+        &mut self,
+        property: &ExpectDef,
+        generators: &mut crate::property::Generators,
+        options: &ExpectOptions,
+    ) -> ExpectOutcome {
+        use crate::property::{Rng, MAX_SIZE};
+        /// Shrink attempts per property — each is one full evaluation.
+        const MAX_SHRINK_RUNS: u32 = 1_000;
+        let ExprKind::Lambda { params, body, .. } = &property.expr.kind else {
+            return ExpectOutcome::Error(RunError {
+                message: "a `property` must be a lambda over its generated inputs".to_string(),
+                span: property.expr.span,
+            });
+        };
+        let mut domain = Vec::with_capacity(params.len());
+        for param in params.iter() {
+            let Some(annotation) = &param.ty else {
+                return ExpectOutcome::Error(RunError {
+                    message: format!("property parameter `{}` needs a type", param.name),
+                    span: param.span,
+                });
+            };
+            match generators.domain(annotation) {
+                Ok(ty) => domain.push(ty),
+                Err(message) => {
+                    return ExpectOutcome::Error(RunError {
+                        message,
+                        span: annotation.span,
+                    })
+                }
+            }
+        }
+        let budget = options.budget;
+        let run = |interp: &mut Self, inputs: &[Value]| {
+            interp.fuel = budget.map(Fuel::new);
+            let bound = params
+                .iter()
+                .zip(inputs)
+                .map(|(param, value)| (param.binding, value.clone()))
+                .collect();
+            interp.eval_check(body, &Env::empty().child(bound))
+        };
+        let final_result = |outcome: &ExpectOutcome| match outcome {
+            ExpectOutcome::Error(error) => {
+                error.message.starts_with("unknown external")
+                    || error.message.starts_with("evaluation exceeded its step budget")
+            }
+            _ => false,
+        };
+        let mut rng = Rng::new(options.seed);
+        let cases = options.cases.max(1);
+        for case in 0..cases {
+            let size = (u64::from(case) * u64::from(MAX_SIZE) / u64::from(cases)) as u32;
+            let mut inputs = Vec::with_capacity(domain.len());
+            for (ty, param) in domain.iter().zip(params.iter()) {
+                match generators.generate(ty, size, &mut rng) {
+                    Ok(value) => inputs.push(value),
+                    Err(message) => {
+                        return ExpectOutcome::Error(RunError {
+                            message,
+                            span: param.span,
+                        })
+                    }
+                }
+            }
+            let mut failure = run(self, &inputs);
+            if matches!(failure, ExpectOutcome::Pass) {
+                continue;
+            }
+            if final_result(&failure) {
+                return failure;
+            }
+            let (mut shrinks, mut attempts) = (0, 0);
+            'shrinking: while attempts < MAX_SHRINK_RUNS {
+                for (i, ty) in domain.iter().enumerate() {
+                    for candidate in generators.shrink(ty, &inputs[i]) {
+                        if attempts == MAX_SHRINK_RUNS {
+                            break 'shrinking;
+                        }
+                        attempts += 1;
+                        let mut next = inputs.clone();
+                        next[i] = candidate;
+                        let outcome = run(self, &next);
+                        if !matches!(outcome, ExpectOutcome::Pass) && !final_result(&outcome) {
+                            inputs = next;
+                            failure = outcome;
+                            shrinks += 1;
+                            continue 'shrinking;
+                        }
+                    }
+                }
+                break;
+            }
+            return ExpectOutcome::Falsified(Box::new(Counterexample {
+                seed: options.seed,
+                case: case + 1,
+                shrinks,
+                inputs: params
+                    .iter()
+                    .zip(&inputs)
+                    .map(|(param, value)| (param.name.clone(), value.to_string()))
+                    .collect(),
+                failure,
+            }));
+        }
+        ExpectOutcome::Pass
+    }
+
     /// Build the operator table before the defs run (see
     /// [`Interp::load_brand_ops`]).
     fn load_unit_operators(&mut self, module: &Module) -> Result<(), RunError> {
@@ -1097,7 +1849,7 @@ implementation",
     }
 
     fn call_main(&mut self, def: &Def) -> Result<Value, RunError> {
-        let main = self.globals.get("main").expect("just defined").clone();
+        let main = self.globals.get("main").expect("just defined");
         match &main {
             Value::Closure(closure) if closure.params.is_empty() => {
                 self.call(main.clone(), vec![], "main".to_string(), def.span, None)
@@ -1144,55 +1896,114 @@ implementation",
         Ok(())
     }
 
-    /// Binary-search a canonical Map vector, charging before each key
-    /// comparison. Precharging matters for strings: their shared prefix can
-    /// be arbitrarily long, so charging only after `str::cmp` would let the
-    /// comparison itself escape the evaluator's bound.
-    fn map_search(
+    /// Sort validated entries into canonical order (last write wins) for
+    /// `Map.fromList` and `Set.fromList`. An over-budget sort is refused
+    /// before its first comparison: the preflight is conservative, and the
+    /// actual comparison work is charged afterward.
+    fn canonicalize_charged(
         &mut self,
-        entries: &[(MapKey, Value)],
-        key: &MapKey,
+        entries: Vec<(MapKey, Value)>,
+        max_comparison_units: u64,
         span: Span,
-    ) -> Result<Result<usize, usize>, RunError> {
-        let mut left = 0usize;
-        let mut right = entries.len();
-        while left < right {
-            let mid = left + (right - left) / 2;
-            let candidate = &entries[mid].0;
-            self.charge(candidate.comparison_units(key), span)?;
-            match candidate.compare(key) {
-                Ordering::Less => left = mid + 1,
-                Ordering::Greater => right = mid,
-                Ordering::Equal => return Ok(Ok(mid)),
-            }
-        }
-        Ok(Err(left))
+    ) -> Result<Vec<(MapKey, Value)>, RunError> {
+        let comparison_count_ceiling = stable_sort_comparison_ceiling(entries.len())
+            .saturating_add(u64::try_from(entries.len().saturating_sub(1)).unwrap_or(u64::MAX));
+        let comparison_work_ceiling = comparison_count_ceiling.saturating_mul(max_comparison_units);
+        self.preflight_charge(comparison_work_ceiling, span)?;
+        let (out, comparison_work) = canonicalize_map_entries(entries);
+        debug_assert!(comparison_work <= comparison_work_ceiling);
+        self.charge(comparison_work, span)?;
+        Ok(out)
     }
 
     fn eval(&mut self, expr: &Expr, env: &Env) -> Result<Value, RunError> {
         self.depth += 1;
         if self.depth > MAX_EVAL_DEPTH {
             self.depth -= 1;
-            return Err(RunError {
-                message: format!(
-                    "evaluation nested too deeply (exceeded the depth cap of {MAX_EVAL_DEPTH}): \
-deep recursion, or deeply nested expressions. Deep list iteration belongs in the builtin list \
-functions (List.fold/map/filter/any/all/length/…), which loop in the interpreter and consume no \
-evaluation depth."
-                ),
-                span: expr.span,
-            });
+            return Err(depth_error(expr.span));
         }
         // Coverage: one armed-check per evaluation (a `None` test when off —
         // the same hot-path cost class as the binding-site hook).
         if let Some(recorder) = &mut self.recorder {
             recorder.cover(expr.span.start);
         }
-        let result = self.eval_inner(expr, env);
+        let result = if self.debug.is_some() {
+            self.eval_debugged(expr, env)
+        } else {
+            self.eval_inner(expr, env)
+        };
         self.depth -= 1;
         result
     }
 
+    /// [`Self::eval`] under a [`DebugHook`]: track the innermost frame's
+    /// position, offer the hook a pause when it reaches a new line, and show
+    /// it the first failure before the stack unwinds. Out of line so the
+    /// plain eval frame stays lean (see [`MAX_EVAL_DEPTH`]).
+    #[inline(never)]
+    fn eval_debugged(&mut self, expr: &Expr, env: &Env) -> Result<Value, RunError> {
+        // Building a closure does nothing worth stopping on.
+        if !matches!(expr.kind, ExprKind::Lambda { .. }) {
+            let debug = self.debug.as_mut().expect("checked by eval");
+            let line = debug.hook.line(expr.span.start);
+            let Some(frame) = debug.frames.last_mut() else {
+                return self.eval_inner(expr, env);
+            };
+            frame.env = env.clone();
+            if frame.line != Some(line) {
+                frame.line = Some(line);
+                let mut debug = self.debug.take().expect("checked by eval");
+                let resume = debug.hook.pause(&mut Paused {
+                    span: expr.span,
+                    frames: &debug.frames,
+                    interp: self,
+                });
+                if resume == Resume::Stop {
+                    debug.stopped = true;
+                }
+                let stopped = debug.stopped;
+                self.debug = Some(debug);
+                if stopped {
+                    return Err(RunError {
+                        message: "stopped by the debugger".to_string(),
+                        span: expr.span,
+                    });
+                }
+            }
+        }
+        let result = self.eval_inner(expr, env);
+        if let Err(error) = &result {
+            let mut debug = self.debug.take().expect("checked by eval");
+            if !debug.stopped && !debug.faulted {
+                debug.faulted = true;
+                debug.hook.fault(
+                    &mut Paused {
+                        span: expr.span,
+                        frames: &debug.frames,
+                        interp: self,
+                    },
+                    error,
+                );
+            }
+            self.debug = Some(debug);
+        }
+        result
+    }
+
+    /// Track a `let mut` slot entering (`Some`) or leaving (`None`) the
+    /// innermost debug frame, so [`Paused::locals`] can show it.
+    #[inline(never)]
+    fn debug_mut(&mut self, binding: Option<BindingId>) {
+        if let Some(frame) = self.debug.as_mut().and_then(|debug| debug.frames.last_mut()) {
+            match binding {
+                Some(binding) => frame.muts.push(binding),
+                None => {
+                    frame.muts.pop();
+                }
+            }
+        }
+    }
+
     /// Record a value bound at `binding`'s site, when the recorder is armed —
     /// a cheap `None` check otherwise (the binding-site hot-path cost). See
     /// [`Recorder`].
@@ -1276,6 +2087,7 @@ evaluation depth."
     fn eval_inner(&mut self, expr: &Expr, env: &Env) -> Result<Value, RunError> {
         match &expr.kind {
             ExprKind::Number(n) => Ok(Value::Number(*n)),
+            ExprKind::Int(n) => Ok(Value::Int(*n)),
             ExprKind::String(s) => Ok(Value::String(Rc::from(s.as_str()))),
             ExprKind::InterpolatedString(parts) => self.eval_interpolated(parts, env, expr.span),
             ExprKind::Bool(b) => Ok(Value::Bool(*b)),
@@ -1289,7 +2101,7 @@ evaluation depth."
                 Ok(value)
             }
             ExprKind::Global(name) => {
-                let value = self.globals.get(name).cloned().ok_or_else(|| RunError {
+                let value = self.globals.get(name).ok_or_else(|| RunError {
                     message: format!("global `{name}` used before its definition"),
                     span: expr.span,
                 })?;
@@ -1325,7 +2137,7 @@ evaluation depth."
                 for item in items {
                     out.push(self.eval(item, env)?);
                 }
-                Ok(Value::List(Rc::new(out)))
+                Ok(Value::List(List::from(out)))
             }
             ExprKind::ListCons { items, tail } => {
                 self.charge(1, expr.span)?;
@@ -1334,10 +2146,7 @@ evaluation depth."
                     out.push(self.eval(item, env)?);
                 }
                 match self.eval(tail, env)? {
-                    Value::List(rest) => {
-                        out.extend(rest.iter().cloned());
-                        Ok(Value::List(Rc::new(out)))
-                    }
+                    Value::List(rest) => Ok(Value::List(List::from(out).concat(&rest))),
                     other => Err(RunError {
                         message: format!(
                             "`..` spreads a list, but the tail is {}",
@@ -1407,7 +2216,13 @@ evaluation depth."
                 self.record_binding(*binding, name, binder_span, &value);
                 if *mutable {
                     self.mut_slots.entry(binding.0).or_default().push(value);
+                    if self.debug.is_some() {
+                        self.debug_mut(Some(*binding));
+                    }
                     let result = self.eval(body, env);
+                    if self.debug.is_some() {
+                        self.debug_mut(None);
+                    }
                     self.mut_slots
                         .get_mut(&binding.0)
                         .expect("pushed above")
@@ -1525,6 +2340,7 @@ evaluation depth."
             }
             ExprKind::Neg(inner) => match self.eval(inner, env)? {
                 Value::Number(n) => Ok(Value::Number(-n)),
+                Value::Int(n) => int_result(n.checked_neg(), "-", expr.span),
                 other => Err(RunError {
                     message: format!("cannot negate {}", other.kind_name()),
                     span: expr.span,
@@ -1589,7 +2405,26 @@ evaluation depth."
                 for arm in arms {
                     let mut vars = Vec::new();
                     if match_pattern(&arm.pattern, &value, &mut vars) {
-                        if self.recorder.is_some() {
+                        // Bindings are recorded only once the guard has
+                        // passed — a rejected arm was never taken.
+                        let recorded = self.recorder.is_some().then(|| vars.clone());
+                        let child = env.child(vars);
+                        if let Some(guard) = &arm.guard {
+                            match self.eval(guard, &child)? {
+                                Value::Bool(true) => {}
+                                Value::Bool(false) => continue,
+                                other => {
+                                    return Err(RunError {
+                                        message: format!(
+                                            "`when` guard needs a bool, got {}",
+                                            other.kind_name()
+                                        ),
+                                        span: guard.span,
+                                    })
+                                }
+                            }
+                        }
+                        if let Some(vars) = recorded {
                             let mut sites = Vec::new();
                             pattern_binder_sites(&arm.pattern, &mut sites);
                             for (binding, bound) in &vars {
@@ -1600,7 +2435,6 @@ evaluation depth."
                                 }
                             }
                         }
-                        let child = env.child(vars);
                         return self.eval(&arm.body, &child);
                     }
                 }
@@ -1624,9 +2458,12 @@ evaluation depth."
             BinOp::Add => self.arith(op, lhs, rhs, span, |a, b| a + b),
             BinOp::Sub => self.arith(op, lhs, rhs, span, |a, b| a - b),
             BinOp::Mul => self.arith(op, lhs, rhs, span, |a, b| a * b),
-            // Division follows IEEE-754 (x/0 is ±inf/NaN, printed as
-            // `inf`/`NaN`) — one number type, no checked division.
+            // Float division follows IEEE-754 (x/0 is ±inf/NaN, printed as
+            // `inf`/`NaN`); int division is checked (see `int_arith`).
             BinOp::Div => self.arith(op, lhs, rhs, span, |a, b| a / b),
+            // Euclidean, like `Math.mod`: never negative, and a zero divisor
+            // is NaN for floats and an error for ints.
+            BinOp::Rem => self.arith(op, lhs, rhs, span, f64::rem_euclid),
             BinOp::Lt => self.compare(op, lhs, rhs, span, |a, b| a < b),
             BinOp::Gt => self.compare(op, lhs, rhs, span, |a, b| a > b),
             // IEEE ordering, like `<`/`>`: every comparison with NaN is false.
@@ -1666,6 +2503,10 @@ evaluation depth."
     ) -> Result<Value, RunError> {
         match (lhs, rhs) {
             (Value::Number(a), Value::Number(b)) => Ok(Value::Number(arith(a, b))),
+            (Value::Int(a), Value::Int(b)) => int_arith(op, a, b, span),
+            (Value::Int(_), Value::Number(_)) | (Value::Number(_), Value::Int(_)) => {
+                Err(mixed_numbers(op, span))
+            }
             // Not two numbers: a branded operand may still have a declared
             // implementation for this operator.
             (a, b) => self.brand_arith(op, a, b, span),
@@ -1692,7 +2533,7 @@ evaluation depth."
             // ordinary unknown-external error at the use site rather than a
             // load failure.
             OpImpl::External(path) => self.external_value(&path, span),
-            OpImpl::Global(name) => self.globals.get(&name).cloned().ok_or_else(|| RunError {
+            OpImpl::Global(name) => self.globals.get(&name).ok_or_else(|| RunError {
                 message: format!(
                     "`{}` on this branded value calls `{name}`, which is used before its \
 definition (a top-level initializer may only use globals defined above it)",
@@ -1715,6 +2556,17 @@ definition (a top-level initializer may only use globals defined above it)",
         span: Span,
     ) -> Result<Value, RunError> {
         use crate::ast::BinOp;
+        if !op.brandable() {
+            return Err(RunError {
+                message: format!(
+                    "`{}` needs two numbers, got {} and {}",
+                    op.symbol(),
+                    lhs.kind_name(),
+                    rhs.kind_name()
+                ),
+                span,
+            });
+        }
         let slot = op_slot(op);
         let declared = |ops: &BrandOps, value: &Value| {
             brand_tag(value)
@@ -1726,7 +2578,7 @@ definition (a top-level initializer may only use globals defined above it)",
         // matching what the checker resolves (`/` deliberately does not).
         let found = match declared(&self.brand_ops, &lhs) {
             Some(implementation) => Some((implementation, lhs.clone(), rhs.clone())),
-            None if matches!(op, BinOp::Mul) && matches!(lhs, Value::Number(_)) => {
+            None if matches!(op, BinOp::Mul) && matches!(lhs, Value::Number(_) | Value::Int(_)) => {
                 declared(&self.brand_ops, &rhs)
                     .map(|implementation| (implementation, rhs.clone(), lhs.clone()))
             }
@@ -1793,6 +2645,17 @@ definition (a top-level initializer may only use globals defined above it)",
     ) -> Result<Value, RunError> {
         match (lhs, rhs) {
             (Value::Number(a), Value::Number(b)) => Ok(Value::Bool(float_compare(a, b))),
+            // Compared exactly, never through `f64` (which would merge
+            // neighbouring ints past 2^53).
+            (Value::Int(a), Value::Int(b)) => Ok(Value::Bool(match op {
+                crate::ast::BinOp::Lt => a < b,
+                crate::ast::BinOp::Gt => a > b,
+                crate::ast::BinOp::Le => a <= b,
+                _ => a >= b,
+            })),
+            (Value::Int(_), Value::Number(_)) | (Value::Number(_), Value::Int(_)) => {
+                Err(mixed_numbers(op, span))
+            }
             // Not two numbers: a branded operand may still declare `<`.
             (a, b) => match self.brand_compare(op, &a, &b, span)? {
                 Some(value) => Ok(value),
@@ -1906,7 +2769,7 @@ definition (a top-level initializer may only use globals defined above it)",
         // frame loop nothing (frame_bench-verified; a per-eval charge was a
         // measured ~3% wall-clock regression).
         self.charge(1, span)?;
-        self.trace_enter(&label, &args);
+        self.trace_enter(&label, &args, &callee);
         self.call_depth += 1;
         let result = match &callee {
             Value::Closure(closure) => {
@@ -1915,48 +2778,575 @@ definition (a top-level initializer may only use globals defined above it)",
                         Some(builtin) => format!("the function passed to {builtin}"),
                         None => format!("`{label}`"),
                     };
-                    Err(RunError {
-                        message: format!(
-                            "{who} takes {} argument(s), got {}",
-                            closure.params.len(),
-                            args.len()
-                        ),
-                        span,
-                    })
-                } else {
-                    let vars: Vec<(BindingId, Value)> =
-                        closure.params.iter().map(|p| p.binding).zip(args).collect();
+                    Err(RunError {
+                        message: format!(
+                            "{who} takes {} argument(s), got {}",
+                            closure.params.len(),
+                            args.len()
+                        ),
+                        span,
+                    })
+                } else {
+                    if self.recorder.is_some() {
+                        for (param, value) in closure.params.iter().zip(args.iter()) {
+                            self.record_binding(param.binding, &param.name, param.span, value);
+                        }
+                    }
+                    if self.debug.is_some() {
+                        self.walk_debugged(closure, args, &label, span)
+                    } else {
+                        match self.codes.get(closure).cloned() {
+                            Some(code) => self.run_code(code, closure, args),
+                            None => self.walk_closure(closure, args),
+                        }
+                    }
+                }
+            }
+            Value::Ctor { name, arity } => {
+                // Saturated by the arity gate above: every mismatch went to
+                // `call_curried`, which refuses it (constructors apply fully).
+                debug_assert_eq!(args.len(), *arity, "ctor mismatch reaches call_curried");
+                Ok(Value::Variant {
+                    ctor: name.clone(),
+                    args: Rc::new(args),
+                })
+            }
+            Value::Builtin(b) => self.call_builtin(*b, args, span),
+            Value::HostFn(path) => self.host.call(path, args, span),
+            other => Err(RunError {
+                message: format!("cannot call {}", other.kind_name()),
+                span,
+            }),
+        };
+        self.call_depth -= 1;
+        match &result {
+            Ok(value) => self.trace_exit(value),
+            Err(_) => self.profile_exit(),
+        }
+        result
+    }
+
+    /// Tree-walk a closure's body: the path for a closure with no compiled
+    /// code (one surviving from an older module across a reload) or whose
+    /// captures cannot be laid out.
+    fn walk_closure(&mut self, closure: &Closure, args: Vec<Value>) -> Result<Value, RunError> {
+        let vars: Vec<(BindingId, Value)> =
+            closure.params.iter().map(|p| p.binding).zip(args).collect();
+        let env = closure.env.child(vars);
+        let body = closure.body.clone();
+        self.eval(&body, &env)
+    }
+
+    /// [`Self::walk_closure`] in a new debug frame: under a [`DebugHook`]
+    /// every closure is tree-walked, so each of its expressions passes the
+    /// hook.
+    #[inline(never)]
+    fn walk_debugged(
+        &mut self,
+        closure: &Closure,
+        args: Vec<Value>,
+        label: &str,
+        span: Span,
+    ) -> Result<Value, RunError> {
+        if let Some(debug) = &mut self.debug {
+            debug.frames.push(DebugFrame::new(label.to_string(), span, closure.env.clone()));
+        }
+        let result = self.walk_closure(closure, args);
+        if let Some(debug) = &mut self.debug {
+            debug.frames.pop();
+        }
+        result
+    }
+
+    /// Run a closure's compiled body: lay out its frame on the shared stack
+    /// (arguments, captures, then the body's binder slots) and execute it.
+    /// One compiled frame is one level of the eval-depth budget — expressions
+    /// nested inside it cost no host stack, and neither do the tail calls it
+    /// makes (see [`Op::TailCall`]).
+    fn run_code(
+        &mut self,
+        code: Rc<Code>,
+        closure: &Closure,
+        args: Vec<Value>,
+    ) -> Result<Value, RunError> {
+        let base = self.stack.len();
+        self.stack.extend(args);
+        if !self.load_captures(&code, &closure.env) {
+            let args = self.stack.split_off(base);
+            return self.walk_closure(closure, args);
+        }
+        self.stack.resize(base + code.slots, Value::Bool(false));
+        self.depth += 1;
+        let mut tails = 0;
+        let result = if self.depth > MAX_EVAL_DEPTH {
+            Err(depth_error(code.body.span))
+        } else {
+            self.exec(code, base, &mut tails)
+        };
+        self.depth -= 1;
+        self.stack.truncate(base);
+        // Every tail call this frame made in place is still a logical call
+        // that entered and now exits — with the one result they all return,
+        // innermost first — so the trace reads exactly as nested calls would.
+        match &result {
+            Ok(value) => {
+                for _ in 0..tails {
+                    self.call_depth -= 1;
+                    self.trace_exit(value);
+                }
+            }
+            Err(_) => {
+                self.call_depth -= tails;
+                for _ in 0..tails {
+                    self.profile_exit();
+                }
+            }
+        }
+        result
+    }
+
+    /// Push `code`'s captured values out of a closure environment. A closure
+    /// the VM built holds one flat scope in exactly the capture order; any
+    /// other shape (built by the tree-walker, or by a reload rebind) is
+    /// searched by binding. False, with nothing pushed, when a capture is
+    /// missing — an IR inconsistency the tree-walker reports at the read.
+    fn load_captures(&mut self, code: &Code, env: &Env) -> bool {
+        if code.captures.is_empty() {
+            return true;
+        }
+        if let Some(vars) = env.flat() {
+            if vars.len() == code.captures.len()
+                && vars
+                    .iter()
+                    .zip(code.captures.iter())
+                    .all(|((binding, _), capture)| binding == capture)
+            {
+                self.stack.extend(vars.iter().map(|(_, value)| value.clone()));
+                return true;
+            }
+        }
+        let start = self.stack.len();
+        for binding in code.captures.iter() {
+            match env.lookup(*binding) {
+                Some(value) => self.stack.push(value),
+                None => {
+                    self.stack.truncate(start);
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("compiled code balances its stack")
+    }
+
+    fn pop_n(&mut self, n: u32) -> Vec<Value> {
+        self.stack.split_off(self.stack.len() - n as usize)
+    }
+
+    /// The VM loop over one compiled frame at `base` — `eval_inner`'s
+    /// semantics op for op, with the same errors at the same spans (see
+    /// [`Op`]). An error leaves the stack for [`Self::run_code`] to unwind;
+    /// `tails` counts the calls a tail call entered in place, whose exits
+    /// `run_code` emits.
+    fn exec(
+        &mut self,
+        mut code: Rc<Code>,
+        base: usize,
+        tails: &mut usize,
+    ) -> Result<Value, RunError> {
+        let mut pc = 0;
+        let mut vars = Vec::new();
+        loop {
+            // Coverage: one armed-check per op (a `None` test when off — the
+            // tree-walker's per-node cost class).
+            if let Some(recorder) = &mut self.recorder {
+                for &start in code.covers(pc) {
+                    recorder.cover(start);
+                }
+            }
+            let span = code.spans[pc];
+            match &code.ops[pc] {
+                Op::Number(n) => self.stack.push(Value::Number(*n)),
+                Op::Int(n) => self.stack.push(Value::Int(*n)),
+                Op::Bool(b) => self.stack.push(Value::Bool(*b)),
+                Op::Const(c) => self.stack.push(code.consts[*c as usize].clone()),
+                Op::Local { slot, site } => {
+                    let value = self.stack[base + *slot as usize].clone();
+                    if self.recorder.is_some() {
+                        let (name, span) = &code.sites[*site as usize];
+                        self.record_ref(name, *span, &value);
+                    }
+                    self.stack.push(value);
+                }
+                Op::Global { global, site } => {
+                    let Some(value) = self.globals.values[*global as usize].clone() else {
+                        let (name, span) = &code.sites[*site as usize];
+                        return Err(RunError {
+                            message: format!("global `{name}` used before its definition"),
+                            span: *span,
+                        });
+                    };
+                    if self.recorder.is_some() {
+                        let (name, span) = &code.sites[*site as usize];
+                        self.record_ref(name, *span, &value);
+                    }
+                    self.stack.push(value);
+                }
+                Op::Unbound(site) => {
+                    let (name, span) = &code.sites[*site as usize];
+                    return Err(RunError {
+                        // Unreachable if lowering is correct; fail loud.
+                        message: format!("internal: unbound local `{name}`"),
+                        span: *span,
+                    });
+                }
+                Op::Undefined(site) => {
+                    let (name, span) = &code.sites[*site as usize];
+                    return Err(RunError {
+                        message: format!("global `{name}` used before its definition"),
+                        span: *span,
+                    });
+                }
+                Op::External(path) => {
+                    let value = self.external_value(&code.paths[*path as usize], span)?;
+                    self.stack.push(value);
+                }
+                Op::Step => self.charge(1, span)?,
+                Op::Record(shape) => {
+                    let names = &code.shapes[*shape as usize];
+                    let values = self.pop_n(names.len() as u32);
+                    let fields = names.iter().cloned().zip(values).collect();
+                    self.stack.push(Value::Record(Rc::new(fields)));
+                }
+                Op::Tuple(n) => {
+                    let items = self.pop_n(*n);
+                    self.stack.push(Value::Tuple(Rc::new(items)));
+                }
+                Op::List(n) => {
+                    let items = self.pop_n(*n);
+                    self.stack.push(Value::List(List::from(items)));
+                }
+                Op::Cons(n) => {
+                    let Value::List(rest) = self.pop() else {
+                        let tail = self.pop();
+                        return Err(RunError {
+                            message: format!(
+                                "`..` spreads a list, but the tail is {}",
+                                tail.kind_name()
+                            ),
+                            span,
+                        });
+                    };
+                    let items = self.pop_n(*n);
+                    self.stack.push(Value::List(List::from(items).concat(&rest)));
+                }
+                Op::UpdateBase => {
+                    let base_value = self.stack.last().expect("the base was pushed");
+                    if !matches!(base_value, Value::Record(_)) {
+                        return Err(RunError {
+                            message: format!(
+                                "`with` update on {}, not a record",
+                                base_value.kind_name()
+                            ),
+                            span,
+                        });
+                    }
+                }
+                // The target is validated BEFORE its replacement runs: with
+                // host externals the RHS can have effects, and an invalid
+                // update must reject without running them.
+                Op::UpdateCheck(name) => {
+                    let name = &code.names[*name as usize];
+                    let Some(Value::Record(fields)) = self.stack.last() else {
+                        unreachable!("`UpdateBase` checked the record")
+                    };
+                    if !fields.iter().any(|(field, _)| field == name) {
+                        return Err(RunError {
+                            message: format!("record has no field `{name}` to update"),
+                            span,
+                        });
+                    }
+                }
+                Op::UpdateSet(name) => {
+                    let name = &code.names[*name as usize];
+                    let value = self.pop();
+                    let Some(Value::Record(fields)) = self.stack.last_mut() else {
+                        unreachable!("`UpdateBase` checked the record")
+                    };
+                    let slot = Rc::make_mut(fields)
+                        .iter_mut()
+                        .find(|(field, _)| field == name)
+                        .expect("`UpdateCheck` found the field");
+                    slot.1 = value;
+                }
+                Op::Field(name) => {
+                    let field = &code.names[*name as usize];
+                    let value = match self.pop() {
+                        Value::Record(fields) => fields
+                            .iter()
+                            .find(|(name, _)| name == field)
+                            .map(|(_, value)| value.clone())
+                            .ok_or_else(|| RunError {
+                                message: format!("record has no field `{field}`"),
+                                span,
+                            })?,
+                        other => {
+                            return Err(RunError {
+                                message: format!(
+                                    "`.{field}` on {}, not a record",
+                                    other.kind_name()
+                                ),
+                                span,
+                            })
+                        }
+                    };
+                    self.stack.push(value);
+                }
+                Op::Bind { slot, binder } => {
+                    let value = self.pop();
+                    if self.recorder.is_some() {
+                        let (binding, name, span) = &code.binders[*binder as usize];
+                        self.record_binding(*binding, name, *span, &value);
+                    }
+                    self.stack[base + *slot as usize] = value;
+                }
+                Op::Store(slot) => {
+                    let value = self.pop();
+                    self.stack[base + *slot as usize] = value;
+                }
+                Op::MakeClosure(proto) => {
+                    let proto = &code.protos[*proto as usize];
+                    let env = if proto.captures.is_empty() {
+                        Env::empty()
+                    } else {
+                        Env::empty().child(
+                            proto
+                                .captures
+                                .iter()
+                                .filter_map(|(binding, slot)| {
+                                    slot.map(|slot| {
+                                        (*binding, self.stack[base + slot as usize].clone())
+                                    })
+                                })
+                                .collect(),
+                        )
+                    };
+                    self.stack.push(Value::Closure(Rc::new(Closure {
+                        params: proto.params.clone(),
+                        body: proto.body.clone(),
+                        env,
+                        expr_id: proto.expr_id,
+                    })));
+                }
+                Op::Call { argc, label } => {
+                    let args = self.pop_n(*argc);
+                    let callee = self.pop();
+                    let label = code.labels[*label as usize].clone();
+                    let value = self.call(callee, args, label, span, None)?;
+                    self.stack.push(value);
+                }
+                Op::TailCall { argc, label } => {
+                    let label = code.labels[*label as usize].clone();
+                    let mut args = self.pop_n(*argc);
+                    let mut callee = self.pop();
+                    // A partial this call saturates is unwrapped first, as
+                    // `call_curried` would (which traces only the inner call).
+                    if let Value::Partial(partial) = &callee {
+                        if matches!(&partial.callee, Value::Closure(closure)
+                            if closure.params.len() == partial.applied.len() + args.len())
+                        {
+                            let mut combined = partial.applied.clone();
+                            combined.extend(args);
+                            args = combined;
+                            callee = partial.callee.clone();
+                        }
+                    }
+                    let target = match &callee {
+                        Value::Closure(closure) if closure.params.len() == args.len() => self
+                            .codes
+                            .get(closure)
+                            .cloned()
+                            .map(|next| (closure.clone(), next)),
+                        _ => None,
+                    };
+                    let Some((closure, next)) = target else {
+                        return self.call(callee, args, label, span, None);
+                    };
+                    // `call`'s bookkeeping for the logical call, minus the
+                    // host recursion: its exit is emitted by `run_code`.
+                    self.charge(1, span)?;
+                    self.trace_enter(&label, &args, &callee);
+                    self.call_depth += 1;
+                    *tails += 1;
                     if self.recorder.is_some() {
-                        for (param, (_, value)) in closure.params.iter().zip(vars.iter()) {
+                        for (param, value) in closure.params.iter().zip(args.iter()) {
                             self.record_binding(param.binding, &param.name, param.span, value);
                         }
                     }
-                    let env = closure.env.child(vars);
-                    let body = closure.body.clone();
-                    self.eval(&body, &env)
+                    self.stack.truncate(base);
+                    self.stack.extend(args);
+                    if !self.load_captures(&next, &closure.env) {
+                        let args = self.stack.split_off(base);
+                        return self.walk_closure(&closure, args);
+                    }
+                    self.stack.resize(base + next.slots, Value::Bool(false));
+                    code = next;
+                    pc = 0;
+                    continue;
                 }
+                Op::Binary(op) => {
+                    let rhs = self.pop();
+                    let lhs = self.pop();
+                    let value = self.binary_op(*op, lhs, rhs, span)?;
+                    self.stack.push(value);
+                }
+                Op::Neg => match self.pop() {
+                    Value::Number(n) => self.stack.push(Value::Number(-n)),
+                    Value::Int(n) => {
+                        let value = int_result(n.checked_neg(), "-", span)?;
+                        self.stack.push(value);
+                    }
+                    other => {
+                        return Err(RunError {
+                            message: format!("cannot negate {}", other.kind_name()),
+                            span,
+                        })
+                    }
+                },
+                Op::AsBool => {
+                    as_bool(self.stack.last().expect("the operand was pushed"), span)?;
+                }
+                Op::Not => {
+                    if let Some(Value::Bool(b)) = self.stack.last_mut() {
+                        *b = !*b;
+                    }
+                }
+                Op::Pop => {
+                    self.pop();
+                }
+                Op::Jump(target) => {
+                    pc = *target as usize;
+                    continue;
+                }
+                Op::JumpIfFalse(target) => match self.pop() {
+                    Value::Bool(true) => {}
+                    Value::Bool(false) => {
+                        pc = *target as usize;
+                        continue;
+                    }
+                    other => {
+                        return Err(RunError {
+                            message: format!(
+                                "`if` condition needs a bool, got {}",
+                                other.kind_name()
+                            ),
+                            span,
+                        })
+                    }
+                },
+                Op::GuardIfFalse(target) => match self.pop() {
+                    Value::Bool(true) => {}
+                    Value::Bool(false) => {
+                        pc = *target as usize;
+                        continue;
+                    }
+                    other => {
+                        return Err(RunError {
+                            message: format!(
+                                "`when` guard needs a bool, got {}",
+                                other.kind_name()
+                            ),
+                            span,
+                        })
+                    }
+                },
+                Op::JumpIfFalseKeep(target) => {
+                    if matches!(self.stack.last(), Some(Value::Bool(false))) {
+                        pc = *target as usize;
+                        continue;
+                    }
+                }
+                Op::JumpIfTrueKeep(target) => {
+                    if matches!(self.stack.last(), Some(Value::Bool(true))) {
+                        pc = *target as usize;
+                        continue;
+                    }
+                }
+                Op::Match { arm, next } => {
+                    let arm = &code.arms[*arm as usize];
+                    let scrutinee = self.stack.last().expect("the scrutinee was pushed");
+                    vars.clear();
+                    if !match_pattern(&arm.pattern, scrutinee, &mut vars) {
+                        pc = *next as usize;
+                        continue;
+                    }
+                    for (binding, value) in vars.drain(..) {
+                        if let Some(slot) = arm.slot(binding) {
+                            self.stack[base + slot as usize] = value;
+                        }
+                    }
+                }
+                Op::Take(arm) => {
+                    let scrutinee = self.pop();
+                    // Bindings are recorded only once the guard has passed —
+                    // a rejected arm was never taken. Re-matching recovers
+                    // them in the order the matched alternative binds them.
+                    if self.recorder.is_some() {
+                        let arm = &code.arms[*arm as usize];
+                        vars.clear();
+                        match_pattern(&arm.pattern, &scrutinee, &mut vars);
+                        let mut sites = Vec::new();
+                        pattern_binder_sites(&arm.pattern, &mut sites);
+                        for (binding, bound) in vars.drain(..) {
+                            if let Some(&(_, name, span)) =
+                                sites.iter().find(|(b, _, _)| *b == binding)
+                            {
+                                self.record_binding(binding, name, span, &bound);
+                            }
+                        }
+                    }
+                }
+                Op::NoMatch => {
+                    let value = self.pop();
+                    return Err(RunError {
+                        message: format!("no pattern matched {value}"),
+                        span,
+                    });
+                }
+                Op::Text(c) => {
+                    let text = code.consts[*c as usize].clone();
+                    if let Value::String(text) = &text {
+                        self.charge(text.len() as u64, span)?;
+                    }
+                    self.stack.push(text);
+                }
+                Op::Stringify => match self.pop() {
+                    Value::String(text) => {
+                        self.charge(text.len() as u64, span)?;
+                        self.stack.push(Value::String(text));
+                    }
+                    value => {
+                        let mut out = String::new();
+                        self.append_interpolated_value(&mut out, &value, span)?;
+                        self.stack.push(Value::String(Rc::from(out)));
+                    }
+                },
+                Op::Concat(n) => {
+                    let mut out = String::new();
+                    for part in self.pop_n(*n) {
+                        if let Value::String(text) = part {
+                            out.push_str(&text);
+                        }
+                    }
+                    self.stack.push(Value::String(Rc::from(out)));
+                }
+                Op::Return => return Ok(self.pop()),
             }
-            Value::Ctor { name, arity } => {
-                // Saturated by the arity gate above: every mismatch went to
-                // `call_curried`, which refuses it (constructors apply fully).
-                debug_assert_eq!(args.len(), *arity, "ctor mismatch reaches call_curried");
-                Ok(Value::Variant {
-                    ctor: name.clone(),
-                    args: Rc::new(args),
-                })
-            }
-            Value::Builtin(b) => self.call_builtin(*b, args, span),
-            Value::HostFn(path) => self.host.call(path, args, span),
-            other => Err(RunError {
-                message: format!("cannot call {}", other.kind_name()),
-                span,
-            }),
-        };
-        self.call_depth -= 1;
-        if let Ok(value) = &result {
-            self.trace_exit(value);
+            pc += 1;
         }
-        result
     }
 
     /// Currying's COLD paths — under-application, over-application, and
@@ -2013,7 +3403,15 @@ definition (a top-level initializer may only use globals defined above it)",
         self.call(saturated, rest, label, span, None)
     }
 
-    fn trace_enter(&mut self, callee: &str, args: &[Value]) {
+    fn trace_enter(&mut self, label: &str, args: &[Value], callee: &Value) {
+        if let Some(profiler) = &self.profiler {
+            let kind = match callee {
+                Value::Builtin(_) => CallKind::Builtin,
+                Value::HostFn(_) => CallKind::Host,
+                _ => CallKind::Function,
+            };
+            profiler.borrow_mut().enter(label, kind);
+        }
         if self.tracing == Tracing::Off {
             return;
         }
@@ -2025,12 +3423,13 @@ definition (a top-level initializer may only use globals defined above it)",
         }
         self.trace.push(TraceEvent::Enter {
             depth: self.call_depth,
-            callee: callee.to_string(),
+            callee: label.to_string(),
             args: args.iter().map(|a| a.to_string()).collect(),
         });
     }
 
     fn trace_exit(&mut self, result: &Value) {
+        self.profile_exit();
         if self.tracing == Tracing::Off {
             return;
         }
@@ -2048,6 +3447,14 @@ definition (a top-level initializer may only use globals defined above it)",
         });
     }
 
+    /// A call ended: the profiler's half of [`Self::trace_exit`], which a
+    /// failed call still owes it (the transcript just stops at the error).
+    fn profile_exit(&mut self) {
+        if let Some(profiler) = &self.profiler {
+            profiler.borrow_mut().exit();
+        }
+    }
+
     fn call_builtin(
         &mut self,
         b: Builtin,
@@ -2070,7 +3477,7 @@ definition (a top-level initializer may only use globals defined above it)",
                             Some(builtin_name(b)),
                         )?);
                     }
-                    Ok(Value::List(Rc::new(out)))
+                    Ok(Value::List(List::from(out)))
                 }
                 _ => err("List.map(fn, list) expects a function and a list".to_string()),
             },
@@ -2096,7 +3503,7 @@ definition (a top-level initializer may only use globals defined above it)",
                             }
                         }
                     }
-                    Ok(Value::List(Rc::new(out)))
+                    Ok(Value::List(List::from(out)))
                 }
                 _ => err("List.filter(fn, list) expects a function and a list".to_string()),
             },
@@ -2137,9 +3544,7 @@ definition (a top-level initializer may only use globals defined above it)",
                     // allocating in one step. (List.grid pays per cell via
                     // its closure calls.)
                     self.charge(count as u64, span)?;
-                    Ok(Value::List(Rc::new(
-                        (0..count).map(|i| Value::Number(i as f64)).collect(),
-                    )))
+                    Ok(Value::List((0..count).map(|i| Value::Number(i as f64)).collect()))
                 }
                 [Value::Number(n)] => err(format!(
                     "List.range needs a finite count up to 1000000, got {n}"
@@ -2181,9 +3586,9 @@ definition (a top-level initializer may only use globals defined above it)",
                                 Some(builtin_name(b)),
                             )?);
                         }
-                        grid.push(Value::List(Rc::new(row)));
+                        grid.push(Value::List(List::from(row)));
                     }
-                    Ok(Value::List(Rc::new(grid)))
+                    Ok(Value::List(List::from(grid)))
                 }
                 [f, Value::Number(_), Value::Number(_)] if is_function(f) => err(
                     "List.grid(fn, rows, cols) needs whole, non-negative counts with at \
@@ -2261,10 +3666,7 @@ most 1000000 cells"
                     // review probe: 26 nestings = 134M elements, seconds of
                     // wall-clock, ~56 charges).
                     self.charge((items.len() + other.len()) as u64, span)?;
-                    let mut out = Vec::with_capacity(items.len() + other.len());
-                    out.extend(items.iter().cloned());
-                    out.extend(other.iter().cloned());
-                    Ok(Value::List(Rc::new(out)))
+                    Ok(Value::List(items.concat(other)))
                 }
                 _ => err("List.append(other, list) expects two lists".to_string()),
             },
@@ -2290,7 +3692,7 @@ most 1000000 cells"
                             }
                         }
                     }
-                    Ok(Value::List(Rc::new(out)))
+                    Ok(Value::List(List::from(out)))
                 }
                 _ => err("List.flatten(list) expects one list of lists".to_string()),
             },
@@ -2352,9 +3754,9 @@ most 1000000 cells"
                     // O(n) copy of already-paid elements: charge n so a
                     // chain of unit-cost reverses can't go budget-quadratic.
                     self.charge(items.len() as u64, span)?;
-                    let mut out: Vec<Value> = items.iter().cloned().collect();
+                    let mut out = items.to_vec();
                     out.reverse();
-                    Ok(Value::List(Rc::new(out)))
+                    Ok(Value::List(List::from(out)))
                 }
                 _ => err("List.reverse(list) expects one list".to_string()),
             },
@@ -2440,7 +3842,7 @@ most 1000000 cells"
                             Some(builtin_name(b)),
                         )?);
                     }
-                    Ok(Value::List(Rc::new(out)))
+                    Ok(Value::List(List::from(out)))
                 }
                 _ => err("List.indexedMap(fn, list) expects a function and a list".to_string()),
             },
@@ -2490,9 +3892,7 @@ most 1000000 cells"
                     }
                     // `sort_by` is a stable merge sort — the guarantee above.
                     keyed.sort_by(|(a, _), (b, _)| sort_key_cmp(*a, *b));
-                    Ok(Value::List(Rc::new(
-                        keyed.into_iter().map(|(_, v)| v).collect(),
-                    )))
+                    Ok(Value::List(keyed.into_iter().map(|(_, v)| v).collect()))
                 }
                 _ => err("List.sortBy(fn, list) expects a function and a list".to_string()),
             },
@@ -2513,7 +3913,7 @@ most 1000000 cells"
                         .zip(other.iter())
                         .map(|(a, b)| Value::Tuple(Rc::new(vec![a.clone(), b.clone()])))
                         .collect();
-                    Ok(Value::List(Rc::new(pairs)))
+                    Ok(Value::List(List::from(pairs)))
                 }
                 _ => err("List.zip(other, list) expects two lists".to_string()),
             },
@@ -2530,21 +3930,20 @@ most 1000000 cells"
                     // `min(len)` bounds ±inf — the `as usize` can never
                     // saturate to an absurd length.
                     let k = n.max(0.0).min(items.len() as f64) as usize;
-                    // O(k) copy of already-paid elements (the reverse rule).
-                    // Charge BEFORE cloning: an exhausted budget must stop the
-                    // copy, not merely report it afterwards.
+                    // Charged as an O(k) copy of already-paid elements (the
+                    // reverse rule), though the persistent split shares all
+                    // but one leaf: the budget prices the result's size.
+                    // Charge BEFORE splitting: an exhausted budget must stop
+                    // the work, not merely report it afterwards.
                     let kept = if b == Builtin::ListTake {
                         k
                     } else {
                         items.len() - k
                     };
                     self.charge(kept as u64, span)?;
-                    let out: Vec<Value> = if b == Builtin::ListTake {
-                        items[..k].to_vec()
-                    } else {
-                        items[k..].to_vec()
-                    };
-                    Ok(Value::List(Rc::new(out)))
+                    let (head, tail) = items.split_at(k);
+                    let out = if b == Builtin::ListTake { head } else { tail };
+                    Ok(Value::List(out))
                 }
                 _ => err(format!(
                     "{}(count, list) expects a number and a list",
@@ -2601,25 +4000,25 @@ most 1000000 cells"
                             }
                         }
                     }
-                    Ok(Value::List(Rc::new(out)))
+                    Ok(Value::List(List::from(out)))
                 }
                 _ => err("List.concatMap(fn, list) expects a function and a list".to_string()),
             },
             Builtin::MapEmpty => match args.as_slice() {
-                [] => Ok(Value::Map(Rc::new(Vec::new()))),
+                [] => Ok(Value::Map(Map::new())),
                 _ => unreachable!("builtin arity checked before dispatch"),
             },
-            // Maps are immutable sorted vectors. Lookup is binary search;
-            // updates copy one canonical vector and preserve key order.
+            // Maps are persistent B-trees in canonical key order (see
+            // crate::persistent). Every key comparison is charged before it
+            // is made — strings can share an arbitrarily long prefix — and
+            // an update charges the path it copies before copying it.
             // Subject-LAST throughout, so `map |> Map.get(key)` works.
             Builtin::MapGet => match args.as_slice() {
                 [key, Value::Map(entries)] => {
                     let key = map_key_from_value(key, builtin_name(b))
                         .map_err(|message| RunError { message, span })?;
-                    let found = self.map_search(entries, &key, span)?;
-                    Ok(option_value(
-                        found.ok().map(|index| entries[index].1.clone()),
-                    ))
+                    let found = entries.lookup(&key, |units| self.charge(units, span))?;
+                    Ok(option_value(found.cloned()))
                 }
                 _ => err("Map.get(key, map) expects a key and a map".to_string()),
             },
@@ -2627,21 +4026,8 @@ most 1000000 cells"
                 [key, value, Value::Map(entries)] => {
                     let key = map_key_from_value(key, builtin_name(b))
                         .map_err(|message| RunError { message, span })?;
-                    let found = self.map_search(entries, &key, span)?;
-                    let out_len = if found.is_ok() {
-                        entries.len()
-                    } else {
-                        entries.len().saturating_add(1)
-                    };
-                    // Pre-charge the immutable copy so a low-budget tool
-                    // cannot allocate it before discovering it is over fuel.
-                    self.charge(out_len as u64, span)?;
-                    let mut out = entries.as_ref().clone();
-                    match found {
-                        Ok(index) => out[index].1 = value.clone(),
-                        Err(index) => out.insert(index, (key, value.clone())),
-                    }
-                    Ok(Value::Map(Rc::new(out)))
+                    let out = entries.insert(key, value.clone(), |units| self.charge(units, span))?;
+                    Ok(Value::Map(out))
                 }
                 _ => {
                     err("Map.insert(key, value, map) expects a key, a value, and a map".to_string())
@@ -2651,14 +4037,8 @@ most 1000000 cells"
                 [key, Value::Map(entries)] => {
                     let key = map_key_from_value(key, builtin_name(b))
                         .map_err(|message| RunError { message, span })?;
-                    let found = self.map_search(entries, &key, span)?;
-                    let Ok(index) = found else {
-                        return Ok(Value::Map(entries.clone()));
-                    };
-                    self.charge(entries.len().saturating_sub(1) as u64, span)?;
-                    let mut out = entries.as_ref().clone();
-                    out.remove(index);
-                    Ok(Value::Map(Rc::new(out)))
+                    let out = entries.remove(&key, |units| self.charge(units, span))?;
+                    Ok(Value::Map(out.unwrap_or_else(|| entries.clone())))
                 }
                 _ => err("Map.remove(key, map) expects a key and a map".to_string()),
             },
@@ -2666,17 +4046,15 @@ most 1000000 cells"
                 [key, Value::Map(entries)] => {
                     let key = map_key_from_value(key, builtin_name(b))
                         .map_err(|message| RunError { message, span })?;
-                    let found = self.map_search(entries, &key, span)?;
-                    Ok(Value::Bool(found.is_ok()))
+                    let found = entries.lookup(&key, |units| self.charge(units, span))?;
+                    Ok(Value::Bool(found.is_some()))
                 }
                 _ => err("Map.member(key, map) expects a key and a map".to_string()),
             },
             Builtin::MapValues => match args.as_slice() {
                 [Value::Map(entries)] => {
                     self.charge(entries.len() as u64, span)?;
-                    Ok(Value::List(Rc::new(
-                        entries.iter().map(|(_, value)| value.clone()).collect(),
-                    )))
+                    Ok(Value::List(entries.iter().map(|(_, value)| value.clone()).collect()))
                 }
                 _ => err("Map.values(map) expects one map".to_string()),
             },
@@ -2684,23 +4062,21 @@ most 1000000 cells"
                 [Value::Map(entries)] => {
                     // Each entry materializes a two-cell tuple.
                     self.charge((entries.len() as u64).saturating_mul(2), span)?;
-                    Ok(Value::List(Rc::new(
+                    Ok(Value::List(
                         entries
                             .iter()
                             .map(|(key, value)| {
                                 Value::Tuple(Rc::new(vec![key.to_value(), value.clone()]))
                             })
                             .collect(),
-                    )))
+                    ))
                 }
                 _ => err("Map.toList(map) expects one map".to_string()),
             },
             Builtin::MapFromList => match args.as_slice() {
                 [Value::List(items)] => {
-                    // Charge entry cloning up front. After validating the
-                    // keys, refuse an over-budget sort before its first
-                    // comparison. The preflight is conservative; actual
-                    // comparison work is charged afterward.
+                    // Charge entry cloning up front, then validate every key
+                    // before sorting.
                     self.charge(items.len() as u64, span)?;
                     let mut sorted = Vec::with_capacity(items.len());
                     let mut max_comparison_units = 1u64;
@@ -2727,23 +4103,181 @@ most 1000000 cells"
                             max_comparison_units.max(key.comparison_unit_ceiling());
                         sorted.push((key, value.clone()));
                     }
-                    let comparison_count_ceiling = stable_sort_comparison_ceiling(items.len())
-                        .saturating_add(
-                            u64::try_from(items.len().saturating_sub(1))
-                                .unwrap_or(u64::MAX),
-                        );
-                    let comparison_work_ceiling =
-                        comparison_count_ceiling.saturating_mul(max_comparison_units);
-                    self.preflight_charge(comparison_work_ceiling, span)?;
-                    let (out, comparison_work) = canonicalize_map_entries(sorted);
-                    debug_assert!(comparison_work <= comparison_work_ceiling);
-                    self.charge(comparison_work, span)?;
-                    Ok(Value::Map(Rc::new(out)))
+                    let out = self.canonicalize_charged(sorted, max_comparison_units, span)?;
+                    Ok(Value::Map(Map::from_canonical(out)))
                 }
                 _ => {
                     err("Map.fromList(entries) expects one list of (key, value) tuples".to_string())
                 }
             },
+            Builtin::SetEmpty => match args.as_slice() {
+                [] => Ok(Value::Set(Set::new())),
+                _ => unreachable!("builtin arity checked before dispatch"),
+            },
+            // Sets are maps without values (crate::persistent::Set): the same
+            // element bound as Map keys, the same canonical order, and the
+            // same charging. Subject-LAST, so `seen |> Set.insert(cell)`.
+            Builtin::SetInsert => match args.as_slice() {
+                [key, Value::Set(keys)] => {
+                    let key = map_key_from_value(key, builtin_name(b))
+                        .map_err(|message| RunError { message, span })?;
+                    let out = keys.insert(key, |units| self.charge(units, span))?;
+                    Ok(Value::Set(out))
+                }
+                _ => err("Set.insert(element, set) expects an element and a set".to_string()),
+            },
+            Builtin::SetRemove => match args.as_slice() {
+                [key, Value::Set(keys)] => {
+                    let key = map_key_from_value(key, builtin_name(b))
+                        .map_err(|message| RunError { message, span })?;
+                    let out = keys.remove(&key, |units| self.charge(units, span))?;
+                    Ok(Value::Set(out.unwrap_or_else(|| keys.clone())))
+                }
+                _ => err("Set.remove(element, set) expects an element and a set".to_string()),
+            },
+            Builtin::SetMember => match args.as_slice() {
+                [key, Value::Set(keys)] => {
+                    let key = map_key_from_value(key, builtin_name(b))
+                        .map_err(|message| RunError { message, span })?;
+                    let found = keys.contains(&key, |units| self.charge(units, span))?;
+                    Ok(Value::Bool(found))
+                }
+                _ => err("Set.member(element, set) expects an element and a set".to_string()),
+            },
+            // The set algebra is one linear merge of the two canonical key
+            // sequences, charged per comparison and per kept element.
+            // `Set.difference(other, set)` is `set` without `other`'s
+            // elements, so `visible |> Set.difference(seen)` reads in order.
+            Builtin::SetUnion | Builtin::SetIntersect | Builtin::SetDifference => {
+                match args.as_slice() {
+                    [Value::Set(other), Value::Set(keys)] => {
+                        let charge = |units| self.charge(units, span);
+                        let out = match b {
+                            Builtin::SetUnion => keys.union(other, charge)?,
+                            Builtin::SetIntersect => keys.intersect(other, charge)?,
+                            _ => keys.difference(other, charge)?,
+                        };
+                        Ok(Value::Set(out))
+                    }
+                    _ => err(format!("{}(other, set) expects two sets", builtin_name(b))),
+                }
+            }
+            Builtin::SetToList => match args.as_slice() {
+                [Value::Set(keys)] => {
+                    self.charge(keys.len() as u64, span)?;
+                    Ok(Value::List(keys.iter().map(MapKey::to_value).collect()))
+                }
+                _ => err("Set.toList(set) expects one set".to_string()),
+            },
+            Builtin::SetFromList => match args.as_slice() {
+                [Value::List(items)] => {
+                    self.charge(items.len() as u64, span)?;
+                    let mut sorted = Vec::with_capacity(items.len());
+                    let mut max_comparison_units = 1u64;
+                    for (index, item) in items.iter().enumerate() {
+                        let key = map_key_from_value(item, builtin_name(b)).map_err(|message| {
+                            RunError {
+                                message: format!("{message} at element {index}"),
+                                span,
+                            }
+                        })?;
+                        max_comparison_units =
+                            max_comparison_units.max(key.comparison_unit_ceiling());
+                        sorted.push((key, Value::Bool(true)));
+                    }
+                    let out = self.canonicalize_charged(sorted, max_comparison_units, span)?;
+                    Ok(Value::Set(Set::from_canonical(
+                        out.into_iter().map(|(key, _)| key).collect(),
+                    )))
+                }
+                _ => err("Set.fromList(elements) expects one list".to_string()),
+            },
+            // Arrays share the list rope (crate::persistent::List), so
+            // `fromList`/`toList` are O(1) conversions and `get`/`set` are
+            // O(log n). Indices are ints: an index outside the array is
+            // absence for `get`, but a BUG for `set`, which errors rather
+            // than quietly dropping the write.
+            Builtin::ArrayMake => match args.as_slice() {
+                [Value::Int(n), value] if (0..=1_000_000).contains(n) => {
+                    // No per-element eval, like `List.range`: charge the
+                    // element count so one step can't allocate a budget's
+                    // worth of cells.
+                    self.charge(*n as u64, span)?;
+                    Ok(Value::Array(List::from(vec![value.clone(); *n as usize])))
+                }
+                [Value::Int(n), _] => err(format!(
+                    "Array.make needs a length from 0 to 1000000, got {n}"
+                )),
+                _ => err("Array.make(length, value) expects an int and a value".to_string()),
+            },
+            Builtin::ArrayGet => match args.as_slice() {
+                [Value::Int(i), Value::Array(items)] => Ok(option_value(
+                    usize::try_from(*i).ok().and_then(|i| items.get(i)).cloned(),
+                )),
+                _ => err("Array.get(index, array) expects an int and an array".to_string()),
+            },
+            Builtin::ArraySet => match args.as_slice() {
+                [Value::Int(i), value, Value::Array(items)] => {
+                    match usize::try_from(*i).ok().and_then(|i| items.set(i, value.clone())) {
+                        Some(out) => Ok(Value::Array(out)),
+                        None => err(format!(
+                            "Array.set index {i} is out of range for an array of length {}",
+                            items.len()
+                        )),
+                    }
+                }
+                _ => err(
+                    "Array.set(index, value, array) expects an int, a value, and an array"
+                        .to_string(),
+                ),
+            },
+            Builtin::ArrayLength => match args.as_slice() {
+                [Value::Array(items)] => Ok(Value::Int(items.len() as i64)),
+                _ => err("Array.length(array) expects one array".to_string()),
+            },
+            Builtin::ArrayMap => match args.as_slice() {
+                [f, Value::Array(items)] => {
+                    let mut out = Vec::with_capacity(items.len());
+                    for (i, item) in items.iter().enumerate() {
+                        out.push(self.call(
+                            f.clone(),
+                            vec![item.clone()],
+                            element_label(b, i),
+                            span,
+                            Some(builtin_name(b)),
+                        )?);
+                    }
+                    Ok(Value::Array(List::from(out)))
+                }
+                _ => err("Array.map(fn, array) expects a function and an array".to_string()),
+            },
+            Builtin::ArrayFold => match args.as_slice() {
+                [f, init, Value::Array(items)] => {
+                    let mut acc = init.clone();
+                    for (i, item) in items.iter().enumerate() {
+                        acc = self.call(
+                            f.clone(),
+                            vec![acc, item.clone()],
+                            element_label(b, i),
+                            span,
+                            Some(builtin_name(b)),
+                        )?;
+                    }
+                    Ok(acc)
+                }
+                _ => err(
+                    "Array.fold(fn, init, array) expects a function, an initial value, and an array"
+                        .to_string(),
+                ),
+            },
+            Builtin::ArrayFromList => match args.as_slice() {
+                [Value::List(items)] => Ok(Value::Array(items.clone())),
+                _ => err("Array.fromList(list) expects one list".to_string()),
+            },
+            Builtin::ArrayToList => match args.as_slice() {
+                [Value::Array(items)] => Ok(Value::List(items.clone())),
+                _ => err("Array.toList(array) expects one array".to_string()),
+            },
             Builtin::TextConcat => match args.as_slice() {
                 [Value::String(a), Value::String(b)] => {
                     // Growth builtin: charge output BYTES (the List.append
@@ -2810,7 +4344,7 @@ most 1000000 cells"
                     self.charge(s.len() as u64, span)?;
                     let parts: Vec<Value> =
                         s.split(sep.as_ref()).map(|p| Value::String(Rc::from(p))).collect();
-                    Ok(Value::List(Rc::new(parts)))
+                    Ok(Value::List(List::from(parts)))
                 }
                 _ => err("Text.split(sep, s) expects two strings".to_string()),
             },
@@ -2886,7 +4420,7 @@ most 1000000 cells"
                             Value::String(Rc::from(c.encode_utf8(&mut buf) as &str))
                         })
                         .collect();
-                    Ok(Value::List(Rc::new(chars)))
+                    Ok(Value::List(List::from(chars)))
                 }
                 _ => err("Text.chars(s) expects one string".to_string()),
             },
@@ -3064,6 +4598,24 @@ got edge0 {edge0} and edge1 {edge1}"
                 })),
                 _ => err("Math.sign(n) expects one number".to_string()),
             },
+            // The only bridges between the two number kinds. `toInt`
+            // truncates toward zero (`Math.floor` first for the other
+            // rounding), and refuses what no `int` can hold.
+            Builtin::MathToFloat => match args.as_slice() {
+                [Value::Int(n)] => Ok(Value::Number(*n as f64)),
+                _ => err("Math.toFloat(n) expects one int".to_string()),
+            },
+            Builtin::MathToInt => match args.as_slice() {
+                // `i64::MAX as f64` rounds UP to 2^63, so the upper bound is
+                // exclusive.
+                [Value::Number(n)] if n.is_finite() && *n >= i64::MIN as f64 && *n < i64::MAX as f64 => {
+                    Ok(Value::Int(n.trunc() as i64))
+                }
+                [Value::Number(n)] => err(format!(
+                    "Math.toInt({n}) has no int answer (ints are 64-bit and finite)"
+                )),
+                _ => err("Math.toInt(n) expects one float".to_string()),
+            },
             // `Math.asin` / `Math.acos` return NaN OUTSIDE [-1, 1] rather
             // than clamping, so a genuinely wrong input stays visible. The
             // common trap is feeding in a dot product that floating-point
@@ -3102,8 +4654,12 @@ got edge0 {edge0} and edge1 {edge1}"
                 [Value::Number(n)] => Ok(Value::Number(n.sqrt())),
                 _ => err("Math.sqrt(n) expects one number".to_string()),
             },
+            // `abs`, `mod`, `min`, and `max` take ints too (the checker
+            // reads an int argument as the int form); int `abs` and `mod`
+            // are checked like the operators.
             Builtin::MathAbs => match args.as_slice() {
                 [Value::Number(n)] => Ok(Value::Number(n.abs())),
+                [Value::Int(n)] => int_result(n.checked_abs(), "Math.abs", span),
                 _ => err("Math.abs(n) expects one number".to_string()),
             },
             Builtin::MathFloor => match args.as_slice() {
@@ -3123,14 +4679,17 @@ got edge0 {edge0} and edge1 {edge1}"
             // non-finite numbers.
             Builtin::MathMod => match args.as_slice() {
                 [Value::Number(a), Value::Number(b)] => Ok(Value::Number(a.rem_euclid(*b))),
+                [Value::Int(a), Value::Int(b)] => int_arith(crate::ast::BinOp::Rem, *a, *b, span),
                 _ => err("Math.mod(a, b) expects two numbers".to_string()),
             },
             Builtin::MathMin => match args.as_slice() {
                 [Value::Number(a), Value::Number(b)] => Ok(Value::Number(a.min(*b))),
+                [Value::Int(a), Value::Int(b)] => Ok(Value::Int(*a.min(b))),
                 _ => err("Math.min(a, b) expects two numbers".to_string()),
             },
             Builtin::MathMax => match args.as_slice() {
                 [Value::Number(a), Value::Number(b)] => Ok(Value::Number(a.max(*b))),
+                [Value::Int(a), Value::Int(b)] => Ok(Value::Int(*a.max(b))),
                 _ => err("Math.max(a, b) expects two numbers".to_string()),
             },
             // pow(base, exp) == base ^ exp (standard math argument order).
@@ -3242,10 +4801,11 @@ fn as_bool(value: &Value, span: Span) -> Result<bool, RunError> {
 }
 
 /// Does `pattern` match `value`? Appends each pattern variable's binding on
-/// the way (bindings are only used if the whole pattern matched — a pattern
-/// either fully matches or its arm is skipped, and sub-patterns are leaves,
-/// so a partial append can never observe a half-match). Pure: literal
-/// patterns compare primitively, so no function-equality error can arise.
+/// the way. A nested pattern may append some bindings before a deeper
+/// sub-pattern fails; that is harmless because the caller uses `vars` only
+/// when the whole pattern matched (a failed arm's partial bindings are
+/// discarded with it). Pure: literal patterns compare primitively, so no
+/// function-equality error can arise.
 fn match_pattern(pattern: &Pattern, value: &Value, vars: &mut Vec<(BindingId, Value)>) -> bool {
     match &pattern.kind {
         PatternKind::Wildcard => true,
@@ -3298,7 +4858,7 @@ fn match_pattern(pattern: &Pattern, value: &Value, vars: &mut Vec<(BindingId, Va
                     return false;
                 }
                 if let Some(tail) = tail {
-                    let rest = Value::List(Rc::new(vals[items.len()..].to_vec()));
+                    let rest = Value::List(vals.split_at(items.len()).1);
                     match_pattern(tail, &rest, vars)
                 } else {
                     true
@@ -3306,7 +4866,27 @@ fn match_pattern(pattern: &Pattern, value: &Value, vars: &mut Vec<(BindingId, Va
             }
             _ => false,
         },
+        // Every named field must exist (fields the pattern doesn't name are
+        // ignored); a missing one is a non-match, like a mismatched ctor.
+        PatternKind::Record(fields) => match value {
+            Value::Record(vals) => fields.iter().all(|field| {
+                vals.iter()
+                    .find(|(name, _)| *name == field.name)
+                    .is_some_and(|(_, v)| match_pattern(&field.pattern, v, vars))
+            }),
+            _ => false,
+        },
+        // A failed alternative's partial bindings are dropped before the
+        // next is tried — the arm's body must only see the matching one's.
+        PatternKind::Or(alternatives) => {
+            let before = vars.len();
+            alternatives.iter().any(|alternative| {
+                vars.truncate(before);
+                match_pattern(alternative, value, vars)
+            })
+        }
         PatternKind::Number(n) => matches!(value, Value::Number(v) if v == n),
+        PatternKind::Int(n) => matches!(value, Value::Int(v) if v == n),
         PatternKind::Bool(b) => matches!(value, Value::Bool(v) if v == b),
         PatternKind::String(s) => matches!(value, Value::String(v) if v.as_ref() == s),
     }
@@ -3315,7 +4895,10 @@ fn match_pattern(pattern: &Pattern, value: &Value, vars: &mut Vec<(BindingId, Va
 /// Each pattern variable's `(binding, name, span)` — the recorder's per-site
 /// key/label/location for match binders (the `Var` pattern's own span is the
 /// name's span), mirroring `goto::pattern_binders`'s traversal.
-fn pattern_binder_sites<'a>(pattern: &'a Pattern, out: &mut Vec<(BindingId, &'a str, Span)>) {
+pub(crate) fn pattern_binder_sites<'a>(
+    pattern: &'a Pattern,
+    out: &mut Vec<(BindingId, &'a str, Span)>,
+) {
     match &pattern.kind {
         PatternKind::Var { binding, name } => out.push((*binding, name, pattern.span)),
         PatternKind::Ctor { args, .. } | PatternKind::Tuple(args) => {
@@ -3331,8 +4914,16 @@ fn pattern_binder_sites<'a>(pattern: &'a Pattern, out: &mut Vec<(BindingId, &'a
                 pattern_binder_sites(tail, out);
             }
         }
+        PatternKind::Record(fields) => {
+            for field in fields {
+                pattern_binder_sites(&field.pattern, out);
+            }
+        }
+        // Every alternative binds the same ids; the first is canonical.
+        PatternKind::Or(alternatives) => pattern_binder_sites(&alternatives[0], out),
         PatternKind::Wildcard
         | PatternKind::Number(_)
+        | PatternKind::Int(_)
         | PatternKind::Bool(_)
         | PatternKind::String(_) => {}
     }
@@ -3349,6 +4940,49 @@ fn pattern_binder_sites<'a>(pattern: &'a Pattern, out: &mut Vec<(BindingId, &'a
 /// recursion limit), and this runs inside editor/tooling processes where a
 /// stack overflow is a host crash. Pairs push in reverse so comparison
 /// order stays left-to-right (first mismatch/error is the leftmost).
+/// Int arithmetic is EXACT or an error: overflow stops the run rather than
+/// wrapping, and `/` is Euclidean division — the quotient whose remainder
+/// `a - (a / b) * b` is never negative, the convention `Math.mod` follows for
+/// floats. Dividing by zero is an error too; there is no `inf` to answer
+/// with.
+fn int_arith(op: crate::ast::BinOp, a: i64, b: i64, span: Span) -> Result<Value, RunError> {
+    use crate::ast::BinOp;
+    let result = match op {
+        BinOp::Add => a.checked_add(b),
+        BinOp::Sub => a.checked_sub(b),
+        BinOp::Mul => a.checked_mul(b),
+        _ if b == 0 => {
+            return Err(RunError {
+                message: format!("int division by zero ({a} {} 0)", op.symbol()),
+                span,
+            })
+        }
+        BinOp::Rem => a.checked_rem_euclid(b),
+        _ => a.checked_div_euclid(b),
+    };
+    int_result(result, op.symbol(), span)
+}
+
+/// A checked int operation's answer, or the overflow error naming it.
+fn int_result(result: Option<i64>, symbol: &str, span: Span) -> Result<Value, RunError> {
+    result.map(Value::Int).ok_or_else(|| RunError {
+        message: format!("int overflow in `{symbol}` (ints are 64-bit and never wrap)"),
+        span,
+    })
+}
+
+/// Int and float never meet implicitly — the checker refuses the node, and
+/// an unchecked run stops here with the same fix.
+fn mixed_numbers(op: crate::ast::BinOp, span: Span) -> RunError {
+    RunError {
+        message: format!(
+            "`{}` mixes int and float — convert one side with `Math.toFloat` or `Math.toInt`",
+            op.symbol()
+        ),
+        span,
+    }
+}
+
 fn value_eq(
     a: &Value,
     b: &Value,
@@ -3387,6 +5021,11 @@ fn value_eq(
                     return Ok(false);
                 }
             }
+            (Value::Int(x), Value::Int(y)) => {
+                if x != y {
+                    return Ok(false);
+                }
+            }
             (Value::String(x), Value::String(y)) => {
                 if x != y {
                     return Ok(false);
@@ -3398,7 +5037,17 @@ fn value_eq(
                 }
             }
             // Structural, element-wise; arity difference is simply unequal.
-            (Value::List(xs), Value::List(ys)) | (Value::Tuple(xs), Value::Tuple(ys)) => {
+            // The persistent collections iterate front to back only: push
+            // their pairs in order, then reverse that run in place.
+            (Value::List(xs), Value::List(ys)) | (Value::Array(xs), Value::Array(ys)) => {
+                if xs.len() != ys.len() {
+                    return Ok(false);
+                }
+                let start = work.len();
+                work.extend(xs.iter().zip(ys.iter()).map(|(x, y)| Work::Pair(x, y)));
+                work[start..].reverse();
+            }
+            (Value::Tuple(xs), Value::Tuple(ys)) => {
                 if xs.len() != ys.len() {
                     return Ok(false);
                 }
@@ -3408,10 +5057,20 @@ fn value_eq(
                 if xs.len() != ys.len() {
                     return Ok(false);
                 }
-                for ((xk, xv), (yk, yv)) in xs.iter().zip(ys.iter()).rev() {
-                    work.push(Work::Pair(xv, yv));
+                let start = work.len();
+                for ((xk, xv), (yk, yv)) in xs.iter().zip(ys.iter()) {
                     work.push(Work::Key(xk, yk));
+                    work.push(Work::Pair(xv, yv));
+                }
+                work[start..].reverse();
+            }
+            (Value::Set(xs), Value::Set(ys)) => {
+                if xs.len() != ys.len() {
+                    return Ok(false);
                 }
+                let start = work.len();
+                work.extend(xs.iter().zip(ys.iter()).map(|(x, y)| Work::Key(x, y)));
+                work[start..].reverse();
             }
             (Value::Record(xs), Value::Record(ys)) => {
                 if xs.len() != ys.len() {
@@ -3520,15 +5179,22 @@ fn sort_key_cmp(a: f64, b: f64) -> std::cmp::Ordering {
 /// refused because NaN has no equality-compatible ordering and cannot cross
 /// the plain-data wire.
 fn map_key_from_value(value: &Value, operation: &str) -> Result<MapKey, String> {
+    // A set's keys are its elements.
+    let noun = if operation.starts_with("Set.") {
+        "elements"
+    } else {
+        "keys"
+    };
     match value {
         Value::Bool(value) => Ok(MapKey::Bool(*value)),
+        Value::Int(value) => Ok(MapKey::Int(*value)),
         Value::Number(value) if value.is_finite() => Ok(MapKey::Number(*value + 0.0)),
         Value::Number(value) => Err(format!(
-            "{operation} keys must be finite; got {value} (NaN/Infinity cannot be map keys)"
+            "{operation} {noun} must be finite; got {value} (NaN/Infinity cannot be map keys)"
         )),
         Value::String(value) => Ok(MapKey::String(value.clone())),
         other => Err(format!(
-            "{operation} keys must be bools, finite floats, or strings; got {}",
+            "{operation} {noun} must be bools, ints, finite floats, or strings; got {}",
             other.kind_name()
         )),
     }
@@ -3645,7 +5311,9 @@ pub fn builtin_arity(b: Builtin) -> usize {
         | Builtin::MathClamp
         | Builtin::MathLerp
         | Builtin::MathSmoothstep
-        | Builtin::MapInsert => 3,
+        | Builtin::MapInsert
+        | Builtin::ArraySet
+        | Builtin::ArrayFold => 3,
         Builtin::ListMap
         | Builtin::ListFilter
         | Builtin::ListAppend
@@ -3673,7 +5341,16 @@ pub fn builtin_arity(b: Builtin) -> usize {
         | Builtin::DebugLog
         | Builtin::MapGet
         | Builtin::MapRemove
-        | Builtin::MapMember => 2,
+        | Builtin::MapMember
+        | Builtin::SetInsert
+        | Builtin::SetRemove
+        | Builtin::SetMember
+        | Builtin::SetUnion
+        | Builtin::SetIntersect
+        | Builtin::SetDifference
+        | Builtin::ArrayMake
+        | Builtin::ArrayGet
+        | Builtin::ArrayMap => 2,
         Builtin::ListRange
         | Builtin::ListMaximum
         | Builtin::ListMinimum
@@ -3707,12 +5384,19 @@ pub fn builtin_arity(b: Builtin) -> usize {
         | Builtin::MathLog
         | Builtin::MathExp
         | Builtin::MathSign
+        | Builtin::MathToFloat
+        | Builtin::MathToInt
         | Builtin::MapValues
         | Builtin::MapToList
-        | Builtin::MapFromList => 1,
+        | Builtin::MapFromList
+        | Builtin::SetToList
+        | Builtin::SetFromList
+        | Builtin::ArrayLength
+        | Builtin::ArrayFromList
+        | Builtin::ArrayToList => 1,
         // `Math.pi` resolves straight to a number in `eval` (it's a constant,
         // never a callable value), so this arity is never consulted.
-        Builtin::MathPi | Builtin::MapEmpty => 0,
+        Builtin::MathPi | Builtin::MapEmpty | Builtin::SetEmpty => 0,
         Builtin::RandomSeed | Builtin::RandomStep => 1,
     }
 }
@@ -3763,6 +5447,23 @@ pub enum Builtin {
     MapValues,
     MapToList,
     MapFromList,
+    SetEmpty,
+    SetInsert,
+    SetRemove,
+    SetMember,
+    SetUnion,
+    SetIntersect,
+    SetDifference,
+    SetToList,
+    SetFromList,
+    ArrayMake,
+    ArrayGet,
+    ArraySet,
+    ArrayLength,
+    ArrayMap,
+    ArrayFold,
+    ArrayFromList,
+    ArrayToList,
     TextConcat,
     TextFromFloat,
     TextFixed,
@@ -3790,6 +5491,8 @@ pub enum Builtin {
     MathLog,
     MathExp,
     MathSign,
+    MathToFloat,
+    MathToInt,
     RandomSeed,
     RandomStep,
     RandomRange,
@@ -3815,7 +5518,7 @@ pub enum Builtin {
 /// Returns `(value, nextSeed)` with `value` in `[0, 1)`.
 fn random_step(seed: f64) -> (f64, f64) {
     // Odd 52-bit golden-ratio gamma — a large stride with full period 2^52.
-    const GAMMA: u64 = 0x9E37_79B9_7F4A_7;
+    const GAMMA: u64 = 0x9_E377_9B97_F4A7;
 
     // Fold whatever came in down to the counter range. Values WE produced are
     // already non-negative integers in `[0, 2^52)`, for which `rem_euclid` is
@@ -3870,6 +5573,22 @@ fn fork_seed(i: f64, seed: f64) -> f64 {
     (mix64(counter ^ index) & MASK52) as f64
 }
 
+/// The eval-depth cap error (see [`MAX_EVAL_DEPTH`]). `#[cold]` like
+/// [`unknown_external_error`]: its formatting stays out of the hot frames.
+#[cold]
+#[inline(never)]
+fn depth_error(span: Span) -> RunError {
+    RunError {
+        message: format!(
+            "evaluation nested too deeply (exceeded the depth cap of {MAX_EVAL_DEPTH}): \
+deep recursion, or deeply nested expressions. Deep list iteration belongs in the builtin list \
+functions (List.fold/map/filter/any/all/length/…), which loop in the interpreter and consume no \
+evaluation depth."
+        ),
+        span,
+    }
+}
+
 /// Resolve an [`ExprKind::External`] path against the registry.
 /// The error for an external that resolved nowhere. A typo'd member of a
 /// BUILTIN namespace (`List.fooo`) is a user error everywhere — it must not
@@ -3892,7 +5611,9 @@ fn unknown_external_error(path: &[String], joined: String, span: Span) -> RunErr
 /// these is a typo (a plain error), never a host-provided external — the
 /// distinction the unknown-external error message (and through it the
 /// expect gutter's `unrunnable` classification) rests on.
-pub const BUILTIN_NAMESPACES: &[&str] = &["List", "Map", "Text", "Math", "Random", "Debug"];
+pub const BUILTIN_NAMESPACES: &[&str] = &[
+    "List", "Array", "Map", "Set", "Text", "Math", "Random", "Debug",
+];
 
 /// The complete builtin registry as a list. Hand-listed because [`Builtin`] is
 /// not iterable — keep in sync with the enum (`builtins_list_is_exhaustive`
@@ -3901,7 +5622,7 @@ pub const BUILTIN_NAMESPACES: &[&str] = &["List", "Map", "Text", "Math", "Random
 /// [`builtin`] so the members the CHECKER knows about (for its
 /// unknown-member diagnostic and its suggestions) and the ones the
 /// interpreter DISPATCHES come from one place.
-pub const ALL_BUILTINS: [Builtin; 76] = [
+pub const ALL_BUILTINS: [Builtin; 95] = [
     Builtin::ListMap,
     Builtin::ListFilter,
     Builtin::ListFold,
@@ -3935,6 +5656,23 @@ pub const ALL_BUILTINS: [Builtin; 76] = [
     Builtin::MapValues,
     Builtin::MapToList,
     Builtin::MapFromList,
+    Builtin::SetEmpty,
+    Builtin::SetInsert,
+    Builtin::SetRemove,
+    Builtin::SetMember,
+    Builtin::SetUnion,
+    Builtin::SetIntersect,
+    Builtin::SetDifference,
+    Builtin::SetToList,
+    Builtin::SetFromList,
+    Builtin::ArrayMake,
+    Builtin::ArrayGet,
+    Builtin::ArraySet,
+    Builtin::ArrayLength,
+    Builtin::ArrayMap,
+    Builtin::ArrayFold,
+    Builtin::ArrayFromList,
+    Builtin::ArrayToList,
     Builtin::MathSin,
     Builtin::MathCos,
     Builtin::MathSqrt,
@@ -3973,6 +5711,8 @@ pub const ALL_BUILTINS: [Builtin; 76] = [
     Builtin::MathLog,
     Builtin::MathExp,
     Builtin::MathSign,
+    Builtin::MathToFloat,
+    Builtin::MathToInt,
     Builtin::RandomSeed,
     Builtin::RandomStep,
     Builtin::RandomRange,
@@ -4028,6 +5768,23 @@ pub fn builtin(path: &[String]) -> Option<Builtin> {
         "Map.values" => Builtin::MapValues,
         "Map.toList" => Builtin::MapToList,
         "Map.fromList" => Builtin::MapFromList,
+        "Set.empty" => Builtin::SetEmpty,
+        "Set.insert" => Builtin::SetInsert,
+        "Set.remove" => Builtin::SetRemove,
+        "Set.member" => Builtin::SetMember,
+        "Set.union" => Builtin::SetUnion,
+        "Set.intersect" => Builtin::SetIntersect,
+        "Set.difference" => Builtin::SetDifference,
+        "Set.toList" => Builtin::SetToList,
+        "Set.fromList" => Builtin::SetFromList,
+        "Array.make" => Builtin::ArrayMake,
+        "Array.get" => Builtin::ArrayGet,
+        "Array.set" => Builtin::ArraySet,
+        "Array.length" => Builtin::ArrayLength,
+        "Array.map" => Builtin::ArrayMap,
+        "Array.fold" => Builtin::ArrayFold,
+        "Array.fromList" => Builtin::ArrayFromList,
+        "Array.toList" => Builtin::ArrayToList,
         "Text.concat" => Builtin::TextConcat,
         "Text.fromFloat" => Builtin::TextFromFloat,
         "Text.fixed" => Builtin::TextFixed,
@@ -4055,6 +5812,8 @@ pub fn builtin(path: &[String]) -> Option<Builtin> {
         "Math.log" => Builtin::MathLog,
         "Math.exp" => Builtin::MathExp,
         "Math.sign" => Builtin::MathSign,
+        "Math.toFloat" => Builtin::MathToFloat,
+        "Math.toInt" => Builtin::MathToInt,
         "Math.sin" => Builtin::MathSin,
         "Math.cos" => Builtin::MathCos,
         "Math.sqrt" => Builtin::MathSqrt,
@@ -4111,6 +5870,23 @@ pub fn builtin_name(b: Builtin) -> &'static str {
         Builtin::MapValues => "Map.values",
         Builtin::MapToList => "Map.toList",
         Builtin::MapFromList => "Map.fromList",
+        Builtin::SetEmpty => "Set.empty",
+        Builtin::SetInsert => "Set.insert",
+        Builtin::SetRemove => "Set.remove",
+        Builtin::SetMember => "Set.member",
+        Builtin::SetUnion => "Set.union",
+        Builtin::SetIntersect => "Set.intersect",
+        Builtin::SetDifference => "Set.difference",
+        Builtin::SetToList => "Set.toList",
+        Builtin::SetFromList => "Set.fromList",
+        Builtin::ArrayMake => "Array.make",
+        Builtin::ArrayGet => "Array.get",
+        Builtin::ArraySet => "Array.set",
+        Builtin::ArrayLength => "Array.length",
+        Builtin::ArrayMap => "Array.map",
+        Builtin::ArrayFold => "Array.fold",
+        Builtin::ArrayFromList => "Array.fromList",
+        Builtin::ArrayToList => "Array.toList",
         Builtin::TextConcat => "Text.concat",
         Builtin::TextFromFloat => "Text.fromFloat",
         Builtin::TextFixed => "Text.fixed",
@@ -4138,6 +5914,8 @@ pub fn builtin_name(b: Builtin) -> &'static str {
         Builtin::MathLog => "Math.log",
         Builtin::MathExp => "Math.exp",
         Builtin::MathSign => "Math.sign",
+        Builtin::MathToFloat => "Math.toFloat",
+        Builtin::MathToInt => "Math.toInt",
         Builtin::MathSin => "Math.sin",
         Builtin::MathCos => "Math.cos",
         Builtin::MathSqrt => "Math.sqrt",
@@ -4328,6 +6106,23 @@ mod builtin_registry_tests {
                 | Builtin::MapValues
                 | Builtin::MapToList
                 | Builtin::MapFromList
+                | Builtin::SetEmpty
+                | Builtin::SetInsert
+                | Builtin::SetRemove
+                | Builtin::SetMember
+                | Builtin::SetUnion
+                | Builtin::SetIntersect
+                | Builtin::SetDifference
+                | Builtin::SetToList
+                | Builtin::SetFromList
+                | Builtin::ArrayMake
+                | Builtin::ArrayGet
+                | Builtin::ArraySet
+                | Builtin::ArrayLength
+                | Builtin::ArrayMap
+                | Builtin::ArrayFold
+                | Builtin::ArrayFromList
+                | Builtin::ArrayToList
                 | Builtin::MathSin
                 | Builtin::MathCos
                 | Builtin::MathSqrt
@@ -4352,6 +6147,8 @@ mod builtin_registry_tests {
                 | Builtin::MathLog
                 | Builtin::MathExp
                 | Builtin::MathSign
+                | Builtin::MathToFloat
+                | Builtin::MathToInt
                 | Builtin::TextConcat
                 | Builtin::TextFromFloat
                 | Builtin::TextFixed
@@ -4373,7 +6170,7 @@ mod builtin_registry_tests {
                 | Builtin::DebugLog => {}
             }
         }
-        assert_eq!(ALL_BUILTINS.len(), 76, "ALL_BUILTINS must list every Builtin");
+        assert_eq!(ALL_BUILTINS.len(), 95, "ALL_BUILTINS must list every Builtin");
 
         // The length check alone would accept a DUPLICATE entry standing in
         // for a missing one.
@@ -4398,7 +6195,7 @@ mod builtin_registry_tests {
 
 #[cfg(test)]
 mod deep_value_tests {
-    use super::{value_eq, FuelWriter, Span, Value};
+    use super::{value_eq, FuelWriter, List, Span, Value};
     use std::fmt::Write;
     use std::rc::Rc;
 
@@ -4406,7 +6203,7 @@ mod deep_value_tests {
     fn nest(depth: usize) -> Value {
         let mut v = Value::Number(0.0);
         for _ in 0..depth {
-            v = Value::List(Rc::new(vec![v]));
+            v = Value::List(List::from(vec![v]));
         }
         v
     }
@@ -4441,7 +6238,7 @@ mod deep_value_tests {
     #[test]
     fn fuel_writer_stops_structural_rendering_at_its_byte_limit() {
         let shared = Value::String(Rc::from("x".repeat(1024)));
-        let value = Value::List(Rc::new(vec![shared; 100]));
+        let value = Value::List(List::from(vec![shared; 100]));
         let mut out = String::new();
         let mut writer = FuelWriter {
             out: &mut out,
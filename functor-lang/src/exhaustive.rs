@@ -0,0 +1,385 @@
+//! Pattern-matrix usefulness for `match` (Maranget, "Warnings for pattern
+//! matching", 2007) — the engine behind the checker's exhaustiveness and
+//! redundancy rules now that patterns nest.
+//!
+//! A pattern is flattened to [`Pat`]: a wildcard (names and `_` alike) or a
+//! constructor applied to sub-patterns. List patterns become `Cons`/`Nil`
+//! chains, so `[h, ..t]` is `Cons(h, t)` and `[a]` is `Cons(a, Nil)`; a
+//! record pattern is a single constructor whose fields are widened, per
+//! column, to the union of the fields any row names (an unnamed field is a
+//! wildcard). [`Matrix::useful`] answers "can this row match a value no
+//! earlier row matches?" and, when it can, returns a WITNESS — such a value,
+//! as a pattern — which is what the "missing …" diagnostics print.
+//!
+//! Constructor universes come from the patterns themselves plus the
+//! declarations: a variant's siblings are its declared constructors, bool
+//! is `true`/`false`, a list is `[]`/`[_, .._]`, tuples and records have one
+//! constructor, and float/string literals never form a complete set. No
+//! types are consulted below the top level — a column whose patterns
+//! disagree with its type was already diagnosed by `check_pattern`.
+
+use crate::ir::{Pattern, PatternKind};
+use crate::types::Type;
+use std::collections::HashMap;
+
+/// A flattened pattern (see the module doc).
+#[derive(Clone, Debug)]
+pub(crate) enum Pat {
+    Wild,
+    Con(Con, Vec<Pat>),
+    /// An or-pattern: a row with one expands to a row per alternative.
+    Or(Vec<Pat>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Con {
+    Variant(String),
+    Bool(bool),
+    Tuple(usize),
+    /// Field names in the order the row's sub-patterns follow.
+    Record(Vec<String>),
+    Nil,
+    Cons,
+    Number(f64),
+    Int(i64),
+    String(String),
+}
+
+impl Pat {
+    pub(crate) fn from_pattern(pattern: &Pattern) -> Pat {
+        match &pattern.kind {
+            PatternKind::Wildcard | PatternKind::Var { .. } => Pat::Wild,
+            PatternKind::Ctor { name, args } => Pat::Con(
+                Con::Variant(name.clone()),
+                args.iter().map(Pat::from_pattern).collect(),
+            ),
+            PatternKind::Tuple(args) => Pat::Con(
+                Con::Tuple(args.len()),
+                args.iter().map(Pat::from_pattern).collect(),
+            ),
+            PatternKind::List { items, tail } => {
+                let end = match tail {
+                    Some(tail) => Pat::from_pattern(tail),
+                    None => Pat::Con(Con::Nil, Vec::new()),
+                };
+                items.iter().rev().fold(end, |rest, item| {
+                    Pat::Con(Con::Cons, vec![Pat::from_pattern(item), rest])
+                })
+            }
+            PatternKind::Record(fields) => Pat::Con(
+                Con::Record(fields.iter().map(|f| f.name.clone()).collect()),
+                fields
+                    .iter()
+                    .map(|f| Pat::from_pattern(&f.pattern))
+                    .collect(),
+            ),
+            PatternKind::Or(alternatives) => {
+                Pat::Or(alternatives.iter().map(Pat::from_pattern).collect())
+            }
+            PatternKind::Number(n) => Pat::Con(Con::Number(*n), Vec::new()),
+            PatternKind::Int(n) => Pat::Con(Con::Int(*n), Vec::new()),
+            PatternKind::Bool(b) => Pat::Con(Con::Bool(*b), Vec::new()),
+            PatternKind::String(s) => Pat::Con(Con::String(s.clone()), Vec::new()),
+        }
+    }
+
+    /// The pattern that matches every value of `ty`'s shape with wildcard
+    /// leaves — the query for a known product scrutinee, so a tuple arm of
+    /// the wrong arity can't pass for a catch-all.
+    pub(crate) fn any_of(ty: &Type) -> Pat {
+        match ty {
+            Type::Tuple(elems) => Pat::Con(Con::Tuple(elems.len()), vec![Pat::Wild; elems.len()]),
+            _ => Pat::Wild,
+        }
+    }
+
+    /// Does the pattern constrain nothing but shape — every leaf a wildcard
+    /// or the end of a list? Such witnesses read better as the older
+    /// shape-level messages ("add a catch-all", "remaining lengths").
+    pub(crate) fn is_shape_only(&self) -> bool {
+        match self {
+            Pat::Wild => true,
+            Pat::Con(Con::Tuple(_) | Con::Record(_) | Con::Cons | Con::Nil, args) => {
+                args.iter().all(|arg| match arg {
+                    Pat::Wild => true,
+                    Pat::Con(Con::Cons | Con::Nil, _) => arg.is_shape_only(),
+                    Pat::Con(..) | Pat::Or(_) => false,
+                })
+            }
+            Pat::Con(..) | Pat::Or(_) => false,
+        }
+    }
+}
+
+/// Source-like rendering, for "missing `…`" messages. A constructor whose
+/// fields are all wildcards prints bare (`Circle`, not `Circle(_)`), and a
+/// record prints only the fields it constrains.
+impl std::fmt::Display for Pat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pat::Wild => write!(f, "_"),
+            Pat::Or(alternatives) => {
+                let alternatives: Vec<String> =
+                    alternatives.iter().map(|a| a.to_string()).collect();
+                write!(f, "{}", alternatives.join(" | "))
+            }
+            Pat::Con(Con::Variant(name), args) => {
+                write!(f, "{name}")?;
+                if args.iter().all(|a| matches!(a, Pat::Wild)) {
+                    return Ok(());
+                }
+                write!(f, "({})", join(args))
+            }
+            Pat::Con(Con::Bool(b), _) => write!(f, "{b}"),
+            Pat::Con(Con::Number(n), _) => write!(f, "{n:?}"),
+            Pat::Con(Con::Int(n), _) => write!(f, "{n}"),
+            Pat::Con(Con::String(s), _) => write!(f, "{s:?}"),
+            Pat::Con(Con::Tuple(_), args) => write!(f, "({})", join(args)),
+            Pat::Con(Con::Record(names), args) => {
+                let shown: Vec<String> = names
+                    .iter()
+                    .zip(args)
+                    .filter(|(_, arg)| !matches!(arg, Pat::Wild))
+                    .map(|(name, arg)| format!("{name}: {arg}"))
+                    .collect();
+                if shown.is_empty() {
+                    write!(f, "_")
+                } else {
+                    write!(f, "{{ {} }}", shown.join(", "))
+                }
+            }
+            Pat::Con(Con::Nil | Con::Cons, _) => {
+                let mut items = Vec::new();
+                let mut rest = self;
+                loop {
+                    match rest {
+                        Pat::Con(Con::Cons, args) => {
+                            items.push(args[0].to_string());
+                            rest = &args[1];
+                        }
+                        Pat::Con(Con::Nil, _) => break,
+                        tail => {
+                            items.push(format!("..{tail}"));
+                            break;
+                        }
+                    }
+                }
+                write!(f, "[{}]", items.join(", "))
+            }
+        }
+    }
+}
+
+fn join(pats: &[Pat]) -> String {
+    pats.iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The declarations a matrix needs: which constructors are siblings, and
+/// their arities. Borrowed from the checker's tables.
+pub(crate) struct Matrix<'a> {
+    pub variants: &'a HashMap<String, (usize, Vec<String>)>,
+    pub ctors: &'a HashMap<String, (String, usize, Vec<Type>)>,
+}
+
+impl Matrix<'_> {
+    /// Is `q` useful against `rows` — does some value match `q` but no row?
+    /// `Some(witness)` (one pattern per column) when it is.
+    pub(crate) fn useful(&self, rows: &[Vec<Pat>], q: &[Pat]) -> Option<Vec<Pat>> {
+        let Some((head, rest)) = q.split_first() else {
+            return rows.is_empty().then(Vec::new);
+        };
+        if rows.iter().any(|row| matches!(row[0], Pat::Or(_))) {
+            let expanded: Vec<Vec<Pat>> = rows.iter().flat_map(|row| expand_or(row)).collect();
+            return self.useful(&expanded, q);
+        }
+        let fields = record_fields(rows, head);
+        match head {
+            // Useful if any alternative is.
+            Pat::Or(alternatives) => alternatives.iter().find_map(|alternative| {
+                let mut q2 = vec![alternative.clone()];
+                q2.extend(rest.iter().cloned());
+                self.useful(rows, &q2)
+            }),
+            Pat::Con(con, _) => {
+                let con = widen(con, &fields);
+                let mut q2 = specialize_row(&con, &fields, head)?;
+                q2.extend(rest.iter().cloned());
+                let witness = self.useful(&self.specialize(rows, &con, &fields), &q2)?;
+                Some(self.rebuild(con, witness))
+            }
+            Pat::Wild => {
+                let present = heads(rows, &fields);
+                if !present.is_empty() && self.complete(&present) {
+                    for con in self.signature(&present) {
+                        let mut q2 = vec![Pat::Wild; self.arity(&con)];
+                        q2.extend(rest.iter().cloned());
+                        if let Some(witness) =
+                            self.useful(&self.specialize(rows, &con, &fields), &q2)
+                        {
+                            return Some(self.rebuild(con, witness));
+                        }
+                    }
+                    return None;
+                }
+                // Incomplete: only the rows that match anything in this
+                // column matter, and the witness is some constructor no row
+                // names (or `_` where there's no naming one).
+                let default: Vec<Vec<Pat>> = rows
+                    .iter()
+                    .filter(|row| matches!(row[0], Pat::Wild))
+                    .map(|row| row[1..].to_vec())
+                    .collect();
+                let mut witness = self.useful(&default, rest)?;
+                let missing = self
+                    .signature(&present)
+                    .into_iter()
+                    .find(|con| !present.contains(con))
+                    .map(|con| {
+                        let arity = self.arity(&con);
+                        Pat::Con(con, vec![Pat::Wild; arity])
+                    })
+                    .unwrap_or(Pat::Wild);
+                witness.insert(0, missing);
+                Some(witness)
+            }
+        }
+    }
+
+    /// Rows whose head matches `con`, with the head replaced by its
+    /// sub-patterns (a wildcard head by as many wildcards).
+    fn specialize(&self, rows: &[Vec<Pat>], con: &Con, fields: &[String]) -> Vec<Vec<Pat>> {
+        rows.iter()
+            .filter_map(|row| {
+                let mut out = match &row[0] {
+                    Pat::Wild => vec![Pat::Wild; self.arity(con)],
+                    head => specialize_row(con, fields, head)?,
+                };
+                out.extend(row[1..].iter().cloned());
+                Some(out)
+            })
+            .collect()
+    }
+
+    /// Do the head constructors in `present` cover every value of the
+    /// column? Literal floats and strings never do.
+    fn complete(&self, present: &[Con]) -> bool {
+        let signature = self.signature(present);
+        !signature.is_empty() && signature.iter().all(|con| present.contains(con))
+    }
+
+    /// Every constructor of the column's type, judged from one that's
+    /// present; empty where the set is infinite (int, float, string).
+    fn signature(&self, present: &[Con]) -> Vec<Con> {
+        match present.first() {
+            Some(Con::Variant(name)) => self
+                .ctors
+                .get(name)
+                .and_then(|(owner, _, _)| self.variants.get(owner))
+                .map(|(_, declared)| declared.iter().map(|c| Con::Variant(c.clone())).collect())
+                .unwrap_or_default(),
+            Some(Con::Bool(_)) => vec![Con::Bool(true), Con::Bool(false)],
+            Some(Con::Nil | Con::Cons) => vec![Con::Nil, Con::Cons],
+            Some(con @ (Con::Tuple(_) | Con::Record(_))) => vec![con.clone()],
+            Some(Con::Number(_) | Con::Int(_) | Con::String(_)) | None => Vec::new(),
+        }
+    }
+
+    /// Fold a specialized witness back up: the constructor's arity-many
+    /// leading patterns become its sub-patterns.
+    fn rebuild(&self, con: Con, mut witness: Vec<Pat>) -> Vec<Pat> {
+        let rest = witness.split_off(self.arity(&con));
+        let mut out = vec![Pat::Con(con, witness)];
+        out.extend(rest);
+        out
+    }
+
+    fn arity(&self, con: &Con) -> usize {
+        match con {
+            Con::Variant(name) => self
+                .ctors
+                .get(name)
+                .map_or(0, |(_, _, fields)| fields.len()),
+            Con::Tuple(n) => *n,
+            Con::Record(fields) => fields.len(),
+            Con::Cons => 2,
+            Con::Bool(_) | Con::Nil | Con::Number(_) | Con::Int(_) | Con::String(_) => 0,
+        }
+    }
+}
+
+/// A row whose head is an or-pattern, as one row per alternative (a row
+/// with any other head, unchanged).
+fn expand_or(row: &[Pat]) -> Vec<Vec<Pat>> {
+    match &row[0] {
+        Pat::Or(alternatives) => alternatives
+            .iter()
+            .map(|alternative| {
+                let mut expanded = vec![alternative.clone()];
+                expanded.extend(row[1..].iter().cloned());
+                expanded
+            })
+            .collect(),
+        _ => vec![row.to_vec()],
+    }
+}
+
+/// The union of the field names the column's record patterns mention
+/// (empty when the column has none) — every record head is widened to it.
+fn record_fields(rows: &[Vec<Pat>], head: &Pat) -> Vec<String> {
+    let mut fields: Vec<String> = Vec::new();
+    for pat in rows.iter().map(|row| &row[0]).chain(std::iter::once(head)) {
+        if let Pat::Con(Con::Record(names), _) = pat {
+            for name in names {
+                if !fields.contains(name) {
+                    fields.push(name.clone());
+                }
+            }
+        }
+    }
+    fields
+}
+
+fn widen(con: &Con, fields: &[String]) -> Con {
+    match con {
+        Con::Record(_) => Con::Record(fields.to_vec()),
+        other => other.clone(),
+    }
+}
+
+/// The distinct head constructors of a column's rows.
+fn heads(rows: &[Vec<Pat>], fields: &[String]) -> Vec<Con> {
+    let mut present: Vec<Con> = Vec::new();
+    for row in rows {
+        if let Pat::Con(con, _) = &row[0] {
+            let con = widen(con, fields);
+            if !present.contains(&con) {
+                present.push(con);
+            }
+        }
+    }
+    present
+}
+
+/// A constructor head's sub-patterns if it matches `con` (records reorder
+/// and pad to the widened field list); `None` when it names another.
+fn specialize_row(con: &Con, fields: &[String], head: &Pat) -> Option<Vec<Pat>> {
+    match (con, head) {
+        (_, Pat::Wild) => None,
+        (Con::Record(_), Pat::Con(Con::Record(names), args)) => Some(
+            fields
+                .iter()
+                .map(|field| {
+                    names
+                        .iter()
+                        .position(|n| n == field)
+                        .map_or(Pat::Wild, |i| args[i].clone())
+                })
+                .collect(),
+        ),
+        (con, Pat::Con(other, args)) if con == other => Some(args.clone()),
+        _ => None,
+    }
+}
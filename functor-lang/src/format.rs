@@ -0,0 +1,1486 @@
+//! The canonical source formatter behind `functor fmt`, the LSP's
+//! `textDocument/formatting`, and the sandbox editor's format command.
+//!
+//! A pretty-printer over the [`parser`](crate::parse) output: the AST is
+//! turned into a small layout document (Wadler-style groups that print flat
+//! when they fit in [`MAX_WIDTH`] columns and break otherwise), so the same
+//! program always comes out the same way, whoever wrote it.
+//!
+//! **Comments.** The lexer drops comments, so they are recovered from the
+//! gaps between tokens and re-attached by position: each comment prints as a
+//! leading line of the next *anchor* — an item, a `let … in` statement, a
+//! match arm, a list/record/argument element, a pipeline stage, an operand of
+//! an operator chain — or, when it shared a line with code, at the end of the
+//! line before it. A comment with no anchor left in its container prints
+//! before the container closes. Comments are never dropped.
+//!
+//! **Safety.** Layout is whitespace-only in this grammar, but the AST forgets
+//! parentheses, so the printer re-derives them from precedence. Every result
+//! is checked before it is returned: the formatted text must parse to the same
+//! AST (spans aside) and carry the same comments, or formatting fails with an
+//! error instead of changing the program.
+
+use crate::ast::*;
+use crate::lexer::{lex, Token, TokenKind};
+use crate::span::Span;
+use crate::{parse, parse_interface, ParseError};
+
+/// The column budget a group must fit in to print flat.
+pub const MAX_WIDTH: usize = 100;
+
+/// Format a whole source file into its canonical layout. `interface` selects
+/// the `.funi` grammar (bodyless `let name : Type` signatures).
+pub fn format_source(src: &str, interface: bool) -> Result<String, ParseError> {
+    Ok(format_impl(src, interface)?.text)
+}
+
+/// Format only the top-level items overlapping `range` (byte offsets into
+/// `src`) — what an editor's "format selection" asks for. Returns the source
+/// span those items cover and its formatted replacement, or `None` when no
+/// item overlaps the range. The whole file must still parse.
+pub fn format_range(
+    src: &str,
+    interface: bool,
+    range: Span,
+) -> Result<Option<(Span, String)>, ParseError> {
+    let formatted = format_impl(src, interface)?;
+    let overlapping: Vec<&ItemLayout> = formatted
+        .items
+        .iter()
+        .filter(|item| {
+            if range.start == range.end {
+                item.source.start <= range.start && range.start <= item.source.end
+            } else {
+                item.source.start < range.end && range.start < item.source.end
+            }
+        })
+        .collect();
+    let (Some(first), Some(last)) = (overlapping.first(), overlapping.last()) else {
+        return Ok(None);
+    };
+    Ok(Some((
+        Span::new(first.source.start, last.source.end),
+        formatted.text[first.start..last.end].to_string(),
+    )))
+}
+
+struct Formatted {
+    text: String,
+    items: Vec<ItemLayout>,
+}
+
+/// Where one top-level item sits in the source and in the formatted text.
+struct ItemLayout {
+    source: Span,
+    start: usize,
+    end: usize,
+}
+
+fn format_impl(src: &str, interface: bool) -> Result<Formatted, ParseError> {
+    let program = parse_as(src, interface)?;
+    let tokens = lex(src, 0)?;
+    let mut printer = Printer {
+        src,
+        tokens: &tokens,
+        comments: collect_comments(src, &tokens),
+        next_comment: 0,
+        marks: 0,
+    };
+    let (doc, sources) = printer.program(&program);
+    let (mut text, marks) = render(&doc);
+    let trimmed = text.trim_end().len();
+    text.truncate(trimmed);
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    verify(src, &program, &text, interface)?;
+    let items = sources
+        .into_iter()
+        .enumerate()
+        .map(|(i, source)| ItemLayout {
+            source,
+            start: marks[2 * i],
+            end: marks[2 * i + 1],
+        })
+        .collect();
+    Ok(Formatted { text, items })
+}
+
+fn parse_as(src: &str, interface: bool) -> Result<Program, ParseError> {
+    if interface {
+        parse_interface(src)
+    } else {
+        parse(src)
+    }
+}
+
+/// The formatted text must mean exactly what the source meant: the same AST
+/// once spans are erased, and the same comments in the same order.
+fn verify(src: &str, program: &Program, text: &str, interface: bool) -> Result<(), ParseError> {
+    let unchanged = parse_as(text, interface).is_ok_and(|reparsed| {
+        erase_spans(&format!("{program:?}")) == erase_spans(&format!("{reparsed:?}"))
+    }) && lex(text, 0).is_ok_and(|tokens| {
+        let before = lex(src, 0).map(|tokens| comment_texts(src, &tokens));
+        before.is_ok_and(|before| before == comment_texts(text, &tokens))
+    });
+    if unchanged {
+        Ok(())
+    } else {
+        Err(ParseError {
+            message: "the formatter could not lay this file out without changing its meaning; \
+the file was left as it is (please report this)"
+                .to_string(),
+            span: Span::new(0, 0),
+        })
+    }
+}
+
+fn comment_texts(src: &str, tokens: &[Token]) -> Vec<String> {
+    collect_comments(src, tokens)
+        .into_iter()
+        .map(|comment| comment.text)
+        .collect()
+}
+
+/// Replace every `start..end` span in a Debug-printed AST with `_`.
+fn erase_spans(debug: &str) -> String {
+    let bytes = debug.as_bytes();
+    let digits = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        i
+    };
+    let mut out = String::with_capacity(debug.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let start_end = digits(i);
+        if start_end > i && debug[start_end..].starts_with("..") {
+            let end_end = digits(start_end + 2);
+            if end_end > start_end + 2 {
+                out.push('_');
+                i = end_end;
+                continue;
+            }
+        }
+        let ch = debug[i..].chars().next().expect("in bounds");
+        out.push(ch);
+        i += ch.len_utf8();
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Comments
+
+/// One `//` comment recovered from between two tokens.
+struct Comment {
+    start: usize,
+    /// The comment as written, trailing whitespace trimmed.
+    text: String,
+    /// Code precedes it on its line: it prints at the end of a line.
+    trailing: bool,
+    /// A blank line separates it from what came before.
+    blank_before: bool,
+}
+
+/// Every comment outside string literals, in source order. Comments inside an
+/// interpolated string's holes are not collected: interpolated strings print
+/// verbatim, holes and all.
+fn collect_comments(src: &str, tokens: &[Token]) -> Vec<Comment> {
+    let mut comments = Vec::new();
+    let mut strings: Vec<TokenKind> = Vec::new();
+    let mut prev_end = None;
+    for token in tokens {
+        if strings.is_empty() {
+            scan_gap(src, prev_end, token.span.start, &mut comments);
+        }
+        match token.kind {
+            TokenKind::InterpolatedStart | TokenKind::InterpolatedOpen => {
+                strings.push(token.kind.clone())
+            }
+            TokenKind::InterpolatedClose | TokenKind::InterpolatedEnd => {
+                strings.pop();
+            }
+            _ => {}
+        }
+        prev_end = Some(token.span.end);
+    }
+    comments
+}
+
+/// Collect the comments in the whitespace-and-comments gap ending at `to`.
+/// `from` is the end of the previous token (`None` at the top of the file).
+fn scan_gap(src: &str, from: Option<usize>, to: usize, comments: &mut Vec<Comment>) {
+    let from_offset = from.unwrap_or(0);
+    let bytes = src.as_bytes();
+    let mut code_on_line = from.is_some();
+    let mut newlines = 0;
+    let mut i = from_offset;
+    while i < to {
+        match bytes[i] {
+            b'\n' => {
+                newlines += 1;
+                code_on_line = false;
+                i += 1;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let end = src[i..to].find('\n').map_or(to, |n| i + n);
+                comments.push(Comment {
+                    start: i,
+                    text: src[i..end].trim_end().to_string(),
+                    trailing: code_on_line,
+                    blank_before: newlines >= 2,
+                });
+                newlines = 0;
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+}
+
+/// Whether a blank line sits directly before `pos` (whitespace only between).
+fn blank_line_before(src: &str, pos: usize) -> bool {
+    let newlines = src[..pos]
+        .bytes()
+        .rev()
+        .take_while(u8::is_ascii_whitespace)
+        .filter(|&b| b == b'\n')
+        .count();
+    newlines >= 2
+}
+
+// ---------------------------------------------------------------------------
+// Layout documents
+
+enum Doc {
+    Text(String),
+    /// A space when the enclosing group is flat, a newline when it breaks.
+    Line,
+    /// Nothing when the enclosing group is flat, a newline when it breaks.
+    SoftLine,
+    /// Always a newline; forces every enclosing group to break.
+    HardLine,
+    /// Text printed only when the enclosing group breaks (trailing commas).
+    IfBreak(&'static str),
+    /// Indent everything inside by two more columns after a break.
+    Nest(Box<Doc>),
+    /// Print flat if the whole group fits, otherwise break its lines. The
+    /// flag records whether the group must break (it holds a hard line).
+    Group(Box<Doc>, bool),
+    Concat(Vec<Doc>),
+    /// A comment that ends the PREVIOUS line; what follows starts a new one.
+    EndComment(String),
+    /// Record the output offset here (see [`format_range`]).
+    Mark(usize),
+}
+
+fn text(s: impl Into<String>) -> Doc {
+    Doc::Text(s.into())
+}
+
+fn concat(parts: Vec<Doc>) -> Doc {
+    Doc::Concat(parts)
+}
+
+fn nest(doc: Doc) -> Doc {
+    Doc::Nest(Box::new(doc))
+}
+
+fn group(doc: Doc) -> Doc {
+    let hard = is_hard(&doc);
+    Doc::Group(Box::new(doc), hard)
+}
+
+fn is_hard(doc: &Doc) -> bool {
+    match doc {
+        Doc::HardLine | Doc::EndComment(_) => true,
+        Doc::Group(_, hard) => *hard,
+        Doc::Nest(inner) => is_hard(inner),
+        Doc::Concat(parts) => parts.iter().any(is_hard),
+        _ => false,
+    }
+}
+
+fn is_empty(doc: &Doc) -> bool {
+    matches!(doc, Doc::Concat(parts) if parts.iter().all(is_empty))
+}
+
+/// `items` separated by `,` + a line break.
+fn comma_separated(items: Vec<Doc>) -> Doc {
+    let mut parts = Vec::new();
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            parts.push(text(","));
+            parts.push(Doc::Line);
+        }
+        parts.push(item);
+    }
+    concat(parts)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// Lay `doc` out, returning the text and the offset of every [`Doc::Mark`].
+fn render(doc: &Doc) -> (String, Vec<usize>) {
+    let mut out = String::new();
+    let mut marks = Vec::new();
+    let mut column = 0;
+    // A trailing comment was just appended: the next text starts a new line.
+    let mut line_pending = false;
+    let mut stack: Vec<(usize, Mode, &Doc)> = vec![(0, Mode::Break, doc)];
+    fn newline(out: &mut String, column: &mut usize, indent: usize) {
+        let trimmed = out.trim_end_matches(' ').len();
+        out.truncate(trimmed);
+        out.push('\n');
+        out.extend(std::iter::repeat_n(' ', indent));
+        *column = indent;
+    }
+    while let Some((indent, mode, doc)) = stack.pop() {
+        match doc {
+            Doc::Text(s) => {
+                if line_pending {
+                    newline(&mut out, &mut column, indent);
+                    line_pending = false;
+                }
+                out.push_str(s);
+                column += s.chars().count();
+            }
+            Doc::Line | Doc::SoftLine if mode == Mode::Flat => {
+                if matches!(doc, Doc::Line) {
+                    out.push(' ');
+                    column += 1;
+                }
+            }
+            Doc::Line | Doc::SoftLine | Doc::HardLine => {
+                newline(&mut out, &mut column, indent);
+                line_pending = false;
+            }
+            Doc::IfBreak(s) => {
+                if mode == Mode::Break {
+                    out.push_str(s);
+                    column += s.len();
+                }
+            }
+            Doc::Nest(inner) => stack.push((indent + 2, mode, inner)),
+            Doc::Concat(parts) => stack.extend(parts.iter().rev().map(|part| (indent, mode, part))),
+            Doc::Group(inner, hard) => {
+                let flat = !hard
+                    && (mode == Mode::Flat
+                        || fits(
+                            MAX_WIDTH as isize - column as isize,
+                            (indent, inner),
+                            &stack,
+                        ));
+                stack.push((indent, if flat { Mode::Flat } else { Mode::Break }, inner));
+            }
+            Doc::EndComment(comment) => {
+                let code_end = out.trim_end().len();
+                let line_start = out[..code_end].rfind('\n').map_or(0, |i| i + 1);
+                if out[line_start..code_end].trim_start().starts_with("//") {
+                    // The line before is itself a comment: joining the two
+                    // would merge them, so this one takes its own line.
+                    newline(&mut out, &mut column, indent);
+                    out.push_str(comment);
+                    line_pending = true;
+                    continue;
+                }
+                let rest = out.split_off(code_end);
+                out.push(' ');
+                out.push_str(comment);
+                if rest.contains('\n') {
+                    out.push_str(&rest);
+                    column = rest.len() - rest.rfind('\n').expect("has a newline") - 1;
+                } else {
+                    line_pending = true;
+                }
+            }
+            Doc::Mark(id) => {
+                if line_pending {
+                    newline(&mut out, &mut column, indent);
+                    line_pending = false;
+                }
+                if marks.len() <= *id {
+                    marks.resize(*id + 1, 0);
+                }
+                marks[*id] = out.len();
+            }
+        }
+    }
+    (out, marks)
+}
+
+/// Whether `first`, laid out flat, fits in `width` columns — counting what
+/// follows it on the same line (the rest of the render stack, in its modes).
+fn fits(mut width: isize, first: (usize, &Doc), rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut pending: Vec<(Mode, &Doc)> = vec![(Mode::Flat, first.1)];
+    let mut rest = rest.iter().rev();
+    loop {
+        if width < 0 {
+            return false;
+        }
+        let (mode, doc) = match pending.pop() {
+            Some(next) => next,
+            None => match rest.next() {
+                Some(&(_, mode, doc)) => (mode, doc),
+                None => return true,
+            },
+        };
+        match doc {
+            Doc::Text(s) => width -= s.chars().count() as isize,
+            Doc::Line if mode == Mode::Flat => width -= 1,
+            Doc::SoftLine if mode == Mode::Flat => {}
+            Doc::Line | Doc::SoftLine | Doc::HardLine | Doc::EndComment(_) => return true,
+            Doc::IfBreak(s) => {
+                if mode == Mode::Break {
+                    width -= s.len() as isize;
+                }
+            }
+            Doc::Nest(inner) => pending.push((mode, inner)),
+            Doc::Concat(parts) => pending.extend(parts.iter().rev().map(|part| (mode, part))),
+            Doc::Group(inner, hard) => {
+                pending.push((if *hard { Mode::Break } else { mode }, inner))
+            }
+            Doc::Mark(_) => {}
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Printing
+
+// Binding strength, loosest first. `OPEN` forms (`let`, `match`, `if`, `:=`,
+// lambdas) extend as far right as they can, so anywhere tighter they need
+// parentheses.
+const OPEN: u8 = 0;
+const PIPE: u8 = 1;
+const OR: u8 = 2;
+const AND: u8 = 3;
+const NOT: u8 = 4;
+const CMP: u8 = 5;
+const ADD: u8 = 6;
+const MUL: u8 = 7;
+const UNARY: u8 = 8;
+const POSTFIX: u8 = 9;
+
+fn precedence(expr: &Expr) -> u8 {
+    match &expr.kind {
+        ExprKind::Let { .. }
+        | ExprKind::Assign { .. }
+        | ExprKind::If { .. }
+        | ExprKind::Match { .. }
+        | ExprKind::Lambda { .. } => OPEN,
+        ExprKind::Pipeline { .. } => PIPE,
+        ExprKind::Logical {
+            op: LogicalOp::Or, ..
+        } => OR,
+        ExprKind::Logical {
+            op: LogicalOp::And, ..
+        } => AND,
+        ExprKind::Not(_) => NOT,
+        ExprKind::Binary { op, .. } => binary_precedence(*op),
+        ExprKind::Neg(_) => UNARY,
+        // `-90deg` is one literal, but only as a whole: nothing may follow it.
+        ExprKind::NumberUnit { value, .. } if numeral_is_negative(*value) => UNARY,
+        _ => POSTFIX,
+    }
+}
+
+fn binary_precedence(op: BinOp) -> u8 {
+    match op {
+        BinOp::Mul | BinOp::Div | BinOp::Rem => MUL,
+        BinOp::Add | BinOp::Sub => ADD,
+        _ => CMP,
+    }
+}
+
+fn numeral_is_negative(value: Numeral) -> bool {
+    match value {
+        Numeral::Int(n) => n < 0,
+        Numeral::Float(n) => n.is_sign_negative(),
+    }
+}
+
+/// Forms whose body breaks onto indented lines of its own.
+fn is_block(expr: &Expr) -> bool {
+    matches!(
+        expr.kind,
+        ExprKind::Let { .. } | ExprKind::Assign { .. } | ExprKind::Match { .. }
+    )
+}
+
+/// Bracketed forms that open on the line they start on (after `=`, `:`, or as
+/// a call's last argument) rather than moving to a line of their own.
+fn hugs(expr: &Expr) -> bool {
+    matches!(
+        expr.kind,
+        ExprKind::Record(_)
+            | ExprKind::RecordUpdate { .. }
+            | ExprKind::List(_)
+            | ExprKind::ListCons { .. }
+            | ExprKind::Lambda { .. }
+    )
+}
+
+/// The leftmost leaf of a postfix chain (`a` in `a.b(c).d`).
+fn leftmost(expr: &Expr) -> &Expr {
+    match &expr.kind {
+        ExprKind::FieldAccess { object, .. } => leftmost(object),
+        ExprKind::Call { callee, .. } => leftmost(callee),
+        _ => expr,
+    }
+}
+
+struct Printer<'a> {
+    src: &'a str,
+    tokens: &'a [Token],
+    comments: Vec<Comment>,
+    next_comment: usize,
+    marks: usize,
+}
+
+impl Printer<'_> {
+    fn mark(&mut self) -> Doc {
+        self.marks += 1;
+        Doc::Mark(self.marks - 1)
+    }
+
+    /// The comments before `pos` not yet printed, as the leading lines of the
+    /// element starting there. `keep_blank` carries a blank line before the
+    /// element over from the source (statement-like positions only).
+    fn leading(&mut self, pos: usize, keep_blank: bool) -> Doc {
+        let mut parts = Vec::new();
+        while let Some(comment) = self
+            .comments
+            .get(self.next_comment)
+            .filter(|comment| comment.start < pos)
+        {
+            if comment.trailing {
+                parts.push(Doc::EndComment(comment.text.clone()));
+            } else {
+                if (keep_blank || !parts.is_empty()) && comment.blank_before {
+                    parts.push(Doc::HardLine);
+                }
+                parts.push(text(comment.text.clone()));
+                parts.push(Doc::HardLine);
+            }
+            self.next_comment += 1;
+        }
+        if (keep_blank || !parts.is_empty()) && blank_line_before(self.src, pos) {
+            parts.push(Doc::HardLine);
+        }
+        concat(parts)
+    }
+
+    /// The comments before `pos` not yet printed, at the END of a container:
+    /// each on a line of its own after the last element.
+    fn dangling(&mut self, pos: usize) -> Doc {
+        let mut parts = Vec::new();
+        while let Some(comment) = self
+            .comments
+            .get(self.next_comment)
+            .filter(|comment| comment.start < pos)
+        {
+            if comment.trailing {
+                parts.push(Doc::EndComment(comment.text.clone()));
+            } else {
+                parts.push(Doc::HardLine);
+                if comment.blank_before {
+                    parts.push(Doc::HardLine);
+                }
+                parts.push(text(comment.text.clone()));
+            }
+            self.next_comment += 1;
+        }
+        concat(parts)
+    }
+
+    /// The source text of the tokens in `span`, minus any parentheses wrapped
+    /// around them — how literals print (their spelling is kept exactly).
+    fn core_text(&self, span: Span) -> &str {
+        let (first, last) = self.core_tokens(span);
+        &self.src[self.tokens[first].span.start..self.tokens[last].span.end]
+    }
+
+    fn core_tokens(&self, span: Span) -> (usize, usize) {
+        let mut first = self.tokens.partition_point(|t| t.span.start < span.start);
+        let mut last = self
+            .tokens
+            .partition_point(|t| t.span.end <= span.end && t.kind != TokenKind::Eof)
+            .saturating_sub(1);
+        while first < last
+            && self.tokens[first].kind == TokenKind::LParen
+            && self.tokens[last].kind == TokenKind::RParen
+        {
+            first += 1;
+            last -= 1;
+        }
+        (first, last)
+    }
+
+    /// A destructuring `let (a, b) = e in …` parses as a one-arm match; the
+    /// source's leading `let` tells the two apart.
+    fn is_destructuring_let(&self, expr: &Expr) -> bool {
+        matches!(&expr.kind, ExprKind::Match { arms, .. } if arms.len() == 1)
+            && self.tokens[self.core_tokens(expr.span).0].kind == TokenKind::Let
+    }
+
+    /// Whether `expr` ends in a `match` whose arms would swallow whatever
+    /// follows it — so a non-final arm holding it needs parentheses.
+    fn ends_in_match(&self, expr: &Expr) -> bool {
+        match &expr.kind {
+            ExprKind::Match { arms, .. } if self.is_destructuring_let(expr) => {
+                self.ends_in_match(&arms[0].body)
+            }
+            ExprKind::Match { .. } => true,
+            ExprKind::Let { body, .. } => self.ends_in_match(body),
+            ExprKind::Assign { rest, .. } => self.ends_in_match(rest),
+            ExprKind::If { else_branch, .. } => self.ends_in_match(else_branch),
+            ExprKind::Lambda { body, .. } => self.ends_in_match(body),
+            _ => false,
+        }
+    }
+
+    // --- Items -------------------------------------------------------------
+
+    /// The whole file, plus each top-level item's source span (its layout
+    /// marks are `2 * i` and `2 * i + 1`).
+    fn program(&mut self, program: &Program) -> (Doc, Vec<Span>) {
+        let mut parts = Vec::new();
+        let mut sources = Vec::new();
+        for (i, item) in program.items.iter().enumerate() {
+            let source = item_span(item);
+            if i > 0 {
+                parts.push(Doc::HardLine);
+            }
+            parts.push(self.leading(source.start, i > 0));
+            parts.push(self.mark());
+            parts.push(self.item(item));
+            parts.push(self.dangling(source.end));
+            parts.push(self.mark());
+            sources.push(source);
+        }
+        if program.items.is_empty() {
+            parts.push(self.leading(self.src.len(), false));
+        } else {
+            parts.push(self.dangling(self.src.len()));
+        }
+        (concat(parts), sources)
+    }
+
+    fn item(&mut self, item: &Item) -> Doc {
+        match item {
+            Item::Let(decl) => {
+                let head = format!(
+                    "{}let {}{}",
+                    visibility(decl.private),
+                    decl.name,
+                    annotation(&decl.ty)
+                );
+                self.binding(head, &decl.value)
+            }
+            Item::Sig(sig) => text(format!("let {} : {}", sig.name, type_text(&sig.ty))),
+            Item::Type(decl) => self.type_decl(decl),
+            Item::Open(open) => text(format!("open {}", open.module)),
+            Item::Expect(expect) => {
+                let keyword = match &expect.kind {
+                    ExpectKind::Example => "expect".to_string(),
+                    ExpectKind::Property => "property".to_string(),
+                    ExpectKind::Snapshot(name) => format!("expectSnapshot {name} ="),
+                };
+                if is_block(&expect.expr) {
+                    // A multi-line test reads as one parenthesized block.
+                    let body = self.expr(&expect.expr, OPEN);
+                    concat(vec![
+                        text(format!("{keyword} (")),
+                        nest(concat(vec![Doc::HardLine, body])),
+                        Doc::HardLine,
+                        text(")"),
+                    ])
+                } else {
+                    let body = self.expr(&expect.expr, OPEN);
+                    concat(vec![text(format!("{keyword} ")), body])
+                }
+            }
+            Item::Module(module) => {
+                let mut body = Vec::new();
+                for (i, item) in module.items.iter().enumerate() {
+                    body.push(Doc::HardLine);
+                    body.push(self.leading(item_span(item).start, i > 0));
+                    body.push(self.item(item));
+                }
+                // Comments after the last member stay inside the block.
+                body.push(self.dangling(module.block.end));
+                if module.items.is_empty() && is_empty(&body[0]) {
+                    return text(format!("module {} {{}}", module.name));
+                }
+                concat(vec![
+                    text(format!("module {} {{", module.name)),
+                    nest(concat(body)),
+                    Doc::HardLine,
+                    text("}"),
+                ])
+            }
+            Item::Unit(unit) => text(format!("unit {} = {}", unit.suffix, unit.target.join("."))),
+            Item::UnitOp(unit) => {
+                let head = format!("unit {} ({})", unit.suffix, unit.op.symbol());
+                self.binding(head, &unit.target)
+            }
+        }
+    }
+
+    fn type_decl(&mut self, decl: &TypeDecl) -> Doc {
+        let mut head = format!("{}type {}", visibility(decl.private), decl.name);
+        if !decl.params.is_empty() {
+            head.push_str(&format!("<{}>", decl.params.join(", ")));
+        }
+        match &decl.body {
+            TypeBody::Abstract => text(head),
+            TypeBody::Host => text(format!("{head} = host")),
+            TypeBody::Record(fields) if fields.is_empty() => text(format!("{head} = {{}}")),
+            TypeBody::Record(fields) => {
+                let mut entries = Vec::new();
+                for (i, field) in fields.iter().enumerate() {
+                    let lead = self.leading(field.span.start, i > 0);
+                    let entry = text(format!("{}: {}", field.name, type_text(&field.ty)));
+                    entries.push(concat(vec![lead, entry]));
+                }
+                let close = self.dangling(decl.span.end);
+                group(concat(vec![
+                    text(format!("{head} = {{")),
+                    nest(concat(vec![
+                        Doc::Line,
+                        comma_separated(entries),
+                        Doc::IfBreak(","),
+                        close,
+                    ])),
+                    Doc::Line,
+                    text("}"),
+                ]))
+            }
+            TypeBody::Variants(variants) => {
+                // One alternative per line, always: variant lists grow.
+                let mut alternatives = Vec::new();
+                for (i, variant) in variants.iter().enumerate() {
+                    alternatives.push(Doc::HardLine);
+                    alternatives.push(self.leading(variant.span.start, i > 0));
+                    alternatives.push(text("| "));
+                    alternatives.push(self.variant(variant));
+                }
+                concat(vec![text(format!("{head} =")), nest(concat(alternatives))])
+            }
+        }
+    }
+
+    fn variant(&mut self, variant: &VariantDecl) -> Doc {
+        if variant.fields.is_empty() {
+            return text(variant.name.clone());
+        }
+        let fields = variant
+            .fields
+            .iter()
+            .map(|field| text(format!("{}: {}", field.name, type_text(&field.ty))))
+            .collect();
+        group(concat(vec![
+            text(format!("{}(", variant.name)),
+            nest(concat(vec![Doc::SoftLine, comma_separated(fields)])),
+            Doc::SoftLine,
+            text(")"),
+        ]))
+    }
+
+    /// `head = value` — a top-level `let`, a `let … in` statement, a unit
+    /// operator. Bracketed values and lambdas open on the same line; anything
+    /// else moves to an indented line of its own when it does not fit.
+    fn binding(&mut self, head: String, value: &Expr) -> Doc {
+        if hugs(value) {
+            let value = self.expr(value, OPEN);
+            return concat(vec![text(format!("{head} = ")), value]);
+        }
+        let lead = self.leading(value.span.start, false);
+        let value = self.expr(value, OPEN);
+        group(concat(vec![
+            text(format!("{head} =")),
+            nest(concat(vec![Doc::Line, lead, value])),
+        ]))
+    }
+
+    // --- Expressions -------------------------------------------------------
+
+    /// `expr` in a position that needs at least `min` binding strength.
+    fn expr(&mut self, expr: &Expr, min: u8) -> Doc {
+        if precedence(expr) < min {
+            self.parenthesized(expr)
+        } else {
+            self.expr_inner(expr)
+        }
+    }
+
+    /// `(expr)`. A parenthesized `match` may stay on one line when it fits
+    /// (`(match d with | Left => -1.0 | Right => 1.0)`); elsewhere its arms
+    /// always take a line each.
+    fn parenthesized(&mut self, expr: &Expr) -> Doc {
+        let inner = match &expr.kind {
+            ExprKind::Match { scrutinee, arms } if !self.is_destructuring_let(expr) => {
+                self.match_expr(scrutinee, arms, true)
+            }
+            _ => self.expr_inner(expr),
+        };
+        concat(vec![text("("), inner, text(")")])
+    }
+
+    fn expr_inner(&mut self, expr: &Expr) -> Doc {
+        match &expr.kind {
+            ExprKind::Number(_)
+            | ExprKind::Int(_)
+            | ExprKind::NumberUnit { .. }
+            | ExprKind::String(_)
+            | ExprKind::InterpolatedString(_) => text(self.core_text(expr.span)),
+            ExprKind::Bool(value) => text(value.to_string()),
+            ExprKind::Ident(segments) => text(segments.join(".")),
+            ExprKind::Record(fields) => {
+                if fields.is_empty() {
+                    return text("{}");
+                }
+                let entries = self.fields(fields);
+                let close = self.dangling(expr.span.end);
+                group(concat(vec![
+                    text("{"),
+                    nest(concat(vec![Doc::Line, entries, Doc::IfBreak(","), close])),
+                    Doc::Line,
+                    text("}"),
+                ]))
+            }
+            ExprKind::RecordUpdate { base, fields } => {
+                // `{ base with` stays on the opening line; the fields sit one
+                // level deeper than the closing brace.
+                let base = self.expr(base, OPEN);
+                let entries = self.fields(fields);
+                let close = self.dangling(expr.span.end);
+                group(concat(vec![
+                    text("{ "),
+                    base,
+                    text(" with"),
+                    nest(nest(concat(vec![
+                        Doc::Line,
+                        entries,
+                        Doc::IfBreak(","),
+                        close,
+                    ]))),
+                    Doc::Line,
+                    text("}"),
+                ]))
+            }
+            ExprKind::List(items) => self.list(items, None, expr.span),
+            ExprKind::ListCons { items, tail } => self.list(items, Some(tail), expr.span),
+            ExprKind::Tuple(items) => {
+                let items = self.elements(items, false);
+                let close = self.dangling(expr.span.end);
+                group(concat(vec![
+                    text("("),
+                    nest(concat(vec![Doc::SoftLine, items, Doc::IfBreak(","), close])),
+                    Doc::SoftLine,
+                    text(")"),
+                ]))
+            }
+            ExprKind::Let { .. } | ExprKind::Assign { .. } => self.statements(expr),
+            ExprKind::Match { .. } if self.is_destructuring_let(expr) => self.statements(expr),
+            ExprKind::FieldAccess { object, field } => {
+                // `(Mod).x` is field access on a value, not the qualified
+                // name `Mod.x` — keep its parentheses.
+                let qualified = matches!(&object.kind, ExprKind::Ident(segments)
+                    if segments.last().is_some_and(|s| s.starts_with(char::is_uppercase)));
+                let object = if qualified {
+                    let inner = self.expr_inner(object);
+                    concat(vec![text("("), inner, text(")")])
+                } else {
+                    self.expr(object, POSTFIX)
+                };
+                concat(vec![object, text(format!(".{field}"))])
+            }
+            ExprKind::Lambda { params, ret, body } => self.lambda(params, ret, body),
+            ExprKind::Call { callee, args } => self.call(callee, args, expr.span),
+            ExprKind::Pipeline { head, stages } => {
+                let head = self.expr(head, OR);
+                let mut rest = Vec::new();
+                for stage in stages {
+                    rest.push(Doc::Line);
+                    rest.push(self.leading(stage.span.start, false));
+                    rest.push(text("|> "));
+                    rest.push(self.expr(stage, OR));
+                }
+                group(concat(vec![head, nest(concat(rest))]))
+            }
+            ExprKind::Binary { .. } | ExprKind::Logical { .. } => self.chain(expr),
+            ExprKind::Neg(inner) => {
+                // A minus straight onto a unit literal would fold into it.
+                let operand = if matches!(leftmost(inner).kind, ExprKind::NumberUnit { .. }) {
+                    let inner = self.expr_inner(inner);
+                    concat(vec![text("("), inner, text(")")])
+                } else {
+                    self.expr(inner, UNARY)
+                };
+                concat(vec![text("-"), operand])
+            }
+            ExprKind::Not(inner) => {
+                let operand = self.expr(inner, NOT);
+                concat(vec![text("not "), operand])
+            }
+            ExprKind::If { .. } => {
+                let parts = self.if_chain(expr);
+                group(concat(parts))
+            }
+            ExprKind::Match { scrutinee, arms } => self.match_expr(scrutinee, arms, false),
+        }
+    }
+
+    /// `match s with` and one `| pattern => body` line per arm, at the
+    /// indentation of the `match`. `inline` lets a short match print on one
+    /// line instead.
+    fn match_expr(&mut self, scrutinee: &Expr, arms: &[MatchArm], inline: bool) -> Doc {
+        let scrutinee = self.expr(scrutinee, OPEN);
+        let mut parts = vec![text("match "), scrutinee, text(" with")];
+        for (i, arm) in arms.iter().enumerate() {
+            parts.push(if inline { Doc::Line } else { Doc::HardLine });
+            parts.push(self.leading(arm.span.start, i > 0));
+            parts.push(self.arm(arm, i + 1 == arms.len()));
+        }
+        group(concat(parts))
+    }
+
+    /// A `let … in` / `:=` chain: one statement per line, then the result.
+    fn statements(&mut self, expr: &Expr) -> Doc {
+        let mut parts = Vec::new();
+        let mut current = expr;
+        loop {
+            let next = match &current.kind {
+                ExprKind::Let {
+                    mutable,
+                    name,
+                    ty,
+                    value,
+                    body,
+                } => {
+                    let keyword = if *mutable { "let mut" } else { "let" };
+                    let head = format!("{keyword} {name}{}", annotation(ty));
+                    parts.push(self.binding(head, value));
+                    body
+                }
+                ExprKind::Assign { name, value, rest } => {
+                    let value = self.expr(value, OPEN);
+                    parts.push(concat(vec![text(format!("{name} := ")), value, text(";")]));
+                    parts.push(Doc::HardLine);
+                    parts.push(self.leading(rest.span.start, true));
+                    current = rest;
+                    continue;
+                }
+                ExprKind::Match { scrutinee, arms } if self.is_destructuring_let(current) => {
+                    let pattern = self.pattern(&arms[0].pattern);
+                    let head = concat(vec![text("let "), pattern, text(" =")]);
+                    let lead = self.leading(scrutinee.span.start, false);
+                    let value = self.expr(scrutinee, OPEN);
+                    parts.push(if hugs(scrutinee) {
+                        concat(vec![head, text(" "), value])
+                    } else {
+                        group(concat(vec![
+                            head,
+                            nest(concat(vec![Doc::Line, lead, value])),
+                        ]))
+                    });
+                    &arms[0].body
+                }
+                _ => {
+                    parts.push(self.expr(current, OPEN));
+                    return concat(parts);
+                }
+            };
+            parts.push(text(" in"));
+            parts.push(Doc::HardLine);
+            parts.push(self.leading(next.span.start, true));
+            current = next;
+        }
+    }
+
+    /// `if c then a else b`, flattening an `else if` chain into one group:
+    /// flat when it fits, otherwise each branch on its own indented line.
+    fn if_chain(&mut self, expr: &Expr) -> Vec<Doc> {
+        let ExprKind::If {
+            cond,
+            then_branch,
+            else_branch,
+        } = &expr.kind
+        else {
+            unreachable!("if_chain on a non-if");
+        };
+        let cond = self.expr(cond, OPEN);
+        let then_lead = self.leading(then_branch.span.start, false);
+        let then_doc = self.expr(then_branch, OPEN);
+        let mut parts = vec![
+            text("if "),
+            cond,
+            text(" then"),
+            nest(concat(vec![Doc::Line, then_lead, then_doc])),
+            Doc::Line,
+            text("else"),
+        ];
+        if matches!(else_branch.kind, ExprKind::If { .. }) {
+            parts.push(text(" "));
+            parts.extend(self.if_chain(else_branch));
+        } else {
+            let lead = self.leading(else_branch.span.start, false);
+            let else_doc = self.expr(else_branch, OPEN);
+            parts.push(nest(concat(vec![Doc::Line, lead, else_doc])));
+        }
+        parts
+    }
+
+    fn arm(&mut self, arm: &MatchArm, last: bool) -> Doc {
+        let mut head = vec![text("| "), self.pattern(&arm.pattern)];
+        if let Some(guard) = &arm.guard {
+            head.push(text(" when "));
+            head.push(self.expr(guard, OPEN));
+        }
+        head.push(text(" =>"));
+        let lead = self.leading(arm.body.span.start, false);
+        let wrapped = !last && self.ends_in_match(&arm.body);
+        let body = if wrapped {
+            // A match in a non-final arm would take the following arms as
+            // its own.
+            self.parenthesized(&arm.body)
+        } else {
+            self.expr(&arm.body, OPEN)
+        };
+        let body = if is_block(&arm.body) && !wrapped {
+            nest(concat(vec![Doc::HardLine, lead, body]))
+        } else {
+            group(nest(concat(vec![Doc::Line, lead, body])))
+        };
+        head.push(body);
+        concat(head)
+    }
+
+    /// `(params): Ret => body`, the body on the same line when it fits.
+    fn lambda(&mut self, params: &[Param], ret: &Option<TypeName>, body: &Expr) -> Doc {
+        let params: Vec<Doc> = params
+            .iter()
+            .map(|param| text(format!("{}{}", param.name, annotation(&param.ty))))
+            .collect();
+        let head = if params.is_empty() {
+            text("()")
+        } else {
+            group(concat(vec![
+                text("("),
+                nest(concat(vec![Doc::SoftLine, comma_separated(params)])),
+                Doc::SoftLine,
+                text(")"),
+            ]))
+        };
+        let ret = match ret {
+            // In return position a function type needs its own parentheses:
+            // the `=>` after it is the body arrow.
+            Some(ty) if ty.name == "=>" => text(format!(": ({})", type_text(ty))),
+            Some(ty) => text(format!(": {}", type_text(ty))),
+            None => text(""),
+        };
+        let lead = self.leading(body.span.start, false);
+        let body_doc = self.expr(body, OPEN);
+        if hugs(body) && !matches!(body.kind, ExprKind::Lambda { .. }) && is_empty(&lead) {
+            // `=> {` / `=> [` — the brackets carry the body's own breaks.
+            return concat(vec![group(concat(vec![head, ret])), text(" => "), body_doc]);
+        }
+        let line = if is_block(body) {
+            Doc::HardLine
+        } else {
+            Doc::Line
+        };
+        group(concat(vec![
+            head,
+            ret,
+            text(" =>"),
+            nest(concat(vec![line, lead, body_doc])),
+        ]))
+    }
+
+    fn call(&mut self, callee: &Expr, args: &[Expr], span: Span) -> Doc {
+        let callee = if matches!(callee.kind, ExprKind::Ident(_)) {
+            self.expr_inner(callee)
+        } else {
+            self.expr(callee, POSTFIX)
+        };
+        if args.is_empty() {
+            let close = self.dangling(span.end);
+            return concat(vec![callee, text("("), close, text(")")]);
+        }
+        let mut leads = Vec::new();
+        let mut docs = Vec::new();
+        for arg in args {
+            leads.push(self.leading(arg.span.start, false));
+            docs.push(self.expr(arg, OPEN));
+        }
+        let close = self.dangling(span.end);
+        let last = args.last().expect("non-empty");
+        let simple_prefix = docs[..docs.len() - 1].iter().all(|doc| !is_hard(doc));
+        if hugs(last) && simple_prefix && leads.iter().all(is_empty) && is_empty(&close) {
+            // `f(a, (x) =>` … / `f([` … — the last argument opens on the
+            // call's own line and closes with it.
+            let mut parts = vec![callee, text("(")];
+            let count = docs.len();
+            for (i, doc) in docs.into_iter().enumerate() {
+                parts.push(doc);
+                if i + 1 < count {
+                    parts.push(text(", "));
+                }
+            }
+            parts.push(text(")"));
+            return concat(parts);
+        }
+        let items = leads
+            .into_iter()
+            .zip(docs)
+            .map(|(lead, doc)| concat(vec![lead, doc]))
+            .collect();
+        group(concat(vec![
+            callee,
+            text("("),
+            nest(concat(vec![
+                Doc::SoftLine,
+                comma_separated(items),
+                Doc::IfBreak(","),
+                close,
+            ])),
+            Doc::SoftLine,
+            text(")"),
+        ]))
+    }
+
+    fn list(&mut self, items: &[Expr], tail: Option<&Expr>, span: Span) -> Doc {
+        if items.is_empty() && tail.is_none() {
+            let close = self.dangling(span.end);
+            return concat(vec![text("["), close, text("]")]);
+        }
+        let mut entries = self.elements(items, true);
+        if let Some(tail) = tail {
+            let lead = self.leading(tail.span.start, false);
+            let tail = self.expr(tail, OPEN);
+            entries = concat(vec![entries, text(","), Doc::Line, lead, text(".."), tail]);
+        }
+        let close = self.dangling(span.end);
+        // A spread must come last: no trailing comma after it.
+        let comma = if tail.is_none() {
+            Doc::IfBreak(",")
+        } else {
+            text("")
+        };
+        group(concat(vec![
+            text("["),
+            nest(concat(vec![Doc::SoftLine, entries, comma, close])),
+            Doc::SoftLine,
+            text("]"),
+        ]))
+    }
+
+    /// Comma-separated full expressions, each with its leading comments.
+    fn elements(&mut self, items: &[Expr], keep_blank: bool) -> Doc {
+        let mut docs = Vec::new();
+        for (i, item) in items.iter().enumerate() {
+            let lead = self.leading(item.span.start, keep_blank && i > 0);
+            let doc = self.expr(item, OPEN);
+            docs.push(concat(vec![lead, doc]));
+        }
+        comma_separated(docs)
+    }
+
+    fn fields(&mut self, fields: &[Field]) -> Doc {
+        let mut docs = Vec::new();
+        for (i, field) in fields.iter().enumerate() {
+            let lead = self.leading(field.span.start, i > 0);
+            let value = if hugs(&field.value) {
+                let value = self.expr(&field.value, OPEN);
+                concat(vec![text(format!("{}: ", field.name)), value])
+            } else {
+                let value_lead = self.leading(field.value.span.start, false);
+                let value = self.expr(&field.value, OPEN);
+                group(concat(vec![
+                    text(format!("{}:", field.name)),
+                    nest(concat(vec![Doc::Line, value_lead, value])),
+                ]))
+            };
+            docs.push(concat(vec![lead, value]));
+        }
+        comma_separated(docs)
+    }
+
+    /// A left-associative operator chain at one precedence level
+    /// (`a + b - c`, `p && q && r`): flat, or one operand per line with the
+    /// operator leading it. An `&&` under `||` keeps (or gains) parentheses:
+    /// the grouping is the one readers should not have to work out.
+    fn chain(&mut self, expr: &Expr) -> Doc {
+        let level = precedence(expr);
+        let min = |operand: &Expr, min: u8| {
+            let and = matches!(
+                operand.kind,
+                ExprKind::Logical {
+                    op: LogicalOp::And,
+                    ..
+                }
+            );
+            if level == OR && and {
+                NOT
+            } else {
+                min
+            }
+        };
+        let mut operands = Vec::new();
+        let mut current = expr;
+        loop {
+            let (op, lhs, rhs) = match &current.kind {
+                ExprKind::Binary { op, lhs, rhs } if binary_precedence(*op) == level => {
+                    (op.symbol(), lhs, rhs)
+                }
+                ExprKind::Logical { op, lhs, rhs } if precedence(current) == level => {
+                    let symbol = match op {
+                        LogicalOp::And => "&&",
+                        LogicalOp::Or => "||",
+                    };
+                    (symbol, lhs, rhs)
+                }
+                _ => break,
+            };
+            operands.push((op, rhs.as_ref()));
+            current = lhs;
+        }
+        let first = self.expr(current, min(current, level));
+        let mut rest = Vec::new();
+        for (op, operand) in operands.into_iter().rev() {
+            rest.push(Doc::Line);
+            rest.push(self.leading(operand.span.start, false));
+            rest.push(text(format!("{op} ")));
+            rest.push(self.expr(operand, min(operand, level + 1)));
+        }
+        group(concat(vec![first, nest(concat(rest))]))
+    }
+
+    // --- Patterns ----------------------------------------------------------
+
+    fn pattern(&mut self, pattern: &Pattern) -> Doc {
+        match &pattern.kind {
+            PatternKind::Wildcard => text("_"),
+            PatternKind::Var(name) => text(name.clone()),
+            PatternKind::Number(_) | PatternKind::Int(_) | PatternKind::String(_) => {
+                text(self.core_text(pattern.span))
+            }
+            PatternKind::Bool(value) => text(value.to_string()),
+            PatternKind::Ctor { name, args } => {
+                if args.is_empty() {
+                    return text(name.clone());
+                }
+                let args = args.iter().map(|arg| self.pattern(arg)).collect();
+                self.bracketed(format!("{name}("), args, ")")
+            }
+            PatternKind::Tuple(items) => {
+                let items = items.iter().map(|item| self.pattern(item)).collect();
+                self.bracketed("(".to_string(), items, ")")
+            }
+            PatternKind::List { items, tail } => {
+                let mut items: Vec<Doc> = items.iter().map(|item| self.pattern(item)).collect();
+                if let Some(tail) = tail {
+                    let tail = self.pattern(tail);
+                    items.push(concat(vec![text(".."), tail]));
+                }
+                if items.is_empty() {
+                    return text("[]");
+                }
+                self.bracketed("[".to_string(), items, "]")
+            }
+            PatternKind::Record(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|field| match &field.pattern.kind {
+                        PatternKind::Var(name) if *name == field.name => text(name.clone()),
+                        _ => {
+                            let pattern = self.pattern(&field.pattern);
+                            concat(vec![text(format!("{}: ", field.name)), pattern])
+                        }
+                    })
+                    .collect();
+                group(concat(vec![
+                    text("{"),
+                    nest(concat(vec![Doc::Line, comma_separated(fields)])),
+                    Doc::Line,
+                    text("}"),
+                ]))
+            }
+            PatternKind::Or(alternatives) => {
+                let mut parts = Vec::new();
+                for (i, alternative) in alternatives.iter().enumerate() {
+                    if i > 0 {
+                        parts.push(text(" | "));
+                    }
+                    parts.push(self.pattern(alternative));
+                }
+                concat(parts)
+            }
+        }
+    }
+
+    fn bracketed(&mut self, open: String, items: Vec<Doc>, close: &str) -> Doc {
+        group(concat(vec![
+            text(open),
+            nest(concat(vec![Doc::SoftLine, comma_separated(items)])),
+            Doc::SoftLine,
+            text(close),
+        ]))
+    }
+}
+
+/// An item's full source extent (a `module`'s whole block, not its header).
+fn item_span(item: &Item) -> Span {
+    match item {
+        Item::Let(decl) => decl.span,
+        Item::Type(decl) => decl.span,
+        Item::Open(decl) => decl.span,
+        Item::Sig(decl) => decl.span,
+        Item::Expect(decl) => decl.span,
+        Item::Module(decl) => decl.block,
+        Item::Unit(decl) => decl.span,
+        Item::UnitOp(decl) => decl.span,
+    }
+}
+
+/// `: Type` for an optional binding annotation.
+fn annotation(ty: &Option<TypeName>) -> String {
+    ty.as_ref()
+        .map_or_else(String::new, |ty| format!(": {}", type_text(ty)))
+}
+
+/// A type expression on one line. Tuples (`*`) and function types (`=>`) are
+/// encoded on [`TypeName`] under reserved names (see the parser).
+/// The `private ` marker a declaration prints with, if it has one.
+fn visibility(private: bool) -> &'static str {
+    if private {
+        "private "
+    } else {
+        ""
+    }
+}
+
+fn type_text(ty: &TypeName) -> String {
+    let list = |types: &[TypeName]| types.iter().map(type_text).collect::<Vec<_>>().join(", ");
+    match ty.name.as_str() {
+        "*" => format!("({})", list(&ty.args)),
+        "=>" => {
+            let (ret, params) = ty.args.split_last().expect("a function type has a return");
+            format!("({}) => {}", list(params), type_text(ret))
+        }
+        _ if ty.args.is_empty() => ty.name.clone(),
+        _ => format!("{}<{}>", ty.name, list(&ty.args)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(src: &str) -> String {
+        format_source(src, false).unwrap_or_else(|e| panic!("{}: {src}", e.message))
+    }
+
+    #[test]
+    fn layout_is_canonical_and_idempotent() {
+        let src = "let   add=(a:int,b:int):int=>a+b\nlet xs = [1,2,3]\n\n\n\
+type   Shape=|Circle(radius:float)|Dot\n";
+        let expected = "let add = (a: int, b: int): int => a + b\nlet xs = [1, 2, 3]\n\n\
+type Shape =\n  | Circle(radius: float)\n  | Dot\n";
+        assert_eq!(fmt(src), expected);
+        assert_eq!(fmt(expected), expected);
+    }
+
+    #[test]
+    fn private_items_keep_their_keyword() {
+        let src = "private   type T=|A\nprivate let  x=1\nmodule M {\n  private let y = 2\n}\n";
+        let expected =
+            "private type T =\n  | A\nprivate let x = 1\nmodule M {\n  private let y = 2\n}\n";
+        assert_eq!(fmt(src), expected);
+        assert_eq!(fmt(expected), expected);
+    }
+
+    #[test]
+    fn comments_survive_in_place() {
+        let src = "// header\n\n/// doc\nlet a = 1 // trailing\n\nlet f = (x) =>\n  \
+// first\n  let y = x in // why\n  // then\n  y\n// end\n";
+        let expected = "// header\n\n/// doc\nlet a = 1 // trailing\n\nlet f = (x) =>\n  \
+// first\n  let y = x in // why\n  // then\n  y\n// end\n";
+        assert_eq!(fmt(src), expected);
+    }
+
+    #[test]
+    fn required_parentheses_are_restored() {
+        let src = "let a = (1 + 2) * 3\nlet b = 1 - (2 - 3)\nlet c = ((x) => x)(1)\n\
+let d = -(90deg)\nlet e = (if p then 1 else 2) + 3\n";
+        assert_eq!(fmt(src), src);
+        // Redundant ones go.
+        assert_eq!(fmt("let a = (1 * 2) + (3)\n"), "let a = 1 * 2 + 3\n");
+    }
+
+    #[test]
+    fn a_match_inside_a_non_final_arm_keeps_its_parentheses() {
+        let src =
+            "let f = (a, b) =>\n  match a with\n  | 0 => (match b with | 0 => 1 | _ => 2)\n  \
+| _ => 3\n";
+        assert_eq!(fmt(src), src);
+        // Broken over lines, the arms line up under the inner `match`.
+        let src = "let f = (a, b) =>\n  match a with\n  | 0 =>\n    (match b with\n    \
+| 0 => 1 // zero\n    | _ => 2)\n  | _ => 3\n";
+        assert_eq!(fmt(src), src);
+    }
+
+    #[test]
+    fn long_lines_break_at_groups() {
+        let src = format!(
+            "let r = {{ alpha: {0}, beta: {0}, gamma: {0} }}\n",
+            "someFunction(1, 2, 3, 4)"
+        );
+        assert_eq!(
+            fmt(&src),
+            "let r = {\n  alpha: someFunction(1, 2, 3, 4),\n  beta: someFunction(1, 2, 3, 4),\n  \
+gamma: someFunction(1, 2, 3, 4),\n}\n"
+        );
+    }
+
+    #[test]
+    fn destructuring_lets_print_as_lets() {
+        let src = "let f = (p) =>\n  let (a, b) = p in\n  let { x, y: z } = a in\n  x + z + b\n";
+        assert_eq!(fmt(src), src);
+    }
+
+    #[test]
+    fn interfaces_format_their_signatures() {
+        let src = "/// The empty set.\nlet empty:()=>Set<'a>\ntype t = host\n";
+        assert_eq!(
+            format_source(src, true).unwrap(),
+            "/// The empty set.\nlet empty : () => Set<'a>\ntype t = host\n"
+        );
+    }
+
+    #[test]
+    fn range_formatting_touches_only_the_overlapping_items() {
+        let src = "let a   = 1\nlet b   = 2\nlet c   = 3\n";
+        let start = src.find("let b").unwrap();
+        let (span, text) = format_range(src, false, Span::new(start, start + 3))
+            .unwrap()
+            .unwrap();
+        assert_eq!(&src[span.start..span.end], "let b   = 2");
+        assert_eq!(text, "let b = 2");
+    }
+
+    #[test]
+    fn a_parse_error_is_reported_not_formatted() {
+        let err = format_source("let = 3", false).unwrap_err();
+        assert_eq!(err.span, Span::new(4, 5));
+    }
+}
@@ -123,6 +123,9 @@ fn collect_binders(expr: &Expr, binders: &mut HashMap<u32, Span>) {
             collect_binders(scrutinee, binders);
             for arm in arms {
                 pattern_binders(&arm.pattern, binders);
+                if let Some(guard) = &arm.guard {
+                    collect_binders(guard, binders);
+                }
                 collect_binders(&arm.body, binders);
             }
         }
@@ -152,8 +155,16 @@ fn pattern_binders(pattern: &Pattern, binders: &mut HashMap<u32, Span>) {
                 pattern_binders(arg, binders);
             }
         }
+        PatternKind::Record(fields) => {
+            for field in fields {
+                pattern_binders(&field.pattern, binders);
+            }
+        }
+        // Every alternative binds the same ids; the first is the definition.
+        PatternKind::Or(alternatives) => pattern_binders(&alternatives[0], binders),
         PatternKind::Wildcard
         | PatternKind::Number(_)
+        | PatternKind::Int(_)
         | PatternKind::Bool(_)
         | PatternKind::String(_) => {}
     }
@@ -214,6 +225,9 @@ fn visit(expr: &Expr, targets: &Targets, consider: &mut impl FnMut(Span, Span))
             visit(scrutinee, targets, consider);
             for arm in arms {
                 pattern_refs(&arm.pattern, targets, consider);
+                if let Some(guard) = &arm.guard {
+                    visit(guard, targets, consider);
+                }
                 visit(&arm.body, targets, consider);
             }
         }
@@ -225,13 +239,42 @@ fn visit(expr: &Expr, targets: &Targets, consider: &mut impl FnMut(Span, Span))
     }
 }
 
-/// A constructor name in a pattern references its [`VariantDecl`]. The
-/// clickable region is the name part only — sub-patterns are binders, not
-/// references (and today they cannot nest further constructors).
+/// A constructor name in a pattern references its [`VariantDecl`], at any
+/// depth (`Some(Hit(p, _))` references both). The clickable region is the
+/// name part only — the rest of the pattern is sub-patterns.
 fn pattern_refs(pattern: &Pattern, targets: &Targets, consider: &mut impl FnMut(Span, Span)) {
-    if let PatternKind::Ctor { name, .. } = &pattern.kind {
-        let region = name_region(pattern.span, name);
-        offer(region, targets.ctors.get(name), consider);
+    match &pattern.kind {
+        PatternKind::Ctor { name, args } => {
+            let region = name_region(pattern.span, name);
+            offer(region, targets.ctors.get(name), consider);
+            for arg in args {
+                pattern_refs(arg, targets, consider);
+            }
+        }
+        PatternKind::Tuple(args) | PatternKind::Or(args) => {
+            for arg in args {
+                pattern_refs(arg, targets, consider);
+            }
+        }
+        PatternKind::List { items, tail } => {
+            for item in items {
+                pattern_refs(item, targets, consider);
+            }
+            if let Some(tail) = tail {
+                pattern_refs(tail, targets, consider);
+            }
+        }
+        PatternKind::Record(fields) => {
+            for field in fields {
+                pattern_refs(&field.pattern, targets, consider);
+            }
+        }
+        PatternKind::Wildcard
+        | PatternKind::Var { .. }
+        | PatternKind::Number(_)
+        | PatternKind::Int(_)
+        | PatternKind::Bool(_)
+        | PatternKind::String(_) => {}
     }
 }
 
@@ -380,6 +423,16 @@ mod tests {
         assert_eq!(def_at(&src, "Circle(r)"), Some("Circle(r: float)"));
     }
 
+    #[test]
+    fn nested_ctor_pattern_resolves_to_the_variant_decl() {
+        let src = format!(
+            "{SHAPE}type Wrap = | Wrap(s: Shape)\n\
+             let f = (w: Wrap): float => match w with | Wrap(Circle(r)) => r | _ => 0.0"
+        );
+        assert_eq!(def_at(&src, "Circle(r))"), Some("Circle(r: float)"));
+        assert_eq!(def_at(&src, "r)) =>"), None);
+    }
+
     #[test]
     fn a_pattern_variable_is_a_binder_not_a_reference() {
         let src =
@@ -105,6 +105,9 @@ fn visit(expr: &Expr, types: &ExprTypes, consider: &mut impl FnMut(Span, String)
             visit(scrutinee, types, consider);
             for arm in arms {
                 pattern_vars(&arm.pattern, types, consider);
+                if let Some(guard) = &arm.guard {
+                    visit(guard, types, consider);
+                }
                 visit(&arm.body, types, consider);
             }
         }
@@ -125,7 +128,7 @@ fn pattern_vars(pattern: &Pattern, types: &ExprTypes, consider: &mut impl FnMut(
             let ty = types.binding(*binding).cloned().unwrap_or(Type::Unknown);
             consider(pattern.span, format!("{name} : {ty}"));
         }
-        PatternKind::Ctor { args, .. } | PatternKind::Tuple(args) => {
+        PatternKind::Ctor { args, .. } | PatternKind::Tuple(args) | PatternKind::Or(args) => {
             for arg in args {
                 pattern_vars(arg, types, consider);
             }
@@ -138,8 +141,14 @@ fn pattern_vars(pattern: &Pattern, types: &ExprTypes, consider: &mut impl FnMut(
                 pattern_vars(tail, types, consider);
             }
         }
+        PatternKind::Record(fields) => {
+            for field in fields {
+                pattern_vars(&field.pattern, types, consider);
+            }
+        }
         PatternKind::Wildcard
         | PatternKind::Number(_)
+        | PatternKind::Int(_)
         | PatternKind::Bool(_)
         | PatternKind::String(_) => {}
     }
@@ -184,6 +193,7 @@ pub(crate) fn children(expr: &Expr) -> Vec<&Expr> {
         // pattern-var spans too, which are not expression nodes.
         ExprKind::Match { .. }
         | ExprKind::Number(_)
+        | ExprKind::Int(_)
         | ExprKind::String(_)
         | ExprKind::Bool(_)
         | ExprKind::Local { .. }
@@ -278,7 +288,7 @@ mod tests {
 
     #[test]
     fn hover_on_the_definition_name_shows_its_type() {
-        let text = hover_at("let threshold = 10", "threshold").unwrap();
+        let text = hover_at("let threshold = 10.0", "threshold").unwrap();
         assert_eq!(text, "threshold : float");
     }
 
@@ -48,6 +48,9 @@ fn visit(expr: &Expr, types: &ExprTypes, hints: &mut Vec<InlayHint>) {
         ExprKind::Match { scrutinee, arms } => {
             visit(scrutinee, types, hints);
             for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    visit(guard, types, hints);
+                }
                 visit(&arm.body, types, hints);
             }
         }
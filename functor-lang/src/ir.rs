@@ -17,7 +17,7 @@
 //! - **Type annotations stay symbolic** ([`TypeName`] carried through
 //!   verbatim) — no inference or checking until B4.
 
-use crate::ast::{BinOp, LogicalOp, TypeBody, TypeName};
+use crate::ast::{BinOp, ExpectKind, LogicalOp, TypeBody, TypeName};
 use crate::span::Span;
 use std::fmt;
 use std::rc::Rc;
@@ -74,6 +74,12 @@ pub struct Module {
     /// externals; they have no body, so evaluation never sees them (the host
     /// provides the value at runtime — there is no paired `.fun`).
     pub signatures: Vec<Signature>,
+    /// Signatures a SEALING `.funi` (one beside its own `.fun` — see
+    /// [`crate::project`]) declares for implemented values, keyed by the
+    /// def's canonical name. Unlike [`Module::signatures`] each names a def:
+    /// the checker verifies the def is at least as general, and types other
+    /// modules' uses of it from here.
+    pub sealed: Vec<Signature>,
     /// `expect <expr>` inline tests, in file order. Deliberately OUTSIDE
     /// `defs`: loading a session ([`crate::eval::Session::load`]) never
     /// evaluates them — only test tooling does
@@ -99,6 +105,10 @@ pub struct Module {
 pub struct UnitDef {
     pub suffix: String,
     pub target: Expr,
+    /// Whether the target takes an `int` (its first parameter is declared
+    /// one), so the suffix's literals are whole numbers: `(int) => 't`
+    /// rather than `(float) => 't`.
+    pub whole: bool,
     pub span: Span,
 }
 
@@ -123,13 +133,15 @@ pub struct UnitOpDef {
     pub span: Span,
 }
 
-/// One lowered `expect <expr>` test. `module` is the owning module's
-/// canonical prefix (`"Utils"`; empty for the entry) — the checker scopes
-/// bare record literals by it, like a def's name prefix. Unnamed: the span
-/// is the test's identity.
+/// One lowered `expect <expr>` test (or `property` — see
+/// [`ExpectKind::Property`], whose `expr` is always an all-annotated
+/// lambda). `module` is the owning module's canonical prefix (`"Utils"`;
+/// empty for the entry) — the checker scopes bare record literals by it,
+/// like a def's name prefix. Unnamed: the span is the test's identity.
 #[derive(Debug)]
 pub struct ExpectDef {
     pub module: String,
+    pub kind: ExpectKind,
     pub expr: Expr,
     pub span: Span,
 }
@@ -180,6 +192,7 @@ pub struct Expr {
 #[derive(Debug)]
 pub enum ExprKind {
     Number(f64),
+    Int(i64),
     String(String),
     /// `$"score: {score}"`, retaining expression nodes so interpolation
     /// participates in checking, tooling, tracing, and hot reload normally.
@@ -301,17 +314,18 @@ pub enum StringPart {
     Expr(Expr),
 }
 
-/// One lowered `| pattern => body` arm. Pattern variables are bindings
-/// scoped to `body` (they may shadow; duplicates within one pattern are
-/// lowering errors).
+/// One lowered `| pattern when guard => body` arm. Pattern variables are
+/// bindings scoped to the guard and `body` (they may shadow; duplicates
+/// within one pattern are lowering errors).
 #[derive(Debug)]
 pub struct MatchArm {
     pub pattern: Pattern,
+    pub guard: Option<Expr>,
     pub body: Expr,
     pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Pattern {
     pub kind: PatternKind,
     pub span: Span,
@@ -319,7 +333,7 @@ pub struct Pattern {
 
 /// The lowered pattern language (see [`crate::ast::PatternKind`]);
 /// variables carry their [`BindingId`]s.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum PatternKind {
     Wildcard,
     Var {
@@ -332,21 +346,36 @@ pub enum PatternKind {
         name: String,
         args: Vec<Pattern>,
     },
-    /// `(x, _)` — sub-patterns are bindings or `_`; arity must match.
+    /// `(x, _)` — arity must match.
     Tuple(Vec<Pattern>),
     /// `[]` (empty), `[a, b]` (exact length), `[head, ..rest]` (at least
-    /// `items.len()`, `rest` binds the remainder as a list). Element and
-    /// tail sub-patterns are variable bindings or `_` only (like tuple/ctor
-    /// patterns). `tail: None` means an exact-length match.
+    /// `items.len()`, `rest` matches the remainder as a list). `tail: None`
+    /// means an exact-length match.
     List {
         items: Vec<Pattern>,
         tail: Option<Box<Pattern>>,
     },
+    /// `{ state: Playing, lives }` — the named fields must exist; others are
+    /// ignored. Lowering guarantees the field names are distinct.
+    Record(Vec<FieldPattern>),
+    /// `A | B` — every alternative binds the same names to the SAME
+    /// binding ids, so the arm body sees one variable whichever matched.
+    Or(Vec<Pattern>),
     Number(f64),
+    Int(i64),
     Bool(bool),
     String(String),
 }
 
+/// One `name: pattern` entry of a record pattern (punning is resolved by
+/// the parser, so every entry has an explicit sub-pattern).
+#[derive(Debug, Clone)]
+pub struct FieldPattern {
+    pub name: String,
+    pub pattern: Pattern,
+    pub span: Span,
+}
+
 /// One `name: value` entry of a record expression.
 #[derive(Debug)]
 pub struct Field {
@@ -2,18 +2,26 @@
 //! always ends with exactly one `Eof` token so the parser can peek ahead
 //! without bounds checks.
 
+use crate::ast::Numeral;
 use crate::span::Span;
 use crate::ParseError;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenKind {
+    /// A literal written with a fractional part: `1.0`, `0.25`.
     Number(f64),
+    /// A literal written as bare digits: `42`. Whole numbers are `int`s —
+    /// the dot is what makes a literal a `float`.
+    Int(i64),
     /// A numeric literal immediately followed by identifier characters:
     /// `90deg`, `0.5s`, `16px`. The lexer is deliberately DUMB here — it does
     /// not know which units exist, only that the source spelled one; the
     /// suffix resolves against the project's `unit` declarations in lowering
     /// (see [`crate::lower`]). Adjacency is required: `90 deg` is two tokens.
-    NumberUnit(f64, String),
+    /// The digits keep their spelling ([`Numeral`]); whether `90deg` builds
+    /// from an int or a float is the unit's target's business, not the
+    /// lexer's.
+    NumberUnit(Numeral, String),
     Str(String),
     InterpolatedStart,
     InterpolatedText(String),
@@ -60,6 +68,7 @@ pub enum TokenKind {
     Minus,
     Star,
     Slash,
+    Percent,
     Lt,
     Gt,
     LtEq,
@@ -77,7 +86,8 @@ pub struct Token {
 pub fn describe(kind: &TokenKind) -> String {
     use TokenKind::*;
     match kind {
-        Number(n) => format!("number `{n}`"),
+        Number(n) => format!("number `{n:?}`"),
+        Int(n) => format!("number `{n}`"),
         NumberUnit(n, suffix) => format!("number `{n}{suffix}`"),
         Str(_) => "a string".to_string(),
         InterpolatedStart => "an interpolated string".to_string(),
@@ -123,6 +133,7 @@ pub fn describe(kind: &TokenKind) -> String {
         Minus => "`-`".to_string(),
         Star => "`*`".to_string(),
         Slash => "`/`".to_string(),
+        Percent => "`%`".to_string(),
         Lt => "`<`".to_string(),
         Gt => "`>`".to_string(),
         LtEq => "`<=`".to_string(),
@@ -242,6 +253,10 @@ fn lex_with_interpolation_depth(
                 i += 1;
                 TokenKind::Slash
             }
+            b'%' => {
+                i += 1;
+                TokenKind::Percent
+            }
             // `<=` / `>=` are single tokens. A generic close immediately
             // followed by `=` (`List<float>= …`) therefore lexes as `>=`; the
             // parser splits it back apart at its two generic-close sites.
@@ -329,13 +344,39 @@ fn lex_with_interpolation_depth(
                 while i < bytes.len() && bytes[i].is_ascii_digit() {
                     i += 1;
                 }
-                if i + 1 < bytes.len() && bytes[i] == b'.' && bytes[i + 1].is_ascii_digit() {
+                let fractional =
+                    i + 1 < bytes.len() && bytes[i] == b'.' && bytes[i + 1].is_ascii_digit();
+                if fractional {
                     i += 1;
                     while i < bytes.len() && bytes[i].is_ascii_digit() {
                         i += 1;
                     }
                 }
-                let n = src[start..i].parse().expect("digit runs parse as f64");
+                // The dot decides the kind: `42` is an int and `42.0` a
+                // float. An int literal too large for 64 bits is refused
+                // here rather than silently rounded into a float.
+                let n = if fractional {
+                    Numeral::Float(src[start..i].parse().expect("digit runs parse as f64"))
+                } else {
+                    match src[start..i].parse() {
+                        Ok(n) => Numeral::Int(n),
+                        Err(_) => {
+                            return Err(ParseError {
+                                message: format!(
+                                    "integer literal `{}` does not fit in an `int` (64-bit) — \
+write `{}.0` for a float",
+                                    &src[start..i],
+                                    &src[start..i]
+                                ),
+                                span: Span::new(base + start, base + i),
+                            })
+                        }
+                    }
+                };
+                let plain = |n: Numeral| match n {
+                    Numeral::Int(n) => TokenKind::Int(n),
+                    Numeral::Float(n) => TokenKind::Number(n),
+                };
                 // A unit SUFFIX: identifier chars touching the digits
                 // (`90deg`, `0.5s`). There is no scientific notation, so no
                 // `1e5` ambiguity, and `90deg` is currently a parse error —
@@ -355,12 +396,12 @@ fn lex_with_interpolation_depth(
                     // keyword lex on its own.
                     if keyword(suffix).is_some() {
                         i = suffix_start;
-                        TokenKind::Number(n)
+                        plain(n)
                     } else {
                         TokenKind::NumberUnit(n, suffix.to_string())
                     }
                 } else {
-                    TokenKind::Number(n)
+                    plain(n)
                 }
             }
             b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
@@ -4,18 +4,24 @@
 //! Lexer + hand-rolled recursive-descent parser producing a surface AST in
 //! which every node carries a byte-offset [`Span`] (line/col derive from the
 //! source via [`line_col`]); a lowering pass ([`lower`]) from that AST to the
-//! name-resolved core IR ([`ir`]); a tree-walking interpreter over the IR
-//! ([`eval`]) with an optional call trace; and a gradual typechecker over the
+//! name-resolved core IR ([`ir`]); an interpreter over the IR ([`eval`]) that
+//! runs function bodies as slot-resolved bytecode, with an optional call
+//! trace; and a gradual typechecker over the
 //! IR ([`types`]) — checking with annotations, not inference.
 
+pub mod actions;
 pub mod ast;
+mod bytecode;
 pub mod codelens;
 pub mod complete;
 pub mod eval;
+mod exhaustive;
 pub mod docs;
+pub mod format;
 pub mod goto;
 pub mod hover;
 pub mod coverage;
+pub mod debug;
 pub mod inlay;
 pub mod ir;
 // Public for consumers that need raw string literals without evaluating
@@ -23,16 +29,26 @@ pub mod ir;
 pub mod lexer;
 mod lower;
 mod parser;
+mod persistent;
 pub mod project;
+pub mod profile;
+pub mod property;
 pub mod rebind;
+pub mod references;
+pub mod repl;
+pub mod semantic;
+pub mod signature;
+pub mod snapshot;
+pub mod symbols;
 mod span;
 pub mod trace;
 pub mod types;
 pub mod value;
 
 pub use eval::{
-    render_trace, run, run_expects, run_expects_budgeted, run_with_host, ExpectOutcome,
-    ExpectReport, FailedCompare,
+    render_trace, run, run_expects, run_expects_budgeted, run_expects_covered, run_expects_with,
+    run_with_host,
+    Counterexample, ExpectOptions, ExpectOutcome, ExpectReport, FailedCompare,
     Host, NoHost, RecordedBinding, RecordedInvocation, RecordedKind, RecordedSite, RunFailure,
     RunOutcome, RunRecord, Session, Tracing,
 };
@@ -71,7 +87,7 @@ pub struct RunError {
 /// One typechecking diagnostic: same shape and rendering as [`ParseError`].
 /// Unlike the other error kinds, [`check`] collects *all* of them rather
 /// than stopping at the first.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CheckError {
     pub message: String,
     pub span: Span,
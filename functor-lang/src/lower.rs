@@ -48,6 +48,11 @@
 //! that collides with a builtin namespace, another file's module, an
 //! `open`ed name, or the file's own top-level names.
 //!
+//! Visibility is per FILE. [`Exports`] records what other files may not
+//! reach — `private` items, and everything a sealing `.funi` leaves out (see
+//! [`crate::project`]) — and a qualified reference to one from another file
+//! is a lowering error; `open` silently skips them.
+//!
 //! ## Name resolution
 //!
 //! For an identifier `first(.rest)*` (the parser only builds multi-segment
@@ -83,7 +88,8 @@
 //! Pattern variables get fresh [`BindingId`]s scoped to their arm's body
 //! (each arm is its own scope level — bindings never leak between arms);
 //! they are plain immutable bindings, so lambdas may capture them. A
-//! duplicate variable within one pattern, an unknown constructor in a
+//! duplicate variable within one pattern (at any nesting depth), a
+//! duplicate field within one record pattern, an unknown constructor in a
 //! pattern, and a constructor pattern whose sub-pattern count differs from
 //! the declared field count are all lowering errors.
 //!
@@ -123,6 +129,22 @@ pub(crate) struct Exports {
     /// stays an [`ExprKind::External`] (host-resolved at runtime), unlike a
     /// `def` which becomes a [`ExprKind::Global`].
     pub signatures: HashSet<String>,
+    /// Defs, constructors, and signatures whose FIRST parameter is declared
+    /// `int` — a `unit` targeting one of these takes whole-number literals
+    /// (`3tiles`), where every other target takes floats (`90deg`).
+    pub whole: HashSet<String>,
+    /// Value-namespace names (defs and constructors) other FILES may not
+    /// reach: `private` ones, and whatever a sealing `.funi` leaves out. The
+    /// declaring file itself still sees them.
+    pub hidden: HashSet<String>,
+    /// The type-namespace twin of [`Exports::hidden`].
+    pub hidden_types: HashSet<String>,
+    /// Types a sealing `.funi` declares ABSTRACT: other files may name them,
+    /// but not build, match, or look inside their values.
+    pub opaque: HashSet<String>,
+    /// The file name of the `.funi` sealing this module, if one does — the
+    /// visibility errors name it.
+    pub sealed_by: Option<String>,
 }
 
 pub(crate) fn exports_of(items: &[ast::Item]) -> Exports {
@@ -131,19 +153,47 @@ pub(crate) fn exports_of(items: &[ast::Item]) -> Exports {
         match item {
             ast::Item::Let(decl) => {
                 exports.defs.insert(decl.name.clone());
+                if decl.private {
+                    exports.hidden.insert(decl.name.clone());
+                }
+                // `let f: (int) => T = …` or `let f = (n: int) => …`.
+                let first = match (&decl.ty, &decl.value.kind) {
+                    (Some(ty), _) => first_param(ty),
+                    (None, ast::ExprKind::Lambda { params, .. }) => {
+                        params.first().and_then(|param| param.ty.as_ref())
+                    }
+                    _ => None,
+                };
+                if first.is_some_and(is_int) {
+                    exports.whole.insert(decl.name.clone());
+                }
             }
             ast::Item::Type(decl) => {
                 exports.types.insert(decl.name.clone());
+                if decl.private {
+                    exports.hidden_types.insert(decl.name.clone());
+                }
                 if let ast::TypeBody::Variants(variants) = &decl.body {
                     for variant in variants {
                         exports
                             .ctors
                             .insert(variant.name.clone(), variant.fields.len());
+                        // A private type's constructors are as private as
+                        // its name.
+                        if decl.private {
+                            exports.hidden.insert(variant.name.clone());
+                        }
+                        if variant.fields.first().is_some_and(|field| is_int(&field.ty)) {
+                            exports.whole.insert(variant.name.clone());
+                        }
                     }
                 }
             }
             ast::Item::Sig(decl) => {
                 exports.signatures.insert(decl.name.clone());
+                if first_param(&decl.ty).is_some_and(is_int) {
+                    exports.whole.insert(decl.name.clone());
+                }
             }
             ast::Item::Open(_) => {}
             // An expect binds nothing.
@@ -160,6 +210,15 @@ pub(crate) fn exports_of(items: &[ast::Item]) -> Exports {
     exports
 }
 
+/// A function type annotation's first parameter (`(int) => T` → `int`).
+fn first_param(ty: &ast::TypeName) -> Option<&ast::TypeName> {
+    (ty.name == "=>" && ty.args.len() >= 2).then(|| &ty.args[0])
+}
+
+fn is_int(ty: &ast::TypeName) -> bool {
+    ty.name == "int" && ty.args.is_empty()
+}
+
 /// The project context a module lowers in (see the module docs): its own
 /// name, the entry module's name (entry members canonicalize BARE), and
 /// every module's exports (self included).
@@ -181,6 +240,9 @@ pub(crate) struct ProjectEnv<'a> {
 #[derive(Clone, Debug)]
 pub(crate) struct UnitTarget {
     kind: UnitKind,
+    /// The target takes an `int` (see [`Exports::whole`]): the suffix's
+    /// literals lower to int arguments, and a fractional one is refused.
+    whole: bool,
     /// The module path providing the target (`"Utils"`, `"Angle"`, or the
     /// declaring module itself). A use site records this as a dependency,
     /// exactly as writing the call by hand would.
@@ -345,7 +407,7 @@ fn collect_names(items: &[ast::Item]) -> Result<Names, LowerError> {
                 // types), yielding nonsense like "expected float, got float".
                 if matches!(
                     decl.name.as_str(),
-                    "float" | "bool" | "string" | "unknown" | "List" | "Map"
+                    "float" | "int" | "bool" | "string" | "unknown" | "List" | "Array" | "Map" | "Set"
                 ) {
                     return Err(LowerError {
                         message: format!("cannot redeclare builtin type `{}`", decl.name),
@@ -459,6 +521,8 @@ are project-wide, like constructors)"
             types,
             defs,
             signatures,
+            // Sealing is a project-level relation between two files.
+            sealed: Vec::new(),
             expects,
             units,
             unit_ops,
@@ -570,7 +634,11 @@ entry, and the `module` blocks they declare",
             deps.insert(file.to_string());
         }
         // Value namespace: the opened module's defs, constructors, and
-        // interface signatures.
+        // interface signatures — only those reachable from here (an `open`
+        // of another file skips its hidden names; see `Exports::hidden`).
+        let reachable = |name: &String, hidden: &HashSet<String>| {
+            file == env.name || !hidden.contains(name)
+        };
         let mut values: Vec<(&String, OpenedName)> = exports
             .defs
             .iter()
@@ -587,6 +655,7 @@ entry, and the `module` blocks they declare",
                     .iter()
                     .map(|s| (s, OpenedName::Signature(module_path.clone()))),
             )
+            .filter(|(name, _)| reachable(name, &exports.hidden))
             .collect();
         values.sort_by_key(|(name, _)| name.as_str().to_string());
         for (name, opened) in values {
@@ -621,7 +690,11 @@ uses as `{}.{name}` instead of opening",
             open_values.insert(name.clone(), opened);
         }
         // Type namespace.
-        let mut types: Vec<&String> = exports.types.iter().collect();
+        let mut types: Vec<&String> = exports
+            .types
+            .iter()
+            .filter(|name| reachable(name, &exports.hidden_types))
+            .collect();
         types.sort();
         for name in types {
             if inline_names.contains_key(name) || type_names.contains(name) {
@@ -653,6 +726,7 @@ qualify uses (`{prev}.{name}` / `{}.{name}`)",
         ctors,
         types: type_names,
         ctor_types,
+        whole: exports_of(items).whole,
         local_modules: inline_names,
         inline: None,
         project,
@@ -728,6 +802,9 @@ struct Lowerer<'p> {
     /// Only used to teach the `Shape.Circle` mistake (constructors resolve
     /// bare); resolution itself never consults it.
     ctor_types: HashMap<String, String>,
+    /// This file's own top-level names that take an `int` first (see
+    /// [`Exports::whole`]) — what a `unit` targeting one of them builds from.
+    whole: HashSet<String>,
     /// This file's inline `module` blocks, by name. Qualified references
     /// (`Server.step`) resolve here BEFORE sibling files, so a file's own
     /// module shadows a same-named sibling.
@@ -860,6 +937,43 @@ impl Lowerer<'_> {
         }
     }
 
+    /// Refuse a reference from another FILE to a member `key` hides from it
+    /// (see [`Exports::hidden`]); `ty` picks the type namespace.
+    fn check_reachable(
+        &self,
+        key: &ModuleKey,
+        member: &str,
+        ty: bool,
+        span: Span,
+    ) -> Result<(), LowerError> {
+        let ModuleKey::Project(path) = key else {
+            return Ok(());
+        };
+        let file = path.split('.').next().unwrap_or(path);
+        if self.project.is_some_and(|env| env.name == file) {
+            return Ok(());
+        }
+        let exports = self.exports_of_key(key);
+        let hidden = if ty {
+            &exports.hidden_types
+        } else {
+            &exports.hidden
+        };
+        if !hidden.contains(member) {
+            return Ok(());
+        }
+        let message = match &exports.sealed_by {
+            Some(interface) => format!(
+                "`{path}.{member}` is not part of module `{path}`'s interface ({interface}) — \
+declare it there to use it from other modules"
+            ),
+            None => format!(
+                "`{path}.{member}` is private to module `{path}` — only its own file can use it"
+            ),
+        };
+        Err(LowerError { message, span })
+    }
+
     /// Record the dependency edge a resolved reference implies (a local
     /// inline module is in this very file, so it implies none).
     fn dep_key(&mut self, key: &ModuleKey) {
@@ -918,7 +1032,8 @@ impl Lowerer<'_> {
         if known.is_empty() {
             format!(
                 "unknown unit `{suffix}` in `…{suffix}` — no units are declared; declare one \
-with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call itself"
+with `unit {suffix} = SomeFn` (a `(float) => 't` or `(int) => 't` function), or write the \
+call itself"
             )
         } else {
             format!(
@@ -950,6 +1065,7 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
             .cloned()
             .or_else(|| self.current_path())
             .unwrap_or_default();
+        let whole = self.takes_int(&decl.target);
         let kind = match resolved.kind {
             ExprKind::Global(name) => UnitKind::Global(name),
             ExprKind::Ctor { name, arity } => UnitKind::Ctor { name, arity },
@@ -959,6 +1075,7 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
                 let module = path.first().cloned().unwrap_or_default();
                 return Ok(UnitTarget {
                     kind: UnitKind::External(path),
+                    whole,
                     owner: module,
                 });
             }
@@ -973,7 +1090,33 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
                 })
             }
         };
-        Ok(UnitTarget { kind, owner })
+        Ok(UnitTarget { kind, whole, owner })
+    }
+
+    /// Whether a unit's (already resolvable) target name is declared to take
+    /// an `int`, read off whichever module declares it — the same lookup
+    /// order [`Self::ident`] resolves the name by.
+    fn takes_int(&self, target: &[String]) -> bool {
+        let Some((member, path)) = target.split_last() else {
+            return false;
+        };
+        if path.is_empty() {
+            if self.globals.contains(member) || self.ctors.contains_key(member) {
+                return self.whole.contains(member);
+            }
+            return self.open_values.get(member).is_some_and(|opened| {
+                self.project
+                    .and_then(|env| env.modules.get(opened.module()))
+                    .is_some_and(|exports| exports.whole.contains(member))
+            });
+        }
+        let borrowed: Vec<&str> = target.iter().map(String::as_str).collect();
+        match self.resolve_module_prefix(&borrowed) {
+            Some((key, consumed)) if consumed == path.len() => {
+                self.exports_of_key(&key).whole.contains(member)
+            }
+            _ => false,
+        }
     }
 
     /// Lower one item into `out`, in the current namespace.
@@ -1018,13 +1161,19 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
             }
             // A unit: no name, no DefId. Uses are already desugared to the
             // plain call; the resolved target is kept only so the CHECKER can
-            // verify it really is a `(float) => 't` function (and teach the
+            // verify it really is a `(float) => 't` (or, for a whole-number
+            // unit, `(int) => 't`) function (and teach the
             // suffix in branded-value errors).
             ast::Item::Unit(decl) => {
                 let target = self.ident(decl.target.clone(), decl.target_span)?;
+                let whole = self
+                    .units
+                    .get(&decl.suffix)
+                    .is_some_and(|unit| unit.whole);
                 out.units.push(UnitDef {
                     suffix: decl.suffix,
                     target,
+                    whole,
                     span: decl.span,
                 });
             }
@@ -1066,6 +1215,7 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
                     .unwrap_or_default();
                 out.expects.push(ExpectDef {
                     module,
+                    kind: decl.kind,
                     expr: self.expr(decl.expr)?,
                     span: decl.span,
                 });
@@ -1096,6 +1246,7 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
                             span: ty.span,
                         });
                     }
+                    self.check_reachable(&key, &member, true, ty.span)?;
                     self.dep_key(&key);
                     self.qualify_key(&key, &member)
                 }
@@ -1202,6 +1353,7 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
                 return Ok(piped);
             }
             ast::ExprKind::Number(n) => ExprKind::Number(n),
+            ast::ExprKind::Int(n) => ExprKind::Int(n),
             // `90deg` → exactly `Angle.degrees(90.0)`: the declared unit's
             // target applied to the literal. Everything downstream (hover,
             // inlay, the checker, the host's teaching errors) sees an
@@ -1213,6 +1365,24 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
                         span,
                     });
                 };
+                // The digits become whichever number the target takes: a
+                // float unit reads `90deg` as `90.0`, and an int unit refuses
+                // a fraction rather than truncating it.
+                let value = match (value, target.whole) {
+                    (ast::Numeral::Int(n), true) => ExprKind::Int(n),
+                    (ast::Numeral::Int(n), false) => ExprKind::Number(n as f64),
+                    (ast::Numeral::Float(n), false) => ExprKind::Number(n),
+                    (ast::Numeral::Float(n), true) => {
+                        return Err(LowerError {
+                            message: format!(
+                                "`{suffix}` counts whole numbers (its target takes an `int`), \
+so `{n:?}{suffix}` has no meaning — write a whole number like `{}{suffix}`",
+                                n.trunc() as i64
+                            ),
+                            span,
+                        });
+                    }
+                };
                 // The literal calls another module's function, so it carries
                 // that module's dependency edge — exactly as writing the call
                 // by hand would (evaluation order and cycle detection both
@@ -1233,7 +1403,7 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
                 };
                 let arg = Expr {
                     id: self.expr_id(),
-                    kind: ExprKind::Number(value),
+                    kind: value,
                     span: digits,
                 };
                 ExprKind::Call {
@@ -1504,10 +1674,13 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
                         lambda_boundary: false,
                         vars,
                     });
+                    // The guard sees the pattern's variables, like the body.
+                    let guard = arm.guard.map(|guard| self.expr(guard)).transpose();
                     let body = self.expr(arm.body);
                     self.scopes.pop();
                     lowered.push(MatchArm {
                         pattern,
+                        guard: guard?,
                         body: body?,
                         span: arm.span,
                     });
@@ -1548,6 +1721,7 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
                     span,
                 });
             };
+            self.check_reachable(&key, &member, false, span)?;
             self.dep_key(&key);
             return Ok((self.qualify_key(&key, &member), arity));
         }
@@ -1572,9 +1746,8 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
     }
 
     /// Lower one pattern, appending its variable bindings to `vars` (the
-    /// caller pushes them as the arm body's scope). Pattern nesting is
-    /// bounded by the grammar (constructor sub-patterns are leaves), so
-    /// recursion depth is at most two.
+    /// caller pushes them as the arm body's scope). Recursion follows the
+    /// pattern's nesting, which the parser's depth guard already bounds.
     fn pattern(
         &mut self,
         pattern: ast::Pattern,
@@ -1636,7 +1809,71 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
                     tail,
                 }
             }
+            // The first alternative binds normally; each later one lowers
+            // against the names bound BEFORE the or-pattern, must introduce
+            // exactly the first's names, and is rewritten onto its ids.
+            ast::PatternKind::Or(alternatives) => {
+                let base = vars.len();
+                let mut lowered = Vec::new();
+                for alternative in alternatives {
+                    let alt_span = alternative.span;
+                    if lowered.is_empty() {
+                        lowered.push(self.pattern(alternative, vars)?);
+                        continue;
+                    }
+                    let mut alt_vars = vars[..base].to_vec();
+                    let mut alt = self.pattern(alternative, &mut alt_vars)?;
+                    let missing = vars[base..]
+                        .iter()
+                        .find(|(name, _, _)| !alt_vars[base..].iter().any(|(n, _, _)| n == name))
+                        .or_else(|| {
+                            alt_vars[base..]
+                                .iter()
+                                .find(|(name, _, _)| !vars[base..].iter().any(|(n, _, _)| n == name))
+                        });
+                    if let Some((name, _, _)) = missing {
+                        return Err(LowerError {
+                            message: format!(
+                                "`{name}` must be bound in every alternative of this or-pattern"
+                            ),
+                            span: alt_span,
+                        });
+                    }
+                    let ids: Vec<(BindingId, BindingId)> = alt_vars[base..]
+                        .iter()
+                        .map(|(name, id, _)| {
+                            let first = vars[base..]
+                                .iter()
+                                .find(|(n, _, _)| n == name)
+                                .map(|(_, first, _)| *first)
+                                .expect("checked above");
+                            (*id, first)
+                        })
+                        .collect();
+                    reassign_bindings(&mut alt, &ids);
+                    lowered.push(alt);
+                }
+                PatternKind::Or(lowered)
+            }
+            ast::PatternKind::Record(fields) => {
+                let mut lowered: Vec<FieldPattern> = Vec::new();
+                for field in fields {
+                    if lowered.iter().any(|f| f.name == field.name) {
+                        return Err(LowerError {
+                            message: format!("duplicate field `{}` in record pattern", field.name),
+                            span: field.span,
+                        });
+                    }
+                    lowered.push(FieldPattern {
+                        pattern: self.pattern(field.pattern, vars)?,
+                        name: field.name,
+                        span: field.span,
+                    });
+                }
+                PatternKind::Record(lowered)
+            }
             ast::PatternKind::Number(n) => PatternKind::Number(n),
+            ast::PatternKind::Int(n) => PatternKind::Int(n),
             ast::PatternKind::Bool(b) => PatternKind::Bool(b),
             ast::PatternKind::String(s) => PatternKind::String(s),
         };
@@ -1754,6 +1991,7 @@ with `unit {suffix} = SomeFn` (a `(float) => 't` function), or write the call it
             None if module_ref.is_some() => {
                 let (key, consumed) = module_ref.expect("checked above");
                 let member = segments[consumed].clone();
+                self.check_reachable(&key, &member, false, span)?;
                 let exports = self.exports_of_key(&key);
                 let kind = if exports.defs.contains(&member) {
                     ExprKind::Global(self.qualify_key(&key, &member))
@@ -1850,3 +2088,38 @@ module — constructors resolve bare: write `{}`",
         None
     }
 }
+
+/// Rewrite a lowered or-pattern alternative's variable ids onto the first
+/// alternative's (`ids` maps each fresh id to the one it replaces it with).
+fn reassign_bindings(pattern: &mut Pattern, ids: &[(BindingId, BindingId)]) {
+    match &mut pattern.kind {
+        PatternKind::Var { binding, .. } => {
+            if let Some((_, first)) = ids.iter().find(|(fresh, _)| fresh == binding) {
+                *binding = *first;
+            }
+        }
+        PatternKind::Ctor { args, .. } | PatternKind::Tuple(args) | PatternKind::Or(args) => {
+            for arg in args {
+                reassign_bindings(arg, ids);
+            }
+        }
+        PatternKind::List { items, tail } => {
+            for item in items {
+                reassign_bindings(item, ids);
+            }
+            if let Some(tail) = tail {
+                reassign_bindings(tail, ids);
+            }
+        }
+        PatternKind::Record(fields) => {
+            for field in fields {
+                reassign_bindings(&mut field.pattern, ids);
+            }
+        }
+        PatternKind::Wildcard
+        | PatternKind::Number(_)
+        | PatternKind::Int(_)
+        | PatternKind::Bool(_)
+        | PatternKind::String(_) => {}
+    }
+}
@@ -1,4 +1,4 @@
-//! The `functor_lang` CLI. Six subcommands:
+//! The `functor_lang` CLI. Seven subcommands:
 //!
 //! ```text
 //! functor-lang parse <file.fun>   # print the surface AST (pretty-Debug; this file only)
@@ -6,11 +6,12 @@
 //! functor-lang check <file.fun>   # typecheck the project; all diagnostics, exit 1
 //! functor-lang run <file.fun>     # evaluate; print main's result, or the entry's bindings
 //! functor-lang trace <file.fun>   # evaluate with the call trace; print the trace
-//! functor-lang test <file.fun>    # evaluate the project's `expect` tests; exit 1 on failure
+//! functor-lang test <file.fun> [--seed N] [--cases N]  # run `expect`/`property` tests
+//! functor-lang repl <file.fun>    # load the project; evaluate expressions and lets interactively
 //! functor-lang bench [--all] [--json] [<file.fun>|<dir>]  # time interpreter eval (see bench.rs)
 //! ```
 //!
-//! `ir`/`check`/`run`/`trace`/`test` treat the file as a project entry (B8): every
+//! `ir`/`check`/`run`/`trace`/`test`/`repl` treat the file as a project entry (B8): every
 //! sibling `.fun` file in its directory loads with it — file = module,
 //! whole-program checking. `parse` stays single-file (it shows one file's
 //! surface syntax).
@@ -31,18 +32,40 @@ fn main() {
     if args.first().is_some_and(|a| a == "bench") {
         bench::main(&args[1..]);
     }
+    let usage = || -> ! {
+        eprintln!(
+            "usage: functor-lang <parse|ir|check|run|trace|test|repl> <file.fun>\n       functor-lang test <file.fun> [--seed N] [--cases N] [--update-snapshots]\n       functor-lang bench [--all] [--json] [<file.fun>|<dir>]"
+        );
+        exit(2);
+    };
+    // `test` alone takes flags: how `property` tests sample, and whether
+    // `expectSnapshot` tests accept their fresh values.
+    let mut options = functor_lang::ExpectOptions::default();
+    let mut update_snapshots = false;
     let (command, path) = match args.as_slice() {
         [command, path]
-            if ["parse", "ir", "check", "run", "trace", "test"].contains(&command.as_str()) =>
+            if ["parse", "ir", "check", "run", "trace", "test", "repl"].contains(&command.as_str()) =>
         {
             (command.as_str(), path)
         }
-        _ => {
-            eprintln!(
-                "usage: functor-lang <parse|ir|check|run|trace|test> <file.fun>\n       functor-lang bench [--all] [--json] [<file.fun>|<dir>]"
-            );
-            exit(2);
+        [command, path, flags @ ..] if command == "test" => {
+            let mut flags = flags.iter();
+            while let Some(flag) = flags.next() {
+                if flag == "--update-snapshots" {
+                    update_snapshots = true;
+                    continue;
+                }
+                match (flag.as_str(), flags.next().map(|n| n.parse::<u64>())) {
+                    ("--seed", Some(Ok(seed))) => options.seed = seed,
+                    ("--cases", Some(Ok(cases))) if cases > 0 => {
+                        options.cases = u32::try_from(cases).unwrap_or(u32::MAX)
+                    }
+                    _ => usage(),
+                }
+            }
+            (command.as_str(), path)
         }
+        _ => usage(),
     };
     if command == "parse" {
         let src = match std::fs::read_to_string(path) {
@@ -62,6 +85,29 @@ fn main() {
         }
         return;
     }
+    if command == "repl" {
+        // Like `run`, under the hostless prelude: engine values need the
+        // engine's host, which is `functor repl`.
+        let mut repl = match functor_lang::repl::Repl::new(std::path::Path::new(path), Vec::new())
+        {
+            Ok(repl) => repl,
+            Err(err) => {
+                eprintln!("error: {err}");
+                exit(1);
+            }
+        };
+        let stdin = std::io::stdin();
+        if let Err(err) = functor_lang::repl::run(
+            &mut repl,
+            &mut functor_lang::NoHost,
+            stdin.lock(),
+            std::io::stdout(),
+        ) {
+            eprintln!("error: {err}");
+            exit(1);
+        }
+        return;
+    }
 
     // Project commands: the file is the entry; siblings load with it.
     let project = match functor_lang::project::load(std::path::Path::new(path)) {
@@ -99,7 +145,22 @@ fn main() {
     if command == "test" {
         // Like `run`, `test` does not typecheck first — `check` is the
         // static gate; here a non-bool expect reports as its own error.
-        let reports = match functor_lang::run_expects(&project.module, &mut functor_lang::NoHost) {
+        let file_of = |span: functor_lang::Span| {
+            Some(project.sources.resolve(span.start).0.path.clone())
+        };
+        options.snapshots =
+            match functor_lang::snapshot::load(&project.module, &file_of, update_snapshots) {
+                Ok(goldens) => goldens,
+                Err(err) => {
+                    eprintln!("error: {err}");
+                    exit(1);
+                }
+            };
+        let reports = match functor_lang::run_expects_with(
+            &project.module,
+            &mut functor_lang::NoHost,
+            &options,
+        ) {
             Ok(reports) => reports,
             Err(failure) => {
                 let (file, line, col) = project.sources.resolve(failure.error.span.start);
@@ -112,9 +173,19 @@ fn main() {
             }
         };
         if reports.is_empty() {
-            println!("no `expect` tests found");
+            println!("no `expect`, `property`, or `expectSnapshot` tests found");
             return;
         }
+        let written = functor_lang::snapshot::save(
+            &project.module,
+            &reports,
+            &options.snapshots,
+            &file_of,
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("error: {err}");
+            exit(1);
+        });
         let mut failed = 0usize;
         for report in &reports {
             let (file, line, _col) = project.sources.resolve(report.span.start);
@@ -140,10 +211,23 @@ fn main() {
                         error.message
                     );
                 }
+                // The seed comes with it: `--seed` replays the same cases.
+                functor_lang::ExpectOutcome::Falsified(counterexample) => {
+                    failed += 1;
+                    println!("{at}: FAILED: {counterexample}");
+                }
+                functor_lang::ExpectOutcome::Changed(diff) => {
+                    failed += 1;
+                    println!("{at}: FAILED: {diff}");
+                }
             }
         }
         let passed = reports.len() - failed;
         println!("{} expects: {passed} passed, {failed} failed", reports.len());
+        if written > 0 {
+            let plural = if written == 1 { "" } else { "s" };
+            println!("{written} snapshot{plural} written");
+        }
         if failed > 0 {
             exit(1);
         }
@@ -3,7 +3,7 @@
 //! Grammar (whitespace and newlines are insignificant):
 //!
 //! ```text
-//! program   := (letDecl | typeDecl)*
+//! program   := ("private"? (letDecl | typeDecl))*
 //! letDecl   := "let" ident "=" expr
 //! typeDecl  := "type" ident ("<" typevar ("," typevar)* ">")?
 //!              "=" ("{" (ident ":" type),* "}" | variant+)
@@ -15,20 +15,25 @@
 //!    "(" … ")" is only a tuple/group — a function return type is parenthesized)
 //! tatom     := typevar | ident ("<" type ("," type)* ">")?
 //! expr      := letIn | assign | match | ifExpr | pipeline
-//! letIn     := "let" ("mut"? ident | tuplePat) "=" expr "in" expr
-//!              (a tuple-pattern let is sugar for a single-arm match)
+//! letIn     := "let" ("mut"? ident | tuplePat | recordPat) "=" expr "in" expr
+//!              (a destructuring let is sugar for a single-arm match)
 //! assign    := ident ":=" expr ";" expr
-//! match     := "match" expr "with" ("|" pattern "=>" expr)+
+//! match     := "match" expr "with" ("|" pattern ("when" expr)? "=>" expr)+
+//!              (`when` is contextual: a guard only after an arm's pattern)
 //! ifExpr    := "if" expr "then" expr "else" (ifExpr | expr)   (else required)
-//! pattern   := "_" | lowerIdent | upperIdent ("(" subpat,+ ")")?
-//!            | tuplePat | "true" | "false" | "-"? number | string
-//! tuplePat  := "(" subpat ("," subpat)+ ","? ")"
-//! subpat    := "_" | lowerIdent
+//! pattern   := patAtom ("|" patAtom)*                 (or-pattern)
+//! patAtom   := "_" | lowerIdent | upperIdent ("(" pattern,+ ")")?
+//!            | tuplePat | listPat | recordPat
+//!            | "true" | "false" | "-"? number | string
+//! tuplePat  := "(" pattern ("," pattern)+ ","? ")"
+//! listPat   := "[" (pattern ("," pattern)* ("," ".." pattern)?)? "]"
+//!            | "[" ".." pattern "]"
+//! recordPat := "{" (ident (":" pattern)?),+ "}"     (bare `ident` puns)
 //! pipeline  := cmp ("|>" cmp)*
 //! cmp       := add (("<" | ">" | "<=" | ">=" | "==" | "!=") add)*
 //!                                                   (left-assoc)
 //! add       := mul (("+" | "-") mul)*               (left-assoc)
-//! mul       := unary (("*" | "/") unary)*           (left-assoc)
+//! mul       := unary (("*" | "/" | "%") unary)*     (left-assoc)
 //! unary     := "-" unary | postfix
 //! postfix   := primary ("(" expr,* ")" | "." ident)*
 //! primary   := number | string | interpolatedString | "true" | "false" | qualifiedIdent
@@ -90,6 +95,7 @@ fn parse_impl(src: &str, base: usize, interface: bool) -> Result<Program, ParseE
         depth: 0,
         interface,
         in_assign_value: 0,
+        snapshots: Vec::new(),
     }
     .program()
 }
@@ -116,6 +122,9 @@ struct Parser {
     /// an assignment value keeps the old (worse) error instead of risking a
     /// wrong one on valid code.
     in_assign_value: u32,
+    /// The `expectSnapshot` names seen so far: one file shares one
+    /// `__snapshots__` document, so a name may appear once per file.
+    snapshots: Vec<String>,
 }
 
 impl Parser {
@@ -218,6 +227,9 @@ impl Parser {
             match self.peek_kind() {
                 TokenKind::Let => items.push(self.let_item()?),
                 TokenKind::Type => items.push(Item::Type(self.type_decl()?)),
+                // `private` is contextual: only a `private` in item position
+                // marks the `let`/`type` after it.
+                TokenKind::Ident(name) if name == "private" => items.push(self.private_item()?),
                 // `open` is contextual: only an `open` in item position is
                 // the module directive, so the name stays usable elsewhere.
                 TokenKind::Ident(name) if name == "open" => {
@@ -227,6 +239,14 @@ impl Parser {
                 TokenKind::Ident(name) if name == "expect" => {
                     items.push(Item::Expect(self.expect_decl()?))
                 }
+                // …and `property` (a generated-input test), and
+                // `expectSnapshot` (a golden-value test).
+                TokenKind::Ident(name) if name == "property" => {
+                    items.push(Item::Expect(self.property_decl()?))
+                }
+                TokenKind::Ident(name) if name == "expectSnapshot" => {
+                    items.push(Item::Expect(self.snapshot_decl()?))
+                }
                 // …and so is `module` (an inline module declaration).
                 TokenKind::Ident(name) if name == "module" => {
                     items.push(Item::Module(self.module_decl()?))
@@ -237,8 +257,10 @@ impl Parser {
                     items.push(self.unit_item()?)
                 }
                 _ => {
-                    return self
-                        .error("`let`, `type`, `open`, `expect`, `unit`, or `module` at top level")
+                    return self.error(
+                        "`let`, `type`, `private`, `open`, `expect`, `property`, \
+`expectSnapshot`, `unit`, or `module` at top level",
+                    )
                 }
             }
         }
@@ -273,9 +295,16 @@ declarations belong in a `.fun` file"
             match self.peek_kind() {
                 TokenKind::Let => items.push(self.let_item()?),
                 TokenKind::Type => items.push(Item::Type(self.type_decl()?)),
+                TokenKind::Ident(kw) if kw == "private" => items.push(self.private_item()?),
                 TokenKind::Ident(kw) if kw == "expect" => {
                     items.push(Item::Expect(self.expect_decl()?))
                 }
+                TokenKind::Ident(kw) if kw == "property" => {
+                    items.push(Item::Expect(self.property_decl()?))
+                }
+                TokenKind::Ident(kw) if kw == "expectSnapshot" => {
+                    items.push(Item::Expect(self.snapshot_decl()?))
+                }
                 TokenKind::Ident(kw) if kw == "module" => {
                     return Err(ParseError {
                         message: "nested modules are not supported yet — declare \
@@ -304,7 +333,12 @@ project-wide, so declare them at the top level of the file"
                         span: self.peek().span,
                     })
                 }
-                _ => return self.error("`let`, `type`, `expect`, or `}` inside a module"),
+                _ => {
+                    return self.error(
+                        "`let`, `type`, `private`, `expect`, `property`, `expectSnapshot`, or \
+`}` inside a module",
+                    )
+                }
             }
         }
         let close = self.bump();
@@ -475,7 +509,93 @@ in a `.fun` file"
         }
         let expr = self.expr()?;
         let span = kw.span.to(expr.span);
-        Ok(ExpectDecl { expr, span })
+        Ok(ExpectDecl {
+            kind: ExpectKind::Example,
+            expr,
+            span,
+        })
+    }
+
+    /// `property (x: T, …) => <expr>` — a test over generated inputs. The
+    /// lambda's parameters are what get generated, so each needs a type.
+    fn property_decl(&mut self) -> Result<ExpectDecl, ParseError> {
+        let kw = self.bump();
+        if self.interface {
+            return Err(ParseError {
+                message: "interface files (.funi) declare signatures — `property` tests \
+belong in a `.fun` file"
+                    .to_string(),
+                span: kw.span,
+            });
+        }
+        let expr = self.expr()?;
+        let ExprKind::Lambda { params, .. } = &expr.kind else {
+            return Err(ParseError {
+                message: "a `property` is a lambda over its generated inputs: \
+`property (xs: List<int>) => List.reverse(List.reverse(xs)) == xs`"
+                    .to_string(),
+                span: expr.span,
+            });
+        };
+        if params.is_empty() {
+            return Err(ParseError {
+                message: "a `property` needs at least one parameter to generate — \
+a fixed check is an `expect`"
+                    .to_string(),
+                span: expr.span,
+            });
+        }
+        if let Some(param) = params.iter().find(|p| p.ty.is_none()) {
+            return Err(ParseError {
+                message: format!(
+                    "property parameter `{}` needs a type — its annotation picks the \
+generator (`{}: List<int>`)",
+                    param.name, param.name
+                ),
+                span: param.span,
+            });
+        }
+        let span = kw.span.to(expr.span);
+        Ok(ExpectDecl {
+            kind: ExpectKind::Property,
+            expr,
+            span,
+        })
+    }
+
+    /// `expectSnapshot name = <expr>` — a test whose expected value is the
+    /// stored golden dump named `name`, so the expression may be any type.
+    fn snapshot_decl(&mut self) -> Result<ExpectDecl, ParseError> {
+        let kw = self.bump();
+        if self.interface {
+            return Err(ParseError {
+                message: "interface files (.funi) declare signatures — `expectSnapshot` tests \
+belong in a `.fun` file"
+                    .to_string(),
+                span: kw.span,
+            });
+        }
+        let (name, name_span) = self.expect_ident(
+            "a snapshot name after `expectSnapshot` (`expectSnapshot title = …`)",
+        )?;
+        if self.snapshots.contains(&name) {
+            return Err(ParseError {
+                message: format!(
+                    "duplicate snapshot `{name}` — each `expectSnapshot` in a file needs its own \
+name, since they share one `__snapshots__` file"
+                ),
+                span: name_span,
+            });
+        }
+        self.snapshots.push(name.clone());
+        self.expect(TokenKind::Eq, "`=` after the snapshot name")?;
+        let expr = self.expr()?;
+        let span = kw.span.to(expr.span);
+        Ok(ExpectDecl {
+            kind: ExpectKind::Snapshot(name),
+            expr,
+            span,
+        })
     }
 
     /// An optional `: Type` binding annotation between a `let` binder name and
@@ -490,6 +610,37 @@ in a `.fun` file"
         }
     }
 
+    /// `private let …` / `private type …` — a definition only its own file
+    /// can reach. A `.funi` lists public names only, so it has no `private`.
+    fn private_item(&mut self) -> Result<Item, ParseError> {
+        let kw = self.bump();
+        if self.interface {
+            return Err(ParseError {
+                message: "interface files (.funi) list what a module makes public — leave a \
+name out instead of marking it `private`"
+                    .to_string(),
+                span: kw.span,
+            });
+        }
+        match self.peek_kind() {
+            TokenKind::Let => {
+                let mut item = self.let_item()?;
+                if let Item::Let(decl) = &mut item {
+                    decl.private = true;
+                    decl.span = kw.span.to(decl.span);
+                }
+                Ok(item)
+            }
+            TokenKind::Type => {
+                let mut decl = self.type_decl()?;
+                decl.private = true;
+                decl.span = kw.span.to(decl.span);
+                Ok(Item::Type(decl))
+            }
+            _ => self.error("`let` or `type` after `private`"),
+        }
+    }
+
     /// A top-level `let` item — a definition (`let name [: T] = value`) in a
     /// `.fun`, or a bodyless signature (`let name : T`) in a `.funi`.
     fn let_item(&mut self) -> Result<Item, ParseError> {
@@ -530,6 +681,7 @@ rebind surface); `mut` is for `let mut … in …` inside a function"
             name,
             ty,
             value,
+            private: false,
             span,
         }))
     }
@@ -581,6 +733,7 @@ rebind surface); `mut` is for `let mut … in …` inside a function"
             return Ok(TypeDecl {
                 name,
                 params,
+                private: false,
                 body: TypeBody::Abstract,
                 span: kw.span.to(end_span),
             });
@@ -602,6 +755,7 @@ belongs in an interface (.funi) file — a `.fun` type is ordinary data"
             return Ok(TypeDecl {
                 name,
                 params,
+                private: false,
                 body: TypeBody::Host,
                 span: kw.span.to(end),
             });
@@ -629,6 +783,7 @@ belongs in an interface (.funi) file — a `.fun` type is ordinary data"
                 Ok(TypeDecl {
                     name,
                     params,
+                    private: false,
                     body: TypeBody::Record(fields),
                     span: kw.span.to(close.span),
                 })
@@ -645,6 +800,7 @@ belongs in an interface (.funi) file — a `.fun` type is ordinary data"
                 Ok(TypeDecl {
                     name,
                     params,
+                    private: false,
                     body: TypeBody::Variants(variants),
                     span,
                 })
@@ -928,10 +1084,10 @@ do-binds)"
         } else {
             false
         };
-        // Destructuring: `let (a, b) = e in body` is sugar for a
-        // single-arm match (sub-patterns are irrefutable, so the arm always
-        // matches a tuple of the right arity).
-        if self.peek_kind() == &TokenKind::LParen {
+        // Destructuring: `let (a, b) = e in body` / `let { x, y } = e in
+        // body` is sugar for a single-arm match (a refutable sub-pattern
+        // makes that match non-exhaustive, which the checker reports).
+        if matches!(self.peek_kind(), TokenKind::LParen | TokenKind::LBrace) {
             if mutable {
                 return Err(ParseError {
                     message: "`mut` cannot destructure — bind a name, or use plain `let`"
@@ -939,7 +1095,11 @@ do-binds)"
                     span: self.peek().span,
                 });
             }
-            let pattern = self.tuple_pattern()?;
+            let pattern = if self.peek_kind() == &TokenKind::LParen {
+                self.tuple_pattern()?
+            } else {
+                self.record_pattern()?
+            };
             self.expect(TokenKind::Eq, "`=`")?;
             let value = self.expr()?;
             self.expect(TokenKind::In, "`in`")?;
@@ -951,6 +1111,7 @@ do-binds)"
                     scrutinee: Box::new(value),
                     arms: vec![MatchArm {
                         pattern,
+                        guard: None,
                         body,
                         span: arm_span,
                     }],
@@ -1019,11 +1180,24 @@ do-binds)"
         while self.peek_kind() == &TokenKind::Pipe {
             let bar = self.bump();
             let pattern = self.pattern()?;
-            self.expect(TokenKind::FatArrow, "`=>` after the pattern")?;
+            let guard = match self.peek_kind() {
+                TokenKind::Ident(kw) if kw == "when" => {
+                    self.bump();
+                    Some(self.expr()?)
+                }
+                _ => None,
+            };
+            let expected = if guard.is_some() {
+                "`=>` after the guard"
+            } else {
+                "`=>` after the pattern"
+            };
+            self.expect(TokenKind::FatArrow, expected)?;
             let body = self.expr()?;
             arms.push(MatchArm {
                 span: bar.span.to(body.span),
                 pattern,
+                guard,
                 body,
             });
         }
@@ -1085,16 +1259,58 @@ and an `else` branch)",
         Ok(acc)
     }
 
+    /// One pattern, at any nesting depth — constructor, tuple, list, and
+    /// record patterns take full sub-patterns. Depth-guarded like
+    /// [`Parser::expr`]: `Some(Some(Some(…)))` nests the host stack too.
     fn pattern(&mut self) -> Result<Pattern, ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(ParseError {
+                message: "pattern nested too deeply".to_string(),
+                span: self.peek().span,
+            });
+        }
+        let result = self.pattern_inner();
+        self.depth -= 1;
+        result
+    }
+
+    /// `A | B | …` — alternatives bind the same names (checked in
+    /// lowering). Inside an arm the `|` after a pattern is always another
+    /// alternative: the next arm's `|` can only follow an arm body.
+    fn pattern_inner(&mut self) -> Result<Pattern, ParseError> {
+        let first = self.pattern_atom()?;
+        if self.peek_kind() != &TokenKind::Pipe {
+            return Ok(first);
+        }
+        let mut alternatives = vec![first];
+        while self.peek_kind() == &TokenKind::Pipe {
+            self.bump();
+            alternatives.push(self.pattern_atom()?);
+        }
+        let span = alternatives[0]
+            .span
+            .to(alternatives.last().expect("at least two alternatives").span);
+        Ok(Pattern {
+            kind: PatternKind::Or(alternatives),
+            span,
+        })
+    }
+
+    fn pattern_atom(&mut self) -> Result<Pattern, ParseError> {
         // A leading `-` folds into a number literal — patterns contain no
         // expressions, so this is the only unary minus they need.
         if self.peek_kind() == &TokenKind::Minus {
-            if let TokenKind::Number(n) = self.nth_kind(1) {
-                let n = *n;
+            let negated = match self.nth_kind(1) {
+                TokenKind::Number(n) => Some(PatternKind::Number(-*n)),
+                TokenKind::Int(n) => Some(PatternKind::Int(-*n)),
+                _ => None,
+            };
+            if let Some(kind) = negated {
                 let minus = self.bump();
                 let number = self.bump();
                 return Ok(Pattern {
-                    kind: PatternKind::Number(-n),
+                    kind,
                     span: minus.span.to(number.span),
                 });
             }
@@ -1106,6 +1322,11 @@ and an `else` branch)",
                 self.bump();
                 PatternKind::Number(n)
             }
+            TokenKind::Int(n) => {
+                let n = *n;
+                self.bump();
+                PatternKind::Int(n)
+            }
             TokenKind::Str(s) => {
                 let s = s.clone();
                 self.bump();
@@ -1132,13 +1353,13 @@ and an `else` branch)",
             TokenKind::Ident(_) => return self.ctor_pattern(),
             TokenKind::LParen => return self.tuple_pattern(),
             TokenKind::LBracket => return self.list_pattern(),
+            TokenKind::LBrace => return self.record_pattern(),
             _ => return self.error("a pattern"),
         };
         Ok(Pattern { kind, span })
     }
 
-    /// `[]` / `[a, b]` / `[head, ..rest]` — element and tail sub-patterns
-    /// are variable bindings or `_` only. `..rest` (last) binds the
+    /// `[]` / `[a, b]` / `[Some(x), ..rest]` — `..rest` (last) matches the
     /// remainder as a list; without it, the length must match exactly.
     fn list_pattern(&mut self) -> Result<Pattern, ParseError> {
         let open = self.expect(TokenKind::LBracket, "`[`")?;
@@ -1147,10 +1368,10 @@ and an `else` branch)",
         while self.peek_kind() != &TokenKind::RBracket {
             if self.peek_kind() == &TokenKind::DotDot {
                 self.bump();
-                tail = Some(Box::new(self.list_element_pattern()?));
+                tail = Some(Box::new(self.pattern()?));
                 break;
             }
-            items.push(self.list_element_pattern()?);
+            items.push(self.pattern()?);
             if self.peek_kind() == &TokenKind::Comma {
                 self.bump();
             } else {
@@ -1164,13 +1385,12 @@ and an `else` branch)",
         })
     }
 
-    /// `(x, _)` / `(a, b, c)` — sub-patterns are variable bindings or `_`
-    /// only (like ctor patterns); at least two elements.
+    /// `(x, _)` / `(Some(a), "Enter")` — at least two elements.
     fn tuple_pattern(&mut self) -> Result<Pattern, ParseError> {
         let open = self.expect(TokenKind::LParen, "`(`")?;
         let mut args = Vec::new();
         loop {
-            args.push(self.sub_pattern()?);
+            args.push(self.pattern()?);
             if self.peek_kind() == &TokenKind::Comma {
                 self.bump();
                 if self.peek_kind() == &TokenKind::RParen {
@@ -1193,8 +1413,60 @@ and an `else` branch)",
         })
     }
 
-    /// `Circle(r, _)` / `Point` — sub-patterns are variable bindings or `_`
-    /// only (the deliberately-minimal B5 pattern language; no nesting).
+    /// `{ state: Playing, lives }` — `name: pattern` entries, or a bare
+    /// (punned) `name` binding the field under its own name. At least one
+    /// entry; the fields not named are ignored.
+    fn record_pattern(&mut self) -> Result<Pattern, ParseError> {
+        let open = self.expect(TokenKind::LBrace, "`{`")?;
+        let mut fields = Vec::new();
+        while self.peek_kind() != &TokenKind::RBrace {
+            let (name, name_span) = self.expect_ident("a field name")?;
+            let pattern = if self.peek_kind() == &TokenKind::Colon {
+                self.bump();
+                self.pattern()?
+            } else {
+                // Punning binds the field's own name, so it must be a name a
+                // variable pattern could spell.
+                if name == "_" || starts_uppercase(&name) {
+                    return Err(ParseError {
+                        message: format!(
+                            "`{name}` cannot be punned — write `{name}: pattern` (a bare field \
+name binds a variable of that name)"
+                        ),
+                        span: name_span,
+                    });
+                }
+                Pattern {
+                    kind: PatternKind::Var(name.clone()),
+                    span: name_span,
+                }
+            };
+            fields.push(FieldPattern {
+                span: name_span.to(pattern.span),
+                name,
+                pattern,
+            });
+            if self.peek_kind() == &TokenKind::Comma {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        let close = self.expect(TokenKind::RBrace, "`,` or `}`")?;
+        if fields.is_empty() {
+            return Err(ParseError {
+                message: "a record pattern needs at least one field (use `_` to match any value)"
+                    .to_string(),
+                span: open.span.to(close.span),
+            });
+        }
+        Ok(Pattern {
+            kind: PatternKind::Record(fields),
+            span: open.span.to(close.span),
+        })
+    }
+
+    /// `Circle(r, _)` / `Some(Hit(pos, _))` / `Point` — sub-patterns nest.
     fn ctor_pattern(&mut self) -> Result<Pattern, ParseError> {
         let (mut name, mut name_span) = self.expect_ident("a constructor name")?;
         // Module-qualified: `Utils.Circle(r)`, or `Game.Server.Spawn(id)` for
@@ -1211,7 +1483,7 @@ and an `else` branch)",
         if self.peek_kind() == &TokenKind::LParen {
             self.bump();
             loop {
-                args.push(self.sub_pattern()?);
+                args.push(self.pattern()?);
                 if self.peek_kind() == &TokenKind::Comma {
                     self.bump();
                     if self.peek_kind() == &TokenKind::RParen {
@@ -1230,86 +1502,6 @@ and an `else` branch)",
         })
     }
 
-    /// A sub-pattern of a tuple or constructor pattern: a variable binding,
-    /// `_`, or a LITERAL (number — negative included — string, or bool). These
-    /// stay non-nesting: a nested constructor/tuple/list sub-pattern is still
-    /// refused, so `(Circle(r), _)` does not parse — only the leaves may be
-    /// literals (`("Enter", true)`).
-    fn sub_pattern(&mut self) -> Result<Pattern, ParseError> {
-        // A leading `-` folds into a negative number literal (as in the
-        // top-level `pattern`).
-        if self.peek_kind() == &TokenKind::Minus {
-            if let TokenKind::Number(n) = self.nth_kind(1) {
-                let n = *n;
-                let minus = self.bump();
-                let number = self.bump();
-                return Ok(Pattern {
-                    kind: PatternKind::Number(-n),
-                    span: minus.span.to(number.span),
-                });
-            }
-        }
-        let span = self.peek().span;
-        let kind = match self.peek_kind() {
-            TokenKind::Number(n) => {
-                let n = *n;
-                self.bump();
-                PatternKind::Number(n)
-            }
-            TokenKind::Str(s) => {
-                let s = s.clone();
-                self.bump();
-                PatternKind::String(s)
-            }
-            TokenKind::True => {
-                self.bump();
-                PatternKind::Bool(true)
-            }
-            TokenKind::False => {
-                self.bump();
-                PatternKind::Bool(false)
-            }
-            TokenKind::Ident(name) if name == "_" => {
-                self.bump();
-                PatternKind::Wildcard
-            }
-            TokenKind::Ident(name) if !starts_uppercase(name) => {
-                let name = name.clone();
-                self.bump();
-                PatternKind::Var(name)
-            }
-            _ => {
-                return self.error(
-                    "a binding name, `_`, or a literal (constructor/tuple patterns do not nest)",
-                )
-            }
-        };
-        Ok(Pattern { kind, span })
-    }
-
-    /// A list element / tail sub-pattern: a variable binding or `_` only.
-    /// Unlike tuple/ctor sub-patterns, list elements may NOT be literals —
-    /// list exhaustiveness is length-based (it assumes every element is
-    /// irrefutable), so a literal element (`[true, ..rest]`) would silently
-    /// break it. Keeping the restriction here scopes the literal widening to
-    /// tuple/ctor patterns, where exhaustiveness accounts for it.
-    fn list_element_pattern(&mut self) -> Result<Pattern, ParseError> {
-        let span = self.peek().span;
-        let kind = match self.peek_kind() {
-            TokenKind::Ident(name) if name == "_" => {
-                self.bump();
-                PatternKind::Wildcard
-            }
-            TokenKind::Ident(name) if !starts_uppercase(name) => {
-                let name = name.clone();
-                self.bump();
-                PatternKind::Var(name)
-            }
-            _ => return self.error("a binding name or `_` (list patterns do not nest)"),
-        };
-        Ok(Pattern { kind, span })
-    }
-
     fn pipeline(&mut self) -> Result<Expr, ParseError> {
         let head = self.logic_or()?;
         if self.peek_kind() != &TokenKind::PipeGt {
@@ -1417,7 +1609,14 @@ and an `else` branch)",
 
     fn multiplicative(&mut self) -> Result<Expr, ParseError> {
         use TokenKind::*;
-        self.left_assoc(&[(Star, BinOp::Mul), (Slash, BinOp::Div)], Self::unary)
+        self.left_assoc(
+            &[
+                (Star, BinOp::Mul),
+                (Slash, BinOp::Div),
+                (Percent, BinOp::Rem),
+            ],
+            Self::unary,
+        )
     }
 
     fn left_assoc(
@@ -1531,6 +1730,14 @@ and an `else` branch)",
                     span,
                 })
             }
+            TokenKind::Int(n) => {
+                let n = *n;
+                self.bump();
+                Ok(Expr {
+                    kind: ExprKind::Int(n),
+                    span,
+                })
+            }
             // `90deg` — a unit-suffixed literal. Which units exist is a
             // lowering question (see `ast::UnitDecl`); the parser only
             // carries the spelling through.
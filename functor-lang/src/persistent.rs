@@ -0,0 +1,1327 @@
+//! Persistent, structurally shared collections behind [`Value::List`],
+//! [`Value::Array`], [`Value::Map`], and [`Value::Set`].
+//!
+//! Functor Lang data is immutable, so an "update" builds a new collection.
+//! Copying a whole `Vec` per update made a model holding a few thousand
+//! entities copy megabytes per frame (and a `timetravel::History` ring hold
+//! one full copy per frame). Both collections here copy only the path to the
+//! change and share everything else with the old value:
+//!
+//! - [`List`] is a chunked rope: up to [`CHUNK`] elements per leaf, leaves
+//!   joined by an AVL-balanced tree of length-annotated branches. Indexing,
+//!   prepending (`[x, ..xs]`), appending, splitting (`[h, ..t]`,
+//!   `List.take`/`drop`), and concatenation are O(log n) plus one leaf copy.
+//!   A list of at most [`CHUNK`] elements is a single leaf — a flat vector,
+//!   exactly the old representation. An `Array` is the same rope; only its
+//!   builtins differ (indexed `get`/`set` instead of head/tail access).
+//! - [`Map`] is a B-tree of at most `CHUNK - 1` entries per node, in the
+//!   canonical key order of [`MapKey::compare`]. Lookup, insert, and remove
+//!   copy O(log n) nodes; iteration is in canonical order, so `Display`, the
+//!   JSON codecs, and structural equality see the same entry sequence the
+//!   sorted-vector representation produced.
+//! - [`Set`] is a [`Map`] whose values are unused, so it shares the key
+//!   bound, the canonical order, and the costs.
+//!
+//! Map and set operations take a `charge` callback for the evaluator's step budget:
+//! it is called with the units of every key comparison (before comparing)
+//! and of every node copy (before copying), so a bounded run can stop an
+//! update before doing the work.
+
+use crate::value::{MapKey, Value};
+use std::cmp::Ordering;
+use std::convert::Infallible;
+use std::rc::Rc;
+
+/// Elements per list leaf; a map node holds one fewer entry.
+const CHUNK: usize = 32;
+const MAX_ENTRIES: usize = CHUNK - 1;
+/// The B-tree fill floor for every map node but the root.
+const MIN_ENTRIES: usize = MAX_ENTRIES / 2;
+
+// ------------------------------------------------------------------- List
+
+/// An immutable list of values. Cloning is an `Rc` bump; see the module
+/// docs for the shape and costs.
+#[derive(Clone, Default)]
+pub struct List {
+    root: Option<Rc<Node>>,
+}
+
+enum Node {
+    /// 1..=[`CHUNK`] elements (a leaf is never empty).
+    Leaf(Vec<Value>),
+    Branch {
+        left: Rc<Node>,
+        right: Rc<Node>,
+        len: usize,
+        height: u8,
+    },
+}
+
+impl Node {
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(items) => items.len(),
+            Node::Branch { len, .. } => *len,
+        }
+    }
+
+    fn height(&self) -> u8 {
+        match self {
+            Node::Leaf(_) => 0,
+            Node::Branch { height, .. } => *height,
+        }
+    }
+
+    fn children(&self) -> (&Rc<Node>, &Rc<Node>) {
+        match self {
+            Node::Branch { left, right, .. } => (left, right),
+            Node::Leaf(_) => unreachable!("a taller subtree is a branch"),
+        }
+    }
+}
+
+fn leaf(items: Vec<Value>) -> Rc<Node> {
+    debug_assert!(!items.is_empty() && items.len() <= CHUNK);
+    Rc::new(Node::Leaf(items))
+}
+
+fn branch(left: Rc<Node>, right: Rc<Node>) -> Rc<Node> {
+    Rc::new(Node::Branch {
+        len: left.len() + right.len(),
+        height: left.height().max(right.height()) + 1,
+        left,
+        right,
+    })
+}
+
+/// [`branch`], rotating once or twice when the heights differ by two (the
+/// most any single update step unbalances a subtree).
+fn balance(left: Rc<Node>, right: Rc<Node>) -> Rc<Node> {
+    let (lh, rh) = (left.height(), right.height());
+    if lh > rh + 1 {
+        let (ll, lr) = left.children();
+        if ll.height() >= lr.height() {
+            branch(ll.clone(), branch(lr.clone(), right))
+        } else {
+            let (lrl, lrr) = lr.children();
+            branch(branch(ll.clone(), lrl.clone()), branch(lrr.clone(), right))
+        }
+    } else if rh > lh + 1 {
+        let (rl, rr) = right.children();
+        if rr.height() >= rl.height() {
+            branch(branch(left, rl.clone()), rr.clone())
+        } else {
+            let (rll, rlr) = rl.children();
+            branch(branch(left, rll.clone()), branch(rlr.clone(), rr.clone()))
+        }
+    } else {
+        branch(left, right)
+    }
+}
+
+/// Concatenate two trees in O(|height difference|): descend the taller
+/// one's inner spine to a subtree of matching height and rebalance on the
+/// way back up. Two leaves that fit one chunk merge.
+fn join(left: Rc<Node>, right: Rc<Node>) -> Rc<Node> {
+    let (lh, rh) = (left.height(), right.height());
+    if lh > rh + 1 {
+        let (ll, lr) = left.children();
+        balance(ll.clone(), join(lr.clone(), right))
+    } else if rh > lh + 1 {
+        let (rl, rr) = right.children();
+        balance(join(left, rl.clone()), rr.clone())
+    } else {
+        match (&*left, &*right) {
+            (Node::Leaf(a), Node::Leaf(b)) if a.len() + b.len() <= CHUNK => {
+                leaf(a.iter().chain(b.iter()).cloned().collect())
+            }
+            _ => branch(left, right),
+        }
+    }
+}
+
+fn join_opt(left: Option<Rc<Node>>, right: Option<Rc<Node>>) -> Option<Rc<Node>> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(join(left, right)),
+        (left, None) => left,
+        (None, right) => right,
+    }
+}
+
+/// The first `at` elements and the rest. Each level joins at most one
+/// subtree back on, so the whole split is O(log n) plus one leaf copy.
+fn split(node: &Rc<Node>, at: usize) -> (Option<Rc<Node>>, Option<Rc<Node>>) {
+    if at == 0 {
+        return (None, Some(node.clone()));
+    }
+    if at >= node.len() {
+        return (Some(node.clone()), None);
+    }
+    match &**node {
+        Node::Leaf(items) => (
+            Some(leaf(items[..at].to_vec())),
+            Some(leaf(items[at..].to_vec())),
+        ),
+        Node::Branch { left, right, .. } => {
+            if at <= left.len() {
+                let (head, tail) = split(left, at);
+                (head, join_opt(tail, Some(right.clone())))
+            } else {
+                let (head, tail) = split(right, at - left.len());
+                (join_opt(Some(left.clone()), head), tail)
+            }
+        }
+    }
+}
+
+/// Prepend a short run (at most one chunk) into the leftmost leaf, or as a
+/// new leaf beside it when the two don't fit one chunk.
+fn prepend(node: &Rc<Node>, items: &[Value]) -> Rc<Node> {
+    match &**node {
+        Node::Leaf(old) if old.len() + items.len() <= CHUNK => {
+            leaf(items.iter().chain(old.iter()).cloned().collect())
+        }
+        Node::Leaf(_) => branch(leaf(items.to_vec()), node.clone()),
+        Node::Branch { left, right, .. } => balance(prepend(left, items), right.clone()),
+    }
+}
+
+/// [`prepend`]'s mirror at the rightmost leaf.
+fn append(node: &Rc<Node>, items: &[Value]) -> Rc<Node> {
+    match &**node {
+        Node::Leaf(old) if old.len() + items.len() <= CHUNK => {
+            leaf(old.iter().chain(items.iter()).cloned().collect())
+        }
+        Node::Leaf(_) => branch(node.clone(), leaf(items.to_vec())),
+        Node::Branch { left, right, .. } => balance(left.clone(), append(right, items)),
+    }
+}
+
+/// `node` with the element at `index` (in range) replaced: the path to its
+/// leaf is copied, every other subtree shared.
+fn replace(node: &Rc<Node>, index: usize, value: Value) -> Rc<Node> {
+    match &**node {
+        Node::Leaf(items) => {
+            let mut items = items.clone();
+            items[index] = value;
+            leaf(items)
+        }
+        Node::Branch { left, right, .. } => {
+            if index < left.len() {
+                branch(replace(left, index, value), right.clone())
+            } else {
+                branch(left.clone(), replace(right, index - left.len(), value))
+            }
+        }
+    }
+}
+
+/// A balanced tree over consecutive chunks of `items`.
+fn build(items: Vec<Value>) -> Option<Rc<Node>> {
+    let mut leaves = Vec::with_capacity(items.len().div_ceil(CHUNK));
+    let mut rest = items.into_iter();
+    loop {
+        let chunk: Vec<Value> = rest.by_ref().take(CHUNK).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        leaves.push(leaf(chunk));
+    }
+    (!leaves.is_empty()).then(|| halve(&leaves))
+}
+
+/// Halving keeps the two sides' leaf counts within one of each other, so
+/// their heights are too.
+fn halve(leaves: &[Rc<Node>]) -> Rc<Node> {
+    match leaves {
+        [single] => single.clone(),
+        _ => {
+            let (left, right) = leaves.split_at(leaves.len() / 2);
+            branch(halve(left), halve(right))
+        }
+    }
+}
+
+impl List {
+    pub fn new() -> List {
+        List::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.as_ref().map_or(0, |root| root.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// The element at `index`, in O(log n).
+    pub fn get(&self, mut index: usize) -> Option<&Value> {
+        let mut node = self.root.as_deref()?;
+        if index >= node.len() {
+            return None;
+        }
+        loop {
+            match node {
+                Node::Leaf(items) => return items.get(index),
+                Node::Branch { left, right, .. } => {
+                    if index < left.len() {
+                        node = left;
+                    } else {
+                        index -= left.len();
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The list with the element at `index` replaced, or `None` when `index`
+    /// is out of range; O(log n) plus one leaf copy.
+    pub fn set(&self, index: usize, value: Value) -> Option<List> {
+        let root = self.root.as_ref().filter(|root| index < root.len())?;
+        Some(List {
+            root: Some(replace(root, index, value)),
+        })
+    }
+
+    pub fn first(&self) -> Option<&Value> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<&Value> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Elements front to back.
+    pub fn iter(&self) -> ListIter<'_> {
+        ListIter::new(self.root.as_deref(), self.len())
+    }
+
+    pub fn to_vec(&self) -> Vec<Value> {
+        self.iter().cloned().collect()
+    }
+
+    /// `self` followed by `other`. A side that fits one chunk is merged into
+    /// the other's edge leaf (the `[x, ..xs]` / `List.append([x], xs)`
+    /// shapes); two large lists join in O(log n).
+    pub fn concat(&self, other: &List) -> List {
+        let (Some(left), Some(right)) = (&self.root, &other.root) else {
+            return if self.is_empty() {
+                other.clone()
+            } else {
+                self.clone()
+            };
+        };
+        let root = match (&**left, &**right) {
+            (Node::Leaf(items), _) => prepend(right, items),
+            (_, Node::Leaf(items)) => append(left, items),
+            _ => join(left.clone(), right.clone()),
+        };
+        List { root: Some(root) }
+    }
+
+    /// The first `at` elements (all of them when `at >= len`) and the rest.
+    pub fn split_at(&self, at: usize) -> (List, List) {
+        match &self.root {
+            None => (List::new(), List::new()),
+            Some(root) => {
+                let (head, tail) = split(root, at);
+                (List { root: head }, List { root: tail })
+            }
+        }
+    }
+
+    /// Whether two lists share one allocation (so snapshots of an unchanged
+    /// list stay free).
+    pub fn ptr_eq(a: &List, b: &List) -> bool {
+        match (&a.root, &b.root) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl From<Vec<Value>> for List {
+    fn from(items: Vec<Value>) -> List {
+        List { root: build(items) }
+    }
+}
+
+impl std::ops::Index<usize> for List {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        self.get(index)
+            .unwrap_or_else(|| panic!("index {index} out of bounds for a list of {}", self.len()))
+    }
+}
+
+impl FromIterator<Value> for List {
+    fn from_iter<I: IntoIterator<Item = Value>>(items: I) -> List {
+        List::from(items.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<'a> IntoIterator for &'a List {
+    type Item = &'a Value;
+    type IntoIter = ListIter<'a>;
+
+    fn into_iter(self) -> ListIter<'a> {
+        self.iter()
+    }
+}
+
+/// In-order iteration over a [`List`]'s leaves: an explicit stack of the
+/// right subtrees still to visit, so it never recurses.
+pub struct ListIter<'a> {
+    pending: Vec<&'a Node>,
+    leaf: std::slice::Iter<'a, Value>,
+    remaining: usize,
+}
+
+impl<'a> ListIter<'a> {
+    fn new(root: Option<&'a Node>, len: usize) -> ListIter<'a> {
+        ListIter {
+            pending: root.into_iter().collect(),
+            leaf: [].iter(),
+            remaining: len,
+        }
+    }
+}
+
+impl<'a> Iterator for ListIter<'a> {
+    type Item = &'a Value;
+
+    fn next(&mut self) -> Option<&'a Value> {
+        loop {
+            if let Some(item) = self.leaf.next() {
+                self.remaining -= 1;
+                return Some(item);
+            }
+            let mut node = self.pending.pop()?;
+            loop {
+                match node {
+                    Node::Leaf(items) => {
+                        self.leaf = items.iter();
+                        break;
+                    }
+                    Node::Branch { left, right, .. } => {
+                        self.pending.push(right);
+                        node = left;
+                    }
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for ListIter<'_> {}
+
+// -------------------------------------------------------------------- Map
+
+/// An immutable map in canonical key order. Cloning is an `Rc` bump; see the
+/// module docs for the shape and costs. Language and host construction
+/// seams validate keys; entries are unique.
+#[derive(Clone, Default)]
+pub struct Map {
+    root: Option<Rc<MapNode>>,
+    len: usize,
+}
+
+/// A B-tree node: sorted entries, and either no children (a leaf) or one
+/// more child than entries. Every leaf sits at the same depth, and every
+/// node but the root holds `MIN_ENTRIES..=MAX_ENTRIES` entries.
+#[derive(Clone)]
+struct MapNode {
+    entries: Vec<(MapKey, Value)>,
+    children: Vec<Rc<MapNode>>,
+}
+
+impl MapNode {
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Units for materializing a copy of this node.
+    fn copy_units(&self) -> u64 {
+        self.entries.len() as u64 + 1
+    }
+}
+
+/// Binary-search one node, charging each comparison before making it:
+/// string keys can share an arbitrarily long prefix, so charging only
+/// afterwards would let the comparison itself escape a bound.
+fn search<E>(
+    entries: &[(MapKey, Value)],
+    key: &MapKey,
+    charge: &mut impl FnMut(u64) -> Result<(), E>,
+) -> Result<Result<usize, usize>, E> {
+    let mut left = 0usize;
+    let mut right = entries.len();
+    while left < right {
+        let mid = left + (right - left) / 2;
+        let candidate = &entries[mid].0;
+        charge(candidate.comparison_units(key))?;
+        match candidate.compare(key) {
+            Ordering::Less => left = mid + 1,
+            Ordering::Greater => right = mid,
+            Ordering::Equal => return Ok(Ok(mid)),
+        }
+    }
+    Ok(Err(left))
+}
+
+/// An insert's new subtree: one node, or a full node split around its
+/// median for the parent to take in.
+enum Inserted {
+    Fit(MapNode),
+    Split(MapNode, (MapKey, Value), MapNode),
+}
+
+/// Split a node that overflowed by one entry.
+fn fit(mut node: MapNode) -> Inserted {
+    if node.entries.len() <= MAX_ENTRIES {
+        return Inserted::Fit(node);
+    }
+    let mid = node.entries.len() / 2;
+    let right = MapNode {
+        entries: node.entries.split_off(mid + 1),
+        children: if node.is_leaf() {
+            Vec::new()
+        } else {
+            node.children.split_off(mid + 1)
+        },
+    };
+    let median = node
+        .entries
+        .pop()
+        .expect("an overflowing node has a median");
+    Inserted::Split(node, median, right)
+}
+
+/// Returns the new subtree and whether the key was new.
+fn insert_into<E>(
+    node: &MapNode,
+    key: MapKey,
+    value: Value,
+    charge: &mut impl FnMut(u64) -> Result<(), E>,
+) -> Result<(Inserted, bool), E> {
+    let found = search(&node.entries, &key, charge)?;
+    charge(node.copy_units())?;
+    match found {
+        Ok(index) => {
+            let mut copy = node.clone();
+            copy.entries[index].1 = value;
+            Ok((Inserted::Fit(copy), false))
+        }
+        Err(index) if node.is_leaf() => {
+            let mut copy = node.clone();
+            copy.entries.insert(index, (key, value));
+            Ok((fit(copy), true))
+        }
+        Err(index) => {
+            let (child, added) = insert_into(&node.children[index], key, value, charge)?;
+            let mut copy = node.clone();
+            match child {
+                Inserted::Fit(child) => copy.children[index] = Rc::new(child),
+                Inserted::Split(left, median, right) => {
+                    copy.children[index] = Rc::new(left);
+                    copy.entries.insert(index, median);
+                    copy.children.insert(index + 1, Rc::new(right));
+                }
+            }
+            Ok((fit(copy), added))
+        }
+    }
+}
+
+/// Returns the new subtree, or `None` when the key is absent (nothing was
+/// copied).
+fn remove_from<E>(
+    node: &MapNode,
+    key: &MapKey,
+    charge: &mut impl FnMut(u64) -> Result<(), E>,
+) -> Result<Option<MapNode>, E> {
+    let found = search(&node.entries, key, charge)?;
+    if node.is_leaf() {
+        let Ok(index) = found else {
+            return Ok(None);
+        };
+        charge(node.copy_units())?;
+        let mut copy = node.clone();
+        copy.entries.remove(index);
+        return Ok(Some(copy));
+    }
+    let (index, child, replacement) = match found {
+        // An inner entry is replaced by its predecessor, the greatest entry
+        // of the subtree to its left.
+        Ok(index) => {
+            let (child, max) = remove_max(&node.children[index], charge)?;
+            (index, child, Some(max))
+        }
+        Err(index) => match remove_from(&node.children[index], key, charge)? {
+            Some(child) => (index, child, None),
+            None => return Ok(None),
+        },
+    };
+    charge(node.copy_units())?;
+    let mut copy = node.clone();
+    if let Some(max) = replacement {
+        copy.entries[index] = max;
+    }
+    copy.children[index] = Rc::new(child);
+    refill(&mut copy, index, charge)?;
+    Ok(Some(copy))
+}
+
+fn remove_max<E>(
+    node: &MapNode,
+    charge: &mut impl FnMut(u64) -> Result<(), E>,
+) -> Result<(MapNode, (MapKey, Value)), E> {
+    charge(node.copy_units())?;
+    let mut copy = node.clone();
+    if copy.is_leaf() {
+        let max = copy.entries.pop().expect("a non-root node is never empty");
+        return Ok((copy, max));
+    }
+    let last = copy.children.len() - 1;
+    let (child, max) = remove_max(&copy.children[last], charge)?;
+    copy.children[last] = Rc::new(child);
+    refill(&mut copy, last, charge)?;
+    Ok((copy, max))
+}
+
+/// Restore the fill floor of `node.children[index]` after a removal: borrow
+/// an entry through the parent from a sibling that can spare one, or merge
+/// with a sibling (the two fit one node exactly when neither can spare).
+fn refill<E>(
+    node: &mut MapNode,
+    index: usize,
+    charge: &mut impl FnMut(u64) -> Result<(), E>,
+) -> Result<(), E> {
+    if node.children[index].entries.len() >= MIN_ENTRIES {
+        return Ok(());
+    }
+    if index > 0 && node.children[index - 1].entries.len() > MIN_ENTRIES {
+        charge(node.children[index - 1].copy_units())?;
+        let left = Rc::make_mut(&mut node.children[index - 1]);
+        let moved = left.entries.pop().expect("a sibling above the floor");
+        let moved_child = left.children.pop();
+        let separator = std::mem::replace(&mut node.entries[index - 1], moved);
+        let child = Rc::make_mut(&mut node.children[index]);
+        child.entries.insert(0, separator);
+        if let Some(moved_child) = moved_child {
+            child.children.insert(0, moved_child);
+        }
+    } else if index + 1 < node.children.len()
+        && node.children[index + 1].entries.len() > MIN_ENTRIES
+    {
+        charge(node.children[index + 1].copy_units())?;
+        let right = Rc::make_mut(&mut node.children[index + 1]);
+        let moved = right.entries.remove(0);
+        let moved_child = (!right.is_leaf()).then(|| right.children.remove(0));
+        let separator = std::mem::replace(&mut node.entries[index], moved);
+        let child = Rc::make_mut(&mut node.children[index]);
+        child.entries.push(separator);
+        if let Some(moved_child) = moved_child {
+            child.children.push(moved_child);
+        }
+    } else {
+        let at = if index > 0 { index - 1 } else { index };
+        let right = node.children.remove(at + 1);
+        let separator = node.entries.remove(at);
+        charge(node.children[at].copy_units() + right.copy_units())?;
+        let left = Rc::make_mut(&mut node.children[at]);
+        left.entries.push(separator);
+        left.entries.extend(right.entries.iter().cloned());
+        left.children.extend(right.children.iter().cloned());
+    }
+    Ok(())
+}
+
+/// A charge callback for uncharged (host-side) use.
+fn free(_: u64) -> Result<(), Infallible> {
+    Ok(())
+}
+
+impl Map {
+    pub fn new() -> Map {
+        Map::default()
+    }
+
+    /// Build from entries already in canonical order with unique keys (see
+    /// [`crate::value::canonicalize_map_entries`]), in O(n): leaves are
+    /// filled evenly, then each level of parents over them.
+    pub fn from_canonical(entries: Vec<(MapKey, Value)>) -> Map {
+        debug_assert!(entries.windows(2).all(|w| w[0].0.compare(&w[1].0).is_lt()));
+        let len = entries.len();
+        if len == 0 {
+            return Map::default();
+        }
+        // `len` entries make `leaves` leaves and `leaves - 1` separators, so
+        // each leaf gets an even share of `len + 1` "entry + separator" slots.
+        let leaves = (len + 1).div_ceil(MAX_ENTRIES + 1);
+        let mut level = Vec::with_capacity(leaves);
+        let mut separators = Vec::with_capacity(leaves - 1);
+        let mut rest = entries.into_iter();
+        for i in 0..leaves {
+            let slots = (len + 1) / leaves + usize::from(i < (len + 1) % leaves);
+            level.push(Rc::new(MapNode {
+                entries: rest.by_ref().take(slots - 1).collect(),
+                children: Vec::new(),
+            }));
+            if i + 1 < leaves {
+                separators.push(rest.next().expect("a separator between leaves"));
+            }
+        }
+        while level.len() > 1 {
+            let count = level.len();
+            let parents = count.div_ceil(MAX_ENTRIES + 1);
+            let mut next = Vec::with_capacity(parents);
+            let mut next_separators = Vec::with_capacity(parents - 1);
+            let mut children = level.into_iter();
+            let mut between = separators.into_iter();
+            for i in 0..parents {
+                let take = count / parents + usize::from(i < count % parents);
+                next.push(Rc::new(MapNode {
+                    children: children.by_ref().take(take).collect(),
+                    entries: between.by_ref().take(take - 1).collect(),
+                }));
+                if i + 1 < parents {
+                    next_separators.push(between.next().expect("a separator between parents"));
+                }
+            }
+            level = next;
+            separators = next_separators;
+        }
+        Map {
+            root: level.pop(),
+            len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Entries in canonical key order.
+    pub fn iter(&self) -> MapIter<'_> {
+        MapIter::new(self.root.as_deref(), self.len)
+    }
+
+    /// The value at `key`, charging each key comparison.
+    pub fn lookup<E>(
+        &self,
+        key: &MapKey,
+        mut charge: impl FnMut(u64) -> Result<(), E>,
+    ) -> Result<Option<&Value>, E> {
+        let Some(mut node) = self.root.as_deref() else {
+            return Ok(None);
+        };
+        loop {
+            match search(&node.entries, key, &mut charge)? {
+                Ok(index) => return Ok(Some(&node.entries[index].1)),
+                Err(_) if node.is_leaf() => return Ok(None),
+                Err(index) => node = &node.children[index],
+            }
+        }
+    }
+
+    pub fn get(&self, key: &MapKey) -> Option<&Value> {
+        let Ok(found) = self.lookup(key, free);
+        found
+    }
+
+    /// A map with `key` set to `value` (replacing any old value), charging
+    /// comparisons and the copied path.
+    pub fn insert<E>(
+        &self,
+        key: MapKey,
+        value: Value,
+        mut charge: impl FnMut(u64) -> Result<(), E>,
+    ) -> Result<Map, E> {
+        let Some(root) = &self.root else {
+            charge(1)?;
+            return Ok(Map {
+                root: Some(Rc::new(MapNode {
+                    entries: vec![(key, value)],
+                    children: Vec::new(),
+                })),
+                len: 1,
+            });
+        };
+        let (inserted, added) = insert_into(root, key, value, &mut charge)?;
+        let root = match inserted {
+            Inserted::Fit(node) => node,
+            Inserted::Split(left, median, right) => MapNode {
+                entries: vec![median],
+                children: vec![Rc::new(left), Rc::new(right)],
+            },
+        };
+        Ok(Map {
+            root: Some(Rc::new(root)),
+            len: self.len + usize::from(added),
+        })
+    }
+
+    /// A map without `key`, or `None` when the key is absent; charges
+    /// comparisons and the copied path.
+    pub fn remove<E>(
+        &self,
+        key: &MapKey,
+        mut charge: impl FnMut(u64) -> Result<(), E>,
+    ) -> Result<Option<Map>, E> {
+        let Some(root) = &self.root else {
+            return Ok(None);
+        };
+        let Some(mut root) = remove_from(root, key, &mut charge)? else {
+            return Ok(None);
+        };
+        // A root emptied by a merge below hands the tree to its one child.
+        if root.entries.is_empty() && !root.is_leaf() {
+            root = Rc::unwrap_or_clone(root.children.pop().expect("the merged child"));
+        }
+        Ok(Some(Map {
+            root: (!root.entries.is_empty()).then(|| Rc::new(root)),
+            len: self.len - 1,
+        }))
+    }
+
+    /// Whether two maps share one allocation.
+    pub fn ptr_eq(a: &Map, b: &Map) -> bool {
+        match (&a.root, &b.root) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = &'a (MapKey, Value);
+    type IntoIter = MapIter<'a>;
+
+    fn into_iter(self) -> MapIter<'a> {
+        self.iter()
+    }
+}
+
+/// In-order iteration over a [`Map`]: a stack of nodes with the index of the
+/// next entry each still owes, so it never recurses.
+pub struct MapIter<'a> {
+    pending: Vec<(&'a MapNode, usize)>,
+    remaining: usize,
+}
+
+impl<'a> MapIter<'a> {
+    fn new(root: Option<&'a MapNode>, len: usize) -> MapIter<'a> {
+        let mut iter = MapIter {
+            pending: Vec::new(),
+            remaining: len,
+        };
+        if let Some(root) = root {
+            iter.descend(root);
+        }
+        iter
+    }
+
+    /// Push `node` and its leftmost descendants.
+    fn descend(&mut self, mut node: &'a MapNode) {
+        loop {
+            self.pending.push((node, 0));
+            match node.children.first() {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for MapIter<'a> {
+    type Item = &'a (MapKey, Value);
+
+    fn next(&mut self) -> Option<&'a (MapKey, Value)> {
+        loop {
+            let (node, index) = self.pending.last_mut()?;
+            let node = *node;
+            if *index < node.entries.len() {
+                let entry = &node.entries[*index];
+                *index += 1;
+                let next = *index;
+                if let Some(child) = node.children.get(next) {
+                    self.descend(child);
+                }
+                self.remaining -= 1;
+                return Some(entry);
+            }
+            self.pending.pop();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for MapIter<'_> {}
+
+// -------------------------------------------------------------------- Set
+
+/// An immutable set of keys in canonical order: a [`Map`] whose values are
+/// never read. Cloning is an `Rc` bump.
+#[derive(Clone, Default)]
+pub struct Set {
+    map: Map,
+}
+
+/// The value every set entry stores; never observed.
+fn present() -> Value {
+    Value::Bool(true)
+}
+
+impl Set {
+    pub fn new() -> Set {
+        Set::default()
+    }
+
+    /// Build from keys already in canonical order and unique, in O(n).
+    pub fn from_canonical(keys: Vec<MapKey>) -> Set {
+        Set {
+            map: Map::from_canonical(keys.into_iter().map(|key| (key, present())).collect()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Keys in canonical order.
+    pub fn iter(&self) -> SetIter<'_> {
+        SetIter {
+            entries: self.map.iter(),
+        }
+    }
+
+    /// Whether the set holds `key`, charging each key comparison.
+    pub fn contains<E>(
+        &self,
+        key: &MapKey,
+        charge: impl FnMut(u64) -> Result<(), E>,
+    ) -> Result<bool, E> {
+        Ok(self.map.lookup(key, charge)?.is_some())
+    }
+
+    /// A set with `key` added, charging comparisons and the copied path.
+    pub fn insert<E>(
+        &self,
+        key: MapKey,
+        charge: impl FnMut(u64) -> Result<(), E>,
+    ) -> Result<Set, E> {
+        Ok(Set {
+            map: self.map.insert(key, present(), charge)?,
+        })
+    }
+
+    /// A set without `key`, or `None` when the key is absent.
+    pub fn remove<E>(
+        &self,
+        key: &MapKey,
+        charge: impl FnMut(u64) -> Result<(), E>,
+    ) -> Result<Option<Set>, E> {
+        Ok(self.map.remove(key, charge)?.map(|map| Set { map }))
+    }
+
+    /// The keys in either set.
+    pub fn union<E>(
+        &self,
+        other: &Set,
+        charge: impl FnMut(u64) -> Result<(), E>,
+    ) -> Result<Set, E> {
+        self.merge(other, (true, true, true), charge)
+    }
+
+    /// The keys in both sets.
+    pub fn intersect<E>(
+        &self,
+        other: &Set,
+        charge: impl FnMut(u64) -> Result<(), E>,
+    ) -> Result<Set, E> {
+        self.merge(other, (false, true, false), charge)
+    }
+
+    /// The keys of `self` that are not in `other`.
+    pub fn difference<E>(
+        &self,
+        other: &Set,
+        charge: impl FnMut(u64) -> Result<(), E>,
+    ) -> Result<Set, E> {
+        self.merge(other, (true, false, false), charge)
+    }
+
+    /// One linear walk over both key sequences, keeping the keys only in
+    /// `self`, in both, and only in `other` as `keep` says. Each comparison
+    /// is charged before it is made, and each kept key as it is copied.
+    fn merge<E>(
+        &self,
+        other: &Set,
+        keep: (bool, bool, bool),
+        mut charge: impl FnMut(u64) -> Result<(), E>,
+    ) -> Result<Set, E> {
+        let (only_left, both, only_right) = keep;
+        let mut left = self.iter().peekable();
+        let mut right = other.iter().peekable();
+        let mut keys = Vec::new();
+        loop {
+            let (take, from_left, from_right) = match (left.peek(), right.peek()) {
+                (Some(a), Some(b)) => {
+                    charge(a.comparison_units(b))?;
+                    match a.compare(b) {
+                        Ordering::Less => (only_left, true, false),
+                        Ordering::Equal => (both, true, true),
+                        Ordering::Greater => (only_right, false, true),
+                    }
+                }
+                (Some(_), None) => (only_left, true, false),
+                (None, Some(_)) => (only_right, false, true),
+                (None, None) => break,
+            };
+            if from_left && from_right {
+                right.next();
+            }
+            let key = if from_left { left.next() } else { right.next() };
+            if take {
+                charge(1)?;
+                keys.push(key.expect("a peeked key").clone());
+            }
+        }
+        Ok(Set::from_canonical(keys))
+    }
+
+    /// Whether two sets share one allocation.
+    pub fn ptr_eq(a: &Set, b: &Set) -> bool {
+        Map::ptr_eq(&a.map, &b.map)
+    }
+}
+
+impl<'a> IntoIterator for &'a Set {
+    type Item = &'a MapKey;
+    type IntoIter = SetIter<'a>;
+
+    fn into_iter(self) -> SetIter<'a> {
+        self.iter()
+    }
+}
+
+/// In-order iteration over a [`Set`]'s keys.
+pub struct SetIter<'a> {
+    entries: MapIter<'a>,
+}
+
+impl<'a> Iterator for SetIter<'a> {
+    type Item = &'a MapKey;
+
+    fn next(&mut self) -> Option<&'a MapKey> {
+        self.entries.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl ExactSizeIterator for SetIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{List, Map, MapNode, Node, Set, CHUNK, MAX_ENTRIES, MIN_ENTRIES};
+    use crate::value::{MapKey, Value};
+    use std::convert::Infallible;
+
+    fn numbers(list: &List) -> Vec<f64> {
+        list.iter()
+            .map(|value| match value {
+                Value::Number(n) => *n,
+                _ => panic!("not a number"),
+            })
+            .collect()
+    }
+
+    fn range(from: usize, to: usize) -> List {
+        (from..to).map(|i| Value::Number(i as f64)).collect()
+    }
+
+    /// The AVL and leaf invariants; returns the height.
+    fn check_node(node: &Node) -> u8 {
+        match node {
+            Node::Leaf(items) => {
+                assert!(!items.is_empty() && items.len() <= CHUNK);
+                0
+            }
+            Node::Branch {
+                left,
+                right,
+                len,
+                height,
+            } => {
+                let (lh, rh) = (check_node(left), check_node(right));
+                assert!(lh.abs_diff(rh) <= 1, "unbalanced: {lh} vs {rh}");
+                assert_eq!(*len, left.len() + right.len());
+                assert_eq!(*height, lh.max(rh) + 1);
+                *height
+            }
+        }
+    }
+
+    fn check_list(list: &List) {
+        if let Some(root) = &list.root {
+            check_node(root);
+        }
+    }
+
+    #[test]
+    fn list_edits_match_a_vector_and_stay_balanced() {
+        let mut list = List::new();
+        let mut model: Vec<f64> = Vec::new();
+        for i in 0..500 {
+            let x = i as f64;
+            if i % 3 == 0 {
+                list = List::from(vec![Value::Number(x)]).concat(&list);
+                model.insert(0, x);
+            } else {
+                list = list.concat(&List::from(vec![Value::Number(x)]));
+                model.push(x);
+            }
+            check_list(&list);
+        }
+        assert_eq!(numbers(&list), model);
+        assert_eq!(list.len(), 500);
+        for (i, x) in model.iter().enumerate() {
+            assert!(matches!(list.get(i), Some(Value::Number(n)) if n == x));
+        }
+        assert!(list.get(500).is_none());
+
+        for at in [0, 1, 31, 32, 33, 250, 499, 500, 900] {
+            let (head, tail) = list.split_at(at);
+            check_list(&head);
+            check_list(&tail);
+            let at = at.min(model.len());
+            assert_eq!(numbers(&head), model[..at]);
+            assert_eq!(numbers(&tail), model[at..]);
+        }
+    }
+
+    #[test]
+    fn large_concatenation_keeps_order_and_balance() {
+        let a = range(0, 1000);
+        let b = range(1000, 1010);
+        let c = range(1010, 5000);
+        let all = a.concat(&b).concat(&c);
+        check_list(&all);
+        assert_eq!(
+            numbers(&all),
+            (0..5000).map(|i| i as f64).collect::<Vec<_>>()
+        );
+        // The untouched inputs are intact.
+        assert_eq!(a.len(), 1000);
+        assert_eq!(numbers(&c)[0], 1010.0);
+    }
+
+    #[test]
+    fn setting_an_index_copies_one_path() {
+        let old = range(0, 1000);
+        let mut model: Vec<f64> = (0..1000).map(|i| i as f64).collect();
+        let mut list = old.clone();
+        for i in [0, 31, 32, 500, 999] {
+            list = list.set(i, Value::Number(-1.0)).expect("in range");
+            model[i] = -1.0;
+            check_list(&list);
+        }
+        assert_eq!(numbers(&list), model);
+        assert!(list.set(1000, Value::Number(0.0)).is_none());
+        assert!(List::new().set(0, Value::Number(0.0)).is_none());
+        assert_eq!(numbers(&old)[500], 500.0);
+    }
+
+    #[test]
+    fn walking_a_list_by_head_and_tail_sees_every_element() {
+        let mut list = range(0, 300);
+        let mut seen = Vec::new();
+        while let Some(Value::Number(head)) = list.first().cloned() {
+            seen.push(head);
+            list = list.split_at(1).1;
+            check_list(&list);
+        }
+        assert_eq!(seen, (0..300).map(|i| i as f64).collect::<Vec<_>>());
+    }
+
+    fn key(n: usize) -> MapKey {
+        MapKey::Number(n as f64)
+    }
+
+    /// The B-tree invariants; returns the leaf depth.
+    fn check_map_node(node: &MapNode, root: bool) -> usize {
+        assert!(node.entries.len() <= MAX_ENTRIES);
+        if !root {
+            assert!(node.entries.len() >= MIN_ENTRIES, "underfull node");
+        }
+        assert!(node
+            .entries
+            .windows(2)
+            .all(|w| w[0].0.compare(&w[1].0).is_lt()));
+        if node.is_leaf() {
+            return 0;
+        }
+        assert_eq!(node.children.len(), node.entries.len() + 1);
+        let depths: Vec<usize> = node
+            .children
+            .iter()
+            .map(|child| check_map_node(child, false))
+            .collect();
+        assert!(depths.windows(2).all(|w| w[0] == w[1]), "uneven leaves");
+        depths[0] + 1
+    }
+
+    fn check_map(map: &Map) {
+        if let Some(root) = &map.root {
+            assert!(!root.entries.is_empty());
+            check_map_node(root, true);
+        }
+        assert_eq!(map.iter().count(), map.len());
+    }
+
+    fn keys(map: &Map) -> Vec<f64> {
+        map.iter()
+            .map(|(key, _)| match key {
+                MapKey::Number(n) => *n,
+                _ => panic!("not a number key"),
+            })
+            .collect()
+    }
+
+    fn free(_: u64) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    #[test]
+    fn map_inserts_and_removes_match_a_sorted_model() {
+        let mut map = Map::new();
+        let mut model = std::collections::BTreeSet::new();
+        // A scrambled but deterministic insertion order.
+        for i in 0..2000usize {
+            let k = (i * 7919) % 2000;
+            let Ok(next) = map.insert(key(k), Value::Number(i as f64), free);
+            map = next;
+            model.insert(k);
+        }
+        check_map(&map);
+        assert_eq!(
+            keys(&map),
+            model.iter().map(|k| *k as f64).collect::<Vec<_>>()
+        );
+
+        for i in 0..1500usize {
+            let k = (i * 104729) % 2000;
+            let Ok(next) = map.remove(&key(k), free);
+            if let Some(next) = next {
+                map = next;
+            }
+            model.remove(&k);
+            check_map(&map);
+        }
+        assert_eq!(
+            keys(&map),
+            model.iter().map(|k| *k as f64).collect::<Vec<_>>()
+        );
+        for k in 0..2000 {
+            assert_eq!(map.get(&key(k)).is_some(), model.contains(&k));
+        }
+    }
+
+    #[test]
+    fn updates_leave_the_old_map_untouched() {
+        let entries: Vec<_> = (0..100).map(|k| (key(k), Value::Number(0.0))).collect();
+        let old = Map::from_canonical(entries);
+        check_map(&old);
+        let Ok(new) = old.insert(key(50), Value::Number(1.0), free);
+        let Ok(Some(smaller)) = old.remove(&key(3), free) else {
+            panic!("key 3 is present");
+        };
+        assert!(matches!(old.get(&key(50)), Some(Value::Number(n)) if *n == 0.0));
+        assert!(matches!(new.get(&key(50)), Some(Value::Number(n)) if *n == 1.0));
+        assert_eq!((old.len(), new.len(), smaller.len()), (100, 100, 99));
+        assert!(old.get(&key(3)).is_some() && smaller.get(&key(3)).is_none());
+        assert!(matches!(old.remove(&key(1000), free), Ok(None)));
+    }
+
+    #[test]
+    fn bulk_construction_is_a_valid_tree_at_every_size() {
+        for len in [0, 1, 31, 32, 33, 63, 64, 500, 1024, 1057, 5000] {
+            let map = Map::from_canonical((0..len).map(|k| (key(k), Value::Bool(true))).collect());
+            check_map(&map);
+            assert_eq!(keys(&map), (0..len).map(|k| k as f64).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn map_updates_copy_one_path() {
+        let map = Map::from_canonical((0..10_000).map(|k| (key(k), Value::Bool(true))).collect());
+        let mut copied = 0u64;
+        let _ = map.insert(key(20_000), Value::Bool(false), |units| {
+            copied += units;
+            Ok::<(), Infallible>(())
+        });
+        // Three levels of at most 32 entries each, plus ~14 comparisons —
+        // not the 10_000 a flat copy cost.
+        assert!(copied < 200, "copied {copied} units");
+    }
+
+    fn set_keys(set: &Set) -> Vec<f64> {
+        set.iter()
+            .map(|key| match key {
+                MapKey::Number(n) => *n,
+                _ => panic!("not a number key"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn set_algebra_matches_a_sorted_model() {
+        use std::collections::BTreeSet;
+        let evens: BTreeSet<usize> = (0..300).step_by(2).collect();
+        let threes: BTreeSet<usize> = (0..300).step_by(3).collect();
+        let set = |model: &BTreeSet<usize>| Set::from_canonical(model.iter().map(|k| key(*k)).collect());
+        let floats = |model: BTreeSet<usize>| model.into_iter().map(|k| k as f64).collect::<Vec<_>>();
+        let (a, b) = (set(&evens), set(&threes));
+
+        let Ok(union) = a.union(&b, free);
+        let Ok(both) = a.intersect(&b, free);
+        let Ok(only) = a.difference(&b, free);
+        check_map(&union.map);
+        check_map(&both.map);
+        check_map(&only.map);
+        assert_eq!(set_keys(&union), floats(&evens | &threes));
+        assert_eq!(set_keys(&both), floats(&evens & &threes));
+        assert_eq!(set_keys(&only), floats(&evens - &threes));
+
+        let Ok(grown) = a.insert(key(1), free);
+        assert!(matches!(grown.contains(&key(1), free), Ok(true)));
+        assert!(matches!(a.contains(&key(1), free), Ok(false)));
+        assert!(matches!(a.remove(&key(1), free), Ok(None)));
+        assert_eq!(grown.len(), a.len() + 1);
+    }
+}
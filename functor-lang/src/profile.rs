@@ -0,0 +1,535 @@
+//! The hook profiler behind `functor run native --profile` and the debug
+//! runtime's `GET /profile`.
+//!
+//! A [`Profiler`] armed on a [`crate::Session`] ([`crate::Session::set_profiler`])
+//! hears every call the session's hooks make through the same seam tracing
+//! does — the enter/exit pair around each closure, builtin, and host call —
+//! but records only the callee's NAME and a timestamp, never a rendered
+//! value, so it is cheap enough to leave on for a whole play session.
+//!
+//! It aggregates, per function: calls, inclusive time (counted once per
+//! outermost activation, so recursion is not double-counted), and exclusive
+//! time (inclusive minus callees). Host externals (`Scene.cube`,
+//! `Physics.raycast`) never call back into Functor Lang, so they are always
+//! leaves — their time is what the engine spent on the game's behalf.
+//!
+//! Three renderings ([`ProfileFormat`]):
+//!
+//! - **summary** — the per-function table as JSON, hottest exclusive first;
+//! - **chrome** — Chrome trace-event JSON (`chrome://tracing`, Perfetto,
+//!   speedscope), one complete (`"X"`) event per call, the most recent
+//!   [`MAX_PROFILE_EVENTS`] kept;
+//! - **collapsed** — `a;b;c <µs>` lines, the folded-stack input of
+//!   `flamegraph.pl` and inferno. Direct self-recursion folds into one frame
+//!   (`loop;loop;loop` reads as `loop`), so a tail-recursive loop is one bar,
+//!   not ten thousand.
+//!
+//! Frames are named the way the trace names calls — the binding a call site
+//! used, `<lambda>` for an anonymous one — except that a builtin's
+//! per-element call (`List.map[3]`) is `List.map[*]`, so a long list is one
+//! row. Time is absent on wasm32 (`Instant` panics there): a profile counts
+//! calls and nothing else.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+use crate::snapshot::Json;
+
+/// How many per-call events the Chrome rendering keeps — the most recent,
+/// so a long session shows its last few seconds. Aggregates are uncapped.
+pub const MAX_PROFILE_EVENTS: usize = 100_000;
+
+/// What a profiled frame called.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallKind {
+    /// A closure or a constructor.
+    Function,
+    /// A builtin (`List.map`); its callbacks nest inside it.
+    Builtin,
+    /// A host external (`Scene.cube`) — always a leaf.
+    Host,
+}
+
+impl CallKind {
+    fn name(self) -> &'static str {
+        match self {
+            CallKind::Function => "function",
+            CallKind::Builtin => "builtin",
+            CallKind::Host => "host",
+        }
+    }
+}
+
+/// One function's aggregate.
+#[derive(Clone, Debug)]
+pub struct FunctionProfile {
+    pub name: String,
+    pub kind: CallKind,
+    pub calls: u64,
+    pub inclusive: Duration,
+    pub exclusive: Duration,
+}
+
+/// A profile rendering; see the module doc.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfileFormat {
+    Summary,
+    Chrome,
+    Collapsed,
+}
+
+impl FromStr for ProfileFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<ProfileFormat, String> {
+        match name {
+            "summary" => Ok(ProfileFormat::Summary),
+            "chrome" => Ok(ProfileFormat::Chrome),
+            "collapsed" => Ok(ProfileFormat::Collapsed),
+            other => Err(format!(
+                "unknown profile format `{other}` (expected summary, chrome, or collapsed)"
+            )),
+        }
+    }
+}
+
+impl ProfileFormat {
+    /// The format a `--profile` path asks for: `.folded` / `.collapsed` /
+    /// `.txt` is a flamegraph's input, anything else a Chrome trace.
+    pub fn for_path(path: &Path) -> ProfileFormat {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("folded" | "collapsed" | "txt") => ProfileFormat::Collapsed,
+            _ => ProfileFormat::Chrome,
+        }
+    }
+
+    /// Whether the rendering is JSON (else plain text).
+    pub fn is_json(self) -> bool {
+        self != ProfileFormat::Collapsed
+    }
+}
+
+/// The aggregating profiler; see the module doc.
+pub struct Profiler {
+    origin: Instant,
+    names: HashMap<String, u32>,
+    functions: Vec<Function>,
+    frames: Vec<Frame>,
+    /// The live stack with direct recursion folded — the key a frame's
+    /// exclusive time is charged to in `stacks`.
+    stack: Vec<u32>,
+    stacks: HashMap<Vec<u32>, Duration>,
+    events: VecDeque<Event>,
+    dropped: u64,
+}
+
+struct Function {
+    name: String,
+    kind: CallKind,
+    calls: u64,
+    inclusive: Duration,
+    exclusive: Duration,
+    /// Live activations — inclusive time is counted when the last one exits.
+    active: u32,
+}
+
+struct Frame {
+    function: u32,
+    start: Instant,
+    children: Duration,
+    /// A direct self-call, sharing its caller's entry in `stack`.
+    folded: bool,
+}
+
+struct Event {
+    function: u32,
+    start: Duration,
+    duration: Duration,
+}
+
+impl Default for Profiler {
+    fn default() -> Profiler {
+        Profiler::new()
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler {
+            origin: Instant::now(),
+            names: HashMap::new(),
+            functions: Vec::new(),
+            frames: Vec::new(),
+            stack: Vec::new(),
+            stacks: HashMap::new(),
+            events: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    /// A call began. `label` is the trace's callee label.
+    pub(crate) fn enter(&mut self, label: &str, kind: CallKind) {
+        let name = frame_name(label);
+        let function = match self.names.get(name.as_ref()) {
+            Some(&function) => function,
+            None => {
+                let function = self.functions.len() as u32;
+                self.names.insert(name.to_string(), function);
+                self.functions.push(Function {
+                    name: name.into_owned(),
+                    kind,
+                    calls: 0,
+                    inclusive: Duration::ZERO,
+                    exclusive: Duration::ZERO,
+                    active: 0,
+                });
+                function
+            }
+        };
+        let stats = &mut self.functions[function as usize];
+        stats.calls += 1;
+        stats.active += 1;
+        let folded = self.stack.last() == Some(&function);
+        if !folded {
+            self.stack.push(function);
+        }
+        self.frames.push(Frame {
+            function,
+            start: Instant::now(),
+            children: Duration::ZERO,
+            folded,
+        });
+    }
+
+    /// The innermost call ended — returned or failed alike.
+    pub(crate) fn exit(&mut self) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+        let now = Instant::now();
+        let elapsed = now.duration_since(frame.start);
+        let exclusive = elapsed.saturating_sub(frame.children);
+        let stats = &mut self.functions[frame.function as usize];
+        stats.exclusive += exclusive;
+        stats.active -= 1;
+        if stats.active == 0 {
+            stats.inclusive += elapsed;
+        }
+        if let Some(parent) = self.frames.last_mut() {
+            parent.children += elapsed;
+        }
+        match self.stacks.get_mut(self.stack.as_slice()) {
+            Some(total) => *total += exclusive,
+            None => {
+                self.stacks.insert(self.stack.clone(), exclusive);
+            }
+        }
+        if !frame.folded {
+            self.stack.pop();
+        }
+        if self.events.len() == MAX_PROFILE_EVENTS {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(Event {
+            function: frame.function,
+            start: frame.start.duration_since(self.origin),
+            duration: elapsed,
+        });
+    }
+
+    /// Every function called so far, hottest exclusive time first (then by
+    /// name, so equal rows — every row, on wasm32 — keep a stable order).
+    pub fn functions(&self) -> Vec<FunctionProfile> {
+        let mut out: Vec<FunctionProfile> = self
+            .functions
+            .iter()
+            .map(|function| FunctionProfile {
+                name: function.name.clone(),
+                kind: function.kind,
+                calls: function.calls,
+                inclusive: function.inclusive,
+                exclusive: function.exclusive,
+            })
+            .collect();
+        out.sort_by(|a, b| {
+            b.exclusive
+                .cmp(&a.exclusive)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        out
+    }
+
+    pub fn render(&self, format: ProfileFormat) -> String {
+        match format {
+            ProfileFormat::Summary => self.summary(),
+            ProfileFormat::Chrome => self.chrome_trace(),
+            ProfileFormat::Collapsed => self.collapsed(),
+        }
+    }
+
+    /// The per-function table as JSON: `{"functions": [{"name", "kind",
+    /// "calls", "inclusive_ms", "exclusive_ms"}]}`.
+    pub fn summary(&self) -> String {
+        let functions = self
+            .functions()
+            .into_iter()
+            .map(|function| {
+                Json::Object(vec![
+                    ("name".to_string(), Json::String(function.name)),
+                    (
+                        "kind".to_string(),
+                        Json::String(function.kind.name().to_string()),
+                    ),
+                    ("calls".to_string(), Json::Int(function.calls as i64)),
+                    (
+                        "inclusive_ms".to_string(),
+                        Json::Number(millis(function.inclusive)),
+                    ),
+                    (
+                        "exclusive_ms".to_string(),
+                        Json::Number(millis(function.exclusive)),
+                    ),
+                ])
+            })
+            .collect();
+        Json::Object(vec![("functions".to_string(), Json::Array(functions))]).compact()
+    }
+
+    /// Chrome trace-event JSON: one complete event per call (`ts`/`dur` in
+    /// µs), categorized by [`CallKind`]; `otherData.dropped_events` counts
+    /// the calls older than the kept window.
+    pub fn chrome_trace(&self) -> String {
+        let events = self
+            .events
+            .iter()
+            .map(|event| {
+                let function = &self.functions[event.function as usize];
+                Json::Object(vec![
+                    ("name".to_string(), Json::String(function.name.clone())),
+                    (
+                        "cat".to_string(),
+                        Json::String(function.kind.name().to_string()),
+                    ),
+                    ("ph".to_string(), Json::String("X".to_string())),
+                    ("ts".to_string(), Json::Number(micros(event.start))),
+                    ("dur".to_string(), Json::Number(micros(event.duration))),
+                    ("pid".to_string(), Json::Int(1)),
+                    ("tid".to_string(), Json::Int(1)),
+                ])
+            })
+            .collect();
+        Json::Object(vec![
+            ("traceEvents".to_string(), Json::Array(events)),
+            (
+                "displayTimeUnit".to_string(),
+                Json::String("ms".to_string()),
+            ),
+            (
+                "otherData".to_string(),
+                Json::Object(vec![(
+                    "dropped_events".to_string(),
+                    Json::Int(self.dropped as i64),
+                )]),
+            ),
+        ])
+        .compact()
+    }
+
+    /// Folded stacks, one `root;…;leaf <exclusive µs>` line each, sorted.
+    /// Stacks that took under a microsecond still appear (as `0`), so a
+    /// wasm32 profile keeps its shape.
+    pub fn collapsed(&self) -> String {
+        let mut lines: Vec<(String, u128)> = self
+            .stacks
+            .iter()
+            .map(|(stack, time)| {
+                let names: Vec<&str> = stack
+                    .iter()
+                    .map(|&function| self.functions[function as usize].name.as_str())
+                    .collect();
+                (names.join(";"), time.as_micros())
+            })
+            .collect();
+        lines.sort();
+        let mut out = String::new();
+        for (stack, time) in lines {
+            let _ = writeln!(out, "{stack} {time}");
+        }
+        out
+    }
+}
+
+/// A trace label as a frame name: a per-element index (`List.map[3]`)
+/// becomes `[*]`, and the folded format's separators (`;`, space) are kept
+/// out of it.
+fn frame_name(label: &str) -> Cow<'_, str> {
+    let indexed = label
+        .strip_suffix(']')
+        .and_then(|rest| rest.rsplit_once('['))
+        .filter(|(_, index)| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()));
+    match indexed {
+        Some((builtin, _)) => Cow::Owned(format!("{builtin}[*]")),
+        None if label.contains([';', ' ']) => Cow::Owned(label.replace([';', ' '], "_")),
+        None => Cow::Borrowed(label),
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1e3
+}
+
+fn micros(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1e6
+}
+
+/// `Instant` panics on wasm32, so there the profiler's clock stands still.
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone, Copy)]
+struct Instant;
+
+#[cfg(target_arch = "wasm32")]
+impl Instant {
+    fn now() -> Instant {
+        Instant
+    }
+
+    fn duration_since(&self, _earlier: Instant) -> Duration {
+        Duration::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hold the current frame open long enough to register on any clock.
+    fn work(millis: u64) {
+        std::thread::sleep(Duration::from_millis(millis));
+    }
+
+    fn row<'a>(functions: &'a [FunctionProfile], name: &str) -> &'a FunctionProfile {
+        functions
+            .iter()
+            .find(|function| function.name == name)
+            .unwrap_or_else(|| panic!("no profiled function `{name}`"))
+    }
+
+    /// `draw` → `Scene.cube` twice: a host leaf's exclusive time is its
+    /// inclusive time, and the caller's exclusive time is its inclusive time
+    /// minus exactly what its callees took.
+    #[test]
+    fn exclusive_time_is_inclusive_minus_nested_host_calls() {
+        let mut profiler = Profiler::new();
+        profiler.enter("draw", CallKind::Function);
+        work(2);
+        for _ in 0..2 {
+            profiler.enter("Scene.cube", CallKind::Host);
+            work(5);
+            profiler.exit();
+        }
+        profiler.exit();
+
+        let functions = profiler.functions();
+        let draw = row(&functions, "draw");
+        let cube = row(&functions, "Scene.cube");
+        assert_eq!((draw.calls, cube.calls), (1, 2));
+        assert_eq!(cube.kind, CallKind::Host);
+        assert_eq!(cube.exclusive, cube.inclusive);
+        assert!(cube.inclusive >= Duration::from_millis(10));
+        assert_eq!(draw.exclusive + cube.inclusive, draw.inclusive);
+        assert!(draw.exclusive >= Duration::from_millis(2));
+        // Hottest exclusive first: the host calls outweigh draw's own work.
+        assert_eq!(functions[0].name, "Scene.cube");
+    }
+
+    /// Recursion counts inclusive time once, for the outermost activation —
+    /// so a function that only calls itself has inclusive == exclusive.
+    #[test]
+    fn recursion_counts_inclusive_time_once() {
+        let mut profiler = Profiler::new();
+        for _ in 0..3 {
+            profiler.enter("loop", CallKind::Function);
+            work(1);
+        }
+        for _ in 0..3 {
+            profiler.exit();
+        }
+        let functions = profiler.functions();
+        let looped = row(&functions, "loop");
+        assert_eq!(looped.calls, 3);
+        assert_eq!(looped.inclusive, looped.exclusive);
+        assert!(looped.inclusive >= Duration::from_millis(3));
+        // Direct self-recursion folds into one collapsed frame.
+        assert!(
+            profiler.collapsed().starts_with("loop "),
+            "{}",
+            profiler.collapsed()
+        );
+    }
+
+    /// Each call is one complete event in exit order (callees first), its
+    /// `ts`/`dur` in µs and nested inside its caller's span.
+    #[test]
+    fn chrome_events_nest_callees_inside_their_caller() {
+        let mut profiler = Profiler::new();
+        profiler.enter("update", CallKind::Function);
+        work(1);
+        profiler.enter("List.map[3]", CallKind::Builtin);
+        work(2);
+        profiler.exit();
+        work(1);
+        profiler.exit();
+
+        let trace = Json::parse(&profiler.chrome_trace()).expect("valid JSON");
+        let Json::Object(fields) = trace else {
+            panic!("a trace is an object");
+        };
+        let Some((_, Json::Array(events))) = fields.iter().find(|(key, _)| key == "traceEvents")
+        else {
+            panic!("traceEvents is an array");
+        };
+        let event = |index: usize| {
+            let Json::Object(event) = &events[index] else {
+                panic!("an event is an object");
+            };
+            let field = |name: &str| {
+                event
+                    .iter()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.clone())
+                    .unwrap_or_else(|| panic!("event lacks `{name}`"))
+            };
+            let number = |name: &str| match field(name) {
+                Json::Number(n) => n,
+                other => panic!("`{name}` is not a number: {other:?}"),
+            };
+            (
+                field("name"),
+                field("cat"),
+                field("ph"),
+                number("ts"),
+                number("dur"),
+            )
+        };
+        assert_eq!(events.len(), 2);
+        let (name, cat, ph, child_ts, child_dur) = event(0);
+        assert_eq!(name, Json::String("List.map[*]".to_string()));
+        assert_eq!(cat, Json::String("builtin".to_string()));
+        assert_eq!(ph, Json::String("X".to_string()));
+        assert!(child_dur >= 2_000.0, "dur is in µs: {child_dur}");
+        let (name, cat, _, parent_ts, parent_dur) = event(1);
+        assert_eq!(name, Json::String("update".to_string()));
+        assert_eq!(cat, Json::String("function".to_string()));
+        assert!(parent_ts <= child_ts);
+        assert!(child_ts + child_dur <= parent_ts + parent_dur + 1e-3);
+        assert!(parent_dur >= child_dur + 2_000.0);
+    }
+}
@@ -28,6 +28,17 @@
 //! **Protected names.** A file whose module name collides with a
 //! builtin/prelude namespace (`List`, `Scene`, …) is refused — otherwise
 //! `Scene.cube` would silently stop meaning the prelude.
+//!
+//! **Visibility.** Every top-level name is public by default. A
+//! `private let` / `private type` is reachable only from its own file
+//! (inline `module` blocks included), and a `.funi` with the same stem as a
+//! `.fun` SEALS that module: other files reach exactly what the interface
+//! declares. Each declared value must be defined, at least as generally as
+//! its signature (the checker verifies it, and other files see the
+//! signature's type); each declared type must be defined with the same
+//! shape, or — declared without a body — becomes ABSTRACT outside the file,
+//! nameable but never built, matched, or opened up. A sealed file's inline
+//! modules are internal.
 
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
@@ -49,7 +60,9 @@ const PROTECTED_NAMESPACES: &[&str] = &[
     "Net",
     "Key",
     "List",
+    "Array",
     "Map",
+    "Set",
     "Text",
     "Math",
     "Random",
@@ -64,6 +77,7 @@ const PROTECTED_NAMESPACES: &[&str] = &[
     "Frame",
     "Light",
     "Fog",
+    "Post",
     "Color",
     "Vec3",
     "Skybox",
@@ -192,6 +206,11 @@ pub struct Project {
     /// [`Project::check`] hands it to the checker so a bare literal never
     /// resolves against an unrelated sibling's type.
     scopes: RecordLiteralScopes,
+    /// Each file module's direct dependencies (the modules it references).
+    deps: HashMap<String, HashSet<String>>,
+    /// Canonical names other files cannot reach (`private`, or left out of
+    /// a sealing `.funi`) → the file module that owns them.
+    hidden: HashMap<String, String>,
 }
 
 impl Project {
@@ -210,6 +229,56 @@ impl Project {
         crate::types::check_with_scopes_and_types(&self.module, &self.scopes)
     }
 
+    /// This project re-linked from edited files: `edit` maps each file (by
+    /// index, in [`SourceMap::files`] order) to its new path, module name,
+    /// and source; span bases are reassigned. `crate::references` proves a
+    /// rename with it.
+    pub(crate) fn relink(
+        &self,
+        mut edit: impl FnMut(usize, &SourceFile) -> (PathBuf, String, String),
+    ) -> Result<Project, ProjectError> {
+        let mut base = 0;
+        let mut files = Vec::new();
+        for (index, file) in self.sources.files().iter().enumerate() {
+            let (path, module, src) = edit(index, file);
+            let len = src.len();
+            files.push(SourceFile {
+                interface: file.interface,
+                path,
+                module,
+                src,
+                base,
+            });
+            base += len + 1;
+        }
+        link(files)
+    }
+
+    /// `modules` and every file module that depends on one of them, directly
+    /// or transitively — what an edit to those files can change (`functor
+    /// test --watch` re-runs these modules' tests).
+    pub fn dependents(&self, modules: &HashSet<String>) -> HashSet<String> {
+        let mut affected = modules.clone();
+        loop {
+            let before = affected.len();
+            for (module, deps) in &self.deps {
+                if !affected.contains(module) && deps.iter().any(|dep| affected.contains(dep)) {
+                    affected.insert(module.clone());
+                }
+            }
+            if affected.len() == before {
+                return affected;
+            }
+        }
+    }
+
+    /// Whether code in file module `module` may reach the canonical `name`
+    /// (a def, constructor, type, or inline module) — `false` only for a name
+    /// another file keeps `private` or leaves out of its sealing `.funi`.
+    pub fn reachable_from(&self, name: &str, module: &str) -> bool {
+        self.hidden.get(name).is_none_or(|owner| owner == module)
+    }
+
     /// The inline `module` block containing project-wide `offset`, if any —
     /// the cursor's namespace for `functor_lang::complete` (blocks never
     /// nest, so at most one matches).
@@ -301,7 +370,9 @@ const RESULT_MODULE_SRC: &str = include_str!("../stdlib/result.fun");
 /// drift test (`builtin_documentation_matches_the_registry`), so a builtin
 /// added, removed, or retyped without updating these files fails the build.
 const LIST_DOC_SRC: &str = include_str!("../stdlib/list.funi");
+const ARRAY_DOC_SRC: &str = include_str!("../stdlib/array.funi");
 const MAP_DOC_SRC: &str = include_str!("../stdlib/map.funi");
+const SET_DOC_SRC: &str = include_str!("../stdlib/set.funi");
 const TEXT_DOC_SRC: &str = include_str!("../stdlib/text.funi");
 const MATH_DOC_SRC: &str = include_str!("../stdlib/math.funi");
 const DEBUG_DOC_SRC: &str = include_str!("../stdlib/debug.funi");
@@ -356,11 +427,11 @@ impl DocumentationModule {
 ///
 /// Two kinds of module appear here, and the difference is invisible to a game:
 ///
-/// - the namespaces the interpreter implements in Rust (`List`, `Map`,
-///   `Text`, `Math`, `Random`, `Debug`), documented by the `.funi` sources
-///   above — `Random`'s is the very module [`core_modules`] injects, and the
-///   rest are documentation-only interfaces pinned to the builtin registry by
-///   a drift test;
+/// - the namespaces the interpreter implements in Rust (`List`, `Array`,
+///   `Map`, `Set`, `Text`, `Math`, `Random`, `Debug`), documented by the
+///   `.funi` sources above — `Random`'s is the very module [`core_modules`]
+///   injects, and the rest are documentation-only interfaces pinned to the
+///   builtin registry by a drift test;
 /// - the modules written in Functor Lang and linked into every project
 ///   (`Option`, `Result`, `Key`, `Mouse`), documented from the exact source
 ///   that runs, so those cannot drift at all.
@@ -377,7 +448,9 @@ pub fn stdlib_documentation_modules() -> Vec<DocumentationModule> {
     };
     vec![
         interface("List", LIST_DOC_SRC),
+        interface("Array", ARRAY_DOC_SRC),
         interface("Map", MAP_DOC_SRC),
+        interface("Set", SET_DOC_SRC),
         interface("Text", TEXT_DOC_SRC),
         interface("Math", MATH_DOC_SRC),
         interface("Random", RANDOM_MODULE_SRC),
@@ -721,15 +794,21 @@ if it is a customized module",
             ));
         }
         if let Some(previous) = files.iter().find(|f| f.module == module) {
-            return Err(at(
-                path,
-                format!(
-                    "module name `{module}` (from {}) is already taken by {} — module names \
-come from file names, capitalized",
-                    file_name(path),
-                    file_name(&previous.path)
-                ),
-            ));
+            // `utils.funi` beside `utils.fun` is not a clash: it seals the
+            // module (see `link`).
+            let seals = previous.interface != is_interface(path)
+                && previous.path.file_stem() == path.file_stem();
+            if !seals {
+                return Err(at(
+                    path,
+                    format!(
+                        "module name `{module}` (from {}) is already taken by {} — module \
+names come from file names, capitalized",
+                        file_name(path),
+                        file_name(&previous.path)
+                    ),
+                ));
+            }
         }
         let len = src.len();
         files.push(SourceFile {
@@ -811,6 +890,21 @@ fn link(files: Vec<SourceFile>) -> Result<Project, ProjectError> {
         programs.push(program);
     }
 
+    // A `.funi` beside its own `.fun` SEALS that module rather than being
+    // one (see the module doc): its program is set aside, and an empty
+    // stand-in keeps `programs` aligned with `files`.
+    let mut seals: Vec<(usize, usize, ast::Program)> = Vec::new();
+    for index in 0..files.len() {
+        let module = &files[index].module;
+        let target = files.iter().position(|f| !f.interface && &f.module == module);
+        if let (true, Some(target)) = (files[index].interface, target) {
+            let program =
+                std::mem::replace(&mut programs[index], ast::Program { items: Vec::new() });
+            seals.push((index, target, program));
+        }
+    }
+    let sealing: HashSet<usize> = seals.iter().map(|(index, ..)| *index).collect();
+
     // Every module's exports, for cross-module resolution during lowering.
     // Inline `module` blocks are keyed by their full path (`Utils.Grid`);
     // a file's own top-level exports never include them.
@@ -818,6 +912,9 @@ fn link(files: Vec<SourceFile>) -> Result<Project, ProjectError> {
     let mut inline_modules: Vec<InlineModule> = Vec::new();
     let file_modules: HashSet<&str> = files.iter().map(|f| f.module.as_str()).collect();
     for (index, (file, program)) in files.iter().zip(&programs).enumerate() {
+        if sealing.contains(&index) {
+            continue;
+        }
         exports.insert(file.module.clone(), exports_of(&program.items));
         for decl in program.items.iter().filter_map(|item| match item {
             ast::Item::Module(decl) => Some(decl),
@@ -872,6 +969,16 @@ rename it"
             });
         }
     }
+    for (index, target, program) in &seals {
+        seal_exports(
+            &mut exports,
+            &files[*target],
+            &programs[*target].items,
+            &files[*index],
+            &program.items,
+        )
+        .map_err(|(span, message)| render_span(&files, *index, span, &message))?;
+    }
     let exports = exports;
 
     // Units are PROJECT-WIDE (file = module makes them like constructors), so
@@ -921,23 +1028,29 @@ project-wide, like constructors)",
     let mut lowered: Vec<Module> = Vec::new();
     let mut deps: HashMap<String, HashSet<String>> = HashMap::new();
     let mut scopes = RecordLiteralScopes::default();
+    // A module member's canonical name. Mirrors `lower::Lowerer::qualify`:
+    // the entry file's members stay bare, so its inline modules keep only
+    // their own segment.
+    let canon = |module: &str, name: &str| {
+        if module == entry {
+            name.to_string()
+        } else if let Some(inline) = module
+            .strip_prefix(entry.as_str())
+            .and_then(|rest| rest.strip_prefix('.'))
+        {
+            format!("{inline}.{name}")
+        } else {
+            format!("{module}.{name}")
+        }
+    };
     for (index, (file, program)) in files.iter().zip(programs).enumerate() {
+        if sealing.contains(&index) {
+            continue;
+        }
         // Record-literal visibility for this module: its own types plus its
-        // `open`ed modules' (by canonical name — the entry's are bare).
-        // Mirrors `lower::Lowerer::qualify`: the entry file's members stay
-        // bare, so its inline modules keep only their own segment.
-        let canon = |module: &str, name: &str| {
-            if module == entry {
-                name.to_string()
-            } else if let Some(inline) = module
-                .strip_prefix(entry.as_str())
-                .and_then(|rest| rest.strip_prefix('.'))
-            {
-                format!("{inline}.{name}")
-            } else {
-                format!("{module}.{name}")
-            }
-        };
+        // `open`ed modules' reachable ones (by canonical name — the entry's
+        // are bare). Another file's abstract types never build from a
+        // literal.
         let mut visible: HashSet<String> = exports[&file.module]
             .types
             .iter()
@@ -950,7 +1063,17 @@ project-wide, like constructors)",
             // `open Server` may name one of this file's inline modules.
             let path = open_module_path(&file.module, &decl.module, &exports);
             if let Some(opened) = exports.get(&path) {
-                visible.extend(opened.types.iter().map(|name| canon(&path, name)));
+                let own = path.split('.').next() == Some(file.module.as_str());
+                visible.extend(
+                    opened
+                        .types
+                        .iter()
+                        .filter(|name| {
+                            own || !(opened.hidden_types.contains(*name)
+                                || opened.opaque.contains(*name))
+                        })
+                        .map(|name| canon(&path, name)),
+                );
             }
         }
         let prefix = if file.module == entry {
@@ -984,8 +1107,12 @@ project-wide, like constructors)",
             } else {
                 format!("{prefix}.{}", decl.name)
             };
+            scopes
+                .owners
+                .insert(inner_prefix.clone(), file.module.clone());
             scopes.by_module.insert(inner_prefix, inner);
         }
+        scopes.owners.insert(prefix.clone(), file.module.clone());
         scopes.by_module.insert(prefix, visible);
 
         let env = ProjectEnv {
@@ -1010,6 +1137,38 @@ project-wide, like constructors)",
         deps.insert(file.module.clone(), module_deps);
     }
 
+    // Each seal's signatures, canonicalized in its module's scope: the
+    // checker holds the implementation to them, and other modules see them.
+    let mut sealed_values = Vec::new();
+    for (index, target, program) in seals {
+        let env = ProjectEnv {
+            name: &files[target].module,
+            entry: &entry,
+            modules: &exports,
+            units: &units,
+        };
+        let (module, next, _) = lower_in_project(program, &env, bases)
+            .map_err(|e| render_span(&files, index, e.span, &e.message))?;
+        bases = next;
+        sealed_values.extend(module.signatures);
+    }
+
+    // What each file keeps to itself, by canonical name: the checker needs
+    // the abstract types, and editor tooling everything hidden.
+    let mut hidden: HashMap<String, String> = HashMap::new();
+    for (path, module) in &exports {
+        let file = path.split('.').next().unwrap_or(path);
+        for name in &module.opaque {
+            scopes.opaque.insert(canon(path, name), file.to_string());
+        }
+        let names = module.hidden.iter().chain(&module.hidden_types);
+        hidden.extend(names.map(|name| (canon(path, name), file.to_string())));
+        if module.sealed_by.is_some() && path.contains('.') {
+            let (parent, name) = path.rsplit_once('.').expect("an inline module path");
+            hidden.insert(canon(parent, name), file.to_string());
+        }
+    }
+
     // Refuse dependency cycles (fail loud with the path), and derive the
     // evaluation order: dependencies before dependents. Iteration order is
     // file order (entry first), so the result is deterministic.
@@ -1038,13 +1197,16 @@ allowed (within one file, definitions may still be mutually recursive)",
         types: Vec::new(),
         defs: Vec::new(),
         signatures: Vec::new(),
+        sealed: sealed_values,
         expects: Vec::new(),
         units: Vec::new(),
         unit_ops: Vec::new(),
     };
     let mut by_module: HashMap<String, Module> = files
         .iter()
-        .map(|f| f.module.clone())
+        .enumerate()
+        .filter(|(index, _)| !sealing.contains(index))
+        .map(|(_, f)| f.module.clone())
         .zip(lowered)
         .collect();
     for name in &order {
@@ -1063,9 +1225,189 @@ allowed (within one file, definitions may still be mutually recursive)",
         entry,
         inline_modules,
         scopes,
+        deps,
+        hidden,
     })
 }
 
+/// Apply the `.funi` `interface` sealing `implementation` (whose items are
+/// `items`) to `exports`: everything the interface leaves out becomes hidden,
+/// its body-less types opaque, and the sealed file's inline modules wholly
+/// internal. A declaration the implementation cannot back is an error at the
+/// interface's span. Value TYPES are the checker's to verify, once they are
+/// canonical.
+fn seal_exports(
+    exports: &mut HashMap<String, Exports>,
+    implementation: &SourceFile,
+    items: &[ast::Item],
+    interface: &SourceFile,
+    declared: &[ast::Item],
+) -> Result<(), (crate::Span, String)> {
+    let (imp, seal) = (
+        file_name(&implementation.path),
+        file_name(&interface.path),
+    );
+    let mut values: HashMap<&str, crate::Span> = HashMap::new();
+    let mut types: HashMap<&str, &ast::TypeDecl> = HashMap::new();
+    for item in declared {
+        match item {
+            ast::Item::Sig(sig) => {
+                values.insert(&sig.name, sig.span);
+            }
+            ast::Item::Type(decl) if matches!(decl.body, ast::TypeBody::Host) => {
+                return Err((
+                    decl.span,
+                    format!(
+                        "{seal} seals {imp}, whose values are data, not engine values — declare \
+`type {}` without a body to make it abstract",
+                        decl.name
+                    ),
+                ));
+            }
+            ast::Item::Type(decl) => {
+                types.insert(&decl.name, decl);
+            }
+            ast::Item::Unit(ast::UnitDecl { span, .. })
+            | ast::Item::UnitOp(ast::UnitOpDecl { span, .. }) => {
+                return Err((
+                    *span,
+                    format!(
+                        "{seal} seals {imp}, so it lists signatures and types — \
+                         declare units in {imp}"
+                    ),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    let module = exports
+        .get_mut(&implementation.module)
+        .expect("every file has exports");
+    module.sealed_by = Some(seal.clone());
+    let mut defined: HashSet<(&str, &str)> = HashSet::new();
+    for item in items {
+        match item {
+            ast::Item::Let(decl) => {
+                defined.insert(("let", &decl.name));
+                match values.get(decl.name.as_str()) {
+                    Some(&span) if decl.private => {
+                        return Err((
+                            span,
+                            format!(
+                                "`{}` is `private` in {imp}, so {seal} cannot export it",
+                                decl.name
+                            ),
+                        ));
+                    }
+                    Some(_) => {}
+                    None => {
+                        module.hidden.insert(decl.name.clone());
+                    }
+                }
+            }
+            ast::Item::Type(decl) => {
+                defined.insert(("type", &decl.name));
+                let ctors: Vec<String> = match &decl.body {
+                    ast::TypeBody::Variants(variants) => {
+                        variants.iter().map(|variant| variant.name.clone()).collect()
+                    }
+                    _ => Vec::new(),
+                };
+                let Some(sealed) = types.get(decl.name.as_str()) else {
+                    module.hidden_types.insert(decl.name.clone());
+                    module.hidden.extend(ctors);
+                    continue;
+                };
+                let mismatch = if decl.private {
+                    Some(format!(
+                        "`{}` is `private` in {imp}, so {seal} cannot export it",
+                        decl.name
+                    ))
+                } else if sealed.params.len() != decl.params.len() {
+                    Some(format!(
+                        "{seal} declares `{}` with {} type parameter(s), but {imp} with {}",
+                        decl.name,
+                        sealed.params.len(),
+                        decl.params.len()
+                    ))
+                } else if !matches!(sealed.body, ast::TypeBody::Abstract)
+                    && !same_shape(&sealed.body, &decl.body)
+                {
+                    Some(format!(
+                        "{seal} declares `{}` with a different shape than {imp} — repeat the \
+definition exactly, or leave the body off to make it abstract",
+                        decl.name
+                    ))
+                } else {
+                    None
+                };
+                if let Some(message) = mismatch {
+                    return Err((sealed.span, message));
+                }
+                if matches!(sealed.body, ast::TypeBody::Abstract) {
+                    module.opaque.insert(decl.name.clone());
+                    module.hidden.extend(ctors);
+                }
+            }
+            _ => {}
+        }
+    }
+    for item in declared {
+        let (name, span, what) = match item {
+            ast::Item::Sig(sig) => (&sig.name, sig.span, "let"),
+            ast::Item::Type(decl) => (&decl.name, decl.span, "type"),
+            _ => continue,
+        };
+        if !defined.contains(&(what, name.as_str())) {
+            return Err((
+                span,
+                format!("{seal} declares `{name}`, but {imp} has no `{what} {name}`"),
+            ));
+        }
+    }
+
+    // Inline modules cannot be declared in an interface, so a sealed file's
+    // are internal.
+    let inline = format!("{}.", implementation.module);
+    for (_, module) in exports
+        .iter_mut()
+        .filter(|(path, _)| path.starts_with(&inline))
+    {
+        let values: Vec<String> = module.defs.iter().chain(module.ctors.keys()).cloned().collect();
+        module.hidden.extend(values);
+        let types: Vec<String> = module.types.iter().cloned().collect();
+        module.hidden_types.extend(types);
+        module.sealed_by = Some(seal.clone());
+    }
+    Ok(())
+}
+
+/// Whether two type bodies declare the same shape, as written (spans aside).
+fn same_shape(a: &ast::TypeBody, b: &ast::TypeBody) -> bool {
+    fn same_type(a: &ast::TypeName, b: &ast::TypeName) -> bool {
+        a.name == b.name
+            && a.args.len() == b.args.len()
+            && a.args.iter().zip(&b.args).all(|(a, b)| same_type(a, b))
+    }
+    let same_fields = |a: &[ast::FieldTy], b: &[ast::FieldTy]| {
+        a.len() == b.len()
+            && a.iter()
+                .zip(b)
+                .all(|(a, b)| a.name == b.name && same_type(&a.ty, &b.ty))
+    };
+    match (a, b) {
+        (ast::TypeBody::Record(a), ast::TypeBody::Record(b)) => same_fields(a, b),
+        (ast::TypeBody::Variants(a), ast::TypeBody::Variants(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b)
+                    .all(|(a, b)| a.name == b.name && same_fields(&a.fields, &b.fields))
+        }
+        _ => false,
+    }
+}
+
 /// Load a project from ONE in-memory entry source (no filesystem) — the
 /// wasm producer's path, where the game is fetched as a single text and
 /// there are no sibling files. The core bundled modules are still injected.
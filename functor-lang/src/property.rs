@@ -0,0 +1,603 @@
+//! Input generation and shrinking for `property` tests (see
+//! [`crate::ast::ExpectKind::Property`]).
+//!
+//! A property's lambda parameters are annotated, and the annotation picks the
+//! generator: primitives, `List`/`Array`, tuples, and the module's declared
+//! record and variant types — resolved through [`crate::types::declarations`],
+//! so a generator sees exactly the shapes the checker does (generic
+//! declarations included). Every value comes from a seeded [`Rng`], so one
+//! seed replays one run exactly.
+//!
+//! Cases grow: the runner ([`crate::eval::run_expects_with`]) passes a
+//! `size` that climbs from 0 to [`MAX_SIZE`] across the run — short lists,
+//! small numbers, and shallow variants first. A failing case is then
+//! SHRUNK: [`Generators::shrink`] proposes simpler values (toward 0, toward
+//! the empty string or list, toward a constructor with fewer fields), and the
+//! runner keeps any that still fail, until none does.
+
+use crate::ast::TypeName;
+use crate::ir::Module;
+use crate::persistent::List;
+use crate::types::{declarations, Declarations, Type};
+use crate::value::Value;
+use std::rc::Rc;
+
+/// Generated cases per `property`, unless the run says otherwise.
+pub const DEFAULT_CASES: u32 = 100;
+
+/// The seed a run uses unless told otherwise. Fixed, so an editor gutter and
+/// a CI run agree without being told; pass another to explore further.
+pub const DEFAULT_SEED: u64 = 0x5eed;
+
+/// The largest `size` a run reaches: the bound on generated list and string
+/// lengths and on number magnitudes (variant depth shrinks with it).
+pub const MAX_SIZE: u32 = 30;
+
+/// How deep generation may nest before a type is declared unbuildable — a
+/// variant whose every constructor recurses (`type t = | T(next: t)`) has no
+/// finite value.
+const MAX_DEPTH: u32 = 64;
+
+/// A small, fast, seedable PRNG (SplitMix64): deterministic across platforms,
+/// which is what makes a reported seed replayable.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n` (`n > 0`).
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// Uniform in `-bound..=bound`.
+    fn around_zero(&mut self, bound: u32) -> i64 {
+        self.below(2 * u64::from(bound) + 1) as i64 - i64::from(bound)
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+}
+
+/// Numbers worth trying on purpose: identities, signs, and fractions.
+const INTERESTING_FLOATS: [f64; 7] = [0.0, 1.0, -1.0, 0.5, -0.5, 1e-9, 1e9];
+const INTERESTING_INTS: [i64; 5] = [0, 1, -1, 1_000_000, -1_000_000];
+/// The occasional non-ASCII character, so a `String.length`-style property
+/// meets a multi-byte one.
+const UNICODE: [char; 4] = ['é', 'λ', '中', '😀'];
+
+/// The generators for one module: its declarations, resolved once.
+pub struct Generators {
+    decls: Declarations,
+}
+
+impl Generators {
+    pub fn new(module: &Module) -> Generators {
+        Generators {
+            decls: declarations(module),
+        }
+    }
+
+    /// Resolve a property parameter's annotation to the type its values are
+    /// generated at, or say why nothing can generate it.
+    pub fn domain(&mut self, annotation: &TypeName) -> Result<Type, String> {
+        let ty = self.decls.resolve(annotation).ok_or_else(|| {
+            format!(
+                "no generator for `{}` — the type does not resolve (see the check errors)",
+                annotation.name
+            )
+        })?;
+        self.generatable(&ty, &mut Vec::new())?;
+        Ok(ty)
+    }
+
+    /// Whether every type reachable from `ty` has a generator. `seen` holds
+    /// the declared types already being checked — a recursive reference is
+    /// fine here; whether it can ever bottom out is generation's question.
+    fn generatable(&self, ty: &Type, seen: &mut Vec<Type>) -> Result<(), String> {
+        match ty {
+            Type::Float | Type::Int | Type::String | Type::Bool => Ok(()),
+            Type::List(elem) | Type::Array(elem) => self.generatable(elem, seen),
+            Type::Tuple(items) => items.iter().try_for_each(|t| self.generatable(t, seen)),
+            Type::Record(name, args) => {
+                if seen.contains(ty) {
+                    return Ok(());
+                }
+                seen.push(ty.clone());
+                let fields = self.decls.record_fields(name, args).unwrap_or_default();
+                fields
+                    .iter()
+                    .try_for_each(|(_, t)| self.generatable(t, seen))
+            }
+            Type::Variant(name, args) => {
+                if seen.contains(ty) {
+                    return Ok(());
+                }
+                seen.push(ty.clone());
+                let ctors = self.decls.constructors(name, args).unwrap_or_default();
+                if ctors.is_empty() {
+                    return Err(format!(
+                        "no generator for `{ty}` — it has no constructors (its values come \
+from the host)"
+                    ));
+                }
+                ctors
+                    .iter()
+                    .flat_map(|(_, fields)| fields)
+                    .try_for_each(|t| self.generatable(t, seen))
+            }
+            Type::Var(_) => Err(format!(
+                "no generator for `{ty}` — a property's inputs need concrete types \
+(`List<int>`, not `List<'a>`)"
+            )),
+            Type::Unknown => Err(
+                "no generator for `unknown` — annotate the concrete type to generate".to_string(),
+            ),
+            Type::Fn(..) => Err(format!("no generator for functions (`{ty}`)")),
+            Type::Map(..) | Type::Set(..) => Err(format!(
+                "no generator for `{ty}` — generate a `List` and build it in the body \
+(`Map.fromList(entries)`)"
+            )),
+        }
+    }
+
+    /// A random value of `ty` at `size` (see [`MAX_SIZE`]).
+    pub fn generate(&self, ty: &Type, size: u32, rng: &mut Rng) -> Result<Value, String> {
+        self.generate_at(ty, size, rng, 0)
+    }
+
+    fn generate_at(
+        &self,
+        ty: &Type,
+        size: u32,
+        rng: &mut Rng,
+        depth: u32,
+    ) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err(unbuildable(ty));
+        }
+        Ok(match ty {
+            Type::Float => Value::Number(if rng.chance(15) {
+                INTERESTING_FLOATS[rng.below(INTERESTING_FLOATS.len() as u64) as usize]
+            } else if rng.chance(40) {
+                rng.around_zero(size) as f64
+            } else {
+                (rng.unit() * 2.0 - 1.0) * f64::from(size.max(1))
+            }),
+            Type::Int => {
+                let n = if rng.chance(10) {
+                    INTERESTING_INTS[rng.below(INTERESTING_INTS.len() as u64) as usize]
+                } else {
+                    rng.around_zero(size)
+                };
+                Value::Int(n)
+            }
+            Type::Bool => Value::Bool(rng.chance(50)),
+            Type::String => {
+                let len = rng.below(u64::from(size) + 1);
+                let text: String = (0..len)
+                    .map(|_| {
+                        if rng.chance(5) {
+                            UNICODE[rng.below(UNICODE.len() as u64) as usize]
+                        } else if rng.chance(80) {
+                            char::from(b'a' + rng.below(26) as u8)
+                        } else {
+                            char::from(b' ' + rng.below(95) as u8)
+                        }
+                    })
+                    .collect();
+                Value::String(Rc::from(text))
+            }
+            Type::List(elem) | Type::Array(elem) => {
+                let len = rng.below(u64::from(size) + 1);
+                let items = (0..len)
+                    .map(|_| self.generate_at(elem, size / 2, rng, depth + 1))
+                    .collect::<Result<Vec<_>, _>>()?;
+                sequence(ty, items)
+            }
+            Type::Tuple(items) => Value::Tuple(Rc::new(
+                items
+                    .iter()
+                    .map(|t| self.generate_at(t, size, rng, depth + 1))
+                    .collect::<Result<_, _>>()?,
+            )),
+            Type::Record(name, args) => {
+                let fields = self.decls.record_fields(name, args).unwrap_or_default();
+                let mut out = Vec::with_capacity(fields.len());
+                for (field, t) in fields {
+                    out.push((field, self.generate_at(&t, size, rng, depth + 1)?));
+                }
+                Value::Record(Rc::new(out))
+            }
+            Type::Variant(name, args) => {
+                let ctors = self.decls.constructors(name, args).unwrap_or_default();
+                // Out of size: only constructors that end the recursion.
+                let pool: Vec<&(String, Vec<Type>)> = if size == 0 {
+                    let base: Vec<_> = ctors
+                        .iter()
+                        .filter(|(_, fields)| !fields.iter().any(|f| mentions(f, name)))
+                        .collect();
+                    if base.is_empty() {
+                        ctors.iter().collect()
+                    } else {
+                        base
+                    }
+                } else {
+                    ctors.iter().collect()
+                };
+                if pool.is_empty() {
+                    return Err(unbuildable(ty));
+                }
+                let (ctor, fields) = pool[rng.below(pool.len() as u64) as usize];
+                let field_size = size.saturating_sub(1) / (fields.len() as u32).max(1);
+                let args = fields
+                    .iter()
+                    .map(|t| self.generate_at(t, field_size, rng, depth + 1))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Value::Variant {
+                    ctor: Rc::from(ctor.as_str()),
+                    args: Rc::new(args),
+                }
+            }
+            Type::Var(_) | Type::Unknown | Type::Fn(..) | Type::Map(..) | Type::Set(..) => {
+                return Err(format!("no generator for `{ty}`"))
+            }
+        })
+    }
+
+    /// The simplest value of `ty`: zeros, empties, `false`, and the first
+    /// constructor that ends the recursion — where shrinking a variant to
+    /// another constructor lands.
+    fn smallest(&self, ty: &Type, depth: u32) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err(unbuildable(ty));
+        }
+        Ok(match ty {
+            Type::Float => Value::Number(0.0),
+            Type::Int => Value::Int(0),
+            Type::Bool => Value::Bool(false),
+            Type::String => Value::String(Rc::from("")),
+            Type::List(_) | Type::Array(_) => sequence(ty, Vec::new()),
+            Type::Tuple(items) => Value::Tuple(Rc::new(
+                items
+                    .iter()
+                    .map(|t| self.smallest(t, depth + 1))
+                    .collect::<Result<_, _>>()?,
+            )),
+            Type::Record(name, args) => {
+                let fields = self.decls.record_fields(name, args).unwrap_or_default();
+                let mut out = Vec::with_capacity(fields.len());
+                for (field, t) in fields {
+                    out.push((field, self.smallest(&t, depth + 1)?));
+                }
+                Value::Record(Rc::new(out))
+            }
+            Type::Variant(name, args) => {
+                let ctors = self.decls.constructors(name, args).unwrap_or_default();
+                let (ctor, fields) = ctors
+                    .iter()
+                    .find(|(_, fields)| !fields.iter().any(|f| mentions(f, name)))
+                    .or(ctors.first())
+                    .ok_or_else(|| unbuildable(ty))?;
+                Value::Variant {
+                    ctor: Rc::from(ctor.as_str()),
+                    args: Rc::new(
+                        fields
+                            .iter()
+                            .map(|t| self.smallest(t, depth + 1))
+                            .collect::<Result<_, _>>()?,
+                    ),
+                }
+            }
+            Type::Var(_) | Type::Unknown | Type::Fn(..) | Type::Map(..) | Type::Set(..) => {
+                return Err(format!("no generator for `{ty}`"))
+            }
+        })
+    }
+
+    /// Simpler candidates for a failing `value` of `ty`, most aggressive
+    /// first. Every candidate is strictly simpler, so greedily taking any
+    /// that still fails terminates.
+    pub fn shrink(&self, ty: &Type, value: &Value) -> Vec<Value> {
+        let mut out = Vec::new();
+        match (ty, value) {
+            (Type::Int, Value::Int(n)) => {
+                let n = *n;
+                if n != 0 {
+                    out.push(Value::Int(0));
+                    if n < 0 && n != i64::MIN {
+                        out.push(Value::Int(-n));
+                    }
+                    if n / 2 != 0 {
+                        out.push(Value::Int(n / 2));
+                    }
+                    if n - n.signum() != n / 2 {
+                        out.push(Value::Int(n - n.signum()));
+                    }
+                }
+            }
+            (Type::Float, Value::Number(x)) => {
+                let x = *x;
+                if x != 0.0 {
+                    let mut push = |candidate: f64| {
+                        // Fewer decimals first, then a smaller magnitude,
+                        // then positive over negative.
+                        let simpler = (decimals(candidate), candidate.abs(), candidate < 0.0)
+                            < (decimals(x), x.abs(), x < 0.0);
+                        let fresh = !out
+                            .iter()
+                            .any(|v| matches!(v, Value::Number(y) if *y == candidate));
+                        if simpler && fresh && candidate.is_finite() {
+                            out.push(Value::Number(candidate));
+                        }
+                    };
+                    push(0.0);
+                    push(-x);
+                    if x.fract() == 0.0 {
+                        push((x / 2.0).trunc());
+                        push(x - x.signum());
+                    } else {
+                        push(x.trunc());
+                        push((x * 10.0).round() / 10.0);
+                        push((x * 100.0).round() / 100.0);
+                    }
+                }
+            }
+            (Type::Bool, Value::Bool(true)) => out.push(Value::Bool(false)),
+            (Type::String, Value::String(text)) => {
+                let chars: Vec<char> = text.chars().collect();
+                let string = |cs: &[char]| Value::String(Rc::from(cs.iter().collect::<String>()));
+                for part in halves(&chars) {
+                    out.push(string(&part));
+                }
+                for i in 0..chars.len().min(16) {
+                    let mut fewer = chars.clone();
+                    fewer.remove(i);
+                    out.push(string(&fewer));
+                }
+                for i in 0..chars.len().min(16) {
+                    if chars[i] != 'a' {
+                        let mut plainer = chars.clone();
+                        plainer[i] = 'a';
+                        out.push(string(&plainer));
+                    }
+                }
+            }
+            (Type::List(elem), Value::List(items)) | (Type::Array(elem), Value::Array(items)) => {
+                let items = items.to_vec();
+                for part in halves(&items) {
+                    out.push(sequence(ty, part));
+                }
+                for i in 0..items.len().min(16) {
+                    let mut fewer = items.clone();
+                    fewer.remove(i);
+                    out.push(sequence(ty, fewer));
+                }
+                for i in 0..items.len().min(16) {
+                    for simpler in self.shrink(elem, &items[i]) {
+                        let mut next = items.clone();
+                        next[i] = simpler;
+                        out.push(sequence(ty, next));
+                    }
+                }
+            }
+            (Type::Tuple(types), Value::Tuple(items)) => {
+                for (i, t) in types.iter().enumerate() {
+                    for simpler in self.shrink(t, &items[i]) {
+                        let mut next = items.as_ref().clone();
+                        next[i] = simpler;
+                        out.push(Value::Tuple(Rc::new(next)));
+                    }
+                }
+            }
+            (Type::Record(name, args), Value::Record(fields)) => {
+                let types = self.decls.record_fields(name, args).unwrap_or_default();
+                for (i, (field, value)) in fields.iter().enumerate() {
+                    let Some((_, t)) = types.iter().find(|(f, _)| f == field) else {
+                        continue;
+                    };
+                    for simpler in self.shrink(t, value) {
+                        let mut next = fields.as_ref().clone();
+                        next[i].1 = simpler;
+                        out.push(Value::Record(Rc::new(next)));
+                    }
+                }
+            }
+            (Type::Variant(name, args), Value::Variant { ctor, args: values }) => {
+                let ctors = self.decls.constructors(name, args).unwrap_or_default();
+                let Some((_, fields)) = ctors.iter().find(|(c, _)| c.as_str() == ctor.as_ref())
+                else {
+                    return out;
+                };
+                // A constructor with fewer fields, at its simplest…
+                for (other, other_fields) in &ctors {
+                    if other_fields.len() < fields.len() {
+                        let smallest = other_fields
+                            .iter()
+                            .map(|t| self.smallest(t, 0))
+                            .collect::<Result<Vec<_>, _>>();
+                        if let Ok(smallest) = smallest {
+                            out.push(Value::Variant {
+                                ctor: Rc::from(other.as_str()),
+                                args: Rc::new(smallest),
+                            });
+                        }
+                    }
+                }
+                // …a same-typed subterm (a subtree in place of its tree)…
+                for (t, value) in fields.iter().zip(values.iter()) {
+                    if t == ty {
+                        out.push(value.clone());
+                    }
+                }
+                // …or the same constructor with one field simpler.
+                for (i, t) in fields.iter().enumerate() {
+                    for simpler in self.shrink(t, &values[i]) {
+                        let mut next = values.as_ref().clone();
+                        next[i] = simpler;
+                        out.push(Value::Variant {
+                            ctor: ctor.clone(),
+                            args: Rc::new(next),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+        out
+    }
+}
+
+/// A `List` or an `Array` of `items`, whichever `ty` is.
+fn sequence(ty: &Type, items: Vec<Value>) -> Value {
+    match ty {
+        Type::Array(_) => Value::Array(List::from(items)),
+        _ => Value::List(List::from(items)),
+    }
+}
+
+/// The first and second halves of a sequence of two or more (plus the empty
+/// one, for any non-empty sequence).
+fn halves<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let mut out = vec![Vec::new()];
+    if items.len() > 1 {
+        let mid = items.len() / 2;
+        out.push(items[..mid].to_vec());
+        out.push(items[mid..].to_vec());
+    }
+    out
+}
+
+/// How many decimals `x` needs, up to two — 3 stands for "more".
+fn decimals(x: f64) -> u32 {
+    (0..3)
+        .find(|&d| {
+            let scale = 10f64.powi(d as i32);
+            (x * scale).round() / scale == x
+        })
+        .unwrap_or(3)
+}
+
+/// Whether `ty` nests the declared type `name` outside any sequence — a
+/// field that keeps the recursion going (a `List` of it can be empty).
+fn mentions(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Record(n, args) | Type::Variant(n, args) => {
+            n == name || args.iter().any(|a| mentions(a, name))
+        }
+        Type::Tuple(items) => items.iter().any(|t| mentions(t, name)),
+        _ => false,
+    }
+}
+
+fn unbuildable(ty: &Type) -> String {
+    format!("cannot generate a finite `{ty}` — every constructor recurses into it")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generators(src: &str) -> Generators {
+        let module = crate::lower(crate::parse(src).expect("parse")).expect("lower");
+        Generators::new(&module)
+    }
+
+    fn annotation(name: &str, args: Vec<TypeName>) -> TypeName {
+        TypeName {
+            name: name.to_string(),
+            args,
+            span: crate::Span { start: 0, end: 0 },
+        }
+    }
+
+    #[test]
+    fn one_seed_replays_one_run() {
+        let mut gens = generators("let x = 1");
+        let ty = gens
+            .domain(&annotation("List", vec![annotation("float", vec![])]))
+            .expect("generatable");
+        let run = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..20)
+                .map(|size| gens.generate(&ty, size, &mut rng).unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn recursive_variants_bottom_out_and_shrink_to_subtrees() {
+        let mut gens =
+            generators("type tree = | Leaf | Node(left: tree, value: int, right: tree)\nlet x = 1");
+        let ty = gens
+            .domain(&annotation("tree", vec![]))
+            .expect("generatable");
+        let mut rng = Rng::new(1);
+        for _ in 0..50 {
+            gens.generate(&ty, MAX_SIZE, &mut rng).expect("finite");
+        }
+        let leaf = Value::Variant {
+            ctor: Rc::from("Leaf"),
+            args: Rc::new(Vec::new()),
+        };
+        let node = Value::Variant {
+            ctor: Rc::from("Node"),
+            args: Rc::new(vec![leaf.clone(), Value::Int(3), leaf.clone()]),
+        };
+        let shrunk: Vec<String> = gens
+            .shrink(&ty, &node)
+            .iter()
+            .map(Value::to_string)
+            .collect();
+        assert_eq!(shrunk[0], "Leaf");
+        assert!(
+            shrunk.contains(&"Node(Leaf, 0, Leaf)".to_string()),
+            "{shrunk:?}"
+        );
+    }
+
+    #[test]
+    fn types_without_a_generator_say_why() {
+        let mut gens = generators("type t = | T(next: t)\nlet x = 1");
+        let err = gens
+            .domain(&annotation("List", vec![annotation("'a", vec![])]))
+            .err()
+            .expect("no generator");
+        assert!(err.contains("concrete types"), "{err}");
+        let err = gens
+            .domain(&annotation(
+                "Map",
+                vec![annotation("string", vec![]), annotation("int", vec![])],
+            ))
+            .err()
+            .expect("no generator");
+        assert!(err.contains("generate a `List`"), "{err}");
+        let ty = gens.domain(&annotation("t", vec![])).expect("declared");
+        let err = gens
+            .generate(&ty, 3, &mut Rng::new(0))
+            .err()
+            .expect("unbuildable");
+        assert!(err.contains("finite"), "{err}");
+    }
+}
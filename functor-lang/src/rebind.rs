@@ -45,7 +45,7 @@ use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::ir::{BindingId, Expr, ExprId, ExprKind, Module, Param, Pattern, PatternKind};
-use crate::value::{Closure, Env, Value};
+use crate::value::{Closure, Env, Map, Value};
 
 /// What a reload's rebind pass did — the producer prints this.
 #[derive(Debug, Default)]
@@ -111,18 +111,24 @@ pub fn rebind_value(value: &Value, old: &Module, new: &Module) -> (Value, Rebind
 fn walk(value: &Value, old: &ModuleIndex, new: &ModuleIndex, report: &mut RebindReport) -> Value {
     match value {
         Value::Number(_)
+        | Value::Int(_)
         | Value::String(_)
         | Value::Bool(_)
+        // Set elements are keys: scalars only.
+        | Value::Set(_)
         | Value::Builtin(_)
         | Value::HostFn(_)
         // Host values are opaque to the language; they cannot hold Functor Lang
         // closures.
         | Value::HostData(_) => value.clone(),
         Value::Ctor { name, arity } => rebind_ctor(name, *arity, old, new, report),
-        Value::List(items) => Value::List(Rc::new(
-            items.iter().map(|v| walk(v, old, new, report)).collect(),
-        )),
-        Value::Map(entries) => Value::Map(Rc::new(
+        Value::List(items) => {
+            Value::List(items.iter().map(|v| walk(v, old, new, report)).collect())
+        }
+        Value::Array(items) => {
+            Value::Array(items.iter().map(|v| walk(v, old, new, report)).collect())
+        }
+        Value::Map(entries) => Value::Map(Map::from_canonical(
             entries
                 .iter()
                 .map(|(key, value)| (key.clone(), walk(value, old, new, report)))
@@ -439,6 +445,9 @@ fn collect(expr: &Expr, def: &str, path: &mut Vec<String>, index: &mut ModuleInd
                 pattern_binders(&arm.pattern, &mut |binding, _| {
                     index.binder_kinds.insert(binding.0, BinderKind::PatternVar);
                 });
+                if let Some(guard) = &arm.guard {
+                    seg(guard, format!("|{i}when"), path, index);
+                }
                 seg(&arm.body, format!("|{i}"), path, index);
             }
         }
@@ -456,6 +465,7 @@ fn collect(expr: &Expr, def: &str, path: &mut Vec<String>, index: &mut ModuleInd
         ExprKind::Neg(inner) | ExprKind::Not(inner) => collect(inner, def, path, index),
         ExprKind::FieldAccess { object, .. } => collect(object, def, path, index),
         ExprKind::Number(_)
+        | ExprKind::Int(_)
         | ExprKind::String(_)
         | ExprKind::Bool(_)
         | ExprKind::Local { .. }
@@ -470,7 +480,7 @@ fn collect(expr: &Expr, def: &str, path: &mut Vec<String>, index: &mut ModuleInd
 /// `bound` (params of the lambda itself plus binders introduced inside).
 /// `LocalMut` cannot cross a lambda boundary (lowering rejects the capture),
 /// so only `Local` matters. First-use order, deduplicated by binding.
-fn free_vars(expr: &Expr, bound: &mut Vec<BindingId>, free: &mut Vec<(String, BindingId)>) {
+pub(crate) fn free_vars(expr: &Expr, bound: &mut Vec<BindingId>, free: &mut Vec<(String, BindingId)>) {
     match &expr.kind {
         ExprKind::Local { binding, name } => {
             if !bound.contains(binding) && !free.iter().any(|(_, b)| b == binding) {
@@ -494,6 +504,9 @@ fn free_vars(expr: &Expr, bound: &mut Vec<BindingId>, free: &mut Vec<(String, Bi
             for arm in arms {
                 let before = bound.len();
                 pattern_binders(&arm.pattern, &mut |binding, _| bound.push(binding));
+                if let Some(guard) = &arm.guard {
+                    free_vars(guard, bound, free);
+                }
                 free_vars(&arm.body, bound, free);
                 bound.truncate(before);
             }
@@ -510,7 +523,7 @@ fn free_vars(expr: &Expr, bound: &mut Vec<BindingId>, free: &mut Vec<(String, Bi
     }
 }
 
-fn pattern_binders(pattern: &Pattern, f: &mut impl FnMut(BindingId, &str)) {
+pub(crate) fn pattern_binders(pattern: &Pattern, f: &mut impl FnMut(BindingId, &str)) {
     match &pattern.kind {
         PatternKind::Var { binding, name } => f(*binding, name),
         PatternKind::Ctor { args, .. } | PatternKind::Tuple(args) => {
@@ -526,8 +539,16 @@ fn pattern_binders(pattern: &Pattern, f: &mut impl FnMut(BindingId, &str)) {
                 pattern_binders(tail, f);
             }
         }
+        PatternKind::Record(fields) => {
+            for field in fields {
+                pattern_binders(&field.pattern, f);
+            }
+        }
+        // Every alternative binds the same ids; the first is canonical.
+        PatternKind::Or(alternatives) => pattern_binders(&alternatives[0], f),
         PatternKind::Wildcard
         | PatternKind::Number(_)
+        | PatternKind::Int(_)
         | PatternKind::Bool(_)
         | PatternKind::String(_) => {}
     }
@@ -537,6 +558,7 @@ fn pattern_binders(pattern: &Pattern, f: &mut impl FnMut(BindingId, &str)) {
 pub(crate) fn each_child<'a>(expr: &'a Expr, f: &mut impl FnMut(&'a Expr)) {
     match &expr.kind {
         ExprKind::Number(_)
+        | ExprKind::Int(_)
         | ExprKind::String(_)
         | ExprKind::Bool(_)
         | ExprKind::Local { .. }
@@ -602,6 +624,9 @@ pub(crate) fn each_child<'a>(expr: &'a Expr, f: &mut impl FnMut(&'a Expr)) {
         ExprKind::Match { scrutinee, arms } => {
             f(scrutinee);
             for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    f(guard);
+                }
                 f(&arm.body);
             }
         }
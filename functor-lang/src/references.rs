@@ -0,0 +1,1206 @@
+//! Find-references and rename: every occurrence of the symbol at a byte
+//! offset across a whole project — the language-aware half of the LSP's
+//! `textDocument/references` and `textDocument/rename`. Where
+//! [`crate::goto`] answers "where is this defined", this answers "where is
+//! it used", and like it the editor server only converts positions and
+//! speaks the protocol.
+//!
+//! Resolution is over the name-resolved IR of a linked [`Project`], so
+//! shadowing and module qualification are already decided by lowering:
+//!
+//! - locals (parameters, `let` binders, pattern variables) by
+//!   [`BindingId`];
+//! - top-level lets, constructors, and types by canonical name
+//!   (`Utils.clamp`, `Utils.Red`, `Utils.Vec`);
+//! - record fields by their declaring record type plus field name — which
+//!   record a `p.x`, a literal, or a record pattern means comes from the
+//!   checker's types, so two records that both have an `x` stay apart;
+//! - modules, file (`Utils`) or inline (`Utils.Grid`), by full path: the
+//!   qualifier segments of references, `open` declarations, and the
+//!   `module Grid` header.
+//!
+//! IR spans cover whole written references (`Utils.clamp`, `m.pos.x`,
+//! `Some(x)`), so each occurrence is narrowed to the name itself by
+//! re-reading the source around the span.
+//!
+//! A rename edits every occurrence (a punned `{ score }` pattern expands to
+//! `{ points: score }`), renames the file when the symbol is a file
+//! module, then re-links the edited project and requires the renamed
+//! occurrences to resolve to exactly one symbol with no others — the check
+//! that refuses a new name which would shadow, or be captured by, another
+//! binding. Plain collisions (an existing def, constructor, field, type, or
+//! module of that name) are refused up front with a specific message.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::ast::{FieldTy, TypeBody, TypeName, VariantDecl};
+use crate::hover::children;
+use crate::ir::{BindingId, Expr, ExprKind, Pattern, PatternKind};
+use crate::project::{Project, SourceFile};
+use crate::span::Span;
+use crate::types::{ExprTypes, Type};
+
+/// What a name refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Symbol {
+    /// A lambda parameter, `let` binding, or pattern variable.
+    Local(BindingId),
+    /// A top-level `let`, by canonical name.
+    Global(String),
+    /// A variant constructor, by canonical name.
+    Ctor(String),
+    /// A declared `type`, by canonical name.
+    Type(String),
+    /// A field of a declared record type (`record` is its canonical name).
+    Field { record: String, field: String },
+    /// A file module (`Utils`) or inline `module` block (`Utils.Grid`; the
+    /// entry's are `Game.Server`), by full path.
+    Module(String),
+}
+
+/// One place a symbol's name is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    pub symbol: Symbol,
+    /// The name alone — never its qualifier, arguments, or value.
+    pub span: Span,
+    /// The defining occurrence (a binder, `let name`, `type Name`, a
+    /// variant, a field declaration, a `module Name` header).
+    pub declaration: bool,
+    pun: Pun,
+}
+
+/// How a punned record pattern (`{ score }` — one name that is both the
+/// field and the binder) rewrites when one of its halves is renamed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pun {
+    No,
+    /// The field half: `{ score }` → `{ points: score }`.
+    Field,
+    /// The binder half: `{ score }` → `{ score: points }`.
+    Binder,
+}
+
+/// One text replacement, in the project-wide span space.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub span: Span,
+    pub text: String,
+}
+
+/// A checked rename: the text edits, plus the file move when a file
+/// module was renamed (`utils.fun` → `helpers.fun`).
+#[derive(Debug)]
+pub struct Rename {
+    pub edits: Vec<Edit>,
+    pub moved: Option<(PathBuf, PathBuf)>,
+}
+
+/// The occurrence at `offset` — the narrowest containing it, so the field
+/// of `m.pos` wins over nothing and a qualifier over its reference. A
+/// cursor just past the name still counts.
+pub fn symbol_at(project: &Project, types: &ExprTypes, offset: usize) -> Option<Occurrence> {
+    occurrences(project, types)
+        .into_iter()
+        .filter(|occ| occ.span.start <= offset && offset <= occ.span.end)
+        .min_by_key(|occ| occ.span.end - occ.span.start)
+}
+
+/// Every occurrence of the symbol at `offset`, declarations included, in
+/// source order. Empty when there is no symbol there.
+pub fn references(project: &Project, types: &ExprTypes, offset: usize) -> Vec<Occurrence> {
+    let all = occurrences(project, types);
+    let Some(target) = all
+        .iter()
+        .filter(|occ| occ.span.start <= offset && offset <= occ.span.end)
+        .min_by_key(|occ| occ.span.end - occ.span.start)
+        .map(|occ| occ.symbol.clone())
+    else {
+        return Vec::new();
+    };
+    all.into_iter().filter(|occ| occ.symbol == target).collect()
+}
+
+/// The name span a rename at `offset` would start from, or why nothing
+/// there can be renamed (no symbol, or one the project does not declare).
+pub fn prepare_rename(project: &Project, types: &ExprTypes, offset: usize) -> Result<Span, String> {
+    let target = symbol_at(project, types, offset).ok_or("there is no symbol here to rename")?;
+    let all = references(project, types, offset);
+    renamable(project, &target.symbol, &all)?;
+    Ok(target.span)
+}
+
+/// Rename the symbol at `offset` to `new_name` across the project, or the
+/// reason it cannot be: an invalid name, a symbol the project does not
+/// declare, an existing name it would collide with, or a binding it would
+/// shadow or be captured by.
+pub fn rename(
+    project: &Project,
+    types: &ExprTypes,
+    offset: usize,
+    new_name: &str,
+) -> Result<Rename, String> {
+    let target = symbol_at(project, types, offset).ok_or("there is no symbol here to rename")?;
+    let all = references(project, types, offset);
+    renamable(project, &target.symbol, &all)?;
+    let old_name = text(project, target.span).to_string();
+    if new_name == old_name {
+        return Ok(Rename {
+            edits: Vec::new(),
+            moved: None,
+        });
+    }
+    valid_name(&target.symbol, new_name)?;
+    collisions(project, &target.symbol, new_name)?;
+
+    let mut edits: Vec<(Edit, usize)> = all
+        .iter()
+        .map(|occ| {
+            let (text, at) = match occ.pun {
+                Pun::No => (new_name.to_string(), 0),
+                Pun::Field => (format!("{new_name}: {old_name}"), 0),
+                Pun::Binder => (format!("{old_name}: {new_name}"), old_name.len() + 2),
+            };
+            (
+                Edit {
+                    span: occ.span,
+                    text,
+                },
+                at,
+            )
+        })
+        .collect();
+    edits.sort_by_key(|(edit, _)| edit.span.start);
+    edits.dedup_by_key(|(edit, _)| edit.span);
+
+    let moved = match &target.symbol {
+        Symbol::Module(path) => project
+            .sources
+            .files()
+            .iter()
+            .find(|file| file.module == *path)
+            .map(|file| (file.path.clone(), moved_path(file, new_name))),
+        _ => None,
+    };
+    verify(project, &edits, moved.as_ref(), new_name)?;
+    Ok(Rename {
+        edits: edits.into_iter().map(|(edit, _)| edit).collect(),
+        moved,
+    })
+}
+
+/// Refuse symbols the project cannot rename: anything declared by a
+/// bundled or prelude module (or not declared at all — a host record's
+/// field), and the entry module, whose name comes from the project's entry
+/// file.
+fn renamable(project: &Project, symbol: &Symbol, all: &[Occurrence]) -> Result<(), String> {
+    let bundled = |file: &SourceFile| file.path.to_string_lossy().starts_with('<');
+    if let Symbol::Module(path) = symbol {
+        if *path == project.entry {
+            return Err(format!(
+                "`{path}` is the entry module; its name comes from the entry file"
+            ));
+        }
+        let declared_in = match project.sources.files().iter().find(|f| f.module == *path) {
+            Some(file) => file,
+            None => match project
+                .inline_modules
+                .iter()
+                .find(|m| full_inline(m) == *path)
+            {
+                Some(module) => project.sources.file_at(module.span.start),
+                None => return Err(format!("`{path}` is not declared in this project")),
+            },
+        };
+        if bundled(declared_in) {
+            return Err(format!(
+                "`{path}` is a bundled module and cannot be renamed"
+            ));
+        }
+        return Ok(());
+    }
+    let Some(declaration) = all.iter().find(|occ| occ.declaration) else {
+        return Err("this symbol is not declared in the project".to_string());
+    };
+    if bundled(project.sources.file_at(declaration.span.start)) {
+        return Err("this symbol is declared by a bundled module and cannot be renamed".into());
+    }
+    Ok(())
+}
+
+/// The name must lex as one identifier of the right case for its kind:
+/// constructors, types, and modules are capitalized; values and fields are
+/// not (a capitalized local would read as a module qualifier).
+fn valid_name(symbol: &Symbol, name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !identifier || KEYWORDS.contains(&name) {
+        return Err(format!("`{name}` is not a valid identifier"));
+    }
+    let upper = name.starts_with(|c: char| c.is_ascii_uppercase());
+    match symbol {
+        Symbol::Ctor(_) | Symbol::Type(_) | Symbol::Module(_) if !upper => {
+            Err(format!("`{name}` must start with an uppercase letter"))
+        }
+        Symbol::Local(_) | Symbol::Global(_) | Symbol::Field { .. } if upper => {
+            Err(format!("`{name}` must start with a lowercase letter"))
+        }
+        _ => Ok(()),
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "let", "type", "true", "false", "mut", "with", "in", "match", "if", "then", "else", "not",
+];
+
+/// Primitive and builtin type names a declared type must not take.
+const BUILTIN_TYPES: &[&str] = &[
+    "int", "float", "string", "bool", "unknown", "List", "Array", "Map", "Set",
+];
+
+/// Name-level collisions, refused with a message that names the existing
+/// declaration.
+fn collisions(project: &Project, symbol: &Symbol, name: &str) -> Result<(), String> {
+    let module = &project.module;
+    match symbol {
+        Symbol::Global(canonical) => {
+            let renamed = sibling_name(canonical, name);
+            if module.defs.iter().any(|def| def.name == renamed) {
+                return Err(format!("`{renamed}` is already defined"));
+            }
+        }
+        Symbol::Ctor(_) => {
+            let existing = module.types.iter().find_map(|ty| match &ty.body {
+                TypeBody::Variants(variants) => variants
+                    .iter()
+                    .find(|variant| simple(&variant.name) == name),
+                _ => None,
+            });
+            if let Some(existing) = existing {
+                return Err(format!("a constructor `{}` already exists", existing.name));
+            }
+        }
+        Symbol::Type(canonical) => {
+            let renamed = sibling_name(canonical, name);
+            if BUILTIN_TYPES.contains(&name) || module.types.iter().any(|ty| ty.name == renamed) {
+                return Err(format!("a type `{renamed}` already exists"));
+            }
+        }
+        Symbol::Field { record, .. } => {
+            let fields = module
+                .types
+                .iter()
+                .find(|ty| ty.name == *record)
+                .and_then(|ty| match &ty.body {
+                    TypeBody::Record(fields) => Some(fields),
+                    _ => None,
+                });
+            if fields.is_some_and(|fields| fields.iter().any(|f| f.name == name)) {
+                return Err(format!("`{record}` already has a field `{name}`"));
+            }
+        }
+        Symbol::Module(path) => {
+            let renamed = sibling_name(path, name);
+            let taken = module_paths(project).contains(&renamed)
+                || project.sources.files().iter().any(|f| f.module == name)
+                || project.inline_modules.iter().any(|m| m.name == name);
+            if taken {
+                return Err(format!("a module `{name}` already exists"));
+            }
+            if crate::project::is_protected_namespace(name) {
+                return Err(format!("`{name}` is a built-in namespace"));
+            }
+        }
+        Symbol::Local(_) => {}
+    }
+    Ok(())
+}
+
+/// Apply the edits to a copy of the project, re-link it, and require that
+/// the renamed occurrences — and only they — resolve to one symbol. A name
+/// already in scope at a use site would take over that use (or the renamed
+/// binder would take over another's), which shows up here as a mismatch.
+fn verify(
+    project: &Project,
+    edits: &[(Edit, usize)],
+    moved: Option<&(PathBuf, PathBuf)>,
+    name: &str,
+) -> Result<(), String> {
+    let files = project.sources.files();
+    let owner = |edit: &Edit| {
+        files
+            .iter()
+            .rposition(|file| file.base <= edit.span.start)
+            .unwrap_or(0)
+    };
+    let relinked = project
+        .relink(|index, file| {
+            let mut src = file.src.clone();
+            for (edit, _) in edits.iter().rev().filter(|(edit, _)| owner(edit) == index) {
+                let start = edit.span.start - file.base;
+                src.replace_range(start..edit.span.end - file.base, &edit.text);
+            }
+            match moved {
+                Some((from, to)) if *from == file.path => (to.clone(), name.to_string(), src),
+                _ => (file.path.clone(), file.module.clone(), src),
+            }
+        })
+        .map_err(|e| {
+            format!(
+                "renaming to `{name}` would break the project: {}:{}:{}: {}",
+                e.path.display(),
+                e.line,
+                e.col,
+                e.message
+            )
+        })?;
+
+    // Where each renamed name lands in the edited project.
+    let mut expected: Vec<Span> = Vec::new();
+    let mut shift: HashMap<usize, isize> = HashMap::new();
+    for (edit, at) in edits {
+        let index = owner(edit);
+        let delta = shift.entry(index).or_insert(0);
+        let base = relinked.sources.files()[index].base;
+        let local = (edit.span.start - files[index].base) as isize + *delta;
+        let start = base + local as usize + at;
+        expected.push(Span::new(start, start + name.len()));
+        *delta += edit.text.len() as isize - (edit.span.end - edit.span.start) as isize;
+    }
+    let Some(first) = expected.first() else {
+        return Ok(());
+    };
+
+    // A use the walk could not attribute (a field on a value the checker
+    // could not type) would be left behind under the old name; the edited
+    // program then fails to check where the original did not.
+    let (errors, types) = relinked.check_with_types();
+    let before = project.check();
+    if errors.len() > before.len() {
+        let error = errors
+            .iter()
+            .find(|error| before.iter().all(|old| old.message != error.message))
+            .unwrap_or(&errors[0]);
+        return Err(format!(
+            "renaming to `{name}` would break the project: {}",
+            relinked.sources.render(error.span.start, &error.message)
+        ));
+    }
+    let found: HashSet<(usize, usize)> = references(&relinked, &types, first.start)
+        .iter()
+        .map(|occ| (occ.span.start, occ.span.end))
+        .collect();
+    let wanted: HashSet<(usize, usize)> = expected.iter().map(|s| (s.start, s.end)).collect();
+    if let Some(&(start, _)) = found.symmetric_difference(&wanted).min() {
+        return Err(format!(
+            "renaming to `{name}` would shadow or collide with another `{name}` ({})",
+            relinked.sources.render(start, "here")
+        ));
+    }
+    Ok(())
+}
+
+/// A file module's new path: the same directory and extension, with the
+/// stem's first letter cased as before (`utils.fun` → `helpers.fun`).
+fn moved_path(file: &SourceFile, name: &str) -> PathBuf {
+    let lower = file
+        .path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.starts_with(|c: char| c.is_ascii_lowercase()));
+    let stem = if lower {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .map(|c| c.to_ascii_lowercase().to_string() + chars.as_str())
+            .unwrap_or_default()
+    } else {
+        name.to_string()
+    };
+    let extension = if file.interface { "funi" } else { "fun" };
+    file.path.with_file_name(format!("{stem}.{extension}"))
+}
+
+/// Every occurrence of every symbol in the project, in source order.
+fn occurrences(project: &Project, types: &ExprTypes) -> Vec<Occurrence> {
+    let mut walk = Walk::new(project, types);
+    let module = &project.module;
+    for ty in &module.types {
+        walk.declare(ty.span, simple(&ty.name), Symbol::Type(ty.name.clone()));
+        match &ty.body {
+            TypeBody::Record(fields) => {
+                for field in fields {
+                    let symbol = Symbol::Field {
+                        record: ty.name.clone(),
+                        field: field.name.clone(),
+                    };
+                    walk.declare(field.span, &field.name, symbol);
+                    walk.type_name(&field.ty);
+                }
+            }
+            TypeBody::Variants(variants) => {
+                for variant in variants {
+                    let symbol = Symbol::Ctor(variant.name.clone());
+                    walk.declare(variant.span, simple(&variant.name), symbol);
+                    for field in &variant.fields {
+                        walk.type_name(&field.ty);
+                    }
+                }
+            }
+            TypeBody::Abstract | TypeBody::Host => {}
+        }
+    }
+    for def in &module.defs {
+        let binder = Span::new(def.span.start, def.value.span.start);
+        walk.declare(binder, simple(&def.name), Symbol::Global(def.name.clone()));
+        if let Some(ty) = &def.ty {
+            walk.type_name(ty);
+        }
+        walk.expr(&def.value);
+    }
+    for sig in &module.signatures {
+        walk.type_name(&sig.ty);
+    }
+    for expect in &module.expects {
+        walk.expr(&expect.expr);
+    }
+    for unit in &module.units {
+        walk.expr(&unit.target);
+    }
+    for op in &module.unit_ops {
+        walk.expr(&op.target);
+    }
+    for inline in &project.inline_modules {
+        walk.declare(
+            inline.span,
+            &inline.name,
+            Symbol::Module(full_inline(inline)),
+        );
+    }
+    for file in project.sources.files() {
+        walk.opens(file);
+    }
+
+    let mut out = walk.out;
+    out.sort_by_key(|occ| (occ.span.start, occ.span.end));
+    out.dedup();
+    out
+}
+
+/// The IR walk collecting occurrences, with the lookups it needs to tell
+/// which record a field belongs to.
+struct Walk<'a> {
+    project: &'a Project,
+    types: &'a ExprTypes,
+    records: HashMap<&'a str, &'a [FieldTy]>,
+    variants: HashMap<&'a str, &'a VariantDecl>,
+    declared_types: HashSet<&'a str>,
+    modules: HashSet<String>,
+    out: Vec<Occurrence>,
+}
+
+/// What a pattern is matched against: the checker's type at the top, and
+/// the declared field types below it.
+#[derive(Clone, Copy)]
+enum Shape<'a> {
+    Unknown,
+    Checked(&'a Type),
+    Written(&'a TypeName),
+}
+
+impl<'a> Walk<'a> {
+    fn new(project: &'a Project, types: &'a ExprTypes) -> Self {
+        let mut records = HashMap::new();
+        let mut variants = HashMap::new();
+        for ty in &project.module.types {
+            match &ty.body {
+                TypeBody::Record(fields) => {
+                    records.insert(ty.name.as_str(), fields.as_slice());
+                }
+                TypeBody::Variants(decls) => {
+                    for decl in decls {
+                        variants.insert(decl.name.as_str(), decl);
+                    }
+                }
+                TypeBody::Abstract | TypeBody::Host => {}
+            }
+        }
+        Walk {
+            project,
+            types,
+            records,
+            variants,
+            declared_types: project
+                .module
+                .types
+                .iter()
+                .map(|t| t.name.as_str())
+                .collect(),
+            modules: module_paths(project),
+            out: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, symbol: Symbol, span: Span, declaration: bool, pun: Pun) {
+        self.out.push(Occurrence {
+            symbol,
+            span,
+            declaration,
+            pun,
+        });
+    }
+
+    /// A declaration: the first whole-word `name` in its span (after the
+    /// `let`/`type`/`module` keyword, before any value).
+    fn declare(&mut self, span: Span, name: &str, symbol: Symbol) {
+        if let Some(at) = word_in(self.project, span, name) {
+            self.push(symbol, at, true, Pun::No);
+        }
+    }
+
+    /// A reference to a canonical `name` written at `span`, possibly
+    /// qualified: the name segment is the symbol, and each qualifier
+    /// segment before it is a module reference.
+    fn qualified(&mut self, span: Span, canonical: &str, symbol: Symbol) {
+        let path = full_path(self.project, canonical);
+        let (owner, name) = path.split_at(path.len() - 1);
+        let segments = segments(self.project, span.start);
+        for (k, segment) in segments.iter().enumerate().take(owner.len() + 1) {
+            let qualifiers = &owner[owner.len() - k..];
+            let written = segments[..k].iter().map(|s| text(self.project, *s));
+            if text(self.project, *segment) == name[0]
+                && written.eq(qualifiers.iter().map(String::as_str))
+            {
+                for (j, qualifier) in segments[..k].iter().enumerate() {
+                    let module = owner[..owner.len() - k + j + 1].join(".");
+                    if self.modules.contains(&module) {
+                        self.push(Symbol::Module(module), *qualifier, false, Pun::No);
+                    }
+                }
+                self.push(symbol, *segment, false, Pun::No);
+                return;
+            }
+        }
+    }
+
+    fn type_name(&mut self, ty: &TypeName) {
+        if self.declared_types.contains(ty.name.as_str()) {
+            self.qualified(ty.span, &ty.name, Symbol::Type(ty.name.clone()));
+        }
+        for arg in &ty.args {
+            self.type_name(arg);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Local { binding, name } | ExprKind::LocalMut { binding, name } => {
+                if let Some(at) = word_in(self.project, expr.span, name) {
+                    self.push(Symbol::Local(*binding), at, false, Pun::No);
+                }
+            }
+            ExprKind::Assign { binding, name, .. } => {
+                if let Some(at) = word_in(self.project, expr.span, name) {
+                    self.push(Symbol::Local(*binding), at, false, Pun::No);
+                }
+                self.children(expr);
+            }
+            ExprKind::Global(name) => self.qualified(expr.span, name, Symbol::Global(name.clone())),
+            ExprKind::Ctor { name, .. } => {
+                self.qualified(expr.span, name, Symbol::Ctor(name.clone()))
+            }
+            ExprKind::FieldAccess { object, field } => {
+                if let Some(record) = record_name(self.types.expr(object.id)) {
+                    if let Some(at) = self.field_segment(expr, object, field) {
+                        let symbol = Symbol::Field {
+                            record,
+                            field: field.clone(),
+                        };
+                        self.push(symbol, at, false, Pun::No);
+                    }
+                }
+                self.expr(object);
+            }
+            ExprKind::Record(fields) | ExprKind::RecordUpdate { fields, .. } => {
+                if let Some(record) = record_name(self.types.expr(expr.id)) {
+                    for field in fields {
+                        if let Some(at) = word_in(self.project, field.span, &field.name) {
+                            let symbol = Symbol::Field {
+                                record: record.clone(),
+                                field: field.name.clone(),
+                            };
+                            self.push(symbol, at, false, Pun::No);
+                        }
+                    }
+                }
+                self.children(expr);
+            }
+            ExprKind::Lambda { params, ret, body } => {
+                for param in params.iter() {
+                    if let Some(at) = word_in(self.project, param.span, &param.name) {
+                        self.push(Symbol::Local(param.binding), at, true, Pun::No);
+                    }
+                    if let Some(ty) = &param.ty {
+                        self.type_name(ty);
+                    }
+                }
+                if let Some(ret) = ret {
+                    self.type_name(ret);
+                }
+                self.expr(body);
+            }
+            ExprKind::Let {
+                binding,
+                name,
+                ty,
+                value,
+                ..
+            } => {
+                let binder = Span::new(expr.span.start, value.span.start.max(expr.span.start));
+                if let Some(at) = word_in(self.project, binder, name) {
+                    self.push(Symbol::Local(*binding), at, true, Pun::No);
+                }
+                if let Some(ty) = ty {
+                    self.type_name(ty);
+                }
+                self.children(expr);
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.expr(scrutinee);
+                let shape = self
+                    .types
+                    .expr(scrutinee.id)
+                    .map_or(Shape::Unknown, Shape::Checked);
+                for arm in arms {
+                    self.pattern(&arm.pattern, shape);
+                    if let Some(guard) = &arm.guard {
+                        self.expr(guard);
+                    }
+                    self.expr(&arm.body);
+                }
+            }
+            _ => self.children(expr),
+        }
+    }
+
+    fn children(&mut self, expr: &Expr) {
+        for child in children(expr) {
+            self.expr(child);
+        }
+    }
+
+    /// Where a field access writes its field: the segment after the
+    /// object, or — when a dotted chain shares one span (`Foo.x.y` on an
+    /// uppercase binding, see `lower`'s `ident`) — the segment at this
+    /// access's depth in the chain.
+    fn field_segment(&self, expr: &Expr, object: &Expr, field: &str) -> Option<Span> {
+        let at = if object.span.end < expr.span.end {
+            let file = self.project.sources.file_at(object.span.start);
+            let rest = &file.src[object.span.end - file.base..expr.span.end - file.base];
+            let skipped = rest.len() - rest.trim_start_matches([' ', '\t', '\r', '\n', '.']).len();
+            segments(self.project, object.span.end + skipped)
+                .first()
+                .copied()?
+        } else {
+            let depth = self.chain_depth(expr)?;
+            *segments(self.project, expr.span.start).get(depth)?
+        };
+        (text(self.project, at) == field).then_some(at)
+    }
+
+    /// The index of the last chain segment a node covers (see
+    /// [`Walk::field_segment`]).
+    fn chain_depth(&self, expr: &Expr) -> Option<usize> {
+        match &expr.kind {
+            ExprKind::Local { .. } | ExprKind::LocalMut { .. } => Some(0),
+            ExprKind::Global(name) => {
+                let segments = segments(self.project, expr.span.start);
+                segments
+                    .iter()
+                    .position(|segment| text(self.project, *segment) == simple(name))
+            }
+            ExprKind::FieldAccess { object, .. } => Some(self.chain_depth(object)? + 1),
+            _ => None,
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern, shape: Shape<'a>) {
+        match &pattern.kind {
+            PatternKind::Var { binding, name } => {
+                if let Some(at) = word_in(self.project, pattern.span, name) {
+                    self.push(Symbol::Local(*binding), at, true, Pun::No);
+                }
+            }
+            PatternKind::Ctor { name, args } => {
+                self.qualified(pattern.span, name, Symbol::Ctor(name.clone()));
+                let fields = self.variants.get(name.as_str()).map(|decl| &decl.fields);
+                for (i, arg) in args.iter().enumerate() {
+                    let shape = fields
+                        .and_then(|fields| fields.get(i))
+                        .map_or(Shape::Unknown, |field| Shape::Written(&field.ty));
+                    self.pattern(arg, shape);
+                }
+            }
+            PatternKind::Tuple(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    let shape = match shape {
+                        Shape::Checked(Type::Tuple(types)) => {
+                            types.get(i).map_or(Shape::Unknown, Shape::Checked)
+                        }
+                        Shape::Written(ty) if ty.name == "*" => {
+                            ty.args.get(i).map_or(Shape::Unknown, Shape::Written)
+                        }
+                        _ => Shape::Unknown,
+                    };
+                    self.pattern(item, shape);
+                }
+            }
+            PatternKind::List { items, tail } => {
+                let item_shape = match shape {
+                    Shape::Checked(Type::List(item)) => Shape::Checked(item),
+                    Shape::Written(ty) if ty.name == "List" => {
+                        ty.args.first().map_or(Shape::Unknown, Shape::Written)
+                    }
+                    _ => Shape::Unknown,
+                };
+                for item in items {
+                    self.pattern(item, item_shape);
+                }
+                if let Some(tail) = tail {
+                    self.pattern(tail, shape);
+                }
+            }
+            PatternKind::Or(alternatives) => {
+                for alternative in alternatives {
+                    self.pattern(alternative, shape);
+                }
+            }
+            PatternKind::Record(fields) => {
+                let record = match shape {
+                    Shape::Checked(ty) => record_name(Some(ty)),
+                    Shape::Written(ty) => Some(ty.name.clone()),
+                    Shape::Unknown => None,
+                };
+                let declared = record.as_deref().and_then(|r| self.records.get(r).copied());
+                for field in fields {
+                    let symbol = record.clone().map(|record| Symbol::Field {
+                        record,
+                        field: field.name.clone(),
+                    });
+                    let punned = field.span == field.pattern.span;
+                    if let (true, PatternKind::Var { binding, .. }) = (punned, &field.pattern.kind)
+                    {
+                        if let Some(at) = word_in(self.project, field.span, &field.name) {
+                            self.push(Symbol::Local(*binding), at, true, Pun::Binder);
+                            if let Some(symbol) = symbol {
+                                self.push(symbol, at, false, Pun::Field);
+                            }
+                        }
+                        continue;
+                    }
+                    if let (Some(symbol), Some(at)) =
+                        (symbol, word_in(self.project, field.span, &field.name))
+                    {
+                        self.push(symbol, at, false, Pun::No);
+                    }
+                    let shape = declared
+                        .and_then(|fields| fields.iter().find(|f| f.name == field.name))
+                        .map_or(Shape::Unknown, |f| Shape::Written(&f.ty));
+                    self.pattern(&field.pattern, shape);
+                }
+            }
+            PatternKind::Wildcard
+            | PatternKind::Number(_)
+            | PatternKind::Int(_)
+            | PatternKind::Bool(_)
+            | PatternKind::String(_) => {}
+        }
+    }
+
+    /// `open Utils` / `open Utils.Grid` / `open Grid` (one of the file's
+    /// own inline modules): each written segment names a module.
+    fn opens(&mut self, file: &SourceFile) {
+        let parse = if file.interface {
+            crate::parser::parse_interface_with_base
+        } else {
+            crate::parser::parse_with_base
+        };
+        let Ok(program) = parse(&file.src, file.base) else {
+            return;
+        };
+        let mut items: Vec<&crate::ast::Item> = program.items.iter().collect();
+        while let Some(item) = items.pop() {
+            let decl = match item {
+                crate::ast::Item::Open(decl) => decl,
+                crate::ast::Item::Module(module) => {
+                    items.extend(module.items.iter());
+                    continue;
+                }
+                _ => continue,
+            };
+            let first = decl.module.split('.').next().unwrap_or_default();
+            let Some(start) = word_in(self.project, decl.span, first) else {
+                continue;
+            };
+            let relative = format!("{}.{}", file.module, decl.module);
+            let path = if self.modules.contains(&relative) {
+                relative
+            } else {
+                decl.module.clone()
+            };
+            let path: Vec<&str> = path.split('.').collect();
+            let written = segments(self.project, start.start);
+            let skip = path.len().saturating_sub(written.len());
+            for (j, segment) in written.iter().enumerate().take(path.len()) {
+                let module = path[..skip + j + 1].join(".");
+                if self.modules.contains(&module) {
+                    self.push(Symbol::Module(module), *segment, false, Pun::No);
+                }
+            }
+        }
+    }
+}
+
+/// The record type a checked type names, if it is one.
+fn record_name(ty: Option<&Type>) -> Option<String> {
+    match ty? {
+        Type::Record(name, _) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+/// The last segment of a canonical name.
+fn simple(canonical: &str) -> &str {
+    canonical.rsplit('.').next().unwrap_or(canonical)
+}
+
+/// `canonical` with its last segment replaced by `name`.
+fn sibling_name(canonical: &str, name: &str) -> String {
+    match canonical.rsplit_once('.') {
+        Some((prefix, _)) => format!("{prefix}.{name}"),
+        None => name.to_string(),
+    }
+}
+
+/// An inline module's full path: its file module, then its own name —
+/// `Game.Server` even in the entry, whose members canonicalize bare.
+fn full_inline(module: &crate::project::InlineModule) -> String {
+    format!("{}.{}", module.file, module.name)
+}
+
+/// Every module path in the project: the file modules and the inline
+/// blocks (see [`full_inline`]).
+fn module_paths(project: &Project) -> HashSet<String> {
+    project
+        .sources
+        .files()
+        .iter()
+        .map(|file| file.module.clone())
+        .chain(project.inline_modules.iter().map(full_inline))
+        .collect()
+}
+
+/// A canonical name as full path segments: the entry's bare names (and
+/// its inline modules' `Server.step`) gain the entry module in front, so
+/// every path starts at a file module.
+fn full_path(project: &Project, canonical: &str) -> Vec<String> {
+    let parts: Vec<String> = canonical.split('.').map(str::to_string).collect();
+    let filed = parts.len() > 1
+        && project
+            .sources
+            .files()
+            .iter()
+            .any(|file| file.module == parts[0] && file.module != project.entry);
+    if filed {
+        parts
+    } else {
+        std::iter::once(project.entry.clone())
+            .chain(parts)
+            .collect()
+    }
+}
+
+fn text(project: &Project, span: Span) -> &str {
+    let file = project.sources.file_at(span.start);
+    &file.src[span.start - file.base..span.end - file.base]
+}
+
+fn is_ident(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+/// The dotted identifier chain written at `start` (`Utils.Grid.cell` →
+/// three segments); empty when no identifier starts there.
+fn segments(project: &Project, start: usize) -> Vec<Span> {
+    let file = project.sources.file_at(start);
+    let bytes = file.src.as_bytes();
+    let mut at = start - file.base;
+    let mut out = Vec::new();
+    while bytes
+        .get(at)
+        .is_some_and(|c| c.is_ascii_alphabetic() || *c == b'_')
+    {
+        let begin = at;
+        while bytes.get(at).copied().is_some_and(is_ident) {
+            at += 1;
+        }
+        out.push(Span::new(file.base + begin, file.base + at));
+        if bytes.get(at) != Some(&b'.') {
+            break;
+        }
+        at += 1;
+    }
+    out
+}
+
+/// The first whole-word `name` inside `span`.
+fn word_in(project: &Project, span: Span, name: &str) -> Option<Span> {
+    let file = project.sources.file_at(span.start);
+    let (from, to) = (
+        span.start - file.base,
+        (span.end - file.base).min(file.src.len()),
+    );
+    let bytes = file.src.as_bytes();
+    let mut search = from;
+    while let Some(found) = file.src.get(search..to)?.find(name) {
+        let begin = search + found;
+        let end = begin + name.len();
+        let before = begin > 0 && is_ident(bytes[begin - 1]);
+        let after = bytes.get(end).copied().is_some_and(is_ident);
+        if !before && !after {
+            return Some(Span::new(file.base + begin, file.base + end));
+        }
+        search = end;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(files: &[(&str, &str)]) -> Project {
+        let sources = files
+            .iter()
+            .map(|(path, src)| (PathBuf::from(path), src.to_string()))
+            .collect();
+        crate::project::load_sources_with_prelude(sources, &[])
+            .unwrap_or_else(|e| panic!("{}", e.message))
+    }
+
+    /// The project-wide offset of the `nth` (0-based) `needle` in `path`.
+    fn at(project: &Project, path: &str, needle: &str, nth: usize) -> usize {
+        let file = project
+            .sources
+            .files()
+            .iter()
+            .find(|f| f.path == std::path::Path::new(path))
+            .expect("file");
+        let local = file
+            .src
+            .match_indices(needle)
+            .nth(nth)
+            .map(|(i, _)| i)
+            .expect("needle present");
+        file.base + local
+    }
+
+    /// Each reference as `path:line:col`, in source order.
+    fn refs(project: &Project, offset: usize) -> Vec<String> {
+        let (_, types) = project.check_with_types();
+        references(project, &types, offset)
+            .iter()
+            .map(|occ| {
+                let (file, line, col) = project.sources.resolve(occ.span.start);
+                assert_eq!(text(project, occ.span).len(), occ.span.end - occ.span.start);
+                format!("{}:{line}:{col}", file.path.display())
+            })
+            .collect()
+    }
+
+    /// Apply a rename, returning each edited project file's new text.
+    fn renamed(project: &Project, offset: usize, name: &str) -> Result<Vec<String>, String> {
+        let (_, types) = project.check_with_types();
+        let rename = rename(project, &types, offset, name)?;
+        let mut out = Vec::new();
+        for file in project.sources.files() {
+            if file.path.to_string_lossy().starts_with('<') {
+                continue;
+            }
+            let mut src = file.src.clone();
+            for edit in rename.edits.iter().rev() {
+                if file.base <= edit.span.start && edit.span.start <= file.base + file.src.len() {
+                    let range = edit.span.start - file.base..edit.span.end - file.base;
+                    src.replace_range(range, &edit.text);
+                }
+            }
+            out.push(src);
+        }
+        Ok(out)
+    }
+
+    const GAME: &str = "open Utils\n\
+type Model = { score: int, pos: Utils.Vec }\n\
+let init: Model = { score: 0, pos: { x: 1.0, y: 2.0 } }\n\
+let f = (m: Model) => m.pos.x + Utils.clamp(1.0) + clamp(2.0)\n\
+let g = (m: Model) => match m with | { score } => score\n\
+let h = Utils.Grid.cell\n";
+    const UTILS: &str = "type Vec = { x: float, y: float }\n\
+type Color = | Red | Green\n\
+let clamp = (v) => v\n\
+let red = Red\n\
+module Grid {\n  let cell = 1.0\n  let twice = cell + cell\n}\n";
+
+    fn sample() -> Project {
+        project(&[("game.fun", GAME), ("utils.fun", UTILS)])
+    }
+
+    #[test]
+    fn a_top_level_let_is_found_qualified_opened_and_declared() {
+        let p = sample();
+        let offset = at(&p, "game.fun", "clamp(2.0)", 0);
+        assert_eq!(
+            refs(&p, offset),
+            ["game.fun:4:39", "game.fun:4:52", "utils.fun:3:5"]
+        );
+    }
+
+    #[test]
+    fn a_field_is_found_through_accesses_literals_and_patterns() {
+        let p = sample();
+        let offset = at(&p, "utils.fun", "x: float", 0);
+        assert_eq!(
+            refs(&p, offset),
+            ["game.fun:3:38", "game.fun:4:29", "utils.fun:1:14"]
+        );
+        // `score` the field, not the pattern's binder of the same name.
+        let offset = at(&p, "game.fun", "score: int", 0);
+        assert_eq!(
+            refs(&p, offset),
+            ["game.fun:2:16", "game.fun:3:21", "game.fun:5:40"]
+        );
+    }
+
+    #[test]
+    fn a_module_is_found_in_qualifiers_and_opens() {
+        let p = sample();
+        let offset = at(&p, "game.fun", "Utils", 0);
+        assert_eq!(
+            refs(&p, offset),
+            [
+                "game.fun:1:6",
+                "game.fun:2:33",
+                "game.fun:4:33",
+                "game.fun:6:9"
+            ]
+        );
+        let offset = at(&p, "game.fun", "Grid", 0);
+        assert_eq!(refs(&p, offset), ["game.fun:6:15", "utils.fun:5:8"]);
+    }
+
+    #[test]
+    fn renaming_a_field_rewrites_puns() {
+        let p = sample();
+        let offset = at(&p, "game.fun", "score", 0);
+        let files = renamed(&p, offset, "points").unwrap();
+        assert!(
+            files[0].contains("type Model = { points: int"),
+            "{}",
+            files[0]
+        );
+        assert!(files[0].contains("{ points: 0,"), "{}", files[0]);
+        assert!(
+            files[0].contains("| { points: score } => score"),
+            "{}",
+            files[0]
+        );
+    }
+
+    #[test]
+    fn renaming_a_punned_binder_keeps_the_field() {
+        let p = sample();
+        let offset = at(&p, "game.fun", "score }", 0);
+        let files = renamed(&p, offset, "s").unwrap();
+        assert!(files[0].contains("| { score: s } => s"), "{}", files[0]);
+    }
+
+    #[test]
+    fn renaming_a_constructor_and_a_module() {
+        let p = sample();
+        let files = renamed(&p, at(&p, "utils.fun", "Red", 0), "Crimson").unwrap();
+        assert!(files[1].contains("| Crimson | Green"), "{}", files[1]);
+        assert!(files[1].contains("let red = Crimson"), "{}", files[1]);
+
+        let files = renamed(&p, at(&p, "game.fun", "Grid", 0), "Board").unwrap();
+        assert!(files[0].contains("Utils.Board.cell"), "{}", files[0]);
+        assert!(files[1].contains("module Board {"), "{}", files[1]);
+    }
+
+    #[test]
+    fn renaming_a_file_module_moves_the_file() {
+        let p = sample();
+        let (_, types) = p.check_with_types();
+        let offset = at(&p, "game.fun", "Utils", 0);
+        let rename = rename(&p, &types, offset, "Helpers").unwrap();
+        let moved = rename.moved.expect("file move");
+        assert_eq!(
+            moved,
+            (PathBuf::from("utils.fun"), PathBuf::from("helpers.fun"))
+        );
+        assert_eq!(rename.edits.len(), 4);
+        assert!(rename.edits.iter().all(|edit| edit.text == "Helpers"));
+    }
+
+    #[test]
+    fn a_rename_that_collides_is_refused() {
+        let p = sample();
+        let clamp = at(&p, "utils.fun", "clamp", 0);
+        let err = renamed(&p, clamp, "red").unwrap_err();
+        assert!(err.contains("`Utils.red` is already defined"), "{err}");
+        let err = renamed(&p, at(&p, "utils.fun", "Red", 0), "Green").unwrap_err();
+        assert!(
+            err.contains("constructor `Utils.Green` already exists"),
+            "{err}"
+        );
+        let err = renamed(&p, at(&p, "utils.fun", "x: float", 0), "y").unwrap_err();
+        assert!(err.contains("`Utils.Vec` already has a field `y`"), "{err}");
+        let err = renamed(&p, at(&p, "game.fun", "Grid", 0), "Scene").unwrap_err();
+        assert!(err.contains("built-in namespace"), "{err}");
+        let err = renamed(&p, clamp, "Clamp").unwrap_err();
+        assert!(err.contains("lowercase"), "{err}");
+    }
+
+    #[test]
+    fn a_rename_that_would_shadow_or_capture_is_refused() {
+        let p = project(&[(
+            "game.fun",
+            "let scale = 2.0\nlet f = (x) => let y = x in y * scale\n",
+        )]);
+        // `scale` used where a local `y` is in scope: renaming it to `y`
+        // would make that use read the local instead.
+        let err = renamed(&p, at(&p, "game.fun", "scale", 0), "y").unwrap_err();
+        assert!(err.contains("would shadow or collide"), "{err}");
+        // Renaming the parameter to `scale` would capture the global use.
+        let err = renamed(&p, at(&p, "game.fun", "x)", 0), "scale").unwrap_err();
+        assert!(err.contains("would shadow or collide"), "{err}");
+        // A fresh name is fine.
+        let files = renamed(&p, at(&p, "game.fun", "x)", 0), "input").unwrap();
+        assert_eq!(
+            files[0],
+            "let scale = 2.0\nlet f = (input) => let y = input in y * scale\n"
+        );
+    }
+
+    #[test]
+    fn bundled_symbols_are_not_renamable() {
+        let p = project(&[("game.fun", "let o = Option.map((x) => x, Option.Some(1))\n")]);
+        let err = renamed(&p, at(&p, "game.fun", "map", 0), "apply").unwrap_err();
+        assert!(err.contains("bundled"), "{err}");
+        let err = renamed(&p, at(&p, "game.fun", "Some", 0), "Just").unwrap_err();
+        assert!(err.contains("bundled"), "{err}");
+    }
+}
@@ -0,0 +1,692 @@
+//! An interactive read–eval–print loop over a loaded project — the core
+//! behind `functor-lang repl <entry.fun>` and `functor repl`.
+//!
+//! The project loads exactly the way `run` loads it (the entry plus every
+//! sibling, plus whatever modules the embedding bundles), and each input is
+//! evaluated IN the entry module, so the entry's top-level defs and every
+//! sibling module are in scope unqualified / qualified exactly as they are to
+//! the entry's own code:
+//!
+//! - an expression prints its value and inferred type (`6 : int`);
+//! - a top-level `let` defines (or REDEFINES) a name for later inputs
+//!   (`double : (int) => int = <fn>`). Redefining one of the entry's own
+//!   defs replaces it for the rest of the session, callers included;
+//! - `:type <expr>` prints the type without evaluating, `:reload` re-reads
+//!   the project's files from disk, `:help` lists the commands, `:quit` exits.
+//!
+//! There is no incremental evaluator: every input re-links the project from
+//! an in-memory snapshot of its files (taken at start and by `:reload`, so a
+//! half-saved sibling never changes the session under you) with the session's
+//! lets and the new input appended to the entry, typechecks it, and
+//! re-evaluates the top-level defs through [`Session::load`]. That keeps the
+//! REPL's semantics identical to a real run — an input means what it would
+//! mean pasted at the bottom of the entry file — with one difference: the
+//! defs evaluate in dependency order rather than text order, because a
+//! redefinition lands BELOW the defs that use it (`let big = sq(4.0)` must
+//! see the new `sq`).
+//!
+//! Values print with [`Value::debug`], so a host that implements
+//! [`crate::HostData::debug`] shows the contents of its opaque values
+//! (`<Vec3 (1, 2, 3)>`) rather than just their type.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::ast::Item;
+use crate::eval::{Host, Session};
+use crate::ir::{Def, Module};
+use crate::project::{self, BundledModule, Project, ProjectError};
+use crate::types::Type;
+use crate::value::Value;
+use crate::{line_col, parse, ParseError, Span};
+
+/// A REPL session over one project.
+pub struct Repl {
+    entry: PathBuf,
+    bundled: Vec<BundledModule>,
+    /// The project's files as of the last (re)load, keyed by path. Every
+    /// input links against this snapshot, never against the disk.
+    files: HashMap<PathBuf, String>,
+    /// The session's own top-level lets, `(name, source)`, in definition
+    /// order; a redefinition replaces its earlier entry in place.
+    lets: Vec<(String, String)>,
+    /// Counter for the fresh def name an expression input is bound to.
+    next: usize,
+}
+
+/// One evaluated input.
+pub struct Evaluated {
+    /// The defined name, for a `let` input; `None` for an expression.
+    pub name: Option<String>,
+    pub value: Value,
+    /// The inferred type, when the checker settled one.
+    pub ty: Option<Type>,
+}
+
+impl fmt::Display for Evaluated {
+    /// `value : type` for an expression, `name : type = value` for a `let`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.name, &self.ty) {
+            (Some(name), Some(ty)) => write!(f, "{name} : {ty} = {}", self.value.debug()),
+            (Some(name), None) => write!(f, "{name} = {}", self.value.debug()),
+            (None, Some(ty)) => write!(f, "{} : {ty}", self.value.debug()),
+            (None, None) => write!(f, "{}", self.value.debug()),
+        }
+    }
+}
+
+/// An input prepared for linking: the def it becomes and its source.
+struct Input {
+    name: String,
+    /// `let <name> = …` — the input itself for a `let`, the expression wrapped
+    /// under a fresh name otherwise.
+    src: String,
+    /// Bytes of `src` before the user's text (the wrapper, if any), so
+    /// diagnostics locate in what was typed.
+    prefix: usize,
+    defines: bool,
+}
+
+/// The project linked with the session's lets and one input, plus where
+/// that input landed in the entry file.
+struct Linked {
+    project: Project,
+    /// The entry file's length before the REPL's own text (lets and input).
+    entry_len: usize,
+    /// Offsets of each REPL let, then the input, within the entry file.
+    chunks: Vec<(usize, String)>,
+    input: Input,
+}
+
+impl Repl {
+    /// Start a session over the project rooted at `entry`, with `bundled`
+    /// modules supplied by the embedding (`&[]` for the plain language).
+    pub fn new(entry: &Path, bundled: Vec<BundledModule>) -> Result<Repl, String> {
+        let mut repl = Repl {
+            entry: entry.to_path_buf(),
+            bundled,
+            files: HashMap::new(),
+            lets: Vec::new(),
+            next: 0,
+        };
+        repl.reload()?;
+        Ok(repl)
+    }
+
+    /// Re-read the project's files from disk (the entry and every sibling;
+    /// new siblings join, deleted ones leave). The session's lets are kept.
+    /// Returns how many files loaded; on failure the previous snapshot stays
+    /// in effect.
+    pub fn reload(&mut self) -> Result<usize, String> {
+        let paths = project::project_files(&self.entry).map_err(|e| {
+            format!(
+                "{}: cannot read the project directory: {e}",
+                self.entry.display()
+            )
+        })?;
+        let mut files = HashMap::new();
+        for path in paths {
+            let src = std::fs::read_to_string(&path)
+                .map_err(|e| format!("{}: cannot read: {e}", path.display()))?;
+            files.insert(path, src);
+        }
+        project::load_with_bundled_modules(&self.entry, &files, &self.bundled)
+            .map_err(|e| e.render())?;
+        let count = files.len();
+        self.files = files;
+        Ok(count)
+    }
+
+    /// The names the session has defined, in definition order.
+    pub fn defined(&self) -> impl Iterator<Item = &str> {
+        self.lets.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Evaluate one input — an expression or a top-level `let` — under
+    /// `host`. A `let` is kept for later inputs only if it checks and
+    /// evaluates. Errors are rendered (`<repl>:line:col: …` for the input
+    /// itself, `file:line:col: …` elsewhere).
+    pub fn eval(&mut self, input: &str, host: &mut dyn Host) -> Result<Evaluated, String> {
+        let (linked, ty) = self.check(input)?;
+        let session = Session::load(&linked.project.module, host)
+            .map_err(|failure| linked.render(failure.error.span, &failure.error.message))?;
+        let value = session
+            .global(&linked.input.name)
+            .ok_or_else(|| format!("`{}` has no value", linked.input.name))?;
+        let name = linked.input.defines.then(|| linked.input.name.clone());
+        if linked.input.defines {
+            let Input { name, src, .. } = linked.input;
+            match self.lets.iter_mut().find(|(existing, _)| *existing == name) {
+                Some(slot) => slot.1 = src,
+                None => self.lets.push((name, src)),
+            }
+        } else {
+            self.next += 1;
+        }
+        Ok(Evaluated { name, value, ty })
+    }
+
+    /// The inferred type of an expression, without evaluating it.
+    pub fn type_of(&self, input: &str) -> Result<Type, String> {
+        let (_, ty) = self.check(input)?;
+        ty.ok_or_else(|| "no type was inferred".to_string())
+    }
+
+    /// Link and typecheck `input`: the linked project and the input's type.
+    /// Only the REPL's own text gates an input — like `run`, the REPL does
+    /// not refuse a project that has type errors elsewhere.
+    fn check(&self, input: &str) -> Result<(Linked, Option<Type>), String> {
+        let linked = self.link(input)?;
+        let (errors, types) = linked.project.check_with_types();
+        let own: Vec<String> = errors
+            .iter()
+            .filter(|error| linked.is_repl(error.span.start))
+            .map(|error| linked.render(error.span, &error.message))
+            .collect();
+        if !own.is_empty() {
+            return Err(own.join("\n"));
+        }
+        let ty = linked
+            .project
+            .module
+            .defs
+            .iter()
+            .find(|def| def.name == linked.input.name)
+            .and_then(|def| types.expr(def.value.id))
+            .cloned();
+        Ok((linked, ty))
+    }
+
+    /// Link the project with the session's lets and `input` appended to the
+    /// entry. Entry defs the session has redefined are blanked out of the
+    /// entry's text (same length, so every other span is unchanged).
+    fn link(&self, input: &str) -> Result<Linked, String> {
+        let input = self.prepare(input)?;
+        let original = self
+            .files
+            .get(&self.entry)
+            .ok_or_else(|| format!("{}: not loaded", self.entry.display()))?;
+        let mut redefined: Vec<&str> = self.defined().collect();
+        if input.defines {
+            redefined.push(&input.name);
+        }
+        let mut entry = blank_lets(original, &redefined);
+        let entry_len = entry.len();
+        let mut chunks = Vec::new();
+        for (name, src) in &self.lets {
+            if input.defines && *name == input.name {
+                continue;
+            }
+            entry.push('\n');
+            chunks.push((entry.len(), name.clone()));
+            entry.push_str(src);
+        }
+        entry.push('\n');
+        chunks.push((entry.len(), String::new()));
+        entry.push_str(&input.src);
+
+        let mut files = self.files.clone();
+        files.insert(self.entry.clone(), entry);
+        let project = project::load_with_bundled_modules(&self.entry, &files, &self.bundled);
+        let mut linked = Linked {
+            project: match project {
+                Ok(project) => project,
+                Err(error) => {
+                    return Err(self.render_load(&error, &files, entry_len, &chunks, &input))
+                }
+            },
+            entry_len,
+            chunks,
+            input,
+        };
+        // The entry is always the first file — base 0 — but go through the
+        // source map rather than assume it.
+        let base = linked.project.sources.files()[0].base;
+        for (offset, _) in &mut linked.chunks {
+            *offset += base;
+        }
+        linked.entry_len += base;
+        let module = &mut linked.project.module;
+        let order = dependency_order(module);
+        let mut defs: Vec<Option<Def>> = std::mem::take(&mut module.defs)
+            .into_iter()
+            .map(Some)
+            .collect();
+        module.defs = order.into_iter().filter_map(|i| defs[i].take()).collect();
+        Ok(linked)
+    }
+
+    /// Turn raw input into the def it links as.
+    fn prepare(&self, input: &str) -> Result<Input, String> {
+        let input = input.trim();
+        if let Ok(program) = parse(input) {
+            if let [Item::Let(decl)] = program.items.as_slice() {
+                return Ok(Input {
+                    name: decl.name.clone(),
+                    src: input.to_string(),
+                    prefix: 0,
+                    defines: true,
+                });
+            }
+            if !program.items.is_empty() {
+                return Err(
+                    "only expressions and single top-level `let`s can be entered; put other \
+declarations in a project file and `:reload`"
+                        .to_string(),
+                );
+            }
+        }
+        let name = self.fresh();
+        let prefix = format!("let {name} = ");
+        let src = format!("{prefix}{input}");
+        if let Err(error) = parse(&src) {
+            return Err(render_input(
+                input,
+                error.span.start.saturating_sub(prefix.len()),
+                &error.message,
+            ));
+        }
+        Ok(Input {
+            name,
+            src,
+            prefix: prefix.len(),
+            defines: false,
+        })
+    }
+
+    /// A def name no project file or session let mentions.
+    fn fresh(&self) -> String {
+        let mut n = self.next;
+        loop {
+            let name = format!("repl{n}");
+            let taken = self.files.values().any(|src| src.contains(&name))
+                || self.lets.iter().any(|(_, src)| src.contains(&name));
+            if !taken {
+                return name;
+            }
+            n += 1;
+        }
+    }
+
+    /// Render a load failure, mapping a position in the REPL's own text back
+    /// to that text.
+    fn render_load(
+        &self,
+        error: &ProjectError,
+        files: &HashMap<PathBuf, String>,
+        entry_len: usize,
+        chunks: &[(usize, String)],
+        input: &Input,
+    ) -> String {
+        let src = &files[&self.entry];
+        if error.path != self.entry || line_col(src, entry_len).0 >= error.line {
+            return error.render();
+        }
+        let offset = offset_of(src, error.line, error.col);
+        render_chunk(src, offset, chunks, input, &error.message)
+    }
+}
+
+impl Linked {
+    /// Whether project-wide `offset` falls in the REPL's own text.
+    fn is_repl(&self, offset: usize) -> bool {
+        let entry = &self.project.sources.files()[0];
+        offset >= self.entry_len && offset <= entry.base + entry.src.len()
+    }
+
+    /// Render a diagnostic at `span`: relative to the input when it falls in
+    /// the REPL's text, `file:line:col` otherwise.
+    fn render(&self, span: Span, message: &str) -> String {
+        if !self.is_repl(span.start) {
+            return self.project.sources.render(span.start, message);
+        }
+        let entry = &self.project.sources.files()[0];
+        let chunks: Vec<(usize, String)> = self
+            .chunks
+            .iter()
+            .map(|(offset, name)| (offset - entry.base, name.clone()))
+            .collect();
+        render_chunk(
+            &entry.src,
+            span.start - entry.base,
+            &chunks,
+            &self.input,
+            message,
+        )
+    }
+}
+
+/// Render `message` at entry-local `offset`, which is in one of the REPL's
+/// chunks: the input (`<repl>:line:col`) or an earlier session let.
+fn render_chunk(
+    src: &str,
+    offset: usize,
+    chunks: &[(usize, String)],
+    input: &Input,
+    message: &str,
+) -> String {
+    let Some((start, name)) = chunks.iter().rev().find(|(start, _)| *start <= offset) else {
+        return format!("<repl>: {message}");
+    };
+    if !name.is_empty() {
+        return format!("<repl>: in `{name}` (defined earlier): {message}");
+    }
+    let local = (offset - start).saturating_sub(input.prefix);
+    render_input(&src[start + input.prefix..], local, message)
+}
+
+/// `<repl>:line:col: message` at byte `offset` of the typed input.
+fn render_input(input: &str, offset: usize, message: &str) -> String {
+    let (line, col) = line_col(input, offset);
+    format!("<repl>:{line}:{col}: {message}")
+}
+
+/// The byte offset of 1-based (`line`, `col`) in `src` — [`line_col`]'s
+/// inverse (columns count characters).
+fn offset_of(src: &str, line: usize, col: usize) -> usize {
+    let start = if line <= 1 {
+        0
+    } else {
+        src.match_indices('\n')
+            .nth(line - 2)
+            .map_or(src.len(), |(i, _)| i + 1)
+    };
+    src[start..]
+        .char_indices()
+        .nth(col - 1)
+        .map_or(src.len(), |(i, _)| start + i)
+}
+
+/// `src` with the top-level lets named in `names` replaced by spaces
+/// (newlines kept, so every line and offset is unchanged). A source that
+/// does not parse is returned as-is — linking it reports the real error.
+fn blank_lets(src: &str, names: &[&str]) -> String {
+    let Ok(program) = parse(src) else {
+        return src.to_string();
+    };
+    let mut out = src.to_string();
+    for item in &program.items {
+        if let Item::Let(decl) = item {
+            if names.contains(&decl.name.as_str()) {
+                let blank: String = src[decl.span.start..decl.span.end]
+                    .chars()
+                    .map(|c| {
+                        if c == '\n' {
+                            "\n".to_string()
+                        } else {
+                            " ".repeat(c.len_utf8())
+                        }
+                    })
+                    .collect();
+                out.replace_range(decl.span.start..decl.span.end, &blank);
+            }
+        }
+    }
+    out
+}
+
+/// The indices of `module`'s defs with each def after the defs it
+/// references and otherwise in text order. Mutually recursive defs keep
+/// their text order among themselves.
+fn dependency_order(module: &Module) -> Vec<usize> {
+    crate::types::scc_groups(module)
+        .into_iter()
+        .flat_map(|mut group| {
+            group.sort_unstable();
+            group
+        })
+        .collect()
+}
+
+/// Whether `input` stops partway through a construct (an open bracket, a
+/// dangling operator, a `match` with no arms yet), so the loop should read
+/// another line rather than report an error.
+pub fn is_incomplete(input: &str) -> bool {
+    let input = input.trim();
+    if input.is_empty() {
+        return false;
+    }
+    let at_end = |error: &ParseError| error.message.ends_with("found end of input");
+    match parse(input) {
+        Ok(_) => false,
+        Err(error) if at_end(&error) => true,
+        Err(_) => parse(&format!("let repl = {input}")).is_err_and(|error| at_end(&error)),
+    }
+}
+
+const HELP: &str = "\
+Enter an expression to evaluate it, or `let name = …` to (re)define a name.
+  :type <expr>  show an expression's type without evaluating it (:t)
+  :reload       re-read the project's files from disk (:r)
+  :help         show this help (:h)
+  :quit         leave the REPL (:q)
+An unfinished line continues the input until a blank line.";
+
+/// Run the interactive loop: read inputs from `input` (continuing to a
+/// blank line when the first line [`is_incomplete`]), evaluate them under `host`, and
+/// write results and errors to `output`. Returns at end of input or `:quit`.
+pub fn run(
+    repl: &mut Repl,
+    host: &mut dyn Host,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    writeln!(
+        output,
+        "Functor Lang REPL — {}. :help for commands.",
+        repl.entry.display()
+    )?;
+    let mut lines = input.lines();
+    loop {
+        write!(output, "> ")?;
+        output.flush()?;
+        let Some(first) = lines.next() else {
+            writeln!(output)?;
+            return Ok(());
+        };
+        let mut buffer = first?;
+        // An unfinished first line opens a multi-line input, which runs to
+        // the next blank line: "complete so far" is not "done" — a `match`
+        // parses after its first arm.
+        if is_incomplete(&buffer) {
+            loop {
+                write!(output, "| ")?;
+                output.flush()?;
+                let Some(line) = lines.next() else { break };
+                let line = line?;
+                if line.trim().is_empty() {
+                    break;
+                }
+                buffer.push('\n');
+                buffer.push_str(&line);
+            }
+        }
+        let text = buffer.trim();
+        let (command, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let reply = match command {
+            "" => continue,
+            ":q" | ":quit" => return Ok(()),
+            ":h" | ":help" => Ok(HELP.to_string()),
+            ":r" | ":reload" => repl
+                .reload()
+                .map(|count| format!("reloaded {count} file(s)")),
+            ":t" | ":type" => repl
+                .type_of(rest)
+                .map(|ty| format!("{} : {ty}", rest.trim())),
+            _ if command.starts_with(':') => Err(format!(
+                "unknown command `{command}` — :help lists the commands"
+            )),
+            _ => repl.eval(text, host).map(|evaluated| evaluated.to_string()),
+        };
+        match reply {
+            Ok(text) => writeln!(output, "{text}")?,
+            Err(text) => writeln!(output, "error: {text}")?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NoHost;
+
+    /// Write `files` into a fresh scratch directory and return it with its
+    /// entry (the first file).
+    fn project(name: &str, files: &[(&str, &str)]) -> (PathBuf, PathBuf) {
+        let dir =
+            std::env::temp_dir().join(format!("functor-lang-repl-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, src) in files {
+            std::fs::write(dir.join(name), src).unwrap();
+        }
+        let entry = dir.join(files[0].0);
+        (dir, entry)
+    }
+
+    fn eval(repl: &mut Repl, input: &str) -> String {
+        match repl.eval(input, &mut NoHost) {
+            Ok(evaluated) => evaluated.to_string(),
+            Err(error) => format!("error: {error}"),
+        }
+    }
+
+    #[test]
+    fn expressions_print_value_and_type_over_the_project() {
+        let (dir, entry) = project(
+            "project",
+            &[
+                ("main.fun", "let double = (n: int): int => n * 2\n"),
+                (
+                    "util.fun",
+                    "let greet = (name: string): string => $\"hi {name}\"\n",
+                ),
+            ],
+        );
+        let mut repl = Repl::new(&entry, Vec::new()).unwrap();
+        assert_eq!(eval(&mut repl, "double(3)"), "6 : int");
+        assert_eq!(
+            eval(&mut repl, "Util.greet(\"ada\")"),
+            "\"hi ada\" : string"
+        );
+        assert_eq!(repl.type_of("double").unwrap().to_string(), "(int) => int");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn lets_define_and_redefine_top_level_names() {
+        let (dir, entry) = project(
+            "lets",
+            &[(
+                "main.fun",
+                "let step = (n: int): int => n + 1\nlet twice = (n: int): int => step(step(n))\n",
+            )],
+        );
+        let mut repl = Repl::new(&entry, Vec::new()).unwrap();
+        assert_eq!(eval(&mut repl, "twice(0)"), "2 : int");
+        assert_eq!(eval(&mut repl, "let base = 10"), "base : int = 10");
+        assert_eq!(eval(&mut repl, "base + 1"), "11 : int");
+        assert_eq!(eval(&mut repl, "let base = 20"), "base : int = 20");
+        assert_eq!(eval(&mut repl, "base + 1"), "21 : int");
+        // Redefining an ENTRY def replaces it for its callers too.
+        assert!(eval(&mut repl, "let step = (n: int): int => n + base")
+            .starts_with("step : (int) => int = "));
+        assert_eq!(eval(&mut repl, "twice(0)"), "40 : int");
+        assert_eq!(repl.defined().collect::<Vec<_>>(), ["base", "step"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn redefining_an_entry_def_reaches_the_values_that_use_it() {
+        let (dir, entry) = project(
+            "entry-dependents",
+            &[(
+                "main.fun",
+                "let sq = (x: float) => x * x\nlet big = sq(4.0)\n",
+            )],
+        );
+        let mut repl = Repl::new(&entry, Vec::new()).unwrap();
+        assert_eq!(eval(&mut repl, "big"), "16 : float");
+        assert!(
+            eval(&mut repl, "let sq = (x: float) => x + x").starts_with("sq : (float) => float = ")
+        );
+        assert_eq!(eval(&mut repl, "big"), "8 : float");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn redefining_a_session_let_reaches_the_lets_that_use_it() {
+        let (dir, entry) = project("let-dependents", &[("main.fun", "let one = 1\n")]);
+        let mut repl = Repl::new(&entry, Vec::new()).unwrap();
+        assert_eq!(eval(&mut repl, "let k = 1.0"), "k : float = 1");
+        assert_eq!(eval(&mut repl, "let w = k + 1.0"), "w : float = 2");
+        assert_eq!(eval(&mut repl, "let k = 5.0"), "k : float = 5");
+        assert_eq!(eval(&mut repl, "w"), "6 : float");
+        assert_eq!(repl.defined().collect::<Vec<_>>(), ["k", "w"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn errors_locate_in_the_input_and_leave_the_session_unchanged() {
+        let (dir, entry) = project("errors", &[("main.fun", "let one = 1\n")]);
+        let mut repl = Repl::new(&entry, Vec::new()).unwrap();
+        assert_eq!(
+            eval(&mut repl, "one + nope"),
+            "error: <repl>:1:7: unknown name `nope`"
+        );
+        let error = eval(&mut repl, "let bad: string = one");
+        assert!(error.starts_with("error: <repl>:1:"), "{error}");
+        assert_eq!(repl.defined().count(), 0);
+        assert_eq!(eval(&mut repl, "one"), "1 : int");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reload_picks_up_edited_and_new_siblings() {
+        let (dir, entry) = project(
+            "reload",
+            &[("main.fun", "let one = 1\n"), ("util.fun", "let k = 1\n")],
+        );
+        let mut repl = Repl::new(&entry, Vec::new()).unwrap();
+        std::fs::write(dir.join("util.fun"), "let k = 5\n").unwrap();
+        std::fs::write(dir.join("extra.fun"), "let m = 7\n").unwrap();
+        // The snapshot holds until `:reload`.
+        assert_eq!(eval(&mut repl, "Util.k"), "1 : int");
+        assert_eq!(repl.reload().unwrap(), 3);
+        assert_eq!(eval(&mut repl, "Util.k + Extra.m"), "12 : int");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn unfinished_input_asks_for_more() {
+        assert!(is_incomplete("double("));
+        assert!(is_incomplete("let f = (n) =>"));
+        assert!(is_incomplete("1 +"));
+        assert!(!is_incomplete("double(3)"));
+        assert!(!is_incomplete("1 + )"));
+        assert!(!is_incomplete(""));
+    }
+
+    #[test]
+    fn the_loop_reads_continues_and_quits() {
+        let (dir, entry) = project(
+            "loop",
+            &[("main.fun", "let double = (n: int): int => n * 2\n")],
+        );
+        let mut repl = Repl::new(&entry, Vec::new()).unwrap();
+        let input = "double(\n  4)\n\n:t double\n:bogus\n:q\ndouble(1)\n";
+        let mut output = Vec::new();
+        run(&mut repl, &mut NoHost, input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("> | | 8 : int\n"), "{output}");
+        assert!(output.contains("double : (int) => int\n"), "{output}");
+        assert!(
+            output.contains("error: unknown command `:bogus`"),
+            "{output}"
+        );
+        assert!(!output.contains("2 : int"), "{output}");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,722 @@
+//! Semantic tokens: what each identifier in a file IS, from the resolved IR
+//! and the checker's types — the language-aware half of the LSP's
+//! `textDocument/semanticTokens`. A TextMate grammar sees only spelling, so
+//! `Utils.clamp` and `Shape.Circle` look alike, a host value looks like a
+//! local, and `90deg` is just a number; lowering and checking already know
+//! the difference:
+//!
+//! - qualifiers are **namespaces**, and the last segment of an external is
+//!   a **function** or **variable** marked `defaultLibrary` (a builtin) or
+//!   `host` (typed by a `.funi` signature the host implements);
+//! - constructors are **enum members**, wherever they appear (expressions,
+//!   patterns, declarations);
+//! - `'a` in an annotation or type declaration is a **type parameter**;
+//! - a unit-suffixed literal is a **number** plus a **unit** (the suffix,
+//!   which brands it), and a `unit` declaration's suffix is a unit too;
+//! - a binding some `:=` assigns is `mutable` at every occurrence, and the
+//!   assignment target itself is also a `modification`.
+//!
+//! Whether a name is a function or a variable comes from its checked type.
+//! Every token is checked against the text it covers, so a span lowering
+//! synthesized (a desugared pipeline, a `let` pattern) never paints the
+//! wrong characters.
+
+use std::collections::HashSet;
+
+use crate::ast::{TypeBody, TypeName};
+use crate::eval::builtin;
+use crate::ir::{Expr, ExprKind, Module, Pattern, PatternKind};
+use crate::project::{Project, SourceFile};
+use crate::span::Span;
+use crate::types::{ExprTypes, Type};
+
+/// What a token is. The order is the LSP legend's ([`TokenType::ALL`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenType {
+    Namespace,
+    Type,
+    TypeParameter,
+    Parameter,
+    Variable,
+    Property,
+    EnumMember,
+    Function,
+    Number,
+    /// A unit suffix (`deg` in `90deg`).
+    Unit,
+}
+
+impl TokenType {
+    pub const ALL: [TokenType; 10] = [
+        TokenType::Namespace,
+        TokenType::Type,
+        TokenType::TypeParameter,
+        TokenType::Parameter,
+        TokenType::Variable,
+        TokenType::Property,
+        TokenType::EnumMember,
+        TokenType::Function,
+        TokenType::Number,
+        TokenType::Unit,
+    ];
+
+    /// The legend name: the LSP's standard token type, or `unit`.
+    pub fn name(self) -> &'static str {
+        match self {
+            TokenType::Namespace => "namespace",
+            TokenType::Type => "type",
+            TokenType::TypeParameter => "typeParameter",
+            TokenType::Parameter => "parameter",
+            TokenType::Variable => "variable",
+            TokenType::Property => "property",
+            TokenType::EnumMember => "enumMember",
+            TokenType::Function => "function",
+            TokenType::Number => "number",
+            TokenType::Unit => "unit",
+        }
+    }
+}
+
+/// A qualifier of a token. The order is the LSP legend's
+/// ([`TokenModifier::ALL`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenModifier {
+    /// The binding site of a name.
+    Declaration,
+    /// A builtin (`List.map`, `Math.sin`).
+    DefaultLibrary,
+    /// A host-implemented value (`Scene.cube`).
+    Host,
+    /// A binding some `:=` assigns.
+    Mutable,
+    /// The target of a `:=`.
+    Modification,
+}
+
+impl TokenModifier {
+    pub const ALL: [TokenModifier; 5] = [
+        TokenModifier::Declaration,
+        TokenModifier::DefaultLibrary,
+        TokenModifier::Host,
+        TokenModifier::Mutable,
+        TokenModifier::Modification,
+    ];
+
+    /// The legend name: the LSP's standard modifier, or `host` / `mutable`.
+    pub fn name(self) -> &'static str {
+        match self {
+            TokenModifier::Declaration => "declaration",
+            TokenModifier::DefaultLibrary => "defaultLibrary",
+            TokenModifier::Host => "host",
+            TokenModifier::Mutable => "mutable",
+            TokenModifier::Modification => "modification",
+        }
+    }
+}
+
+/// One classified range of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticToken {
+    /// Project-wide (see [`crate::project::SourceMap`]).
+    pub span: Span,
+    pub ty: TokenType,
+    pub modifiers: Vec<TokenModifier>,
+}
+
+/// The tokens of `file` (one of `project`'s sources), in source order and
+/// never overlapping. `types` is the project's
+/// [`Project::check_with_types`] table.
+pub fn semantic_tokens(
+    project: &Project,
+    types: &ExprTypes,
+    file: &SourceFile,
+) -> Vec<SemanticToken> {
+    let module = &project.module;
+    let mut tokens = Tokens {
+        file,
+        module,
+        types,
+        assigned: HashSet::new(),
+        params: HashSet::new(),
+        out: Vec::new(),
+    };
+    let in_file = |span: Span| tokens.owns(span);
+    let roots: Vec<&Expr> = module
+        .defs
+        .iter()
+        .filter(|def| in_file(def.span))
+        .map(|def| &def.value)
+        .chain(
+            module
+                .expects
+                .iter()
+                .filter(|expect| in_file(expect.span))
+                .map(|expect| &expect.expr),
+        )
+        .chain(
+            module
+                .units
+                .iter()
+                .filter(|unit| in_file(unit.span))
+                .map(|unit| &unit.target),
+        )
+        .chain(
+            module
+                .unit_ops
+                .iter()
+                .filter(|op| in_file(op.span))
+                .map(|op| &op.target),
+        )
+        .collect();
+    for root in &roots {
+        tokens.scan(root);
+    }
+
+    for ty in &module.types {
+        if !tokens.owns(ty.span) {
+            continue;
+        }
+        let written = last_segment(&ty.name);
+        let kind = match ty.body {
+            TypeBody::Host => Some(TokenModifier::Host),
+            _ => None,
+        };
+        let modifiers: Vec<TokenModifier> = std::iter::once(TokenModifier::Declaration)
+            .chain(kind)
+            .collect();
+        tokens.word_after(ty.span, "type", written, TokenType::Type, modifiers);
+        let header_end = match tokens.text(ty.span).and_then(|text| text.find('=')) {
+            Some(at) => ty.span.start + at,
+            None => ty.span.end,
+        };
+        for param in &ty.params {
+            let header = Span::new(ty.span.start, header_end);
+            tokens.word_after(
+                header,
+                "",
+                param,
+                TokenType::TypeParameter,
+                vec![TokenModifier::Declaration],
+            );
+        }
+        match &ty.body {
+            TypeBody::Record(fields) => {
+                for field in fields {
+                    tokens.word_at(
+                        field.span.start,
+                        &field.name,
+                        TokenType::Property,
+                        vec![TokenModifier::Declaration],
+                    );
+                    tokens.type_name(&field.ty);
+                }
+            }
+            TypeBody::Variants(variants) => {
+                for variant in variants {
+                    tokens.word_at(
+                        variant.span.start,
+                        last_segment(&variant.name),
+                        TokenType::EnumMember,
+                        vec![TokenModifier::Declaration],
+                    );
+                    for field in &variant.fields {
+                        tokens.word_at(
+                            field.span.start,
+                            &field.name,
+                            TokenType::Property,
+                            vec![TokenModifier::Declaration],
+                        );
+                        tokens.type_name(&field.ty);
+                    }
+                }
+            }
+            TypeBody::Abstract | TypeBody::Host => {}
+        }
+    }
+
+    for def in &module.defs {
+        if !tokens.owns(def.span) {
+            continue;
+        }
+        let ty = match tokens.is_function(types.expr(def.value.id)) {
+            true => TokenType::Function,
+            false => TokenType::Variable,
+        };
+        let head = Span::new(def.span.start, def.value.span.start.max(def.span.start));
+        tokens.word_after(
+            head,
+            "let",
+            last_segment(&def.name),
+            ty,
+            vec![TokenModifier::Declaration],
+        );
+        if let Some(annotation) = &def.ty {
+            tokens.type_name(annotation);
+        }
+    }
+    for sig in &module.signatures {
+        if !tokens.owns(sig.span) {
+            continue;
+        }
+        let ty = match sig.ty.name.as_str() {
+            "=>" => TokenType::Function,
+            _ => TokenType::Variable,
+        };
+        let path: Vec<String> = sig.name.split('.').map(str::to_string).collect();
+        let owner = match builtin(&path) {
+            Some(_) => TokenModifier::DefaultLibrary,
+            None => TokenModifier::Host,
+        };
+        tokens.word_after(
+            sig.span,
+            "let",
+            last_segment(&sig.name),
+            ty,
+            vec![TokenModifier::Declaration, owner],
+        );
+        tokens.type_name(&sig.ty);
+    }
+    for unit in &module.units {
+        if tokens.owns(unit.span) {
+            tokens.word_after(
+                unit.span,
+                "unit",
+                &unit.suffix,
+                TokenType::Unit,
+                vec![TokenModifier::Declaration],
+            );
+        }
+    }
+    for op in &module.unit_ops {
+        if tokens.owns(op.span) {
+            tokens.word_after(op.span, "unit", &op.suffix, TokenType::Unit, Vec::new());
+        }
+    }
+
+    let mut out = tokens.out;
+    out.sort_by_key(|token| (token.span.start, token.span.end));
+    let mut end = 0;
+    out.retain(|token| {
+        let keep = token.span.start >= end;
+        if keep {
+            end = token.span.end;
+        }
+        keep
+    });
+    out
+}
+
+struct Tokens<'a> {
+    file: &'a SourceFile,
+    module: &'a Module,
+    types: &'a ExprTypes,
+    /// Bindings some `:=` assigns.
+    assigned: HashSet<u32>,
+    /// Bindings that are lambda parameters.
+    params: HashSet<u32>,
+    out: Vec<SemanticToken>,
+}
+
+impl Tokens<'_> {
+    fn owns(&self, span: Span) -> bool {
+        self.file.base <= span.start && span.end <= self.file.base + self.file.src.len()
+    }
+
+    fn text(&self, span: Span) -> Option<&str> {
+        if !self.owns(span) || span.start > span.end {
+            return None;
+        }
+        self.file
+            .src
+            .get(span.start - self.file.base..span.end - self.file.base)
+    }
+
+    fn is_function(&self, ty: Option<&Type>) -> bool {
+        matches!(ty, Some(Type::Fn(..)))
+    }
+
+    fn push(&mut self, span: Span, ty: TokenType, modifiers: Vec<TokenModifier>) {
+        self.out.push(SemanticToken {
+            span,
+            ty,
+            modifiers,
+        });
+    }
+
+    /// `word` written at `start`.
+    fn word_at(&mut self, start: usize, word: &str, ty: TokenType, modifiers: Vec<TokenModifier>) {
+        let span = Span::new(start, start + word.len());
+        if self.text(span) == Some(word) {
+            self.push(span, ty, modifiers);
+        }
+    }
+
+    /// The first whole-word `word` in `span` after the `keyword` (or
+    /// anywhere, for an empty `keyword`).
+    fn word_after(
+        &mut self,
+        span: Span,
+        keyword: &str,
+        word: &str,
+        ty: TokenType,
+        modifiers: Vec<TokenModifier>,
+    ) {
+        let Some(text) = self.text(span).map(str::to_string) else {
+            return;
+        };
+        let from = match keyword {
+            "" => 0,
+            keyword => match find_word(&text, keyword) {
+                Some(at) => at + keyword.len(),
+                None => return,
+            },
+        };
+        if let Some(at) = find_word(&text[from..], word) {
+            let start = span.start + from + at;
+            self.push(Span::new(start, start + word.len()), ty, modifiers);
+        }
+    }
+
+    /// A (possibly qualified) reference spanning `span`: every qualifier is
+    /// a namespace, and the last segment — which must spell `name`'s — is
+    /// `ty`.
+    fn reference(&mut self, span: Span, name: &str, ty: TokenType, modifiers: Vec<TokenModifier>) {
+        let Some(text) = self.text(span).map(str::to_string) else {
+            return;
+        };
+        let segments: Vec<&str> = text.split('.').collect();
+        let is_ident = |segment: &str| {
+            !segment.is_empty() && segment.chars().all(|c| c.is_alphanumeric() || c == '_')
+        };
+        if !segments.iter().all(|segment| is_ident(segment))
+            || segments.last() != Some(&last_segment(name))
+        {
+            return;
+        }
+        let mut start = span.start;
+        for (i, segment) in segments.iter().enumerate() {
+            let at = Span::new(start, start + segment.len());
+            if i + 1 == segments.len() {
+                self.push(at, ty, modifiers);
+                break;
+            }
+            self.push(at, TokenType::Namespace, Vec::new());
+            start = at.end + 1;
+        }
+    }
+
+    /// A type annotation: its names are types (qualifiers namespaces), its
+    /// `'a`s type parameters.
+    fn type_name(&mut self, ty: &TypeName) {
+        match ty.name.as_str() {
+            "=>" | "*" => {}
+            name if name.starts_with('\'') => {
+                self.word_at(ty.span.start, name, TokenType::TypeParameter, Vec::new());
+            }
+            name => {
+                let head = Span::new(ty.span.start, ty.span.start + name.len());
+                self.reference(head, name, TokenType::Type, Vec::new());
+            }
+        }
+        for arg in &ty.args {
+            self.type_name(arg);
+        }
+    }
+
+    /// A binding occurrence: a parameter or a variable (a function when its
+    /// type is one), `mutable` when assigned.
+    fn binding(&self, binding: u32, ty: Option<&Type>) -> (TokenType, Vec<TokenModifier>) {
+        let kind = if self.params.contains(&binding) {
+            TokenType::Parameter
+        } else if self.is_function(ty) {
+            TokenType::Function
+        } else {
+            TokenType::Variable
+        };
+        let modifiers = match self.assigned.contains(&binding) {
+            true => vec![TokenModifier::Mutable],
+            false => Vec::new(),
+        };
+        (kind, modifiers)
+    }
+
+    /// Record which bindings are assigned or are parameters, then classify.
+    fn scan(&mut self, root: &Expr) {
+        fn bindings(expr: &Expr, assigned: &mut HashSet<u32>, params: &mut HashSet<u32>) {
+            match &expr.kind {
+                ExprKind::Assign { binding, .. } => {
+                    assigned.insert(binding.0);
+                }
+                ExprKind::Lambda { params: list, .. } => {
+                    params.extend(list.iter().map(|param| param.binding.0));
+                }
+                _ => {}
+            }
+            crate::rebind::each_child(expr, &mut |child| bindings(child, assigned, params));
+        }
+        bindings(root, &mut self.assigned, &mut self.params);
+        self.expr(root);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        let ty = self.types.expr(expr.id);
+        match &expr.kind {
+            ExprKind::Local { binding, name } | ExprKind::LocalMut { binding, name } => {
+                let (kind, modifiers) = self.binding(binding.0, ty);
+                self.reference(expr.span, name, kind, modifiers);
+            }
+            ExprKind::Global(name) => {
+                let kind = match self.is_function(ty) {
+                    true => TokenType::Function,
+                    false => TokenType::Variable,
+                };
+                self.reference(expr.span, name, kind, Vec::new());
+            }
+            ExprKind::External(path) => {
+                let kind = match self.is_function(ty) {
+                    true => TokenType::Function,
+                    false => TokenType::Variable,
+                };
+                let joined = path.join(".");
+                let owner = if builtin(path).is_some() {
+                    Some(TokenModifier::DefaultLibrary)
+                } else if self.module.signatures.iter().any(|sig| sig.name == joined) {
+                    Some(TokenModifier::Host)
+                } else {
+                    None
+                };
+                self.reference(expr.span, &joined, kind, owner.into_iter().collect());
+            }
+            ExprKind::Ctor { name, .. } => {
+                self.reference(expr.span, name, TokenType::EnumMember, Vec::new());
+            }
+            ExprKind::Let {
+                binding,
+                name,
+                ty: annotation,
+                value,
+                ..
+            } => {
+                let binder = self.types.binding(*binding).or(self.types.expr(value.id));
+                let (kind, mut modifiers) = self.binding(binding.0, binder);
+                modifiers.insert(0, TokenModifier::Declaration);
+                let head = Span::new(expr.span.start, value.span.start.max(expr.span.start));
+                self.word_after(head, "let", name, kind, modifiers);
+                if let Some(annotation) = annotation {
+                    self.type_name(annotation);
+                }
+            }
+            ExprKind::Assign { binding, name, .. } => {
+                let (kind, mut modifiers) = self.binding(binding.0, self.types.binding(*binding));
+                modifiers.push(TokenModifier::Modification);
+                self.word_at(expr.span.start, name, kind, modifiers);
+            }
+            ExprKind::FieldAccess { field, .. } => {
+                self.word_at(
+                    expr.span.end.saturating_sub(field.len()),
+                    field,
+                    TokenType::Property,
+                    Vec::new(),
+                );
+            }
+            ExprKind::Record(fields) | ExprKind::RecordUpdate { fields, .. } => {
+                for field in fields {
+                    self.word_at(
+                        field.span.start,
+                        &field.name,
+                        TokenType::Property,
+                        Vec::new(),
+                    );
+                }
+            }
+            ExprKind::Lambda { params, ret, .. } => {
+                for param in params.iter() {
+                    let (kind, mut modifiers) =
+                        self.binding(param.binding.0, self.types.binding(param.binding));
+                    modifiers.insert(0, TokenModifier::Declaration);
+                    self.word_at(param.span.start, &param.name, kind, modifiers);
+                    if let Some(annotation) = &param.ty {
+                        self.type_name(annotation);
+                    }
+                }
+                if let Some(ret) = ret {
+                    self.type_name(ret);
+                }
+            }
+            ExprKind::Match { arms, .. } => {
+                for arm in arms {
+                    self.pattern(&arm.pattern);
+                }
+            }
+            // `90deg` lowers to a call of the unit's target on `90.0`, the
+            // digits spanning the argument and the suffix the callee.
+            ExprKind::Call { callee, args }
+                if args.len() == 1
+                    && args[0].span.start == expr.span.start
+                    && args[0].span.end == callee.span.start
+                    && callee.span.end == expr.span.end
+                    && callee.span.start < callee.span.end =>
+            {
+                self.push(args[0].span, TokenType::Number, Vec::new());
+                self.push(callee.span, TokenType::Unit, Vec::new());
+                return;
+            }
+            _ => {}
+        }
+        crate::rebind::each_child(expr, &mut |child| self.expr(child));
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        match &pattern.kind {
+            PatternKind::Var { binding, name } => {
+                let (kind, mut modifiers) = self.binding(binding.0, self.types.binding(*binding));
+                modifiers.insert(0, TokenModifier::Declaration);
+                self.word_at(pattern.span.start, name, kind, modifiers);
+            }
+            PatternKind::Ctor { name, args } => {
+                let text = self.text(pattern.span).unwrap_or("");
+                let head = text
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                    .unwrap_or(text.len());
+                let head = Span::new(pattern.span.start, pattern.span.start + head);
+                self.reference(head, name, TokenType::EnumMember, Vec::new());
+                for arg in args {
+                    self.pattern(arg);
+                }
+            }
+            PatternKind::Tuple(items) | PatternKind::Or(items) => {
+                for item in items {
+                    self.pattern(item);
+                }
+            }
+            PatternKind::List { items, tail } => {
+                for item in items {
+                    self.pattern(item);
+                }
+                if let Some(tail) = tail {
+                    self.pattern(tail);
+                }
+            }
+            PatternKind::Record(fields) => {
+                for field in fields {
+                    self.word_at(
+                        field.span.start,
+                        &field.name,
+                        TokenType::Property,
+                        Vec::new(),
+                    );
+                    self.pattern(&field.pattern);
+                }
+            }
+            PatternKind::Wildcard
+            | PatternKind::Number(_)
+            | PatternKind::Int(_)
+            | PatternKind::Bool(_)
+            | PatternKind::String(_) => {}
+        }
+    }
+}
+
+/// The written name of a canonical one (`Utils.clamp` → `clamp`).
+fn last_segment(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+/// The first whole-word occurrence of `word` in `text`.
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '\'';
+    text.match_indices(word).map(|(at, _)| at).find(|&at| {
+        let before = text[..at].chars().next_back();
+        let after = text[at + word.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `text:type[+modifier…]` per token of `src`, space-separated, with a
+    /// two-module host prelude.
+    fn tokens(src: &str) -> String {
+        let prelude = [
+            (
+                "Scene".to_string(),
+                "type t = host\nlet cube : () => t\n".to_string(),
+            ),
+            (
+                "Angle".to_string(),
+                "type t = host\nlet degrees : (float) => t\n".to_string(),
+            ),
+        ];
+        let project = crate::project::load_sources_with_prelude(
+            vec![("main.fun".into(), src.to_string())],
+            &prelude,
+        )
+        .unwrap_or_else(|e| panic!("{}", e.message));
+        let (_, types) = project.check_with_types();
+        let file = &project.sources.files()[0];
+        semantic_tokens(&project, &types, file)
+            .iter()
+            .map(|token| {
+                let mut out = format!(
+                    "{}:{}",
+                    &src[token.span.start..token.span.end],
+                    token.ty.name()
+                );
+                for modifier in &token.modifiers {
+                    out.push_str(&format!("+{}", modifier.name()));
+                }
+                out
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn constructors_types_and_type_parameters() {
+        assert_eq!(
+            tokens(
+                "type Box<'a> = { item: 'a }\n\
+type Shape = | Circle(radius: float) | Point\n\
+let area = (s: Shape): float => match s with | Circle(r) => r | Point => 0.0\n"
+            ),
+            "Box:type+declaration 'a:typeParameter+declaration item:property+declaration \
+'a:typeParameter \
+Shape:type+declaration Circle:enumMember+declaration radius:property+declaration \
+float:type Point:enumMember+declaration \
+area:function+declaration s:parameter+declaration Shape:type float:type \
+s:parameter Circle:enumMember r:variable+declaration r:variable Point:enumMember"
+        );
+    }
+
+    #[test]
+    fn builtins_and_host_values_are_marked() {
+        assert_eq!(
+            tokens("let xs = List.map((x) => x, [1.0])\nlet c = Scene.cube()\n"),
+            "xs:variable+declaration List:namespace map:function+defaultLibrary \
+x:parameter+declaration x:parameter \
+c:variable+declaration Scene:namespace cube:function+host"
+        );
+    }
+
+    #[test]
+    fn assigned_bindings_are_mutable() {
+        assert_eq!(
+            tokens("let f = (n: int) => let mut total = 0 in total := total + n; total\n"),
+            "f:function+declaration n:parameter+declaration int:type \
+total:variable+declaration+mutable \
+total:variable+mutable+modification total:variable+mutable n:parameter \
+total:variable+mutable"
+        );
+    }
+
+    #[test]
+    fn unit_literals_split_into_number_and_unit() {
+        assert_eq!(
+            tokens("unit deg = Angle.degrees\nlet turn = 90deg\n"),
+            "deg:unit+declaration Angle:namespace degrees:function+host \
+turn:variable+declaration 90:number deg:unit"
+        );
+    }
+}
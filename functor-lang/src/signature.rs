@@ -0,0 +1,338 @@
+//! Signature help: the signature of the call the cursor is inside, with the
+//! parameter it is on — the language-aware half of the LSP's
+//! `textDocument/signatureHelp`. Like [`crate::complete`] it runs on a live,
+//! usually broken buffer (`Utils.clamp(x, ` does not parse), so the halves
+//! come from the same two places:
+//!
+//! - the **call context** — which callee, which argument, and whether the
+//!   call is a `|>` stage — from lexing the buffer up to the cursor and
+//!   walking back to the innermost unclosed `callee(`;
+//! - the **signature** from a loaded [`Project`] (possibly the last-good
+//!   one): a `.funi` signature, a def (its parameter names, with the
+//!   checker's types), a constructor's declared fields, or a builtin's
+//!   scheme ([`builtin_signature`]).
+//!
+//! A `|>` stage gets its LAST argument from the pipeline (`xs |> List.map(f)`
+//! is `List.map(f, xs)`), so that parameter is reported as piped and the
+//! written arguments count from the first.
+
+use crate::ast::TypeBody;
+use crate::complete::{
+    canonical_qualifier, ctor_return, lex_completion_prefix, owning_module, qualifier_chain,
+};
+use crate::eval::{builtin_name, ALL_BUILTINS};
+use crate::hover::type_name_text;
+use crate::ir::ExprKind;
+use crate::lexer::{Token, TokenKind};
+use crate::project::Project;
+use crate::types::{builtin_signature, Type};
+
+/// One signature to show: its label and where each parameter sits in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureHelp {
+    /// `Utils.clamp : (v: float, lo: float, hi: float) => float`, in
+    /// hover's `name : Type` form.
+    pub label: String,
+    /// Each parameter's byte range within `label`.
+    pub params: Vec<(usize, usize)>,
+    /// The parameter the cursor's argument binds; `None` past the last.
+    pub active: Option<usize>,
+    /// The parameter a `|>` pipeline supplies (always the last), when the
+    /// call is a pipeline stage.
+    pub piped: Option<usize>,
+    /// The callee's doc comment, if it has one.
+    pub doc: Option<String>,
+}
+
+/// The signature help at `offset`. Same contract as
+/// [`crate::complete::complete`]: `offset` is LOCAL to `live_text`; the
+/// callee resolves in `current_module` (and the `inline_module` block the
+/// cursor is in, if any) against `project`.
+pub fn signature_help(
+    project: &Project,
+    current_module: &str,
+    inline_module: Option<&str>,
+    live_text: &str,
+    offset: usize,
+) -> Option<SignatureHelp> {
+    if offset > live_text.len() || !live_text.is_char_boundary(offset) {
+        return None;
+    }
+    let mut tokens = lex_completion_prefix(&live_text[..offset])?;
+    tokens.pop(); // the Eof sentinel
+    let call = call_at(&tokens)?;
+    let scopes: Vec<&str> = inline_module
+        .into_iter()
+        .chain(Some(current_module))
+        .collect();
+    let mut help = resolve(project, current_module, &scopes, &call.callee)?;
+    help.piped = (call.piped && !help.params.is_empty()).then(|| help.params.len() - 1);
+    let available = help.piped.unwrap_or(help.params.len());
+    help.active = (call.argument < available).then_some(call.argument);
+    Some(help)
+}
+
+/// The innermost unclosed call around the cursor.
+struct Call {
+    /// The written callee (`Utils.clamp`, `Some`).
+    callee: String,
+    /// Which written argument the cursor is in (commas before it).
+    argument: usize,
+    /// Whether the call is a `|>` stage.
+    piped: bool,
+}
+
+/// Walk back from the cursor over balanced brackets to the innermost `(`
+/// that follows an identifier chain. An unclosed `[`, `{`, or bare `(` (a
+/// list, a record, a tuple or lambda's parameters) is an argument of an
+/// outer call, so the walk continues past it with the comma count reset.
+fn call_at(tokens: &[Token]) -> Option<Call> {
+    let mut depth = 0usize;
+    let mut commas = 0;
+    for i in (0..tokens.len()).rev() {
+        match tokens[i].kind {
+            TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace => depth += 1,
+            TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace if depth > 0 => depth -= 1,
+            TokenKind::LParen if i > 0 && matches!(tokens[i - 1].kind, TokenKind::Ident(_)) => {
+                let callee = qualifier_chain(tokens, i - 1);
+                let segments = callee.split('.').count();
+                let head = (i - 1).checked_sub(2 * (segments - 1))?;
+                let piped = head > 0 && tokens[head - 1].kind == TokenKind::PipeGt;
+                return Some(Call {
+                    callee,
+                    argument: commas,
+                    piped,
+                });
+            }
+            TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace => commas = 0,
+            TokenKind::Comma if depth == 0 => commas += 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The label, parameter ranges, and doc comment of the callee written as
+/// `callee` (no parameter active yet): a `.funi` signature, a def, a constructor, or a builtin, in
+/// that order of lookup.
+fn resolve(
+    project: &Project,
+    current_module: &str,
+    scopes: &[&str],
+    callee: &str,
+) -> Option<SignatureHelp> {
+    let module = &project.module;
+    let (qualifier, name) = match callee.rsplit_once('.') {
+        Some((qualifier, name)) => (Some(qualifier), name),
+        None => (None, callee),
+    };
+    // The canonical names `callee` can mean here, most specific first.
+    let candidates: Vec<String> = match qualifier {
+        Some(qualifier) => {
+            let canonical = canonical_qualifier(project, current_module, qualifier)?;
+            vec![if canonical.is_empty() {
+                name.to_string()
+            } else {
+                format!("{canonical}.{name}")
+            }]
+        }
+        None => scopes
+            .iter()
+            .map(|scope| {
+                if *scope == project.entry {
+                    name.to_string()
+                } else {
+                    format!("{scope}.{name}")
+                }
+            })
+            .collect(),
+    };
+    let docs = |span| crate::docs::doc_comment(&project.sources, span);
+
+    for canonical in &candidates {
+        if let Some(sig) = module.signatures.iter().find(|sig| sig.name == *canonical) {
+            let params: Vec<(Option<&str>, String)> = match sig.ty.name.as_str() {
+                "=>" => sig.ty.args[..sig.ty.args.len() - 1]
+                    .iter()
+                    .map(|ty| (None, type_name_text(ty)))
+                    .collect(),
+                _ => return None,
+            };
+            let ret = type_name_text(sig.ty.args.last()?);
+            return Some(layout(&sig.name, &params, &ret, docs(sig.span)));
+        }
+        if let Some(def) = module.defs.iter().find(|def| def.name == *canonical) {
+            let (_, types) = project.check_with_types();
+            let Some(Type::Fn(param_types, ret)) = types.expr(def.value.id) else {
+                return None;
+            };
+            let names: Vec<Option<&str>> = match &def.value.kind {
+                ExprKind::Lambda { params, .. } => {
+                    params.iter().map(|p| Some(p.name.as_str())).collect()
+                }
+                _ => vec![None; param_types.len()],
+            };
+            let params: Vec<(Option<&str>, String)> = names
+                .into_iter()
+                .zip(param_types.iter().map(Type::to_string))
+                .collect();
+            return Some(layout(&def.name, &params, &ret.to_string(), docs(def.span)));
+        }
+    }
+
+    // Constructors resolve bare project-wide; a qualified one by name.
+    for ty in &module.types {
+        let TypeBody::Variants(variants) = &ty.body else {
+            continue;
+        };
+        let found = variants.iter().find(|variant| match qualifier {
+            Some(_) => candidates.contains(&variant.name),
+            None => {
+                variant.name == name
+                    || (variant.name.rsplit('.').next() == Some(name)
+                        && scopes.contains(&owning_module(&variant.name, &project.entry)))
+            }
+        });
+        if let Some(variant) = found {
+            if variant.fields.is_empty() {
+                return None;
+            }
+            let params: Vec<(Option<&str>, String)> = variant
+                .fields
+                .iter()
+                .map(|field| (Some(field.name.as_str()), type_name_text(&field.ty)))
+                .collect();
+            return Some(layout(
+                &variant.name,
+                &params,
+                &ctor_return(ty),
+                docs(variant.span),
+            ));
+        }
+    }
+
+    let builtin = ALL_BUILTINS.iter().find(|b| builtin_name(**b) == callee)?;
+    let Type::Fn(param_types, ret) = builtin_signature(*builtin) else {
+        return None;
+    };
+    let params: Vec<(Option<&str>, String)> = param_types
+        .iter()
+        .map(|ty| (None, ty.to_string()))
+        .collect();
+    Some(layout(callee, &params, &ret.to_string(), None))
+}
+
+/// Lay out `name : (p: T, …) => ret`, recording each parameter's range.
+fn layout(
+    name: &str,
+    params: &[(Option<&str>, String)],
+    ret: &str,
+    doc: Option<String>,
+) -> SignatureHelp {
+    let mut label = format!("{name} : (");
+    let mut ranges = Vec::new();
+    for (i, (param, ty)) in params.iter().enumerate() {
+        if i > 0 {
+            label.push_str(", ");
+        }
+        let start = label.len();
+        match param {
+            Some(param) => label.push_str(&format!("{param}: {ty}")),
+            None => label.push_str(ty),
+        }
+        ranges.push((start, label.len()));
+    }
+    label.push_str(&format!(") => {ret}"));
+    SignatureHelp {
+        label,
+        params: ranges,
+        active: None,
+        piped: None,
+        doc,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(src: &str) -> Project {
+        crate::project::load_single_source("Main", src).unwrap_or_else(|e| panic!("{}", e.message))
+    }
+
+    /// Help at the end of `live`, against `src` loaded as the last-good
+    /// project: the label with the active parameter bracketed.
+    fn help(src: &str, live: &str) -> Option<String> {
+        let project = project(src);
+        let help = signature_help(&project, "Main", None, live, live.len())?;
+        let mut label = help.label.clone();
+        if let Some(active) = help.active {
+            let (start, end) = help.params[active];
+            label.insert(end, ']');
+            label.insert(start, '[');
+        }
+        if let Some(piped) = help.piped {
+            label.push_str(&format!(" (piped: {piped})"));
+        }
+        Some(label)
+    }
+
+    const SRC: &str = "// Clamp v into [lo, hi].\n\
+let clamp = (v: float, lo: float, hi: float): float => v\n\
+type Shape = | Circle(radius: float) | Point\n";
+
+    #[test]
+    fn a_def_call_highlights_the_argument_under_the_cursor() {
+        assert_eq!(
+            help(SRC, "let x = clamp(").as_deref(),
+            Some("clamp : ([v: float], lo: float, hi: float) => float")
+        );
+        assert_eq!(
+            help(SRC, "let x = clamp(1.0, [2.0, 3.0], ").as_deref(),
+            Some("clamp : (v: float, lo: float, [hi: float]) => float")
+        );
+        // Past the last parameter nothing is active.
+        assert_eq!(
+            help(SRC, "let x = clamp(1.0, 2.0, 3.0, ").as_deref(),
+            Some("clamp : (v: float, lo: float, hi: float) => float")
+        );
+    }
+
+    #[test]
+    fn a_def_carries_its_doc_comment() {
+        let project = project(SRC);
+        let help = signature_help(&project, "Main", None, "clamp(", 6).unwrap();
+        assert_eq!(help.doc.as_deref(), Some("Clamp v into [lo, hi]."));
+    }
+
+    #[test]
+    fn nested_calls_answer_for_the_innermost() {
+        assert_eq!(
+            help(SRC, "let x = clamp(Circle(").as_deref(),
+            Some("Circle : ([radius: float]) => Shape")
+        );
+        assert_eq!(
+            help(SRC, "let x = clamp(Circle(1.0), ").as_deref(),
+            Some("clamp : (v: float, [lo: float], hi: float) => float")
+        );
+    }
+
+    #[test]
+    fn a_pipeline_stage_supplies_the_last_parameter() {
+        assert_eq!(
+            help(SRC, "let x = 1.0 |> clamp(0.0, ").as_deref(),
+            Some("clamp : (v: float, [lo: float], hi: float) => float (piped: 2)")
+        );
+        assert_eq!(
+            help(SRC, "let x = xs |> List.map(").as_deref(),
+            Some("List.map : ([('a) => 'b], List<'a>) => List<'b> (piped: 1)")
+        );
+    }
+
+    #[test]
+    fn no_help_outside_a_call_or_for_nullary_constructors() {
+        assert_eq!(help(SRC, "let x = clamp(1.0, 2.0, 3.0) + "), None);
+        assert_eq!(help(SRC, "let x = (a, "), None);
+        assert_eq!(help(SRC, "let x = Point("), None);
+    }
+}
@@ -0,0 +1,892 @@
+//! Golden values for `expectSnapshot` tests (see
+//! [`crate::ast::ExpectKind::Snapshot`]).
+//!
+//! A snapshot's value is DUMPED to a [`Json`] tree — plain data in the shape
+//! of the engine's canonical `EffectValue` JSON (`{"Record": [["x",
+//! {"Number": 1.0}]]}`), and a host value (a `Frame`, a `Scene`) as whatever
+//! structural dump its [`crate::HostData::snapshot`] gives — and compared
+//! with the dump stored under its name in `__snapshots__/<file>.json`, beside
+//! the module that declares it. A mismatch is reported as a list of
+//! [`Change`]s, each at a path through the value
+//! (`frame.scene.children[2].transform[12]: expected 1.0, got 2.0`), rather
+//! than the bare "not equal" a `Frame.equals` expect can give.
+//!
+//! A snapshot with nothing stored yet passes, and [`save`] records it; a
+//! stored one that differs fails until the run is told to update
+//! ([`Goldens::update`], `functor test --update-snapshots`), which rewrites
+//! it. The interpreter never touches the filesystem: callers [`load`] the
+//! stored dumps into the run's options and [`save`] its reports afterwards.
+//! No serde here (the crate has no dependencies), so the JSON reader and
+//! writer are the small hand-rolled pair below.
+
+use crate::ast::ExpectKind;
+use crate::eval::ExpectReport;
+use crate::ir::Module;
+use crate::span::Span;
+use crate::value::{MapKey, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// How deep a dump (or a stored file) may nest. Far beyond any real frame —
+/// the bound only keeps a pathological value from exhausting the stack.
+const MAX_DEPTH: usize = 256;
+
+/// How many [`Change`]s a [`SnapshotDiff`] keeps; the rest are counted.
+pub const MAX_CHANGES: usize = 20;
+
+/// A JSON document. Objects keep their key order, so a dump reads in the
+/// order its value was built.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    /// A number written without a fraction or exponent — kept exact.
+    Int(i64),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// A single-key object, the externally tagged shape every plain-data
+    /// dump uses (`{"Number": 1.0}`).
+    pub fn tagged(tag: &str, value: Json) -> Json {
+        Json::Object(vec![(tag.to_string(), value)])
+    }
+
+    /// Parse one JSON document (surrounding whitespace allowed).
+    pub fn parse(src: &str) -> Result<Json, String> {
+        let mut reader = Reader { src, pos: 0 };
+        let json = reader.value(0)?;
+        reader.skip_ws();
+        if reader.pos != src.len() {
+            return Err(reader.error("trailing characters after the document"));
+        }
+        Ok(json)
+    }
+
+    /// One line, spaced for reading: `{"x": 1.0, "y": [1, 2]}`.
+    pub fn compact(&self) -> String {
+        let mut out = String::new();
+        self.write_compact(&mut out);
+        out
+    }
+
+    fn is_scalar(&self) -> bool {
+        !matches!(self, Json::Array(_) | Json::Object(_))
+    }
+
+    fn write_compact(&self, out: &mut String) {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Json::Int(n) => {
+                let _ = write!(out, "{n}");
+            }
+            // `Debug` always writes a `.` or an exponent (`2.0`, `1e21`), so
+            // a float reads back as a float, never as an `Int`.
+            Json::Number(n) => {
+                let _ = write!(out, "{n:?}");
+            }
+            Json::String(s) => write_string(out, s),
+            Json::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    item.write_compact(out);
+                }
+                out.push(']');
+            }
+            Json::Object(fields) => {
+                out.push('{');
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    write_string(out, key);
+                    out.push_str(": ");
+                    value.write_compact(out);
+                }
+                out.push('}');
+            }
+        }
+    }
+
+    /// Two-space indented, one field or element per line — except arrays of
+    /// scalars (a transform, a color), which stay on one line.
+    fn write_pretty(&self, out: &mut String, indent: usize) {
+        match self {
+            Json::Array(items) if !items.is_empty() && !items.iter().all(Json::is_scalar) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    out.push_str(if i > 0 { ",\n" } else { "\n" });
+                    push_indent(out, indent + 1);
+                    item.write_pretty(out, indent + 1);
+                }
+                out.push('\n');
+                push_indent(out, indent);
+                out.push(']');
+            }
+            Json::Object(fields) if !fields.is_empty() => {
+                out.push('{');
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    out.push_str(if i > 0 { ",\n" } else { "\n" });
+                    push_indent(out, indent + 1);
+                    write_string(out, key);
+                    out.push_str(": ");
+                    value.write_pretty(out, indent + 1);
+                }
+                out.push('\n');
+                push_indent(out, indent);
+                out.push('}');
+            }
+            _ => self.write_compact(out),
+        }
+    }
+}
+
+/// The pretty (multi-line) rendering — the snapshot file format.
+impl std::fmt::Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        self.write_pretty(&mut out, 0);
+        f.write_str(&out)
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// The JSON reader behind [`Json::parse`].
+struct Reader<'s> {
+    src: &'s str,
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{message} at byte {}", self.pos)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_ws();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn literal(&mut self, word: &str, json: Json) -> Result<Json, String> {
+        if self.src[self.pos..].starts_with(word) {
+            self.pos += word.len();
+            Ok(json)
+        } else {
+            Err(self.error("expected a JSON value"))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json, String> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+        self.skip_ws();
+        match self.peek() {
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if !self.eat(b']') {
+                    loop {
+                        items.push(self.value(depth + 1)?);
+                        if self.eat(b']') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            return Err(self.error("expected `,` or `]`"));
+                        }
+                    }
+                }
+                Ok(Json::Array(items))
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                if !self.eat(b'}') {
+                    loop {
+                        self.skip_ws();
+                        if self.peek() != Some(b'"') {
+                            return Err(self.error("expected a string key"));
+                        }
+                        let key = self.string()?;
+                        if !self.eat(b':') {
+                            return Err(self.error("expected `:`"));
+                        }
+                        fields.push((key, self.value(depth + 1)?));
+                        if self.eat(b'}') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            return Err(self.error("expected `,` or `}`"));
+                        }
+                    }
+                }
+                Ok(Json::Object(fields))
+            }
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => Err(self.error("expected a JSON value")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+        while matches!(
+            self.peek(),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.pos += 1;
+        }
+        let text = &self.src[start..self.pos];
+        if !text.contains(['.', 'e', 'E']) {
+            if let Ok(n) = text.parse::<i64>() {
+                return Ok(Json::Int(n));
+            }
+        }
+        text.parse::<f64>()
+            .map(Json::Number)
+            .map_err(|_| format!("malformed number `{text}` at byte {start}"))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.pos += 1; // the opening quote
+        let mut out = String::new();
+        loop {
+            let Some(c) = self.src[self.pos..].chars().next() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(out),
+                '\\' => {
+                    let escape = self
+                        .peek()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    match escape {
+                        b'"' => out.push('"'),
+                        b'\\' => out.push('\\'),
+                        b'/' => out.push('/'),
+                        b'b' => out.push('\u{8}'),
+                        b'f' => out.push('\u{c}'),
+                        b'n' => out.push('\n'),
+                        b'r' => out.push('\r'),
+                        b't' => out.push('\t'),
+                        b'u' => {
+                            let high = self.hex4()?;
+                            let code = if (0xd800..0xdc00).contains(&high)
+                                && self.src[self.pos..].starts_with("\\u")
+                            {
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                0x10000
+                                    + ((high - 0xd800) << 10)
+                                    + (low.wrapping_sub(0xdc00) & 0x3ff)
+                            } else {
+                                high
+                            };
+                            out.push(
+                                char::from_u32(code)
+                                    .ok_or_else(|| self.error("invalid \\u escape"))?,
+                            );
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    }
+                }
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self
+            .src
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| self.error("short \\u escape"))?;
+        let code = u32::from_str_radix(digits, 16).map_err(|_| self.error("invalid \\u escape"))?;
+        self.pos += 4;
+        Ok(code)
+    }
+}
+
+// ------------------------------------------------------------------ dumping
+
+/// Dump a value for storage. Plain data takes the canonical `EffectValue`
+/// JSON shape; a host value is `{"Host": ["Frame", <its dump>]}`. Functions,
+/// host values without a dump, and non-finite numbers have no stable form
+/// and are errors.
+pub fn dump(value: &Value) -> Result<Json, String> {
+    dump_at(value, 0)
+}
+
+fn dump_at(value: &Value, depth: usize) -> Result<Json, String> {
+    if depth > MAX_DEPTH {
+        return Err("the value is nested too deeply to snapshot".to_string());
+    }
+    let items = |items: &mut dyn Iterator<Item = &Value>| -> Result<Json, String> {
+        Ok(Json::Array(
+            items
+                .map(|item| dump_at(item, depth + 1))
+                .collect::<Result<_, _>>()?,
+        ))
+    };
+    Ok(match value {
+        Value::Number(n) if !n.is_finite() => {
+            return Err(format!(
+                "a non-finite number ({n}) has no snapshot form — JSON cannot carry NaN/Infinity"
+            ))
+        }
+        Value::Number(n) => Json::tagged("Number", Json::Number(*n)),
+        Value::Int(n) => Json::tagged("Int", Json::Int(*n)),
+        Value::Bool(b) => Json::tagged("Bool", Json::Bool(*b)),
+        Value::String(s) => Json::tagged("Text", Json::String(s.to_string())),
+        Value::List(list) => Json::tagged("List", items(&mut list.iter())?),
+        Value::Array(list) => Json::tagged("Array", items(&mut list.iter())?),
+        Value::Tuple(list) => Json::tagged("Tuple", items(&mut list.iter())?),
+        Value::Map(entries) => Json::tagged(
+            "Map",
+            Json::Array(
+                entries
+                    .iter()
+                    .map(|(key, value)| {
+                        Ok(Json::Array(vec![dump_key(key), dump_at(value, depth + 1)?]))
+                    })
+                    .collect::<Result<_, String>>()?,
+            ),
+        ),
+        Value::Set(keys) => Json::tagged("Set", Json::Array(keys.iter().map(dump_key).collect())),
+        Value::Record(fields) => Json::tagged(
+            "Record",
+            Json::Array(
+                fields
+                    .iter()
+                    .map(|(name, value)| {
+                        Ok(Json::Array(vec![
+                            Json::String(name.clone()),
+                            dump_at(value, depth + 1)?,
+                        ]))
+                    })
+                    .collect::<Result<_, String>>()?,
+            ),
+        ),
+        Value::Variant { ctor, args } => Json::tagged(
+            "Variant",
+            Json::Array(vec![
+                Json::String(ctor.to_string()),
+                items(&mut args.iter())?,
+            ]),
+        ),
+        Value::HostData(data) => match data.snapshot() {
+            Some(json) => Json::tagged(
+                "Host",
+                Json::Array(vec![Json::String(data.type_name().to_string()), json]),
+            ),
+            None => {
+                return Err(format!(
+                    "a `{}` host value has no snapshot form",
+                    data.type_name()
+                ))
+            }
+        },
+        Value::Ctor { .. }
+        | Value::Closure(_)
+        | Value::Partial(_)
+        | Value::Builtin(_)
+        | Value::HostFn(_) => {
+            return Err(format!(
+                "a function ({value}) has no snapshot form — snapshot the data it produces"
+            ))
+        }
+    })
+}
+
+fn dump_key(key: &MapKey) -> Json {
+    match key {
+        MapKey::Bool(b) => Json::tagged("Bool", Json::Bool(*b)),
+        MapKey::Int(n) => Json::tagged("Int", Json::Int(*n)),
+        MapKey::Number(n) => Json::tagged("Number", Json::Number(*n)),
+        MapKey::String(s) => Json::tagged("Text", Json::String(s.to_string())),
+    }
+}
+
+// ------------------------------------------------------------------ diffing
+
+/// One structural difference between a stored dump and a fresh one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// Where, from the snapshot's name: `frame.scene.children[2]`.
+    pub path: String,
+    /// The stored side, rendered on one line; `None` when it is new.
+    pub expected: Option<String>,
+    /// The fresh side; `None` when it went away.
+    pub actual: Option<String>,
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.expected, &self.actual) {
+            (Some(expected), Some(actual)) => {
+                write!(f, "{}: expected {expected}, got {actual}", self.path)
+            }
+            (Some(expected), None) => write!(f, "{}: missing (was {expected})", self.path),
+            (None, Some(actual)) => write!(f, "{}: added {actual}", self.path),
+            (None, None) => write!(f, "{}: changed", self.path),
+        }
+    }
+}
+
+/// A snapshot whose fresh dump no longer matches the stored one.
+#[derive(Debug, Clone)]
+pub struct SnapshotDiff {
+    pub name: String,
+    /// The first [`MAX_CHANGES`] differences, in value order.
+    pub changes: Vec<Change>,
+    /// How many differences there are in all.
+    pub total: usize,
+}
+
+impl SnapshotDiff {
+    pub fn new(name: &str, expected: &Json, actual: &Json) -> SnapshotDiff {
+        let mut changes = Vec::new();
+        diff_at(name.to_string(), expected, actual, &mut changes);
+        let total = changes.len();
+        changes.truncate(MAX_CHANGES);
+        SnapshotDiff {
+            name: name.to_string(),
+            changes,
+            total,
+        }
+    }
+}
+
+impl std::fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = if self.total == 1 { "" } else { "s" };
+        write!(
+            f,
+            "snapshot `{}` changed, {} difference{plural} (`--update-snapshots` accepts it):",
+            self.name, self.total
+        )?;
+        for change in &self.changes {
+            write!(f, "\n  {change}")?;
+        }
+        if self.total > self.changes.len() {
+            write!(f, "\n  … and {} more", self.total - self.changes.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// A side of a [`Change`], on one line: a tagged scalar shows bare
+/// (`2.0`, not `{"Number": 2.0}`), and anything long is cut.
+fn brief(json: &Json) -> String {
+    let shown = match json {
+        Json::Object(fields) if fields.len() == 1 => match (fields[0].0.as_str(), &fields[0].1) {
+            ("Number" | "Int" | "Bool" | "Text", scalar) => scalar.compact(),
+            _ => json.compact(),
+        },
+        _ => json.compact(),
+    };
+    const LIMIT: usize = 80;
+    match shown.char_indices().nth(LIMIT) {
+        Some((cut, _)) => format!("{}…", &shown[..cut]),
+        None => shown,
+    }
+}
+
+/// The single tag of an externally tagged dump node, and its payload.
+fn tag(json: &Json) -> Option<(&str, &Json)> {
+    match json {
+        Json::Object(fields) if fields.len() == 1 => Some((&fields[0].0, &fields[0].1)),
+        _ => None,
+    }
+}
+
+/// `[[name, value], …]` pairs — a record's fields, a map's entries — with the
+/// first element rendered as the path key.
+fn pairs(json: &Json) -> Option<Vec<(String, &Json)>> {
+    let Json::Array(items) = json else {
+        return None;
+    };
+    items
+        .iter()
+        .map(|item| match item {
+            Json::Array(pair) if pair.len() == 2 => match &pair[0] {
+                Json::String(name) => Some((name.clone(), &pair[1])),
+                key => Some((brief(key), &pair[1])),
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn diff_at(path: String, expected: &Json, actual: &Json, out: &mut Vec<Change>) {
+    if expected == actual {
+        return;
+    }
+    match (expected, actual) {
+        // Same tag: descend by what the tag means — fields by name, entries
+        // by key, elements by index, a host value into its own dump.
+        _ if tag(expected).is_some() && tag(expected).map(|t| t.0) == tag(actual).map(|t| t.0) => {
+            let ((kind, e), (_, a)) = (tag(expected).unwrap(), tag(actual).unwrap());
+            match kind {
+                "List" | "Array" | "Tuple" => diff_items(&path, e, a, out),
+                "Record" => match (pairs(e), pairs(a)) {
+                    (Some(e), Some(a)) => diff_keyed(&path, ".", "", &e, &a, out),
+                    _ => leaf(path, expected, actual, out),
+                },
+                "Map" => match (pairs(e), pairs(a)) {
+                    (Some(e), Some(a)) => diff_keyed(&path, "[", "]", &e, &a, out),
+                    _ => leaf(path, expected, actual, out),
+                },
+                "Variant" | "Host" => match (e, a) {
+                    (Json::Array(e), Json::Array(a))
+                        if e.len() == 2 && a.len() == 2 && e[0] == a[0] =>
+                    {
+                        if kind == "Variant" {
+                            diff_items(&path, &e[1], &a[1], out)
+                        } else {
+                            diff_at(path, &e[1], &a[1], out)
+                        }
+                    }
+                    _ => leaf(path, expected, actual, out),
+                },
+                // Would-be EffectValue tags aside, a one-key object from a
+                // host dump descends by its key like any other object.
+                "Number" | "Int" | "Bool" | "Text" | "Set" => leaf(path, expected, actual, out),
+                _ => diff_at(format!("{path}.{kind}"), e, a, out),
+            }
+        }
+        (Json::Object(e), Json::Object(a)) => {
+            let e: Vec<(String, &Json)> = e.iter().map(|(k, v)| (k.clone(), v)).collect();
+            let a: Vec<(String, &Json)> = a.iter().map(|(k, v)| (k.clone(), v)).collect();
+            diff_keyed(&path, ".", "", &e, &a, out)
+        }
+        (Json::Array(_), Json::Array(_)) => diff_items(&path, expected, actual, out),
+        _ => leaf(path, expected, actual, out),
+    }
+}
+
+fn leaf(path: String, expected: &Json, actual: &Json, out: &mut Vec<Change>) {
+    out.push(Change {
+        path,
+        expected: Some(brief(expected)),
+        actual: Some(brief(actual)),
+    });
+}
+
+/// Elements pair by index; a length change is reported per extra element.
+fn diff_items(path: &str, expected: &Json, actual: &Json, out: &mut Vec<Change>) {
+    let (Json::Array(e), Json::Array(a)) = (expected, actual) else {
+        return leaf(path.to_string(), expected, actual, out);
+    };
+    for (i, (e, a)) in e.iter().zip(a).enumerate() {
+        diff_at(format!("{path}[{i}]"), e, a, out);
+    }
+    for (i, e) in e.iter().enumerate().skip(a.len()) {
+        out.push(Change {
+            path: format!("{path}[{i}]"),
+            expected: Some(brief(e)),
+            actual: None,
+        });
+    }
+    for (i, a) in a.iter().enumerate().skip(e.len()) {
+        out.push(Change {
+            path: format!("{path}[{i}]"),
+            expected: None,
+            actual: Some(brief(a)),
+        });
+    }
+}
+
+/// Members pair by key (`open`/`close` wrap it in the path): stored keys in
+/// stored order, then keys only the fresh dump has.
+fn diff_keyed(
+    path: &str,
+    open: &str,
+    close: &str,
+    expected: &[(String, &Json)],
+    actual: &[(String, &Json)],
+    out: &mut Vec<Change>,
+) {
+    let at = |key: &str| format!("{path}{open}{key}{close}");
+    for (key, e) in expected {
+        match actual.iter().find(|(k, _)| k == key) {
+            Some((_, a)) => diff_at(at(key), e, a, out),
+            None => out.push(Change {
+                path: at(key),
+                expected: Some(brief(e)),
+                actual: None,
+            }),
+        }
+    }
+    for (key, a) in actual {
+        if !expected.iter().any(|(k, _)| k == key) {
+            out.push(Change {
+                path: at(key),
+                expected: None,
+                actual: Some(brief(a)),
+            });
+        }
+    }
+}
+
+// -------------------------------------------------------------------- files
+
+/// The stored dumps a run compares against, by the declaring
+/// `expectSnapshot`'s span start (the test's identity, like every expect).
+#[derive(Debug, Clone, Default)]
+pub struct Goldens {
+    pub stored: HashMap<usize, Json>,
+    /// Accept every fresh dump: nothing fails as changed, and [`save`]
+    /// rewrites what differs and drops names no test declares any more.
+    pub update: bool,
+}
+
+/// Where the snapshots of `source` live: `__snapshots__/<stem>.json` in the
+/// same directory.
+pub fn path_for(source: &Path) -> PathBuf {
+    let stem = source
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    source
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join("__snapshots__")
+        .join(format!("{stem}.json"))
+}
+
+/// Read a snapshot file: a JSON object from name to dump. A missing file is
+/// empty — nothing recorded yet.
+pub fn read_file(path: &Path) -> Result<BTreeMap<String, Json>, String> {
+    let src = match std::fs::read_to_string(path) {
+        Ok(src) => src,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(format!("{}: {err}", path.display())),
+    };
+    match Json::parse(&src) {
+        Ok(Json::Object(fields)) => Ok(fields.into_iter().collect()),
+        Ok(_) => Err(format!(
+            "{}: a snapshot file is a JSON object of named values",
+            path.display()
+        )),
+        Err(err) => Err(format!(
+            "{}: malformed snapshot file: {err}",
+            path.display()
+        )),
+    }
+}
+
+/// A snapshot file's text: names sorted, so rewrites diff cleanly.
+pub fn render_file(entries: &BTreeMap<String, Json>) -> String {
+    let object = Json::Object(
+        entries
+            .iter()
+            .map(|(name, json)| (name.clone(), json.clone()))
+            .collect(),
+    );
+    format!("{object}\n")
+}
+
+/// The module's snapshot tests with the file each belongs to, resolved by
+/// `file_of` (`None` skips one — an engine-bundled module has no file).
+fn snapshot_files<'m>(
+    module: &'m Module,
+    file_of: &dyn Fn(Span) -> Option<PathBuf>,
+) -> Vec<(usize, &'m str, PathBuf)> {
+    module
+        .expects
+        .iter()
+        .enumerate()
+        .filter_map(|(i, expect)| match &expect.kind {
+            ExpectKind::Snapshot(name) => {
+                Some((i, name.as_str(), path_for(&file_of(expect.span)?)))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Read the stored dumps for every `expectSnapshot` in `module`.
+pub fn load(
+    module: &Module,
+    file_of: &dyn Fn(Span) -> Option<PathBuf>,
+    update: bool,
+) -> Result<Goldens, String> {
+    let mut files: HashMap<PathBuf, BTreeMap<String, Json>> = HashMap::new();
+    let mut stored = HashMap::new();
+    for (i, name, path) in snapshot_files(module, file_of) {
+        if !files.contains_key(&path) {
+            files.insert(path.clone(), read_file(&path)?);
+        }
+        if let Some(json) = files[&path].get(name) {
+            stored.insert(module.expects[i].span.start, json.clone());
+        }
+    }
+    Ok(Goldens { stored, update })
+}
+
+/// Record a run's fresh dumps: a snapshot with nothing stored is written,
+/// and under [`Goldens::update`] so is every one that changed (and names no
+/// test declares are dropped). A snapshot that errored keeps its stored
+/// dump, and so does one the run did not select (`reports` are
+/// [`crate::run_expects_with`]'s, matched to their tests by span). Returns
+/// how many snapshots were written.
+pub fn save(
+    module: &Module,
+    reports: &[ExpectReport],
+    goldens: &Goldens,
+    file_of: &dyn Fn(Span) -> Option<PathBuf>,
+) -> Result<usize, String> {
+    // Per file: its entries, the names still declared, and whether to write.
+    type Pending<'m> = (BTreeMap<String, Json>, Vec<&'m str>, bool);
+    let mut files: BTreeMap<PathBuf, Pending> = BTreeMap::new();
+    let mut written = 0;
+    for (i, name, path) in snapshot_files(module, file_of) {
+        if !files.contains_key(&path) {
+            files.insert(path.clone(), (read_file(&path)?, Vec::new(), false));
+        }
+        let (entries, live, changed) = files.get_mut(&path).expect("inserted above");
+        live.push(name);
+        let span = module.expects[i].span;
+        let fresh = reports.iter().find(|report| report.span == span);
+        let Some(fresh) = fresh.and_then(|report| report.snapshot.as_ref()) else {
+            continue;
+        };
+        let record = match entries.get(name) {
+            None => true,
+            Some(stored) => goldens.update && stored != fresh,
+        };
+        if record {
+            entries.insert(name.to_string(), fresh.clone());
+            *changed = true;
+            written += 1;
+        }
+    }
+    for (path, (mut entries, live, mut changed)) in files {
+        if goldens.update {
+            let before = entries.len();
+            entries.retain(|name, _| live.contains(&name.as_str()));
+            changed |= entries.len() != before;
+        }
+        if !changed {
+            continue;
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+        }
+        std::fs::write(&path, render_file(&entries))
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_roundtrips_through_its_pretty_form() {
+        let json = Json::Object(vec![
+            ("n".to_string(), Json::Number(2.0)),
+            ("i".to_string(), Json::Int(-3)),
+            ("big".to_string(), Json::Number(1e21)),
+            ("s".to_string(), Json::String("a \"q\"\n\u{1}é".to_string())),
+            (
+                "xs".to_string(),
+                Json::Array(vec![Json::Null, Json::Bool(true), Json::Array(vec![])]),
+            ),
+            ("o".to_string(), Json::Object(vec![])),
+        ]);
+        let text = json.to_string();
+        assert_eq!(Json::parse(&text), Ok(json.clone()));
+        assert_eq!(Json::parse(&json.compact()), Ok(json));
+        assert!(Json::parse("[1, 2").is_err());
+        assert!(Json::parse("{} x").is_err());
+    }
+
+    #[test]
+    fn scalar_arrays_stay_on_one_line() {
+        let json = Json::Object(vec![(
+            "m".to_string(),
+            Json::Array(vec![Json::Number(1.0), Json::Number(0.5)]),
+        )]);
+        assert_eq!(json.to_string(), "{\n  \"m\": [1.0, 0.5]\n}");
+    }
+
+    #[test]
+    fn diffs_name_the_path_to_each_change() {
+        let record = |x: f64, tail: Vec<Json>| {
+            Json::tagged(
+                "Record",
+                Json::Array(vec![
+                    Json::Array(vec![
+                        Json::String("x".to_string()),
+                        Json::tagged("Number", Json::Number(x)),
+                    ]),
+                    Json::Array(vec![
+                        Json::String("xs".to_string()),
+                        Json::tagged("List", Json::Array(tail)),
+                    ]),
+                ]),
+            )
+        };
+        let one = || Json::tagged("Int", Json::Int(1));
+        let diff = SnapshotDiff::new(
+            "p",
+            &record(1.0, vec![one()]),
+            &record(2.0, vec![one(), one()]),
+        );
+        let lines: Vec<String> = diff.changes.iter().map(Change::to_string).collect();
+        assert_eq!(lines, ["p.x: expected 1.0, got 2.0", "p.xs[1]: added 1"]);
+    }
+}
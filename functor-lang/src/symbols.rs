@@ -0,0 +1,362 @@
+//! Document and workspace symbols: the outline of a file (its lets, types
+//! with their fields and constructors, inline modules, `.funi` signatures,
+//! and `expect`s) and a name search across a project's files — the
+//! language-aware half of the LSP's `textDocument/documentSymbol` and
+//! `workspace/symbol`, and the sandbox editor's outline.
+//!
+//! Both read the AST, not the IR: an outline follows the file as written
+//! (nesting, declaration order, expects) and a file that parses but fails to
+//! check still has one.
+
+use crate::ast::{ExpectKind, ExprKind, Item, Program, TypeBody};
+use crate::parser::{parse_interface_with_base, parse_with_base};
+use crate::project::Project;
+use crate::span::Span;
+use crate::ParseError;
+
+/// What a symbol declares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// An inline `module Name { … }`.
+    Module,
+    /// A `let` bound to a lambda, or a `.funi` signature of function type.
+    Function,
+    /// Any other `let` or signature.
+    Value,
+    /// A record `type`.
+    Record,
+    /// A variant `type`.
+    Variant,
+    /// An abstract or host `type`.
+    Type,
+    /// One `| Ctor` of a variant type.
+    Constructor,
+    /// One field of a record type.
+    Field,
+    /// An `expect` inline test.
+    Test,
+}
+
+/// One entry of a file's outline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentSymbol {
+    pub name: String,
+    /// A one-line summary: a function's parameters, a field's type, a
+    /// type's parameters.
+    pub detail: Option<String>,
+    pub kind: SymbolKind,
+    /// The whole declaration.
+    pub span: Span,
+    /// The declared name within `span` (the whole span for an `expect`).
+    pub selection: Span,
+    pub children: Vec<DocumentSymbol>,
+}
+
+/// One match of a workspace search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The module (or inline module) declaring it: `Utils`, `Utils.Grid`.
+    pub container: String,
+    /// The declared name, project-wide (see [`crate::project::SourceMap`]).
+    pub span: Span,
+}
+
+/// The outline of one source text; `interface` parses it as a `.funi`.
+/// Spans are local to `src`.
+pub fn document_symbols(src: &str, interface: bool) -> Result<Vec<DocumentSymbol>, ParseError> {
+    let program = parse(src, interface, 0)?;
+    Ok(outline(src, 0, &program.items))
+}
+
+/// Every declaration in `project`'s own files (not the bundled modules)
+/// whose name matches `query`: a case-insensitive subsequence match, so
+/// `clmp` finds `clamp`; an empty query matches everything. Expects have no
+/// name to search for and are left out.
+pub fn workspace_symbols(project: &Project, query: &str) -> Vec<WorkspaceSymbol> {
+    let mut found = Vec::new();
+    for file in project.sources.files() {
+        if file.path.to_string_lossy().starts_with('<') {
+            continue;
+        }
+        let Ok(program) = parse(&file.src, file.interface, file.base) else {
+            continue;
+        };
+        let symbols = outline(&file.src, file.base, &program.items);
+        collect(&symbols, &file.module, query, &mut found);
+    }
+    found
+}
+
+fn parse(src: &str, interface: bool, base: usize) -> Result<Program, ParseError> {
+    if interface {
+        parse_interface_with_base(src, base)
+    } else {
+        parse_with_base(src, base)
+    }
+}
+
+fn collect(
+    symbols: &[DocumentSymbol],
+    container: &str,
+    query: &str,
+    out: &mut Vec<WorkspaceSymbol>,
+) {
+    for symbol in symbols {
+        if symbol.kind == SymbolKind::Test {
+            continue;
+        }
+        if matches(&symbol.name, query) {
+            out.push(WorkspaceSymbol {
+                name: symbol.name.clone(),
+                kind: symbol.kind,
+                container: container.to_string(),
+                span: symbol.selection,
+            });
+        }
+        let inner = format!("{container}.{}", symbol.name);
+        collect(&symbol.children, &inner, query, out);
+    }
+}
+
+fn matches(name: &str, query: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|q| name.any(|c| c == q))
+}
+
+/// The symbols of `items`; `base` is `src`'s offset in the spans.
+fn outline(src: &str, base: usize, items: &[Item]) -> Vec<DocumentSymbol> {
+    let text = |span: Span| &src[span.start - base..span.end - base];
+    let name_in = |span: Span, name: &str| {
+        find_word(text(span), name)
+            .map(|at| Span::new(span.start + at, span.start + at + name.len()))
+            .unwrap_or(span)
+    };
+    let leaf = |name: &str, detail: Option<String>, kind, span: Span| DocumentSymbol {
+        name: name.to_string(),
+        detail,
+        kind,
+        span,
+        selection: name_in(span, name),
+        children: Vec::new(),
+    };
+    let mut symbols = Vec::new();
+    for item in items {
+        match item {
+            Item::Let(decl) => {
+                let (kind, detail) = match &decl.value.kind {
+                    ExprKind::Lambda { params, ret, .. } => {
+                        let params: Vec<String> = params
+                            .iter()
+                            .map(|p| match &p.ty {
+                                Some(ty) => format!("{}: {}", p.name, text(ty.span)),
+                                None => p.name.clone(),
+                            })
+                            .collect();
+                        let ret = ret.as_ref().map(|ty| format!(": {}", text(ty.span)));
+                        (
+                            SymbolKind::Function,
+                            Some(format!(
+                                "({}){}",
+                                params.join(", "),
+                                ret.unwrap_or_default()
+                            )),
+                        )
+                    }
+                    _ => (
+                        SymbolKind::Value,
+                        decl.ty.as_ref().map(|ty| text(ty.span).to_string()),
+                    ),
+                };
+                symbols.push(leaf(&decl.name, detail, kind, decl.span));
+            }
+            Item::Sig(decl) => {
+                let kind = match decl.ty.name.as_str() {
+                    "=>" => SymbolKind::Function,
+                    _ => SymbolKind::Value,
+                };
+                symbols.push(leaf(
+                    &decl.name,
+                    Some(text(decl.ty.span).to_string()),
+                    kind,
+                    decl.span,
+                ));
+            }
+            Item::Type(decl) => {
+                let detail =
+                    (!decl.params.is_empty()).then(|| format!("<{}>", decl.params.join(", ")));
+                let (kind, children) = match &decl.body {
+                    TypeBody::Record(fields) => (
+                        SymbolKind::Record,
+                        fields
+                            .iter()
+                            .map(|f| {
+                                leaf(
+                                    &f.name,
+                                    Some(text(f.ty.span).to_string()),
+                                    SymbolKind::Field,
+                                    f.span,
+                                )
+                            })
+                            .collect(),
+                    ),
+                    TypeBody::Variants(variants) => (
+                        SymbolKind::Variant,
+                        variants
+                            .iter()
+                            .map(|v| {
+                                let fields: Vec<&str> =
+                                    v.fields.iter().map(|f| text(f.ty.span)).collect();
+                                let detail = (!fields.is_empty())
+                                    .then(|| format!("({})", fields.join(", ")));
+                                leaf(&v.name, detail, SymbolKind::Constructor, v.span)
+                            })
+                            .collect(),
+                    ),
+                    TypeBody::Abstract | TypeBody::Host => (SymbolKind::Type, Vec::new()),
+                };
+                symbols.push(DocumentSymbol {
+                    children,
+                    ..leaf(&decl.name, detail, kind, decl.span)
+                });
+            }
+            Item::Module(decl) => symbols.push(DocumentSymbol {
+                name: decl.name.clone(),
+                detail: None,
+                kind: SymbolKind::Module,
+                span: decl.block,
+                selection: name_in(decl.span, &decl.name),
+                children: outline(src, base, &decl.items),
+            }),
+            Item::Expect(decl) => {
+                let body = text(decl.expr.span)
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let keyword = match &decl.kind {
+                    ExpectKind::Example => "expect".to_string(),
+                    ExpectKind::Property => "property".to_string(),
+                    ExpectKind::Snapshot(name) => format!("expectSnapshot {name} ="),
+                };
+                symbols.push(DocumentSymbol {
+                    name: format!("{keyword} {body}"),
+                    detail: None,
+                    kind: SymbolKind::Test,
+                    span: decl.span,
+                    selection: decl.span,
+                    children: Vec::new(),
+                });
+            }
+            Item::Open(_) | Item::Unit(_) | Item::UnitOp(_) => {}
+        }
+    }
+    symbols
+}
+
+/// The first whole-word occurrence of `word` in `text` (skipping the
+/// keyword before it, so `let let_x` selects `let_x`).
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word).map(|(at, _)| at).find(|&at| {
+        let before = text[..at].chars().next_back();
+        let after = text[at + word.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `kind name [detail]` lines, children indented.
+    fn render(symbols: &[DocumentSymbol], depth: usize, out: &mut String) {
+        for symbol in symbols {
+            out.push_str(&format!(
+                "{}{:?} {}",
+                "  ".repeat(depth),
+                symbol.kind,
+                symbol.name
+            ));
+            if let Some(detail) = &symbol.detail {
+                out.push_str(&format!(" [{detail}]"));
+            }
+            out.push('\n');
+            render(&symbol.children, depth + 1, out);
+        }
+    }
+
+    fn outline_of(src: &str, interface: bool) -> String {
+        let mut out = String::new();
+        render(&document_symbols(src, interface).unwrap(), 0, &mut out);
+        out
+    }
+
+    #[test]
+    fn outlines_lets_types_modules_and_expects() {
+        let src = "type Shape = | Circle(radius: float) | Point\n\
+type Model = { score: int, pos: Vec3.t }\n\
+let speed: float = 2.0\n\
+let clamp = (v: float, lo, hi): float => v\n\
+module Grid {\n  let cell = 1.0\n  type Box<'a> = { item: 'a }\n}\n\
+expect clamp(1.0,\n  0.0, 2.0) == 1.0\n";
+        assert_eq!(
+            outline_of(src, false),
+            "Variant Shape\n  Constructor Circle [(float)]\n  Constructor Point\n\
+Record Model\n  Field score [int]\n  Field pos [Vec3.t]\n\
+Value speed [float]\n\
+Function clamp [(v: float, lo, hi): float]\n\
+Module Grid\n  Value cell\n  Record Box [<'a>]\n    Field item ['a]\n\
+Test expect clamp(1.0, 0.0, 2.0) == 1.0\n"
+        );
+    }
+
+    #[test]
+    fn outlines_interface_signatures() {
+        let src = "type t = host\nlet translate : (Vec3.t, t) => t\nlet origin : Vec3.t\n";
+        assert_eq!(
+            outline_of(src, true),
+            "Type t\nFunction translate [(Vec3.t, t) => t]\nValue origin [Vec3.t]\n"
+        );
+    }
+
+    #[test]
+    fn the_selection_is_the_declared_name() {
+        let src = "let let_x = 1\nmodule Grid {\n  let cell = 1.0\n}\n";
+        let symbols = document_symbols(src, false).unwrap();
+        let at = |span: Span| &src[span.start..span.end];
+        assert_eq!(at(symbols[0].selection), "let_x");
+        assert_eq!(symbols[0].selection.start, 4);
+        assert_eq!(at(symbols[1].selection), "Grid");
+        assert_eq!(at(symbols[1].span), "module Grid {\n  let cell = 1.0\n}");
+        assert_eq!(at(symbols[1].children[0].selection), "cell");
+    }
+
+    #[test]
+    fn workspace_search_matches_subsequences_across_modules() {
+        let project = crate::project::load_single_source(
+            "Main",
+            "let clamp = (v) => v\nlet color = 1\nmodule Grid {\n  let cell = 1.0\n}\nexpect clamp(1) == 1\n",
+        )
+        .unwrap_or_else(|e| panic!("{}", e.message));
+        let found: Vec<(String, String)> = workspace_symbols(&project, "CL")
+            .into_iter()
+            .map(|s| (s.container, s.name))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Main".to_string(), "clamp".to_string()),
+                ("Main".to_string(), "color".to_string()),
+                ("Main.Grid".to_string(), "cell".to_string()),
+            ]
+        );
+        // Bundled modules are not part of the workspace.
+        assert!(workspace_symbols(&project, "")
+            .iter()
+            .all(|s| s.container.starts_with("Main")));
+    }
+}
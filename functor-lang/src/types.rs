@@ -24,14 +24,16 @@
 //!
 //! ## The type language
 //!
-//! - Primitives `float`, `string`, `bool` (numbers are all `float`).
-//!   Lowercase — a capitalized `Float` is a diagnosed error, not a type.
+//! - Primitives `float`, `int`, `string`, `bool`. Whole-number literals are
+//!   `int`s and literals with a fractional part `float`s; the two never mix
+//!   implicitly (`Math.toFloat`/`Math.toInt` convert). Lowercase — a
+//!   capitalized `Float` is a diagnosed error, not a type.
 //! - `unknown` — the explicit dynamic seam, compatible with everything.
 //! - Declared record types (`type Position = { x: float, y: float }`) —
 //!   nominal, by name.
 //! - Declared variant types (`type Shape = | Circle(radius: float) | Point`)
 //!   — nominal, by name, like records.
-//! - `List<T>` and `Map<K, V>`.
+//! - `List<T>`, `Array<T>`, `Map<K, V>`, and `Set<T>`.
 //! - Function types, from lambda annotations
 //!   (`(a: float, b: float): float => …`); an unannotated return type is the
 //!   body's type when that is known (inferred in a single quiet enrichment
@@ -76,7 +78,8 @@
 //! [`check`] walks the whole module and returns **every** diagnostic, sorted
 //! by source position — it never stops at the first error.
 
-use crate::ast::{BinOp, TypeBody, TypeName};
+use crate::ast::{BinOp, ExpectKind, TypeBody, TypeName};
+use crate::exhaustive::{Con, Matrix, Pat};
 use crate::eval::{builtin, builtin_members, callee_label, Builtin, BUILTIN_NAMESPACES};
 use crate::ir::{
     BindingId, Expr, ExprId, ExprKind, Field, MatchArm, Module, Pattern, PatternKind, StringPart,
@@ -100,13 +103,20 @@ pub enum Type {
     /// use. Displays as `'a`, `'b`, … (normalized per top-level def).
     Var(u32),
     Float,
+    Int,
     String,
     Bool,
     List(Box<Type>),
-    /// An immutable keyed collection. Runtime keys are bounded to bool,
+    /// An indexable sequence (`Array.get`/`set` by int). A different type
+    /// from `List` even though the runtime shares the representation.
+    Array(Box<Type>),
+    /// An immutable keyed collection. Runtime keys are bounded to bool, int,
     /// finite float, and string; inference keeps a map's key type
     /// homogeneous in ordinary (non-`unknown`) code.
     Map(Box<Type>, Box<Type>),
+    /// An immutable set, its elements bounded exactly like [`Type::Map`]
+    /// keys.
+    Set(Box<Type>),
     /// A product type: `Float * Float` in annotations. Structural, like the
     /// runtime.
     Tuple(Vec<Type>),
@@ -129,10 +139,13 @@ impl fmt::Display for Type {
             // (see `Checker::zonk_normalized`).
             Type::Var(v) => write!(f, "'{}", var_name(*v)),
             Type::Float => write!(f, "float"),
+            Type::Int => write!(f, "int"),
             Type::String => write!(f, "string"),
             Type::Bool => write!(f, "bool"),
             Type::List(elem) => write!(f, "List<{elem}>"),
+            Type::Array(elem) => write!(f, "Array<{elem}>"),
             Type::Map(key, value) => write!(f, "Map<{key}, {value}>"),
+            Type::Set(elem) => write!(f, "Set<{elem}>"),
             Type::Tuple(elems) => {
                 write!(f, "(")?;
                 for (i, elem) in elems.iter().enumerate() {
@@ -200,10 +213,13 @@ pub fn compatible(a: &Type, b: &Type) -> bool {
         // An unsolved variable is compatible with anything at this level of
         // scrutiny (the unifier is where variables get COMMITTED).
         (Type::Var(_), _) | (_, Type::Var(_)) => true,
-        (Type::Float, Type::Float) | (Type::String, Type::String) | (Type::Bool, Type::Bool) => {
-            true
-        }
-        (Type::List(x), Type::List(y)) => compatible(x, y),
+        (Type::Float, Type::Float)
+        | (Type::Int, Type::Int)
+        | (Type::String, Type::String)
+        | (Type::Bool, Type::Bool) => true,
+        (Type::List(x), Type::List(y))
+        | (Type::Array(x), Type::Array(y))
+        | (Type::Set(x), Type::Set(y)) => compatible(x, y),
         (Type::Map(xk, xv), Type::Map(yk, yv)) => compatible(xk, yk) && compatible(xv, yv),
         (Type::Tuple(xs), Type::Tuple(ys)) => {
             xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| compatible(x, y))
@@ -221,6 +237,20 @@ pub fn compatible(a: &Type, b: &Type) -> bool {
     }
 }
 
+/// The argument count of a `Math` function that takes ints as well as
+/// floats (see [`Checker::number_math_call`]), or `None` for any other
+/// callee.
+fn int_math_arity(callee: &Expr) -> Option<usize> {
+    let ExprKind::External(path) = &callee.kind else {
+        return None;
+    };
+    match builtin(path)? {
+        Builtin::MathAbs => Some(1),
+        Builtin::MathMin | Builtin::MathMax | Builtin::MathMod => Some(2),
+        _ => None,
+    }
+}
+
 /// The nominal name a BRAND could be declared on — a declared record or
 /// variant type (an opaque `type t` is a variant with no constructors, which
 /// is exactly what `Angle.t` is). Everything else can carry no unit operator.
@@ -231,12 +261,13 @@ fn brand_name(ty: &Type) -> Option<String> {
     }
 }
 
-/// The type an operator's SECOND operand takes on a given brand: a plain
-/// float for the scalar `*` and `/`, and the brand itself for everything else
+/// The type an operator's SECOND operand takes on a given brand: the brand's
+/// plain `scalar` (`float`, or `int` for a brand counted in whole numbers)
+/// for the scalar `*` and `/`, and the brand itself for everything else
 /// (`+`/`-` stay inside the brand, and a comparison compares like with like).
-fn operand_type(op: BinOp, brand: &Type) -> Type {
+fn operand_type(op: BinOp, brand: &Type, scalar: &Type) -> Type {
     match op {
-        BinOp::Mul | BinOp::Div => Type::Float,
+        BinOp::Mul | BinOp::Div => scalar.clone(),
         _ => brand.clone(),
     }
 }
@@ -267,11 +298,10 @@ fn contains_fn(ty: &Type) -> bool {
     }
 }
 
-/// Every variable binding a pattern introduces (shallow — the pattern
-/// language has no nesting beyond ctor/tuple args).
-fn pattern_var_bindings(pattern: &Pattern, f: &mut impl FnMut(u32)) {
+/// Every variable binding a pattern introduces, at any depth.
+fn pattern_var_bindings(pattern: &Pattern, f: &mut impl FnMut(u32, &str)) {
     match &pattern.kind {
-        PatternKind::Var { binding, .. } => f(binding.0),
+        PatternKind::Var { binding, name } => f(binding.0, name),
         PatternKind::Ctor { args, .. } | PatternKind::Tuple(args) => {
             for arg in args {
                 pattern_var_bindings(arg, f);
@@ -285,28 +315,32 @@ fn pattern_var_bindings(pattern: &Pattern, f: &mut impl FnMut(u32)) {
                 pattern_var_bindings(tail, f);
             }
         }
+        PatternKind::Record(fields) => {
+            for field in fields {
+                pattern_var_bindings(&field.pattern, f);
+            }
+        }
+        // Every alternative binds the same ids; the first is canonical.
+        PatternKind::Or(alternatives) => pattern_var_bindings(&alternatives[0], f),
         PatternKind::Wildcard
         | PatternKind::Number(_)
+        | PatternKind::Int(_)
         | PatternKind::Bool(_)
         | PatternKind::String(_) => {}
     }
 }
 
-/// Is `pattern` (a tuple/ctor sub-pattern) irrefutable — matching any value of
-/// the right type? Names and `_` are; a literal sub-pattern is not. Used by
-/// exhaustiveness: a tuple/ctor arm only counts as a catch-all / as covering
-/// its constructor when every sub-pattern is irrefutable.
-fn sub_pattern_irrefutable(pattern: &Pattern) -> bool {
-    matches!(
-        pattern.kind,
-        PatternKind::Wildcard | PatternKind::Var { .. }
-    )
-}
-
 /// Substitute declaration parameter placeholders (`Var(i)`, i < args.len())
 /// with concrete type arguments — how generic record/variant field types
 /// meet their use sites. Non-generic declarations contain no placeholders,
 /// so this is the identity for them.
+/// The module a canonical def name belongs to (`"Utils.foo"` → `"Utils"`;
+/// `"Utils.Grid.cell"` → `"Utils.Grid"`, an inline module; bare → the
+/// entry's `""`).
+fn module_prefix(name: &str) -> &str {
+    name.rsplit_once('.').map_or("", |(module, _)| module)
+}
+
 fn subst_params(ty: &Type, args: &[Type]) -> Type {
     if args.is_empty() {
         return ty.clone();
@@ -315,12 +349,14 @@ fn subst_params(ty: &Type, args: &[Type]) -> Type {
         Type::Var(v) => args
             .get(*v as usize)
             .cloned()
-            .unwrap_or_else(|| Type::Var(*v)),
+            .unwrap_or(Type::Var(*v)),
         Type::List(e) => Type::List(Box::new(subst_params(e, args))),
+        Type::Array(e) => Type::Array(Box::new(subst_params(e, args))),
         Type::Map(key, value) => Type::Map(
             Box::new(subst_params(key, args)),
             Box::new(subst_params(value, args)),
         ),
+        Type::Set(e) => Type::Set(Box::new(subst_params(e, args))),
         Type::Tuple(es) => Type::Tuple(es.iter().map(|e| subst_params(e, args)).collect()),
         Type::Fn(ps, r) => Type::Fn(
             ps.iter().map(|p| subst_params(p, args)).collect(),
@@ -344,10 +380,12 @@ fn renumber_with(ty: &Type, order: &[u32]) -> Type {
             Type::Var(idx)
         }
         Type::List(e) => Type::List(Box::new(renumber_with(e, order))),
+        Type::Array(e) => Type::Array(Box::new(renumber_with(e, order))),
         Type::Map(key, value) => Type::Map(
             Box::new(renumber_with(key, order)),
             Box::new(renumber_with(value, order)),
         ),
+        Type::Set(e) => Type::Set(Box::new(renumber_with(e, order))),
         Type::Tuple(es) => Type::Tuple(es.iter().map(|e| renumber_with(e, order)).collect()),
         Type::Fn(ps, r) => Type::Fn(
             ps.iter().map(|p| renumber_with(p, order)).collect(),
@@ -373,7 +411,7 @@ fn free_vars_of(ty: &Type, out: &mut Vec<u32>) {
                 out.push(*v);
             }
         }
-        Type::List(elem) => free_vars_of(elem, out),
+        Type::List(elem) | Type::Array(elem) | Type::Set(elem) => free_vars_of(elem, out),
         Type::Map(key, value) => {
             free_vars_of(key, out);
             free_vars_of(value, out);
@@ -394,7 +432,7 @@ fn free_vars_of(ty: &Type, out: &mut Vec<u32>) {
                 free_vars_of(arg, out);
             }
         }
-        Type::Unknown | Type::Float | Type::String | Type::Bool => {}
+        Type::Unknown | Type::Float | Type::Int | Type::String | Type::Bool => {}
     }
 }
 
@@ -451,9 +489,9 @@ fn nearest_member(member: &str, members: &[&'static str]) -> Option<&'static str
 
 /// The primitive a capitalized/aliased spelling was reaching for. Functor
 /// Lang's primitives are lowercase, but nearly every other language
-/// capitalizes them, so `Float`/`String`/`Bool` are the mistake people
-/// actually make — and `Int`/`Number`/`Double` name a type Functor Lang
-/// doesn't have (all numbers are `float`).
+/// capitalizes them, so `Float`/`Int`/`String`/`Bool` are the mistake people
+/// actually make — and `Number`/`Double` name a type Functor Lang doesn't
+/// have under that name (a number with a fractional part is a `float`).
 ///
 /// Deliberately NOT here: `Any`/`Object`/`Dynamic`. Answering those with
 /// "did you mean `unknown`?" would recommend the check-disabling seam as a
@@ -462,7 +500,8 @@ fn nearest_member(member: &str, members: &[&'static str]) -> Option<&'static str
 /// `unknown` as a deliberate choice without endorsing it.
 fn miscased_primitive(name: &str) -> Option<&'static str> {
     match name.to_lowercase().as_str() {
-        "float" | "int" | "integer" | "number" | "double" | "num" => Some("float"),
+        "float" | "number" | "double" | "num" => Some("float"),
+        "int" | "integer" | "long" => Some("int"),
         "string" | "str" | "text" => Some("string"),
         "bool" | "boolean" => Some("bool"),
         _ => None,
@@ -610,7 +649,7 @@ pub fn builtin_signature(b: Builtin) -> Type {
             ],
             List(Box::new(Var(1))),
         ),
-        // Map keys infer normally but are dynamically bounded to bool,
+        // Map keys infer normally but are dynamically bounded to bool, int,
         // finite float, and string (the HM type language has no type-class
         // constraint syntax). Every operation preserves one K/V pair.
         // Map.empty : () => Map<'key, 'value>
@@ -647,6 +686,53 @@ pub fn builtin_signature(b: Builtin) -> Type {
             vec![List(Box::new(Tuple(vec![Var(0), Var(1)])))],
             Map(Box::new(Var(0)), Box::new(Var(1))),
         ),
+        // Set elements carry the Map key bound.
+        // Set.empty : () => Set<'a>
+        Builtin::SetEmpty => func(vec![], Set(Box::new(Var(0)))),
+        // Subject-LAST. Set.insert / Set.remove : ('a, Set<'a>) => Set<'a>
+        Builtin::SetInsert | Builtin::SetRemove => {
+            func(vec![Var(0), Set(Box::new(Var(0)))], Set(Box::new(Var(0))))
+        }
+        // Subject-LAST. Set.member : ('a, Set<'a>) => bool
+        Builtin::SetMember => func(vec![Var(0), Set(Box::new(Var(0)))], Bool),
+        // Subject-LAST. Set.union / intersect / difference : (Set<'a>, Set<'a>) => Set<'a>
+        Builtin::SetUnion | Builtin::SetIntersect | Builtin::SetDifference => func(
+            vec![Set(Box::new(Var(0))), Set(Box::new(Var(0)))],
+            Set(Box::new(Var(0))),
+        ),
+        // Set.toList : (Set<'a>) => List<'a>
+        Builtin::SetToList => func(vec![Set(Box::new(Var(0)))], List(Box::new(Var(0)))),
+        // Set.fromList : (List<'a>) => Set<'a>
+        Builtin::SetFromList => func(vec![List(Box::new(Var(0)))], Set(Box::new(Var(0)))),
+        // Array.make : (Int, 'a) => Array<'a>
+        Builtin::ArrayMake => func(vec![Int, Var(0)], Array(Box::new(Var(0)))),
+        // Subject-LAST. Array.get : (Int, Array<'a>) => Option.t<'a>
+        Builtin::ArrayGet => func(vec![Int, Array(Box::new(Var(0)))], option(Var(0))),
+        // Subject-LAST. Array.set : (Int, 'a, Array<'a>) => Array<'a>
+        Builtin::ArraySet => func(
+            vec![Int, Var(0), Array(Box::new(Var(0)))],
+            Array(Box::new(Var(0))),
+        ),
+        // Array.length : (Array<'a>) => Int
+        Builtin::ArrayLength => func(vec![Array(Box::new(Var(0)))], Int),
+        // Subject-LAST. Array.map : (('a) => 'b, Array<'a>) => Array<'b>
+        Builtin::ArrayMap => func(
+            vec![func(vec![Var(0)], Var(1)), Array(Box::new(Var(0)))],
+            Array(Box::new(Var(1))),
+        ),
+        // Subject-LAST. Array.fold : (('b, 'a) => 'b, 'b, Array<'a>) => 'b
+        Builtin::ArrayFold => func(
+            vec![
+                func(vec![Var(1), Var(0)], Var(1)),
+                Var(1),
+                Array(Box::new(Var(0))),
+            ],
+            Var(1),
+        ),
+        // Array.fromList : (List<'a>) => Array<'a>
+        Builtin::ArrayFromList => func(vec![List(Box::new(Var(0)))], Array(Box::new(Var(0)))),
+        // Array.toList : (Array<'a>) => List<'a>
+        Builtin::ArrayToList => func(vec![Array(Box::new(Var(0)))], List(Box::new(Var(0)))),
         // Text.concat : (String, String) => String
         Builtin::TextConcat => func(vec![String, String], String),
         // Text.fromFloat : (Float) => String
@@ -690,6 +776,9 @@ pub fn builtin_signature(b: Builtin) -> Type {
         | Builtin::MathLog
         | Builtin::MathExp
         | Builtin::MathSign => func(vec![Float], Float),
+        // Math.toFloat : (int) => float; Math.toInt : (float) => int
+        Builtin::MathToFloat => func(vec![Int], Float),
+        Builtin::MathToInt => func(vec![Float], Int),
         // Math.atan2 / mod / min / max / pow : (Float, Float) => Float
         Builtin::MathAtan2
         | Builtin::MathMod
@@ -752,11 +841,21 @@ impl ExprTypes {
 /// existing literal ambiguous — an implicit cross-module dependency the
 /// project's dependency graph never sees. Annotated positions are
 /// unaffected (a qualified annotation reaches any module's type).
+///
+/// It also carries which FILE owns each module, so what a sealing `.funi`
+/// hides — an abstract type's representation, an implementation's own type —
+/// stays hidden from every other file.
 #[derive(Default)]
 pub struct RecordLiteralScopes {
     /// Def-name prefix (the module: `"Utils"`; `""` for the entry, whose
     /// canonical names are bare) → visible canonical record-type names.
     pub by_module: HashMap<String, std::collections::HashSet<String>>,
+    /// Def-name prefix → the file module it belongs to (`"Utils.Grid"` →
+    /// `"Utils"`; the entry's `""` and inline `"Server"` → `"Game"`).
+    pub owners: HashMap<String, String>,
+    /// Canonical types a sealing `.funi` declares abstract → the file module
+    /// that may still build, match, and look inside them.
+    pub opaque: HashMap<String, String>,
 }
 
 /// Check a lowered module; returns every diagnostic, sorted by position.
@@ -790,115 +889,73 @@ pub fn check_with_scopes_and_types(
     check_impl(module, Some(scopes))
 }
 
-fn check_impl(
-    module: &Module,
-    scopes: Option<&RecordLiteralScopes>,
-) -> (Vec<CheckError>, ExprTypes) {
-    let mut checker = Checker {
-        scopes,
-        current_module: String::new(),
-        subst: HashMap::new(),
-        next_var: 0,
-        schemes: HashMap::new(),
-        signatures: HashMap::new(),
-        in_type_decl: false,
-        annot_vars: HashMap::new(),
-        records: HashMap::new(),
-        variants: HashMap::new(),
-        ctors: HashMap::new(),
-        globals: HashMap::new(),
-        locals: HashMap::new(),
-        diags: Vec::new(),
-        expr_types: HashMap::new(),
-        partials: HashMap::new(),
-        def_param_names: HashMap::new(),
-        match_scrutinees: Vec::new(),
-        unit_hints: HashMap::new(),
-        host_types: HashSet::new(),
-        brand_ops: HashMap::new(),
-        pending_ops: Vec::new(),
-        pending_eq: Vec::new(),
-    };
+/// The declared record and variant shapes of a module, resolved exactly as
+/// the checker resolves them — what a `property` test derives its input
+/// generators from (see [`crate::property`]). Annotations resolve silently:
+/// a bad one is the checker's diagnostic, and here just fails to resolve.
+pub struct Declarations {
+    checker: Checker<'static>,
+}
 
-    // Record type names first (nominal references may be forward), then
-    // resolve each declaration's field types (reporting bad type arity).
-    for decl in &module.types {
-        match &decl.body {
-            TypeBody::Record(_) => {
-                checker
-                    .records
-                    .insert(decl.name.clone(), (decl.params.len(), Vec::new()));
-            }
-            TypeBody::Variants(variants) => {
-                checker.variants.insert(
-                    decl.name.clone(),
-                    (
-                        decl.params.len(),
-                        variants.iter().map(|v| v.name.clone()).collect(),
-                    ),
-                );
-            }
-            // An opaque nominal: a variant with NO constructors, so
-            // annotations resolve to `Type::Variant` (reusing unify/display)
-            // and its values can only come from host code.
-            TypeBody::Abstract => {
-                checker
-                    .variants
-                    .insert(decl.name.clone(), (decl.params.len(), Vec::new()));
-            }
-            // `type t = host` — abstract, PLUS the one fact the checker
-            // cannot infer: its values are host values, which structural
-            // equality refuses at run time. Recorded so `==` on one is a
-            // check-time error (see `Checker::host_opaque`).
-            TypeBody::Host => {
-                checker
-                    .variants
-                    .insert(decl.name.clone(), (decl.params.len(), Vec::new()));
-                checker.host_types.insert(decl.name.clone());
-            }
-        }
-    }
-    checker.in_type_decl = true;
-    for decl in &module.types {
-        // Declared params resolve to out-of-band placeholders Var(0..n); an
-        // UNDECLARED lowercase name in a declaration is still the teaching
-        // error (see resolve_type).
-        checker.annot_vars.clear();
-        for (i, param) in decl.params.iter().enumerate() {
-            checker
-                .annot_vars
-                .insert(param.clone(), Type::Var(i as u32));
-        }
-        match &decl.body {
-            TypeBody::Record(decl_fields) => {
-                let fields = decl_fields
-                    .iter()
-                    .map(|f| (f.name.clone(), checker.resolve_type(&f.ty, true)))
-                    .collect();
-                checker
-                    .records
-                    .insert(decl.name.clone(), (decl.params.len(), fields));
-            }
-            TypeBody::Variants(variants) => {
-                for variant in variants {
-                    let fields = variant
-                        .fields
-                        .iter()
-                        .map(|f| checker.resolve_type(&f.ty, true))
-                        .collect();
-                    checker.ctors.insert(
-                        variant.name.clone(),
-                        (decl.name.clone(), decl.params.len(), fields),
-                    );
-                }
-            }
-            // Nothing to resolve — an abstract type has no fields or ctors.
-            TypeBody::Abstract | TypeBody::Host => {}
+/// Resolve `module`'s type declarations (the checker's declaration pass
+/// alone — no def is inferred).
+pub fn declarations(module: &Module) -> Declarations {
+    let mut checker = Checker::new(None);
+    checker.declare_types(module);
+    Declarations { checker }
+}
+
+impl Declarations {
+    /// Resolve an annotation to a [`Type`]; `None` when it does not resolve
+    /// cleanly (an unknown name, a wrong arity). Type variables resolve to
+    /// fresh [`Type::Var`]s, scoped to ONE call.
+    pub fn resolve(&mut self, ty: &TypeName) -> Option<Type> {
+        let diags = self.checker.diags.len();
+        self.checker.annot_vars.clear();
+        let resolved = self.checker.resolve_type(ty, true);
+        if self.checker.diags.len() > diags {
+            self.checker.diags.truncate(diags);
+            return None;
         }
+        Some(resolved)
     }
 
-    checker.in_type_decl = false;
-    checker.annot_vars.clear();
+    /// A declared record type's fields at the given type arguments, in
+    /// declaration order.
+    pub fn record_fields(&self, name: &str, args: &[Type]) -> Option<Vec<(String, Type)>> {
+        let (_, fields) = self.checker.records.get(name)?;
+        Some(
+            fields
+                .iter()
+                .map(|(field, ty)| (field.clone(), subst_params(ty, args)))
+                .collect(),
+        )
+    }
+
+    /// A declared variant type's constructors at the given type arguments,
+    /// in declaration order, each with its field types. Empty for an
+    /// abstract (or host) type, whose values only host code can make.
+    pub fn constructors(&self, name: &str, args: &[Type]) -> Option<Vec<(String, Vec<Type>)>> {
+        let (_, names) = self.checker.variants.get(name)?;
+        Some(
+            names
+                .iter()
+                .filter_map(|ctor| {
+                    let (_, _, fields) = self.checker.ctors.get(ctor)?;
+                    let fields = fields.iter().map(|f| subst_params(f, args)).collect();
+                    Some((ctor.clone(), fields))
+                })
+                .collect(),
+        )
+    }
+}
+
+fn check_impl(
+    module: &Module,
+    scopes: Option<&RecordLiteralScopes>,
+) -> (Vec<CheckError>, ExprTypes) {
+    let mut checker = Checker::new(scopes);
+    checker.declare_types(module);
 
     // Interface (`.funi`) value signatures: resolve each declared type now that
     // all types (including the interfaces' own) are registered, and generalize
@@ -911,6 +968,17 @@ fn check_impl(
     }
     checker.annot_vars.clear();
 
+    // Sealed values: the signature a sealing `.funi` declares is the type
+    // OTHER files see (resolved now, like an interface signature); the def
+    // itself is held to it once inferred, below.
+    for seal in &module.sealed {
+        checker.annot_vars.clear();
+        let ty = checker.resolve_type(&seal.ty, true);
+        let scheme = checker.generalize(&ty);
+        checker.sealed.insert(seal.name.clone(), scheme);
+    }
+    checker.annot_vars.clear();
+
     // Placeholder signatures: annotation-derived, with FRESH inference
     // variables where nothing is annotated (B7 — this is what makes
     // unannotated code inferable instead of Unknown). Param/return annotation
@@ -944,6 +1012,7 @@ fn check_impl(
                     Type::Fn(params, Box::new(ret))
                 }
                 ExprKind::Number(_) => Type::Float,
+                ExprKind::Int(_) => Type::Int,
                 ExprKind::String(_) => Type::String,
                 ExprKind::InterpolatedString(_) => Type::String,
                 ExprKind::Bool(_) => Type::Bool,
@@ -1001,6 +1070,8 @@ fn check_impl(
         };
         if let Some((name, brand)) = result {
             unit_brands.insert(unit.suffix.clone(), brand);
+            let scalar = if unit.whole { Type::Int } else { Type::Float };
+            checker.brand_scalars.insert(name.clone(), scalar);
             checker
                 .unit_hints
                 .entry(name)
@@ -1103,11 +1174,7 @@ suffix of `{name}` shares one implementation"
             // The def's module, from its canonical name ("Utils.foo" →
             // "Utils"; "Utils.Grid.cell" → "Utils.Grid", an inline module;
             // bare → the entry) — scopes bare record literals.
-            checker.current_module = def
-                .name
-                .rsplit_once('.')
-                .map(|(module, _)| module.to_string())
-                .unwrap_or_default();
+            checker.current_module = module_prefix(&def.name).to_string();
             let placeholder = checker
                 .globals
                 .get(&def.name)
@@ -1144,8 +1211,47 @@ suffix of `{name}` shares one implementation"
         }
     }
 
-    // `unit` declarations: the target must be exactly a `(float) => 't`
-    // function or constructor, since that is what a suffixed literal calls.
+    // Sealed values, checked against their interface: a fresh instance of
+    // the def must unify with the declared signature, leaving the
+    // signature's own type variables distinct and unsolved — the def is at
+    // least as general as what other files were promised.
+    for seal in &module.sealed {
+        let Some(scheme) = checker.schemes.get(&seal.name).cloned() else {
+            continue;
+        };
+        checker.annot_vars.clear();
+        checker.current_module = module_prefix(&seal.name).to_string();
+        let declared = checker.resolve_type(&seal.ty, false);
+        let actual = checker.instantiate(&scheme);
+        let (shown_declared, shown_actual) = checker.normalize_pair(&declared, &actual);
+        let what = format!("`{}` against its interface", seal.name);
+        if !checker.unify(&actual, &declared, seal.span, &what) {
+            continue;
+        }
+        let mut solved: Vec<Type> = checker
+            .annot_vars
+            .values()
+            .map(|var| checker.zonk(var))
+            .collect();
+        let vars = solved.len();
+        solved.retain(|ty| matches!(ty, Type::Var(_)));
+        solved.sort_by_key(|ty| ty.to_string());
+        solved.dedup_by_key(|ty| ty.to_string());
+        if solved.len() != vars {
+            checker.diag(
+                seal.span,
+                format!(
+                    "`{}` is less general than its interface declares: the interface promises \
+{shown_declared}, the implementation is {shown_actual}",
+                    seal.name
+                ),
+            );
+        }
+    }
+
+    // `unit` declarations: the target must be exactly a `(float) => 't` (or,
+    // for a unit counting whole numbers, `(int) => 't`) function or
+    // constructor, since that is what a suffixed literal calls.
     // Checked after inference, so a target defined in Functor Lang has its
     // real type.
     for unit in &module.units {
@@ -1153,7 +1259,8 @@ suffix of `{name}` shares one implementation"
         checker.current_module = String::new();
         let got = checker.infer(&unit.target);
         let result = checker.fresh();
-        let want = Type::Fn(vec![Type::Float], Box::new(result));
+        let arg = if unit.whole { Type::Int } else { Type::Float };
+        let want = Type::Fn(vec![arg], Box::new(result));
         checker.unify(
             &got,
             &want,
@@ -1172,7 +1279,7 @@ suffix of `{name}` shares one implementation"
         checker.current_module = unit_op.module.clone();
         let Some(brand) = brand.clone() else { continue };
         let want = Type::Fn(
-            vec![brand.clone(), operand_type(unit_op.op, &brand)],
+            vec![brand.clone(), operand_type(unit_op.op, &brand, &checker.brand_scalar(&brand))],
             Box::new(result_type(unit_op.op, &brand)),
         );
         checker.expect(
@@ -1186,10 +1293,23 @@ suffix of `{name}` shares one implementation"
     // `expect` tests: each must be a bool. Checked after every def has
     // generalized, so an expect instantiates the same schemes a later def
     // would (`expect id(1.0) == 1.0` beside `id` used at string elsewhere).
+    // A `property` is a lambda over its generated inputs, answering a bool;
+    // an `expectSnapshot` may be anything its stored dump can describe.
     for exp in &module.expects {
         checker.annot_vars.clear();
         checker.current_module = exp.module.clone();
-        checker.expect(&exp.expr, &Type::Bool, "an `expect` test");
+        match (&exp.kind, &exp.expr.kind) {
+            (ExpectKind::Property, ExprKind::Lambda { params, .. }) => {
+                let params = params.iter().map(|_| checker.fresh()).collect();
+                let law = Type::Fn(params, Box::new(Type::Bool));
+                checker.expect(&exp.expr, &law, "a `property` test");
+            }
+            (ExpectKind::Snapshot(_), _) => {
+                let any = checker.fresh();
+                checker.expect(&exp.expr, &any, "an `expectSnapshot` test");
+            }
+            _ => checker.expect(&exp.expr, &Type::Bool, "an `expect` test"),
+        }
         checker.flush_pending_ops();
     }
 
@@ -1227,7 +1347,7 @@ suffix of `{name}` shares one implementation"
 /// Strongly-connected components of the def call graph (edges = `Global`
 /// references), in dependency order — the generalization boundaries.
 /// Iterative Tarjan; module-sized inputs, no recursion depth concerns.
-fn scc_groups(module: &Module) -> Vec<Vec<usize>> {
+pub(crate) fn scc_groups(module: &Module) -> Vec<Vec<usize>> {
     let index_of: HashMap<&str, usize> = module
         .defs
         .iter()
@@ -1321,6 +1441,10 @@ struct Checker<'s> {
     /// (`Scene.cube`); an `External` reference to one is typed from here
     /// instead of `Unknown`, instantiated fresh per use.
     signatures: HashMap<String, Scheme>,
+    /// Sealed values' interface signatures, keyed by the def's canonical name
+    /// — what a use from another FILE instantiates (see
+    /// [`RecordLiteralScopes::owners`]).
+    sealed: HashMap<String, Scheme>,
     /// Resolving a TYPE DECLARATION's field annotations (lowercase names
     /// are refused there — see `resolve_type`).
     in_type_decl: bool,
@@ -1387,6 +1511,10 @@ struct Checker<'s> {
     /// The value is the SUFFIX the operator was declared through (`deg`),
     /// which only diagnostics need — brands are shared across suffixes.
     brand_ops: HashMap<(String, BinOp), String>,
+    /// The plain number each brand is built from, indexed like
+    /// [`Self::unit_hints`]: `float`, or `int` for a brand whose unit target
+    /// takes an `int`. It is the scalar its `*` and `/` take.
+    brand_scalars: HashMap<String, Type>,
     /// Arithmetic nodes whose operands were still unsolved when they were
     /// first seen, deferred to [`Checker::flush_pending_ops`] (which runs at
     /// every point where inference has settled: the end of each SCC group,
@@ -1428,6 +1556,111 @@ struct PartialApp {
     missing: Vec<(Option<String>, Type)>,
 }
 
+impl<'s> Checker<'s> {
+    fn new(scopes: Option<&'s RecordLiteralScopes>) -> Checker<'s> {
+        Checker {
+            scopes,
+            current_module: String::new(),
+            subst: HashMap::new(),
+            next_var: 0,
+            schemes: HashMap::new(),
+            signatures: HashMap::new(),
+            sealed: HashMap::new(),
+            in_type_decl: false,
+            annot_vars: HashMap::new(),
+            records: HashMap::new(),
+            variants: HashMap::new(),
+            ctors: HashMap::new(),
+            globals: HashMap::new(),
+            locals: HashMap::new(),
+            diags: Vec::new(),
+            expr_types: HashMap::new(),
+            partials: HashMap::new(),
+            def_param_names: HashMap::new(),
+            match_scrutinees: Vec::new(),
+            unit_hints: HashMap::new(),
+            brand_scalars: HashMap::new(),
+            host_types: HashSet::new(),
+            brand_ops: HashMap::new(),
+            pending_ops: Vec::new(),
+            pending_eq: Vec::new(),
+        }
+    }
+
+    /// The declaration pass — shared by [`check_impl`] and [`declarations`].
+    fn declare_types(&mut self, module: &Module) {
+        // Record type names first (nominal references may be forward), then
+        // resolve each declaration's field types (reporting bad type arity).
+        for decl in &module.types {
+            match &decl.body {
+                TypeBody::Record(_) => {
+                    self.records.insert(decl.name.clone(), (decl.params.len(), Vec::new()));
+                }
+                TypeBody::Variants(variants) => {
+                    self.variants.insert(
+                        decl.name.clone(),
+                        (
+                            decl.params.len(),
+                            variants.iter().map(|v| v.name.clone()).collect(),
+                        ),
+                    );
+                }
+                // An opaque nominal: a variant with NO constructors, so
+                // annotations resolve to `Type::Variant` (reusing unify/display)
+                // and its values can only come from host code.
+                TypeBody::Abstract => {
+                    self.variants.insert(decl.name.clone(), (decl.params.len(), Vec::new()));
+                }
+                // `type t = host` — abstract, PLUS the one fact the checker
+                // cannot infer: its values are host values, which structural
+                // equality refuses at run time. Recorded so `==` on one is a
+                // check-time error (see `Checker::host_opaque`).
+                TypeBody::Host => {
+                    self.variants.insert(decl.name.clone(), (decl.params.len(), Vec::new()));
+                    self.host_types.insert(decl.name.clone());
+                }
+            }
+        }
+        self.in_type_decl = true;
+        for decl in &module.types {
+            // Declared params resolve to out-of-band placeholders Var(0..n); an
+            // UNDECLARED lowercase name in a declaration is still the teaching
+            // error (see resolve_type).
+            self.annot_vars.clear();
+            for (i, param) in decl.params.iter().enumerate() {
+                self.annot_vars.insert(param.clone(), Type::Var(i as u32));
+            }
+            match &decl.body {
+                TypeBody::Record(decl_fields) => {
+                    let fields = decl_fields
+                        .iter()
+                        .map(|f| (f.name.clone(), self.resolve_type(&f.ty, true)))
+                        .collect();
+                    self.records.insert(decl.name.clone(), (decl.params.len(), fields));
+                }
+                TypeBody::Variants(variants) => {
+                    for variant in variants {
+                        let fields = variant
+                            .fields
+                            .iter()
+                            .map(|f| self.resolve_type(&f.ty, true))
+                            .collect();
+                        self.ctors.insert(
+                            variant.name.clone(),
+                            (decl.name.clone(), decl.params.len(), fields),
+                        );
+                    }
+                }
+                // Nothing to resolve — an abstract type has no fields or ctors.
+                TypeBody::Abstract | TypeBody::Host => {}
+            }
+        }
+
+        self.in_type_decl = false;
+        self.annot_vars.clear();
+    }
+}
+
 impl Checker<'_> {
     fn diag(&mut self, span: Span, message: String) {
         self.diags.push(CheckError { message, span });
@@ -1446,6 +1679,41 @@ impl Checker<'_> {
         }
     }
 
+    /// The file module owning def-name prefix `module`, in a project.
+    fn file_of(&self, module: &str) -> Option<&str> {
+        let scopes = self.scopes?;
+        scopes.owners.get(module).map(String::as_str)
+    }
+
+    /// Whether the CURRENT def lives outside file module `owner`.
+    fn outside(&self, owner: Option<&str>) -> bool {
+        match (owner, self.file_of(&self.current_module)) {
+            (Some(owner), Some(here)) => owner != here,
+            _ => false,
+        }
+    }
+
+    /// Refuse looking inside record type `name` where a sealing interface
+    /// makes it abstract (another file); `true` when refused.
+    fn opaque_here(&mut self, name: &str, span: Span) -> bool {
+        let owner = self
+            .scopes
+            .and_then(|scopes| scopes.opaque.get(name))
+            .map(String::as_str);
+        if !self.outside(owner) {
+            return false;
+        }
+        self.diag(
+            span,
+            format!(
+                "`{name}` is abstract outside module `{}` — its interface hides the \
+representation, so use the functions it exports",
+                owner.unwrap_or_default()
+            ),
+        );
+        true
+    }
+
     fn fresh(&mut self) -> Type {
         let v = self.next_var;
         self.next_var += 1;
@@ -1461,9 +1729,11 @@ impl Checker<'_> {
                 None => Type::Var(*v),
             },
             Type::List(elem) => Type::List(Box::new(self.zonk(elem))),
+            Type::Array(elem) => Type::Array(Box::new(self.zonk(elem))),
             Type::Map(key, value) => {
                 Type::Map(Box::new(self.zonk(key)), Box::new(self.zonk(value)))
             }
+            Type::Set(elem) => Type::Set(Box::new(self.zonk(elem))),
             Type::Tuple(elems) => Type::Tuple(elems.iter().map(|e| self.zonk(e)).collect()),
             Type::Fn(params, ret) => Type::Fn(
                 params.iter().map(|p| self.zonk(p)).collect(),
@@ -1504,6 +1774,7 @@ impl Checker<'_> {
             (Type::Var(v), _) => self.bind(*v, &b, span, what),
             (_, Type::Var(v)) => self.bind(*v, &a, span, what),
             (Type::Float, Type::Float)
+            | (Type::Int, Type::Int)
             | (Type::String, Type::String)
             | (Type::Bool, Type::Bool) => true,
             (Type::Record(x, xa), Type::Record(y, ya))
@@ -1517,7 +1788,9 @@ impl Checker<'_> {
                 }
                 ok
             }
-            (Type::List(x), Type::List(y)) => self.unify_rec(x, y, span, what),
+            (Type::List(x), Type::List(y))
+            | (Type::Array(x), Type::Array(y))
+            | (Type::Set(x), Type::Set(y)) => self.unify_rec(x, y, span, what),
             (Type::Map(xk, xv), Type::Map(yk, yv)) => {
                 self.unify_rec(xk, yk, span, what) & self.unify_rec(xv, yv, span, what)
             }
@@ -1569,7 +1842,7 @@ impl Checker<'_> {
     /// element") — the legible-error contract.
     fn mismatch(&mut self, expected: &Type, got: &Type, span: Span, what: &str) {
         let hint = match (expected, got) {
-            (Type::Variant(name, _) | Type::Record(name, _), Type::Float) => {
+            (Type::Variant(name, _) | Type::Record(name, _), Type::Float | Type::Int) => {
                 self.unit_hint(name, None).unwrap_or_default()
             }
             _ => String::new(),
@@ -1594,7 +1867,8 @@ impl Checker<'_> {
                 } else {
                     format!("{value}")
                 };
-                let call_arg = if value.fract() == 0.0 && value.is_finite() {
+                let whole = self.brand_scalars.get(name) == Some(&Type::Int);
+                let call_arg = if value.fract() == 0.0 && value.is_finite() && !whole {
                     format!("{bare}.0")
                 } else {
                     bare.clone()
@@ -1621,8 +1895,9 @@ unit-suffixed literal ({suffixes})"
         })
     }
 
-    /// Enforce Map's deliberately bounded key domain when a direct builtin
-    /// call has made the key type concrete. Unsolved variables and `unknown`
+    /// Enforce Map's deliberately bounded key domain (which Set shares for
+    /// its elements) when a direct builtin call has made the key type
+    /// concrete. Unsolved variables and `unknown`
     /// remain valid gradual/generic seams; the evaluator repeats the check on
     /// every operation, so an invalid value can never enter a Map even when a
     /// polymorphic helper delayed the concrete type beyond this call site.
@@ -1633,29 +1908,43 @@ unit-suffixed literal ({suffixes})"
         let Some(builtin) = builtin(path) else {
             return;
         };
-        let (key_ty, span) = match builtin {
+        let (what, key_ty, span) = match builtin {
             Builtin::MapGet | Builtin::MapInsert | Builtin::MapRemove | Builtin::MapMember => {
                 match (params.first(), args.first()) {
-                    (Some(key), Some(arg)) => (self.zonk(key), arg.span),
+                    (Some(key), Some(arg)) => ("Map keys", self.zonk(key), arg.span),
                     _ => return,
                 }
             }
             Builtin::MapFromList => match (params.first(), args.first()) {
                 (Some(Type::List(entries)), Some(arg)) => match entries.as_ref() {
-                    Type::Tuple(pair) if pair.len() == 2 => (self.zonk(&pair[0]), arg.span),
+                    Type::Tuple(pair) if pair.len() == 2 => {
+                        ("Map keys", self.zonk(&pair[0]), arg.span)
+                    }
                     _ => return,
                 },
                 _ => return,
             },
+            Builtin::SetInsert | Builtin::SetRemove | Builtin::SetMember => {
+                match (params.first(), args.first()) {
+                    (Some(element), Some(arg)) => ("Set elements", self.zonk(element), arg.span),
+                    _ => return,
+                }
+            }
+            Builtin::SetFromList => match (params.first(), args.first()) {
+                (Some(Type::List(element)), Some(arg)) => {
+                    ("Set elements", self.zonk(element), arg.span)
+                }
+                _ => return,
+            },
             _ => return,
         };
         if !matches!(
             key_ty,
-            Type::Bool | Type::Float | Type::String | Type::Unknown | Type::Var(_)
+            Type::Bool | Type::Int | Type::Float | Type::String | Type::Unknown | Type::Var(_)
         ) {
             self.diag(
                 span,
-                format!("Map keys must be bool, finite float, or string; got {key_ty}"),
+                format!("{what} must be bool, int, finite float, or string; got {key_ty}"),
             );
         }
     }
@@ -1670,9 +1959,11 @@ unit-suffixed literal ({suffixes})"
             match ty {
                 Type::Var(v) => mapping.get(v).cloned().unwrap_or(Type::Var(*v)),
                 Type::List(e) => Type::List(Box::new(walk(e, mapping))),
+                Type::Array(e) => Type::Array(Box::new(walk(e, mapping))),
                 Type::Map(key, value) => {
                     Type::Map(Box::new(walk(key, mapping)), Box::new(walk(value, mapping)))
                 }
+                Type::Set(e) => Type::Set(Box::new(walk(e, mapping))),
                 Type::Tuple(es) => Type::Tuple(es.iter().map(|e| walk(e, mapping)).collect()),
                 Type::Fn(ps, r) => Type::Fn(
                     ps.iter().map(|p| walk(p, mapping)).collect(),
@@ -1739,12 +2030,13 @@ unit-suffixed literal ({suffixes})"
             Type::Unknown
         };
         match ty.name.as_str() {
-            "float" | "string" | "bool" => {
+            "float" | "int" | "string" | "bool" => {
                 if !ty.args.is_empty() {
                     return arity_error(self, 0);
                 }
                 match ty.name.as_str() {
                     "float" => Type::Float,
+                    "int" => Type::Int,
                     "string" => Type::String,
                     _ => Type::Bool,
                 }
@@ -1767,6 +2059,37 @@ unit-suffixed literal ({suffixes})"
                 }
                 Type::List(Box::new(self.resolve_type(&ty.args[0], report)))
             }
+            "Array" => {
+                if ty.args.len() != 1 {
+                    return arity_error(self, 1);
+                }
+                Type::Array(Box::new(self.resolve_type(&ty.args[0], report)))
+            }
+            "Set" => {
+                if ty.args.len() != 1 {
+                    return arity_error(self, 1);
+                }
+                let element = self.resolve_type(&ty.args[0], report);
+                if report
+                    && !matches!(
+                        element,
+                        Type::Bool
+                            | Type::Int
+                            | Type::Float
+                            | Type::String
+                            | Type::Unknown
+                            | Type::Var(_)
+                    )
+                {
+                    self.diag(
+                        ty.args[0].span,
+                        format!(
+                            "Set elements must be bool, int, finite float, or string; got {element}"
+                        ),
+                    );
+                }
+                Type::Set(Box::new(element))
+            }
             "Map" => {
                 if ty.args.len() != 2 {
                     return arity_error(self, 2);
@@ -1775,12 +2098,17 @@ unit-suffixed literal ({suffixes})"
                 if report
                     && !matches!(
                         key,
-                        Type::Bool | Type::Float | Type::String | Type::Unknown | Type::Var(_)
+                        Type::Bool
+                            | Type::Int
+                            | Type::Float
+                            | Type::String
+                            | Type::Unknown
+                            | Type::Var(_)
                     )
                 {
                     self.diag(
                         ty.args[0].span,
-                        format!("Map keys must be bool, finite float, or string; got {key}"),
+                        format!("Map keys must be bool, int, finite float, or string; got {key}"),
                     );
                 }
                 Type::Map(
@@ -1916,12 +2244,12 @@ the type: `type Name<{name}> = …`"
         }
         if let Some(prim) = miscased_primitive(name) {
             // Distinguish "you capitalized it" from "that type doesn't exist
-            // here": `Int`/`Number` are not miscasings of `float`, they are
-            // another language's idea of numbers.
+            // here": `Integer`/`Number` are not miscasings of `int`/`float`,
+            // they are another language's idea of numbers.
             let why = if lowered == prim {
                 "Functor Lang's primitive types are lowercase"
             } else {
-                "Functor Lang has a single number type, `float`"
+                "Functor Lang's number types are `int` and `float`"
             };
             return format!("{head} — did you mean `{prim}`? ({why})");
         }
@@ -1931,7 +2259,9 @@ the type: `type Name<{name}> = …`"
         // three-edit one that spends its budget on the capital F. Budget: 2
         // for a name long enough that two edits still leave it recognizable,
         // 1 for short names where 2 edits could reach anything.
-        let mut candidates: Vec<&str> = vec!["float", "string", "bool", "unknown", "List", "Map"];
+        let mut candidates: Vec<&str> = vec![
+            "float", "int", "string", "bool", "unknown", "List", "Array", "Map", "Set",
+        ];
         candidates.extend(declared);
         let budget = if name.chars().count() >= 4 { 2 } else { 1 };
         let near = candidates
@@ -2039,15 +2369,17 @@ if this position is deliberately untyped"
         }
         // A bare number where a branded value belongs — the mistake the unit
         // suffixes exist for, so name the literal the source wrote in the fix.
-        if let (ExprKind::Number(value), Type::Variant(name, _) | Type::Record(name, _)) =
-            (&expr.kind, expected)
+        let literal = match &expr.kind {
+            ExprKind::Number(value) => Some((*value, Type::Float)),
+            ExprKind::Int(value) => Some((*value as f64, Type::Int)),
+            _ => None,
+        };
+        if let (Some((value, got)), Type::Variant(name, _) | Type::Record(name, _)) =
+            (literal, expected)
         {
-            if let Some(hint) = self.unit_hint(name, Some(*value)) {
-                self.diag(
-                    expr.span,
-                    format!("{what}: expected {expected}, got float{hint}"),
-                );
-                self.expr_types.insert(expr.id.raw(), Type::Float);
+            if let Some(hint) = self.unit_hint(name, Some(value)) {
+                self.diag(expr.span, format!("{what}: expected {expected}, got {got}{hint}"));
+                self.expr_types.insert(expr.id.raw(), got);
                 return;
             }
         }
@@ -2209,6 +2541,12 @@ missing {missing}. Did you forget an argument?"
     /// literal field must exist in the declaration and match its type, and
     /// every declared field must be present.
     fn check_record_literal(&mut self, fields: &[Field], name: &str, args: &[Type], span: Span) {
+        if self.opaque_here(name, span) {
+            for field in fields {
+                self.infer(&field.value);
+            }
+            return;
+        }
         let (_, decl) = self
             .records
             .get(name)
@@ -2249,6 +2587,7 @@ missing {missing}. Did you forget an argument?"
     fn infer_inner(&mut self, expr: &Expr) -> Type {
         match &expr.kind {
             ExprKind::Number(_) => Type::Float,
+            ExprKind::Int(_) => Type::Int,
             ExprKind::Tuple(items) => {
                 Type::Tuple(items.iter().map(|item| self.infer(item)).collect())
             }
@@ -2267,7 +2606,14 @@ missing {missing}. Did you forget an argument?"
                 .get(&binding.0)
                 .cloned()
                 .unwrap_or(Type::Unknown),
-            ExprKind::Global(name) => match self.schemes.get(name).cloned() {
+            // Another file sees a sealed value at its interface's type.
+            ExprKind::Global(name) => match self
+                .sealed
+                .get(name)
+                .filter(|_| self.outside(self.file_of(module_prefix(name))))
+                .or_else(|| self.schemes.get(name))
+                .cloned()
+            {
                 Some(scheme) => self.instantiate(&scheme),
                 // Same dependency group: monomorphic placeholder (the HM
                 // letrec rule).
@@ -2382,6 +2728,12 @@ missing {missing}. Did you forget an argument?"
                 let base_ty = self.infer(base);
                 let base_ty = self.zonk(&base_ty);
                 match &base_ty {
+                    Type::Record(name, _) if self.opaque_here(name, expr.span) => {
+                        for field in fields {
+                            self.infer(&field.value);
+                        }
+                        base_ty
+                    }
                     Type::Record(name, targs) => {
                         let name = name.clone();
                         let targs = targs.clone();
@@ -2470,6 +2822,7 @@ missing {missing}. Did you forget an argument?"
                 let object_ty = self.infer(object);
                 let object_ty = self.zonk(&object_ty);
                 match &object_ty {
+                    Type::Record(name, _) if self.opaque_here(name, expr.span) => Type::Unknown,
                     Type::Record(name, targs) => {
                         let field_ty = self
                             .records
@@ -2534,6 +2887,9 @@ missing {missing}. Did you forget an argument?"
                         return Type::Unknown;
                     }
                 }
+                if int_math_arity(callee) == Some(args.len()) {
+                    return self.number_math_call(callee, args);
+                }
                 match callee_ty {
                     // Currying: a call may supply FEWER args (partial
                     // application → a function of the remaining params), an
@@ -2660,10 +3016,12 @@ missing {missing}. Did you forget an argument?"
                 let ty = self.zonk(&ty);
                 if let Type::Var(_) = ty {
                     self.unify(&ty, &Type::Float, inner.span, "unary `-` operand");
+                } else if ty == Type::Int {
+                    return Type::Int;
                 } else if !compatible(&ty, &Type::Float) {
                     self.diag(
                         inner.span,
-                        format!("unary `-` needs a float operand, got {ty}"),
+                        format!("unary `-` needs a number operand, got {ty}"),
                     );
                 }
                 Type::Float
@@ -2692,7 +3050,7 @@ missing {missing}. Did you forget an argument?"
                 } = &leaf.kind
                 {
                     let cond_ty = self.infer(cond);
-                    self.require_cond_bool(&cond_ty, cond.span);
+                    self.require_cond_bool(&cond_ty, cond.span, "`if` condition");
                     links.push((leaf.id, self.infer(then_branch), then_branch.span));
                     leaf = else_branch;
                 }
@@ -2737,14 +3095,8 @@ missing {missing}. Did you forget an argument?"
     fn check_match(&mut self, expr: &Expr, scrutinee: &Expr, arms: &[MatchArm]) -> Type {
         let scrutinee_ty = self.infer(scrutinee);
         let scrutinee_ty = self.zonk(&scrutinee_ty);
-        let mut has_catch_all = false;
-        let mut saw_true = false;
-        let mut saw_false = false;
-        let mut covered_ctors: Vec<&str> = Vec::new();
-        // List coverage: exact-length patterns (`[a, b]` → 2) and min-length
-        // tail patterns (`[h, ..t]` → 1, matches len >= 1).
-        let mut list_exact: Vec<usize> = Vec::new();
-        let mut list_tail_mins: Vec<usize> = Vec::new();
+        // One matrix row per unguarded arm checked so far (see `exhaustive`).
+        let mut rows: Vec<Vec<Pat>> = Vec::new();
         let mut result: Option<Type> = None;
         // Enclosing-match context for the greedy-arm hint (see
         // `match_scrutinees`); popped below, after the arms are checked. The
@@ -2752,50 +3104,34 @@ missing {missing}. Did you forget an argument?"
         // still has was not stolen from it. [BOTH engines — review]
         let own_ctors: HashSet<String> = arms
             .iter()
-            .filter_map(|arm| match &arm.pattern.kind {
+            .flat_map(|arm| match &arm.pattern.kind {
+                PatternKind::Or(alternatives) => alternatives.iter().collect(),
+                _ => vec![&arm.pattern],
+            })
+            .filter_map(|pattern| match &pattern.kind {
                 PatternKind::Ctor { name, .. } => Some(name.clone()),
                 _ => None,
             })
             .collect();
         self.match_scrutinees.push((scrutinee_ty.clone(), own_ctors));
         for arm in arms {
-            // Arms after a catch-all are unreachable at runtime — they are
-            // still CHECKED (garbage draws diagnostics) but must not
-            // CONSTRAIN the scrutinee (an unreachable `"s"` arm must not
-            // pin an inferred scrutinee to String). [Codex M — B7 review]
-            self.check_pattern_constraining(&arm.pattern, &scrutinee_ty, !has_catch_all);
-            match &arm.pattern.kind {
-                PatternKind::Wildcard | PatternKind::Var { .. } => has_catch_all = true,
-                PatternKind::List { items, tail } => {
-                    if tail.is_some() {
-                        list_tail_mins.push(items.len());
-                    } else {
-                        list_exact.push(items.len());
-                    }
-                }
-                // A tuple arm catches every tuple of its arity only when all
-                // its sub-patterns are irrefutable (names/`_`); a literal
-                // sub-pattern (`("Enter", true)`) makes it refutable, so it no
-                // longer counts as a catch-all. And the arity must be able to
-                // match the scrutinee (a mismatch is diagnosed in
-                // check_pattern; it must not also count as exhaustive).
-                PatternKind::Tuple(args) if args.iter().all(sub_pattern_irrefutable) => {
-                    match &self.zonk(&scrutinee_ty) {
-                        Type::Tuple(elems) if elems.len() != args.len() => {}
-                        _ => has_catch_all = true,
-                    }
+            // An arm no value can reach — every value it matches is taken
+            // by the arms above it (after `_`, or `Some(0.0)` after
+            // `Some(_)`) — is still CHECKED (garbage draws diagnostics) but
+            // must not CONSTRAIN the scrutinee (an unreachable `"s"` arm must
+            // not pin an inferred scrutinee to String). [Codex M — B7 review]
+            let row = vec![Pat::from_pattern(&arm.pattern)];
+            let reachable = self.matrix().useful(&rows, &row).is_some();
+            self.check_pattern_constraining(&arm.pattern, &scrutinee_ty, reachable);
+            // A guarded arm may decline any value, so it covers nothing:
+            // only unguarded arms join the matrix (exhaustiveness, and the
+            // reachability of the arms after it).
+            match &arm.guard {
+                Some(guard) => {
+                    let guard_ty = self.infer(guard);
+                    self.require_cond_bool(&guard_ty, guard.span, "`when` guard");
                 }
-                PatternKind::Tuple(_) => {}
-                // A ctor arm fully covers that constructor only when all its
-                // sub-patterns are irrefutable; `Circle(0.0)` matches some
-                // `Circle`s, not all, so it doesn't count toward coverage.
-                PatternKind::Ctor { name, args } if args.iter().all(sub_pattern_irrefutable) => {
-                    covered_ctors.push(name)
-                }
-                PatternKind::Ctor { .. } => {}
-                PatternKind::Bool(true) => saw_true = true,
-                PatternKind::Bool(false) => saw_false = true,
-                PatternKind::Number(_) | PatternKind::String(_) => {}
+                None => rows.push(row),
             }
             // Arms UNIFY into one result type — a var arm is constrained by
             // its siblings instead of collapsing the match to Unknown.
@@ -2812,99 +3148,96 @@ missing {missing}. Did you forget an argument?"
         // stale-zonk hole both engines found: an inferred-scrutinee match
         // silently skipped exhaustiveness). [BOTH engines, High]
         let scrutinee_ty = self.zonk(&scrutinee_ty);
-        if !has_catch_all {
-            match &scrutinee_ty {
-                Type::Variant(name, _) => {
-                    let missing: Vec<String> = self
-                        .variants
-                        .get(name)
-                        .map(|(_, declared)| {
-                            declared
-                                .iter()
-                                .filter(|c| !covered_ctors.contains(&c.as_str()))
-                                .map(|c| format!("`{c}`"))
-                                .collect()
-                        })
-                        .unwrap_or_default();
-                    if !missing.is_empty() {
-                        self.diag(
-                            expr.span,
-                            format!(
-                                "match on `{name}` is not exhaustive: missing {}",
-                                missing.join(", ")
-                            ),
-                        );
-                    }
-                }
-                Type::Bool => {
-                    if !(saw_true && saw_false) {
-                        let missing = match (saw_true, saw_false) {
-                            (false, true) => "`true`",
-                            (true, false) => "`false`",
-                            _ => "`true`, `false`",
-                        };
-                        self.diag(
-                            expr.span,
-                            format!("match on bool is not exhaustive: missing {missing}"),
-                        );
-                    }
-                }
-                // Literal patterns can never cover all numbers or strings.
-                Type::Float | Type::String => {
-                    self.diag(
-                        expr.span,
-                        format!(
-                            "match on {scrutinee_ty} is not exhaustive: literal patterns need \
-a catch-all arm (`_` or a name)"
-                        ),
-                    );
-                }
-                // A list match is exhaustive iff SOME `[x1..xn, ..t]`
-                // pattern bounds the tail (covering len >= n) AND every
-                // shorter length 0..n is covered by an exact pattern (or a
-                // shorter tail pattern). `[]` + `[h, ..t]` is the canonical
-                // exhaustive recursion.
-                Type::List(_) => {
-                    let covered = list_tail_mins.iter().min().is_some_and(|&min| {
-                        (0..min).all(|len| {
-                            list_exact.contains(&len) || list_tail_mins.iter().any(|&m| m <= len)
-                        })
-                    });
-                    if !covered {
-                        self.diag(
-                            expr.span,
-                            format!(
-                                "match on {scrutinee_ty} is not exhaustive: add `[..rest]`, a catch-all (`_`), or arms covering the remaining lengths"
-                            ),
-                        );
-                    }
-                }
-                // A known product left uncovered. Either no arm matches the
-                // arity at all, or some do but are all refutable (a literal
-                // sub-pattern) with no catch-all — distinguish the two so the
-                // message fits the feature's primary use (input mapping).
-                Type::Tuple(elems) => {
-                    let arity_matched = arms.iter().any(|arm| {
-                        matches!(&arm.pattern.kind, PatternKind::Tuple(args) if args.len() == elems.len())
-                    });
-                    let detail = if arity_matched {
-                        "its arms are refutable — add a catch-all (`_` or a name)".to_string()
-                    } else {
-                        format!("no arm matches a {}-element tuple", elems.len())
-                    };
-                    self.diag(
-                        expr.span,
-                        format!("match on {scrutinee_ty} is not exhaustive: {detail}"),
-                    );
-                }
-                // Unknown stays gradual; List/Record/Fn scrutinees already
-                // drew per-pattern compatibility diagnostics above.
-                _ => {}
-            }
+        if let Some(message) = self.non_exhaustive(&scrutinee_ty, &rows, arms) {
+            self.diag(expr.span, message);
         }
         result.unwrap_or(Type::Unknown)
     }
 
+    fn matrix(&self) -> Matrix<'_> {
+        Matrix {
+            variants: &self.variants,
+            ctors: &self.ctors,
+        }
+    }
+
+    /// The "not exhaustive" diagnostic for a match whose arms are `rows`,
+    /// if some value of the (known) scrutinee type escapes them all.
+    fn non_exhaustive(&self, scrutinee_ty: &Type, rows: &[Vec<Pat>], arms: &[MatchArm]) -> Option<String> {
+        let matrix = self.matrix();
+        let witness = matrix
+            .useful(rows, &[Pat::any_of(scrutinee_ty)])
+            .and_then(|mut w| w.pop())?;
+        match scrutinee_ty {
+            // Every declared constructor some value of which escapes, in
+            // declaration order — bare when the whole constructor is
+            // missing (`Rect`), with the escaping sub-pattern otherwise
+            // (`Some(None)`).
+            Type::Variant(name, _) => {
+                let declared = self.variants.get(name).map(|(_, d)| d.clone()).unwrap_or_default();
+                let missing: Vec<String> = declared
+                    .iter()
+                    .filter_map(|c| {
+                        let arity = self.ctors.get(c).map_or(0, |(_, _, f)| f.len());
+                        let q = Pat::Con(Con::Variant(c.clone()), vec![Pat::Wild; arity]);
+                        matrix.useful(rows, &[q]).and_then(|mut w| w.pop())
+                    })
+                    .map(|w| format!("`{w}`"))
+                    .collect();
+                Some(format!(
+                    "match on `{name}` is not exhaustive: missing {}",
+                    missing.join(", ")
+                ))
+            }
+            Type::Bool => {
+                let missing: Vec<String> = [true, false]
+                    .into_iter()
+                    .filter(|b| matrix.useful(rows, &[Pat::Con(Con::Bool(*b), Vec::new())]).is_some())
+                    .map(|b| format!("`{b}`"))
+                    .collect();
+                Some(format!("match on bool is not exhaustive: missing {}", missing.join(", ")))
+            }
+            // Literal patterns can never cover all numbers or strings.
+            Type::Float | Type::Int | Type::String => Some(format!(
+                "match on {scrutinee_ty} is not exhaustive: literal patterns need a catch-all arm \
+(`_` or a name)"
+            )),
+            // A list escapes by length (`[]` + `[h, ..t]` is the canonical
+            // exhaustive recursion) or by an element no arm covers.
+            Type::List(_) if witness.is_shape_only() => Some(format!(
+                "match on {scrutinee_ty} is not exhaustive: add `[..rest]`, a catch-all (`_`), or arms covering the remaining lengths"
+            )),
+            // A known product left uncovered. Either no arm matches the
+            // arity at all, or some do but are all refutable (a literal
+            // sub-pattern) with no catch-all — distinguish the two so the
+            // message fits the feature's primary use (input mapping).
+            Type::Tuple(elems) if witness.is_shape_only() => {
+                let arity_matched = arms.iter().any(|arm| {
+                    matches!(&arm.pattern.kind, PatternKind::Tuple(args) if args.len() == elems.len())
+                });
+                let detail = if arity_matched {
+                    "its arms are refutable — add a catch-all (`_` or a name)".to_string()
+                } else {
+                    format!("no arm matches a {}-element tuple", elems.len())
+                };
+                Some(format!("match on {scrutinee_ty} is not exhaustive: {detail}"))
+            }
+            Type::Record(name, _) if witness.is_shape_only() => Some(format!(
+                "match on `{name}` is not exhaustive: its arms are refutable — add a catch-all \
+(`_` or a name)"
+            )),
+            Type::List(_) | Type::Tuple(_) => Some(format!(
+                "match on {scrutinee_ty} is not exhaustive: missing `{witness}`"
+            )),
+            Type::Record(name, _) => Some(format!(
+                "match on `{name}` is not exhaustive: missing `{witness}`"
+            )),
+            // Unknown stays gradual; Fn/Map scrutinees already drew
+            // per-pattern compatibility diagnostics.
+            _ => None,
+        }
+    }
+
     /// Join two match arms' types. Plain unification, with ONE contract
     /// lift first: the B6 producer treats a bare model as
     /// `(model, Effect.none())`, so an arm returning `m` beside an arm
@@ -2982,6 +3315,9 @@ the inner match, not of the `{type_name}` match around it)"
 
     fn check_pattern_constraining(&mut self, pattern: &Pattern, scrutinee: &Type, constrain: bool) {
         let scrutinee = &self.zonk(scrutinee);
+        if let PatternKind::Or(alternatives) = &pattern.kind {
+            return self.check_or_pattern(alternatives, scrutinee, constrain);
+        }
         // A variable scrutinee is CONSTRAINED by the pattern: a ctor pattern
         // makes it the owning variant type, a tuple pattern a product of
         // fresh elements, a literal its primitive — then check proceeds with
@@ -2990,7 +3326,7 @@ the inner match, not of the `{type_name}` match around it)"
         if let Type::Var(_) = scrutinee {
             if !constrain {
                 // Bind this arm's variables gradually and stop.
-                pattern_var_bindings(pattern, &mut |binding| {
+                pattern_var_bindings(pattern, &mut |binding, _| {
                     self.locals.insert(binding, Type::Unknown);
                 });
                 return;
@@ -3008,10 +3344,31 @@ the inner match, not of the `{type_name}` match around it)"
                     Some(Type::Tuple((0..args.len()).map(|_| self.fresh()).collect()))
                 }
                 PatternKind::List { .. } => Some(Type::List(Box::new(self.fresh()))),
+                // The one visible declared record with every named field —
+                // the field-access rule; none or several stays gradual.
+                PatternKind::Record(fields) => {
+                    let candidates: Vec<(String, usize)> = self
+                        .records
+                        .iter()
+                        .filter(|(name, _)| self.literal_candidate(name))
+                        .filter(|(_, (_, decl))| {
+                            fields.iter().all(|f| decl.iter().any(|(n, _)| *n == f.name))
+                        })
+                        .map(|(name, (params, _))| (name.clone(), *params))
+                        .collect();
+                    match candidates.as_slice() {
+                        [(name, params)] => {
+                            let targs = (0..*params).map(|_| self.fresh()).collect();
+                            Some(Type::Record(name.clone(), targs))
+                        }
+                        _ => None,
+                    }
+                }
                 PatternKind::Number(_) => Some(Type::Float),
+                PatternKind::Int(_) => Some(Type::Int),
                 PatternKind::Bool(_) => Some(Type::Bool),
                 PatternKind::String(_) => Some(Type::String),
-                PatternKind::Wildcard | PatternKind::Var { .. } => None,
+                PatternKind::Wildcard | PatternKind::Var { .. } | PatternKind::Or(_) => None,
             };
             if let Some(constrained) = constrained {
                 self.unify(scrutinee, &constrained, pattern.span, "match pattern");
@@ -3116,12 +3473,94 @@ is {other}"
                     }
                 }
             },
+            PatternKind::Record(fields) => match scrutinee {
+                Type::Record(name, _) if self.opaque_here(name, pattern.span) => {
+                    for field in fields {
+                        self.check_pattern(&field.pattern, &Type::Unknown);
+                    }
+                }
+                Type::Record(name, targs) => {
+                    for field in fields {
+                        let decl_ty = self
+                            .records
+                            .get(name)
+                            .and_then(|(_, decl)| decl.iter().find(|(n, _)| *n == field.name))
+                            .map(|(_, ty)| subst_params(ty, targs));
+                        match decl_ty {
+                            Some(ty) => self.check_pattern(&field.pattern, &ty),
+                            None => {
+                                self.diag(
+                                    field.span,
+                                    format!("`{name}` has no field `{}`", field.name),
+                                );
+                                self.check_pattern(&field.pattern, &Type::Unknown);
+                            }
+                        }
+                    }
+                }
+                Type::Unknown | Type::Var(_) => {
+                    for field in fields {
+                        self.check_pattern(&field.pattern, &Type::Unknown);
+                    }
+                }
+                other => {
+                    self.diag(
+                        pattern.span,
+                        format!("a record pattern cannot match {other} — it can never match"),
+                    );
+                    for field in fields {
+                        self.check_pattern(&field.pattern, &Type::Unknown);
+                    }
+                }
+            },
+            // Handled before the variable-scrutinee step above.
+            PatternKind::Or(_) => {}
             PatternKind::Number(_) => self.literal_pattern(scrutinee, Type::Float, pattern.span),
+            PatternKind::Int(_) => self.literal_pattern(scrutinee, Type::Int, pattern.span),
             PatternKind::Bool(_) => self.literal_pattern(scrutinee, Type::Bool, pattern.span),
             PatternKind::String(_) => self.literal_pattern(scrutinee, Type::String, pattern.span),
         }
     }
 
+    /// Each alternative checks against the scrutinee in turn (the first may
+    /// solve an inferred one for the rest), and must bind every name at the
+    /// type the first alternative gave it — the arm body sees one variable
+    /// whichever alternative matched.
+    fn check_or_pattern(&mut self, alternatives: &[Pattern], scrutinee: &Type, constrain: bool) {
+        let mut first: Vec<(u32, String, Type)> = Vec::new();
+        for (i, alternative) in alternatives.iter().enumerate() {
+            self.check_pattern_constraining(alternative, scrutinee, constrain);
+            let mut bound: Vec<(u32, String)> = Vec::new();
+            pattern_var_bindings(alternative, &mut |binding, name| {
+                bound.push((binding, name.to_string()))
+            });
+            if i == 0 {
+                first = bound
+                    .into_iter()
+                    .map(|(binding, name)| {
+                        let ty = self.locals.get(&binding).cloned().unwrap_or(Type::Unknown);
+                        (binding, name, ty)
+                    })
+                    .collect();
+                continue;
+            }
+            for (binding, name, expected) in &first {
+                let got = self.locals.get(binding).cloned().unwrap_or(Type::Unknown);
+                if !self.unify_rec(&got, expected, alternative.span, "or-pattern") {
+                    let (expected, got) = self.normalize_pair(expected, &got);
+                    self.diag(
+                        alternative.span,
+                        format!(
+                            "`{name}` is {got} in this alternative but {expected} in the first \
+— every alternative must bind it at one type"
+                        ),
+                    );
+                }
+                self.locals.insert(*binding, expected.clone());
+            }
+        }
+    }
+
     fn literal_pattern(&mut self, scrutinee: &Type, literal: Type, span: Span) {
         if !compatible(scrutinee, &literal) {
             self.diag(
@@ -3149,6 +3588,7 @@ is {other}"
             | BinOp::Sub
             | BinOp::Mul
             | BinOp::Div
+            | BinOp::Rem
             | BinOp::Lt
             | BinOp::Gt
             | BinOp::Le
@@ -3273,6 +3713,14 @@ is {other}"
             })
     }
 
+    /// The scalar a brand's `*` and `/` take — `float` unless one of its
+    /// units counts whole numbers.
+    fn brand_scalar(&self, brand: &Type) -> Type {
+        brand_name(brand)
+            .and_then(|name| self.brand_scalars.get(&name).cloned())
+            .unwrap_or(Type::Float)
+    }
+
     fn require_float(&mut self, op: BinOp, ty: &Type, span: Span) {
         let ty = self.zonk(ty);
         if let Type::Var(_) = ty {
@@ -3293,7 +3741,33 @@ is {other}"
         }
     }
 
-    /// A node over NUMBERS by default — the four arithmetic operators and
+    /// A saturated `Math.abs`/`min`/`max`/`mod` call. Their declared
+    /// signatures are float, but an int argument makes the call int — the
+    /// same rule [`Self::int_binary`] applies to an operator node — and then
+    /// every argument and the answer are int.
+    fn number_math_call(&mut self, callee: &Expr, args: &[Expr]) -> Type {
+        let arg_tys: Vec<Type> = args
+            .iter()
+            .map(|arg| {
+                let ty = self.infer(arg);
+                self.zonk(&ty)
+            })
+            .collect();
+        let number = if arg_tys.contains(&Type::Int) {
+            Type::Int
+        } else {
+            Type::Float
+        };
+        for (i, (arg, ty)) in args.iter().zip(&arg_tys).enumerate() {
+            if !self.report_forgotten_arg(arg, ty, &number) {
+                let what = format!("argument {} of `{}`", i + 1, callee_label(callee));
+                self.unify(ty, &number, arg.span, &what);
+            }
+        }
+        number
+    }
+
+    /// A node over NUMBERS by default — the arithmetic operators and
     /// the four orderings — which is the ad-hoc overloading seam.
     ///
     /// A brand with a declared implementation for this operator wins; a node
@@ -3322,6 +3796,9 @@ is {other}"
         if let Some(ty) = self.brand_binary(op, &lhs, lhs_span, &rhs, rhs_span, node_span) {
             return ty;
         }
+        if let Some(ty) = self.int_binary(op, &lhs, lhs_span, &rhs, rhs_span) {
+            return ty;
+        }
         let declarable = op.declarable();
         // Deferring is only worth it while a BRANDED reading is still
         // possible. `brand_binary` has already tried the solved sides, so
@@ -3369,6 +3846,50 @@ is {other}"
         result_type(op, &Type::Float)
     }
 
+    /// Resolve an arithmetic node as INT arithmetic when either operand is
+    /// already known to be an `int`: the other side must be one too (an
+    /// unsolved one becomes one). `None` leaves the node to the float path.
+    /// Int and float never mix implicitly — that is the point of having an
+    /// exact type — so a float on the other side is refused with the
+    /// conversion to write.
+    fn int_binary(
+        &mut self,
+        op: BinOp,
+        lhs: &Type,
+        lhs_span: Span,
+        rhs: &Type,
+        rhs_span: Span,
+    ) -> Option<Type> {
+        if *lhs != Type::Int && *rhs != Type::Int {
+            return None;
+        }
+        for (ty, span) in [(lhs, lhs_span), (rhs, rhs_span)] {
+            match ty {
+                Type::Int => {}
+                Type::Var(_) => {
+                    self.unify(ty, &Type::Int, span, &format!("`{}` operand", op.symbol()));
+                }
+                Type::Float => self.diag(
+                    span,
+                    format!(
+                        "`{}` mixes int and float — convert one side with `Math.toFloat` or \
+`Math.toInt`",
+                        op.symbol()
+                    ),
+                ),
+                _ if !compatible(ty, &Type::Int) => {
+                    let teaching = self.brand_operator_hint(op, ty);
+                    self.diag(
+                        span,
+                        format!("`{}` needs int operands, got {ty}{teaching}", op.symbol()),
+                    );
+                }
+                _ => {}
+            }
+        }
+        Some(result_type(op, &Type::Int))
+    }
+
     /// Resolve an arithmetic node against the declared unit operators.
     /// `Some` means a brand claimed it (and the other operand has been
     /// constrained to the implementation's signature); `None` means no brand
@@ -3389,7 +3910,7 @@ is {other}"
         // `45deg * 2.0`.
         if let Some(name) = brand_name(lhs) {
             if self.brand_ops.contains_key(&(name.clone(), declarable)) {
-                let want = operand_type(op, lhs);
+                let want = operand_type(op, lhs, &self.brand_scalar(lhs));
                 self.unify(
                     rhs,
                     &want,
@@ -3429,9 +3950,10 @@ is {other}"
             // Scaling commutes, so `2.0 * 45deg` is the declared call with
             // its arguments swapped…
             BinOp::Mul => {
+                let scalar = self.brand_scalar(rhs);
                 self.unify(
                     lhs,
-                    &Type::Float,
+                    &scalar,
                     lhs_span,
                     &format!("the scalar operand of `*` on `{name}`"),
                 );
@@ -3444,7 +3966,7 @@ is {other}"
                 self.diag(
                     node_span,
                     format!(
-                        "`/` on `{name}` divides a branded value by a float — write \
+                        "`/` on `{name}` divides a branded value by a number — write \
 `x / 2.0`, not `2.0 / x`"
                     ),
                 );
@@ -3494,6 +4016,28 @@ is {other}"
                 );
                 continue;
             }
+            // An `int` anywhere on the node — either operand, or the type an
+            // arithmetic result flows into — makes it int arithmetic.
+            if !node.op.is_comparison() && self.zonk(&node.result) == Type::Int {
+                for side in [&lhs, &rhs] {
+                    if let Type::Var(_) = side {
+                        self.unify(side, &Type::Int, node.node_span, "an int operand");
+                    }
+                }
+                lhs = self.zonk(&node.lhs);
+                rhs = self.zonk(&node.rhs);
+            }
+            if let Some(ty) =
+                self.int_binary(node.op, &lhs, node.lhs_span, &rhs, node.rhs_span)
+            {
+                self.unify(
+                    &ty,
+                    &node.result,
+                    node.node_span,
+                    &format!("the result of `{}`", node.op.symbol()),
+                );
+                continue;
+            }
             // `v * v` (the SAME unsolved operand twice) can only be float:
             // the scalar form's operands have different types, so no brand
             // reading exists to be ambiguous with. `v + v` has one, and stays
@@ -3651,7 +4195,7 @@ from them instead (`{name}` supports no `==`{structural})",
         let nested = |ty: &Type| self.opaque_engine_type_at(ty, false);
         match ty {
             Type::Map(key, value) => nested(key).or_else(|| nested(value)),
-            Type::List(element) => nested(element),
+            Type::List(element) | Type::Array(element) => nested(element),
             Type::Tuple(elements) => elements.iter().find_map(nested),
             Type::Record(_, args) | Type::Variant(_, args) => {
                 // The type's OWN name first — a nominal is opaque before its
@@ -3702,14 +4246,16 @@ from them instead (`{name}` supports no `==`{structural})",
 
     /// Constrain an `if` condition to `Bool` — unifying an unsolved variable,
     /// erroring on a known non-bool with an if-specific message.
-    fn require_cond_bool(&mut self, ty: &Type, span: Span) {
+    /// `what` names the condition for the diagnostic — "`if` condition" or
+    /// "`when` guard".
+    fn require_cond_bool(&mut self, ty: &Type, span: Span, what: &str) {
         let ty = self.zonk(ty);
         if let Type::Var(_) = ty {
-            self.unify(&ty, &Type::Bool, span, "`if` condition");
+            self.unify(&ty, &Type::Bool, span, what);
             return;
         }
         if !compatible(&ty, &Type::Bool) {
-            self.diag(span, format!("`if` condition needs a bool, got {ty}"));
+            self.diag(span, format!("{what} needs a bool, got {ty}"));
         }
     }
 }
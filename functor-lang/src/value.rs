@@ -3,9 +3,14 @@
 //!
 //! The `Display` impl is the canonical textual form used by `functor-lang run`/`trace`
 //! output (and the committed `.run`/`.trace` goldens): numbers via Rust's
-//! `f64` `Display`, strings in double quotes with `Debug` escaping, records
+//! `f64`/`i64` `Display` (so a whole float prints like the int it equals),
+//! strings in double quotes with `Debug` escaping, records
 //! and collections structurally (Maps in canonical key order), closures as
 //! `<fn(param, …)>` (their environment is not printed).
+//!
+//! Lists, arrays, maps, and sets are persistent ([`List`], [`Map`], [`Set`]):
+//! an update copies the path to the change and shares the rest with the old
+//! value.
 
 use crate::eval::builtin_name;
 use crate::ir::{BindingId, Expr, Param};
@@ -13,32 +18,39 @@ use std::cmp::Ordering;
 use std::fmt;
 use std::rc::Rc;
 
+pub use crate::persistent::{List, ListIter, Map, MapIter, Set, SetIter};
+
 /// One key in an immutable [`Value::Map`].
 ///
 /// The language deliberately bounds map keys to scalar plain data: bools,
-/// finite floats, and strings. The explicit cross-kind order keeps maps that
+/// ints, finite floats, and strings. The explicit cross-kind order keeps maps that
 /// arrive through an `unknown` seam canonical too; normally HM inference makes
 /// a map homogeneous.
 #[derive(Clone)]
 pub enum MapKey {
     Bool(bool),
+    Int(i64),
     Number(f64),
     String(Rc<str>),
 }
 
 impl MapKey {
     /// The canonical target-independent key order:
-    /// bool < float < string, then false < true / numeric / lexicographic
-    /// Unicode scalar-value text (Rust UTF-8 `str` order).
+    /// bool < int < float < string, then false < true / numeric /
+    /// lexicographic Unicode scalar-value text (Rust UTF-8 `str` order). An
+    /// int and a float are different keys even when they are equal numbers,
+    /// exactly as `1 == 1.0` is not a comparison the language allows.
     pub fn compare(&self, other: &MapKey) -> Ordering {
         let rank = |key: &MapKey| match key {
             MapKey::Bool(_) => 0,
-            MapKey::Number(_) => 1,
-            MapKey::String(_) => 2,
+            MapKey::Int(_) => 1,
+            MapKey::Number(_) => 2,
+            MapKey::String(_) => 3,
         };
         match rank(self).cmp(&rank(other)) {
             Ordering::Equal => match (self, other) {
                 (MapKey::Bool(a), MapKey::Bool(b)) => a.cmp(b),
+                (MapKey::Int(a), MapKey::Int(b)) => a.cmp(b),
                 // Construction rejects non-finite values and normalizes -0,
                 // so partial_cmp is total and agrees with language equality.
                 (MapKey::Number(a), MapKey::Number(b)) => {
@@ -69,13 +81,14 @@ impl MapKey {
             MapKey::String(value) => u64::try_from(value.len())
                 .unwrap_or(u64::MAX)
                 .saturating_add(1),
-            MapKey::Bool(_) | MapKey::Number(_) => 1,
+            MapKey::Bool(_) | MapKey::Int(_) | MapKey::Number(_) => 1,
         }
     }
 
     pub fn to_value(&self) -> Value {
         match self {
             MapKey::Bool(value) => Value::Bool(*value),
+            MapKey::Int(value) => Value::Int(*value),
             MapKey::Number(value) => Value::Number(*value),
             MapKey::String(value) => Value::String(value.clone()),
         }
@@ -94,6 +107,7 @@ impl fmt::Display for MapKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             MapKey::Bool(value) => write!(f, "{value}"),
+            MapKey::Int(value) => write!(f, "{value}"),
             MapKey::Number(value) => write!(f, "{value}"),
             MapKey::String(value) => write!(f, "{value:?}"),
         }
@@ -136,12 +150,20 @@ pub fn canonicalize_map_entries(mut entries: Vec<(MapKey, Value)>) -> (Vec<(MapK
 #[derive(Clone)]
 pub enum Value {
     Number(f64),
+    /// A whole number: exact, 64-bit, and overflow-checked (see
+    /// [`crate::eval`]). Never equal to a [`Value::Number`].
+    Int(i64),
     String(Rc<str>),
     Bool(bool),
-    List(Rc<Vec<Value>>),
+    List(List),
     /// Immutable keyed data in canonical key order. Language and host
     /// construction seams validate keys and keep entries unique.
-    Map(Rc<Vec<(MapKey, Value)>>),
+    Map(Map),
+    /// Immutable map keys with no values, in the same canonical order.
+    Set(Set),
+    /// An indexable sequence: the [`List`] rope behind `Array.get`/`set`.
+    /// Never equal to a [`Value::List`] with the same elements.
+    Array(List),
     /// At least two elements; structural equality, `(1, 2)` display.
     Tuple(Rc<Vec<Value>>),
     /// Field order is the construction order (deterministic output).
@@ -191,6 +213,18 @@ pub trait HostData {
     fn is_reload_safe_snapshot(&self) -> bool {
         false
     }
+    /// The value's contents for a debugging surface (the REPL, via
+    /// [`Value::debug`]): `<{type_name} {debug}>`. `None`, the default,
+    /// keeps the bare `<{type_name}>`.
+    fn debug(&self) -> Option<String> {
+        None
+    }
+    /// The value's structural dump for an `expectSnapshot` test (see
+    /// [`crate::snapshot`]). `None`, the default, makes the value
+    /// unsnapshottable — an error naming its type.
+    fn snapshot(&self) -> Option<crate::snapshot::Json> {
+        None
+    }
 }
 
 /// A lambda value: its IR params/body (shared with the [`crate::ir::Module`])
@@ -253,6 +287,28 @@ impl Env {
         }
         None
     }
+
+    /// Every binding in scope, most recently bound first — a debugger's
+    /// view of a frame's locals (see `crate::debug`).
+    pub(crate) fn bindings(&self) -> Vec<(BindingId, Value)> {
+        let mut bindings = Vec::new();
+        let mut cur = self;
+        while let Some(scope) = &cur.0 {
+            bindings.extend(scope.vars.iter().rev().cloned());
+            cur = &scope.parent;
+        }
+        bindings
+    }
+
+    /// The bindings of a single-scope environment — the flat capture list a
+    /// compiled closure carries (see `crate::bytecode`); `None` for an empty
+    /// environment or a chain.
+    pub(crate) fn flat(&self) -> Option<&[(BindingId, Value)]> {
+        match &self.0 {
+            Some(scope) if scope.parent.0.is_none() => Some(&scope.vars),
+            _ => None,
+        }
+    }
 }
 
 /// Preview caps (see [`Value::preview`]): the longest string shown unelided,
@@ -270,12 +326,13 @@ impl Value {
     /// collections are primitive too (`[]` / `Map.fromList([])` are complete).
     pub fn is_primitive(&self) -> bool {
         match self {
-            Value::Number(_) | Value::Bool(_) => true,
+            Value::Number(_) | Value::Int(_) | Value::Bool(_) => true,
             // The cap is CHARACTERS; `take(N+1)` bounds the count work.
             Value::String(s) => s.chars().take(MAX_PREVIEW_STRING + 1).count() <= MAX_PREVIEW_STRING,
             Value::Variant { args, .. } => args.is_empty(),
-            Value::List(items) => items.is_empty(),
+            Value::List(items) | Value::Array(items) => items.is_empty(),
             Value::Map(entries) => entries.is_empty(),
+            Value::Set(keys) => keys.is_empty(),
             Value::Record(fields) => fields.is_empty(),
             Value::Tuple(_) => false, // never empty (two elements minimum)
             Value::Ctor { .. }
@@ -345,6 +402,24 @@ impl Value {
                 };
                 format!("Map.fromList([{}{tail}])", shown.join(", "))
             }
+            Value::Set(keys) => {
+                let shown: Vec<String> = keys
+                    .iter()
+                    .take(MAX_PREVIEW_ITEMS)
+                    .map(|key| key.to_value().preview_at(depth + 1))
+                    .collect();
+                let tail = if keys.len() > MAX_PREVIEW_ITEMS { ", …" } else { "" };
+                format!("Set.fromList([{}{tail}])", shown.join(", "))
+            }
+            Value::Array(items) => {
+                let shown: Vec<String> = items
+                    .iter()
+                    .take(MAX_PREVIEW_ITEMS)
+                    .map(|v| v.preview_at(depth + 1))
+                    .collect();
+                let tail = if items.len() > MAX_PREVIEW_ITEMS { ", …" } else { "" };
+                format!("Array.fromList([{}{tail}])", shown.join(", "))
+            }
             Value::Tuple(items) => {
                 let shown: Vec<String> = items
                     .iter()
@@ -378,48 +453,6 @@ impl Value {
     }
 }
 
-#[cfg(test)]
-mod map_tests {
-    use super::{canonicalize_map_entries, MapKey, Value};
-    use std::rc::Rc;
-
-    #[test]
-    fn comparison_units_cover_string_bytes_and_scalar_constant_work() {
-        let short = MapKey::String(Rc::from("abc"));
-        let long = MapKey::String(Rc::from("abcdef"));
-
-        assert_eq!(MapKey::Bool(false).comparison_units(&MapKey::Bool(true)), 1);
-        assert_eq!(
-            MapKey::Number(1.0).comparison_units(&MapKey::Number(2.0)),
-            1
-        );
-        assert_eq!(short.comparison_units(&MapKey::Bool(false)), 1);
-        assert_eq!(short.comparison_units(&long), 4);
-        assert_eq!(short.comparison_unit_ceiling(), 4);
-        assert_eq!(long.comparison_unit_ceiling(), 7);
-    }
-
-    #[test]
-    fn duplicate_heavy_canonicalization_compacts_and_keeps_the_last_value() {
-        let entries = (0..1024)
-            .map(|value| {
-                (
-                    MapKey::String(Rc::from("same")),
-                    Value::Number(value as f64),
-                )
-            })
-            .collect();
-        let (canonical, _) = canonicalize_map_entries(entries);
-
-        assert_eq!(canonical.len(), 1);
-        assert!(
-            canonical.capacity() < 1024,
-            "a one-entry map retained the untrusted input capacity"
-        );
-        assert!(matches!(canonical[0].1, Value::Number(value) if value == 1023.0));
-    }
-}
-
 // NOTE on deep values and the native stack: `Value` deliberately has NO
 // manual `Drop` — an iterative-teardown Drop was built and measured at ~2x
 // frame_bench wall-clock (every dying container paid worklist/TLS churn that
@@ -440,6 +473,29 @@ impl fmt::Display for Value {
     /// overflow is a host crash, not an error. Output is byte-identical to
     /// the old recursive rendering (pinned by the run/trace goldens).
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render(f, false)
+    }
+}
+
+/// A [`Value`] rendered with its host values' contents; see
+/// [`Value::debug`].
+pub struct DebugValue<'a>(&'a Value);
+
+impl fmt::Display for DebugValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.render(f, true)
+    }
+}
+
+impl Value {
+    /// This value's [`fmt::Display`] rendering, except that a host value
+    /// shows its contents when it has a [`HostData::debug`]
+    /// (`<Vec3 (1, 2, 3)>` rather than `<Vec3>`).
+    pub fn debug(&self) -> DebugValue<'_> {
+        DebugValue(self)
+    }
+
+    fn render(&self, f: &mut fmt::Formatter<'_>, debug: bool) -> fmt::Result {
         /// Pending work: a value to render, or literal text (separators,
         /// closers, field names — all borrowed from `self` or 'static).
         enum Tok<'a> {
@@ -462,6 +518,7 @@ impl fmt::Display for Value {
             };
             match value {
                 Value::Number(n) => write!(f, "{n}")?,
+                Value::Int(n) => write!(f, "{n}")?,
                 Value::String(s) => write!(f, "{s:?}")?,
                 Value::Bool(b) => write!(f, "{b}")?,
                 // Sequences push their parts in REVERSE (the stack pops
@@ -476,29 +533,52 @@ impl fmt::Display for Value {
                         }
                     }
                 }
-                Value::List(items) => {
-                    f.write_str("[")?;
-                    stack.push(Tok::Text("]"));
-                    for (i, item) in items.iter().enumerate().rev() {
-                        stack.push(Tok::Val(item));
+                // The persistent collections iterate front to back only:
+                // push their parts in order, then reverse that run in place.
+                Value::List(items) | Value::Array(items) => {
+                    if matches!(value, Value::Array(_)) {
+                        f.write_str("Array.fromList([")?;
+                        stack.push(Tok::Text("])"));
+                    } else {
+                        f.write_str("[")?;
+                        stack.push(Tok::Text("]"));
+                    }
+                    let start = stack.len();
+                    for (i, item) in items.iter().enumerate() {
                         if i > 0 {
                             stack.push(Tok::Text(", "));
                         }
+                        stack.push(Tok::Val(item));
                     }
+                    stack[start..].reverse();
                 }
                 Value::Map(entries) => {
                     f.write_str("Map.fromList([")?;
                     stack.push(Tok::Text("])"));
-                    for (i, (key, value)) in entries.iter().enumerate().rev() {
-                        stack.push(Tok::Text(")"));
-                        stack.push(Tok::Val(value));
-                        stack.push(Tok::Text(", "));
-                        stack.push(Tok::Key(key));
+                    let start = stack.len();
+                    for (i, (key, value)) in entries.iter().enumerate() {
+                        if i > 0 {
+                            stack.push(Tok::Text(", "));
+                        }
                         stack.push(Tok::Text("("));
+                        stack.push(Tok::Key(key));
+                        stack.push(Tok::Text(", "));
+                        stack.push(Tok::Val(value));
+                        stack.push(Tok::Text(")"));
+                    }
+                    stack[start..].reverse();
+                }
+                Value::Set(keys) => {
+                    f.write_str("Set.fromList([")?;
+                    stack.push(Tok::Text("])"));
+                    let start = stack.len();
+                    for (i, key) in keys.iter().enumerate() {
                         if i > 0 {
                             stack.push(Tok::Text(", "));
                         }
+                        stack.push(Tok::Key(key));
                     }
+                    stack[start..].reverse();
                 }
                 Value::Record(fields) => {
                     f.write_str("{ ")?;
@@ -549,7 +629,10 @@ impl fmt::Display for Value {
                 }
                 Value::Builtin(b) => write!(f, "<builtin {}>", builtin_name(*b))?,
                 Value::HostFn(path) => write!(f, "<host {path}>")?,
-                Value::HostData(data) => write!(f, "<{}>", data.type_name())?,
+                Value::HostData(data) => match data.debug().filter(|_| debug) {
+                    Some(contents) => write!(f, "<{} {contents}>", data.type_name())?,
+                    None => write!(f, "<{}>", data.type_name())?,
+                },
             }
         }
         Ok(())
@@ -566,9 +649,10 @@ impl Value {
     /// through [`HostData`].
     pub fn is_reload_safe_snapshot(&self) -> bool {
         match self {
-            Value::List(items) | Value::Tuple(items) => {
+            Value::List(items) | Value::Array(items) => {
                 items.iter().all(Value::is_reload_safe_snapshot)
             }
+            Value::Tuple(items) => items.iter().all(Value::is_reload_safe_snapshot),
             Value::Map(entries) => entries
                 .iter()
                 .all(|(_, value)| value.is_reload_safe_snapshot()),
@@ -581,7 +665,11 @@ impl Value {
                 partial.callee.is_reload_safe_snapshot()
                     && partial.applied.iter().all(Value::is_reload_safe_snapshot)
             }
-            Value::Number(_) | Value::String(_) | Value::Bool(_) => true,
+            Value::Number(_)
+            | Value::Int(_)
+            | Value::String(_)
+            | Value::Bool(_)
+            | Value::Set(_) => true,
             Value::Ctor { .. } | Value::Builtin(_) | Value::HostFn(_) => false,
             Value::HostData(data) => data.is_reload_safe_snapshot(),
         }
@@ -591,10 +679,13 @@ impl Value {
     pub fn kind_name(&self) -> &'static str {
         match self {
             Value::Number(_) => "a number",
+            Value::Int(_) => "an int",
             Value::String(_) => "a string",
             Value::Bool(_) => "a bool",
             Value::List(_) => "a list",
             Value::Map(_) => "a map",
+            Value::Set(_) => "a set",
+            Value::Array(_) => "an array",
             Value::Tuple(_) => "a tuple",
             Value::Record(_) => "a record",
             Value::Variant { .. } => "a variant",
@@ -607,3 +698,45 @@ impl Value {
         }
     }
 }
+
+#[cfg(test)]
+mod map_tests {
+    use super::{canonicalize_map_entries, MapKey, Value};
+    use std::rc::Rc;
+
+    #[test]
+    fn comparison_units_cover_string_bytes_and_scalar_constant_work() {
+        let short = MapKey::String(Rc::from("abc"));
+        let long = MapKey::String(Rc::from("abcdef"));
+
+        assert_eq!(MapKey::Bool(false).comparison_units(&MapKey::Bool(true)), 1);
+        assert_eq!(
+            MapKey::Number(1.0).comparison_units(&MapKey::Number(2.0)),
+            1
+        );
+        assert_eq!(short.comparison_units(&MapKey::Bool(false)), 1);
+        assert_eq!(short.comparison_units(&long), 4);
+        assert_eq!(short.comparison_unit_ceiling(), 4);
+        assert_eq!(long.comparison_unit_ceiling(), 7);
+    }
+
+    #[test]
+    fn duplicate_heavy_canonicalization_compacts_and_keeps_the_last_value() {
+        let entries = (0..1024)
+            .map(|value| {
+                (
+                    MapKey::String(Rc::from("same")),
+                    Value::Number(value as f64),
+                )
+            })
+            .collect();
+        let (canonical, _) = canonicalize_map_entries(entries);
+
+        assert_eq!(canonical.len(), 1);
+        assert!(
+            canonical.capacity() < 1024,
+            "a one-entry map retained the untrusted input capacity"
+        );
+        assert!(matches!(canonical[0].1, Value::Number(value) if value == 1023.0));
+    }
+}
@@ -77,13 +77,13 @@ fn map_key_domain_is_checked_for_concrete_calls_and_annotations() {
     let (message, _, _) = single_diag("let bad = () => Map.empty() |> Map.insert([1.0], \"x\")");
     assert_eq!(
         message,
-        "Map keys must be bool, finite float, or string; got List<float>"
+        "Map keys must be bool, int, finite float, or string; got List<float>"
     );
 
     let (message, _, _) = single_diag("let bad = (map: Map<List<float>, string>) => map");
     assert_eq!(
         message,
-        "Map keys must be bool, finite float, or string; got List<float>"
+        "Map keys must be bool, int, finite float, or string; got List<float>"
     );
 }
 
@@ -102,6 +102,67 @@ fn map_builtin_namespace_is_closed() {
     );
 }
 
+// ------------------------------------------------------ immutable Set, Array
+
+#[test]
+fn set_and_array_operations_infer_through_pipelines() {
+    assert_clean(
+        r#"
+let visit = (seen: Set<string>, cell: string): Set<string> =>
+  seen |> Set.insert(cell) |> Set.union(Set.fromList(["start"]))
+let cells = (seen: Set<string>): List<string> => Set.toList(seen)
+let mark = (grid: Array<int>, i: int): Array<int> => grid |> Array.set(i, 1)
+let total = (grid: Array<int>): int => grid |> Array.fold((acc: int, x: int) => acc + x, 0)
+let at = (grid: Array<int>) => grid |> Array.get(0)
+"#,
+    );
+}
+
+#[test]
+fn set_and_array_reject_mismatched_element_and_index_types() {
+    let diags = check_src(
+        r#"
+let seen = Set.empty() |> Set.insert("a")
+let badElement = seen |> Set.member(2)
+let grid = Array.make(3, 0.0)
+let badIndex = grid |> Array.get(1.0)
+let notAList = List.length(grid)
+"#,
+    );
+    let messages: Vec<&str> = diags
+        .iter()
+        .map(|(message, _, _)| message.as_str())
+        .collect();
+    assert_eq!(messages.len(), 3, "{messages:?}");
+    assert!(
+        messages.iter().any(|message| message.contains("Set<int>")),
+        "missing element mismatch: {messages:?}"
+    );
+    assert!(
+        messages.iter().any(|message| message.contains("expected int, got float")),
+        "missing index mismatch: {messages:?}"
+    );
+    assert!(
+        messages.iter().any(|message| message.contains("Array<float>")),
+        "missing Array/List mismatch: {messages:?}"
+    );
+}
+
+#[test]
+fn set_element_domain_is_checked_like_map_keys() {
+    let (message, _, _) = single_diag("let bad = () => Set.empty() |> Set.insert([1.0])");
+    assert_eq!(
+        message,
+        "Set elements must be bool, int, finite float, or string; got List<float>"
+    );
+
+    let (message, _, _) = single_diag("let bad = (set: Set<List<float>>) => set");
+    assert_eq!(
+        message,
+        "Set elements must be bool, int, finite float, or string; got List<float>"
+    );
+}
+
 #[test]
 fn interpolation_is_string_and_checks_every_hole() {
     assert_clean(
@@ -179,6 +240,11 @@ fn example_lists_checks_clean() {
     example_checks_clean("lists");
 }
 
+#[test]
+fn example_patterns_checks_clean() {
+    example_checks_clean("patterns");
+}
+
 #[test]
 fn example_strings_checks_clean() {
     example_checks_clean("strings");
@@ -223,10 +289,66 @@ fn error_equality_across_known_types() {
 #[test]
 fn error_negating_a_bool() {
     let (message, line, col) = single_diag("let f = (b: bool) => -b");
-    assert_eq!(message, "unary `-` needs a float operand, got bool");
+    assert_eq!(message, "unary `-` needs a number operand, got bool");
     assert_eq!((line, col), (1, 23));
 }
 
+/// An int literal is an `int`, and pins the unannotated operand it meets.
+#[test]
+fn int_literals_infer_int_and_pin_unannotated_operands() {
+    assert_clean(
+        "let inc = (n) => n + 1\n\
+         let a: int = inc(41)\n\
+         let b: int = -a * 2 / 3\n\
+         let small: bool = b < 10\n",
+    );
+    assert_clean(
+        "let half = (n: int): float => Math.toFloat(n) * 0.5\n\
+         let next = (x: float): int => Math.toInt(x) + 1\n",
+    );
+}
+
+/// `%` and the int forms of `Math.mod`/`abs`/`min`/`max` type as int
+/// exactly when an int operand or argument is in play.
+#[test]
+fn int_remainder_and_math_functions_infer_int() {
+    assert_clean(
+        "let wrap = (n) => n % 8\n\
+         let a: int = wrap(-1) + Math.mod(-7, 3)\n\
+         let b: int = Math.abs(a) |> Math.max(Math.min(a, 2))\n\
+         let c: float = 7.5 % 2.0 + Math.abs(-1.0)\n",
+    );
+    let (message, line, col) = single_diag("let x = Math.min(1, 2.5)");
+    assert_eq!(message, "argument 2 of `Math.min`: expected int, got float");
+    assert_eq!((line, col), (1, 21));
+    let (message, _, _) = single_diag("let x = 1 % 2.5");
+    assert_eq!(
+        message,
+        "`%` mixes int and float — convert one side with `Math.toFloat` or `Math.toInt`"
+    );
+}
+
+#[test]
+fn error_mixing_int_and_float() {
+    let (message, line, col) = single_diag("let x = 1 + 2.5");
+    assert_eq!(
+        message,
+        "`+` mixes int and float — convert one side with `Math.toFloat` or `Math.toInt`"
+    );
+    assert_eq!((line, col), (1, 13));
+    let (message, _, _) = single_diag("let n: int = 2.5");
+    assert!(message.contains("expected int, got float"), "{message}");
+}
+
+#[test]
+fn error_int_literal_match_needs_a_catch_all() {
+    let (message, _, _) = single_diag("let f = (n: int): float => match n with | 0 => 1.0");
+    assert_eq!(
+        message,
+        "match on int is not exhaustive: literal patterns need a catch-all arm (`_` or a name)"
+    );
+}
+
 #[test]
 fn error_record_literal_extra_and_missing_fields() {
     let diags = check_src("type P = { x: float }\nlet f = (): P => { y: 1.0 }");
@@ -534,7 +656,12 @@ fn unknown_type_name_is_an_error() {
 /// with the exact correction.
 #[test]
 fn miscased_primitives_suggest_the_lowercase_spelling() {
-    for (bad, good) in [("Float", "float"), ("String", "string"), ("Bool", "bool")] {
+    for (bad, good) in [
+        ("Float", "float"),
+        ("Int", "int"),
+        ("String", "string"),
+        ("Bool", "bool"),
+    ] {
         let (message, _, _) = single_diag(&format!("let x: {bad} = 1.0"));
         assert_eq!(
             message,
@@ -549,14 +676,19 @@ fn miscased_primitives_suggest_the_lowercase_spelling() {
 /// A type from another language's number tower is not a *casing* mistake, so
 /// it gets its own explanation rather than a misleading one.
 #[test]
-fn foreign_number_types_point_at_float() {
-    for bad in ["Int", "Integer", "Number", "Double"] {
+fn foreign_number_types_point_at_int_or_float() {
+    for (bad, good) in [
+        ("Integer", "int"),
+        ("Long", "int"),
+        ("Number", "float"),
+        ("Double", "float"),
+    ] {
         let (message, _, _) = single_diag(&format!("let x: {bad} = 1.0"));
         assert_eq!(
             message,
             format!(
-                "unknown type name `{bad}` — did you mean `float`? \
-(Functor Lang has a single number type, `float`)"
+                "unknown type name `{bad}` — did you mean `{good}`? \
+(Functor Lang's number types are `int` and `float`)"
             )
         );
     }
@@ -2253,3 +2385,157 @@ fn literal_sub_pattern_type_mismatch_is_diagnosed() {
         "unexpected: {message}"
     );
 }
+
+// ------------------------------------------------------------ nested patterns
+
+const SHOTS: &str = "type Shot = | Hit(x: float, y: float) | Miss\n\
+                     type Result = | Found(shot: Shot) | Nothing\n";
+
+/// A constructor that is only PARTLY covered is reported with the nested
+/// value that escapes, not as missing outright.
+#[test]
+fn nested_ctor_exhaustiveness_names_the_escaping_value() {
+    let (message, _, _) = single_diag(&format!(
+        "{SHOTS}let f = (r: Result) => match r with | Found(Hit(x, _)) => x | Nothing => 0.0"
+    ));
+    assert_eq!(message, "match on `Result` is not exhaustive: missing `Found(Miss)`");
+    assert_clean(&format!(
+        "{SHOTS}let f = (r: Result) =>\n\
+         \x20 match r with | Found(Hit(x, _)) => x | Found(Miss) => 1.0 | Nothing => 0.0"
+    ));
+}
+
+/// Nested tuples/bools are covered structurally: `(true, _)` + `(_, true)`
+/// leaves exactly `(false, false)`.
+#[test]
+fn nested_tuple_exhaustiveness() {
+    let (message, _, _) = single_diag(
+        "let f = (p: (bool, bool)) => match p with | (true, _) => 0.0 | (_, true) => 1.0",
+    );
+    assert_eq!(
+        message,
+        "match on (bool, bool) is not exhaustive: missing `(false, false)`"
+    );
+    assert_clean(
+        "let f = (p: (bool, bool)) =>\n\
+         \x20 match p with | (true, _) => 0.0 | (_, true) => 1.0 | (false, false) => 2.0",
+    );
+}
+
+/// List elements count too: `[]` + `[Miss, ..]` leaves a list led by a hit.
+#[test]
+fn list_element_exhaustiveness() {
+    let (message, _, _) = single_diag(&format!(
+        "{SHOTS}let f = (xs: List<Shot>) => match xs with | [] => 0.0 | [Miss, .._] => 1.0"
+    ));
+    assert_eq!(
+        message,
+        "match on List<Shot> is not exhaustive: missing `[Hit, .._]`"
+    );
+}
+
+const GAME: &str = "type State = | Playing | Over\n\
+                    type Game = { state: State, lives: float }\n";
+
+#[test]
+fn record_patterns_type_fields_and_check_exhaustiveness() {
+    let (message, _, _) = single_diag(&format!(
+        "{GAME}let f = (g: Game) => match g with | {{ state: Over }} => 1.0"
+    ));
+    assert_eq!(
+        message,
+        "match on `Game` is not exhaustive: missing `{ state: Playing }`"
+    );
+    // A punned field binds the declared field type.
+    assert_clean(&format!(
+        "{GAME}let f = (g: Game): float =>\n\
+         \x20 match g with | {{ state: Over }} => 0.0 | {{ lives }} => lives + 1.0"
+    ));
+}
+
+/// An unannotated scrutinee is solved by the one declared record that has
+/// every named field.
+#[test]
+fn record_pattern_constrains_an_inferred_scrutinee() {
+    assert_clean(&format!(
+        "{GAME}let f = (g) => match g with | {{ state: Playing, lives }} => lives | _ => 0.0\n\
+         let x: float = f({{ state: Over, lives: 2.0 }})"
+    ));
+}
+
+#[test]
+fn record_pattern_errors() {
+    let (message, _, _) = single_diag(&format!(
+        "{GAME}let f = (g: Game) => match g with | {{ nope }} => 1.0 | _ => 0.0"
+    ));
+    assert_eq!(message, "`Game` has no field `nope`");
+    let (message, _, _) =
+        single_diag("let f = (x: float) => match x with | { lives } => 1.0 | _ => 0.0");
+    assert_eq!(
+        message,
+        "a record pattern cannot match float — it can never match"
+    );
+}
+
+/// A nested arm every value of which an earlier arm already takes is
+/// unreachable: it must not constrain the scrutinee either.
+#[test]
+fn nested_unreachable_arms_do_not_constrain() {
+    assert_clean(&format!(
+        "{SHOTS}let f = (r: Result) => match r with | Found(_) => 1.0 | Found(Hit(0.0, _)) => 2.0 | Nothing => 0.0"
+    ));
+    assert_clean("let f = (x) => (match x with | (_, _) => 1.0 | (\"s\", 1.0) => 2.0) + 1.0");
+}
+
+// ------------------------------------------------- guards and or-patterns
+
+const KEYS: &str = "type Key = | W | Up | S\n";
+
+/// A guard may decline any value, so a guarded arm covers nothing.
+#[test]
+fn guarded_arms_do_not_count_toward_exhaustiveness() {
+    let (message, _, _) = single_diag(&format!(
+        "{KEYS}let f = (k: Key) => match k with | W when true => 1.0 | Up => 2.0 | S => 3.0"
+    ));
+    assert_eq!(message, "match on `Key` is not exhaustive: missing `W`");
+    let (message, _, _) =
+        single_diag("let f = (n: float) => match n with | x when x > 0.0 => x");
+    assert!(message.contains("not exhaustive"), "unexpected: {message}");
+}
+
+#[test]
+fn or_patterns_cover_each_alternative() {
+    assert_clean(&format!(
+        "{KEYS}let f = (k: Key) => match k with | W | Up => 1.0 | S => 3.0"
+    ));
+    let (message, _, _) =
+        single_diag(&format!("{KEYS}let f = (k: Key) => match k with | W | Up => 1.0"));
+    assert_eq!(message, "match on `Key` is not exhaustive: missing `S`");
+}
+
+#[test]
+fn guard_must_be_bool_and_sees_pattern_variables() {
+    let (message, _, _) =
+        single_diag("let f = (n: float) => match n with | x when x => 1.0 | _ => 0.0");
+    assert_eq!(message, "`when` guard needs a bool, got float");
+    assert_clean("let f = (p: (float, float)) => match p with | (a, b) when a < b => b | (a, _) => a");
+}
+
+/// Alternatives bind each name at ONE type; an inferred scrutinee is solved
+/// across them.
+#[test]
+fn or_pattern_alternatives_bind_one_type() {
+    let diags = check_src(
+        "let f = (p: (float, string)) => match p with | (x, \"s\") | (\"t\", x) => 1.0 | _ => 0.0",
+    );
+    assert!(
+        diags.iter().any(|(m, _, _)| m
+            == "`x` is string in this alternative but float in the first — every alternative \
+                must bind it at one type"),
+        "unexpected: {diags:?}"
+    );
+    assert_clean(
+        "let f = (p) => match p with | (x, \"s\") | (\"t\", x) => x | _ => \"\"\n\
+         let y: string = f((\"a\", \"s\"))",
+    );
+}
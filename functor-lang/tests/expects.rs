@@ -181,6 +181,22 @@ fn runaway_expect_exceeds_the_budget() {
     );
 }
 
+#[test]
+fn runaway_tail_loop_exceeds_the_budget() {
+    // A tail loop holds no frames, so only the step budget can stop it.
+    let src = "let spin = (n) => spin(n + 1.0)\n\
+               expect spin(0.0) == 0.0\n";
+    let out = budgeted(src, 1_000);
+    let ExpectOutcome::Error(err) = &out[0].outcome else {
+        panic!("expected a budget error");
+    };
+    assert!(
+        err.message.contains("step budget (1000 steps)"),
+        "unexpected message: {}",
+        err.message
+    );
+}
+
 #[test]
 fn list_range_allocation_is_charged() {
     // No per-element evals happen in List.range — the bulk charge must
@@ -378,8 +394,7 @@ fn container_construction_is_charged() {
     let src = "let deep = List.range(1000.0) |> List.fold((acc, x) => [[[[[acc]]]]], [0.0])\n\
                expect List.length(deep) == 1.0\n";
     let failure = functor_lang::run_expects_budgeted(&lower(src), &mut NoHost, Some(4_000))
-        .err()
-        .expect("the def-load fold should exceed the budget");
+        .expect_err("the def-load fold should exceed the budget");
     assert!(failure.error.message.contains("step budget"));
 }
 
@@ -399,8 +414,7 @@ fn structural_equality_is_charged_by_size() {
 fn def_load_is_budgeted_too() {
     let src = "let table = List.range(100000.0)\nexpect 1.0 == 1.0\n";
     let failure = functor_lang::run_expects_budgeted(&lower(src), &mut NoHost, Some(1_000))
-        .err()
-        .expect("def load should exceed the budget");
+        .expect_err("def load should exceed the budget");
     assert!(
         failure.error.message.contains("step budget"),
         "unexpected message: {}",
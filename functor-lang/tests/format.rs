@@ -0,0 +1,59 @@
+//! `functor fmt` verification: every `.fun` / `.funi` file in the repo
+//! formats without error (the formatter checks its own output still parses to
+//! the same program with the same comments), and formatting is idempotent.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn sources(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "target" && name != "node_modules" {
+                sources(&path, out);
+            }
+        } else if name.ends_with(".fun") || name.ends_with(".funi") {
+            out.push(path);
+        }
+    }
+}
+
+#[test]
+fn every_repo_source_formats_idempotently() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let mut files = Vec::new();
+    for dir in ["functor-lang", "functor-prelude", "examples", "site/examples"] {
+        sources(&root.join(dir), &mut files);
+    }
+    assert!(files.len() > 20, "found only {} sources", files.len());
+    for path in files {
+        let src = fs::read_to_string(&path).unwrap();
+        // Some fixtures are deliberately malformed; the formatter refuses them.
+        let interface = path.extension().is_some_and(|ext| ext == "funi");
+        let parsed = if interface {
+            functor_lang::parse_interface(&src)
+        } else {
+            functor_lang::parse(&src)
+        };
+        if parsed.is_err() {
+            continue;
+        }
+        let once = functor_lang::format::format_source(&src, interface)
+            .unwrap_or_else(|err| panic!("{}: {}", path.display(), err.message));
+        let twice = functor_lang::format::format_source(&once, interface).unwrap();
+        assert_eq!(once, twice, "{} is not stable under formatting", path.display());
+        for (i, line) in once.lines().enumerate() {
+            assert!(
+                !line.ends_with(' '),
+                "{}:{}: trailing whitespace",
+                path.display(),
+                i + 1
+            );
+        }
+    }
+}
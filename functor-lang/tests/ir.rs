@@ -73,6 +73,11 @@ fn golden_lists() {
     check_golden("lists");
 }
 
+#[test]
+fn golden_patterns() {
+    check_golden("patterns");
+}
+
 #[test]
 fn golden_strings() {
     check_golden("strings");
@@ -404,6 +409,20 @@ fn error_unknown_ctor_in_pattern() {
     assert_eq!((line, col), (1, 31));
 }
 
+/// Every or-pattern alternative binds the same names (the arm body sees
+/// one variable whichever matched) — a name missing from either side is a
+/// lowering error at the alternative that disagrees.
+#[test]
+fn error_or_pattern_alternatives_bind_different_names() {
+    let (message, line, col) =
+        lower_err("let f = (p) => match p with | (x, 1.0) | (1.0, y) => x | _ => 0.0");
+    assert_eq!(message, "`x` must be bound in every alternative of this or-pattern");
+    assert_eq!((line, col), (1, 42));
+    let (message, _, _) =
+        lower_err("let f = (p) => match p with | (_, 1.0) | (1.0, y) => 0.0 | _ => 0.0");
+    assert_eq!(message, "`y` must be bound in every alternative of this or-pattern");
+}
+
 /// A constructor pattern must name exactly the declared field count.
 #[test]
 fn error_pattern_arity_mismatch() {
@@ -73,6 +73,11 @@ fn golden_lists() {
     check_golden("lists");
 }
 
+#[test]
+fn golden_patterns() {
+    check_golden("patterns");
+}
+
 #[test]
 fn golden_strings() {
     check_golden("strings");
@@ -106,6 +111,20 @@ fn error_unterminated_string() {
     );
 }
 
+#[test]
+fn error_int_literal_out_of_range() {
+    assert_eq!(
+        parse_err("let n = 9223372036854775808"),
+        (
+            "integer literal `9223372036854775808` does not fit in an `int` (64-bit) — write \
+`9223372036854775808.0` for a float"
+                .to_string(),
+            1,
+            9
+        )
+    );
+}
+
 #[test]
 fn interpolated_string_parses_text_and_full_expression_holes() {
     let src = r#"let label = $"score: {1.0 + 2.0}; {{ready}}""#;
@@ -419,6 +438,24 @@ fn error_module_in_an_interface_file() {
     );
 }
 
+/// A `.funi` IS the list of public names, so `private` has no meaning there.
+#[test]
+fn error_private_in_an_interface_file() {
+    let err = functor_lang::parse_interface("private let x : float\n")
+        .expect_err("interface should reject `private`");
+    assert_eq!(
+        err.message,
+        "interface files (.funi) list what a module makes public — leave a name out instead of \
+marking it `private`"
+    );
+    let program = functor_lang::parse("private type T = | A\nprivate let x = 1.0\n").unwrap();
+    assert!(program.items.iter().all(|item| match item {
+        Item::Let(decl) => decl.private,
+        Item::Type(decl) => decl.private,
+        _ => false,
+    }));
+}
+
 /// A constructor's fields are named in the declaration.
 #[test]
 fn error_variant_field_needs_a_name() {
@@ -480,22 +517,159 @@ fn error_match_requires_leading_bar() {
     );
 }
 
-/// Sub-patterns are bindings, `_`, or literals — constructor patterns don't
-/// nest another constructor/tuple.
+/// The first arm's pattern of the `match` that is the program's first
+/// lambda body.
+fn first_arm_pattern(program: &functor_lang::ast::Program) -> &functor_lang::ast::Pattern {
+    let Item::Let(decl) = &program.items[0] else {
+        panic!("expected a let declaration");
+    };
+    let ExprKind::Lambda { body, .. } = &decl.value.kind else {
+        panic!("expected a lambda");
+    };
+    let ExprKind::Match { arms, .. } = &body.kind else {
+        panic!("expected a match");
+    };
+    &arms[0].pattern
+}
+
+/// Constructor patterns nest other constructors, tuples, and lists.
+#[test]
+fn constructor_patterns_nest() {
+    use functor_lang::ast::PatternKind;
+    let program =
+        functor_lang::parse("let f = (s) => match s with | Some(Hit((x, 0.0), _)) => x | _ => 0.0")
+            .unwrap();
+    let pattern = first_arm_pattern(&program);
+    let PatternKind::Ctor { name, args } = &pattern.kind else {
+        panic!("expected a ctor pattern");
+    };
+    assert_eq!(name, "Some");
+    let PatternKind::Ctor { name, args } = &args[0].kind else {
+        panic!("expected a nested ctor pattern");
+    };
+    assert_eq!(name, "Hit");
+    let PatternKind::Tuple(pos) = &args[0].kind else {
+        panic!("expected a nested tuple pattern");
+    };
+    assert!(matches!(pos[1].kind, PatternKind::Number(n) if n == 0.0));
+    assert!(matches!(args[1].kind, PatternKind::Wildcard));
+}
+
+/// List elements and tails are full patterns too — literals included.
+#[test]
+fn list_patterns_nest() {
+    use functor_lang::ast::PatternKind;
+    let program =
+        functor_lang::parse("let f = (xs) => match xs with | [true, (a, _), ..[b]] => a | _ => 0.0")
+            .unwrap();
+    let pattern = first_arm_pattern(&program);
+    let PatternKind::List { items, tail } = &pattern.kind else {
+        panic!("expected a list pattern");
+    };
+    assert!(matches!(items[0].kind, PatternKind::Bool(true)));
+    assert!(matches!(items[1].kind, PatternKind::Tuple(_)));
+    assert!(matches!(tail.as_deref().map(|t| &t.kind), Some(PatternKind::List { .. })));
+}
+
+/// A record pattern names fields with sub-patterns; a bare field name puns
+/// a variable of the same name, spanning the name.
 #[test]
-fn error_nested_constructor_pattern() {
+fn record_pattern_with_punning() {
+    use functor_lang::ast::PatternKind;
+    let src = "let f = (g) => match g with | { state: Playing, lives } => lives | _ => 0.0";
+    let program = functor_lang::parse(src).unwrap();
+    let pattern = first_arm_pattern(&program);
+    let PatternKind::Record(fields) = &pattern.kind else {
+        panic!("expected a record pattern");
+    };
+    assert_eq!(fields[0].name, "state");
+    assert!(matches!(&fields[0].pattern.kind, PatternKind::Ctor { name, .. } if name == "Playing"));
+    assert_eq!(fields[1].name, "lives");
+    assert!(matches!(&fields[1].pattern.kind, PatternKind::Var(n) if n == "lives"));
+    let span = fields[1].pattern.span;
+    assert_eq!(&src[span.start..span.end], "lives");
+}
+
+#[test]
+fn error_empty_record_pattern() {
     assert_eq!(
-        parse_err("let f = (s) => match s with | Circle(Point) => 1.0 | _ => 0.0"),
+        parse_err("let f = (g) => match g with | {} => 1.0"),
         (
-            "expected a binding name, `_`, or a literal (constructor/tuple patterns do not nest), \
-             found `Point`"
-                .to_string(),
+            "a record pattern needs at least one field (use `_` to match any value)".to_string(),
             1,
-            38
+            31
         )
     );
 }
 
+#[test]
+fn error_punned_constructor_field() {
+    let (message, _, _) = parse_err("let f = (g) => match g with | { Playing } => 1.0 | _ => 0.0");
+    assert_eq!(
+        message,
+        "`Playing` cannot be punned — write `Playing: pattern` (a bare field name binds a \
+         variable of that name)"
+    );
+}
+
+/// `A | B` after an arm's `|` is one or-pattern; the next arm's `|` only
+/// ever follows a body.
+#[test]
+fn or_pattern_alternatives_parse_as_one_arm() {
+    use functor_lang::ast::PatternKind;
+    let src = "let f = (k) => match k with | W | Up => 1.0 | Some(A | B) => 2.0 | _ => 0.0";
+    let program = functor_lang::parse(src).unwrap();
+    let Item::Let(decl) = &program.items[0] else {
+        panic!("expected a let declaration");
+    };
+    let ExprKind::Lambda { body, .. } = &decl.value.kind else {
+        panic!("expected a lambda");
+    };
+    let ExprKind::Match { arms, .. } = &body.kind else {
+        panic!("expected a match");
+    };
+    assert_eq!(arms.len(), 3);
+    let PatternKind::Or(alternatives) = &arms[0].pattern.kind else {
+        panic!("expected an or-pattern");
+    };
+    assert_eq!(alternatives.len(), 2);
+    let span = arms[0].pattern.span;
+    assert_eq!(&src[span.start..span.end], "W | Up");
+    let PatternKind::Ctor { args, .. } = &arms[1].pattern.kind else {
+        panic!("expected a ctor pattern");
+    };
+    assert!(matches!(args[0].kind, PatternKind::Or(_)));
+}
+
+/// `when` is contextual: a guard after an arm's pattern, a plain name
+/// elsewhere.
+#[test]
+fn match_guard_parses_after_the_pattern() {
+    let src = "let f = (n) => match n with | x when x > 0.0 => x | _ => 0.0
+let when = 1.0";
+    let program = functor_lang::parse(src).unwrap();
+    let Item::Let(decl) = &program.items[0] else {
+        panic!("expected a let declaration");
+    };
+    let ExprKind::Lambda { body, .. } = &decl.value.kind else {
+        panic!("expected a lambda");
+    };
+    let ExprKind::Match { arms, .. } = &body.kind else {
+        panic!("expected a match");
+    };
+    let guard = arms[0].guard.as_ref().expect("a guard");
+    assert_eq!(&src[guard.span.start..guard.span.end], "x > 0.0");
+    assert!(arms[1].guard.is_none());
+}
+
+#[test]
+fn error_guard_without_arrow() {
+    assert_eq!(
+        parse_err("let f = (n) => match n with | x when x > 0.0 x"),
+        ("expected `=>` after the guard, found `x`".to_string(), 1, 46)
+    );
+}
+
 /// Literals ARE allowed as tuple/ctor sub-patterns (`("Enter", true)`) — the
 /// input-mapping shape. String, number (incl. negative), and bool leaves.
 #[test]
@@ -545,30 +719,6 @@ fn ctor_pattern_allows_literal_sub_pattern() {
     assert!(matches!(args[0].kind, PatternKind::Number(n) if n == 0.0));
 }
 
-/// A nested tuple sub-pattern is still refused (only literal leaves, no
-/// deeper structure).
-#[test]
-fn error_nested_tuple_sub_pattern() {
-    let (message, _, _) = parse_err("let f = (p) => match p with | ((a, b), c) => a | _ => 0.0");
-    assert_eq!(
-        message,
-        "expected a binding name, `_`, or a literal (constructor/tuple patterns do not nest), \
-         found `(`"
-    );
-}
-
-/// Literals are NOT allowed in LIST element/tail patterns — list
-/// exhaustiveness is length-based and would silently break. The widening is
-/// scoped to tuple/ctor patterns.
-#[test]
-fn error_literal_in_list_pattern() {
-    let (message, _, _) = parse_err("let f = (xs) => match xs with | [] => 0.0 | [true, ..r] => 1.0");
-    assert_eq!(
-        message,
-        "expected a binding name or `_` (list patterns do not nest), found `true`"
-    );
-}
-
 /// GREEDY ARMS: a nested match inside an arm consumes the following `|`
 /// arms as its own; parenthesizing restores them to the outer match (the
 /// documented F#/OCaml convention).
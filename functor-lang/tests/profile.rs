@@ -0,0 +1,146 @@
+//! Hook profiler verification (`functor run native --profile`, `GET /profile`):
+//! a profiler armed on a session aggregates every call its hooks make — host
+//! externals as leaves, failed calls unwound — and renders the Chrome and
+//! collapsed-stack formats, with NO effect on evaluation.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use functor_lang::profile::{CallKind, FunctionProfile, ProfileFormat, Profiler};
+use functor_lang::snapshot::Json;
+use functor_lang::value::Value;
+use functor_lang::{Host, NoHost, RunError, Session, Span};
+
+/// A host providing one external, `Scene.cube`, which builds a number.
+struct Scene;
+
+impl Host for Scene {
+    fn provides(&self, path: &str) -> bool {
+        path == "Scene.cube"
+    }
+
+    fn call(&mut self, _path: &str, args: Vec<Value>, _span: Span) -> Result<Value, RunError> {
+        Ok(args.into_iter().next().unwrap_or(Value::Number(0.0)))
+    }
+}
+
+fn profiled(src: &str) -> (Session, Rc<RefCell<Profiler>>) {
+    let program = functor_lang::parse(src).expect("source should parse");
+    let module = functor_lang::lower(program).expect("source should lower");
+    let mut session = Session::load(&module, &mut Scene)
+        .unwrap_or_else(|failure| panic!("load error: {}", failure.error.message));
+    let profiler = Rc::new(RefCell::new(Profiler::new()));
+    session.set_profiler(Some(profiler.clone()));
+    (session, profiler)
+}
+
+fn function<'a>(functions: &'a [FunctionProfile], name: &str) -> &'a FunctionProfile {
+    functions
+        .iter()
+        .find(|function| function.name == name)
+        .unwrap_or_else(|| panic!("no profiled function `{name}`"))
+}
+
+const GAME: &str = "let square = (x) => x * x\n\
+let countdown = (n) => if n <= 0.0 then 0.0 else countdown(n - 1.0)\n\
+let draw = (model) =>\n  \
+  let sizes = List.map(square, [1.0, 2.0, 3.0]) in\n  \
+  Scene.cube(countdown(model))";
+
+#[test]
+fn a_profiled_hook_aggregates_calls_with_host_externals_as_leaves() {
+    let (session, profiler) = profiled(GAME);
+    for _ in 0..2 {
+        let result = session
+            .call("draw", vec![Value::Number(3.0)], &mut Scene)
+            .expect("draw");
+        assert_eq!(result.to_string(), "0");
+    }
+    let profiler = profiler.borrow();
+    let functions = profiler.functions();
+
+    assert_eq!(function(&functions, "draw").calls, 2);
+    // Per-element calls fold into one row, whatever the index.
+    let element = function(&functions, "List.map[*]");
+    assert_eq!(element.calls, 6);
+    assert_eq!(element.kind, CallKind::Function);
+    assert_eq!(function(&functions, "List.map").kind, CallKind::Builtin);
+    // Four activations per draw (3, 2, 1, 0), tail calls included.
+    assert_eq!(function(&functions, "countdown").calls, 8);
+    let cube = function(&functions, "Scene.cube");
+    assert_eq!((cube.kind, cube.calls), (CallKind::Host, 2));
+    for function in &functions {
+        assert!(
+            function.exclusive <= function.inclusive,
+            "{}",
+            function.name
+        );
+    }
+
+    let collapsed = profiler.collapsed();
+    let stacks: Vec<&str> = collapsed
+        .lines()
+        .map(|line| line.rsplit_once(' ').expect("`stack weight`").0)
+        .collect();
+    // Self-recursion folds to one frame; the host call is a leaf under draw.
+    assert_eq!(
+        stacks,
+        [
+            "draw",
+            "draw;List.map",
+            "draw;List.map;List.map[*]",
+            "draw;Scene.cube",
+            "draw;countdown"
+        ]
+    );
+
+    let trace = Json::parse(&profiler.render(ProfileFormat::Chrome)).expect("chrome JSON");
+    let Json::Object(fields) = trace else {
+        panic!("a trace is an object");
+    };
+    let Some((_, Json::Array(events))) = fields.iter().find(|(key, _)| key == "traceEvents") else {
+        panic!("no traceEvents");
+    };
+    // One complete event per call: 2 × (draw + List.map + 3 + 4 + cube).
+    assert_eq!(events.len(), 20);
+    assert!(
+        events[0].compact().contains("\"ph\": \"X\""),
+        "{}",
+        events[0]
+    );
+}
+
+#[test]
+fn a_failed_call_unwinds_its_frames() {
+    let src = "let boom = (x) => List.head([]) + x\nlet update = (m) => boom(m)";
+    let (session, profiler) = profiled(src);
+    assert!(session
+        .call("update", vec![Value::Number(1.0)], &mut NoHost)
+        .is_err());
+    assert!(session
+        .call("update", vec![Value::Number(1.0)], &mut NoHost)
+        .is_err());
+
+    // The second call's stack starts at the root again: nothing leaked open.
+    let collapsed = profiler.borrow().collapsed();
+    assert!(
+        collapsed
+            .lines()
+            .all(|line| !line.starts_with("update;boom;update")),
+        "{collapsed}"
+    );
+    let functions = profiler.borrow().functions();
+    assert_eq!(function(&functions, "update").calls, 2);
+    assert_eq!(function(&functions, "boom").calls, 2);
+
+    let summary = Json::parse(&profiler.borrow().summary()).expect("summary JSON");
+    assert!(
+        summary.compact().contains("\"name\": \"update\""),
+        "{summary}"
+    );
+    assert_eq!("collapsed".parse(), Ok(ProfileFormat::Collapsed));
+    assert_eq!(
+        ProfileFormat::for_path(std::path::Path::new("out.json")),
+        ProfileFormat::Chrome
+    );
+}
@@ -1139,6 +1139,41 @@ fn stored_closures_from_siblings_rebind_across_reload() {
     assert_eq!(number(&result), 7.0);
 }
 
+/// A closure applied to a NEWER session without being rebound keeps its own
+/// body: the new module's compiled code is keyed by the same lambda ids, but
+/// it only runs a closure whose body it was compiled from.
+#[test]
+fn unrebound_closures_keep_their_body_in_a_newer_session() {
+    let game = "let main = () => Util.makeSpring(3.0)\n";
+    let old = load(
+        "stale-old",
+        &[
+            ("game.fun", game),
+            ("util.fun", "let makeSpring = (k) => (x) => x * k\n"),
+        ],
+    );
+    let record = functor_lang::run(&old.module, Tracing::Off)
+        .unwrap_or_else(|f| panic!("v1 runs: {}", f.error.message));
+    let RunOutcome::Main(stored) = record.outcome else {
+        panic!("expected a closure");
+    };
+    let new = load(
+        "stale-new",
+        &[
+            ("game.fun", game),
+            // Same shape, so the lambdas' ids line up with the old ones.
+            ("util.fun", "let makeSpring = (k) => (x) => x + k\n"),
+        ],
+    );
+    let session = functor_lang::Session::load(&new.module, &mut functor_lang::NoHost)
+        .unwrap_or_else(|f| panic!("v2 session: {}", f.error.message));
+    let result = session
+        .apply(stored, vec![Value::Number(2.0)], "spring", &mut functor_lang::NoHost)
+        .expect("apply");
+    // Old body, old captured k: 2 * 3.
+    assert_eq!(number(&result), 6.0);
+}
+
 /// Same-named defs in DIFFERENT modules stay distinct rebind identities:
 /// editing one module's `make` must not confuse a closure from the other's.
 #[test]
@@ -2355,3 +2390,152 @@ fn a_host_type_is_rejected_in_an_implementation_file() {
     );
     assert!(message.contains("belongs in an interface (.funi) file"), "{message}");
 }
+
+// ── Visibility: `private` and sealing interfaces ─────────────────────────
+
+/// A `private let` is an ordinary binding inside its own file, and a load
+/// error when another file names it.
+#[test]
+fn a_private_let_is_reachable_only_from_its_own_file() {
+    let value = run_main(
+        "private-let",
+        &[
+            ("game.fun", "let main = () => Utils.twice(4.0)\n"),
+            (
+                "utils.fun",
+                "private let helper = (n: float): float => n + n\n\
+                 let twice = (n: float): float => helper(n)\n",
+            ),
+        ],
+    );
+    assert_eq!(number(&value), 8.0);
+    let message = load_err(
+        "private-let-leak",
+        &[
+            ("game.fun", "let main = () => Utils.helper(4.0)\n"),
+            ("utils.fun", "private let helper = (n: float): float => n + n\n"),
+        ],
+    );
+    assert!(message.contains("private to module `Utils`"), "{message}");
+}
+
+/// A `private type` hides its name and constructors, and `open` brings in only
+/// what the module makes public.
+#[test]
+fn a_private_type_and_open_respect_visibility() {
+    let message = load_err(
+        "private-type",
+        &[
+            ("game.fun", "let main = () => Utils.Secret(1.0)\n"),
+            ("utils.fun", "private type Hidden = | Secret(value: float)\n"),
+        ],
+    );
+    assert!(message.contains("private to module `Utils`"), "{message}");
+    let message = load_err(
+        "private-open",
+        &[
+            ("game.fun", "open Utils\nlet main = () => helper(1.0)\n"),
+            (
+                "utils.fun",
+                "private let helper = (n: float): float => n\n\
+                 let visible = 1.0\n",
+            ),
+        ],
+    );
+    assert!(message.contains("helper"), "{message}");
+}
+
+const STACK_IMPL: &str = "type Stack = { items: List<float> }\n\
+                          let empty = (): Stack => { items: [] }\n\
+                          let push = (s: Stack, n: float): Stack =>\n\
+                          \x20 { items: List.append(s.items, [n]) }\n\
+                          let size = (s: Stack): float => List.length(s.items)\n\
+                          let helper = 2.0\n";
+
+const STACK_SEAL: &str = "type Stack\n\
+                          let empty : () => Stack\n\
+                          let push : (Stack, float) => Stack\n\
+                          let size : (Stack) => float\n";
+
+/// A `.funi` beside a `.fun` seals it: declared values stay usable and the
+/// program runs, everything undeclared is refused from other files.
+#[test]
+fn a_sealing_interface_exports_only_what_it_declares() {
+    let files = [
+        (
+            "game.fun",
+            "let main = () => Utils.size(Utils.push(Utils.push(Utils.empty(), 1.0), 2.0))\n",
+        ),
+        ("utils.fun", STACK_IMPL),
+        ("utils.funi", STACK_SEAL),
+    ];
+    let project = load("sealed", &files);
+    assert!(project.check().is_empty(), "{:?}", project.check());
+    assert_eq!(number(&run_main("sealed-run", &files)), 2.0);
+    let message = load_err(
+        "sealed-hidden",
+        &[
+            ("game.fun", "let main = () => Utils.helper\n"),
+            ("utils.fun", STACK_IMPL),
+            ("utils.funi", STACK_SEAL),
+        ],
+    );
+    assert!(
+        message.contains("is not part of module `Utils`'s interface (utils.funi)"),
+        "{message}"
+    );
+}
+
+/// An abstract type in the seal keeps its representation private: other files
+/// can pass a `Utils.Stack` around but not look inside it.
+#[test]
+fn an_abstract_sealed_type_is_opaque_outside_its_file() {
+    let project = load(
+        "sealed-opaque",
+        &[
+            ("game.fun", "let peek = (s: Utils.Stack) => s.items\n"),
+            ("utils.fun", STACK_IMPL),
+            ("utils.funi", STACK_SEAL),
+        ],
+    );
+    let diags = project.check();
+    assert!(
+        diags
+            .iter()
+            .any(|d| d.message.contains("is abstract outside module `Utils`")),
+        "{diags:?}"
+    );
+}
+
+/// The implementation is verified against its seal: a missing definition is a
+/// load error, a less general one a check error.
+#[test]
+fn a_sealed_implementation_is_verified_against_its_interface() {
+    let message = load_err(
+        "sealed-missing",
+        &[
+            ("game.fun", "let main = () => 1.0\n"),
+            ("utils.fun", "let one = 1.0\n"),
+            ("utils.funi", "let one : float\nlet two : float\n"),
+        ],
+    );
+    assert!(
+        message.contains("utils.funi declares `two`, but utils.fun has no `let two`"),
+        "{message}"
+    );
+    let project = load(
+        "sealed-general",
+        &[
+            ("game.fun", "let main = () => 1.0\n"),
+            ("utils.fun", "let id = (x: float): float => x\n"),
+            ("utils.funi", "let id : ('a) => 'a\n"),
+        ],
+    );
+    let diags = project.check();
+    assert!(
+        diags
+            .iter()
+            .any(|d| d.message.contains("less general than its interface")),
+        "{diags:?}"
+    );
+}
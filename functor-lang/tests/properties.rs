@@ -0,0 +1,157 @@
+//! `property` tests: parsing (a contextual keyword over an all-annotated
+//! lambda), checking (the body must be bool), generation from declared
+//! records and variants, shrinking to a minimal counterexample, seeded
+//! replay, and the step budget applied per case.
+
+use functor_lang::{Counterexample, ExpectOptions, ExpectOutcome, NoHost};
+
+fn lower(src: &str) -> functor_lang::ir::Module {
+    let program = functor_lang::parse(src).expect("source should parse");
+    functor_lang::lower(program).expect("source should lower")
+}
+
+fn run(src: &str, options: &ExpectOptions) -> Vec<functor_lang::ExpectReport> {
+    functor_lang::run_expects_with(&lower(src), &mut NoHost, options)
+        .unwrap_or_else(|failure| panic!("defs should load: {}", failure.error.message))
+}
+
+fn reports(src: &str) -> Vec<functor_lang::ExpectReport> {
+    run(src, &ExpectOptions::default())
+}
+
+fn falsified(report: &functor_lang::ExpectReport) -> &Counterexample {
+    match &report.outcome {
+        ExpectOutcome::Falsified(counterexample) => counterexample,
+        other => panic!("expected a counterexample, got {other:?}"),
+    }
+}
+
+// ---------------------------------------------------------------- parsing
+
+#[test]
+fn property_parses_beside_expects_and_stays_a_usable_name() {
+    let module =
+        lower("let property = 2.0\nproperty (x: float) => x == x\nexpect property == 2.0\n");
+    assert_eq!(module.expects.len(), 2);
+    assert_eq!(module.defs.len(), 1);
+}
+
+#[test]
+fn property_must_be_a_lambda_with_annotated_parameters() {
+    let err = functor_lang::parse("property 1 == 1\n").expect_err("not a lambda");
+    assert!(
+        err.message.contains("lambda over its generated inputs"),
+        "{}",
+        err.message
+    );
+    let err = functor_lang::parse("property (x) => x == x\n").expect_err("unannotated");
+    assert!(err.message.contains("`x` needs a type"), "{}", err.message);
+}
+
+// --------------------------------------------------------------- checking
+
+#[test]
+fn non_bool_property_is_a_check_error() {
+    let module = lower("property (x: float) => x + 1.0\n");
+    let diags = functor_lang::check(&module);
+    assert_eq!(diags.len(), 1, "{diags:?}");
+    assert!(
+        diags[0].message.contains("expected bool, got float"),
+        "{}",
+        diags[0].message
+    );
+    let module = lower("property (x: float, y: float) => x + y == y + x\n");
+    assert!(functor_lang::check(&module).is_empty());
+}
+
+// ------------------------------------------------------------- generating
+
+#[test]
+fn a_law_that_holds_passes() {
+    let out = reports(
+        "type point = { x: float, y: float }\n\
+         property (xs: List<int>) => List.reverse(List.reverse(xs)) == xs\n\
+         property (p: point, s: string) => { x: p.x, y: p.y } == p && Text.concat(s, \"\") == s\n",
+    );
+    assert!(
+        out.iter().all(|r| matches!(r.outcome, ExpectOutcome::Pass)),
+        "{out:?}"
+    );
+}
+
+#[test]
+fn a_failure_shrinks_to_the_boundary() {
+    let out = reports("property (n: int) => n < 10\n");
+    let cx = falsified(&out[0]);
+    assert_eq!(cx.inputs, [("n".to_string(), "10".to_string())]);
+    let ExpectOutcome::Fail(Some(cmp)) = &cx.failure else {
+        panic!("expected a decomposed comparison, got {:?}", cx.failure);
+    };
+    assert_eq!((cmp.lhs.as_str(), cmp.rhs.as_str()), ("10", "10"));
+}
+
+#[test]
+fn variants_shrink_toward_simpler_constructors() {
+    let src = "type tree = | Leaf | Node(left: tree, value: int, right: tree)\n\
+               let sum = (t: tree): int =>\n  match t with\n  | Leaf => 0\n  \
+               | Node(l, v, r) => sum(l) + v + sum(r)\n\
+               property (t: tree) => sum(t) < 5\n";
+    let out = reports(src);
+    assert_eq!(falsified(&out[0]).inputs[0].1, "Node(Leaf, 5, Leaf)");
+}
+
+#[test]
+fn a_runtime_error_is_a_counterexample_too() {
+    let out = reports("property (n: int) => 100 / n != 7\n");
+    let cx = falsified(&out[0]);
+    assert_eq!(cx.inputs[0].1, "0");
+    assert!(
+        matches!(cx.failure, ExpectOutcome::Error(_)),
+        "{:?}",
+        cx.failure
+    );
+}
+
+#[test]
+fn the_seed_replays_the_run_and_is_reported() {
+    let src = "property (xs: List<float>) => List.length(xs) < 4.0\n";
+    let options = ExpectOptions {
+        seed: 42,
+        ..ExpectOptions::default()
+    };
+    let (first, again) = (run(src, &options), run(src, &options));
+    let (a, b) = (falsified(&first[0]), falsified(&again[0]));
+    assert_eq!((a.case, &a.inputs), (b.case, &b.inputs));
+    assert_eq!(a.inputs[0].1, "[0, 0, 0, 0]");
+    let (state, detail) = first[0].outcome.status();
+    assert_eq!(state, "fail");
+    assert!(detail.unwrap().contains("seed 42"));
+}
+
+#[test]
+fn types_without_a_generator_are_errors_at_the_annotation() {
+    let src = "property (xs: List<'a>) => xs == xs\n";
+    let out = reports(src);
+    let ExpectOutcome::Error(err) = &out[0].outcome else {
+        panic!("expected an error, got {:?}", out[0].outcome);
+    };
+    assert!(err.message.contains("concrete types"), "{}", err.message);
+    assert_eq!(&src[err.span.start..err.span.end], "List<'a>");
+}
+
+// ------------------------------------------------------------- step budget
+
+#[test]
+fn a_runaway_case_reports_the_budget_unshrunk() {
+    let src = "let sum = (n) => List.range(n) |> List.fold((acc, x) => acc + x, 0.0)\n\
+               property (n: int) => sum(1000000.0) > Math.toFloat(n)\n";
+    let options = ExpectOptions {
+        budget: Some(1_000),
+        ..ExpectOptions::default()
+    };
+    let out = run(src, &options);
+    let ExpectOutcome::Error(err) = &out[0].outcome else {
+        panic!("expected a budget error, got {:?}", out[0].outcome);
+    };
+    assert!(err.message.contains("step budget"), "{}", err.message);
+}
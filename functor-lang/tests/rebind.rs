@@ -232,7 +232,7 @@ fn closures_nested_in_maps_rebind_and_keep_snapshots_unsafe() {
         panic!("expected map");
     };
     assert_eq!(entries.len(), 1);
-    assert_eq!(num(&apply(&b, entries[0].1.clone(), 2.0)), 12.0);
+    assert_eq!(num(&apply(&b, entries.iter().next().unwrap().1.clone(), 2.0)), 12.0);
 }
 
 #[test]
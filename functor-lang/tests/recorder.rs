@@ -4,7 +4,7 @@
 //! last-value + hit-count per site, kind/preview rendering policy, and a
 //! `truncated` cap flag — and NO effect on evaluation itself.
 
-use functor_lang::value::Value;
+use functor_lang::value::{List, Value};
 use functor_lang::{
     NoHost, RecordedBinding, RecordedInvocation, RecordedKind, RecordedSite, Session,
 };
@@ -94,7 +94,7 @@ fn records_match_binders() {
 fn loop_site_keeps_last_value_and_counts_hits() {
     let src = "let sum = (xs) => List.fold((acc, x) => acc + x, 0.0, xs)";
     let session = session(src);
-    let xs = Value::List(Rc::new(vec![
+    let xs = Value::List(List::from(vec![
         Value::Number(1.0),
         Value::Number(2.0),
         Value::Number(3.0),
@@ -216,7 +216,7 @@ fn long_values_elide_in_previews() {
     assert_eq!(s.preview, format!("\"{}…\"", "x".repeat(40)));
 
     // A long list elides after 4 items.
-    let xs = Value::List(Rc::new((0..10).map(|i| Value::Number(i as f64)).collect()));
+    let xs = Value::List((0..10).map(|i| Value::Number(i as f64)).collect());
     let (_, inv) = session
         .call_recorded("firstOf", vec![xs], &mut NoHost)
         .expect("call_recorded");
@@ -228,7 +228,7 @@ fn loop_reference_sites_count_reads() {
     // Inside the fold closure, `acc`'s READ site is hit once per element.
     let src = "let sum = (xs) => List.fold((acc, x) => acc + x, 0.0, xs)";
     let session = session(src);
-    let xs = Value::List(Rc::new(vec![
+    let xs = Value::List(List::from(vec![
         Value::Number(1.0),
         Value::Number(2.0),
         Value::Number(3.0),
@@ -333,7 +333,7 @@ fn empty_collections_are_primitive() {
     let src = "let firstOf = (xs) => xs";
     let session = session(src);
     let (_, inv) = session
-        .call_recorded("firstOf", vec![Value::List(Rc::new(vec![]))], &mut NoHost)
+        .call_recorded("firstOf", vec![Value::List(List::from(vec![]))], &mut NoHost)
         .expect("call_recorded");
     let xs = binding(&inv, "xs");
     assert_eq!(xs.kind, RecordedKind::Primitive);
@@ -386,3 +386,20 @@ fn cap_breach_truncates_but_result_is_exact() {
         .expect("call");
     assert_eq!(recorded.to_string(), plain.to_string());
 }
+
+#[test]
+fn tail_calls_record_every_logical_call() {
+    // The tail call reuses the frame, but each logical call still binds its
+    // params: the site count is the call count and the last value wins.
+    let src = "let down = (n) => if n < 1.0 then 0.0 else down(n - 1.0)";
+    let session = session(src);
+    let (result, inv) = session
+        .call_recorded("down", vec![Value::Number(5.0)], &mut NoHost)
+        .expect("call_recorded");
+
+    assert_eq!(result.to_string(), "0");
+    let n = binding(&inv, "n");
+    assert_eq!(n.count, 6);
+    assert_eq!(n.value, "0");
+    assert_eq!((n.min, n.max), (Some(0.0), Some(5.0)));
+}
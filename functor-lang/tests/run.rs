@@ -162,7 +162,7 @@ fn map_rejects_non_finite_and_non_scalar_keys() {
         ),
         (
             "let main = () => Map.empty() |> Map.insert([1.0], 1.0)",
-            "keys must be bools, finite floats, or strings",
+            "keys must be bools, ints, finite floats, or strings",
         ),
     ] {
         let (message, _, _) = run_err(src);
@@ -179,6 +179,95 @@ fn map_from_list_rejects_malformed_entries() {
     );
 }
 
+// ------------------------------------------------------ immutable Set, Array
+
+#[test]
+fn set_operations_are_immutable_canonical_and_subject_last() {
+    assert_eq!(
+        main_result(
+            r#"
+let main = () =>
+  let seen = Set.fromList(["b", "a", "b"]) in
+  let more = seen |> Set.insert("c") |> Set.remove("a") in
+  (seen, more, Set.member("a", seen), Set.member("a", more))
+"#
+        ),
+        r#"(Set.fromList(["a", "b"]), Set.fromList(["b", "c"]), true, false)"#
+    );
+}
+
+#[test]
+fn set_algebra_reads_in_pipeline_order() {
+    assert_eq!(
+        main_result(
+            r#"
+let main = () =>
+  let visible = Set.fromList([1, 2, 3, 4]) in
+  let seen = Set.fromList([3, 4, 5]) in
+  (Set.union(seen, visible) |> Set.toList,
+   Set.intersect(seen, visible) |> Set.toList,
+   visible |> Set.difference(seen) |> Set.toList,
+   Set.fromList([2, 1]) == (Set.empty() |> Set.insert(1) |> Set.insert(2)))
+"#
+        ),
+        "([1, 2, 3, 4, 5], [3, 4], [1, 2], true)"
+    );
+}
+
+#[test]
+fn set_rejects_non_scalar_elements() {
+    let (message, _, _) = run_err("let main = () => Set.empty() |> Set.insert([1.0])");
+    assert!(
+        message.contains("Set.insert elements must be bools, ints, finite floats, or strings"),
+        "unexpected message: {message}"
+    );
+}
+
+#[test]
+fn array_reads_and_writes_by_int_index() {
+    assert_eq!(
+        main_result(
+            r#"
+let main = () =>
+  let grid = Array.make(3, 0) in
+  let marked = grid |> Array.set(1, 7) in
+  (grid, marked, Array.get(1, marked), Array.get(3, marked), Array.get(-1, marked),
+   Array.length(marked))
+"#
+        ),
+        "(Array.fromList([0, 0, 0]), Array.fromList([0, 7, 0]), Option.Some(7), Option.None, \
+Option.None, 3)"
+    );
+    assert_eq!(
+        main_result(
+            r#"
+let main = () =>
+  let xs = Array.fromList([1, 2, 3]) |> Array.map((x) => x * 10) in
+  (Array.fold((acc, x) => acc + x, 0, xs), Array.toList(xs),
+   xs == Array.fromList([10, 20, 30]))
+"#
+        ),
+        "(60, [10, 20, 30], true)"
+    );
+}
+
+#[test]
+fn array_writes_outside_the_array_and_bad_lengths_are_errors() {
+    for (src, needle) in [
+        (
+            "let main = () => Array.make(2, 0) |> Array.set(2, 1)",
+            "Array.set index 2 is out of range for an array of length 2",
+        ),
+        (
+            "let main = () => Array.make(-1, 0)",
+            "Array.make needs a length from 0 to 1000000, got -1",
+        ),
+    ] {
+        let (message, _, _) = run_err(src);
+        assert!(message.contains(needle), "unexpected message: {message}");
+    }
+}
+
 #[test]
 fn string_interpolation_renders_values_and_literal_braces() {
     assert_eq!(
@@ -293,6 +382,11 @@ fn golden_run_lists() {
     check_golden("lists", "run");
 }
 
+#[test]
+fn golden_run_patterns() {
+    check_golden("patterns", "run");
+}
+
 #[test]
 fn golden_run_strings() {
     check_golden("strings", "run");
@@ -467,8 +561,9 @@ fn list_grid_rejects_non_integer_or_negative_dims() {
 
 #[test]
 fn error_infinite_recursion_is_a_clean_error() {
+    // Not a tail call (the `+` waits on it), so every call holds a frame.
     let (message, _, _) = run_err(
-        "let spin = (n) => spin(n + 1.0)\n\
+        "let spin = (n) => 1.0 + spin(n + 1.0)\n\
          let main = () => spin(0.0)",
     );
     // The improved cap error names the numeric cap and points at the
@@ -1039,6 +1134,90 @@ fn math_sign_is_zero_at_zero() {
     );
 }
 
+/// Int arithmetic is exact at magnitudes a float cannot represent, and `/`
+/// is Euclidean: the remainder `a - (a / b) * b` is never negative.
+#[test]
+fn int_arithmetic_is_exact_and_divides_euclidean() {
+    assert_eq!(
+        main_result("let main = () => [7 / 2, -7 / 2, 7 / -2, -7 / -2]"),
+        "[3, -4, -3, 4]"
+    );
+    assert_eq!(
+        main_result("let main = () => 9007199254740993 + 0"),
+        "9007199254740993"
+    );
+    assert_eq!(
+        main_result("let main = () => 9007199254740993 > 9007199254740992"),
+        "true"
+    );
+    assert_eq!(main_result("let main = () => -(2 * 3 - 10)"), "4");
+}
+
+/// `%` is the remainder that goes with Euclidean `/`: never negative, for
+/// ints and floats alike, and it binds like `*`.
+#[test]
+fn remainder_operator_is_euclidean() {
+    assert_eq!(
+        main_result("let main = () => [7 % 3, -7 % 3, 7 % -3, -7 % -3]"),
+        "[1, 2, 1, 2]"
+    );
+    assert_eq!(
+        main_result("let main = () => List.map((a) => a / 3 * 3 + a % 3 == a, [-7, -1, 0, 8])"),
+        "[true, true, true, true]"
+    );
+    assert_eq!(main_result("let main = () => -1.0 % 8.0"), "7");
+    assert_eq!(main_result("let main = () => 10 - 7 % 4 * 2"), "4");
+    let (message, _, _) = run_err("let main = () => 5 % 0");
+    assert_eq!(message, "int division by zero (5 % 0)");
+}
+
+/// An int never wraps and never answers `inf`: overflow and a zero divisor
+/// stop the run.
+#[test]
+fn int_overflow_and_division_by_zero_are_runtime_errors() {
+    let (message, _, _) = run_err("let main = () => 9223372036854775807 + 1");
+    assert_eq!(message, "int overflow in `+` (ints are 64-bit and never wrap)");
+    let (message, _, _) = run_err("let main = () => -(-9223372036854775807 - 1)");
+    assert_eq!(message, "int overflow in `-` (ints are 64-bit and never wrap)");
+    let (message, _, _) = run_err("let main = () => 1 / 0");
+    assert_eq!(message, "int division by zero (1 / 0)");
+}
+
+/// The two number kinds meet only through an explicit conversion.
+#[test]
+fn int_and_float_convert_explicitly_and_never_mix() {
+    let (message, _, _) = run_err("let main = () => 1 + 1.0");
+    assert_eq!(
+        message,
+        "`+` mixes int and float — convert one side with `Math.toFloat` or `Math.toInt`"
+    );
+    assert_eq!(main_result("let main = () => 1 == 1.0"), "false");
+    assert_eq!(main_result("let main = () => Math.toFloat(3) / 2.0"), "1.5");
+    assert_eq!(
+        main_result("let main = () => [Math.toInt(2.7), Math.toInt(-2.7)]"),
+        "[2, -2]"
+    );
+    let (message, _, _) = run_err("let main = () => Math.toInt(0.0 / 0.0)");
+    assert!(message.contains("has no int answer"), "{message}");
+}
+
+/// Int literals match int scrutinees (a leading `-` included), and ints are
+/// Map keys ordered after bools and before floats.
+#[test]
+fn int_patterns_and_map_keys() {
+    let src = "let name = (n: int): string =>\n\
+               match n with | 0 => \"zero\" | -1 => \"minus one\" | _ => \"many\"\n\
+               let main = () => [name(0), name(-1), name(7)]";
+    assert_eq!(main_result(src), "[\"zero\", \"minus one\", \"many\"]");
+    assert_eq!(
+        main_result(
+            "let main = () => Map.empty() |> Map.insert(2.0, \"f\") |> Map.insert(2, \"i\") \
+|> Map.insert(true, \"b\") |> Map.values"
+        ),
+        "[\"b\", \"i\", \"f\"]"
+    );
+}
+
 /// The remaining single-argument math additions.
 #[test]
 fn math_transcendental_additions() {
@@ -1110,9 +1289,7 @@ fn text_length_and_chars_are_scalar_values() {
     // (The escape is Rust's — the `.fun` source holds the literal combining
     // character, since Functor Lang has no `\u{…}` escape.)
     assert_eq!(
-        main_result(&format!(
-            "let main = () => Text.length(\"e\u{301}\")"
-        )),
+        main_result("let main = () => Text.length(\"e\u{301}\")"),
         "2"
     );
 
@@ -1333,6 +1510,25 @@ fn math_mod_is_euclidean() {
     assert_eq!(main_result("let main = () => Math.mod(0.0 - 1.0, 0.0 - 8.0)"), "7");
 }
 
+/// `Math.mod`, `abs`, `min`, and `max` answer ints for int arguments, with
+/// the same sign rule and overflow checks as the operators.
+#[test]
+fn math_number_functions_take_ints() {
+    assert_eq!(
+        main_result("let main = () => [Math.mod(7, 3), Math.mod(-7, 3), Math.mod(-1, -8)]"),
+        "[1, 2, 7]"
+    );
+    assert_eq!(
+        main_result("let main = () => [Math.abs(-4), Math.min(3, -2), Math.max(3, 9)]"),
+        "[4, -2, 9]"
+    );
+    assert_eq!(main_result("let main = () => -5 |> Math.abs"), "5");
+    let (message, _, _) = run_err("let main = () => Math.mod(5, 0)");
+    assert_eq!(message, "int division by zero (5 % 0)");
+    let (message, _, _) = run_err("let main = () => Math.abs(-9223372036854775807 - 1)");
+    assert_eq!(message, "int overflow in `Math.abs` (ints are 64-bit and never wrap)");
+}
+
 // `Math.pi` is a value, so applying it as a function is a runtime error.
 #[test]
 fn error_calling_math_pi() {
@@ -2278,16 +2474,14 @@ fn long_logical_chain_does_not_overflow() {
     // Left-assoc `&&`/`||` chains parse iteratively (no depth guard), so eval
     // must walk their spine iteratively too — a flat 2000-term chain must not
     // consume host stack per term. `true && … && true && false` == false.
-    let chain = std::iter::repeat("true")
-        .take(2000)
+    let chain = std::iter::repeat_n("true", 2000)
         .collect::<Vec<_>>()
         .join(" && ");
     assert_eq!(
         main_result(&format!("let main = () => {chain} && false")),
         "false"
     );
-    let ors = std::iter::repeat("false")
-        .take(2000)
+    let ors = std::iter::repeat_n("false", 2000)
         .collect::<Vec<_>>()
         .join(" || ");
     assert_eq!(
@@ -2420,3 +2614,178 @@ fn negative_number_sub_pattern() {
                let main = () => [sign((-1.0, 5.0)), sign((2.0, 5.0))]";
     assert_eq!(main_result(src), "[\"neg\", \"other\"]");
 }
+
+#[test]
+fn nested_patterns_match_structurally() {
+    let src = "type Shot = | Hit(x: float, y: float) | Miss\n\
+               type Result = | Found(shot: Shot) | Nothing\n\
+               let f = (r) =>\n\
+               \x20 match r with\n\
+               \x20 | Found(Hit(0.0, y)) => y\n\
+               \x20 | Found(Hit(x, _)) => x\n\
+               \x20 | _ => -1.0\n\
+               let main = () => [f(Found(Hit(0.0, 5.0))), f(Found(Hit(2.0, 5.0))), f(Found(Miss)), f(Nothing)]";
+    assert_eq!(main_result(src), "[5, 2, -1, -1]");
+}
+
+/// A record pattern ignores fields it doesn't name; a pattern naming a
+/// field the value lacks simply doesn't match.
+#[test]
+fn record_patterns_match_named_fields() {
+    let src = "let f = (r) =>\n\
+               \x20 match r with\n\
+               \x20 | { kind: \"dot\", size } => size\n\
+               \x20 | { radius: [r, .._] } => r\n\
+               \x20 | _ => 0.0\n\
+               let main = () => [f({ kind: \"dot\", size: 3.0, at: 1.0 }), f({ kind: \"ring\", size: 2.0 }), f({ radius: [7.0] })]";
+    assert_eq!(main_result(src), "[3, 0, 7]");
+}
+
+#[test]
+fn let_destructures_a_record_pattern() {
+    let src = "let main = () =>\n\
+               \x20 let { x, y: (a, b) } = { x: 1.0, y: (2.0, 3.0) } in\n\
+               \x20 x + a + b";
+    assert_eq!(main_result(src), "6");
+}
+
+/// A declined guard falls through to the next arm; the guard sees the
+/// pattern's variables.
+#[test]
+fn guards_fall_through_to_later_arms() {
+    let src = "let classify = (n) =>\n\
+               \x20 match n with\n\
+               \x20 | x when x < 0.0 => \"neg\"\n\
+               \x20 | 0.0 => \"zero\"\n\
+               \x20 | _ => \"pos\"\n\
+               let main = () => [classify(-2.0), classify(0.0), classify(3.0)]";
+    assert_eq!(main_result(src), "[\"neg\", \"zero\", \"pos\"]");
+}
+
+#[test]
+fn guard_must_be_bool_at_runtime() {
+    let (message, _, _) = run_err("let main = () => match 1.0 with | x when x => 1.0 | _ => 0.0");
+    assert_eq!(message, "`when` guard needs a bool, got a number");
+}
+
+/// Whichever alternative matched binds the shared variable — a failed
+/// alternative's partial bindings never leak into the body.
+#[test]
+fn or_patterns_bind_from_the_matching_alternative() {
+    let src = "let pick = (p) =>\n\
+               \x20 match p with\n\
+               \x20 | (x, 1.0) | (1.0, x) => x\n\
+               \x20 | _ => 0.0\n\
+               let main = () => [pick((5.0, 1.0)), pick((1.0, 7.0)), pick((2.0, 2.0))]";
+    assert_eq!(main_result(src), "[5, 7, 0]");
+}
+
+// --- Compiled closures (bytecode VM) ---
+
+/// A closure's captures come from every kind of enclosing binder — a
+/// parameter, a `let`, and a match variable — and survive two levels of
+/// nesting.
+#[test]
+fn compiled_closures_capture_params_lets_and_match_binders() {
+    let src = "let make = (k) =>\n\
+               \x20 let offset = k * 2.0 in\n\
+               \x20 match (k, offset) with\n\
+               \x20 | (a, b) when a > 0.0 => (x) => (y) => x + y + a + b\n\
+               \x20 | _ => (x) => (y) => 0.0\n\
+               let main = () => [make(1.0)(10.0)(100.0), make(-1.0)(10.0)(100.0)]";
+    assert_eq!(main_result(src), "[113, 0]");
+}
+
+/// A compiled call is ONE level of the eval-depth budget however its body
+/// nests, so a recursion twice as deep as a tree-walked body allowed now
+/// fits — while unbounded recursion still stops at the cap.
+#[test]
+fn compiled_recursion_costs_one_depth_level_per_call() {
+    let src = "let sumTo = (n, acc) =>\n\
+               \x20 match n < 1.0 with\n\
+               \x20 | true => acc\n\
+               \x20 | false => n + sumTo(n - 1.0, acc)\n\
+               let main = () => sumTo(120.0, 0.0)";
+    assert_eq!(main_result(src), "7260");
+}
+
+/// `let mut` slots live in the compiled frame: each call gets its own.
+#[test]
+fn compiled_mut_slots_are_per_frame() {
+    let src = "let count = (n) =>\n\
+               \x20 let mut total = 0.0 in\n\
+               \x20 total := total + n;\n\
+               \x20 if n > 0.0 then total + count(n - 1.0) else total\n\
+               let main = () => count(3.0)";
+    assert_eq!(main_result(src), "6");
+}
+
+// --- Tail calls ---
+
+/// A self-tail call replaces its frame: a loop far past the depth cap runs
+/// in constant host stack.
+#[test]
+fn self_tail_calls_run_past_the_depth_cap() {
+    let src = "let loop = (n, acc) =>\n\
+               \x20 if n < 1.0 then acc else loop(n - 1.0, acc + n)\n\
+               let main = () => loop(100000.0, 0.0)";
+    assert_eq!(main_result(src), "5000050000");
+}
+
+/// Mutual tail calls between two globals are replaced just the same, out of
+/// `match` arms as well as `if` branches.
+#[test]
+fn mutual_tail_calls_run_past_the_depth_cap() {
+    let src = "let isEven = (n) =>\n\
+               \x20 match n with\n\
+               \x20 | 0.0 => true\n\
+               \x20 | _ => isOdd(n - 1.0)\n\
+               let isOdd = (n) => if n == 0.0 then false else isEven(n - 1.0)\n\
+               let main = () => (isEven(50000.0), isOdd(50001.0), isEven(7.0))";
+    assert_eq!(main_result(src), "(true, true, false)");
+}
+
+/// A tail call through a `let` body and a list pattern walks a list far
+/// longer than the depth cap.
+#[test]
+fn tail_recursion_over_a_long_list() {
+    let src = "let total = (xs, acc) =>\n\
+               \x20 match xs with\n\
+               \x20 | [] => acc\n\
+               \x20 | [x, ..rest] =>\n\
+               \x20   let next = acc + x in\n\
+               \x20   total(rest, next)\n\
+               let main = () => total(List.range(2000.0), 0.0)";
+    assert_eq!(main_result(src), "1999000");
+}
+
+/// A saturating call of a partial application in tail position is a tail
+/// call too.
+#[test]
+fn tail_calls_through_a_partial_application() {
+    let src = "let count = (step, n) => if n < 1.0 then 0.0 else count(step)(n - step)\n\
+               let main = () => count(1.0, 50000.0)";
+    assert_eq!(main_result(src), "0");
+}
+
+/// The trace still shows every logical call of a tail loop, nested, each
+/// exiting with the loop's one result.
+#[test]
+fn tail_calls_still_trace_each_logical_call() {
+    let record = run_src(
+        "let down = (n) => if n < 1.0 then \"done\" else down(n - 1.0)\n\
+         let main = () => down(2.0)",
+        Tracing::On,
+    );
+    assert_eq!(
+        functor_lang::render_trace(&record.trace),
+        "> main()\n\
+         \x20 > down(2)\n\
+         \x20   > down(1)\n\
+         \x20     > down(0)\n\
+         \x20     < \"done\"\n\
+         \x20   < \"done\"\n\
+         \x20 < \"done\"\n\
+         < \"done\"\n"
+    );
+}
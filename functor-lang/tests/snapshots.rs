@@ -0,0 +1,175 @@
+//! `expectSnapshot` tests: parsing (a contextual keyword, a name unique per
+//! file), the stored dump's shape, structural diffs on a change, and the
+//! `__snapshots__` file a run records into and an update rewrites.
+
+use functor_lang::snapshot::{self, Goldens, Json};
+use functor_lang::{ExpectOptions, ExpectOutcome, NoHost};
+
+fn lower(src: &str) -> functor_lang::ir::Module {
+    let program = functor_lang::parse(src).expect("source should parse");
+    functor_lang::lower(program).expect("source should lower")
+}
+
+fn run(module: &functor_lang::ir::Module, goldens: Goldens) -> Vec<functor_lang::ExpectReport> {
+    let options = ExpectOptions {
+        snapshots: goldens,
+        ..ExpectOptions::default()
+    };
+    functor_lang::run_expects_with(module, &mut NoHost, &options)
+        .unwrap_or_else(|failure| panic!("defs should load: {}", failure.error.message))
+}
+
+const SRC: &str = "type shape = | Dot | Box(w: float, h: float)\n\
+                   let scene = (w: float) => { shapes: [Dot, Box(w, 2.0)], count: 2 }\n\
+                   expectSnapshot scene = scene(1.0)\n";
+
+#[test]
+fn snapshot_parses_checks_and_formats_as_written() {
+    let module = lower(SRC);
+    assert_eq!(module.expects.len(), 1);
+    assert!(functor_lang::check(&module).is_empty());
+    let formatted = functor_lang::format::format_source(SRC, false).unwrap();
+    assert!(
+        formatted.contains("\nexpectSnapshot scene = scene(1.0)\n"),
+        "{formatted}"
+    );
+    let err = functor_lang::parse("expectSnapshot a = 1\nexpectSnapshot a = 2\n").expect_err("dup");
+    assert!(
+        err.message.contains("duplicate snapshot `a`"),
+        "{}",
+        err.message
+    );
+    let err = functor_lang::parse("expectSnapshot = 1\n").expect_err("unnamed");
+    assert!(err.message.contains("a snapshot name"), "{}", err.message);
+}
+
+#[test]
+fn plain_data_dumps_in_the_effect_value_shape() {
+    let reports = run(&lower(SRC), Goldens::default());
+    assert!(matches!(reports[0].outcome, ExpectOutcome::Pass));
+    let dump = reports[0].snapshot.as_ref().expect("a dump");
+    assert_eq!(
+        dump.compact(),
+        "{\"Record\": [[\"shapes\", {\"List\": [{\"Variant\": [\"Dot\", []]}, \
+         {\"Variant\": [\"Box\", [{\"Number\": 1.0}, {\"Number\": 2.0}]]}]}], \
+         [\"count\", {\"Int\": 2}]]}"
+    );
+}
+
+#[test]
+fn a_function_has_no_snapshot_form() {
+    let reports = run(&lower("expectSnapshot f = (x) => x\n"), Goldens::default());
+    let ExpectOutcome::Error(err) = &reports[0].outcome else {
+        panic!("expected an error, got {:?}", reports[0].outcome);
+    };
+    assert!(
+        err.message.contains("snapshot `f`: a function"),
+        "{}",
+        err.message
+    );
+    assert!(reports[0].snapshot.is_none());
+}
+
+#[test]
+fn a_changed_value_fails_with_a_structural_diff_unless_updating() {
+    let module = lower(SRC);
+    let old = run(
+        &lower(&SRC.replace("scene(1.0)", "scene(3.0)")),
+        Goldens::default(),
+    );
+    let stored = old[0].snapshot.clone().unwrap();
+    let goldens = |update| Goldens {
+        stored: [(module.expects[0].span.start, stored.clone())].into(),
+        update,
+    };
+    let reports = run(&module, goldens(false));
+    let ExpectOutcome::Changed(diff) = &reports[0].outcome else {
+        panic!("expected a change, got {:?}", reports[0].outcome);
+    };
+    let lines: Vec<String> = diff.changes.iter().map(|c| c.to_string()).collect();
+    assert_eq!(lines, ["scene.shapes[1][0]: expected 3.0, got 1.0"]);
+    let (state, detail) = reports[0].outcome.status();
+    assert_eq!(state, "fail");
+    assert!(detail.unwrap().contains("--update-snapshots"));
+    assert!(matches!(
+        run(&module, goldens(true))[0].outcome,
+        ExpectOutcome::Pass
+    ));
+}
+
+#[test]
+fn runs_record_new_snapshots_and_updates_rewrite_changed_ones() {
+    let dir = std::env::temp_dir().join(format!("functor-lang-snapshots-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("game.fun");
+    let stored = dir.join("__snapshots__").join("game.json");
+    assert_eq!(snapshot::path_for(&source), stored);
+    let file_of = |_| Some(source.clone());
+    let cycle = |src: &str, update: bool| {
+        let module = lower(src);
+        let goldens = snapshot::load(&module, &file_of, update).expect("loads");
+        let reports = run(&module, goldens.clone());
+        let written = snapshot::save(&module, &reports, &goldens, &file_of).expect("saves");
+        (reports, written)
+    };
+
+    let (_, written) = cycle(SRC, false);
+    assert_eq!(written, 1);
+    let text = std::fs::read_to_string(&stored).unwrap();
+    assert!(
+        text.starts_with("{\n  \"scene\": {\n    \"Record\": ["),
+        "{text}"
+    );
+    assert_eq!(cycle(SRC, false).1, 0);
+
+    let changed = SRC.replace("scene(1.0)", "scene(5.0)");
+    let (reports, written) = cycle(&changed, false);
+    assert!(matches!(reports[0].outcome, ExpectOutcome::Changed(_)));
+    assert_eq!(
+        (written, std::fs::read_to_string(&stored).unwrap()),
+        (0, text)
+    );
+
+    let (reports, written) = cycle(&changed, true);
+    assert!(matches!(reports[0].outcome, ExpectOutcome::Pass));
+    assert_eq!(written, 1);
+    let entries = snapshot::read_file(&stored).unwrap();
+    let Some(Json::Object(fields)) = entries.get("scene") else {
+        panic!("a stored record: {entries:?}");
+    };
+    assert_eq!(fields[0].0, "Record");
+    assert!(std::fs::read_to_string(&stored).unwrap().contains("5.0"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn an_unselected_snapshot_keeps_its_stored_value() {
+    let dir = std::env::temp_dir().join(format!("functor-lang-selected-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("game.fun");
+    let file_of = |_| Some(source.clone());
+    let src = |a: &str| format!("expectSnapshot a = {a}\nexpectSnapshot b = 2\n");
+    let module = lower(&src("1"));
+    let goldens = snapshot::load(&module, &file_of, false).unwrap();
+    let reports = run(&module, goldens.clone());
+    assert_eq!(snapshot::save(&module, &reports, &goldens, &file_of), Ok(2));
+
+    let module = lower(&src("5"));
+    let goldens = snapshot::load(&module, &file_of, true).unwrap();
+    let options = ExpectOptions {
+        snapshots: goldens.clone(),
+        selected: Some([module.expects[1].span.start].into()),
+        ..ExpectOptions::default()
+    };
+    let reports = functor_lang::run_expects_with(&module, &mut NoHost, &options)
+        .unwrap_or_else(|failure| panic!("defs should load: {}", failure.error.message));
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].span, module.expects[1].span);
+    assert!(reports[0].duration.is_some());
+    assert_eq!(snapshot::save(&module, &reports, &goldens, &file_of), Ok(0));
+    let stored = snapshot::read_file(&snapshot::path_for(&source)).unwrap();
+    assert_eq!(stored["a"].compact(), "{\"Int\": 1}");
+    let _ = std::fs::remove_dir_all(&dir);
+}
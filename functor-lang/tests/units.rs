@@ -7,7 +7,7 @@
 //! desugars each suffixed literal to exactly the call the `unit` names — so
 //! everything downstream sees an ordinary call.
 
-use functor_lang::ast::{ExprKind, Item};
+use functor_lang::ast::{ExprKind, Item, Numeral};
 use functor_lang::lexer::{lex, TokenKind};
 use functor_lang::{RunOutcome, Tracing};
 
@@ -22,9 +22,9 @@ fn digits_followed_by_identifier_chars_lex_as_one_token() {
     assert_eq!(
         kinds[..3],
         [
-            &TokenKind::NumberUnit(90.0, "deg".to_string()),
-            &TokenKind::NumberUnit(0.5, "rad".to_string()),
-            &TokenKind::NumberUnit(16.0, "px".to_string()),
+            &TokenKind::NumberUnit(Numeral::Int(90), "deg".to_string()),
+            &TokenKind::NumberUnit(Numeral::Float(0.5), "rad".to_string()),
+            &TokenKind::NumberUnit(Numeral::Int(16), "px".to_string()),
         ]
     );
 }
@@ -34,7 +34,7 @@ fn digits_followed_by_identifier_chars_lex_as_one_token() {
 #[test]
 fn a_space_keeps_the_number_and_the_name_apart() {
     let tokens = lex("90 deg", 0).expect("lexes");
-    assert_eq!(tokens[0].kind, TokenKind::Number(90.0));
+    assert_eq!(tokens[0].kind, TokenKind::Int(90));
     assert_eq!(tokens[1].kind, TokenKind::Ident("deg".to_string()));
 }
 
@@ -44,7 +44,7 @@ fn a_space_keeps_the_number_and_the_name_apart() {
 fn plain_literals_are_unchanged() {
     let tokens = lex("1.5 + 2", 0).expect("lexes");
     assert_eq!(tokens[0].kind, TokenKind::Number(1.5));
-    assert_eq!(tokens[2].kind, TokenKind::Number(2.0));
+    assert_eq!(tokens[2].kind, TokenKind::Int(2));
 }
 
 /// The suffix spans the whole identifier, digits included (`16px2`), so a
@@ -52,7 +52,7 @@ fn plain_literals_are_unchanged() {
 #[test]
 fn a_suffix_runs_to_the_end_of_the_identifier() {
     let tokens = lex("16px2", 0).expect("lexes");
-    assert_eq!(tokens[0].kind, TokenKind::NumberUnit(16.0, "px2".to_string()));
+    assert_eq!(tokens[0].kind, TokenKind::NumberUnit(Numeral::Int(16), "px2".to_string()));
 }
 
 /// The lexer never produces a negative literal (as for plain numbers) — the
@@ -63,7 +63,7 @@ fn unary_minus_is_a_separate_token() {
     assert_eq!(tokens[0].kind, TokenKind::Minus);
     assert_eq!(
         tokens[1].kind,
-        TokenKind::NumberUnit(2.5, "px".to_string())
+        TokenKind::NumberUnit(Numeral::Float(2.5), "px".to_string())
     );
 }
 
@@ -78,7 +78,7 @@ fn a_prefix_minus_folds_into_the_literal() {
     };
     match &decl.value.kind {
         ExprKind::NumberUnit { value, suffix } => {
-            assert_eq!((*value, suffix.as_str()), (-2.5, "px"))
+            assert_eq!((*value, suffix.as_str()), (Numeral::Float(-2.5), "px"))
         }
         other => panic!("expected a negative unit literal, got {other:?}"),
     }
@@ -126,7 +126,7 @@ fn a_suffixed_literal_parses_as_a_literal_with_its_suffix() {
     };
     match &decl.value.kind {
         ExprKind::NumberUnit { value, suffix } => {
-            assert_eq!((*value, suffix.as_str()), (90.0, "deg"))
+            assert_eq!((*value, suffix.as_str()), (Numeral::Int(90), "deg"))
         }
         other => panic!("expected a unit literal, got {other:?}"),
     }
@@ -187,8 +187,7 @@ fn check_src(src: &str) -> Vec<String> {
 fn lower_err(src: &str) -> String {
     let program = functor_lang::parse(src).expect("source should parse");
     functor_lang::lower(program)
-        .err()
-        .expect("source should not lower")
+        .expect_err("source should not lower")
         .message
 }
 
@@ -358,7 +357,7 @@ fn hovering_a_suffixed_literal_shows_both_halves() {
 #[test]
 fn a_keyword_touching_a_number_is_not_a_suffix() {
     let tokens = lex("1else", 0).expect("lexes");
-    assert_eq!(tokens[0].kind, TokenKind::Number(1.0));
+    assert_eq!(tokens[0].kind, TokenKind::Int(1));
     assert_eq!(tokens[1].kind, TokenKind::Else);
     let src = "let pick = (c: bool): float => if c then 1else 2.0\n";
     assert!(functor_lang::parse(src).is_ok(), "`1else 2.0` still parses");
@@ -899,3 +898,45 @@ fn a_brand_without_an_equality_declaration_stays_structural() {
     assert!(check_src(src).is_empty(), "{:?}", check_src(src));
     assert_eq!(main_result(src), "(true, false)");
 }
+
+// ------------------------------------------------------- whole-number units
+
+/// A brand counted in whole numbers: its unit's target takes an `int`, so
+/// its `*` scales by an `int` too.
+const TICKS: &str = "type Ticks = | Ticks(count: int)\n\
+                     unit tick = Ticks\n\
+                     unit tick (+) = (a: Ticks, b: Ticks): Ticks =>\n  \
+                       match (a, b) with | (Ticks(x), Ticks(y)) => Ticks(x + y)\n\
+                     unit tick (*) = (a: Ticks, k: int): Ticks =>\n  \
+                       match a with | Ticks(x) => Ticks(x * k)\n";
+
+/// A unit whose target takes an `int` reads its literal as an `int`, and the
+/// brand's operators run exact int arithmetic underneath.
+#[test]
+fn a_unit_whose_target_takes_an_int_counts_whole_numbers() {
+    let src = format!("{TICKS}let main = () => 3tick + 4tick * 2\n");
+    assert!(check_src(&src).is_empty(), "{:?}", check_src(&src));
+    assert_eq!(main_result(&src), "Ticks(11)");
+}
+
+/// A fraction of a whole-number unit has no meaning, so it is refused where
+/// the literal is desugared rather than truncated.
+#[test]
+fn a_fractional_literal_on_a_whole_number_unit_is_an_error() {
+    let message = lower_err(&format!("{TICKS}let a = 1.5tick\n"));
+    assert!(message.contains("`tick` counts whole numbers"), "{message}");
+    assert!(message.contains("write a whole number like `1tick`"), "{message}");
+}
+
+/// The scalar of a whole-number brand is an `int`: scaling by a float is the
+/// int/float mix the checker refuses everywhere else.
+#[test]
+fn a_whole_number_brand_scales_by_an_int() {
+    let diags = check_src(&format!("{TICKS}let a = 3tick * 2.0\n"));
+    assert!(
+        diags
+            .iter()
+            .any(|d| d.contains("the right operand of `*` on `Ticks`") && d.contains("int")),
+        "{diags:?}"
+    );
+}
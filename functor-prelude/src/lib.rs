@@ -53,6 +53,7 @@ pub fn modules() -> Vec<(String, String)> {
         module("Frame", include_str!("../prelude/frame.funi")),
         module("Light", include_str!("../prelude/light.funi")),
         module("Fog", include_str!("../prelude/fog.funi")),
+        module("Post", include_str!("../prelude/post.funi")),
         module("Skybox", include_str!("../prelude/skybox.funi")),
         module(
             "RenderTarget",
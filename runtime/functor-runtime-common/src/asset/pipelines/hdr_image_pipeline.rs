@@ -0,0 +1,80 @@
+use crate::{
+    asset::{AssetCache, AssetPipeline},
+    environment::HdrImage,
+};
+
+/// Decode an image file to linear float RGB (`HdrImage`) with no GL
+/// hydration — the input of an equirect environment, which is prefiltered on
+/// the CPU before anything uploads. A Radiance `.hdr` keeps its values above
+/// 1.0; 8-bit formats map to [0, 1]. Like [`super::RawImagePipeline`], an
+/// unrecognized or corrupt file becomes a 0×0 sentinel the environment treats
+/// as a failed load rather than a panic.
+pub struct HdrImagePipeline;
+
+impl AssetPipeline<HdrImage> for HdrImagePipeline {
+    fn process(
+        &self,
+        bytes: Vec<u8>,
+        _asset_cache: &AssetCache,
+        _context: crate::asset::AssetPipelineContext,
+    ) -> HdrImage {
+        match image::load_from_memory(&bytes) {
+            Ok(image) => {
+                let rgb = image.to_rgb32f();
+                HdrImage {
+                    width: rgb.width(),
+                    height: rgb.height(),
+                    pixels: rgb.pixels().map(|p| p.0).collect(),
+                }
+            }
+            Err(e) => {
+                eprintln!("[hdr-image] cannot decode image: {e}");
+                HdrImage {
+                    width: 0,
+                    height: 0,
+                    pixels: vec![],
+                }
+            }
+        }
+    }
+
+    fn unloaded_asset(&self, _context: crate::asset::AssetPipelineContext) -> HdrImage {
+        HdrImage {
+            width: 1,
+            height: 1,
+            pixels: vec![[0.5; 3]],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::AssetPipelineContext;
+
+    fn process(bytes: Vec<u8>) -> HdrImage {
+        HdrImagePipeline.process(bytes, &AssetCache::new(), AssetPipelineContext {})
+    }
+
+    #[test]
+    fn radiance_hdr_keeps_values_above_one() {
+        let image = image::Rgb32FImage::from_pixel(2, 1, image::Rgb([4.0, 0.5, 0.25]));
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgb32F(image)
+            .write_to(&mut bytes, image::ImageFormat::Hdr)
+            .unwrap();
+        let data = process(bytes.into_inner());
+        assert_eq!((data.width, data.height), (2, 1));
+        assert!(
+            (data.pixels[0][0] - 4.0).abs() < 0.05,
+            "{:?}",
+            data.pixels[0]
+        );
+    }
+
+    #[test]
+    fn unrecognized_bytes_become_the_sentinel() {
+        let data = process(vec![1, 2, 3, 4]);
+        assert_eq!((data.width, data.height), (0, 0));
+    }
+}
@@ -7,5 +7,8 @@ pub use model_pipeline::*;
 mod raw_image_pipeline;
 pub use raw_image_pipeline::*;
 
+mod hdr_image_pipeline;
+pub use hdr_image_pipeline::*;
+
 mod heightmap_pipeline;
 pub use heightmap_pipeline::*;
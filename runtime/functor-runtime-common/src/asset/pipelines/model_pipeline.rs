@@ -6,8 +6,10 @@ use gltf::{buffer::Source as BufferSource, image::Source as ImageSource};
 
 use crate::animation::{Animation, AnimationChannel, AnimationProperty, AnimationValue, Keyframe};
 use crate::model::{
-    build_skeleton_from_skin, document_hierarchy, HierarchyNode, Model, ModelMesh, Skeleton,
+    build_skeleton_from_skin, document_hierarchy, HierarchyNode, Model, ModelMaterial, ModelMesh,
+    Skeleton,
 };
+use crate::pbr::PbrFactors;
 use crate::render::VertexPositionTextureSkinned;
 use crate::{
     asset::{AssetCache, AssetPipeline},
@@ -222,43 +224,62 @@ fn process_node(
 
             // Parse material
             let material = primitive.material();
+            let mut factors = PbrFactors::from_gltf(&material);
+            let texture_of = |info: Option<gltf::Texture>| {
+                info.map(|texture| {
+                    let image = &images[texture.source().index()];
+                    Texture2D::init_from_data(image.clone(), TextureOptions::default())
+                })
+            };
 
-            let (base_color_texture, base_color_factor) =
+            let base_color_texture =
                 if let Some(specular_glossiness_material) = material.pbr_specular_glossiness() {
-                    (
-                        specular_glossiness_material.diffuse_texture(),
-                        specular_glossiness_material.diffuse_factor(),
-                    )
+                    specular_glossiness_material.diffuse_texture()
                 } else {
-                    let material = material.pbr_metallic_roughness();
-                    (material.base_color_texture(), material.base_color_factor())
+                    material.pbr_metallic_roughness().base_color_texture()
                 };
 
-            let texture = if let Some(texture) = base_color_texture {
-                let texture_info = texture.texture();
-                let source = texture_info.source();
-                let image = &images[source.index()];
-
-                let texture_data = image.clone();
-                Texture2D::init_from_data(texture_data, TextureOptions::default())
+            let texture = if let Some(texture) = texture_of(base_color_texture.map(|t| t.texture()))
+            {
+                texture
             } else {
                 // No texture: glTF defines the color as the base color factor
                 // alone, so sample a 1x1 solid of it (untextured materials —
-                // e.g. Xbot.glb, HVGirl.glb — are flat factor colors).
+                // e.g. Xbot.glb, HVGirl.glb — are flat factor colors). The
+                // factor is folded in here, so the shader's is white.
                 let to_u8 = |c: f32| (c.clamp(0.0, 1.0) * 255.0) as u8;
-                let data = TextureData::solid_color([
-                    to_u8(base_color_factor[0]),
-                    to_u8(base_color_factor[1]),
-                    to_u8(base_color_factor[2]),
-                    to_u8(base_color_factor[3]),
-                ]);
+                let [r, g, b, a] = factors.base_color;
+                let data = TextureData::solid_color([to_u8(r), to_u8(g), to_u8(b), to_u8(a)]);
+                factors.base_color = [1.0, 1.0, 1.0, 1.0];
                 Texture2D::init_from_data(data, TextureOptions::default())
             };
 
+            let model_material = ModelMaterial {
+                factors,
+                normal_texture: texture_of(material.normal_texture().map(|t| t.texture())),
+                // Specular-glossiness packs specular/glossiness, not
+                // metallic/roughness, in its second texture — skip it.
+                metallic_roughness_texture: texture_of(
+                    material
+                        .pbr_specular_glossiness()
+                        .is_none()
+                        .then(|| {
+                            material
+                                .pbr_metallic_roughness()
+                                .metallic_roughness_texture()
+                        })
+                        .flatten()
+                        .map(|t| t.texture()),
+                ),
+                occlusion_texture: texture_of(material.occlusion_texture().map(|t| t.texture())),
+                emissive_texture: texture_of(material.emissive_texture().map(|t| t.texture())),
+            };
+
             let mesh = IndexedMesh::create(vertices, indices);
             let model_mesh = ModelMesh {
                 mesh,
                 base_color_texture: texture,
+                material: model_material,
                 transform,
             };
 
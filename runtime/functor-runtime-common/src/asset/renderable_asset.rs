@@ -41,6 +41,20 @@ impl<T: RenderableAsset> RuntimeRenderableAsset<T> {
         self
     }
 
+    /// The CPU-side asset and its options while they have not been uploaded.
+    /// `None` once hydrated — the upload consumes the CPU copy — which is why
+    /// only a renderer that never hydrates (the software renderer) reads this.
+    pub fn dehydrated(&self) -> Option<(Ref<'_, T>, Ref<'_, T::OptionsType>)> {
+        let state = self.state.borrow();
+        if !matches!(*state, Some(RenderableAssetState::Dehydrated(..))) {
+            return None;
+        }
+        Some(Ref::map_split(state, |s| match s {
+            Some(RenderableAssetState::Dehydrated(asset, options)) => (asset, options),
+            _ => unreachable!(),
+        }))
+    }
+
     pub fn get_opt(&self) -> Option<Ref<'_, T::HydratedType>> {
         if let Some(RenderableAssetState::Hydrated(ref _loaded)) = *self.state.borrow() {
             Some(Ref::map(self.state.borrow(), |s| {
@@ -346,6 +346,86 @@ fn handle(mut stream: TcpStream, tx: &mpsc::Sender<DebugRequest>) -> Option<()>
                 ),
             }
         }
+        ("POST", "/debug") => {
+            let command = match parse_json::<serde_json::Value>(&mut reader, content_length) {
+                Ok(command) => command,
+                Err(error) => {
+                    respond_text(
+                        &mut stream,
+                        cors_origin,
+                        400,
+                        "Bad Request",
+                        &format!("bad debug json: {error}"),
+                    );
+                    return Some(());
+                }
+            };
+            let (resp_tx, resp_rx) = mpsc::channel();
+            if tx.send(DebugRequest::Debug(command, resp_tx)).is_err() {
+                return runtime_gone(&mut stream, cors_origin);
+            }
+            match recv(resp_rx) {
+                Ok(Ok(body)) => respond_bytes(
+                    &mut stream,
+                    cors_origin,
+                    200,
+                    "OK",
+                    "application/json",
+                    body.to_string().as_bytes(),
+                ),
+                Ok(Err(message)) => {
+                    respond_text(&mut stream, cors_origin, 400, "Bad Request", &message)
+                }
+                Err(_) => respond_text(
+                    &mut stream,
+                    cors_origin,
+                    500,
+                    "Internal Server Error",
+                    "debug failed",
+                ),
+            }
+        }
+        ("GET", "/profile") => {
+            let format = match debug_protocol::parse_profile_query(query) {
+                Ok(format) => format,
+                Err(message) => {
+                    respond_text(&mut stream, cors_origin, 400, "Bad Request", &message);
+                    return Some(());
+                }
+            };
+            let (resp_tx, resp_rx) = mpsc::channel();
+            if tx.send(DebugRequest::Profile(format, resp_tx)).is_err() {
+                return runtime_gone(&mut stream, cors_origin);
+            }
+            match recv(resp_rx) {
+                Ok(Ok(profile)) => respond_bytes(
+                    &mut stream,
+                    cors_origin,
+                    200,
+                    "OK",
+                    if format.is_json() {
+                        "application/json"
+                    } else {
+                        "text/plain"
+                    },
+                    profile.as_bytes(),
+                ),
+                Ok(Err(message)) => respond_text(
+                    &mut stream,
+                    cors_origin,
+                    501,
+                    "Not Implemented",
+                    &message,
+                ),
+                Err(_) => respond_text(
+                    &mut stream,
+                    cors_origin,
+                    500,
+                    "Internal Server Error",
+                    "profile failed",
+                ),
+            }
+        }
         ("POST", "/time") => {
             let command = match parse_json::<TimeCommand>(&mut reader, content_length) {
                 Ok(command) => command,
@@ -1014,6 +1094,85 @@ Content-Length: {}\r\n\r\n{body}",
         assert!(response.ends_with("pinned by --fixed-time"));
     }
 
+    /// A debugger command crosses to the loop as parsed JSON and its answer
+    /// comes back as the JSON body; one the game cannot run is a 400 carrying
+    /// the reason, which the adapter shows as the failed request's message.
+    #[test]
+    fn debug_commands_round_trip_as_json() {
+        for (answer, expected) in [
+            (Ok(serde_json::json!({ "stopped": null })), "HTTP/1.1 200 OK\r\n"),
+            (Err("not stopped".to_string()), "HTTP/1.1 400 Bad Request\r\n"),
+        ] {
+            let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+            let body = r#"{"command":"status"}"#;
+            let request = format!(
+                "POST /debug HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            );
+            let client = connect(&listener, request);
+            let (tx, rx) = mpsc::channel();
+            let server = std::thread::spawn(move || handle(listener.accept().unwrap().0, &tx));
+
+            let rendered = answer.clone().map(|body| body.to_string());
+            match rx.recv().unwrap() {
+                DebugRequest::Debug(command, response) => {
+                    assert_eq!(command["command"], "status");
+                    response.send(answer).unwrap();
+                }
+                _ => panic!("expected debug request"),
+            }
+
+            assert_eq!(server.join().unwrap(), Some(()));
+            let response = client.join().unwrap();
+            assert!(response.starts_with(expected), "{response}");
+            let rendered = rendered.unwrap_or_else(|message| message);
+            assert!(response.ends_with(&rendered), "{response}");
+        }
+    }
+
+    /// The profile's format rides the query string to the loop and picks the
+    /// content type back; a runtime with no hooks is a 501, a bad format a
+    /// 400 that never reaches the loop.
+    #[test]
+    fn profile_requests_carry_their_format() {
+        use functor_lang::profile::ProfileFormat;
+        for (query, answer, expected) in [
+            ("", Ok("{\"functions\":[]}"), "HTTP/1.1 200 OK\r\nContent-Type: application/json"),
+            ("?format=collapsed", Ok("draw 12\n"), "HTTP/1.1 200 OK\r\nContent-Type: text/plain"),
+            ("?format=chrome", Err("no hooks"), "HTTP/1.1 501 Not Implemented\r\n"),
+        ] {
+            let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+            let request = format!("GET /profile{query} HTTP/1.1\r\nHost: localhost\r\n\r\n");
+            let client = connect(&listener, request);
+            let (tx, rx) = mpsc::channel();
+            let server = std::thread::spawn(move || handle(listener.accept().unwrap().0, &tx));
+
+            match rx.recv().unwrap() {
+                DebugRequest::Profile(format, response) => {
+                    let asked = query.trim_start_matches("?format=");
+                    let asked = if asked.is_empty() { "summary" } else { asked };
+                    assert_eq!(Ok(format), asked.parse::<ProfileFormat>());
+                    response.send(answer.map(str::to_string).map_err(str::to_string)).unwrap();
+                }
+                _ => panic!("expected profile request"),
+            }
+
+            assert_eq!(server.join().unwrap(), Some(()));
+            let response = client.join().unwrap();
+            assert!(response.starts_with(expected), "{response}");
+            assert!(response.ends_with(answer.unwrap_or_else(|message| message)), "{response}");
+        }
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let request = "GET /profile?format=svg HTTP/1.1\r\nHost: localhost\r\n\r\n".into();
+        let client = connect(&listener, request);
+        let (tx, _rx) = mpsc::channel();
+        let server = std::thread::spawn(move || handle(listener.accept().unwrap().0, &tx));
+        assert_eq!(server.join().unwrap(), Some(()));
+        let response = client.join().unwrap();
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{response}");
+    }
+
     /// The embedder transport's egress: the runtime's answer is the drained
     /// `ConnCommand` JSON verbatim, and a runtime on the socket transport
     /// REFUSES with 409 rather than answering an empty array — a coordinator
@@ -86,7 +86,16 @@ pub const DEBUG_PROTOCOL_SERVICE: &str = "functor debug runtime";
 /// transitions folded through the shared reducer (evented, like `key`, not
 /// whole-sample like `xr`) — and the optional `touch` field on `GET /state`'s
 /// input snapshot.
-pub const DEBUG_PROTOCOL_VERSION: u32 = 14;
+///
+/// 15 adds `POST /debug`, the source-level debugger `functor-lang-dap`
+/// attaches through: Debug Adapter Protocol commands (`setBreakpoints`,
+/// `continue`, `stackTrace`, …) answered with DAP response bodies, plus a
+/// polled `status`. Additive — a pre-v15 runtime answers 404.
+///
+/// 16 adds `GET /profile`, the hook profiler: per-function calls and
+/// inclusive/exclusive time as a summary, a Chrome trace, or collapsed
+/// stacks (`?format=`). Additive — a pre-v16 runtime answers 404.
+pub const DEBUG_PROTOCOL_VERSION: u32 = 16;
 
 /// The well-known localhost port `functor develop` serves this protocol on
 /// when no explicit `--debug-port` is given, so an agent can attach to a
@@ -145,7 +154,7 @@ pub const DEBUG_ROUTES: &[DebugRoute] = &[
     DebugRoute {
         method: "GET",
         path: "/scene",
-        description: "current frame as JSON: camera + scene + lights",
+        description: "current frame as JSON: camera + scene + lights, plus fog, post effects and other decorations when set",
     },
     DebugRoute {
         method: "GET",
@@ -207,6 +216,16 @@ pub const DEBUG_ROUTES: &[DebugRoute] = &[
         path: "/net/deliver",
         description: "embedder transport (--net-transport embedder): deliver inbound network events, a JSON array of {kind:\"connected\"|\"message\"|\"disconnected\"|\"error\", key, conn, text?/message?}; folded through update before the response; 409 under the default socket transport",
     },
+    DebugRoute {
+        method: "POST",
+        path: "/debug",
+        description: "source-level debugger — {\"command\":\"setBreakpoints\",\"arguments\":{...}} with Debug Adapter Protocol commands and response bodies (setBreakpoints, setExceptionBreakpoints, pause, continue, next, stepIn, stepOut, stackTrace, scopes, variables, evaluate, disconnect) plus {\"command\":\"status\"}; a breakpoint hit in a frame's update pauses the clock like POST /time; 400 with the message for a command that cannot run",
+    },
+    DebugRoute {
+        method: "GET",
+        path: "/profile",
+        description: "hook profiler — per-function calls, inclusive and exclusive time of everything the game's hooks ran since profiling began (the first request arms it, as --profile does at startup); ?format=summary (default, JSON) | chrome (Chrome trace-event JSON) | collapsed (folded stacks for flamegraph.pl, text/plain); 400 for an unknown format, 501 for a runtime with no hooks to profile",
+    },
 ];
 
 /// Build the JSON body returned by `GET /` on every runtime target.
@@ -418,7 +437,7 @@ pub struct RewindCommand {
 
 /// Why `POST /capture` could not return pixels.
 pub enum CaptureError {
-    /// No framebuffer is available, such as in desktop headless mode (HTTP 503).
+    /// No framebuffer is available, such as an idle XR session (HTTP 503).
     Unavailable(String),
     /// Readback or PNG encoding failed (HTTP 500).
     Failed(String),
@@ -521,6 +540,20 @@ pub fn parse_entry_role_query(
     }
 }
 
+/// The rendering a `GET /profile` query asks for: `format=summary` (also
+/// the default), `chrome`, or `collapsed`. An unknown key or format is an
+/// error, like an unknown entry-role key.
+pub fn parse_profile_query(query: &str) -> Result<functor_lang::profile::ProfileFormat, String> {
+    let mut format = functor_lang::profile::ProfileFormat::Summary;
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        match pair.split_once('=').unwrap_or((pair, "")) {
+            ("format", name) => format = name.parse()?,
+            (key, _) => return Err(format!("unknown profile key `{key}` — expected `format`")),
+        }
+    }
+    Ok(format)
+}
+
 /// One project-relative asset uploaded by `POST /reload-asset`.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ProjectAsset {
@@ -641,6 +674,16 @@ pub enum DebugRequest {
         Vec<crate::net::DeliveredEvent>,
         Sender<Result<String, String>>,
     ),
+    /// One source-level debugger command (see
+    /// [`crate::functor_lang_debug`]); `Err` is a command the game cannot
+    /// run — nothing stopped, or no source-level debugger.
+    Debug(serde_json::Value, Sender<Result<serde_json::Value, String>>),
+    /// The hook profile in the requested format, arming the profiler first
+    /// if it is not running; `Err` when the game has no hooks to profile.
+    Profile(
+        functor_lang::profile::ProfileFormat,
+        Sender<Result<String, String>>,
+    ),
 }
 
 #[cfg(test)]
@@ -680,6 +723,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn a_profile_query_names_its_format_or_is_refused() {
+        use functor_lang::profile::ProfileFormat;
+        assert_eq!(parse_profile_query(""), Ok(ProfileFormat::Summary));
+        assert_eq!(parse_profile_query("format=chrome"), Ok(ProfileFormat::Chrome));
+        assert_eq!(parse_profile_query("format=collapsed"), Ok(ProfileFormat::Collapsed));
+        assert!(parse_profile_query("format=svg").is_err());
+        assert!(parse_profile_query("fromat=chrome").is_err());
+    }
+
     /// A role that cannot be honored exactly must be REFUSED, never coerced:
     /// silently booting the wrong contract is the failure this whole query
     /// exists to prevent.
@@ -1038,6 +1091,8 @@ mod tests {
             "POST /rewind",
             "GET /net/outbound",
             "POST /net/deliver",
+            "POST /debug",
+            "GET /profile",
         ]
         .into_iter()
         .map(str::to_owned)
@@ -1060,7 +1115,7 @@ mod tests {
         let discovery: Value = serde_json::from_str(&discovery_json()).unwrap();
         assert_eq!(discovery["service"], DEBUG_PROTOCOL_SERVICE);
         assert_eq!(discovery["protocol_version"], DEBUG_PROTOCOL_VERSION);
-        assert_eq!(DEBUG_PROTOCOL_VERSION, 14);
+        assert_eq!(DEBUG_PROTOCOL_VERSION, 16);
     }
 
     /// The v10 fields are ADDITIVE: a pre-v10 payload (which carries neither)
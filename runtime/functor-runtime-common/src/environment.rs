@@ -0,0 +1,656 @@
+//! Image-based lighting: a frame's environment cubemap, prefiltered on the CPU
+//! into a specular mip chain (one GGX roughness per level) and nine
+//! spherical-harmonic coefficients of diffuse irradiance, plus reflection
+//! probes — the same prefiltered pair, baked from the scene at a point.
+//!
+//! The shared lighting GLSL samples whichever set is nearest a draw: the
+//! irradiance joins the diffuse (ambient) term and the specular cube adds
+//! Fresnel-weighted reflections. Both renderers read the CPU data here — the
+//! GL path uploads it once, the software renderer samples it directly.
+
+use cgmath::{vec3, InnerSpace, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::math::Angle;
+use crate::skybox::SkyboxDescription;
+use crate::texture::TextureData;
+use crate::Camera;
+
+/// Largest face size of a prefiltered specular cube; bigger sources are box
+/// filtered down first (reflections blur past the first mips anyway).
+pub const MAX_SPECULAR_SIZE: usize = 128;
+
+/// Face size reflection probes bake at, in texels.
+pub const PROBE_SIZE: u32 = 64;
+
+/// The texture unit the specular cube binds to: past the terrain's detail
+/// (1–4) and macro (5) units, since a cube and a 2D sampler may not share one.
+pub const ENVIRONMENT_TEXTURE_UNIT: u32 = 6;
+
+/// GGX importance samples per prefiltered texel.
+const SPECULAR_SAMPLES: u32 = 64;
+
+/// Largest face size the irradiance projection integrates over — irradiance is
+/// so smooth that a 32² source loses nothing the nine coefficients keep.
+const IRRADIANCE_SOURCE_SIZE: usize = 32;
+
+/// Where a frame's environment light comes from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EnvironmentSource {
+    /// A skybox's six faces, decoded like the skybox itself (8-bit).
+    Skybox(SkyboxDescription),
+    /// An equirectangular (latitude-longitude) image by asset path — usually a
+    /// Radiance `.hdr`, whose values above 1.0 survive prefiltering. The
+    /// image's center column faces -Z, its top row +Y.
+    Equirect(String),
+}
+
+impl EnvironmentSource {
+    /// The asset paths this source decodes.
+    pub fn paths(&self) -> Vec<&str> {
+        match self {
+            EnvironmentSource::Skybox(skybox) => skybox.faces().to_vec(),
+            EnvironmentSource::Equirect(path) => vec![path],
+        }
+    }
+
+    /// The prefilter cache key: the paths joined with '\n' (the skybox key
+    /// rule), tagged so an equirect never collides with a one-face skybox.
+    pub fn key(&self) -> String {
+        match self {
+            EnvironmentSource::Skybox(skybox) => format!("skybox\n{}", skybox.faces().join("\n")),
+            EnvironmentSource::Equirect(path) => format!("equirect\n{path}"),
+        }
+    }
+}
+
+/// A frame's image-based light (`Frame.withEnvironment`): the source cubemap
+/// and a multiplier on both its diffuse and specular contributions. The
+/// environment lights surfaces only — draw the sky with `Frame.withSkybox`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Environment {
+    pub source: EnvironmentSource,
+    pub intensity: f32,
+}
+
+/// A reflection probe (`Frame.withReflectionProbe`): the frame's scene, sky
+/// and lights captured into a cubemap at `position` and prefiltered like an
+/// environment. Each draw reflects its nearest probe in place of the frame
+/// environment. A probe bakes once — when first declared and the frame's
+/// assets have settled — and re-bakes when moved or after an asset reload.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReflectionProbe {
+    pub position: [f32; 3],
+}
+
+impl ReflectionProbe {
+    /// The bake cache key: the exact position bits.
+    pub fn key(&self) -> String {
+        let [x, y, z] = self.position.map(f32::to_bits);
+        format!("{x:08x}:{y:08x}:{z:08x}")
+    }
+}
+
+/// The item in `probes` whose position is nearest `point` — how each draw
+/// picks its reflection probe.
+pub fn nearest_probe<T>(probes: &[(Vector3<f32>, T)], point: Vector3<f32>) -> Option<&T> {
+    probes
+        .iter()
+        .min_by(|(a, _), (b, _)| {
+            (a - point)
+                .magnitude2()
+                .total_cmp(&(b - point).magnitude2())
+        })
+        .map(|(_, item)| item)
+}
+
+/// The camera that renders cube face `face` (GL order: +X, -X, +Y, -Y, +Z, -Z)
+/// of a probe at `position`: 90° square frusta whose up vectors follow the GL
+/// cube-map convention, so a read-back image (row 0 at the bottom) IS the
+/// face in upload order.
+pub fn probe_face_camera(position: [f32; 3], face: usize, near: f32, far: f32) -> Camera {
+    let (forward, up) = match face {
+        0 => ([1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+        1 => ([-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+        2 => ([0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+        3 => ([0.0, -1.0, 0.0], [0.0, 0.0, -1.0]),
+        4 => ([0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+        _ => ([0.0, 0.0, -1.0], [0.0, -1.0, 0.0]),
+    };
+    let [x, y, z] = position;
+    let target = [x + forward[0], y + forward[1], z + forward[2]];
+    let mut camera = Camera::look_at(position, target, up, Angle::from_degrees(90.0));
+    camera.near = near;
+    camera.far = far;
+    camera
+}
+
+/// A decoded float image: linear RGB, row 0 at the top — what the HDR
+/// pipeline produces for equirect sources.
+pub struct HdrImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[f32; 3]>,
+}
+
+/// A float RGB cubemap: six square faces in GL order, each row-major with row
+/// 0 at `t = 0` (the first row GL uploads).
+#[derive(Clone, Debug, PartialEq)]
+pub struct CubeImage {
+    pub size: usize,
+    pub faces: [Vec<[f32; 3]>; 6],
+}
+
+impl CubeImage {
+    /// Build each texel from its center direction.
+    pub fn from_fn(size: usize, mut texel: impl FnMut(Vector3<f32>) -> [f32; 3]) -> CubeImage {
+        let faces = std::array::from_fn(|face| {
+            let mut pixels = Vec::with_capacity(size * size);
+            for y in 0..size {
+                for x in 0..size {
+                    pixels.push(texel(texel_direction(face, x, y, size)));
+                }
+            }
+            pixels
+        });
+        CubeImage { size, faces }
+    }
+
+    /// Six decoded skybox faces (8-bit RGBA, `SkyboxDescription::faces`
+    /// order). `None` unless all are square, non-empty and the same size —
+    /// the skybox's validation rule.
+    pub fn from_faces(faces: &[&TextureData]) -> Option<CubeImage> {
+        let size = faces.first()?.width as usize;
+        let valid = faces.len() == 6
+            && size > 0
+            && faces.iter().all(|f| {
+                f.width as usize == size
+                    && f.height as usize == size
+                    && f.bytes.len() == size * size * 4
+            });
+        if !valid {
+            return None;
+        }
+        Some(CubeImage::from_rgba_faces(
+            size,
+            std::array::from_fn(|i| faces[i].bytes.as_slice()),
+        ))
+    }
+
+    /// Six 8-bit RGBA faces of `size`², rows in `t` order — decoded faces, or a
+    /// probe's read-back renders (row 0 at the bottom).
+    pub fn from_rgba_faces(size: usize, faces: [&[u8]; 6]) -> CubeImage {
+        CubeImage {
+            size,
+            faces: faces.map(|bytes| {
+                bytes
+                    .chunks_exact(4)
+                    .map(|p| {
+                        [
+                            p[0] as f32 / 255.0,
+                            p[1] as f32 / 255.0,
+                            p[2] as f32 / 255.0,
+                        ]
+                    })
+                    .collect()
+            }),
+        }
+    }
+
+    /// Resample an equirectangular image into a cube of `size`² faces
+    /// (bilinear, wrapping horizontally).
+    pub fn from_equirect(image: &HdrImage, size: usize) -> CubeImage {
+        let (w, h) = (image.width as usize, image.height as usize);
+        let at = |x: i64, y: i64| {
+            let x = x.rem_euclid(w as i64) as usize;
+            let y = y.clamp(0, h as i64 - 1) as usize;
+            image.pixels[y * w + x]
+        };
+        CubeImage::from_fn(size, |d| {
+            let u = 0.5 + d.x.atan2(-d.z) / std::f32::consts::TAU;
+            let v = d.y.clamp(-1.0, 1.0).acos() / std::f32::consts::PI;
+            bilinear(u * w as f32 - 0.5, v * h as f32 - 0.5, at)
+        })
+    }
+
+    fn texel(&self, face: usize, x: i64, y: i64) -> [f32; 3] {
+        let last = self.size as i64 - 1;
+        self.faces[face][y.clamp(0, last) as usize * self.size + x.clamp(0, last) as usize]
+    }
+
+    /// `texture(samplerCube, dir)`: bilinear, with each face's edges clamped.
+    pub fn sample(&self, dir: Vector3<f32>) -> [f32; 3] {
+        let Some((face, s, t)) = face_coords(dir) else {
+            return [0.0; 3];
+        };
+        let n = self.size as f32;
+        bilinear(s * n - 0.5, t * n - 0.5, |x, y| self.texel(face, x, y))
+    }
+
+    /// Average 2×2 blocks into a cube of half the size (at least 1).
+    pub fn downsample(&self) -> CubeImage {
+        let size = (self.size / 2).max(1);
+        let faces = std::array::from_fn(|face| {
+            let mut pixels = Vec::with_capacity(size * size);
+            for y in 0..size {
+                for x in 0..size {
+                    let mut sum = [0.0; 3];
+                    for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                        let p = self.texel(face, (2 * x + dx) as i64, (2 * y + dy) as i64);
+                        for c in 0..3 {
+                            sum[c] += p[c] * 0.25;
+                        }
+                    }
+                    pixels.push(sum);
+                }
+            }
+            pixels
+        });
+        CubeImage { size, faces }
+    }
+
+    /// This cube and its box-filtered halvings, down to 1×1 faces.
+    fn pyramid(self) -> Vec<CubeImage> {
+        let mut levels = vec![self];
+        while levels.last().expect("non-empty").size > 1 {
+            let next = levels.last().expect("non-empty").downsample();
+            levels.push(next);
+        }
+        levels
+    }
+}
+
+/// The prefiltered form of an environment or probe cubemap.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrefilteredEnvironment {
+    /// The specular mip chain: level `k` of `n` holds the environment
+    /// convolved with the GGX lobe of perceptual roughness `k / (n - 1)`.
+    pub specular: Vec<CubeImage>,
+    /// Nine spherical-harmonic coefficients of cosine-convolved radiance,
+    /// scaled by 1/π: evaluated at a normal they give the diffuse light a
+    /// white Lambertian surface reflects (a uniform sky of 1.0 gives 1.0).
+    pub irradiance: [[f32; 3]; 9],
+}
+
+impl PrefilteredEnvironment {
+    /// Prefilter `source` into at most `max_size`² specular faces.
+    pub fn new(source: &CubeImage, max_size: usize) -> PrefilteredEnvironment {
+        let limit = source.size.min(max_size).max(1);
+        let base = 1 << limit.ilog2();
+        let mut reduced = source.clone();
+        while reduced.size >= base * 2 {
+            reduced = reduced.downsample();
+        }
+        if reduced.size != base {
+            reduced = CubeImage::from_fn(base, |d| reduced.sample(d));
+        }
+        let pyramid = reduced.pyramid();
+        let irradiance = irradiance_sh(
+            pyramid
+                .iter()
+                .find(|level| level.size <= IRRADIANCE_SOURCE_SIZE)
+                .expect("the pyramid ends at 1x1"),
+        );
+
+        let count = pyramid.len();
+        let mut specular = vec![pyramid[0].clone()];
+        for level in 1..count {
+            let roughness = level as f32 / (count - 1) as f32;
+            let size = base >> level;
+            specular.push(CubeImage::from_fn(size, |n| {
+                prefilter_texel(&pyramid, n, roughness)
+            }));
+        }
+        PrefilteredEnvironment {
+            specular,
+            irradiance,
+        }
+    }
+
+    /// The highest specular mip level.
+    pub fn max_lod(&self) -> f32 {
+        (self.specular.len() - 1) as f32
+    }
+
+    /// The SH coefficients flattened for a `vec3[9]` uniform.
+    pub fn irradiance_uniform(&self) -> [f32; 27] {
+        std::array::from_fn(|i| self.irradiance[i / 3][i % 3])
+    }
+
+    /// `irradianceAt` in the lighting GLSL: diffuse light for normal `n`.
+    pub fn irradiance_at(&self, n: Vector3<f32>) -> Vector3<f32> {
+        let basis = sh_basis(n);
+        let mut sum = vec3(0.0, 0.0, 0.0);
+        for (coefficient, y) in self.irradiance.iter().zip(basis) {
+            sum += vec3(coefficient[0], coefficient[1], coefficient[2]) * y;
+        }
+        vec3(sum.x.max(0.0), sum.y.max(0.0), sum.z.max(0.0))
+    }
+
+    /// `textureLod(environmentSpecular, dir, lod)`: trilinear across levels.
+    pub fn specular_at(&self, dir: Vector3<f32>, lod: f32) -> Vector3<f32> {
+        let [r, g, b] = sample_lod(&self.specular, dir, lod);
+        vec3(r, g, b)
+    }
+}
+
+/// The center direction (unnormalized) of texel (`x`, `y`) on `face`.
+fn texel_direction(face: usize, x: usize, y: usize, size: usize) -> Vector3<f32> {
+    let s = (x as f32 + 0.5) / size as f32;
+    let t = (y as f32 + 0.5) / size as f32;
+    face_direction(face, s * 2.0 - 1.0, t * 2.0 - 1.0)
+}
+
+/// The inverse of the GL face-selection table: `(sc, tc)` on `face` → dir.
+fn face_direction(face: usize, sc: f32, tc: f32) -> Vector3<f32> {
+    match face {
+        0 => vec3(1.0, -tc, -sc),
+        1 => vec3(-1.0, -tc, sc),
+        2 => vec3(sc, 1.0, tc),
+        3 => vec3(sc, -1.0, -tc),
+        4 => vec3(sc, -tc, 1.0),
+        _ => vec3(-sc, -tc, -1.0),
+    }
+}
+
+/// The GL cube-map face selection: a direction's face and `(s, t)` in [0, 1].
+fn face_coords(dir: Vector3<f32>) -> Option<(usize, f32, f32)> {
+    let (ax, ay, az) = (dir.x.abs(), dir.y.abs(), dir.z.abs());
+    let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+        if dir.x >= 0.0 {
+            (0, -dir.z, -dir.y, ax)
+        } else {
+            (1, dir.z, -dir.y, ax)
+        }
+    } else if ay >= az {
+        if dir.y >= 0.0 {
+            (2, dir.x, dir.z, ay)
+        } else {
+            (3, dir.x, -dir.z, ay)
+        }
+    } else if dir.z >= 0.0 {
+        (4, dir.x, -dir.y, az)
+    } else {
+        (5, -dir.x, -dir.y, az)
+    };
+    if ma == 0.0 || !ma.is_finite() {
+        return None;
+    }
+    Some((face, 0.5 * (sc / ma + 1.0), 0.5 * (tc / ma + 1.0)))
+}
+
+/// Bilinear filtering at continuous texel coordinates (texel centers at
+/// integers) over a texel fetch that handles its own addressing.
+fn bilinear(x: f32, y: f32, texel: impl Fn(i64, i64) -> [f32; 3]) -> [f32; 3] {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let (a, b) = (texel(x0, y0), texel(x0 + 1, y0));
+    let (c, d) = (texel(x0, y0 + 1), texel(x0 + 1, y0 + 1));
+    std::array::from_fn(|i| {
+        let top = a[i] + (b[i] - a[i]) * fx;
+        let bottom = c[i] + (d[i] - c[i]) * fx;
+        top + (bottom - top) * fy
+    })
+}
+
+/// Trilinear sampling of a mip chain at a fractional level.
+fn sample_lod(levels: &[CubeImage], dir: Vector3<f32>, lod: f32) -> [f32; 3] {
+    let lod = lod.clamp(0.0, (levels.len() - 1) as f32);
+    let lower = lod.floor() as usize;
+    let upper = (lower + 1).min(levels.len() - 1);
+    let (a, b) = (levels[lower].sample(dir), levels[upper].sample(dir));
+    let f = lod - lower as f32;
+    std::array::from_fn(|i| a[i] + (b[i] - a[i]) * f)
+}
+
+/// The solid angle texel (`x`, `y`) of a `size`² face subtends.
+fn texel_solid_angle(x: usize, y: usize, size: usize) -> f32 {
+    let area = |x: f32, y: f32| (x * y).atan2((x * x + y * y + 1.0).sqrt());
+    let step = 2.0 / size as f32;
+    let (x0, y0) = (x as f32 * step - 1.0, y as f32 * step - 1.0);
+    let (x1, y1) = (x0 + step, y0 + step);
+    area(x0, y0) - area(x0, y1) - area(x1, y0) + area(x1, y1)
+}
+
+/// The nine real spherical harmonics (bands 0–2) at unit direction `d`.
+fn sh_basis(d: Vector3<f32>) -> [f32; 9] {
+    [
+        0.282_095,
+        0.488_603 * d.y,
+        0.488_603 * d.z,
+        0.488_603 * d.x,
+        1.092_548 * d.x * d.y,
+        1.092_548 * d.y * d.z,
+        0.315_392 * (3.0 * d.z * d.z - 1.0),
+        1.092_548 * d.x * d.z,
+        0.546_274 * (d.x * d.x - d.y * d.y),
+    ]
+}
+
+/// Project `cube`'s radiance onto SH9 and apply the clamped-cosine
+/// convolution (Ramamoorthi–Hanrahan: π, 2π/3, π/4 per band) divided by π.
+fn irradiance_sh(cube: &CubeImage) -> [[f32; 3]; 9] {
+    const BAND: [f32; 9] = [
+        1.0,
+        2.0 / 3.0,
+        2.0 / 3.0,
+        2.0 / 3.0,
+        0.25,
+        0.25,
+        0.25,
+        0.25,
+        0.25,
+    ];
+    let mut sh = [[0.0f32; 3]; 9];
+    for (face, pixels) in cube.faces.iter().enumerate() {
+        for y in 0..cube.size {
+            for x in 0..cube.size {
+                let weight = texel_solid_angle(x, y, cube.size);
+                let basis = sh_basis(texel_direction(face, x, y, cube.size).normalize());
+                let radiance = pixels[y * cube.size + x];
+                for (coefficient, b) in sh.iter_mut().zip(basis) {
+                    for c in 0..3 {
+                        coefficient[c] += radiance[c] * b * weight;
+                    }
+                }
+            }
+        }
+    }
+    for (coefficient, band) in sh.iter_mut().zip(BAND) {
+        for value in coefficient.iter_mut() {
+            *value *= band;
+        }
+    }
+    sh
+}
+
+/// One texel of a prefiltered specular level: GGX importance sampling around
+/// `n` with view = normal (the split-sum approximation), each sample read from
+/// the pyramid level whose texels match its lobe footprint (filtered
+/// importance sampling) so few samples converge without fireflies.
+fn prefilter_texel(pyramid: &[CubeImage], n: Vector3<f32>, roughness: f32) -> [f32; 3] {
+    let n = n.normalize();
+    let a = roughness * roughness;
+    let a2 = a * a;
+    let up = if n.z.abs() < 0.999 {
+        vec3(0.0, 0.0, 1.0)
+    } else {
+        vec3(1.0, 0.0, 0.0)
+    };
+    let tangent_x = up.cross(n).normalize();
+    let tangent_y = n.cross(tangent_x);
+    let base = pyramid[0].size as f32;
+    let texel_solid_angle = 4.0 * std::f32::consts::PI / (6.0 * base * base);
+
+    let mut sum = [0.0f32; 3];
+    let mut weight = 0.0;
+    for i in 0..SPECULAR_SAMPLES {
+        let (u, v) = hammersley(i, SPECULAR_SAMPLES);
+        let phi = std::f32::consts::TAU * u;
+        let cos_theta = ((1.0 - v) / (1.0 + (a2 - 1.0) * v)).sqrt();
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let h = tangent_x * (sin_theta * phi.cos())
+            + tangent_y * (sin_theta * phi.sin())
+            + n * cos_theta;
+        let l = h * (2.0 * n.dot(h)) - n;
+        let n_dot_l = n.dot(l);
+        if n_dot_l <= 0.0 {
+            continue;
+        }
+        let d = {
+            let denom = cos_theta * cos_theta * (a2 - 1.0) + 1.0;
+            a2 / (std::f32::consts::PI * denom * denom)
+        };
+        // pdf(l) = D · (n·h) / (4 · v·h), and v = n makes v·h = n·h.
+        let pdf = d / 4.0;
+        let sample_solid_angle = 1.0 / (SPECULAR_SAMPLES as f32 * pdf + 1e-4);
+        let lod = 0.5 * (sample_solid_angle / texel_solid_angle).log2() + 1.0;
+        let radiance = sample_lod(pyramid, l, lod);
+        for c in 0..3 {
+            sum[c] += radiance[c] * n_dot_l;
+        }
+        weight += n_dot_l;
+    }
+    if weight <= 0.0 {
+        return pyramid[0].sample(n);
+    }
+    sum.map(|c| c / weight)
+}
+
+/// The `i`th of `count` points of the Hammersley set in [0, 1)².
+fn hammersley(i: u32, count: u32) -> (f32, f32) {
+    (
+        i as f32 / count as f32,
+        i.reverse_bits() as f32 * (1.0 / 4_294_967_296.0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uniform(size: usize, value: [f32; 3]) -> CubeImage {
+        CubeImage::from_fn(size, |_| value)
+    }
+
+    fn close(a: Vector3<f32>, b: Vector3<f32>) -> bool {
+        (a - b).magnitude() < 0.02
+    }
+
+    #[test]
+    fn texel_directions_round_trip_through_face_selection() {
+        for face in 0..6 {
+            for (x, y) in [(0, 0), (3, 1), (7, 7)] {
+                let (found, s, t) = face_coords(texel_direction(face, x, y, 8)).unwrap();
+                assert_eq!(found, face);
+                assert!((s * 8.0 - 0.5 - x as f32).abs() < 1e-4);
+                assert!((t * 8.0 - 0.5 - y as f32).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn a_uniform_sky_prefilters_to_itself() {
+        let env = PrefilteredEnvironment::new(&uniform(16, [0.5, 1.0, 2.0]), MAX_SPECULAR_SIZE);
+        assert_eq!(env.specular.len(), 5);
+        assert_eq!(env.specular.last().unwrap().size, 1);
+        let expected = vec3(0.5, 1.0, 2.0);
+        for n in [vec3(0.0, 1.0, 0.0), vec3(1.0, -1.0, 0.3).normalize()] {
+            assert!(
+                close(env.irradiance_at(n), expected),
+                "{:?}",
+                env.irradiance_at(n)
+            );
+            for lod in [0.0, 1.5, env.max_lod()] {
+                assert!(close(env.specular_at(n, lod), expected));
+            }
+        }
+    }
+
+    #[test]
+    fn irradiance_faces_the_bright_hemisphere() {
+        // White above the horizon, black below.
+        let sky = CubeImage::from_fn(16, |d| if d.y > 0.0 { [1.0; 3] } else { [0.0; 3] });
+        let env = PrefilteredEnvironment::new(&sky, MAX_SPECULAR_SIZE);
+        let up = env.irradiance_at(vec3(0.0, 1.0, 0.0)).x;
+        let side = env.irradiance_at(vec3(1.0, 0.0, 0.0)).x;
+        let down = env.irradiance_at(vec3(0.0, -1.0, 0.0)).x;
+        // Exact values: 1.0, 0.5, 0.0 — SH9 is close, not exact.
+        assert!((up - 1.0).abs() < 0.1, "{up}");
+        assert!((side - 0.5).abs() < 0.05, "{side}");
+        assert!(down < 0.1, "{down}");
+    }
+
+    #[test]
+    fn rougher_levels_blur_a_bright_spot() {
+        // One bright direction (+X) on a black sky.
+        let sky = CubeImage::from_fn(32, |d| {
+            if d.normalize().x > 0.95 {
+                [10.0; 3]
+            } else {
+                [0.0; 3]
+            }
+        });
+        let env = PrefilteredEnvironment::new(&sky, MAX_SPECULAR_SIZE);
+        let toward = vec3(1.0, 0.0, 0.0);
+        let aside = vec3(1.0, 0.0, 1.0).normalize();
+        // The mirror level keeps the spot sharp; the roughest spreads it.
+        assert!(env.specular_at(toward, 0.0).x > 9.0);
+        assert!(env.specular_at(aside, 0.0).x < 0.01);
+        assert!(env.specular_at(aside, env.max_lod()).x > 0.01);
+        assert!(env.specular_at(toward, env.max_lod()).x < 5.0);
+    }
+
+    #[test]
+    fn large_sources_are_reduced_to_the_specular_cap() {
+        let env = PrefilteredEnvironment::new(&uniform(200, [1.0; 3]), 64);
+        assert_eq!(env.specular[0].size, 64);
+        assert_eq!(env.specular.len(), 7);
+    }
+
+    #[test]
+    fn equirect_center_faces_negative_z() {
+        // A 4x2 image whose column 2 (u in [0.5, 0.75)) is red.
+        let mut pixels = vec![[0.0; 3]; 8];
+        pixels[2] = [1.0, 0.0, 0.0];
+        pixels[6] = [1.0, 0.0, 0.0];
+        let image = HdrImage {
+            width: 4,
+            height: 2,
+            pixels,
+        };
+        let cube = CubeImage::from_equirect(&image, 8);
+        let toward = cube.sample(vec3(0.4, 0.0, -1.0).normalize());
+        let away = cube.sample(vec3(0.0, 0.0, 1.0));
+        assert!(toward[0] > 0.5, "{toward:?}");
+        assert!(away[0] < 0.1, "{away:?}");
+    }
+
+    #[test]
+    fn probe_face_cameras_look_down_their_axes() {
+        use cgmath::Matrix4;
+        for face in 0..6 {
+            let camera = probe_face_camera([1.0, 2.0, 3.0], face, 0.1, 50.0);
+            let view_projection: Matrix4<f32> =
+                camera.projection_matrix(1.0) * camera.view_matrix();
+            // The face's (s, t) = (0.75, 0.25) direction lands at the matching
+            // read-back pixel (row 0 at the bottom).
+            let dir = face_direction(face, 0.5, -0.5);
+            let clip = view_projection * (vec3(1.0, 2.0, 3.0) + dir).extend(1.0);
+            let (sx, sy) = (clip.x / clip.w, clip.y / clip.w);
+            assert!(
+                (sx - 0.5).abs() < 1e-4 && (sy + 0.5).abs() < 1e-4,
+                "face {face}"
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_probe_wins() {
+        let probes = vec![(vec3(0.0, 0.0, 0.0), "a"), (vec3(10.0, 0.0, 0.0), "b")];
+        assert_eq!(nearest_probe(&probes, vec3(6.0, 1.0, 0.0)), Some(&"b"));
+        assert_eq!(nearest_probe(&probes, vec3(-3.0, 0.0, 0.0)), Some(&"a"));
+        assert_eq!(nearest_probe::<&str>(&[], vec3(0.0, 0.0, 0.0)), None);
+    }
+}
@@ -2,8 +2,13 @@ use cgmath::{Matrix4, SquareMatrix};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    fog::Fog, render_target::RenderTargetDescriptor, skybox::SkyboxDescription, ui::View, Camera,
-    Light, Scene3D, SceneObject, SpriteLayer,
+    environment::{Environment, ReflectionProbe},
+    fog::Fog,
+    post::PostEffect,
+    render_target::RenderTargetDescriptor,
+    skybox::SkyboxDescription,
+    ui::View,
+    Camera, Light, Scene3D, SceneObject, SpriteLayer,
 };
 
 fn is_false(value: &bool) -> bool {
@@ -29,14 +34,15 @@ pub struct UiTargetPass {
     pub view: View,
 }
 
-/// What a game's `draw` returns each frame: a 3D pass plus any ordered 2D
-/// sprite layers. Intentionally a growable record (post-processing etc. can be
-/// added later) so the render boundary signature doesn't churn.
+/// What a game's `draw` returns each frame: a 3D pass, its post-processing
+/// stack, and any ordered 2D sprite layers. Intentionally a growable record so
+/// the render boundary signature doesn't churn.
 ///
 /// `PartialEq` is the structural walk behind `Frame.equals`: every field —
 /// camera, scene, lights (ordered), render-target passes (ordered), ui-target
 /// passes (ordered), fog,
-/// skybox, clear color, 2D layers (ordered), and the `pure_2d` marker. It
+/// skybox, environment, reflection probes (ordered), clear color, post effects
+/// (ordered), 2D layers (ordered), and the `pure_2d` marker. It
 /// inherits [`Scene3D`]'s rules: floats compare exactly, assets compare by
 /// locator, and animation compares as declared.
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -60,12 +66,26 @@ pub struct Frame {
     /// A cubemap skybox drawn behind everything (fog does not apply to it).
     #[serde(default)]
     pub skybox: Option<SkyboxDescription>,
+    /// Image-based light for lit surfaces (`Frame.withEnvironment`): diffuse
+    /// irradiance and prefiltered reflections. Skipped when unset so frames
+    /// without one serialize unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<Environment>,
+    /// Reflection probes baked from this frame's scene; each lit draw
+    /// reflects the nearest one (`Frame.withReflectionProbe`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reflection_probes: Vec<ReflectionProbe>,
     /// Explicit background clear color (`Frame.withClearColor`). When set it
     /// wins over the fog-color-as-clear-color default; when `None` the clear
     /// color falls back to the fog color, else the engine default. It only
     /// paints the background — it does not affect fog blending.
     #[serde(default)]
     pub clear_color: Option<[f32; 3]>,
+    /// Screen-space effects applied, in order, to the 3D pass before the
+    /// sprite layers draw (`Frame.withPost`). Skipped when empty so frames
+    /// without post-processing serialize unchanged.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post: Vec<PostEffect>,
     /// Ordered center-origin, Y-up 2D passes. They render after the 3D scene;
     /// later layers appear above earlier ones.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -88,7 +108,10 @@ impl Frame {
             ui_targets: vec![],
             fog: None,
             skybox: None,
+            environment: None,
+            reflection_probes: vec![],
             clear_color: None,
+            post: vec![],
             sprite_layers: vec![],
             pure_2d: false,
         }
@@ -162,6 +185,21 @@ impl Frame {
         frame
     }
 
+    /// Image-based light for this frame's lit surfaces — irradiance for the
+    /// ambient term, the prefiltered specular chain for reflections. Subject-
+    /// last so it pipes (`frame |> Frame.withEnvironment(source, intensity)`).
+    pub fn with_environment(mut frame: Frame, environment: Environment) -> Frame {
+        frame.environment = Some(environment);
+        frame
+    }
+
+    /// Bake a reflection probe from this frame's scene at `probe.position`.
+    /// Declaration order does not matter: each draw picks its nearest probe.
+    pub fn with_reflection_probe(mut frame: Frame, probe: ReflectionProbe) -> Frame {
+        frame.reflection_probes.push(probe);
+        frame
+    }
+
     /// Explicit background clear color, overriding the fog-color default.
     /// Subject-last so it pipes (`frame |> Frame.withClearColor(r, g, b)`).
     pub fn with_clear_color(mut frame: Frame, r: f32, g: f32, b: f32) -> Frame {
@@ -169,6 +207,14 @@ impl Frame {
         frame
     }
 
+    /// The post-processing stack for this frame's 3D pass, replacing any
+    /// earlier one; an empty list turns post-processing off. Subject-last so
+    /// it pipes (`frame |> Frame.withPost([Post.bloom(…), …])`).
+    pub fn with_post(mut frame: Frame, post: Vec<PostEffect>) -> Frame {
+        frame.post = post;
+        frame
+    }
+
     /// Add a 2D layer above the 3D pass and any earlier sprite layers.
     pub fn with_2d(mut frame: Frame, layer: SpriteLayer) -> Frame {
         frame.sprite_layers.push(layer);
@@ -219,4 +265,46 @@ mod tests {
         // The fog itself is untouched — only the background clear changed.
         assert!(frame.fog.is_some());
     }
+
+    #[test]
+    fn environment_and_probes_stay_off_the_wire_until_set() {
+        use crate::environment::EnvironmentSource;
+        let json = serde_json::to_string(&bare()).unwrap();
+        assert!(!json.contains("environment") && !json.contains("reflection_probes"));
+
+        let frame = Frame::with_environment(
+            bare(),
+            Environment {
+                source: EnvironmentSource::Equirect("sky.hdr".into()),
+                intensity: 0.5,
+            },
+        );
+        let frame = Frame::with_reflection_probe(
+            frame,
+            ReflectionProbe {
+                position: [1.0, 2.0, 3.0],
+            },
+        );
+        let json = serde_json::to_string(&frame).unwrap();
+        assert_eq!(serde_json::from_str::<Frame>(&json).unwrap(), frame);
+    }
+
+    #[test]
+    fn post_replaces_the_stack_and_stays_off_the_wire_until_set() {
+        assert!(!serde_json::to_string(&bare()).unwrap().contains("post"));
+
+        let frame = Frame::with_post(bare(), vec![PostEffect::Fxaa]);
+        let frame = Frame::with_post(
+            frame,
+            vec![PostEffect::bloom(0.7, 1.2), PostEffect::vignette(0.4, 0.5)],
+        );
+        assert_eq!(
+            frame.post,
+            [PostEffect::bloom(0.7, 1.2), PostEffect::vignette(0.4, 0.5)]
+        );
+        assert_ne!(frame, bare());
+        let json = serde_json::to_string(&frame).unwrap();
+        assert_eq!(serde_json::from_str::<Frame>(&json).unwrap(), frame);
+        assert_eq!(Frame::with_post(frame, vec![]), bare());
+    }
 }
@@ -0,0 +1,603 @@
+//! The running game's source-level debugger — the runtime half of
+//! `functor-lang-dap`'s attach, served over `POST /debug`.
+//!
+//! A frame runs at full speed with no hook armed; it only keeps its replay
+//! journal (see [`crate::inspector`]). While the client has breakpoints set,
+//! the shell hands each real frame's journal to [`GameDebugger::scan`] after
+//! the frame, which replays every journaled entry-point call under a
+//! [`functor_lang::debug::Debugger`]. A hit pauses the clock the way
+//! `POST /time` does and leaves the game stopped INSIDE that call's replay:
+//! stepping, frames, variables, and evaluation all act on it, and `continue`
+//! replays on through the rest of the frame before the clock resumes. Entry
+//! points are pure functions of their journaled args, so the replay is the
+//! frame's real execution, observed — the model the game kept is the one the
+//! replay computes.
+//!
+//! Commands and replies are Debug Adapter Protocol request `arguments` and
+//! response `body` shapes, so the adapter forwards them as they are; the one
+//! extension is `status`, which the adapter polls to learn of a stop. Source
+//! paths are project file NAMES — a game may be running pushed sources that
+//! exist on no disk — and the adapter maps them back into its workspace.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use functor_lang::debug::{Breakpoint, Debugger, Outcome, Run, Step, StopReason};
+use functor_lang::eval::DebugHook;
+use functor_lang::Session;
+use serde_json::{json, Value as Json};
+
+use crate::functor_lang_prelude::FunctorHost;
+use crate::functor_lang_producer::JournalEntry;
+
+/// What a debug command asks of the shell's clock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugClock {
+    Keep,
+    /// A stop: freeze the game on the frame it is inside.
+    Pause,
+    /// The stopped frame ran to its end: play on.
+    Resume,
+}
+
+/// One game's debugger state across frames and reloads.
+#[derive(Default)]
+pub struct GameDebugger {
+    /// Linked lazily from the game's sources, and dropped on reload.
+    debugger: Option<Debugger>,
+    /// The client's breakpoints by the path it named, kept across reloads.
+    breakpoints: HashMap<PathBuf, Vec<Breakpoint>>,
+    ignore_errors: bool,
+    /// `pause` arrived while no frame had journaled a call: stop at the
+    /// first call of the next frame.
+    pause_requested: bool,
+    /// The journal of the frame the game is stopped inside, and which of
+    /// its calls.
+    frame: Vec<JournalEntry>,
+    entry: usize,
+    /// Stops so far — `status` reports it, so a poller can tell a new stop
+    /// from the one it has already shown.
+    stops: u64,
+}
+
+impl GameDebugger {
+    pub fn new() -> GameDebugger {
+        GameDebugger::default()
+    }
+
+    /// The program changed: the linked project and any stop inside the old
+    /// program go; the breakpoints stay.
+    pub fn reload(&mut self) {
+        self.debugger = None;
+        self.frame.clear();
+    }
+
+    /// Does the next real frame need [`Self::scan`]ning? Without breakpoints
+    /// or a pending pause, debugging costs a frame nothing.
+    pub fn armed(&self) -> bool {
+        self.pause_requested || self.breakpoints.values().any(|set| !set.is_empty())
+    }
+
+    /// Replay a real frame's journal under the breakpoints. True when it
+    /// stopped — the shell then pauses its clock.
+    pub fn scan(
+        &mut self,
+        journal: &[JournalEntry],
+        session: &Session,
+        sources: &[(String, String)],
+    ) -> bool {
+        if !self.armed() || !self.frame.is_empty() || journal.is_empty() {
+            return false;
+        }
+        if let Err(error) = self.link(sources) {
+            eprintln!("[debug] {error}");
+            return false;
+        }
+        let stop_on_entry = std::mem::take(&mut self.pause_requested);
+        self.frame = journal.to_vec();
+        quietly(|| self.run_from(0, None, stop_on_entry, session))
+    }
+
+    /// Answer one `POST /debug` command (see the module doc); `journal` is
+    /// the last real frame's, for a `pause`.
+    pub fn command(
+        &mut self,
+        request: &Json,
+        journal: &[JournalEntry],
+        session: &Session,
+        sources: &[(String, String)],
+    ) -> Result<(Json, DebugClock), String> {
+        let arguments = &request["arguments"];
+        let command = request["command"].as_str().unwrap_or_default();
+        match command {
+            "status" => Ok((self.status(), DebugClock::Keep)),
+            "setBreakpoints" => {
+                let path = PathBuf::from(arguments["source"]["path"].as_str().unwrap_or_default());
+                let breakpoints: Vec<Breakpoint> = arguments["breakpoints"]
+                    .as_array()
+                    .map(Vec::as_slice)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|breakpoint| Breakpoint {
+                        line: breakpoint["line"].as_u64().unwrap_or_default() as usize,
+                        condition: breakpoint["condition"]
+                            .as_str()
+                            .filter(|condition| !condition.trim().is_empty())
+                            .map(str::to_string),
+                    })
+                    .collect();
+                let verified = self.link(sources)?.set_breakpoints(&path, &breakpoints);
+                let body = breakpoints
+                    .iter()
+                    .zip(verified)
+                    .map(|(breakpoint, verified)| {
+                        json!({ "verified": verified, "line": breakpoint.line })
+                    })
+                    .collect::<Vec<_>>();
+                self.breakpoints.insert(path, breakpoints);
+                Ok((json!({ "breakpoints": body }), DebugClock::Keep))
+            }
+            "setExceptionBreakpoints" => {
+                let filters = arguments["filters"]
+                    .as_array()
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                self.ignore_errors = !filters.iter().any(|filter| filter == "error");
+                if let Some(debugger) = &mut self.debugger {
+                    debugger.set_stop_on_errors(!self.ignore_errors);
+                }
+                Ok((json!({}), DebugClock::Keep))
+            }
+            "pause" => {
+                if !self.frame.is_empty() {
+                    return Ok((self.status(), DebugClock::Keep));
+                }
+                if journal.is_empty() {
+                    self.pause_requested = true;
+                    return Ok((self.status(), DebugClock::Keep));
+                }
+                self.link(sources)?;
+                self.frame = journal.to_vec();
+                let stopped = quietly(|| self.run_from(0, None, true, session));
+                let clock = if stopped {
+                    DebugClock::Pause
+                } else {
+                    DebugClock::Keep
+                };
+                Ok((self.status(), clock))
+            }
+            "continue" | "next" | "stepIn" | "stepOut" => {
+                if self.frame.is_empty() {
+                    return Err("not stopped".to_string());
+                }
+                let step = match command {
+                    "continue" => Step::Continue,
+                    "next" => Step::Over,
+                    "stepIn" => Step::In,
+                    _ => Step::Out,
+                };
+                let stopped = quietly(|| self.run_from(self.entry, Some(step), false, session));
+                let clock = if stopped {
+                    DebugClock::Keep
+                } else {
+                    DebugClock::Resume
+                };
+                Ok((self.status(), clock))
+            }
+            "stackTrace" => {
+                let debugger = self.stopped()?;
+                let frames: Vec<Json> = debugger
+                    .stopped()
+                    .map(|stopped| stopped.frames.as_slice())
+                    .unwrap_or_default()
+                    .iter()
+                    .enumerate()
+                    .map(|(id, frame)| {
+                        let (path, line, column) = debugger.locate(frame.span.start);
+                        json!({
+                            "id": id,
+                            "name": frame.name,
+                            "line": line,
+                            "column": column,
+                            "source": source(path),
+                        })
+                    })
+                    .collect();
+                let total = frames.len();
+                Ok((
+                    json!({ "stackFrames": frames, "totalFrames": total }),
+                    DebugClock::Keep,
+                ))
+            }
+            "scopes" => {
+                let frame = arguments["frameId"].as_u64().unwrap_or_default() as usize;
+                let scopes: Vec<Json> = self
+                    .stopped()?
+                    .scopes(frame)
+                    .into_iter()
+                    .map(|scope| {
+                        json!({
+                            "name": scope.name,
+                            "variablesReference": scope.reference,
+                            "expensive": false,
+                        })
+                    })
+                    .collect();
+                Ok((json!({ "scopes": scopes }), DebugClock::Keep))
+            }
+            "variables" => {
+                let reference = arguments["variablesReference"].as_u64().unwrap_or_default();
+                let variables: Vec<Json> = self
+                    .stopped()?
+                    .variables(reference as usize)
+                    .into_iter()
+                    .map(|variable| {
+                        json!({
+                            "name": variable.name,
+                            "value": variable.value,
+                            "variablesReference": variable.reference,
+                        })
+                    })
+                    .collect();
+                Ok((json!({ "variables": variables }), DebugClock::Keep))
+            }
+            "evaluate" => {
+                let frame = arguments["frameId"].as_u64().unwrap_or_default() as usize;
+                let expression = arguments["expression"].as_str().unwrap_or_default();
+                let debugger = self.stopped()?;
+                let variable = quietly(|| debugger.evaluate(frame, expression, &mut FunctorHost))?;
+                let body = json!({
+                    "result": variable.value,
+                    "variablesReference": variable.reference,
+                });
+                Ok((body, DebugClock::Keep))
+            }
+            "disconnect" => {
+                let was_stopped = !self.frame.is_empty();
+                *self = GameDebugger {
+                    stops: self.stops,
+                    ..GameDebugger::default()
+                };
+                let clock = if was_stopped {
+                    DebugClock::Resume
+                } else {
+                    DebugClock::Keep
+                };
+                Ok((json!({}), clock))
+            }
+            other => Err(format!("unsupported debug command `{other}`")),
+        }
+    }
+
+    /// The `status` reply: the current stop, if any.
+    fn status(&self) -> Json {
+        let stopped = self
+            .debugger
+            .as_ref()
+            .and_then(Debugger::stopped)
+            .filter(|_| !self.frame.is_empty());
+        let Some(stopped) = stopped else {
+            return json!({ "stopped": null, "stops": self.stops });
+        };
+        let (reason, text) = match &stopped.reason {
+            StopReason::Entry => ("entry", None),
+            StopReason::Step => ("step", None),
+            StopReason::Breakpoint => ("breakpoint", None),
+            StopReason::Condition(message) => ("breakpoint", Some(message.clone())),
+            StopReason::Error(message) => ("exception", Some(message.clone())),
+        };
+        let entry = &self.frame[self.entry];
+        json!({
+            "stopped": {
+                "reason": reason,
+                "text": text,
+                "description": format!("{} ({})", entry.entry, entry.provenance.render(&entry.args)),
+            },
+            "stops": self.stops,
+        })
+    }
+
+    /// The debugger, while the game is stopped.
+    fn stopped(&mut self) -> Result<&mut Debugger, String> {
+        match &mut self.debugger {
+            Some(debugger) if !self.frame.is_empty() => Ok(debugger),
+            _ => Err("not stopped".to_string()),
+        }
+    }
+
+    /// The debugger, linked from `sources` if it is not yet — the game's
+    /// files as it loaded them, with the engine's bundled modules, so every
+    /// span matches the running session's.
+    fn link(&mut self, sources: &[(String, String)]) -> Result<&mut Debugger, String> {
+        if self.debugger.is_none() {
+            let files = sources
+                .iter()
+                .map(|(path, src)| (PathBuf::from(path), src.clone()))
+                .collect();
+            let project = functor_lang::project::load_sources_with_bundled_modules(
+                files,
+                &functor_prelude::bundled_modules(),
+            )
+            .map_err(|error| format!("cannot link the game for debugging: {}", error.render()))?;
+            let mut debugger = Debugger::new(project);
+            debugger.set_stop_on_errors(!self.ignore_errors);
+            for (path, breakpoints) in &self.breakpoints {
+                debugger.set_breakpoints(path, breakpoints);
+            }
+            self.debugger = Some(debugger);
+        }
+        Ok(self.debugger.as_mut().expect("linked above"))
+    }
+
+    /// Run the frame's calls from `first` — resuming the stopped one by
+    /// `step`, or starting it — until one stops. A step that runs off the
+    /// end of a call stops at the start of the next; a continue runs to the
+    /// next breakpoint. True when stopped; false once the frame is done.
+    fn run_from(
+        &mut self,
+        first: usize,
+        mut step: Option<Step>,
+        stop_on_entry: bool,
+        session: &Session,
+    ) -> bool {
+        let stop_on_entry = stop_on_entry || step.is_some_and(|step| step != Step::Continue);
+        let Some(debugger) = &mut self.debugger else {
+            return false;
+        };
+        for index in first..self.frame.len() {
+            let run = replay(session, &self.frame[index]);
+            let outcome = match step.take() {
+                Some(step) => debugger.resume(step, run),
+                None => debugger.start(stop_on_entry, run),
+            };
+            if let Outcome::Stopped(_) = outcome {
+                self.entry = index;
+                self.stops += 1;
+                return true;
+            }
+        }
+        debugger.clear();
+        self.frame.clear();
+        false
+    }
+}
+
+/// One journaled call, replayed under a hook.
+fn replay<'a>(session: &'a Session, entry: &'a JournalEntry) -> impl Run + 'a {
+    move |hook: &mut dyn DebugHook| {
+        Some(
+            session
+                .call_debugged(entry.entry, entry.args.clone(), &mut FunctorHost, hook)?
+                .map(Some),
+        )
+    }
+}
+
+/// Run `f` as an observer, like the inspector's replay: `Debug.log` lines
+/// already printed live are not printed again, and `Ui.*` handlers a replay
+/// registers do not join the next real frame's.
+fn quietly<T>(f: impl FnOnce() -> T) -> T {
+    let _mute = functor_lang::suppress_trace();
+    let saved = crate::functor_lang_prelude::take_ui_handlers();
+    let result = f();
+    let _replayed = crate::functor_lang_prelude::take_ui_handlers();
+    crate::functor_lang_prelude::restore_ui_handlers(saved);
+    result
+}
+
+/// A DAP `Source` for a project file; bundled modules (`<prelude>/…`) have
+/// no file to open.
+fn source(path: &Path) -> Json {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned());
+    if path.to_string_lossy().starts_with('<') {
+        json!({ "name": name, "presentationHint": "deemphasize" })
+    } else {
+        json!({ "name": name, "path": name })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functor_lang_producer::Provenance;
+    use functor_lang::Value;
+
+    const GAME: &str = "let init = 0.0\n\
+\n\
+let update = (model, msg) =>\n\
+\x20 let next = model + msg in\n\
+\x20 next\n";
+
+    fn session(sources: &[(String, String)]) -> Session {
+        let files = sources
+            .iter()
+            .map(|(path, src)| (PathBuf::from(path), src.clone()))
+            .collect();
+        let project = functor_lang::project::load_sources_with_bundled_modules(
+            files,
+            &functor_prelude::bundled_modules(),
+        )
+        .unwrap_or_else(|error| panic!("{}", error.render()));
+        Session::load(&project.module, &mut FunctorHost)
+            .unwrap_or_else(|f| panic!("{}", f.error.message))
+    }
+
+    fn update(model: f64, msg: f64) -> JournalEntry {
+        JournalEntry {
+            entry: "update",
+            args: vec![Value::Number(model), Value::Number(msg)],
+            provenance: Provenance::Subscription,
+        }
+    }
+
+    fn command(
+        debugger: &mut GameDebugger,
+        request: Json,
+        session: &Session,
+        sources: &[(String, String)],
+    ) -> (Json, DebugClock) {
+        debugger
+            .command(&request, &[], session, sources)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    #[test]
+    fn a_breakpoint_hit_in_a_frame_stops_inside_that_call() {
+        let sources = vec![("game.fun".to_string(), GAME.to_string())];
+        let session = session(&sources);
+        let mut debugger = GameDebugger::new();
+        assert!(!debugger.armed(), "nothing to scan without breakpoints");
+        let (body, _) = command(
+            &mut debugger,
+            json!({
+                "command": "setBreakpoints",
+                "arguments": {
+                    "source": { "path": "/work/game/game.fun" },
+                    "breakpoints": [{ "line": 5, "condition": "next > 2.0" }],
+                },
+            }),
+            &session,
+            &sources,
+        );
+        assert_eq!(body["breakpoints"][0]["verified"], true);
+        assert!(debugger.armed());
+
+        // The first call's `next` is 1: no stop. The second's is 3.
+        let journal = [update(0.0, 1.0), update(1.0, 2.0), update(3.0, 4.0)];
+        assert!(debugger.scan(&journal, &session, &sources));
+        let (status, _) = command(
+            &mut debugger,
+            json!({ "command": "status" }),
+            &session,
+            &sources,
+        );
+        assert_eq!(status["stopped"]["reason"], "breakpoint");
+        assert_eq!(status["stops"], 1);
+
+        let (stack, _) = command(
+            &mut debugger,
+            json!({ "command": "stackTrace" }),
+            &session,
+            &sources,
+        );
+        assert_eq!(stack["stackFrames"][0]["name"], "update");
+        assert_eq!(stack["stackFrames"][0]["line"], 5);
+        assert_eq!(stack["stackFrames"][0]["source"]["path"], "game.fun");
+
+        let (scopes, _) = command(
+            &mut debugger,
+            json!({ "command": "scopes", "arguments": { "frameId": 0 } }),
+            &session,
+            &sources,
+        );
+        let locals = scopes["scopes"][0]["variablesReference"].clone();
+        let (variables, _) = command(
+            &mut debugger,
+            json!({ "command": "variables", "arguments": { "variablesReference": locals } }),
+            &session,
+            &sources,
+        );
+        let names: Vec<(&str, &str)> = variables["variables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| (v["name"].as_str().unwrap(), v["value"].as_str().unwrap()))
+            .collect();
+        assert_eq!(names, [("model", "1"), ("msg", "2"), ("next", "3")]);
+
+        // Evaluation runs under the engine host.
+        let (evaluated, _) = command(
+            &mut debugger,
+            json!({
+                "command": "evaluate",
+                "arguments": { "frameId": 0, "expression": "Vec3.make(next, msg, 0.0)" },
+            }),
+            &session,
+            &sources,
+        );
+        assert_eq!(evaluated["result"], "<Vec3 (3, 2, 0)>");
+
+        // Continue stops again in the third call, then runs off the frame.
+        let (status, clock) = command(
+            &mut debugger,
+            json!({ "command": "continue" }),
+            &session,
+            &sources,
+        );
+        assert_eq!(clock, DebugClock::Keep);
+        assert_eq!(status["stops"], 2);
+        let (status, clock) = command(
+            &mut debugger,
+            json!({ "command": "continue" }),
+            &session,
+            &sources,
+        );
+        assert_eq!(clock, DebugClock::Resume);
+        assert_eq!(status["stopped"], Json::Null);
+    }
+
+    #[test]
+    fn pause_stops_at_the_first_call_of_the_last_frame() {
+        let sources = vec![("game.fun".to_string(), GAME.to_string())];
+        let session = session(&sources);
+        let mut debugger = GameDebugger::new();
+        let journal = [update(0.0, 1.0)];
+        let (status, clock) = debugger
+            .command(&json!({ "command": "pause" }), &journal, &session, &sources)
+            .unwrap();
+        assert_eq!(clock, DebugClock::Pause);
+        assert_eq!(status["stopped"]["reason"], "entry");
+        // A step off the end of the only call finishes the frame.
+        let (_, clock) = command(
+            &mut debugger,
+            json!({ "command": "next" }),
+            &session,
+            &sources,
+        );
+        assert_eq!(clock, DebugClock::Keep);
+        let (_, clock) = command(
+            &mut debugger,
+            json!({ "command": "stepOut" }),
+            &session,
+            &sources,
+        );
+        assert_eq!(clock, DebugClock::Resume);
+    }
+
+    /// The debugger links its own copy of the project; stops are only right
+    /// if its spans are the running game's — a disk load and a sources load
+    /// must lay the project out identically.
+    #[test]
+    fn the_relinked_project_has_the_games_spans() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let util = "let twice = (x) => x * 2.0\n";
+        std::fs::write(dir.path().join("game.fun"), GAME).unwrap();
+        std::fs::write(dir.path().join("util.fun"), util).unwrap();
+        let from_disk = functor_lang::project::load_with_bundled_modules(
+            &dir.path().join("game.fun"),
+            &HashMap::new(),
+            &functor_prelude::bundled_modules(),
+        )
+        .unwrap_or_else(|error| panic!("{}", error.render()));
+        let from_sources = functor_lang::project::load_sources_with_bundled_modules(
+            vec![
+                (PathBuf::from("game.fun"), GAME.to_string()),
+                (PathBuf::from("util.fun"), util.to_string()),
+            ],
+            &functor_prelude::bundled_modules(),
+        )
+        .unwrap_or_else(|error| panic!("{}", error.render()));
+        let bases = |project: &functor_lang::project::Project| {
+            project
+                .sources
+                .files()
+                .iter()
+                .map(|file| (file.module.clone(), file.base))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(bases(&from_disk), bases(&from_sources));
+    }
+}
@@ -22,6 +22,10 @@
 //! boots an embedded scene and then receives games over the network. The web
 //! producer is this file's ancestor and can converge onto it later.
 
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use functor_lang::profile::{ProfileFormat, Profiler};
 use functor_lang::project::SourceMap;
 use functor_lang::{Session, Value};
 
@@ -205,6 +209,9 @@ pub struct FunctorLangEmbeddedGame {
     /// frame. Invalidated when the frame advances (`tick`), the paused frame
     /// changes (rewind/seek), or the program reloads.
     cached_trace: Option<String>,
+    /// The hook profiler behind `GET /profile`, armed on first use and
+    /// carried across pushes like the desktop producer's.
+    profiler: Option<Rc<RefCell<Profiler>>>,
     /// Per-file sha256 of the loaded `.fun` source, computed at load / reload
     /// (not per frame) — the wire contract's `sources`.
     source_hashes: Vec<InspectorSource>,
@@ -343,6 +350,7 @@ impl FunctorLangEmbeddedGame {
             journal_ring: std::collections::VecDeque::new(),
             runnable,
             cached_trace: None,
+            profiler: None,
             source_hashes,
             sources,
             path,
@@ -437,6 +445,7 @@ impl FunctorLangEmbeddedGame {
         self.names = loaded.names;
         self.module = loaded.module;
         self.session = loaded.session;
+        self.session.set_profiler(self.profiler.clone());
         self.has_input = loaded.has_input;
         self.has_sampled_input = loaded.has_sampled_input;
         self.pending_sampled_input = None;
@@ -537,6 +546,7 @@ impl FunctorLangEmbeddedGame {
         self.names = loaded.names;
         self.module = loaded.module;
         self.session = loaded.session;
+        self.session.set_profiler(self.profiler.clone());
         self.model = loaded.init;
         self.has_input = loaded.has_input;
         self.has_sampled_input = loaded.has_sampled_input;
@@ -1149,6 +1159,22 @@ AudioScene.empty), got {}",
         json
     }
 
+    fn start_profiling(&mut self) -> Result<(), String> {
+        if self.profiler.is_none() {
+            let profiler = Rc::new(RefCell::new(Profiler::new()));
+            self.session.set_profiler(Some(profiler.clone()));
+            self.profiler = Some(profiler);
+        }
+        Ok(())
+    }
+
+    fn profile(&self, format: ProfileFormat) -> Result<String, String> {
+        match &self.profiler {
+            Some(profiler) => Ok(profiler.borrow().render(format)),
+            None => Err("the hook profiler is not running".to_string()),
+        }
+    }
+
     fn net_drain_commands(&self) -> String {
         // HttpRequest commands (Effect.httpGet/httpPost); the shell performs
         // them (or drains-and-drops when it has no HTTP host yet).
@@ -1336,6 +1362,44 @@ let ui = (model) => Ui.button("hud", FromHud)
         let _ = game.render(ft); // still renders under the old program
     }
 
+    /// The profiler sees the hooks the shell drives, host externals as their
+    /// leaves, and keeps profiling the code a push swaps in.
+    #[test]
+    fn a_profile_spans_frames_and_pushes() {
+        let mut game = FunctorLangEmbeddedGame::create(
+            vec![("game.fun".to_string(), BOOT.to_string())],
+            Box::new(NativePlatform),
+        )
+        .expect("boot scene loads");
+        assert!(game.profile(ProfileFormat::Summary).is_err(), "not armed yet");
+        game.start_profiling().expect("the interpreter producer profiles");
+        let ft = frame_time(0.016, 0.016);
+        game.tick(ft);
+        let _ = game.render(ft);
+        game.reload_source(&BOOT.replace("model.spin + dt", "model.spin + dt + dt"))
+            .expect("push reloads");
+        let ft = frame_time(0.032, 0.016);
+        game.tick(ft);
+        let _ = game.render(ft);
+
+        let collapsed = game.profile(ProfileFormat::Collapsed).expect("armed");
+        let stacks: Vec<&str> = collapsed
+            .lines()
+            .filter_map(|line| line.rsplit_once(' ').map(|(stack, _)| stack))
+            .collect();
+        assert!(stacks.contains(&"tick"), "{collapsed}");
+        assert!(stacks.contains(&"draw;Scene.cube"), "{collapsed}");
+        let summary: serde_json::Value =
+            serde_json::from_str(&game.profile(ProfileFormat::Summary).expect("armed"))
+                .expect("summary is JSON");
+        let tick = summary["functions"]
+            .as_array()
+            .and_then(|functions| functions.iter().find(|function| function["name"] == "tick"))
+            .expect("tick was profiled");
+        assert_eq!(tick["calls"], 2, "both generations of tick: {summary}");
+        assert_eq!(tick["kind"], "function");
+    }
+
     #[test]
     fn a_prefixed_role_resolves_and_names_the_prefixed_contract() {
         // The boot scene as a `server` role (same-file entries): every entry
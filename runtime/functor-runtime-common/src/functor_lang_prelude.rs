@@ -39,6 +39,11 @@
 //!   (a diffuse-lit textured surface — F#'s `Material.litTexture`)
 //! Scene.emissiveTexture(texture, scene)                     -> Scene
 //!   (a self-lit textured surface, fullbright — F#'s `Material.emissiveTexture`)
+//! Scene.pbrMaterial(color, metallic, roughness)             -> PbrMaterial
+//! Scene.pbrNormalTexture(texture, material) / pbrAlphaMask(cutoff, material) / … -> PbrMaterial
+//! Scene.pbr(material, scene)                                -> Scene
+//!   (glTF's metallic-roughness model for procedural geometry — the same
+//!    shading imported models get)
 //! Camera3D.lookAt(Vec3.make(ex, ey, ez), Vec3.make(tx, ty, tz))                     -> Camera3D
 //!   (up is +Y; vertical fov pinned at 45°, near/far at protocol defaults)
 //! Camera3D.toWorldRay(mouse, camera)                         -> Option<{ origin, direction }>
@@ -83,6 +88,12 @@
 //! Frame.withFog(fog, frame)                                  -> Frame
 //!   (frame-level distance fog on every forward material, emissive included —
 //!    fog occludes glow; the fog color is also the pass's clear color)
+//! Post.bloom(threshold, intensity) / aces(exposure) / filmic(exposure) -> Post
+//! Post.lut(texture, strength) / vignette(intensity, smoothness) -> Post
+//! Post.fxaa() / depthOfField(focusDistance, focusRange, maxBlur) -> Post
+//! Frame.withPost([post, …], frame)                           -> Frame
+//!   (screen-space effects over the 3D pass, in list order, before the 2D
+//!    layers; a LUT is a file texture and grading waits for it to load)
 //! Frame.withClearColor(color, frame)                         -> Frame
 //!   (explicit background clear color, overriding the fog-color default; it
 //!    only paints the background, not fog blending)
@@ -105,6 +116,14 @@
 //!   (a cubemap sky drawn behind everything; while the six faces load the
 //!    clear color shows, a failed face disables the sky with one warning;
 //!    fog does not apply to the sky — it IS the horizon)
+//! Frame.withEnvironment(source, intensity, frame)           -> Frame
+//!   (image-based light for every lit surface: `source` is a Skybox or an
+//!    `Asset.texture` equirect (`.hdr` keeps values above 1.0). A specular
+//!    mip chain and diffuse irradiance are prefiltered on the CPU once the
+//!    image loads; it lights, it does not draw — pair it with withSkybox)
+//! Frame.withReflectionProbe(position, frame)                -> Frame
+//!   (a cube of the frame's own scene baked at `position` once its assets
+//!    settle; each draw uses its nearest probe instead of the environment)
 //! Time.seconds(n) / Time.millis(n)                          -> Duration
 //!   (like Angle: timing functions take Duration VALUES, never bare
 //!    numbers — seconds/milliseconds confusion is unrepresentable)
@@ -161,24 +180,30 @@
 //!   place, *then* moves — the order the source reads.
 
 use cgmath::Matrix4;
-use functor_lang::value::HostData;
+use functor_lang::value::{HostData, List, Map, Set};
 use functor_lang::{Host, RunError, Span, Value};
 use std::rc::Rc;
 
 use crate::anim::AnimExpr;
+use crate::environment::{Environment, EnvironmentSource, ReflectionProbe};
 use crate::fog::Fog;
 use crate::math::Angle;
+use crate::pbr::{AlphaMode, PbrDescription};
 use crate::physics;
+use crate::post::PostEffect;
 use crate::render_target::RenderTargetDescriptor;
 use crate::scene3d::{
     InstanceData, MaterialDescription, ModelDescription, ModelHandle, SpriteSampling,
     TextureDescription,
 };
+use crate::shadow::MAX_SHADOW_CASCADES;
 use crate::skybox::SkyboxDescription;
 use crate::terrain::TerrainDescription;
 use crate::ui::{self, View};
 use crate::webview::HtmlNode;
-use crate::{Camera, Camera2D, Frame, Light, Scene3D, SceneObject, Shape, SpriteLayer};
+use crate::{
+    Camera, Camera2D, Frame, Light, Scene3D, SceneObject, ShadowSettings, Shape, SpriteLayer,
+};
 
 mod sprite;
 
@@ -194,6 +219,10 @@ pub struct FunctorLangInstance(pub InstanceData);
 /// A [`TerrainDescription`] as an opaque, immutable Functor Lang value.
 pub struct FunctorLangTerrain(pub TerrainDescription);
 
+/// A [`PbrDescription`] as an opaque, immutable Functor Lang value — built by
+/// `Scene.pbrMaterial` and the `Scene.pbr*` modifiers, applied by `Scene.pbr`.
+pub struct FunctorLangPbrMaterial(pub PbrDescription);
+
 /// A [`Camera`] as an opaque Functor Lang value.
 pub struct FunctorLangCamera(pub Camera);
 
@@ -552,19 +581,34 @@ fn active_world_raycast(origin: [f32; 3], dir: [f32; 3], max_dist: f32) -> Effec
 /// INSIDE the drain, so the bound holds even mid-frame.
 pub const EFFECT_LOG_CAP: usize = 256;
 
-/// A serializable key for an [`EffectValue::Map`]. It mirrors Functor Lang's
-/// deliberately bounded scalar key domain.
+/// A serializable key for an [`EffectValue::Map`] (or element of an
+/// [`EffectValue::Set`]). It mirrors Functor Lang's deliberately bounded
+/// scalar key domain.
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum EffectMapKey {
     Bool(bool),
+    Int(i64),
     Number(f64),
     Text(String),
 }
 
 impl EffectMapKey {
+    fn from_functor_lang(key: &functor_lang::value::MapKey) -> EffectMapKey {
+        match key {
+            functor_lang::value::MapKey::Bool(value) => EffectMapKey::Bool(*value),
+            functor_lang::value::MapKey::Int(value) => EffectMapKey::Int(*value),
+            // No finiteness check needed here (unlike a non-finite VALUE in
+            // `effect_value_from_value`): the interpreter already refuses a
+            // NaN/Infinity key at insertion, so one cannot reach this walker.
+            functor_lang::value::MapKey::Number(value) => EffectMapKey::Number(*value),
+            functor_lang::value::MapKey::String(value) => EffectMapKey::Text(value.to_string()),
+        }
+    }
+
     fn to_functor_lang(&self) -> Result<functor_lang::value::MapKey, String> {
         match self {
             EffectMapKey::Bool(value) => Ok(functor_lang::value::MapKey::Bool(*value)),
+            EffectMapKey::Int(value) => Ok(functor_lang::value::MapKey::Int(*value)),
             // JSON refuses NaN/infinity, but fake/replay values are also
             // publicly constructible in memory. Validate that external seam
             // instead of letting MapKey::compare panic or admitting infinity.
@@ -588,11 +632,19 @@ impl EffectMapKey {
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum EffectValue {
     Number(f64),
+    /// A Functor Lang `int`, kept distinct from `Number` on the wire (JSON
+    /// `{"Int":3}`) so an exact count never comes back as a float.
+    Int(i64),
     Bool(bool),
     Text(String),
     List(Vec<EffectValue>),
     /// Canonically key-sorted immutable map data.
     Map(Vec<(EffectMapKey, EffectValue)>),
+    /// Canonically sorted set elements.
+    Set(Vec<EffectMapKey>),
+    /// An indexable `Array`, kept distinct from `List` so it roundtrips as
+    /// an array.
+    Array(Vec<EffectValue>),
     /// Structural tuple (at least two elements), mirroring [`Value::Tuple`] —
     /// so an `Effect.sendMsg` payload carrying a tuple field roundtrips as a
     /// tuple, not a list.
@@ -612,9 +664,10 @@ impl EffectValue {
     pub fn to_functor_lang(&self) -> Result<Value, String> {
         Ok(match self {
             EffectValue::Number(n) => Value::Number(*n),
+            EffectValue::Int(n) => Value::Int(*n),
             EffectValue::Bool(b) => Value::Bool(*b),
             EffectValue::Text(s) => Value::String(Rc::from(s.as_str())),
-            EffectValue::List(items) => Value::List(Rc::new(
+            EffectValue::List(items) => Value::List(List::from(
                 items
                     .iter()
                     .map(EffectValue::to_functor_lang)
@@ -629,8 +682,25 @@ impl EffectValue {
                 // same canonical order and last-write-wins rule as
                 // Map.fromList instead of trusting serialized entry order.
                 let (canonical, _) = functor_lang::value::canonicalize_map_entries(sorted);
-                Value::Map(Rc::new(canonical))
+                Value::Map(Map::from_canonical(canonical))
             }
+            EffectValue::Set(elements) => {
+                let mut keys = elements
+                    .iter()
+                    .map(EffectMapKey::to_functor_lang)
+                    .collect::<Result<Vec<_>, String>>()?;
+                // The same external seam as a map: restore canonical order
+                // and drop repeats rather than trusting the wire.
+                keys.sort_by(|a, b| a.compare(b));
+                keys.dedup();
+                Value::Set(Set::from_canonical(keys))
+            }
+            EffectValue::Array(items) => Value::Array(List::from(
+                items
+                    .iter()
+                    .map(EffectValue::to_functor_lang)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
             EffectValue::Tuple(items) => Value::Tuple(Rc::new(
                 items
                     .iter()
@@ -683,6 +753,8 @@ const VALUE_JSON_MAX_DEPTH: usize = 120;
 /// sigils as a strong convention, not a proof):
 ///
 /// - maps: `{"$map": [[key, value], ...]}` (canonical key order)
+/// - sets: `{"$set": [...]}` (canonical order)
+/// - arrays: `{"$array": [...]}` (so they stay distinct from lists)
 /// - tuples: `{"$tuple": [...]}` (so they stay distinct from lists)
 /// - variants: `{"$ctor": "Some", "args": [...]}`
 /// - callables: `{"$fn": "<fn(dt)>"}` — the `Display` form
@@ -703,11 +775,8 @@ fn value_to_json_at(value: &Value, depth: usize) -> serde_json::Value {
     if depth > VALUE_JSON_MAX_DEPTH {
         return json!({ "$truncated": "max depth" });
     }
-    let items_json = |items: &[Value], charge: usize| -> Vec<Json> {
-        items
-            .iter()
-            .map(|v| value_to_json_at(v, depth + charge))
-            .collect()
+    let items_json = |items: &mut dyn Iterator<Item = &Value>, charge: usize| -> Vec<Json> {
+        items.map(|v| value_to_json_at(v, depth + charge)).collect()
     };
     match value {
         Value::Number(n) if n.is_finite() => json!(n),
@@ -715,9 +784,10 @@ fn value_to_json_at(value: &Value, depth: usize) -> serde_json::Value {
         Value::Number(n) => {
             json!({ "$number": if *n > 0.0 { "Infinity" } else { "-Infinity" } })
         }
+        Value::Int(n) => json!(n),
         Value::String(s) => json!(s.as_ref()),
         Value::Bool(b) => json!(b),
-        Value::List(items) => Json::Array(items_json(items, 1)),
+        Value::List(items) => Json::Array(items_json(&mut items.iter(), 1)),
         Value::Map(entries) => json!({
             "$map": entries
                 .iter()
@@ -729,7 +799,14 @@ fn value_to_json_at(value: &Value, depth: usize) -> serde_json::Value {
                 })
                 .collect::<Vec<_>>()
         }),
-        Value::Tuple(items) => json!({ "$tuple": items_json(items, 2) }),
+        Value::Set(keys) => json!({
+            "$set": keys
+                .iter()
+                .map(|key| value_to_json_at(&key.to_value(), depth + 2))
+                .collect::<Vec<_>>()
+        }),
+        Value::Array(items) => json!({ "$array": items_json(&mut items.iter(), 2) }),
+        Value::Tuple(items) => json!({ "$tuple": items_json(&mut items.iter(), 2) }),
         Value::Record(fields) => Json::Object(
             fields
                 .iter()
@@ -737,7 +814,7 @@ fn value_to_json_at(value: &Value, depth: usize) -> serde_json::Value {
                 .collect(),
         ),
         Value::Variant { ctor, args } => {
-            json!({ "$ctor": ctor.as_ref(), "args": items_json(args, 2) })
+            json!({ "$ctor": ctor.as_ref(), "args": items_json(&mut args.iter(), 2) })
         }
         Value::Ctor { .. }
         | Value::Closure(_)
@@ -762,6 +839,7 @@ pub fn effect_value_from_value(value: &Value) -> Result<EffectValue, String> {
             "not plain data: a non-finite number ({n}) — the wire cannot carry NaN/Infinity"
         )),
         Value::Number(n) => Ok(EffectValue::Number(*n)),
+        Value::Int(n) => Ok(EffectValue::Int(*n)),
         Value::Bool(b) => Ok(EffectValue::Bool(*b)),
         Value::String(s) => Ok(EffectValue::Text(s.to_string())),
         Value::List(items) => Ok(EffectValue::List(
@@ -771,21 +849,16 @@ pub fn effect_value_from_value(value: &Value) -> Result<EffectValue, String> {
             entries
                 .iter()
                 .map(|(key, value)| {
-                    let key = match key {
-                        functor_lang::value::MapKey::Bool(value) => EffectMapKey::Bool(*value),
-                        // No finiteness check needed here (unlike a non-finite
-                        // VALUE above): the interpreter already refuses a
-                        // NaN/Infinity map key at insertion, so one cannot
-                        // reach this walker.
-                        functor_lang::value::MapKey::Number(value) => EffectMapKey::Number(*value),
-                        functor_lang::value::MapKey::String(value) => {
-                            EffectMapKey::Text(value.to_string())
-                        }
-                    };
-                    Ok((key, effect_value_from_value(value)?))
+                    Ok((EffectMapKey::from_functor_lang(key), effect_value_from_value(value)?))
                 })
                 .collect::<Result<_, String>>()?,
         )),
+        Value::Set(keys) => Ok(EffectValue::Set(
+            keys.iter().map(EffectMapKey::from_functor_lang).collect(),
+        )),
+        Value::Array(items) => Ok(EffectValue::Array(
+            items.iter().map(effect_value_from_value).collect::<Result<_, _>>()?,
+        )),
         Value::Tuple(items) => Ok(EffectValue::Tuple(
             items.iter().map(effect_value_from_value).collect::<Result<_, _>>()?,
         )),
@@ -801,7 +874,7 @@ pub fn effect_value_from_value(value: &Value) -> Result<EffectValue, String> {
         )),
         other => Err(format!(
             "not plain data: {} — a message must be numbers, strings, bools, \
-lists, maps, tuples, records, and variants of those (no functions, no host values)",
+lists, arrays, maps, sets, tuples, records, and variants of those (no functions, no host values)",
             value_kind(other)
         )),
     }
@@ -811,10 +884,13 @@ lists, maps, tuples, records, and variants of those (no functions, no host value
 fn value_kind(value: &Value) -> &'static str {
     match value {
         Value::Number(_) => "a number",
+        Value::Int(_) => "an int",
         Value::String(_) => "a string",
         Value::Bool(_) => "a bool",
         Value::List(_) => "a list",
         Value::Map(_) => "a map",
+        Value::Set(_) => "a set",
+        Value::Array(_) => "an array",
         Value::Tuple(_) => "a tuple",
         Value::Record(_) => "a record",
         Value::Variant { .. } => "a variant",
@@ -1347,6 +1423,28 @@ impl HostData for FunctorLangFog {
     }
 }
 
+/// A [`PostEffect`] as an opaque Functor Lang value — `Post.bloom(…)` and
+/// friends, listed by `Frame.withPost`.
+pub struct FunctorLangPost(pub PostEffect);
+
+impl HostData for FunctorLangPost {
+    fn type_name(&self) -> &'static str {
+        "Post"
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn is_reload_safe_snapshot(&self) -> bool {
+        true
+    }
+    fn debug(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
+    fn snapshot(&self) -> Option<functor_lang::snapshot::Json> {
+        serde_snapshot(&self.0)
+    }
+}
+
 /// A [`SkyboxDescription`] as an opaque Functor Lang value — `Skybox.files(…)`.
 /// `Frame.withSkybox` accepts ONLY this (the Angle rule).
 pub struct FunctorLangSkybox(pub SkyboxDescription);
@@ -1401,6 +1499,10 @@ impl HostData for FunctorLangColor {
     fn is_reload_safe_snapshot(&self) -> bool {
         true
     }
+    fn debug(&self) -> Option<String> {
+        let (r, g, b) = self.0;
+        Some(format!("({r}, {g}, {b})"))
+    }
 }
 
 impl HostData for FunctorLangVec3 {
@@ -1415,6 +1517,10 @@ impl HostData for FunctorLangVec3 {
     fn is_reload_safe_snapshot(&self) -> bool {
         true
     }
+    fn debug(&self) -> Option<String> {
+        let (x, y, z) = self.0;
+        Some(format!("({x}, {y}, {z})"))
+    }
 }
 
 impl HostData for FunctorLangAngle {
@@ -1424,6 +1530,9 @@ impl HostData for FunctorLangAngle {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+    fn debug(&self) -> Option<String> {
+        Some(format!("{}deg", self.0.radians().to_degrees()))
+    }
 }
 
 /// A [`physics::Shape`] as an opaque Functor Lang value.
@@ -1470,6 +1579,12 @@ impl HostData for FunctorLangLight {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+    fn debug(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
+    fn snapshot(&self) -> Option<functor_lang::snapshot::Json> {
+        serde_snapshot(&self.0)
+    }
 }
 
 /// A continuous soundscape voice (`AudioSource.ambient`/`at`) as an opaque Functor Lang
@@ -1505,6 +1620,15 @@ impl HostData for FunctorLangScene {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+    // The node tree's own Debug: every child, material, and transform, which
+    // is what the REPL wants when poking at a `view` helper.
+    fn debug(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
+    // The `/scene` wire form: the same fields `Scene.equals` walks.
+    fn snapshot(&self) -> Option<functor_lang::snapshot::Json> {
+        serde_snapshot(&self.0)
+    }
 }
 
 impl HostData for FunctorLangInstance {
@@ -1519,6 +1643,9 @@ impl HostData for FunctorLangInstance {
     fn is_reload_safe_snapshot(&self) -> bool {
         true
     }
+    fn debug(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
 }
 
 impl HostData for FunctorLangTerrain {
@@ -1533,6 +1660,24 @@ impl HostData for FunctorLangTerrain {
     }
 }
 
+impl HostData for FunctorLangPbrMaterial {
+    fn type_name(&self) -> &'static str {
+        "PbrMaterial"
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn is_reload_safe_snapshot(&self) -> bool {
+        true
+    }
+    fn debug(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
+    fn snapshot(&self) -> Option<functor_lang::snapshot::Json> {
+        serde_snapshot(&self.0)
+    }
+}
+
 impl HostData for FunctorLangCamera {
     fn type_name(&self) -> &'static str {
         "Camera3D"
@@ -1540,6 +1685,12 @@ impl HostData for FunctorLangCamera {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+    fn debug(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
+    fn snapshot(&self) -> Option<functor_lang::snapshot::Json> {
+        serde_snapshot(&self.0)
+    }
 }
 
 impl HostData for FunctorLangFrame {
@@ -1549,6 +1700,35 @@ impl HostData for FunctorLangFrame {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+    // Everything `Frame.equals` compares, as a tree an `expectSnapshot` can
+    // diff field by field.
+    fn snapshot(&self) -> Option<functor_lang::snapshot::Json> {
+        serde_snapshot(&self.0)
+    }
+}
+
+/// A protocol value's serde form as an `expectSnapshot` dump — the
+/// structural walk behind `Scene.equals`/`Frame.equals`, written out.
+fn serde_snapshot(value: &impl serde::Serialize) -> Option<functor_lang::snapshot::Json> {
+    fn convert(json: serde_json::Value) -> functor_lang::snapshot::Json {
+        use functor_lang::snapshot::Json;
+        match json {
+            serde_json::Value::Null => Json::Null,
+            serde_json::Value::Bool(b) => Json::Bool(b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Json::Int(i),
+                None => Json::Number(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_json::Value::String(s) => Json::String(s),
+            serde_json::Value::Array(items) => {
+                Json::Array(items.into_iter().map(convert).collect())
+            }
+            serde_json::Value::Object(fields) => {
+                Json::Object(fields.into_iter().map(|(k, v)| (k, convert(v))).collect())
+            }
+        }
+    }
+    serde_json::to_value(value).ok().map(convert)
 }
 
 /// Extract the [`Frame`] from a Functor Lang value (an `Frame.create` result), for
@@ -1783,6 +1963,103 @@ piped through RenderTarget.sized";
             Ok(FunctorLangFog(Fog::exp(density as f32, r, g, b)))
         },
     );
+    // Post effects check their parameters here, like Fog; the runtime's
+    // `PostEffect` takes whatever it is given.
+    fn post_unit(name: &str, what: &str, value: f64) -> Result<f32, String> {
+        if (0.0..=1.0).contains(&value) {
+            Ok(value as f32)
+        } else {
+            Err(format!(
+                "{name} {what} must be between 0 and 1, got {value}"
+            ))
+        }
+    }
+    fn post_exposure(name: &str, exposure: f64) -> Result<f32, String> {
+        if exposure > 0.0 {
+            Ok(exposure as f32)
+        } else {
+            Err(format!("{name} exposure must be positive, got {exposure}"))
+        }
+    }
+    reg.fn2(
+        "Post.bloom",
+        "Post.bloom(threshold, intensity)",
+        |threshold: f64, intensity: f64| {
+            let threshold = post_unit("Post.bloom", "threshold", threshold)?;
+            if intensity < 0.0 {
+                return Err(format!(
+                    "Post.bloom intensity must not be negative, got {intensity}"
+                ));
+            }
+            Ok(FunctorLangPost(PostEffect::bloom(
+                threshold,
+                intensity as f32,
+            )))
+        },
+    );
+    reg.fn1("Post.aces", "Post.aces(exposure)", |exposure: f64| {
+        let exposure = post_exposure("Post.aces", exposure)?;
+        Ok(FunctorLangPost(PostEffect::aces(exposure)))
+    });
+    reg.fn1("Post.filmic", "Post.filmic(exposure)", |exposure: f64| {
+        let exposure = post_exposure("Post.filmic", exposure)?;
+        Ok(FunctorLangPost(PostEffect::filmic(exposure)))
+    });
+    reg.fn2(
+        "Post.lut",
+        "Post.lut(texture, strength)",
+        |texture: FunctorLangTexture, strength: f64| {
+            let strength = post_unit("Post.lut", "strength", strength)?;
+            Ok(FunctorLangPost(PostEffect::color_grade(
+                texture.0, strength,
+            )))
+        },
+    );
+    reg.fn2(
+        "Post.vignette",
+        "Post.vignette(intensity, smoothness)",
+        |intensity: f64, smoothness: f64| {
+            let intensity = post_unit("Post.vignette", "intensity", intensity)?;
+            if smoothness <= 0.0 || smoothness > 1.0 {
+                return Err(format!(
+                    "Post.vignette smoothness must be above 0 and at most 1, got {smoothness}"
+                ));
+            }
+            Ok(FunctorLangPost(PostEffect::vignette(
+                intensity,
+                smoothness as f32,
+            )))
+        },
+    );
+    reg.fn0("Post.fxaa", "Post.fxaa()", || {
+        FunctorLangPost(PostEffect::Fxaa)
+    });
+    reg.fn3(
+        "Post.depthOfField",
+        "Post.depthOfField(focusDistance, focusRange, maxBlur)",
+        |focus_distance: f64, focus_range: f64, max_blur: f64| {
+            if focus_distance < 0.0 {
+                return Err(format!(
+                    "Post.depthOfField focusDistance must not be negative, got {focus_distance}"
+                ));
+            }
+            if focus_range <= 0.0 {
+                return Err(format!(
+                    "Post.depthOfField focusRange must be positive, got {focus_range}"
+                ));
+            }
+            if !(0.0..=16.0).contains(&max_blur) {
+                return Err(format!(
+                    "Post.depthOfField maxBlur must be between 0 and 16 pixels, got {max_blur}"
+                ));
+            }
+            Ok(FunctorLangPost(PostEffect::depth_of_field(
+                focus_distance as f32,
+                focus_range as f32,
+                max_blur as f32,
+            )))
+        },
+    );
 }
 
 crate::host_returnable!(
@@ -1792,8 +2069,10 @@ crate::host_returnable!(
     FunctorLangVec3,
     FunctorLangRenderTarget,
     FunctorLangFog,
+    FunctorLangPost,
     FunctorLangUiAnchor,
     FunctorLangTerrain,
+    FunctorLangPbrMaterial,
     FunctorLangScene,
     FunctorLangInstance,
     FunctorLangCamera,
@@ -2398,6 +2677,98 @@ row 0 has {cols} heights, row {r} has {}",
             )
         },
     );
+    // The metallic-roughness material is built as its own value and applied
+    // with `Scene.pbr`, so one material can dress many nodes and the texture
+    // slots stay named rather than positional. Factors outside 0..1 are
+    // errors, not clamps, matching `Scene.opacity`.
+    fn unit_factor(name: &str, what: &str, value: f64) -> Result<f32, String> {
+        if (0.0..=1.0).contains(&value) {
+            Ok(value as f32)
+        } else {
+            Err(format!(
+                "{name} {what} must be between 0 and 1, got {value}"
+            ))
+        }
+    }
+    reg.fn3(
+        "Scene.pbrMaterial",
+        "Scene.pbrMaterial(color, metallic, roughness)",
+        |color: FunctorLangColor, metallic: f64, roughness: f64| {
+            let (r, g, b) = color.0;
+            Ok(FunctorLangPbrMaterial(PbrDescription::new(
+                [r, g, b, 1.0],
+                unit_factor("Scene.pbrMaterial", "metallic", metallic)?,
+                unit_factor("Scene.pbrMaterial", "roughness", roughness)?,
+            )))
+        },
+    );
+    fn pbr_texture(
+        slot: fn(&mut PbrDescription) -> &mut Option<TextureDescription>,
+    ) -> impl Fn(FunctorLangTexture, FunctorLangPbrMaterial) -> FunctorLangPbrMaterial {
+        move |texture, mut material| {
+            *slot(&mut material.0) = Some(texture.0);
+            material
+        }
+    }
+    reg.fn2(
+        "Scene.pbrBaseColorTexture",
+        "Scene.pbrBaseColorTexture(texture, material)",
+        pbr_texture(|m| &mut m.base_color_texture),
+    );
+    reg.fn2(
+        "Scene.pbrMetallicRoughnessTexture",
+        "Scene.pbrMetallicRoughnessTexture(texture, material)",
+        pbr_texture(|m| &mut m.metallic_roughness_texture),
+    );
+    reg.fn2(
+        "Scene.pbrNormalTexture",
+        "Scene.pbrNormalTexture(texture, material)",
+        pbr_texture(|m| &mut m.normal_texture),
+    );
+    reg.fn2(
+        "Scene.pbrOcclusionTexture",
+        "Scene.pbrOcclusionTexture(texture, material)",
+        pbr_texture(|m| &mut m.occlusion_texture),
+    );
+    reg.fn2(
+        "Scene.pbrEmissiveTexture",
+        "Scene.pbrEmissiveTexture(texture, material)",
+        pbr_texture(|m| &mut m.emissive_texture),
+    );
+    reg.fn2(
+        "Scene.pbrEmissive",
+        "Scene.pbrEmissive(color, material)",
+        |color: FunctorLangColor, mut material: FunctorLangPbrMaterial| {
+            let (r, g, b) = color.0;
+            material.0.factors.emissive = [r, g, b];
+            material
+        },
+    );
+    reg.fn2(
+        "Scene.pbrAlphaMask",
+        "Scene.pbrAlphaMask(cutoff, material)",
+        |cutoff: f64, mut material: FunctorLangPbrMaterial| {
+            let cutoff = unit_factor("Scene.pbrAlphaMask", "cutoff", cutoff)?;
+            material.0.factors.alpha_mode = AlphaMode::Mask(cutoff);
+            Ok(material)
+        },
+    );
+    reg.fn2(
+        "Scene.pbrAlphaBlend",
+        "Scene.pbrAlphaBlend(alpha, material)",
+        |alpha: f64, mut material: FunctorLangPbrMaterial| {
+            material.0.factors.base_color[3] = unit_factor("Scene.pbrAlphaBlend", "alpha", alpha)?;
+            material.0.factors.alpha_mode = AlphaMode::Blend;
+            Ok(material)
+        },
+    );
+    reg.fn2(
+        "Scene.pbr",
+        "Scene.pbr(material, scene)",
+        |material: FunctorLangPbrMaterial, scene: FunctorLangScene| {
+            material_scene(MaterialDescription::Pbr(Box::new(material.0)), scene)
+        },
+    );
     // Scene LAST, so it pipes: `Scene.quad() |> Scene.screen(feed)` — an
     // emissive (fullbright, screens glow) surface showing the target's
     // texture. A target no frame declares shows magenta.
@@ -2707,6 +3078,55 @@ fn register_light(reg: &mut crate::host_registry::Registry) {
         "Light.castShadows(light)",
         |light: FunctorLangLight| FunctorLangLight(light.0.cast_shadows()),
     );
+    // The shadow knobs take the light LAST (`|> Light.castShadowsAt(1024.0)`)
+    // and keep whichever knobs they don't set, so they compose.
+    const CAST_SHADOWS_AT: &str = "Light.castShadowsAt(resolution, light)";
+    reg.fn2(
+        "Light.castShadowsAt",
+        CAST_SHADOWS_AT,
+        |resolution: f64, light: FunctorLangLight| {
+            if resolution.fract() != 0.0 || !(64.0..=4096.0).contains(&resolution) {
+                return Err(format!(
+                    "usage: {CAST_SHADOWS_AT} — a whole texel count from 64 to 4096, \
+got {resolution}"
+                ));
+            }
+            let settings = ShadowSettings {
+                resolution: resolution as u32,
+                ..light.0.shadow_settings()
+            };
+            Ok(FunctorLangLight(light.0.cast_shadows_with(settings)))
+        },
+    );
+    const CAST_CASCADED: &str = "Light.castCascadedShadows(count, distance, light)";
+    reg.fn3(
+        "Light.castCascadedShadows",
+        CAST_CASCADED,
+        |count: f64, distance: f64, light: FunctorLangLight| {
+            if !matches!(light.0, Light::Directional { .. }) {
+                return Err(format!(
+                    "usage: {CAST_CASCADED} — cascades split a directional light's view"
+                ));
+            }
+            if count.fract() != 0.0 || !(1.0..=MAX_SHADOW_CASCADES as f64).contains(&count) {
+                return Err(format!(
+                    "usage: {CAST_CASCADED} — a whole cascade count from 1 to \
+{MAX_SHADOW_CASCADES}, got {count}"
+                ));
+            }
+            if !distance.is_finite() || distance <= 0.0 {
+                return Err(format!(
+                    "usage: {CAST_CASCADED} — a positive finite distance, got {distance}"
+                ));
+            }
+            let settings = ShadowSettings {
+                cascades: count as u32,
+                distance: distance as f32,
+                ..light.0.shadow_settings()
+            };
+            Ok(FunctorLangLight(light.0.cast_shadows_with(settings)))
+        },
+    );
 }
 
 fn register_frame(reg: &mut crate::host_registry::Registry) {
@@ -2729,7 +3149,10 @@ fn register_frame(reg: &mut crate::host_registry::Registry) {
                 ui_targets: vec![],
                 fog: None,
                 skybox: None,
+                environment: None,
+                reflection_probes: vec![],
                 clear_color: None,
+                post: vec![],
                 sprite_layers: vec![],
                 pure_2d: false,
             })
@@ -2762,6 +3185,14 @@ interactive widgets render but their handlers are inert",
             FunctorLangFrame(Frame::with_fog(frame.0, fog.0))
         },
     );
+    reg.fn2(
+        "Frame.withPost",
+        "Frame.withPost([post, …], frame)",
+        |post: Vec<FunctorLangPost>, frame: FunctorLangFrame| {
+            let post = post.into_iter().map(|effect| effect.0).collect();
+            FunctorLangFrame(Frame::with_post(frame.0, post))
+        },
+    );
     reg.fn2(
         "Frame.withSkybox",
         "Frame.withSkybox(skybox, frame)",
@@ -2769,6 +3200,34 @@ interactive widgets render but their handlers are inert",
             FunctorLangFrame(Frame::with_skybox(frame.0, sky.0))
         },
     );
+    const WITH_ENVIRONMENT: &str = "Frame.withEnvironment(source, intensity, frame) — source \
+is a Skybox.files(…) or an Asset.texture(\"sky.hdr\") equirect; intensity is a \
+non-negative number";
+    reg.fn3(
+        "Frame.withEnvironment",
+        WITH_ENVIRONMENT,
+        |source: EnvironmentSourceArg, intensity: f64, frame: FunctorLangFrame| {
+            if !intensity.is_finite() || intensity < 0.0 {
+                return Err(format!("usage: {WITH_ENVIRONMENT} (got intensity {intensity})"));
+            }
+            let environment = Environment {
+                source: source.0,
+                intensity: intensity as f32,
+            };
+            Ok(FunctorLangFrame(Frame::with_environment(frame.0, environment)))
+        },
+    );
+    reg.fn2(
+        "Frame.withReflectionProbe",
+        "Frame.withReflectionProbe(position, frame)",
+        |position: FunctorLangVec3, frame: FunctorLangFrame| {
+            let (x, y, z) = position.0;
+            let probe = ReflectionProbe {
+                position: [x, y, z],
+            };
+            FunctorLangFrame(Frame::with_reflection_probe(frame.0, probe))
+        },
+    );
     // Sets the background clear color explicitly, overriding the fog-color
     // default.
     reg.fn2(
@@ -3369,6 +3828,38 @@ one with Asset.model/texture(…) first"
     }
 }
 
+/// `Frame.withEnvironment`'s source: a Skybox, or a texture Asset read as an
+/// equirect. Bare strings and other asset kinds get teaching errors.
+struct EnvironmentSourceArg(EnvironmentSource);
+
+impl crate::host_registry::FromArg for EnvironmentSourceArg {
+    fn from_arg(value: &Value, path: &str, span: Span) -> Result<Self, RunError> {
+        if asset_of(value).is_some() {
+            let path = asset_path(value, AssetKind::Texture, path, span)?;
+            return Ok(EnvironmentSourceArg(EnvironmentSource::Equirect(path)));
+        }
+        match value {
+            Value::String(s) => Err(RunError {
+                message: format!(
+                    "{path}: expected a Skybox or a texture Asset, got a bare string \
+(\"{s}\") — build one with Skybox.files(…) or Asset.texture(…)"
+                ),
+                span,
+            }),
+            _ => {
+                let skybox = skybox_of(value, path, span).map_err(|_| RunError {
+                    message: format!(
+                        "{path}: expected a Skybox or a texture Asset, got {}",
+                        value.kind_name()
+                    ),
+                    span,
+                })?;
+                Ok(EnvironmentSourceArg(EnvironmentSource::Skybox(skybox.clone())))
+            }
+        }
+    }
+}
+
 /// The Anim pose algebra — clip sampling, blending, the rest pose, additive
 /// layers, masks, per-joint rotation, model-space look-at, and two-bone reach.
 /// Playheads, targets, and weights are explicit in the values, so the pose
@@ -4414,6 +4905,8 @@ macro_rules! handle_arg {
 
 handle_arg!(
     FunctorLangTerrain => "a Terrain",
+    FunctorLangPbrMaterial => "a PbrMaterial",
+    FunctorLangPost => "a Post effect",
     FunctorLangScene => "a Scene",
     FunctorLangInstance => "an Instance",
     FunctorLangLight => "a Light",
@@ -5091,7 +5584,7 @@ pub fn asset_progress_value(progress: &crate::asset::AssetProgress) -> Value {
     Value::Record(Rc::new(vec![
         ("loaded".to_string(), Value::Number(progress.loaded as f64)),
         ("total".to_string(), Value::Number(progress.total as f64)),
-        ("failed".to_string(), Value::List(Rc::new(failed))),
+        ("failed".to_string(), Value::List(List::from(failed))),
     ]))
 }
 
@@ -5224,7 +5717,8 @@ pub fn split_model_effect(value: Value) -> (Value, Option<EffectTree>) {
 pub fn contains_effect(value: &Value) -> bool {
     match value {
         Value::HostData(data) => data.as_any().downcast_ref::<FunctorLangEffect>().is_some(),
-        Value::Tuple(items) | Value::List(items) => items.iter().any(contains_effect),
+        Value::Tuple(items) => items.iter().any(contains_effect),
+        Value::List(items) | Value::Array(items) => items.iter().any(contains_effect),
         Value::Map(entries) => entries.iter().any(|(_, value)| contains_effect(value)),
         Value::Record(fields) => fields.iter().any(|(_, v)| contains_effect(v)),
         Value::Variant { args, .. } => args.iter().any(contains_effect),
@@ -7611,6 +8105,56 @@ texture placeholder for a model asset; construct it with Asset.model(…)",
         );
     }
 
+    #[test]
+    fn shadow_knobs_compose_and_validate() {
+        let frame = frame_of(
+            "let main = () =>\n\
+             Frame.createLit(\n\
+               Camera3D.lookAt(Vec3.make(0.0, 4.0, -10.0), Vec3.make(0.0, 0.0, 0.0)),\n\
+               Scene.cube() |> Scene.lit(Color.rgb(0.8, 0.8, 0.8)),\n\
+               [\n\
+                 Light.directional(Vec3.make(0.4, -1.0, 0.2), Color.rgb(1.0, 1.0, 1.0), 1.0)\n\
+                   |> Light.castCascadedShadows(3.0, 80.0)\n\
+                   |> Light.castShadowsAt(1024.0),\n\
+                 Light.point(Vec3.make(0.0, 3.0, 0.0), Color.rgb(1.0, 0.8, 0.6), 2.0, 9.0)\n\
+                   |> Light.castShadowsAt(512.0),\n\
+               ])",
+        );
+        assert_eq!(
+            frame.lights[0].shadow_settings(),
+            ShadowSettings {
+                resolution: 1024,
+                cascades: 3,
+                distance: 80.0,
+            }
+        );
+        assert!(frame.lights[1].casts_shadows(), "point casts shadows");
+        assert_eq!(frame.lights[1].shadow_settings().resolution, 512);
+        assert_eq!(frame.lights[1].shadow_settings().cascades, 1);
+
+        assert_eq!(
+            fail_message(
+                "let main = () => Light.point(Vec3.make(0.0, 1.0, 0.0), Color.rgb(1.0, 1.0, 1.0), 1.0, 5.0) |> Light.castCascadedShadows(2.0, 40.0)"
+            ),
+            "usage: Light.castCascadedShadows(count, distance, light) — cascades split a \
+directional light's view"
+        );
+        assert_eq!(
+            fail_message(
+                "let main = () => Light.directional(Vec3.make(0.0, -1.0, 0.0), Color.rgb(1.0, 1.0, 1.0), 1.0) |> Light.castCascadedShadows(6.0, 40.0)"
+            ),
+            "usage: Light.castCascadedShadows(count, distance, light) — a whole cascade count \
+from 1 to 4, got 6"
+        );
+        assert_eq!(
+            fail_message(
+                "let main = () => Light.ambient(Color.rgb(1.0, 1.0, 1.0)) |> Light.castShadowsAt(100.5)"
+            ),
+            "usage: Light.castShadowsAt(resolution, light) — a whole texel count from 64 to \
+4096, got 100.5"
+        );
+    }
+
     // Host errors are spanned Functor Lang runtime errors, not panics.
     #[test]
     fn prelude_errors_are_spanned() {
@@ -8159,6 +8703,56 @@ Vec3.make(x, y, z)"
         );
     }
 
+    /// `Scene.pbr` lowers the built material onto the subtree: every
+    /// modifier fills its own slot, and blending carries its alpha into the
+    /// base color.
+    #[test]
+    fn scene_pbr_builds_a_metallic_roughness_material() {
+        let value = eval(
+            "let rust = Scene.pbrMaterial(Color.rgb(0.5, 0.25, 0.0), 1.0, 0.5)\n\
+               |> Scene.pbrNormalTexture(Texture.file(\"n.png\"))\n\
+               |> Scene.pbrOcclusionTexture(Asset.texture(\"ao.png\"))\n\
+               |> Scene.pbrEmissive(Color.rgb(1.0, 0.0, 0.0))\n\
+               |> Scene.pbrAlphaBlend(0.5)\n\
+             let main = () => Scene.sphere() |> Scene.pbr(rust)",
+        );
+        let scene = scene_of(&value).expect("a Scene");
+        let SceneObject::Material(MaterialDescription::Pbr(pbr), _) = &scene.obj else {
+            panic!("expected a Pbr material node, got {:?}", scene.obj);
+        };
+        assert_eq!(pbr.factors.base_color, [0.5, 0.25, 0.0, 0.5]);
+        assert_eq!((pbr.factors.metallic, pbr.factors.roughness), (1.0, 0.5));
+        assert_eq!(pbr.factors.emissive, [1.0, 0.0, 0.0]);
+        assert_eq!(pbr.factors.alpha_mode, AlphaMode::Blend);
+        assert!(pbr.factors.double_sided);
+        assert_eq!(
+            pbr.normal_texture,
+            Some(TextureDescription::File("n.png".to_string()))
+        );
+        assert_eq!(
+            pbr.occlusion_texture,
+            Some(TextureDescription::File("ao.png".to_string()))
+        );
+        assert_eq!(pbr.base_color_texture, None);
+    }
+
+    #[test]
+    fn scene_pbr_factors_outside_unit_range_are_errors() {
+        for (src, want) in [
+            (
+                "let main = () => Scene.pbrMaterial(Color.rgb(1.0, 1.0, 1.0), 1.5, 0.5)",
+                "Scene.pbrMaterial metallic must be between 0 and 1, got 1.5",
+            ),
+            (
+                "let main = () => Scene.pbrMaterial(Color.rgb(1.0, 1.0, 1.0), 0.0, 0.5) \
+                 |> Scene.pbrAlphaMask(-0.1)",
+                "Scene.pbrAlphaMask cutoff must be between 0 and 1, got -0.1",
+            ),
+        ] {
+            assert_eq!(fail_message(src), want, "for {src}");
+        }
+    }
+
     /// `Scene.billboard` builds the plain `Shape::Billboard` leaf — a
     /// camera-FREE scene value (the view-dependence is the renderer's, at
     /// draw time), so it stamps, compares, and replays like any other shape.
@@ -8491,6 +9085,12 @@ Scene.cube()))"
                 "{base} |> Frame.withFog(Fog.linear(5.0, 50.0, Color.rgb(0.1, 0.1, 0.2)))"
             ),
         );
+        // Post effects — present vs absent, and their order.
+        differs(&base, &format!("{base} |> Frame.withPost([Post.fxaa()])"));
+        differs(
+            &format!("{base} |> Frame.withPost([Post.fxaa(), Post.aces(1.0)])"),
+            &format!("{base} |> Frame.withPost([Post.aces(1.0), Post.fxaa()])"),
+        );
         // Skybox.
         differs(
             &base,
@@ -9076,6 +9676,90 @@ Fog.linear(near, far, color) or Fog.exp(density, color)"
         );
     }
 
+    // The post vocabulary: branded effects listed in order on the frame, and
+    // the wire shape round-trips.
+    #[test]
+    fn functor_lang_snippet_declares_a_post_stack() {
+        let frame = frame_of(
+            "let main = () =>\n\
+             Frame.create(Camera3D.lookAt(Vec3.make(0.0, 2.0, -8.0), Vec3.make(0.0, 1.0, 0.0)), Scene.cube())\n\
+             |> Frame.withPost([Post.bloom(0.8, 1.5), Post.aces(1.2), \
+Post.lut(Texture.file(\"grade.png\"), 0.5), Post.vignette(0.4, 0.5), Post.fxaa(), \
+Post.depthOfField(6.0, 4.0, 8.0)])",
+        );
+        assert_eq!(
+            frame.post,
+            vec![
+                PostEffect::bloom(0.8, 1.5),
+                PostEffect::aces(1.2),
+                PostEffect::color_grade(TextureDescription::File("grade.png".into()), 0.5),
+                PostEffect::vignette(0.4, 0.5),
+                PostEffect::Fxaa,
+                PostEffect::depth_of_field(6.0, 4.0, 8.0),
+            ]
+        );
+        let json = serde_json::to_string(&frame).expect("serialize");
+        let back: Frame = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(back.post, frame.post);
+
+        // `withPost` replaces the stack rather than appending to it.
+        let replaced = frame_of(
+            "let main = () =>\n\
+             Frame.create(Camera3D.lookAt(Vec3.make(0.0, 2.0, -8.0), Vec3.make(0.0, 1.0, 0.0)), Scene.cube())\n\
+             |> Frame.withPost([Post.fxaa()]) |> Frame.withPost([Post.filmic(1.0)])",
+        );
+        assert_eq!(replaced.post, vec![PostEffect::filmic(1.0)]);
+    }
+
+    // Post parameters outside their ranges are teaching errors at
+    // construction, like Fog's.
+    #[test]
+    fn post_parameters_are_checked() {
+        let fail = |src: &str| {
+            let module = functor_lang::lower(functor_lang::parse(src).unwrap()).unwrap();
+            functor_lang::run_with_host(&module, Tracing::Off, &mut FunctorHost)
+                .err()
+                .expect("should fail")
+                .error
+                .message
+        };
+        assert_eq!(
+            fail("let main = () => Post.bloom(1.5, 1.0)"),
+            "Post.bloom threshold must be between 0 and 1, got 1.5"
+        );
+        assert_eq!(
+            fail("let main = () => Post.bloom(0.5, -1.0)"),
+            "Post.bloom intensity must not be negative, got -1"
+        );
+        assert_eq!(
+            fail("let main = () => Post.aces(0.0)"),
+            "Post.aces exposure must be positive, got 0"
+        );
+        assert_eq!(
+            fail("let main = () => Post.vignette(0.5, 0.0)"),
+            "Post.vignette smoothness must be above 0 and at most 1, got 0"
+        );
+        assert_eq!(
+            fail("let main = () => Post.depthOfField(5.0, 0.0, 4.0)"),
+            "Post.depthOfField focusRange must be positive, got 0"
+        );
+        assert_eq!(
+            fail("let main = () => Post.depthOfField(5.0, 2.0, 32.0)"),
+            "Post.depthOfField maxBlur must be between 0 and 16 pixels, got 32"
+        );
+        assert_eq!(
+            fail("let main = () => Post.lut(Texture.file(\"grade.png\"), 2.0)"),
+            "Post.lut strength must be between 0 and 1, got 2"
+        );
+        assert_eq!(
+            fail(
+                "let main = () => Frame.create(Camera3D.lookAt(Vec3.make(0.0, 0.0, -5.0), \
+Vec3.make(0.0, 0.0, 0.0)), Scene.cube()) |> Frame.withPost([0.5])"
+            ),
+            "Frame.withPost: expected a Post effect, got a number"
+        );
+    }
+
     // The skybox vocabulary: a branded Skybox on the frame, six faces in GL
     // upload order, round-tripping the protocol wire shape.
     #[test]
@@ -9128,6 +9812,63 @@ paths (+X, -X, +Y, -Y, +Z, -Z)"
         );
     }
 
+    // Frame.withEnvironment takes a Skybox or an equirect texture Asset;
+    // probes accumulate in declaration order.
+    #[test]
+    fn functor_lang_snippet_declares_an_environment_and_probes() {
+        let base = "Frame.create(Camera3D.lookAt(Vec3.make(0.0, 2.0, -8.0), \
+Vec3.make(0.0, 1.0, 0.0)), Scene.cube())";
+        let frame = frame_of(&format!(
+            "let main = () =>\n\
+             {base}\n\
+             |> Frame.withEnvironment(Asset.texture(\"studio.hdr\"), 1.5)\n\
+             |> Frame.withReflectionProbe(Vec3.make(0.0, 1.0, 0.0))\n\
+             |> Frame.withReflectionProbe(Vec3.make(4.0, 1.0, 0.0))"
+        ));
+        let environment = frame.environment.as_ref().expect("environment set");
+        assert_eq!(
+            environment.source,
+            EnvironmentSource::Equirect("studio.hdr".to_string())
+        );
+        assert_eq!(environment.intensity, 1.5);
+        let positions: Vec<[f32; 3]> = frame.reflection_probes.iter().map(|p| p.position).collect();
+        assert_eq!(positions, [[0.0, 1.0, 0.0], [4.0, 1.0, 0.0]]);
+
+        let frame = frame_of(&format!(
+            "let sky = Skybox.files(\"px.jpg\", \"nx.jpg\", \"py.jpg\", \"ny.jpg\", \
+\"pz.jpg\", \"nz.jpg\")\n\
+             let main = () => {base} |> Frame.withEnvironment(sky, 1.0)"
+        ));
+        let Some(EnvironmentSource::Skybox(sky)) = frame.environment.map(|e| e.source) else {
+            panic!("a skybox environment");
+        };
+        assert_eq!(sky.faces()[0], "px.jpg");
+    }
+
+    // The Angle rule again: bare paths, non-texture assets and bad
+    // intensities are teaching errors.
+    #[test]
+    fn environment_sources_must_be_skyboxes_or_texture_assets() {
+        let with = |args: &str| {
+            fail_message(&format!(
+                "let main = () => Frame.create(Camera3D.lookAt(Vec3.make(0.0, 0.0, -5.0), \
+Vec3.make(0.0, 0.0, 0.0)), Scene.cube()) |> Frame.withEnvironment({args})"
+            ))
+        };
+        assert_eq!(
+            with("\"sky.hdr\", 1.0"),
+            "Frame.withEnvironment: expected a Skybox or a texture Asset, got a bare string \
+(\"sky.hdr\") — build one with Skybox.files(…) or Asset.texture(…)"
+        );
+        assert_eq!(
+            with("Asset.sound(\"wind.ogg\"), 1.0"),
+            "Frame.withEnvironment: expected a texture asset, got a sound asset (\"wind.ogg\") \
+— construct it with Asset.texture(…)"
+        );
+        assert!(with("Asset.texture(\"sky.hdr\"), -1.0")
+            .starts_with("usage: Frame.withEnvironment(source, intensity, frame)"));
+    }
+
     // The physics vocabulary: a Functor Lang snippet declares a PhysicsScene the
     // shells can hand to `World::reconcile` — bodies, attributes, gravity.
     #[test]
@@ -10360,6 +11101,87 @@ translation-only; use Physics.at with an unrotated Scene.terrain"
         assert_eq!(effect_value_from_value(&functor_lang).unwrap(), value);
     }
 
+    /// An `int` crosses the wire as its own tag — exact past 2^53, and never
+    /// read back as the float it would print like.
+    #[test]
+    fn int_effect_values_stay_exact_ints_on_the_wire() {
+        let value = EffectValue::Record(vec![
+            ("tick".to_string(), EffectValue::Int(9_007_199_254_740_993)),
+            ("x".to_string(), EffectValue::Number(3.0)),
+            (
+                "counts".to_string(),
+                EffectValue::Map(vec![(EffectMapKey::Int(-2), EffectValue::Int(3))]),
+            ),
+        ]);
+        let json = serde_json::to_string(&value).expect("serialize");
+        assert!(json.contains(r#"{"Int":9007199254740993}"#), "{json}");
+        let back: EffectValue = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(back, value);
+
+        let functor_lang = value.to_functor_lang().unwrap();
+        let Value::Record(fields) = &functor_lang else {
+            panic!("expected a record, got {}", functor_lang.kind_name());
+        };
+        assert!(matches!(fields[0].1, Value::Int(9_007_199_254_740_993)));
+        assert!(matches!(fields[1].1, Value::Number(n) if n == 3.0));
+        assert_eq!(effect_value_from_value(&functor_lang).unwrap(), value);
+    }
+
+    /// An `expectSnapshot` dump of plain data IS the value's canonical
+    /// `EffectValue` JSON, read back — the stored files and the wire agree.
+    #[test]
+    fn snapshot_dumps_of_plain_data_are_effect_value_json() {
+        let value = EffectValue::Record(vec![
+            ("tick".to_string(), EffectValue::Int(42)),
+            ("x".to_string(), EffectValue::Number(2.0)),
+            (
+                "parts".to_string(),
+                EffectValue::Tuple(vec![
+                    EffectValue::Set(vec![EffectMapKey::Int(1), EffectMapKey::Text("b".into())]),
+                    EffectValue::Array(vec![EffectValue::Bool(true)]),
+                    EffectValue::Variant("Some".to_string(), vec![EffectValue::Number(0.5)]),
+                ]),
+            ),
+            (
+                "scores".to_string(),
+                EffectValue::Map(vec![(EffectMapKey::Number(1.0), EffectValue::List(vec![]))]),
+            ),
+        ]);
+        let wire = serde_json::to_string(&value).expect("serialize");
+        let dump = functor_lang::snapshot::dump(&value.to_functor_lang().unwrap()).unwrap();
+        assert_eq!(functor_lang::snapshot::Json::parse(&wire), Ok(dump));
+    }
+
+    /// Sets and arrays keep their own shape across the wire, and a set read
+    /// off it is re-canonicalized like a map.
+    #[test]
+    fn set_and_array_effect_values_roundtrip_as_themselves() {
+        let value = EffectValue::Tuple(vec![
+            EffectValue::Set(vec![
+                EffectMapKey::Text("b".to_string()),
+                EffectMapKey::Int(1),
+                EffectMapKey::Text("b".to_string()),
+            ]),
+            EffectValue::Array(vec![EffectValue::Int(7), EffectValue::Int(8)]),
+        ]);
+        let json = serde_json::to_string(&value).expect("serialize");
+        let back: EffectValue = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(back, value);
+
+        let functor_lang = value.to_functor_lang().unwrap();
+        assert_eq!(
+            functor_lang.to_string(),
+            r#"(Set.fromList([1, "b"]), Array.fromList([7, 8]))"#
+        );
+        assert_eq!(
+            effect_value_from_value(&functor_lang).unwrap(),
+            EffectValue::Tuple(vec![
+                EffectValue::Set(vec![EffectMapKey::Int(1), EffectMapKey::Text("b".to_string())]),
+                EffectValue::Array(vec![EffectValue::Int(7), EffectValue::Int(8)]),
+            ])
+        );
+    }
+
     #[test]
     fn effect_map_values_restore_canonical_order_at_the_replay_seam() {
         let value = EffectValue::Map(vec![
@@ -10421,11 +11243,11 @@ translation-only; use Physics.at with an unrotated Scene.terrain"
             ("alive".to_string(), Value::Bool(true)),
             (
                 "items".to_string(),
-                Value::List(Rc::new(vec![Value::Number(1.0), Value::Number(2.0)])),
+                Value::List(List::from(vec![Value::Number(1.0), Value::Number(2.0)])),
             ),
             (
                 "scores".to_string(),
-                Value::Map(Rc::new(vec![
+                Value::Map(Map::from_canonical(vec![
                     (
                         functor_lang::value::MapKey::String(Rc::from("a")),
                         Value::Number(1.0),
@@ -10479,7 +11301,7 @@ translation-only; use Physics.at with an unrotated Scene.terrain"
     fn value_to_json_bounds_depth() {
         let mut value = Value::Number(0.0);
         for _ in 0..(VALUE_JSON_MAX_DEPTH + 10) {
-            value = Value::List(Rc::new(vec![value]));
+            value = Value::List(List::from(vec![value]));
         }
         let mut node = &value_to_json(&value);
         while let Some(items) = node.as_array() {
@@ -10624,12 +11446,12 @@ translation-only; use Physics.at with an unrotated Scene.terrain"
         assert!(contains_effect(&fx));
         let nested = Value::Record(std::rc::Rc::new(vec![(
             "inner".to_string(),
-            Value::List(std::rc::Rc::new(vec![Value::Tuple(std::rc::Rc::new(
+            Value::List(List::from(vec![Value::Tuple(std::rc::Rc::new(
                 vec![Value::Number(1.0), fx],
             ))])),
         )]));
         assert!(contains_effect(&nested));
-        let in_map = Value::Map(std::rc::Rc::new(vec![(
+        let in_map = Value::Map(functor_lang::value::Map::from_canonical(vec![(
             functor_lang::value::MapKey::String(std::rc::Rc::from("command")),
             nested,
         )]));
@@ -11421,7 +12243,7 @@ the game dir"
             ("score".to_string(), Value::Number(42.0)),
             (
                 "tags".to_string(),
-                Value::List(std::rc::Rc::new(vec![Value::String(Rc::from("a"))])),
+                Value::List(List::from(vec![Value::String(Rc::from("a"))])),
             ),
         ]));
         drain(&mut model, "save");
@@ -12487,7 +13309,7 @@ a number",
         let held = |keys: Vec<Value>| {
             Value::Record(std::rc::Rc::new(vec![(
                 "heldKeys".to_string(),
-                Value::List(std::rc::Rc::new(keys)),
+                Value::List(List::from(keys)),
             )]))
         };
         let tick = |session: &functor_lang::Session, model: Value| {
@@ -259,7 +259,7 @@ fn convex_outline(points: Vec<FunctorLangPoint2>, path: &str) -> Result<Vec<[f32
 }
 
 fn point_list_value(points: &[[f32; 2]]) -> Value {
-    Value::List(Rc::new(
+    Value::List(
         points
             .iter()
             .map(|point| {
@@ -269,7 +269,7 @@ fn point_list_value(points: &[[f32; 2]]) -> Value {
                 ]))
             })
             .collect(),
-    ))
+    )
 }
 
 fn sprite_node(name: &str, args: Vec<Value>) -> Value {
@@ -292,7 +292,7 @@ fn sprite_region_node(x: f64, y: f64, width: f64, height: f64) -> Value {
 }
 
 fn sprite_children(items: Vec<FunctorLangSprite>) -> Value {
-    Value::List(Rc::new(items.into_iter().map(|item| item.0).collect()))
+    Value::List(items.into_iter().map(|item| item.0).collect())
 }
 
 fn sprite_texture_parts(texture: FunctorLangTexture) -> Result<(String, Vec<String>), String> {
@@ -348,12 +348,12 @@ fn sprite_image_node(
         ]);
     }
     args.push(Value::String(path.into()));
-    args.push(Value::List(Rc::new(
+    args.push(Value::List(
         pending
             .into_iter()
             .map(|path| Value::String(path.into()))
             .collect(),
-    )));
+    ));
     Ok(sprite_node(
         if source_pixels.is_some() {
             "ImageRegion"
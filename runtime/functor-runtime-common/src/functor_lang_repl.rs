@@ -0,0 +1,106 @@
+//! The Functor Lang REPL under the ENGINE prelude — the library core behind
+//! `functor repl`.
+//!
+//! `functor-lang repl` runs [`functor_lang::repl`] under `NoHost`, so a
+//! project whose modules build `Scene.*` / `Vec3.*` values will not even
+//! load there (the same reason `functor test` exists; see
+//! [`crate::functor_lang_test`]). This binds the real [`FunctorHost`] plus the
+//! engine's bundled modules and `.funi` interfaces, so engine values can be
+//! built, typechecked, and printed — with their contents, through the
+//! prelude's `HostData::debug` impls (`<Vec3 (1, 2, 3)>`).
+//!
+//! The `Ui.*` constructors register handlers in the prelude's
+//! `UI_HANDLERS` thread-local, which nothing consumes here; [`run`]'s host
+//! drops them as they register, as [`crate::functor_lang_test`] does at the
+//! end of its run.
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use functor_lang::repl::Repl;
+use functor_lang::{Host, RunError, Span, Value};
+
+use crate::functor_lang_prelude::FunctorHost;
+
+/// Start a REPL session over the project rooted at `entry`, loaded exactly as
+/// `build` and the producers load it.
+pub fn repl(entry: &Path) -> Result<Repl, String> {
+    Repl::new(entry, functor_prelude::bundled_modules())
+}
+
+/// Run the interactive loop over `repl` under the engine host (see
+/// [`functor_lang::repl::run`]).
+pub fn run(repl: &mut Repl, input: impl BufRead, output: impl Write) -> io::Result<()> {
+    functor_lang::repl::run(repl, &mut ReplHost, input, output)
+}
+
+/// [`FunctorHost`], dropping the `Ui.*` handlers each call registered: the
+/// REPL re-evaluates the whole project per input, so they would otherwise
+/// pile up for the life of the session.
+struct ReplHost;
+
+impl Host for ReplHost {
+    fn provides(&self, path: &str) -> bool {
+        FunctorHost.provides(path)
+    }
+
+    fn call(&mut self, path: &str, args: Vec<Value>, span: Span) -> Result<Value, RunError> {
+        let result = FunctorHost.call(path, args, span);
+        let _ = crate::functor_lang_prelude::take_ui_handlers();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Write a throwaway project directory and return its entry path.
+    fn project(files: &[(&str, &str)]) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().expect("tempdir");
+        for (file, src) in files {
+            std::fs::write(dir.path().join(file), src).expect("write");
+        }
+        let entry = dir.path().join(files[0].0);
+        (dir, entry)
+    }
+
+    fn eval(repl: &mut Repl, input: &str) -> String {
+        match repl.eval(input, &mut ReplHost) {
+            Ok(evaluated) => evaluated.to_string(),
+            Err(error) => format!("error: {error}"),
+        }
+    }
+
+    /// The point of binding the engine host: `functor-lang repl` cannot even
+    /// load a project with a top-level `Color.rgb`, and prints host values
+    /// opaquely; here they build and print with their contents.
+    #[test]
+    fn engine_values_build_and_print_with_their_contents() {
+        let (_dir, entry) = project(&[(
+            "game.fun",
+            "let sky = Color.rgb(0.5, 0.25, 1.0)\nlet up = Vec3.make(0.0, 1.0, 0.0)\n",
+        )]);
+        let mut repl = repl(&entry).expect("project loads");
+        assert_eq!(eval(&mut repl, "sky"), "<Color (0.5, 0.25, 1)> : Color.t");
+        assert_eq!(
+            eval(&mut repl, "let at = Vec3.make(1.0, 2.0, 3.0)"),
+            "at : Vec3.t = <Vec3 (1, 2, 3)>"
+        );
+        assert_eq!(eval(&mut repl, "90deg"), "<Angle 90deg> : Angle.t");
+        let scene = eval(&mut repl, "Scene.lit(sky, Scene.cube())");
+        assert!(scene.starts_with("<Scene "), "{scene}");
+        assert!(scene.ends_with("> : Scene.t"), "{scene}");
+    }
+
+    #[test]
+    fn the_loop_runs_under_the_engine_host() {
+        let (_dir, entry) = project(&[("game.fun", "let origin = Vec3.make(0.0, 0.0, 0.0)\n")]);
+        let mut repl = repl(&entry).expect("project loads");
+        let mut output = Vec::new();
+        run(&mut repl, "origin\n".as_bytes(), &mut output).expect("loop runs");
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("> <Vec3 (0, 0, 0)> : Vec3.t\n"), "{output}");
+    }
+}
@@ -1,5 +1,5 @@
-//! Run a game project's inline `expect` tests under the ENGINE prelude,
-//! headlessly — no GL context, no window, no game loop.
+//! Run a game project's inline `expect` and `property` tests under the ENGINE
+//! prelude, headlessly — no GL context, no window, no game loop.
 //!
 //! `functor-lang test` (the language crate's CLI) evaluates expects under the
 //! plain `NoHost` prelude, so it fails on any project whose modules mention
@@ -18,19 +18,32 @@
 //! end, so a project whose expects construct widgets doesn't leak closures.
 //!
 //! This is the library core behind `functor test`; the CLI command is a thin
-//! wrapper that renders [`ExpectRun`] as diagnostics.
+//! wrapper that renders [`ExpectRun`] as diagnostics (or, with `--reporter`,
+//! as a [`crate::functor_lang_test_report`]). A [`Selection`] narrows a run to
+//! the tests matching `--filter`/`--module`, and `--watch` re-runs only the
+//! modules an edit can affect ([`SourceSnapshot`], [`affected_modules`]).
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use crate::functor_lang_prelude::FunctorHost;
 
 /// One evaluated `expect`, located in the file that wrote it.
 #[derive(Debug, Clone)]
 pub struct ExpectCase {
+    /// The test's source on one line (`expect double(2.0) == 4.0`) — what
+    /// `--filter` matches and reporters name the test by.
+    pub name: String,
+    /// The declaring module: the file's (`Helpers`), or an inline block's
+    /// (`Helpers.Grid`).
+    pub module: String,
     pub file: PathBuf,
     pub line: usize,
     pub col: usize,
+    /// Where the test's source ends (1-based, like `line`/`col`).
+    pub end_line: usize,
+    pub end_col: usize,
     /// The offending source line, carried from the already-in-memory
     /// `SourceFile` so the caller renders a caret without re-reading (and
     /// without risking a *different* snapshot than the one evaluated).
@@ -38,12 +51,24 @@ pub struct ExpectCase {
     /// `None` when the expect held; otherwise the human-facing reason (a
     /// rendered comparison, or a located runtime error).
     pub failure: Option<String>,
+    /// `pass` / `fail` / `error` / `unrunnable` — the editor gutters' states
+    /// ([`functor_lang::ExpectOutcome::status`]).
+    pub state: &'static str,
+    /// A failed comparison's operator and rendered sides.
+    pub compare: Option<functor_lang::FailedCompare>,
+    pub duration: Option<Duration>,
 }
 
 /// Every expect in the project, in source order.
 #[derive(Debug, Clone)]
 pub struct ExpectRun {
     pub cases: Vec<ExpectCase>,
+    /// `expectSnapshot` values written to `__snapshots__` by this run — new
+    /// ones, plus changed ones when the run updates snapshots.
+    pub snapshots_written: usize,
+    /// Line, branch and function coverage of the project's own files — only
+    /// from [`run_covered`].
+    pub coverage: Option<Vec<functor_lang::coverage::FileCoverage>>,
 }
 
 impl ExpectRun {
@@ -71,6 +96,100 @@ pub struct ExpectRunError {
     pub message: String,
 }
 
+/// Which tests a run evaluates. Every condition set must hold; the default
+/// selects every test.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// A substring of the test's [`ExpectCase::name`] (`functor test
+    /// --filter`).
+    pub filter: Option<String>,
+    /// A module (`--module`): a file's name selects its inline blocks too,
+    /// `Helpers.Grid` just that block.
+    pub module: Option<String>,
+    /// File modules to run — the watch loop's [`affected_modules`].
+    pub modules: Option<HashSet<String>>,
+}
+
+impl Selection {
+    fn includes(&self, test: &Located) -> bool {
+        let module = self.module.as_deref();
+        self.filter
+            .as_deref()
+            .is_none_or(|filter| test.name.contains(filter))
+            && module.is_none_or(|module| in_module(&test.module, module))
+            && (self.modules.as_ref()).is_none_or(|modules| modules.contains(&test.file_module))
+    }
+}
+
+/// Whether `module` (`Helpers.Grid`) is `wanted` or one of its blocks.
+fn in_module(module: &str, wanted: &str) -> bool {
+    module
+        .strip_prefix(wanted)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// A test's name and module, from the source it was loaded from.
+struct Located {
+    name: String,
+    module: String,
+    file_module: String,
+}
+
+fn locate(project: &functor_lang::project::Project, span: functor_lang::Span) -> Located {
+    let file = project.sources.resolve(span.start).0;
+    let text = file
+        .src
+        .get(span.start - file.base..span.end - file.base)
+        .unwrap_or_default();
+    let module = match project.inline_module_at(span.start) {
+        Some(block) => format!("{}.{}", file.module, block.name),
+        None => file.module.clone(),
+    };
+    Located {
+        name: text.split_whitespace().collect::<Vec<_>>().join(" "),
+        module,
+        file_module: file.module.clone(),
+    }
+}
+
+/// The project's source files and their contents. `functor test --watch`
+/// polls one (contents, not mtimes — like `push --watch`) and re-runs what
+/// the changed files can affect.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceSnapshot(BTreeMap<PathBuf, String>);
+
+impl SourceSnapshot {
+    pub fn read(entry: &Path) -> std::io::Result<SourceSnapshot> {
+        let mut files = BTreeMap::new();
+        for path in functor_lang::project::project_files(entry)? {
+            let src = std::fs::read_to_string(&path)?;
+            files.insert(path, src);
+        }
+        Ok(SourceSnapshot(files))
+    }
+
+    /// Files added, edited, or removed since `earlier`.
+    pub fn changed_since(&self, earlier: &SourceSnapshot) -> Vec<PathBuf> {
+        let edited = self.0.iter().filter(|(path, src)| earlier.0.get(*path) != Some(src));
+        let removed = earlier.0.keys().filter(|path| !self.0.contains_key(*path));
+        edited.map(|(path, _)| path).chain(removed).cloned().collect()
+    }
+}
+
+/// The file modules whose tests an edit to `paths` can change: theirs and
+/// every module depending on them. `None` when a path is no longer one of
+/// `project`'s files (a removed module) — re-run everything.
+pub fn affected_modules(
+    project: &functor_lang::project::Project,
+    paths: &[PathBuf],
+) -> Option<HashSet<String>> {
+    let changed = paths
+        .iter()
+        .map(|path| Some(project.sources.file_by_path(path)?.module.clone()))
+        .collect::<Option<HashSet<_>>>()?;
+    Some(project.dependents(&changed))
+}
+
 /// Load `entry` as a project (the entry plus every sibling `.fun`, plus the
 /// engine's bundled modules and `.funi` interfaces — exactly what `build` and
 /// the producers load) and evaluate its `expect` tests under the engine host.
@@ -91,7 +210,7 @@ pub fn run_project_expects(entry: &Path) -> Result<ExpectRun, ExpectRunError> {
         col: e.col,
         message: e.message,
     })?;
-    run_expects_in(&project)
+    run_expects_in(&project, &functor_lang::ExpectOptions::default())
 }
 
 /// Evaluate an ALREADY-LOADED project's `expect` tests under the engine host.
@@ -104,22 +223,104 @@ pub fn run_project_expects(entry: &Path) -> Result<ExpectRun, ExpectRunError> {
 /// Expects from the engine's own bundled modules are EXCLUDED: they are not
 /// the user's tests, and their `<builtin>/…` paths are not files anyone can
 /// open. (`build`'s module count filters the same marker.)
-pub fn run_expects_in(project: &functor_lang::project::Project) -> Result<ExpectRun, ExpectRunError> {
-    let reports =
-        functor_lang::run_expects(&project.module, &mut FunctorHost).map_err(|failure| {
-            let (file, line, col) = project.sources.resolve(failure.error.span.start);
-            ExpectRunError {
-                file: file.path.clone(),
-                line,
-                col,
-                message: failure.error.message.clone(),
-            }
-        });
+///
+/// `options` sets how `property` tests sample (their case count and seed),
+/// and whether `expectSnapshot` tests accept changed values
+/// (`options.snapshots.update`). The stored values themselves are read here,
+/// from each module's `__snapshots__` file, and new or accepted ones are
+/// written back after the run.
+pub fn run_expects_in(
+    project: &functor_lang::project::Project,
+    options: &functor_lang::ExpectOptions,
+) -> Result<ExpectRun, ExpectRunError> {
+    run_selected(project, options, &Selection::default())
+}
+
+/// [`run_expects_in`], evaluating only the tests `selection` includes. A
+/// `selection.module` the project does not declare is an error rather than
+/// an empty run, so a typo cannot pass CI.
+pub fn run_selected(
+    project: &functor_lang::project::Project,
+    options: &functor_lang::ExpectOptions,
+    selection: &Selection,
+) -> Result<ExpectRun, ExpectRunError> {
+    run(project, options, selection, false)
+}
+
+/// [`run_selected`], also recording which of the project's expressions the
+/// run evaluated ([`ExpectRun::coverage`] — `functor test --coverage`). The
+/// engine's bundled modules are left out, like their tests.
+pub fn run_covered(
+    project: &functor_lang::project::Project,
+    options: &functor_lang::ExpectOptions,
+    selection: &Selection,
+) -> Result<ExpectRun, ExpectRunError> {
+    run(project, options, selection, true)
+}
+
+fn run(
+    project: &functor_lang::project::Project,
+    options: &functor_lang::ExpectOptions,
+    selection: &Selection,
+    cover: bool,
+) -> Result<ExpectRun, ExpectRunError> {
+    let file_of = |span: functor_lang::Span| {
+        let path = &project.sources.resolve(span.start).0.path;
+        (!path.starts_with("<builtin>")).then(|| path.clone())
+    };
+    let snapshot_error = |message: String| ExpectRunError {
+        file: project.sources.resolve(0).0.path.clone(),
+        line: 1,
+        col: 1,
+        message,
+    };
+    if let Some(module) = &selection.module {
+        let files = project.sources.files().iter().map(|file| file.module.clone());
+        let blocks = (project.inline_modules.iter())
+            .map(|block| format!("{}.{}", block.file, block.name));
+        if !files.chain(blocks).any(|declared| in_module(&declared, module)) {
+            return Err(snapshot_error(format!("no module `{module}` in the project")));
+        }
+    }
+    let mut options = options.clone();
+    let located: HashMap<usize, Located> = (project.module.expects.iter())
+        .map(|expect| (expect.span.start, locate(project, expect.span)))
+        .collect();
+    options.selected = Some(
+        located
+            .iter()
+            .filter(|(_, test)| selection.includes(test))
+            .map(|(start, _)| *start)
+            .collect(),
+    );
+    options.snapshots =
+        functor_lang::snapshot::load(&project.module, &file_of, options.snapshots.update)
+            .map_err(snapshot_error)?;
+    let reports = if cover {
+        functor_lang::run_expects_covered(&project.module, &mut FunctorHost, &options)
+            .map(|(reports, covered)| (reports, Some(covered)))
+    } else {
+        functor_lang::run_expects_with(&project.module, &mut FunctorHost, &options)
+            .map(|reports| (reports, None))
+    };
+    let reports = reports.map_err(|failure| {
+        let (file, line, col) = project.sources.resolve(failure.error.span.start);
+        ExpectRunError {
+            file: file.path.clone(),
+            line,
+            col,
+            message: failure.error.message.clone(),
+        }
+    });
     // The `Ui.*` constructors register handlers in a thread-local the producer
     // drains per frame; nothing consumes them here, so drop whatever the
     // expects accumulated (also on the error path — the def load runs first).
     let _ = crate::functor_lang_prelude::take_ui_handlers();
-    let reports = reports?;
+    let (reports, covered) = reports?;
+    let coverage = covered.map(|covered| functor_lang::coverage::report(project, &covered));
+    let snapshots_written =
+        functor_lang::snapshot::save(&project.module, &reports, &options.snapshots, &file_of)
+            .map_err(snapshot_error)?;
 
     let cases = reports
         .iter()
@@ -133,6 +334,7 @@ pub fn run_expects_in(project: &functor_lang::project::Project) -> Result<Expect
         })
         .map(|report| {
             let (file, line, col) = project.sources.resolve(report.span.start);
+            let (_, end_line, end_col) = project.sources.resolve(report.span.end);
             let failure = match &report.outcome {
                 functor_lang::ExpectOutcome::Pass => None,
                 functor_lang::ExpectOutcome::Fail(Some(cmp)) => Some(format!(
@@ -149,18 +351,40 @@ pub fn run_expects_in(project: &functor_lang::project::Project) -> Result<Expect
                     "expect errored: {}",
                     project.sources.render(error.span.start, &error.message)
                 )),
+                // Carries the seed, so `functor test --seed` replays it.
+                functor_lang::ExpectOutcome::Falsified(counterexample) => {
+                    Some(format!("property {counterexample}"))
+                }
+                // A path per difference: which field of the frame moved.
+                functor_lang::ExpectOutcome::Changed(diff) => Some(diff.to_string()),
+            };
+            let Located { name, module, .. } = locate(project, report.span);
+            let compare = match &report.outcome {
+                functor_lang::ExpectOutcome::Fail(compare) => compare.clone(),
+                _ => None,
             };
             ExpectCase {
+                name,
+                module,
                 file: file.path.clone(),
                 line,
                 col,
+                end_line,
+                end_col,
                 source_line: nth_line(&file.src, line),
                 failure,
+                state: report.outcome.status().0,
+                compare,
+                duration: report.duration,
             }
         })
         .collect();
 
-    Ok(ExpectRun { cases })
+    Ok(ExpectRun {
+        cases,
+        snapshots_written,
+        coverage,
+    })
 }
 
 /// The 1-based `line`th line of `src`, for the diagnostic caret.
@@ -199,7 +423,7 @@ let ground = Scene.lit(sky, Scene.plane())
 let double = (n) => n * 2.0
 
 expect double(2.0) == 4.0
-expect List.length([Scene.cube(), Scene.sphere()]) == 2
+expect List.length([Scene.cube(), Scene.sphere()]) == 2.0
 "#,
             )],
         );
@@ -268,11 +492,11 @@ expect List.length([Scene.cube(), Scene.sphere()]) == 2
 
 unit px = Px
 
-expect List.length([90deg, 1.5rad]) == 2
-expect List.length([0.5s, 500ms, 250us, 2min, 1hr]) == 5
+expect List.length([90deg, 1.5rad]) == 2.0
+expect List.length([0.5s, 500ms, 250us, 2min, 1hr]) == 5.0
 expect 16px == Px(16.0)
 expect -2.5px == Px(-2.5)
-expect List.length([Scene.cube() |> Scene.rotateY(90deg)]) == 1
+expect List.length([Scene.cube() |> Scene.rotateY(90deg)]) == 1.0
 "#,
             )],
         );
@@ -281,6 +505,200 @@ expect List.length([Scene.cube() |> Scene.rotateY(90deg)]) == 1
         assert_eq!(run.failed(), 0, "{:?}", run.cases);
     }
 
+    /// Properties run under the engine host too, and a falsified one names
+    /// its inputs and the seed that replays them.
+    #[test]
+    fn a_falsified_property_reports_its_inputs_and_seed() {
+        let (_dir, entry) = project(
+            "game.fun",
+            &[(
+                "game.fun",
+                "let sky = Color.rgb(0.1, 0.2, 0.3)\n\
+                 property (n: int) => n < 10\n\
+                 property (xs: List<float>) => List.length(xs) >= 0.0\n",
+            )],
+        );
+        let run = run_project_expects(&entry).expect("project runs");
+        assert_eq!(run.total(), 2);
+        let message = run.cases[0].failure.as_deref().expect("falsified");
+        assert!(message.starts_with("property falsified by n = 10 (seed "), "{message}");
+        assert!(run.cases[1].failure.is_none());
+    }
+
+    /// A frame snapshot is recorded beside its module on the first run, and
+    /// a later `draw` change fails with the path to what moved — not just
+    /// "the frames differ" — until the run updates it.
+    #[test]
+    fn a_frame_snapshot_records_then_diffs_then_updates() {
+        let game = |x: &str| {
+            format!(
+                "let draw = () =>\n  Frame.create(\n    \
+                 Camera3D.lookAt(Vec3.make(0.0, 1.0, -3.0), Vec3.make(0.0, 0.0, 0.0)),\n    \
+                 Scene.cube() |> Scene.translate(Vec3.make({x}, 0.0, 0.0))\n  )\n\
+                 expectSnapshot frame = draw()\n\
+                 expectSnapshot score = {{ points: 3, name: \"p1\" }}\n"
+            )
+        };
+        let (dir, entry) = project("game.fun", &[("game.fun", &game("1.0"))]);
+        let stored = dir.path().join("__snapshots__").join("game.json");
+
+        let run = run_project_expects(&entry).expect("project runs");
+        assert_eq!((run.failed(), run.snapshots_written), (0, 2), "{:?}", run.cases);
+        let text = std::fs::read_to_string(&stored).expect("snapshots written");
+        assert!(text.contains("\"frame\""), "{text}");
+        let run = run_project_expects(&entry).expect("project runs");
+        assert_eq!((run.failed(), run.snapshots_written), (0, 0));
+
+        std::fs::write(&entry, game("2.0")).unwrap();
+        let run = run_project_expects(&entry).expect("project runs");
+        assert_eq!(run.failed(), 1);
+        let message = run.cases[0].failure.as_deref().expect("changed");
+        assert!(message.starts_with("snapshot `frame` changed"), "{message}");
+        assert!(message.contains("frame.scene."), "{message}");
+        assert!(message.contains("expected 1.0, got 2.0"), "{message}");
+        assert_eq!(std::fs::read_to_string(&stored).unwrap(), text);
+
+        let project = functor_lang::project::load_with_bundled_modules(
+            &entry,
+            &HashMap::new(),
+            &functor_prelude::bundled_modules(),
+        )
+        .unwrap_or_else(|err| panic!("loads: {}", err.message));
+        let mut update = functor_lang::ExpectOptions::default();
+        update.snapshots.update = true;
+        let run = run_expects_in(&project, &update).expect("project runs");
+        assert_eq!((run.failed(), run.snapshots_written), (0, 1));
+        assert_eq!(run_project_expects(&entry).expect("project runs").failed(), 0);
+    }
+
+    /// `--filter` matches the test's source text, `--module` a file (and
+    /// its inline blocks) or one block, and both together narrow further.
+    #[test]
+    fn a_selection_runs_only_the_matching_tests() {
+        let (_dir, entry) = project(
+            "game.fun",
+            &[
+                ("game.fun", "expect 1.0 + 1.0 == 2.0
+"),
+                (
+                    "helpers.fun",
+                    "let inc = (n) => n + 1.0
+expect inc(1.0) == 2.0
+                     module Grid {
+  expect inc(2.0) == 3.0
+}
+",
+                ),
+            ],
+        );
+        let project = functor_lang::project::load_with_bundled_modules(
+            &entry,
+            &HashMap::new(),
+            &functor_prelude::bundled_modules(),
+        )
+        .unwrap_or_else(|err| panic!("loads: {}", err.message));
+        let names = |selection: Selection| {
+            let options = functor_lang::ExpectOptions::default();
+            let run = run_selected(&project, &options, &selection).expect("project runs");
+            let cases = run.cases.into_iter();
+            cases.map(|case| format!("{} {}", case.module, case.name)).collect::<Vec<_>>()
+        };
+        let module = |name: &str| Selection {
+            module: Some(name.to_string()),
+            ..Selection::default()
+        };
+        assert_eq!(
+            names(module("Helpers")),
+            ["Helpers expect inc(1.0) == 2.0", "Helpers.Grid expect inc(2.0) == 3.0"]
+        );
+        assert_eq!(names(module("Helpers.Grid")).len(), 1);
+        let filtered = Selection {
+            filter: Some("inc(".to_string()),
+            ..module("Helpers.Grid")
+        };
+        assert_eq!(names(filtered), ["Helpers.Grid expect inc(2.0) == 3.0"]);
+        let filtered = Selection {
+            filter: Some("1.0 +".to_string()),
+            ..Selection::default()
+        };
+        assert_eq!(names(filtered), ["Game expect 1.0 + 1.0 == 2.0"]);
+        let options = functor_lang::ExpectOptions::default();
+        let err = run_selected(&project, &options, &module("Help")).expect_err("no such module");
+        assert_eq!(err.message, "no module `Help` in the project");
+    }
+
+    /// The watch loop re-runs a changed module and everything that uses it,
+    /// and everything when a file disappears.
+    #[test]
+    fn an_edit_affects_its_module_and_its_dependents() {
+        let (dir, entry) = project(
+            "game.fun",
+            &[
+                ("game.fun", "expect Helpers.inc(1.0) == 2.0
+"),
+                ("helpers.fun", "let inc = (n) => n + 1.0
+"),
+                ("other.fun", "expect 2.0 == 2.0
+"),
+            ],
+        );
+        let before = SourceSnapshot::read(&entry).expect("reads");
+        let helpers = dir.path().join("helpers.fun");
+        std::fs::write(&helpers, "let inc = (n) => n + 2.0
+").unwrap();
+        let after = SourceSnapshot::read(&entry).expect("reads");
+        assert_eq!(after.changed_since(&before), std::slice::from_ref(&helpers));
+        assert!(after.changed_since(&after).is_empty());
+
+        let project = functor_lang::project::load_with_bundled_modules(
+            &entry,
+            &HashMap::new(),
+            &functor_prelude::bundled_modules(),
+        )
+        .unwrap_or_else(|err| panic!("loads: {}", err.message));
+        let affected = affected_modules(&project, &[helpers]).expect("a project file");
+        assert_eq!(affected, HashSet::from(["Helpers".to_string(), "Game".to_string()]));
+        let selection = Selection {
+            modules: Some(affected),
+            ..Selection::default()
+        };
+        let options = functor_lang::ExpectOptions::default();
+        let run = run_selected(&project, &options, &selection).expect("project runs");
+        assert_eq!((run.total(), run.failed()), (1, 1));
+        assert!(affected_modules(&project, &[dir.path().join("gone.fun")]).is_none());
+    }
+
+    /// Coverage covers the project's own files only, and shows the
+    /// `update` arm no expect reaches.
+    #[test]
+    fn a_covered_run_reports_the_unreached_handler() {
+        let (_dir, entry) = project(
+            "game.fun",
+            &[(
+                "game.fun",
+                "type Msg = | Jump | Quit\n\
+                 let sky = Color.rgb(0.1, 0.2, 0.3)\n\
+                 let update = (msg, y) =>\n  match msg with\n  | Jump => y + 1.0\n  \
+                 | Quit => y\n\
+                 expect update(Jump, 0.0) == 1.0\n",
+            )],
+        );
+        let project = functor_lang::project::load_with_bundled_modules(
+            &entry,
+            &HashMap::new(),
+            &functor_prelude::bundled_modules(),
+        )
+        .unwrap_or_else(|err| panic!("loads: {}", err.message));
+        let options = functor_lang::ExpectOptions::default();
+        let run = run_covered(&project, &options, &Selection::default()).expect("project runs");
+        let files = run.coverage.expect("coverage");
+        assert_eq!(files.len(), 1, "{files:?}");
+        let branch = &files[0].branches[0];
+        assert_eq!((branch.line, branch.taken.clone()), (4, vec![true, false]));
+        assert!(files[0].lines.contains(&(6, false)));
+        assert!(run_project_expects(&entry).unwrap().coverage.is_none());
+    }
+
     #[test]
     fn a_project_with_no_expects_runs_clean() {
         let (_dir, entry) = project("game.fun", &[("game.fun", "let x = 1.0\n")]);
@@ -0,0 +1,353 @@
+//! Machine-readable `functor test` output (`--reporter junit|tap|json`): one
+//! result per evaluated test, rendered from an [`ExpectRun`] — JUnit XML for
+//! CI dashboards, TAP 13 for TAP harnesses, and ndjson for scripts.
+//!
+//! Every format carries the same facts per test: its name and module, its
+//! source span (file relative to the project root, 1-based line/col), its
+//! state (`pass` / `fail` / `error` / `unrunnable`), how long it took, the
+//! failure detail, and a failed comparison's operator and sides.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::functor_lang_test::{ExpectCase, ExpectRun};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reporter {
+    Junit,
+    Tap,
+    Json,
+}
+
+impl FromStr for Reporter {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Reporter, String> {
+        match name {
+            "junit" => Ok(Reporter::Junit),
+            "tap" => Ok(Reporter::Tap),
+            "json" => Ok(Reporter::Json),
+            other => Err(format!(
+                "unknown reporter `{other}` (expected junit, tap, or json)"
+            )),
+        }
+    }
+}
+
+impl Reporter {
+    /// The whole report for `run`; file paths are made relative to `root`.
+    pub fn render(self, run: &ExpectRun, root: &Path) -> String {
+        match self {
+            Reporter::Junit => junit(run, root),
+            Reporter::Tap => tap(run, root),
+            Reporter::Json => json(run, root),
+        }
+    }
+}
+
+fn relative(case: &ExpectCase, root: &Path) -> String {
+    let path = case.file.strip_prefix(root).unwrap_or(&case.file);
+    path.display().to_string()
+}
+
+/// Seconds, as JUnit's `time` attribute writes them.
+fn seconds(cases: &[&ExpectCase]) -> f64 {
+    cases
+        .iter()
+        .filter_map(|case| case.duration)
+        .map(|duration| duration.as_secs_f64())
+        .sum()
+}
+
+fn is_error(case: &ExpectCase) -> bool {
+    matches!(case.state, "error" | "unrunnable")
+}
+
+/// One `<testsuite>` per module, in the order modules first appear.
+fn junit(run: &ExpectRun, root: &Path) -> String {
+    let mut suites: Vec<(&str, Vec<&ExpectCase>)> = Vec::new();
+    for case in &run.cases {
+        match suites.iter_mut().find(|(module, _)| *module == case.module) {
+            Some((_, cases)) => cases.push(case),
+            None => suites.push((&case.module, vec![case])),
+        }
+    }
+    let all: Vec<&ExpectCase> = run.cases.iter().collect();
+    let errors = all.iter().filter(|case| is_error(case)).count();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuites name=\"functor test\" tests=\"{}\" failures=\"{}\" errors=\"{errors}\" \
+         time=\"{:.6}\">\n",
+        all.len(),
+        run.failed() - errors,
+        seconds(&all)
+    ));
+    for (module, cases) in &suites {
+        let errors = cases.iter().filter(|case| is_error(case)).count();
+        let failed = cases.iter().filter(|case| case.failure.is_some()).count();
+        out.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{errors}\" \
+             time=\"{:.6}\">\n",
+            xml(module),
+            cases.len(),
+            failed - errors,
+            seconds(cases)
+        ));
+        for case in cases {
+            out.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\" file=\"{}\" line=\"{}\"",
+                xml(&case.name),
+                xml(module),
+                xml(&relative(case, root)),
+                case.line
+            ));
+            if let Some(duration) = case.duration {
+                out.push_str(&format!(" time=\"{:.6}\"", duration.as_secs_f64()));
+            }
+            let Some(detail) = &case.failure else {
+                out.push_str("/>\n");
+                continue;
+            };
+            let element = if is_error(case) { "error" } else { "failure" };
+            let mut body = detail.clone();
+            if let Some(compare) = &case.compare {
+                body.push_str(&format!(
+                    "\noperator: {}\nleft: {}\nright: {}",
+                    compare.op, compare.lhs, compare.rhs
+                ));
+            }
+            out.push_str(&format!(
+                ">\n      <{element} message=\"{}\" type=\"{}\">{}</{element}>\n    </testcase>\n",
+                xml(detail.lines().next().unwrap_or_default()),
+                case.state,
+                xml(&body)
+            ));
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+/// Escaped for an XML attribute or text node.
+fn xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' | '\t' | '\r' => out.push(c),
+            // XML 1.0 cannot carry other control characters at all.
+            c if c.is_control() => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// TAP version 13: a plan, one `ok`/`not ok` line per test, and a YAML
+/// block under each failure.
+fn tap(run: &ExpectRun, root: &Path) -> String {
+    let mut out = format!("TAP version 13\n1..{}\n", run.total());
+    for (index, case) in run.cases.iter().enumerate() {
+        let status = if case.failure.is_some() {
+            "not ok"
+        } else {
+            "ok"
+        };
+        // `#` starts a TAP directive (`# SKIP`); a test named with one must
+        // not become one.
+        let description = format!("{}: {}", case.module, case.name).replace('#', "\\#");
+        out.push_str(&format!("{status} {} - {description}\n", index + 1));
+        let Some(detail) = &case.failure else {
+            continue;
+        };
+        // JSON strings are valid YAML scalars, so quoting is one call.
+        let quote = |text: &str| serde_json::Value::from(text).to_string();
+        out.push_str(&format!(
+            "  ---\n  message: {}\n  severity: {}\n  at:\n    file: {}\n    line: {}\n    \
+             column: {}\n",
+            quote(detail),
+            case.state,
+            quote(&relative(case, root)),
+            case.line,
+            case.col
+        ));
+        if let Some(duration) = case.duration {
+            out.push_str(&format!(
+                "  duration_ms: {:.3}\n",
+                duration.as_secs_f64() * 1e3
+            ));
+        }
+        if let Some(compare) = &case.compare {
+            out.push_str(&format!(
+                "  compare:\n    operator: {}\n    left: {}\n    right: {}\n",
+                quote(compare.op),
+                quote(&compare.lhs),
+                quote(&compare.rhs)
+            ));
+        }
+        out.push_str("  ...\n");
+    }
+    out
+}
+
+/// ndjson: one compact object per test.
+fn json(run: &ExpectRun, root: &Path) -> String {
+    let mut out = String::new();
+    for case in &run.cases {
+        let result = serde_json::json!({
+            "name": case.name,
+            "module": case.module,
+            "file": relative(case, root),
+            "span": {
+                "start": { "line": case.line, "col": case.col },
+                "end": { "line": case.end_line, "col": case.end_col },
+            },
+            "state": case.state,
+            "durationMs": case.duration.map(|duration| duration.as_secs_f64() * 1e3),
+            "detail": case.failure,
+            "compare": case.compare.as_ref().map(|compare| serde_json::json!({
+                "op": compare.op,
+                "left": compare.lhs,
+                "right": compare.rhs,
+            })),
+        });
+        out.push_str(&result.to_string());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn case(name: &str, module: &str, state: &'static str) -> ExpectCase {
+        let failed = state != "pass";
+        ExpectCase {
+            name: name.to_string(),
+            module: module.to_string(),
+            file: PathBuf::from("/game/helpers.fun"),
+            line: 3,
+            col: 1,
+            end_line: 3,
+            end_col: 24,
+            source_line: None,
+            failure: failed.then(|| format!("expect failed: {name} <broke>")),
+            state,
+            compare: (state == "fail").then(|| functor_lang::FailedCompare {
+                op: "==",
+                lhs: "4.0".to_string(),
+                rhs: "5.0".to_string(),
+            }),
+            duration: Some(Duration::from_micros(1500)),
+        }
+    }
+
+    fn run() -> ExpectRun {
+        ExpectRun {
+            cases: vec![
+                case("expect inc(1.0) == 2.0", "Helpers", "pass"),
+                case("expect inc(3.0) == 5.0", "Helpers", "fail"),
+                case("expect #tag == 1", "Helpers.Grid", "error"),
+            ],
+            snapshots_written: 0,
+            coverage: None,
+        }
+    }
+
+    #[test]
+    fn junit_groups_by_module_and_escapes() {
+        let xml = Reporter::Junit.render(&run(), Path::new("/game"));
+        assert!(
+            xml.contains(
+                "<testsuites name=\"functor test\" tests=\"3\" failures=\"1\" errors=\"1\" \
+                 time=\"0.004500\">"
+            ),
+            "{xml}"
+        );
+        assert!(
+            xml.contains(
+                "<testsuite name=\"Helpers\" tests=\"2\" failures=\"1\" errors=\"0\" \
+                 time=\"0.003000\">"
+            ),
+            "{xml}"
+        );
+        assert!(
+            xml.contains(
+                "<testcase name=\"expect inc(1.0) == 2.0\" classname=\"Helpers\" \
+                 file=\"helpers.fun\" line=\"3\" time=\"0.001500\"/>"
+            ),
+            "{xml}"
+        );
+        assert!(
+            xml.contains(
+                "<failure message=\"expect failed: expect inc(3.0) == 5.0 &lt;broke&gt;\" \
+                 type=\"fail\">"
+            ),
+            "{xml}"
+        );
+        assert!(
+            xml.contains("\noperator: ==\nleft: 4.0\nright: 5.0</failure>"),
+            "{xml}"
+        );
+        assert!(xml.contains("<error message="), "{xml}");
+        assert!(xml.ends_with("</testsuites>\n"));
+    }
+
+    #[test]
+    fn tap_plans_every_test_and_describes_failures_in_yaml() {
+        let tap = Reporter::Tap.render(&run(), Path::new("/game"));
+        let lines: Vec<&str> = tap.lines().collect();
+        assert_eq!(
+            lines[..3],
+            [
+                "TAP version 13",
+                "1..3",
+                "ok 1 - Helpers: expect inc(1.0) == 2.0"
+            ]
+        );
+        assert!(
+            tap.contains("not ok 2 - Helpers: expect inc(3.0) == 5.0\n  ---\n"),
+            "{tap}"
+        );
+        assert!(
+            tap.contains("  at:\n    file: \"helpers.fun\"\n    line: 3\n"),
+            "{tap}"
+        );
+        assert!(
+            tap.contains("    operator: \"==\"\n    left: \"4.0\"\n"),
+            "{tap}"
+        );
+        assert!(
+            tap.contains("not ok 3 - Helpers.Grid: expect \\#tag == 1\n"),
+            "{tap}"
+        );
+        assert!(tap.contains("  severity: error\n"), "{tap}");
+    }
+
+    #[test]
+    fn json_is_one_object_per_test() {
+        let text = Reporter::Json.render(&run(), Path::new("/game"));
+        let results: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("one object per line"))
+            .collect();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["state"], "pass");
+        assert!(results[0]["compare"].is_null());
+        assert_eq!(results[1]["file"], "helpers.fun");
+        assert_eq!(results[1]["span"]["end"]["col"], 24);
+        assert_eq!(results[1]["durationMs"], 1.5);
+        assert_eq!(results[1]["compare"]["right"], "5.0");
+        assert_eq!(results[2]["module"], "Helpers.Grid");
+        assert_eq!("tap".parse(), Ok(Reporter::Tap));
+        assert!("xml".parse::<Reporter>().is_err());
+    }
+}
@@ -142,6 +142,21 @@ impl HeightmapMesh {
     }
 }
 
+/// The grid mesh for `(rows, cols)` displaced by `heights`, as plain data —
+/// the same vertices [`HeightmapMesh`] uploads, for the software renderer.
+pub(crate) fn heightmap_mesh_data(
+    rows: usize,
+    cols: usize,
+    heights: &[f32],
+) -> (Vec<VertexPositionTexture>, Vec<u32>) {
+    let rows = rows.max(2);
+    let cols = cols.max(2);
+    let indices = build_indices(rows, cols);
+    let mut vertices = Vec::with_capacity(rows * cols);
+    fill_vertices(&mut vertices, rows, cols, heights, &indices);
+    (vertices, indices)
+}
+
 /// Triangle indices for a `rows × cols` grid — a function of the grid size only,
 /// so it's built once per mesh and never re-uploaded.
 fn build_indices(rows: usize, cols: usize) -> Vec<u32> {
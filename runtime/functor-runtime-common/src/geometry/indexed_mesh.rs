@@ -1,3 +1,5 @@
+use std::cell::Ref;
+
 use glow::{Buffer, HasContext, VertexArray};
 
 use crate::{
@@ -7,6 +9,10 @@ use crate::{
 
 use super::Geometry;
 
+/// Borrowed vertices and indices of a never-uploaded mesh
+/// ([`IndexedMesh::cpu_data`]).
+pub(crate) type CpuMeshData<'a, T> = (Ref<'a, [T]>, Ref<'a, [u32]>);
+
 pub struct IndexedMesh<T: Vertex> {
     ora: RuntimeRenderableAsset<IndexedMeshData<T>>,
 }
@@ -42,6 +48,15 @@ impl<T: Vertex> IndexedMesh<T> {
         IndexedMesh { ora }
     }
 
+    /// The CPU-side vertices and indices, while the mesh has never been
+    /// uploaded — what the software renderer rasterizes.
+    pub(crate) fn cpu_data(&self) -> Option<CpuMeshData<'_, T>> {
+        let (data, _) = self.ora.dehydrated()?;
+        Some(Ref::map_split(data, |data| {
+            (data.vertices.as_slice(), data.indices.as_slice())
+        }))
+    }
+
     /// Hydrate (if needed) and hand back the mesh's GL buffer handles.
     pub(crate) fn buffers(&self, gl: &glow::Context) -> MeshBufferHandles {
         let data = self.ora.get(gl);
@@ -139,6 +139,14 @@ impl PolygonMesh {
     }
 }
 
+/// The fan mesh for `points`, as plain data — the same vertices
+/// [`PolygonMesh`] uploads, for the software renderer.
+pub(crate) fn polygon_mesh_data(points: &[[f32; 2]]) -> (Vec<VertexPositionTexture>, Vec<u32>) {
+    let mut vertices = Vec::with_capacity(points.len());
+    fill_vertices(&mut vertices, points.len(), points);
+    (vertices, build_fan_indices(points.len()))
+}
+
 /// Fan triangles from vertex 0 — a function of the point count only.
 fn build_fan_indices(count: usize) -> Vec<u32> {
     let mut indices = Vec::with_capacity((count - 2) * 3);
@@ -1101,7 +1101,7 @@ fn gamepad_value(pad: &GamepadSnapshot) -> functor_lang::Value {
 
 fn touch_value(touch: &TouchSnapshot) -> functor_lang::Value {
     let points = |points: &[TouchPoint]| {
-        functor_lang::Value::List(std::rc::Rc::new(
+        functor_lang::Value::List(
             points
                 .iter()
                 .map(|point| {
@@ -1112,7 +1112,7 @@ fn touch_value(touch: &TouchSnapshot) -> functor_lang::Value {
                     ])
                 })
                 .collect(),
-        ))
+        )
     };
     record([
         ("touches", points(&touch.touches)),
@@ -1126,11 +1126,11 @@ fn touch_value(touch: &TouchSnapshot) -> functor_lang::Value {
 /// `sampledInput` hook.
 pub fn input_snapshot_value(snapshot: &InputSnapshot) -> functor_lang::Value {
     let key_values = |keys: &[Key]| {
-        functor_lang::Value::List(std::rc::Rc::new(
+        functor_lang::Value::List(
             keys.iter()
                 .filter_map(|key| key_input_value(*key as i32))
                 .collect(),
-        ))
+        )
     };
     let mouse_buttons_value = |buttons: MouseButtons| {
         record([
@@ -16,6 +16,7 @@ use serde::Serialize;
 
 use crate::animation::{Animation, AnimationChannel, AnimationProperty, AnimationValue, Keyframe};
 use crate::model::{build_skeleton_from_skin, document_hierarchy, HierarchyNode, Skeleton};
+use crate::pbr::MaterialFeatures;
 
 /// An axis-aligned bounding box in model space.
 #[derive(Clone, Copy, Debug)]
@@ -90,6 +91,9 @@ pub struct PrimitiveReport {
     pub weight_count: usize,
     /// True when the primitive carries both JOINTS_0 and WEIGHTS_0.
     pub has_skinning: bool,
+    /// Which parts of the metallic-roughness model the primitive's material
+    /// uses (textures, emissive, alpha mode, double-sidedness).
+    pub material: MaterialFeatures,
 }
 
 /// A single animation's summary.
@@ -445,6 +449,7 @@ fn inspect_node(
                 joint_count: joints.len(),
                 weight_count: weights.len(),
                 has_skinning,
+                material: MaterialFeatures::from_gltf(&primitive.material()),
             });
         }
     }
@@ -737,6 +742,14 @@ mod tests {
             "reported count follows populated sparse joints"
         );
 
+        // Every primitive reports its material; the glove is authored with
+        // glTF's opaque default alpha.
+        assert!(!report.primitives.is_empty());
+        for primitive in &report.primitives {
+            assert_eq!(primitive.material.alpha_mode, crate::pbr::AlphaMode::Opaque);
+            assert!(!primitive.material.summary().is_empty());
+        }
+
         // `translation_nonzero` is consistent with the reported translation, and
         // a node with no mesh has an empty (null-serializing) bbox.
         for n in &report.nodes {
@@ -619,7 +619,7 @@ mod tests {
         assert_eq!(json, serde_json::json!(1.0));
         assert_eq!(remaining, 16 - "1.0".len());
 
-        let big = Value::List(Rc::new((0..1000).map(|i| Value::Number(i as f64)).collect()));
+        let big = Value::List((0..1000).map(|i| Value::Number(i as f64)).collect());
         let (json, truncated) = budgeted_value_json(&big, &mut remaining);
         assert!(truncated);
         assert_eq!(json, serde_json::json!({ "$truncated": "trace budget" }));
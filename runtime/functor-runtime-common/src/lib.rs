@@ -57,6 +57,7 @@ pub mod composite;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod debug_http;
 pub mod debug_protocol;
+pub mod environment;
 pub mod events;
 pub mod fog;
 pub mod gpu_counters;
@@ -73,15 +74,22 @@ mod light;
 pub mod manifest;
 pub mod material;
 pub mod math;
+pub mod functor_lang_debug;
 pub mod functor_lang_game_embedded;
 pub mod functor_lang_prelude;
 pub mod host_registry;
 pub mod functor_lang_producer;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod functor_lang_repl;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod functor_lang_test;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod functor_lang_test_report;
 pub mod model;
 pub mod net;
+pub mod pbr;
 pub mod physics;
+pub mod post;
 pub mod protocol;
 pub mod render;
 mod render_context;
@@ -93,6 +101,7 @@ pub mod shadow;
 pub mod skybox;
 mod shader_program;
 mod sprite2d;
+pub mod software_renderer;
 // The built-in font is an implementation detail of `Sprite.text`, not public
 // API — nothing outside this crate should depend on its atlas layout.
 mod sprite_font;
@@ -114,6 +123,7 @@ pub use frame_time::*;
 pub use game_clock::GameClock;
 pub use input::*;
 pub use light::*;
+pub use post::PostEffect;
 pub use render_context::*;
 pub use render_target::RenderTargetDescriptor;
 pub use renderer::*;
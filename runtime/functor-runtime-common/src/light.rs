@@ -2,7 +2,9 @@ use cgmath::{Matrix4, SquareMatrix, Vector3};
 use glow::HasContext;
 use serde::{Deserialize, Serialize};
 
+use crate::environment::ENVIRONMENT_TEXTURE_UNIT;
 use crate::shader_program::{ShaderProgram, UniformLocation};
+use crate::shadow::MAX_SHADOW_TILES;
 use crate::RenderContext;
 
 /// A light source. Pure data in the `Frame`, so lights serialize for `/scene`
@@ -13,17 +15,20 @@ pub enum Light {
     /// surface regardless of orientation.
     Ambient { color: [f32; 3] },
     /// A distant "sun": parallel rays travelling along `direction`. `intensity`
-    /// scales `color`. `casts_shadows` opts it into rendering a shadow map.
+    /// scales `color`. `casts_shadows` opts it into rendering a shadow map —
+    /// one fixed box, or `shadow.cascades` camera-fitted splits.
     Directional {
         direction: [f32; 3],
         color: [f32; 3],
         intensity: f32,
         #[serde(default)]
         casts_shadows: bool,
+        #[serde(default, skip_serializing_if = "ShadowSettings::is_default")]
+        shadow: ShadowSettings,
     },
     /// An omnidirectional point light at `position`, fading to nothing by
-    /// `range` (world units). (`casts_shadows` is carried for the API, but point
-    /// shadows — a cube map — are not implemented yet.)
+    /// `range` (world units). `casts_shadows` renders a six-face cube shadow
+    /// map out to `range`.
     Point {
         position: [f32; 3],
         color: [f32; 3],
@@ -31,6 +36,8 @@ pub enum Light {
         range: f32,
         #[serde(default)]
         casts_shadows: bool,
+        #[serde(default, skip_serializing_if = "ShadowSettings::is_default")]
+        shadow: ShadowSettings,
     },
     /// A cone of light from `position` aimed along `direction`, with a soft edge
     /// at `cone_angle` (radians from the axis) and distance falloff to `range`.
@@ -43,9 +50,43 @@ pub enum Light {
         cone_angle: f32,
         #[serde(default)]
         casts_shadows: bool,
+        #[serde(default, skip_serializing_if = "ShadowSettings::is_default")]
+        shadow: ShadowSettings,
     },
 }
 
+/// How a casting light's shadow map is laid out. Omitted from the wire while
+/// it is the default, so frames that never tune a shadow keep their shape.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ShadowSettings {
+    /// Texels per side of each shadow view (the single map, one cascade, or
+    /// one cube face). Clamped so the whole atlas fits
+    /// [`crate::shadow::MAX_SHADOW_ATLAS_SIZE`].
+    pub resolution: u32,
+    /// Directional only: how many camera-fitted cascades split the view out to
+    /// `distance`. `1` keeps the fixed box around the origin.
+    pub cascades: u32,
+    /// Directional only: how far from the camera (world units) the cascades
+    /// reach; beyond it reads as lit.
+    pub distance: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> ShadowSettings {
+        ShadowSettings {
+            resolution: 2048,
+            cascades: 1,
+            distance: 50.0,
+        }
+    }
+}
+
+impl ShadowSettings {
+    pub fn is_default(&self) -> bool {
+        *self == ShadowSettings::default()
+    }
+}
+
 impl Light {
     pub fn ambient(r: f32, g: f32, b: f32) -> Light {
         Light::Ambient { color: [r, g, b] }
@@ -65,6 +106,7 @@ impl Light {
             color: [r, g, b],
             intensity,
             casts_shadows: false,
+            shadow: ShadowSettings::default(),
         }
     }
 
@@ -75,6 +117,7 @@ impl Light {
             intensity,
             range,
             casts_shadows: false,
+            shadow: ShadowSettings::default(),
         }
     }
 
@@ -101,56 +144,32 @@ impl Light {
             range,
             cone_angle,
             casts_shadows: false,
+            shadow: ShadowSettings::default(),
         }
     }
 
     /// Opt this light into casting shadows (renders a shadow map). No-op for
     /// ambient lights.
-    pub fn cast_shadows(self) -> Light {
-        match self {
-            Light::Directional {
-                direction,
-                color,
-                intensity,
-                ..
-            } => Light::Directional {
-                direction,
-                color,
-                intensity,
-                casts_shadows: true,
-            },
-            Light::Point {
-                position,
-                color,
-                intensity,
-                range,
-                ..
-            } => Light::Point {
-                position,
-                color,
-                intensity,
-                range,
-                casts_shadows: true,
-            },
-            Light::Spot {
-                position,
-                direction,
-                color,
-                intensity,
-                range,
-                cone_angle,
-                ..
-            } => Light::Spot {
-                position,
-                direction,
-                color,
-                intensity,
-                range,
-                cone_angle,
-                casts_shadows: true,
-            },
-            Light::Ambient { .. } => self,
+    pub fn cast_shadows(mut self) -> Light {
+        match &mut self {
+            Light::Directional { casts_shadows, .. }
+            | Light::Point { casts_shadows, .. }
+            | Light::Spot { casts_shadows, .. } => *casts_shadows = true,
+            Light::Ambient { .. } => {}
+        }
+        self
+    }
+
+    /// Cast shadows laid out by `settings` (resolution, cascades). No-op for
+    /// ambient lights.
+    pub fn cast_shadows_with(mut self, settings: ShadowSettings) -> Light {
+        match &mut self {
+            Light::Directional { shadow, .. }
+            | Light::Point { shadow, .. }
+            | Light::Spot { shadow, .. } => *shadow = settings,
+            Light::Ambient { .. } => {}
         }
+        self.cast_shadows()
     }
 
     /// Whether this light is opted into casting shadows.
@@ -162,6 +181,16 @@ impl Light {
             Light::Ambient { .. } => false,
         }
     }
+
+    /// This light's shadow layout (the default for ambient lights).
+    pub fn shadow_settings(&self) -> ShadowSettings {
+        match self {
+            Light::Directional { shadow, .. }
+            | Light::Point { shadow, .. }
+            | Light::Spot { shadow, .. } => *shadow,
+            Light::Ambient { .. } => ShadowSettings::default(),
+        }
+    }
 }
 
 /// The maximum lights a single `LitMaterial` shader evaluates per draw. Beyond
@@ -262,8 +291,11 @@ pub fn pack_lights(lights: &[Light]) -> LightUniforms {
 /// `SkinnedMaterial` concat it so they shade identically. Declares the packed
 /// light uniforms, the shadow-map uniforms, and `accumulateLights`, which sums
 /// the frame's diffuse + specular light at a surface point (shadowing the
-/// casting light's contribution only). `__MAX_LIGHTS__` is substituted by
-/// [`lighting_glsl`] so the GLSL array size matches the Rust cap.
+/// casting light's contribution only, and adding the image-based light when
+/// the pass has an environment), plus `shadePbr`, the metallic-roughness BRDF
+/// the `PbrMaterial` shades with. `__MAX_LIGHTS__` is substituted by
+/// [`lighting_glsl`] so the GLSL array size matches the Rust cap (likewise
+/// `__MAX_SHADOW_TILES__`).
 const LIGHTING_GLSL_TEMPLATE: &str = r#"
         #define MAX_LIGHTS __MAX_LIGHTS__
 
@@ -280,40 +312,125 @@ const LIGHTING_GLSL_TEMPLATE: &str = r#"
         const float shininess = 32.0;
         const float specularStrength = 0.4;
 
-        // Shadow map of the single casting light (directional or spot for now).
-        // `shadowLightIndex` is which light it belongs to, so only that light's
-        // contribution is shadowed.
+        // Shadow atlas of the single casting light. Each of its views (the one
+        // map, a cascade, a cube face) has a light matrix and a uv rectangle in
+        // the atlas. `shadowLightIndex` is which light it belongs to, so only
+        // that light's contribution is shadowed.
+        #define MAX_SHADOW_TILES __MAX_SHADOW_TILES__
         uniform sampler2D shadowMap;
-        uniform mat4 lightSpaceMatrix;
+        uniform mat4 lightSpaceMatrix[MAX_SHADOW_TILES];
+        uniform vec4 shadowTileRect[MAX_SHADOW_TILES]; // xy origin, zw size (uv)
+        uniform int shadowTileCount;
         uniform int shadowEnabled;
         uniform int shadowLightIndex;
 
+        // Image-based light — the frame environment or the draw's nearest
+        // reflection probe: a prefiltered specular cube with one GGX roughness
+        // per mip, and nine SH coefficients of diffuse irradiance.
+        uniform samplerCube environmentSpecular;
+        uniform vec3 environmentIrradiance[9];
+        uniform float environmentMaxLod;
+        uniform float environmentIntensity;
+        uniform int environmentEnabled;
+        // The GGX roughness whose lobe roughly matches the Blinn-Phong
+        // highlight above (shininess 32).
+        const float environmentRoughness = 0.5;
+
+        vec3 irradianceAt(vec3 n) {
+            vec3 e = environmentIrradiance[0] * 0.282095
+                + environmentIrradiance[1] * (0.488603 * n.y)
+                + environmentIrradiance[2] * (0.488603 * n.z)
+                + environmentIrradiance[3] * (0.488603 * n.x)
+                + environmentIrradiance[4] * (1.092548 * n.x * n.y)
+                + environmentIrradiance[5] * (1.092548 * n.y * n.z)
+                + environmentIrradiance[6] * (0.315392 * (3.0 * n.z * n.z - 1.0))
+                + environmentIrradiance[7] * (1.092548 * n.x * n.z)
+                + environmentIrradiance[8] * (0.546274 * (n.x * n.x - n.y * n.y));
+            return max(e, vec3(0.0));
+        }
+
+        // The environment's diffuse light (the caller multiplies it by albedo,
+        // like an ambient light) and its reflection, weighted by Schlick's
+        // Fresnel for a dielectric (F0 = 0.04) so it strengthens at grazing
+        // angles.
+        void environmentLight(
+            vec3 n, vec3 viewDir, float roughness, out vec3 diffuse, out vec3 specular
+        ) {
+            float ndotv = max(dot(n, viewDir), 0.0);
+            float fresnel = 0.04 + (max(1.0 - roughness, 0.04) - 0.04) * pow(1.0 - ndotv, 5.0);
+            vec3 r = reflect(-viewDir, n);
+            diffuse = irradianceAt(n) * environmentIntensity;
+            specular = textureLod(environmentSpecular, r, roughness * environmentMaxLod).rgb
+                * fresnel * environmentIntensity;
+        }
+
         // Inverse of the depth material's packDepth (RGBA8 -> [0,1] depth).
         float unpackDepth(vec4 rgba) {
             return dot(rgba, vec4(1.0, 1.0 / 255.0, 1.0 / 65025.0, 1.0 / 16581375.0));
         }
 
-        // 0 = fully lit, 1 = fully shadowed. 3x3 PCF; out-of-frustum reads as lit.
-        // Works for ortho (directional) and perspective (spot) light matrices —
-        // the divide by w handles the perspective case.
+        // 0 = fully lit, 1 = fully shadowed. The first view whose frustum holds
+        // the point wins (cascades are nearest first; cube faces overlap only
+        // at their margins); outside every view reads as lit. Works for ortho
+        // (directional) and perspective (spot, point) matrices — the divide by
+        // w handles the perspective case. 3x3 percentage-closer filtering,
+        // with the taps clamped to the view's own tile.
         float sampleShadow(vec3 worldPos, float ndotl) {
-            vec4 lightSpacePos = lightSpaceMatrix * vec4(worldPos, 1.0);
-            vec3 proj = lightSpacePos.xyz / lightSpacePos.w;
-            proj = proj * 0.5 + 0.5;
-            if (proj.z > 1.0 || proj.x < 0.0 || proj.x > 1.0 || proj.y < 0.0 || proj.y > 1.0) {
-                return 0.0;
-            }
-            // Slope-scaled bias to fight shadow acne on grazing surfaces.
-            float bias = max(0.0015 * (1.0 - ndotl), 0.0008);
-            vec2 texelSize = 1.0 / vec2(textureSize(shadowMap, 0));
-            float shadow = 0.0;
-            for (int x = -1; x <= 1; x++) {
-                for (int y = -1; y <= 1; y++) {
-                    float closest = unpackDepth(texture(shadowMap, proj.xy + vec2(x, y) * texelSize));
-                    shadow += (proj.z - bias > closest) ? 1.0 : 0.0;
+            for (int i = 0; i < shadowTileCount; i++) {
+                vec4 lightSpacePos = lightSpaceMatrix[i] * vec4(worldPos, 1.0);
+                if (lightSpacePos.w <= 0.0) {
+                    continue;
                 }
+                vec3 proj = lightSpacePos.xyz / lightSpacePos.w;
+                proj = proj * 0.5 + 0.5;
+                if (proj.z > 1.0 || proj.x < 0.0 || proj.x > 1.0 || proj.y < 0.0 || proj.y > 1.0) {
+                    continue;
+                }
+                // Slope-scaled bias to fight shadow acne on grazing surfaces.
+                float bias = max(0.0015 * (1.0 - ndotl), 0.0008);
+                vec2 texelSize = 1.0 / vec2(textureSize(shadowMap, 0));
+                vec4 rect = shadowTileRect[i];
+                vec2 uv = rect.xy + proj.xy * rect.zw;
+                vec2 lo = rect.xy + 0.5 * texelSize;
+                vec2 hi = rect.xy + rect.zw - 0.5 * texelSize;
+                float shadow = 0.0;
+                for (int x = -1; x <= 1; x++) {
+                    for (int y = -1; y <= 1; y++) {
+                        vec2 tap = clamp(uv + vec2(x, y) * texelSize, lo, hi);
+                        float closest = unpackDepth(texture(shadowMap, tap));
+                        shadow += (proj.z - bias > closest) ? 1.0 : 0.0;
+                    }
+                }
+                return shadow / 9.0;
+            }
+            return 0.0;
+        }
+
+        // The unit vector from a surface point toward (non-ambient) light
+        // `i`, and the light's attenuation there.
+        float lightToward(int i, vec3 worldPos, out vec3 l) {
+            int t = lightType[i];
+            if (t == 1) {
+                l = -normalize(lightDirection[i]);
+                return 1.0;
+            }
+            // Point (t == 2) or spot (t == 3): both attenuate by distance.
+            vec3 toLight = lightPosition[i] - worldPos;
+            float dist = length(toLight);
+            l = toLight / max(dist, 1e-4);
+
+            float range = max(lightRange[i], 1e-4);
+            float a = clamp(1.0 - (dist * dist) / (range * range), 0.0, 1.0);
+            float atten = a * a;
+
+            if (t == 3) {
+                float cosAngle = dot(-l, normalize(lightDirection[i]));
+                // Soft edge over a small band inside the cone.
+                float outer = lightConeCos[i];
+                float inner = mix(1.0, outer, 0.85);
+                atten *= clamp((cosAngle - outer) / max(inner - outer, 1e-4), 0.0, 1.0);
             }
-            return shadow / 9.0;
+            return atten;
         }
 
         // Sum the frame's lights at a surface point. Diffuse and specular are
@@ -325,36 +442,13 @@ const LIGHTING_GLSL_TEMPLATE: &str = r#"
             specularLight = vec3(0.0);
 
             for (int i = 0; i < numLights; i++) {
-                int t = lightType[i];
-                if (t == 0) {
+                if (lightType[i] == 0) {
                     diffuseLight += lightColor[i]; // ambient: never shadowed
                     continue;
                 }
 
-                // Unit vector toward the light, and an attenuation factor.
                 vec3 l;
-                float atten = 1.0;
-                if (t == 1) {
-                    l = -normalize(lightDirection[i]);
-                } else {
-                    // Point (t == 2) or spot (t == 3): both attenuate by distance.
-                    vec3 toLight = lightPosition[i] - worldPos;
-                    float dist = length(toLight);
-                    l = toLight / max(dist, 1e-4);
-
-                    float range = max(lightRange[i], 1e-4);
-                    float a = clamp(1.0 - (dist * dist) / (range * range), 0.0, 1.0);
-                    atten = a * a;
-
-                    if (t == 3) {
-                        float cosAngle = dot(-l, normalize(lightDirection[i]));
-                        // Soft edge over a small band inside the cone.
-                        float outer = lightConeCos[i];
-                        float inner = mix(1.0, outer, 0.85);
-                        atten *= clamp((cosAngle - outer) / max(inner - outer, 1e-4), 0.0, 1.0);
-                    }
-                }
-
+                float atten = lightToward(i, worldPos, l);
                 float ndotl = max(dot(n, l), 0.0);
                 vec3 diffuse = lightColor[i] * ndotl * atten;
                 // Blinn-Phong specular, only where the surface faces the light.
@@ -372,12 +466,98 @@ const LIGHTING_GLSL_TEMPLATE: &str = r#"
                 diffuseLight += diffuse;
                 specularLight += specular;
             }
+
+            if (environmentEnabled == 1) {
+                vec3 environmentDiffuse;
+                vec3 environmentReflection;
+                environmentLight(
+                    n, viewDir, environmentRoughness, environmentDiffuse, environmentReflection
+                );
+                diffuseLight += environmentDiffuse;
+                specularLight += environmentReflection;
+            }
+        }
+
+        // The glTF metallic-roughness BRDF at a surface point: Lambert diffuse
+        // plus a GGX / Smith / Schlick specular lobe per light, and — when the
+        // pass has an environment — its irradiance and prefiltered reflection,
+        // split-sum weighted with an analytic fit of the BRDF integral (Karis).
+        // Ambient lights act as a uniform environment. `occlusion` dims only
+        // the indirect (ambient and image-based) light. Returns the outgoing
+        // radiance; emissive is the caller's to add.
+        const float PI = 3.14159265;
+
+        vec2 environmentBrdf(float ndotv, float roughness) {
+            vec4 c0 = vec4(-1.0, -0.0275, -0.572, 0.022);
+            vec4 c1 = vec4(1.0, 0.0425, 1.04, -0.04);
+            vec4 r = roughness * c0 + c1;
+            float a004 = min(r.x * r.x, exp2(-9.28 * ndotv)) * r.x + r.y;
+            return vec2(-1.04, 1.04) * a004 + r.zw;
+        }
+
+        vec3 shadePbr(
+            vec3 n, vec3 worldPos, vec3 albedo, float metallic, float roughness, float occlusion
+        ) {
+            vec3 viewDir = normalize(viewPos - worldPos);
+            float ndotv = max(dot(n, viewDir), 1e-4);
+            roughness = clamp(roughness, 0.04, 1.0);
+            metallic = clamp(metallic, 0.0, 1.0);
+            vec3 f0 = mix(vec3(0.04), albedo, metallic);
+            vec3 diffuseColor = albedo * (1.0 - metallic);
+            float alpha = roughness * roughness;
+            float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+            vec2 brdf = environmentBrdf(ndotv, roughness);
+            vec3 indirectSpecular = f0 * brdf.x + brdf.y;
+
+            vec3 color = vec3(0.0);
+            for (int i = 0; i < numLights; i++) {
+                if (lightType[i] == 0) {
+                    color += (diffuseColor + indirectSpecular) * lightColor[i] * occlusion;
+                    continue;
+                }
+
+                vec3 l;
+                float atten = lightToward(i, worldPos, l);
+                float ndotl = max(dot(n, l), 0.0);
+                if (ndotl <= 0.0) {
+                    continue;
+                }
+                vec3 h = normalize(l + viewDir);
+                float ndoth = max(dot(n, h), 0.0);
+                float vdoth = max(dot(viewDir, h), 0.0);
+                float d = ndoth * ndoth * (alpha * alpha - 1.0) + 1.0;
+                float distribution = alpha * alpha / (PI * d * d);
+                float visibility = 1.0 / (4.0 * (ndotl * (1.0 - k) + k) * (ndotv * (1.0 - k) + k));
+                vec3 fresnel = f0 + (1.0 - f0) * pow(1.0 - vdoth, 5.0);
+                // PI-scaled so a white dielectric under a unit light matches
+                // the Lambert (albedo * ndotl) of the lit materials.
+                vec3 brdfLight = (1.0 - fresnel) * diffuseColor
+                    + PI * distribution * visibility * fresnel;
+                vec3 radiance = brdfLight * lightColor[i] * ndotl * atten;
+
+                if (shadowEnabled == 1 && i == shadowLightIndex) {
+                    radiance *= 1.0 - sampleShadow(worldPos, ndotl);
+                }
+                color += radiance;
+            }
+
+            if (environmentEnabled == 1) {
+                vec3 r = reflect(-viewDir, n);
+                vec3 reflection =
+                    textureLod(environmentSpecular, r, roughness * environmentMaxLod).rgb;
+                color += (irradianceAt(n) * diffuseColor + reflection * indirectSpecular)
+                    * environmentIntensity * occlusion;
+            }
+            return color;
         }
 "#;
 
-/// [`LIGHTING_GLSL_TEMPLATE`] with the `MAX_LIGHTS` cap substituted in.
+/// [`LIGHTING_GLSL_TEMPLATE`] with the `MAX_LIGHTS` and `MAX_SHADOW_TILES`
+/// caps substituted in.
 pub fn lighting_glsl() -> String {
-    LIGHTING_GLSL_TEMPLATE.replace("__MAX_LIGHTS__", &MAX_LIGHTS.to_string())
+    LIGHTING_GLSL_TEMPLATE
+        .replace("__MAX_LIGHTS__", &MAX_LIGHTS.to_string())
+        .replace("__MAX_SHADOW_TILES__", &MAX_SHADOW_TILES.to_string())
 }
 
 /// The lighting + shadow uniform locations of one lit forward shader program
@@ -395,8 +575,15 @@ pub struct LightingUniforms {
     view_pos_loc: UniformLocation,
     shadow_map_loc: UniformLocation,
     light_space_matrix_loc: UniformLocation,
+    shadow_tile_rect_loc: UniformLocation,
+    shadow_tile_count_loc: UniformLocation,
     shadow_enabled_loc: UniformLocation,
     shadow_light_index_loc: UniformLocation,
+    environment_specular_loc: UniformLocation,
+    environment_irradiance_loc: UniformLocation,
+    environment_max_lod_loc: UniformLocation,
+    environment_intensity_loc: UniformLocation,
+    environment_enabled_loc: UniformLocation,
 }
 
 impl LightingUniforms {
@@ -412,16 +599,30 @@ impl LightingUniforms {
             view_pos_loc: shader.get_uniform_location(gl, "viewPos"),
             shadow_map_loc: shader.get_uniform_location(gl, "shadowMap"),
             light_space_matrix_loc: shader.get_uniform_location(gl, "lightSpaceMatrix"),
+            shadow_tile_rect_loc: shader.get_uniform_location(gl, "shadowTileRect"),
+            shadow_tile_count_loc: shader.get_uniform_location(gl, "shadowTileCount"),
             shadow_enabled_loc: shader.get_uniform_location(gl, "shadowEnabled"),
             shadow_light_index_loc: shader.get_uniform_location(gl, "shadowLightIndex"),
+            environment_specular_loc: shader.get_uniform_location(gl, "environmentSpecular"),
+            environment_irradiance_loc: shader.get_uniform_location(gl, "environmentIrradiance"),
+            environment_max_lod_loc: shader.get_uniform_location(gl, "environmentMaxLod"),
+            environment_intensity_loc: shader.get_uniform_location(gl, "environmentIntensity"),
+            environment_enabled_loc: shader.get_uniform_location(gl, "environmentEnabled"),
         }
     }
 
     /// Upload this draw's lights (packed from `ctx.lights`), the specular view
-    /// position (the inverse-view translation), and the shadow map — bound to
-    /// texture unit 1 (unit 0 is albedo, 2 the normal map), leaving unit 0
-    /// active.
-    pub fn set(&self, p: &ShaderProgram, ctx: &RenderContext, view_matrix: &Matrix4<f32>) {
+    /// position (the inverse-view translation), the shadow map — bound to
+    /// texture unit 1 (unit 0 is albedo, 2 the normal map) — and the image-based
+    /// light nearest `anchor` (the draw's world origin) on
+    /// [`ENVIRONMENT_TEXTURE_UNIT`], leaving unit 0 active.
+    pub fn set(
+        &self,
+        p: &ShaderProgram,
+        ctx: &RenderContext,
+        view_matrix: &Matrix4<f32>,
+        anchor: Vector3<f32>,
+    ) {
         let gl = ctx.gl;
         let lights = pack_lights(ctx.lights);
 
@@ -446,7 +647,13 @@ impl LightingUniforms {
             Some(shadow) => {
                 p.set_uniform_1i(gl, &self.shadow_enabled_loc, 1);
                 p.set_uniform_1i(gl, &self.shadow_light_index_loc, shadow.light_index);
-                p.set_uniform_matrix4(gl, &self.light_space_matrix_loc, &shadow.light_space_matrix);
+                p.set_uniform_1i(gl, &self.shadow_tile_count_loc, shadow.tile_count);
+                p.set_uniform_matrix4fv(
+                    gl,
+                    &self.light_space_matrix_loc,
+                    &shadow.light_space_matrices,
+                );
+                p.set_uniform_vec4v(gl, &self.shadow_tile_rect_loc, &shadow.tile_rects);
                 unsafe {
                     gl.active_texture(glow::TEXTURE0 + 1);
                     gl.bind_texture(glow::TEXTURE_2D, Some(shadow.depth_texture));
@@ -457,18 +664,47 @@ impl LightingUniforms {
                 p.set_uniform_1i(gl, &self.shadow_enabled_loc, 0);
             }
         }
+
+        p.set_uniform_1i(
+            gl,
+            &self.environment_specular_loc,
+            ENVIRONMENT_TEXTURE_UNIT as i32,
+        );
+        match ctx.environment.and_then(|lighting| lighting.at(anchor)) {
+            Some(environment) => {
+                p.set_uniform_1i(gl, &self.environment_enabled_loc, 1);
+                p.set_uniform_vec3v(
+                    gl,
+                    &self.environment_irradiance_loc,
+                    &environment.irradiance,
+                );
+                p.set_uniform_1f(gl, &self.environment_max_lod_loc, environment.max_lod);
+                p.set_uniform_1f(gl, &self.environment_intensity_loc, environment.intensity);
+                unsafe {
+                    gl.active_texture(glow::TEXTURE0 + ENVIRONMENT_TEXTURE_UNIT);
+                    gl.bind_texture(glow::TEXTURE_CUBE_MAP, Some(environment.specular_texture));
+                    gl.active_texture(glow::TEXTURE0);
+                }
+            }
+            None => {
+                p.set_uniform_1i(gl, &self.environment_enabled_loc, 0);
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{lighting_glsl, pack_lights, Light, MAX_LIGHTS};
+    use super::{lighting_glsl, pack_lights, Light, ShadowSettings, MAX_LIGHTS};
+    use crate::shadow::MAX_SHADOW_TILES;
 
     #[test]
     fn lighting_glsl_substitutes_the_cap() {
         let glsl = lighting_glsl();
         assert!(glsl.contains(&format!("#define MAX_LIGHTS {MAX_LIGHTS}")));
+        assert!(glsl.contains(&format!("#define MAX_SHADOW_TILES {MAX_SHADOW_TILES}")));
         assert!(!glsl.contains("__MAX_LIGHTS__"));
+        assert!(!glsl.contains("__MAX_SHADOW_TILES__"));
     }
 
     #[test]
@@ -492,6 +728,30 @@ mod tests {
         assert!((u.cone_cos[3] - 1.0).abs() < 1e-6);
     }
 
+    #[test]
+    fn shadow_settings_stay_off_the_wire_until_tuned() {
+        let plain = Light::point(0.0, 2.0, 0.0, 1.0, 1.0, 1.0, 1.0, 8.0).cast_shadows();
+        let json = serde_json::to_string(&plain).unwrap();
+        assert!(!json.contains("shadow\":"), "json: {json}");
+        assert_eq!(serde_json::from_str::<Light>(&json).unwrap(), plain);
+
+        let settings = ShadowSettings {
+            resolution: 1024,
+            cascades: 3,
+            distance: 80.0,
+        };
+        let tuned =
+            Light::directional(0.0, -1.0, 0.0, 1.0, 1.0, 1.0, 1.0).cast_shadows_with(settings);
+        assert!(tuned.casts_shadows());
+        assert_eq!(tuned.shadow_settings(), settings);
+        let json = serde_json::to_string(&tuned).unwrap();
+        assert_eq!(serde_json::from_str::<Light>(&json).unwrap(), tuned);
+        // Ambient lights never cast, whatever they are asked.
+        assert!(!Light::ambient(0.1, 0.1, 0.1)
+            .cast_shadows_with(settings)
+            .casts_shadows());
+    }
+
     #[test]
     fn empty_is_zero_count() {
         assert_eq!(pack_lights(&[]).count, 0);
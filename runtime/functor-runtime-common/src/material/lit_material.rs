@@ -198,7 +198,7 @@ impl Material for LitMaterial {
                     self.use_normal_map as i32,
                 );
 
-                uniforms.lighting.set(p, ctx, view_matrix);
+                uniforms.lighting.set(p, ctx, view_matrix, world_matrix.w.truncate());
                 uniforms.fog.set(p, ctx.gl, ctx.fog, &ctx.camera_pos);
             }
         }
@@ -0,0 +1,433 @@
+use cgmath::Matrix4;
+
+use crate::math::normal_matrix;
+
+use crate::fog::{FogUniforms, FOG_GLSL};
+use crate::light::{lighting_glsl, LightingUniforms};
+use crate::pbr::{
+    AlphaMode, PbrFactors, EMISSIVE_TEXTURE_UNIT, METALLIC_ROUGHNESS_TEXTURE_UNIT,
+    NORMAL_TEXTURE_UNIT, OCCLUSION_TEXTURE_UNIT,
+};
+use crate::shader_program::ShaderProgram;
+use crate::shader_program::UniformLocation;
+use crate::RenderContext;
+
+use super::Material;
+
+// The glTF metallic-roughness material: `shadePbr` from the shared lighting
+// GLSL over the factors and whichever textures the caller bound — base color
+// (unit 0), normal map (2), metallic-roughness (3), occlusion (4) and emissive
+// (5). The static program takes `VertexPositionTexture`-style attributes; the
+// skinned one adds joints/weights (locations 4/5) and deforms the tangent
+// frame like `SkinnedMaterial` deforms the normal.
+const STATIC_VERTEX_SHADER_SOURCE: &str = r#"
+        layout (location = 0) in vec3 inPos;
+        layout (location = 1) in vec2 inTex;
+        layout (location = 2) in vec3 inNormal;
+        layout (location = 3) in vec4 inTangent;
+
+        uniform mat4 world;
+        uniform mat3 normalMatrix;
+        uniform mat4 view;
+        uniform mat4 projection;
+
+        out vec2 texCoord;
+        out vec3 worldNormal;
+        out vec3 worldTangent;
+        out vec3 worldBitangent;
+        out vec3 worldPos;
+
+        void main() {
+            texCoord = inTex;
+            vec3 n = normalMatrix * inNormal;
+            vec3 t = mat3(world) * inTangent.xyz;
+            worldNormal = n;
+            worldTangent = t;
+            worldBitangent = cross(n, t) * inTangent.w;
+            vec4 wp = world * vec4(inPos, 1.0);
+            worldPos = wp.xyz;
+            gl_Position = projection * view * wp;
+        }
+"#;
+
+const SKINNED_VERTEX_SHADER_SOURCE: &str = r#"
+        #define MAX_JOINTS 200
+
+        layout (location = 0) in vec3 inPos;
+        layout (location = 1) in vec2 inTex;
+        layout (location = 2) in vec3 inNormal;
+        layout (location = 3) in vec4 inTangent;
+        layout (location = 4) in vec4 inJointIndices;
+        layout (location = 5) in vec4 inWeights;
+
+        uniform mat4 jointTransforms[MAX_JOINTS];
+        uniform mat4 world;
+        uniform mat3 normalMatrix;
+        uniform mat4 view;
+        uniform mat4 projection;
+
+        out vec2 texCoord;
+        out vec3 worldNormal;
+        out vec3 worldTangent;
+        out vec3 worldBitangent;
+        out vec3 worldPos;
+
+        void main() {
+            mat4 skinMatrix =
+                inWeights.x * jointTransforms[int(inJointIndices.x)] +
+                inWeights.y * jointTransforms[int(inJointIndices.y)] +
+                inWeights.z * jointTransforms[int(inJointIndices.z)] +
+                inWeights.w * jointTransforms[int(inJointIndices.w)];
+
+            texCoord = inTex;
+            vec3 n = normalMatrix * mat3(skinMatrix) * inNormal;
+            vec3 t = mat3(world) * mat3(skinMatrix) * inTangent.xyz;
+            worldNormal = n;
+            worldTangent = t;
+            worldBitangent = cross(n, t) * inTangent.w;
+            vec4 wp = world * skinMatrix * vec4(inPos, 1.0);
+            worldPos = wp.xyz;
+            gl_Position = projection * view * wp;
+        }
+"#;
+
+// Concatenated after `FOG_GLSL` + `lighting_glsl()`. Emissive light is added
+// before fog, so fog occludes glow like it does for `EmissiveMaterial`.
+const FRAGMENT_SHADER_SOURCE: &str = r#"
+        out vec4 fragColor;
+
+        in vec2 texCoord;
+        in vec3 worldNormal;
+        in vec3 worldTangent;
+        in vec3 worldBitangent;
+        in vec3 worldPos;
+
+        uniform vec4 baseColorFactor;
+        uniform float metallicFactor;
+        uniform float roughnessFactor;
+        uniform vec3 emissiveFactor;
+        uniform float normalScale;
+        uniform float occlusionStrength;
+        uniform int alphaMode; // 0=opaque 1=mask 2=blend
+        uniform float alphaCutoff;
+        uniform int doubleSided;
+
+        uniform sampler2D baseColorTexture;
+        uniform int useBaseColorTexture;
+        uniform sampler2D normalMap;
+        uniform int useNormalMap;
+        uniform sampler2D metallicRoughnessTexture;
+        uniform int useMetallicRoughnessTexture;
+        uniform sampler2D occlusionTexture;
+        uniform int useOcclusionTexture;
+        uniform sampler2D emissiveTexture;
+        uniform int useEmissiveTexture;
+
+        void main() {
+            vec4 baseColor = baseColorFactor;
+            if (useBaseColorTexture == 1) {
+                baseColor *= texture(baseColorTexture, texCoord);
+            }
+            if (alphaMode == 1 && baseColor.a < alphaCutoff) {
+                discard;
+            }
+
+            vec3 n = normalize(worldNormal);
+            vec3 t = worldTangent;
+            vec3 b = worldBitangent;
+            // A double-sided surface seen from behind shades its back side.
+            // "Behind" compares the normal with the triangle's own facing
+            // (from screen-space derivatives, turned toward the camera), not
+            // gl_FrontFacing, so it holds whatever the mesh's winding.
+            if (doubleSided == 1) {
+                vec3 face = cross(dFdx(worldPos), dFdy(worldPos));
+                if (dot(face, viewPos - worldPos) < 0.0) {
+                    face = -face;
+                }
+                if (dot(n, face) < 0.0) {
+                    n = -n;
+                    t = -t;
+                    b = -b;
+                }
+            }
+            if (useNormalMap == 1) {
+                vec3 tn = texture(normalMap, texCoord).xyz * 2.0 - 1.0;
+                tn.xy *= normalScale;
+                mat3 tbn = mat3(normalize(t), normalize(b), n);
+                n = normalize(tbn * tn);
+            }
+
+            float metallic = metallicFactor;
+            float roughness = roughnessFactor;
+            if (useMetallicRoughnessTexture == 1) {
+                vec4 packed = texture(metallicRoughnessTexture, texCoord);
+                roughness *= packed.g;
+                metallic *= packed.b;
+            }
+            float occlusion = 1.0;
+            if (useOcclusionTexture == 1) {
+                occlusion = 1.0 + occlusionStrength * (texture(occlusionTexture, texCoord).r - 1.0);
+            }
+            vec3 emissive = emissiveFactor;
+            if (useEmissiveTexture == 1) {
+                emissive *= texture(emissiveTexture, texCoord).rgb;
+            }
+
+            vec3 color = shadePbr(n, worldPos, baseColor.rgb, metallic, roughness, occlusion);
+            float alpha = alphaMode == 2 ? baseColor.a : 1.0;
+            fragColor = vec4(applyFog(color + emissive, worldPos), alpha);
+        }
+"#;
+
+struct Uniforms {
+    world_loc: UniformLocation,
+    normal_matrix_loc: UniformLocation,
+    view_loc: UniformLocation,
+    projection_loc: UniformLocation,
+    joint_transforms_loc: UniformLocation,
+    base_color_factor_loc: UniformLocation,
+    metallic_factor_loc: UniformLocation,
+    roughness_factor_loc: UniformLocation,
+    emissive_factor_loc: UniformLocation,
+    normal_scale_loc: UniformLocation,
+    occlusion_strength_loc: UniformLocation,
+    alpha_mode_loc: UniformLocation,
+    alpha_cutoff_loc: UniformLocation,
+    double_sided_loc: UniformLocation,
+    // (sampler, use flag) per texture slot, in `PbrTextures` order.
+    textures: [(UniformLocation, UniformLocation); 5],
+    lighting: LightingUniforms,
+    fog: FogUniforms,
+}
+
+static mut STATIC_SHADER_PROGRAM: Option<(ShaderProgram, Uniforms)> = None;
+static mut SKINNED_SHADER_PROGRAM: Option<(ShaderProgram, Uniforms)> = None;
+
+/// Which of a metallic-roughness material's texture slots the caller bound.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PbrTextures {
+    pub base_color: bool,
+    pub normal: bool,
+    pub metallic_roughness: bool,
+    pub occlusion: bool,
+    pub emissive: bool,
+}
+
+impl PbrTextures {
+    fn flags(&self) -> [bool; 5] {
+        [
+            self.base_color,
+            self.normal,
+            self.metallic_roughness,
+            self.occlusion,
+            self.emissive,
+        ]
+    }
+}
+
+/// Sampler names and units, in `PbrTextures` order.
+const TEXTURE_SLOTS: [(&str, &str, u32); 5] = [
+    ("baseColorTexture", "useBaseColorTexture", 0),
+    ("normalMap", "useNormalMap", NORMAL_TEXTURE_UNIT),
+    (
+        "metallicRoughnessTexture",
+        "useMetallicRoughnessTexture",
+        METALLIC_ROUGHNESS_TEXTURE_UNIT,
+    ),
+    (
+        "occlusionTexture",
+        "useOcclusionTexture",
+        OCCLUSION_TEXTURE_UNIT,
+    ),
+    (
+        "emissiveTexture",
+        "useEmissiveTexture",
+        EMISSIVE_TEXTURE_UNIT,
+    ),
+];
+
+pub struct PbrMaterial {
+    factors: PbrFactors,
+    textures: PbrTextures,
+    skinned: bool,
+}
+
+use crate::shader::Shader;
+use crate::shader::ShaderType;
+
+impl Material for PbrMaterial {
+    fn initialize(&mut self, ctx: &RenderContext) {
+        unsafe {
+            #[allow(static_mut_refs)]
+            let (program, vertex_source) = if self.skinned {
+                (&mut SKINNED_SHADER_PROGRAM, SKINNED_VERTEX_SHADER_SOURCE)
+            } else {
+                (&mut STATIC_SHADER_PROGRAM, STATIC_VERTEX_SHADER_SOURCE)
+            };
+            if program.is_none() {
+                let vertex_shader = Shader::build(
+                    ctx.gl,
+                    ShaderType::Vertex,
+                    vertex_source,
+                    ctx.shader_version,
+                );
+
+                let fragment_source = format!(
+                    "{}\n{}\n{}",
+                    FOG_GLSL,
+                    lighting_glsl(),
+                    FRAGMENT_SHADER_SOURCE
+                );
+                let fragment_shader = Shader::build(
+                    ctx.gl,
+                    ShaderType::Fragment,
+                    &fragment_source,
+                    ctx.shader_version,
+                );
+
+                let shader = crate::shader_program::ShaderProgram::link(
+                    ctx.gl,
+                    &vertex_shader,
+                    &fragment_shader,
+                );
+
+                let uniforms = Uniforms {
+                    world_loc: shader.get_uniform_location(ctx.gl, "world"),
+                    normal_matrix_loc: shader.get_uniform_location(ctx.gl, "normalMatrix"),
+                    view_loc: shader.get_uniform_location(ctx.gl, "view"),
+                    projection_loc: shader.get_uniform_location(ctx.gl, "projection"),
+                    joint_transforms_loc: shader.get_uniform_location(ctx.gl, "jointTransforms"),
+                    base_color_factor_loc: shader.get_uniform_location(ctx.gl, "baseColorFactor"),
+                    metallic_factor_loc: shader.get_uniform_location(ctx.gl, "metallicFactor"),
+                    roughness_factor_loc: shader.get_uniform_location(ctx.gl, "roughnessFactor"),
+                    emissive_factor_loc: shader.get_uniform_location(ctx.gl, "emissiveFactor"),
+                    normal_scale_loc: shader.get_uniform_location(ctx.gl, "normalScale"),
+                    occlusion_strength_loc: shader
+                        .get_uniform_location(ctx.gl, "occlusionStrength"),
+                    alpha_mode_loc: shader.get_uniform_location(ctx.gl, "alphaMode"),
+                    alpha_cutoff_loc: shader.get_uniform_location(ctx.gl, "alphaCutoff"),
+                    double_sided_loc: shader.get_uniform_location(ctx.gl, "doubleSided"),
+                    textures: TEXTURE_SLOTS.map(|(sampler, flag, _)| {
+                        (
+                            shader.get_uniform_location(ctx.gl, sampler),
+                            shader.get_uniform_location(ctx.gl, flag),
+                        )
+                    }),
+                    lighting: LightingUniforms::get(&shader, ctx.gl),
+                    fog: FogUniforms::get(&shader, ctx.gl),
+                };
+
+                *program = Some((shader, uniforms));
+            }
+        }
+    }
+
+    fn draw_opaque(
+        &self,
+        ctx: &RenderContext,
+        projection_matrix: &Matrix4<f32>,
+        view_matrix: &Matrix4<f32>,
+        world_matrix: &Matrix4<f32>,
+        skinning_data: &[Matrix4<f32>],
+    ) -> bool {
+        unsafe {
+            #[allow(static_mut_refs)]
+            let program = if self.skinned {
+                &SKINNED_SHADER_PROGRAM
+            } else {
+                &STATIC_SHADER_PROGRAM
+            };
+            if let Some((shader, uniforms)) = program {
+                let p = shader;
+                p.use_program(ctx.gl);
+
+                p.set_uniform_matrix4(ctx.gl, &uniforms.world_loc, world_matrix);
+                p.set_uniform_matrix3(
+                    ctx.gl,
+                    &uniforms.normal_matrix_loc,
+                    &normal_matrix(world_matrix),
+                );
+                p.set_uniform_matrix4(ctx.gl, &uniforms.view_loc, view_matrix);
+                p.set_uniform_matrix4(ctx.gl, &uniforms.projection_loc, projection_matrix);
+                if self.skinned {
+                    let joint_matrices = crate::model::flatten_joint_matrices(skinning_data);
+                    p.set_uniform_matrix4fv(
+                        ctx.gl,
+                        &uniforms.joint_transforms_loc,
+                        &joint_matrices,
+                    );
+                }
+
+                let factors = &self.factors;
+                p.set_uniform_vec4(
+                    ctx.gl,
+                    &uniforms.base_color_factor_loc,
+                    &factors.base_color.into(),
+                );
+                p.set_uniform_1f(ctx.gl, &uniforms.metallic_factor_loc, factors.metallic);
+                p.set_uniform_1f(ctx.gl, &uniforms.roughness_factor_loc, factors.roughness);
+                p.set_uniform_vec3(
+                    ctx.gl,
+                    &uniforms.emissive_factor_loc,
+                    &factors.emissive.into(),
+                );
+                p.set_uniform_1f(ctx.gl, &uniforms.normal_scale_loc, factors.normal_scale);
+                p.set_uniform_1f(
+                    ctx.gl,
+                    &uniforms.occlusion_strength_loc,
+                    factors.occlusion_strength,
+                );
+                let (alpha_mode, alpha_cutoff) = match factors.alpha_mode {
+                    AlphaMode::Opaque => (0, 0.0),
+                    AlphaMode::Mask(cutoff) => (1, cutoff),
+                    AlphaMode::Blend => (2, 0.0),
+                };
+                p.set_uniform_1i(ctx.gl, &uniforms.alpha_mode_loc, alpha_mode);
+                p.set_uniform_1f(ctx.gl, &uniforms.alpha_cutoff_loc, alpha_cutoff);
+                p.set_uniform_1i(
+                    ctx.gl,
+                    &uniforms.double_sided_loc,
+                    factors.double_sided as i32,
+                );
+
+                for ((sampler_loc, use_loc), ((_, _, unit), used)) in uniforms
+                    .textures
+                    .iter()
+                    .zip(TEXTURE_SLOTS.iter().zip(self.textures.flags()))
+                {
+                    p.set_uniform_1i(ctx.gl, sampler_loc, *unit as i32);
+                    p.set_uniform_1i(ctx.gl, use_loc, used as i32);
+                }
+
+                uniforms
+                    .lighting
+                    .set(p, ctx, view_matrix, world_matrix.w.truncate());
+                uniforms.fog.set(p, ctx.gl, ctx.fog, &ctx.camera_pos);
+            }
+        }
+
+        true
+    }
+}
+
+impl PbrMaterial {
+    /// A static-mesh material; `textures` says which slots the caller bound
+    /// (each to its unit — see the shader comment above).
+    pub fn create(factors: PbrFactors, textures: PbrTextures) -> Box<dyn Material> {
+        Box::new(PbrMaterial {
+            factors,
+            textures,
+            skinned: false,
+        })
+    }
+
+    /// The skinned counterpart of [`PbrMaterial::create`], for glTF models
+    /// with a skeleton.
+    pub fn create_skinned(factors: PbrFactors, textures: PbrTextures) -> Box<dyn Material> {
+        Box::new(PbrMaterial {
+            factors,
+            textures,
+            skinned: true,
+        })
+    }
+}
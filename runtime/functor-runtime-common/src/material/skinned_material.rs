@@ -176,7 +176,7 @@ impl Material for SkinnedMaterial {
                 let joint_matrices = crate::model::flatten_joint_matrices(skinning_data);
 
                 p.set_uniform_matrix4fv(ctx.gl, &uniforms.joint_transforms_loc, &joint_matrices);
-                uniforms.lighting.set(p, ctx, view_matrix);
+                uniforms.lighting.set(p, ctx, view_matrix, world_matrix.w.truncate());
                 uniforms.fog.set(p, ctx.gl, ctx.fog, &ctx.camera_pos);
             }
         }
@@ -1,8 +1,8 @@
 mod skeleton;
 
 use crate::{
-    animation::Animation, geometry::IndexedMesh, render::VertexPositionTextureSkinned,
-    texture::Texture2D,
+    animation::Animation, geometry::IndexedMesh, pbr::PbrFactors,
+    render::VertexPositionTextureSkinned, texture::Texture2D,
 };
 use cgmath::Matrix4;
 
@@ -12,11 +12,24 @@ pub struct ModelMesh {
     // Material info
     pub base_color_texture: Texture2D,
 
+    pub material: ModelMaterial,
+
     pub mesh: IndexedMesh<VertexPositionTextureSkinned>,
 
     pub transform: Matrix4<f32>,
 }
 
+/// The rest of a primitive's glTF metallic-roughness material. The base
+/// color factor is already folded into an untextured primitive's
+/// `base_color_texture` (a 1x1 of it), so `factors.base_color` is white there.
+pub struct ModelMaterial {
+    pub factors: PbrFactors,
+    pub normal_texture: Option<Texture2D>,
+    pub metallic_roughness_texture: Option<Texture2D>,
+    pub occlusion_texture: Option<Texture2D>,
+    pub emissive_texture: Option<Texture2D>,
+}
+
 pub struct Model {
     pub meshes: Vec<ModelMesh>,
 
@@ -0,0 +1,321 @@
+//! The glTF metallic-roughness material model: base color, metallic and
+//! roughness factors with their packed texture, a tangent-space normal map,
+//! ambient occlusion, emissive, alpha modes and double-sidedness.
+//!
+//! Imported models parse their materials into [`PbrFactors`] (plus textures
+//! the model pipeline decodes); procedural geometry names the same model
+//! through [`PbrDescription`], the serializable form `Scene.pbr` lowers to.
+//! Both shade with `shadePbr` in the shared lighting GLSL (the
+//! `PbrMaterial`) and its mirror in the software renderer.
+
+use serde::{Deserialize, Serialize};
+
+use crate::TextureDescription;
+
+/// The texture unit of the tangent-space normal map — the lit material's, so
+/// a model's normal map binds the same way in either path.
+pub const NORMAL_TEXTURE_UNIT: u32 = 2;
+
+/// The texture unit of the metallic-roughness texture (glTF packs roughness
+/// in green and metalness in blue). Units 0 (base color) and 1 (shadow
+/// atlas) keep their lit-material meaning.
+pub const METALLIC_ROUGHNESS_TEXTURE_UNIT: u32 = 3;
+
+/// The texture unit of the occlusion texture (red channel).
+pub const OCCLUSION_TEXTURE_UNIT: u32 = 4;
+
+/// The texture unit of the emissive texture (sRGB-authored color, sampled
+/// as-is like every other texture here).
+pub const EMISSIVE_TEXTURE_UNIT: u32 = 5;
+
+/// How a material's base-color alpha is interpreted.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AlphaMode {
+    /// Alpha is ignored; the surface is fully opaque.
+    Opaque,
+    /// Fragments with alpha below the cutoff are discarded; the rest are
+    /// opaque (foliage, fences).
+    Mask(f32),
+    /// The surface blends over what is behind it, drawn after the opaque
+    /// geometry without writing depth.
+    Blend,
+}
+
+/// The scalar half of a metallic-roughness material, with glTF's defaults.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PbrFactors {
+    /// Linear RGBA, multiplied with the base-color texture.
+    pub base_color: [f32; 4],
+    /// 0 = dielectric, 1 = metal; multiplied with the texture's blue channel.
+    pub metallic: f32,
+    /// Perceptual roughness; multiplied with the texture's green channel.
+    pub roughness: f32,
+    /// Emitted light, multiplied with the emissive texture.
+    pub emissive: [f32; 3],
+    /// Scales the normal map's tangent-space x and y.
+    pub normal_scale: f32,
+    /// How strongly the occlusion texture dims indirect light (0 = not at
+    /// all).
+    pub occlusion_strength: f32,
+    pub alpha_mode: AlphaMode,
+    /// When false, back faces are culled; when true, they are shaded with a
+    /// flipped normal.
+    pub double_sided: bool,
+}
+
+impl Default for PbrFactors {
+    fn default() -> PbrFactors {
+        PbrFactors {
+            base_color: [1.0, 1.0, 1.0, 1.0],
+            metallic: 1.0,
+            roughness: 1.0,
+            emissive: [0.0, 0.0, 0.0],
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+            alpha_mode: AlphaMode::Opaque,
+            double_sided: false,
+        }
+    }
+}
+
+impl PbrFactors {
+    /// A glTF material's factors. Specular-glossiness materials keep their
+    /// diffuse color and map glossiness to roughness as a dielectric — an
+    /// approximation, since metalness cannot be recovered from a specular
+    /// color.
+    pub fn from_gltf(material: &gltf::Material) -> PbrFactors {
+        let pbr = material.pbr_metallic_roughness();
+        let (base_color, metallic, roughness) = match material.pbr_specular_glossiness() {
+            Some(specular_glossiness) => (
+                specular_glossiness.diffuse_factor(),
+                0.0,
+                1.0 - specular_glossiness.glossiness_factor(),
+            ),
+            None => (
+                pbr.base_color_factor(),
+                pbr.metallic_factor(),
+                pbr.roughness_factor(),
+            ),
+        };
+        PbrFactors {
+            base_color,
+            metallic,
+            roughness,
+            emissive: material.emissive_factor(),
+            normal_scale: material
+                .normal_texture()
+                .map_or(1.0, |normal| normal.scale()),
+            occlusion_strength: material
+                .occlusion_texture()
+                .map_or(1.0, |occlusion| occlusion.strength()),
+            alpha_mode: match material.alpha_mode() {
+                gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+                gltf::material::AlphaMode::Mask => {
+                    AlphaMode::Mask(material.alpha_cutoff().unwrap_or(0.5))
+                }
+                gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+            },
+            double_sided: material.double_sided(),
+        }
+    }
+
+    pub fn blends(&self) -> bool {
+        self.alpha_mode == AlphaMode::Blend
+    }
+
+    /// Whether the material emits any light.
+    pub fn is_emissive(&self) -> bool {
+        self.emissive.iter().any(|channel| *channel > 0.0)
+    }
+}
+
+/// A metallic-roughness material for procedural geometry: the factors plus
+/// the optional texture of each slot.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PbrDescription {
+    pub factors: PbrFactors,
+    pub base_color_texture: Option<TextureDescription>,
+    pub metallic_roughness_texture: Option<TextureDescription>,
+    pub normal_texture: Option<TextureDescription>,
+    pub occlusion_texture: Option<TextureDescription>,
+    pub emissive_texture: Option<TextureDescription>,
+}
+
+impl PbrDescription {
+    /// An untextured material: `base_color` with the given metalness and
+    /// roughness. Double-sided, since procedural meshes do not share a
+    /// winding convention that culling could rely on.
+    pub fn new(base_color: [f32; 4], metallic: f32, roughness: f32) -> PbrDescription {
+        PbrDescription {
+            factors: PbrFactors {
+                base_color,
+                metallic,
+                roughness,
+                double_sided: true,
+                ..PbrFactors::default()
+            },
+            base_color_texture: None,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+        }
+    }
+}
+
+/// Which parts of the material model a glTF primitive's material uses — what
+/// `inspect model` reports per primitive.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MaterialFeatures {
+    /// The material's name; `None` for unnamed materials and the glTF default
+    /// material.
+    pub name: Option<String>,
+    pub base_color_texture: bool,
+    pub metallic_roughness_texture: bool,
+    pub normal_texture: bool,
+    pub occlusion_texture: bool,
+    pub emissive_texture: bool,
+    /// A nonzero emissive factor.
+    pub emissive: bool,
+    pub alpha_mode: AlphaMode,
+    pub double_sided: bool,
+    /// Authored with `KHR_materials_pbrSpecularGlossiness` (approximated; see
+    /// [`PbrFactors::from_gltf`]).
+    pub specular_glossiness: bool,
+}
+
+impl MaterialFeatures {
+    pub fn from_gltf(material: &gltf::Material) -> MaterialFeatures {
+        let factors = PbrFactors::from_gltf(material);
+        let specular_glossiness = material.pbr_specular_glossiness();
+        let base_color_texture = match &specular_glossiness {
+            Some(specular_glossiness) => specular_glossiness.diffuse_texture().is_some(),
+            None => material
+                .pbr_metallic_roughness()
+                .base_color_texture()
+                .is_some(),
+        };
+        MaterialFeatures {
+            name: material.name().map(str::to_owned),
+            base_color_texture,
+            metallic_roughness_texture: specular_glossiness.is_none()
+                && material
+                    .pbr_metallic_roughness()
+                    .metallic_roughness_texture()
+                    .is_some(),
+            normal_texture: material.normal_texture().is_some(),
+            occlusion_texture: material.occlusion_texture().is_some(),
+            emissive_texture: material.emissive_texture().is_some(),
+            emissive: factors.is_emissive(),
+            alpha_mode: factors.alpha_mode,
+            double_sided: factors.double_sided,
+            specular_glossiness: specular_glossiness.is_some(),
+        }
+    }
+
+    /// A one-line summary, e.g. `baseColor metallicRoughness normal mask(0.5)
+    /// doubleSided`; `factors only` when no feature beyond the factors is used.
+    pub fn summary(&self) -> String {
+        let mut features: Vec<String> = [
+            (self.specular_glossiness, "specularGlossiness"),
+            (self.base_color_texture, "baseColor"),
+            (self.metallic_roughness_texture, "metallicRoughness"),
+            (self.normal_texture, "normal"),
+            (self.occlusion_texture, "occlusion"),
+            (self.emissive_texture || self.emissive, "emissive"),
+        ]
+        .into_iter()
+        .filter(|(used, _)| *used)
+        .map(|(_, feature)| feature.to_string())
+        .collect();
+        match self.alpha_mode {
+            AlphaMode::Opaque => {}
+            AlphaMode::Mask(cutoff) => features.push(format!("mask({cutoff})")),
+            AlphaMode::Blend => features.push("blend".to_string()),
+        }
+        if self.double_sided {
+            features.push("doubleSided".to_string());
+        }
+        if features.is_empty() {
+            "factors only".to_string()
+        } else {
+            features.join(" ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn materials(json: &str) -> Vec<(PbrFactors, MaterialFeatures)> {
+        let gltf = gltf::Gltf::from_slice(json.as_bytes()).expect("valid glTF");
+        gltf.document
+            .materials()
+            .map(|material| {
+                (
+                    PbrFactors::from_gltf(&material),
+                    MaterialFeatures::from_gltf(&material),
+                )
+            })
+            .collect()
+    }
+
+    const DOCUMENT: &str = r#"{
+        "asset": { "version": "2.0" },
+        "images": [{ "uri": "a.png" }],
+        "textures": [{ "source": 0 }],
+        "materials": [
+            {},
+            {
+                "name": "visor",
+                "pbrMetallicRoughness": {
+                    "baseColorFactor": [0.2, 0.4, 0.6, 0.5],
+                    "baseColorTexture": { "index": 0 },
+                    "metallicFactor": 0.25,
+                    "roughnessFactor": 0.75,
+                    "metallicRoughnessTexture": { "index": 0 }
+                },
+                "normalTexture": { "index": 0, "scale": 0.5 },
+                "occlusionTexture": { "index": 0, "strength": 0.8 },
+                "emissiveFactor": [1.0, 0.5, 0.0],
+                "alphaMode": "MASK",
+                "alphaCutoff": 0.3,
+                "doubleSided": true
+            },
+            { "alphaMode": "BLEND", "emissiveTexture": { "index": 0 } }
+        ]
+    }"#;
+
+    #[test]
+    fn gltf_materials_parse_every_factor_with_gltf_defaults() {
+        let parsed = materials(DOCUMENT);
+        assert_eq!(parsed[0].0, PbrFactors::default());
+
+        let visor = parsed[1].0;
+        assert_eq!(visor.base_color, [0.2, 0.4, 0.6, 0.5]);
+        assert_eq!((visor.metallic, visor.roughness), (0.25, 0.75));
+        assert_eq!(visor.emissive, [1.0, 0.5, 0.0]);
+        assert_eq!((visor.normal_scale, visor.occlusion_strength), (0.5, 0.8));
+        assert_eq!(visor.alpha_mode, AlphaMode::Mask(0.3));
+        assert!(visor.double_sided);
+        assert!(parsed[2].0.blends());
+    }
+
+    #[test]
+    fn material_features_summarize_what_a_primitive_uses() {
+        let summaries: Vec<String> = materials(DOCUMENT)
+            .iter()
+            .map(|(_, features)| features.summary())
+            .collect();
+        assert_eq!(
+            summaries,
+            [
+                "factors only",
+                "baseColor metallicRoughness normal occlusion emissive mask(0.3) doubleSided",
+                "emissive blend",
+            ]
+        );
+        assert_eq!(materials(DOCUMENT)[1].1.name.as_deref(), Some("visor"));
+    }
+}
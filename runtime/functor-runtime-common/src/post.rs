@@ -0,0 +1,785 @@
+//! The post-processing stack (`Frame.withPost`): screen-space effects applied
+//! to the frame's 3D pass, in list order, before its sprite layers draw.
+//!
+//! Each effect is one or more fullscreen passes over the offscreen targets
+//! `render_target.rs` manages: the main pass renders into a scene target, and
+//! every pass samples the previous image and writes the next, ending with a
+//! copy onto the caller's framebuffer (the compositor's program with one
+//! input). The GL side lives in `SceneContext::draw_post_stack`; the software
+//! renderer mirrors each stage with the pure functions here.
+//!
+//! The targets are RGBA8 like every render target, so the stack runs in
+//! display range: bloom can only lift what the scene already drew brighter
+//! than its threshold, and tonemapping reshapes `[0, 1]` rather than
+//! compressing HDR (exposure scales before the curve, so `> 1` still pushes
+//! midtones toward white). Render-target inner frames and composited frames
+//! ignore their `post` list.
+//!
+//! Like `Fog`, this accepts any parameters without judgement — validation
+//! with teaching errors is the Functor Lang layer's job.
+
+use serde::{Deserialize, Serialize};
+
+use crate::TextureDescription;
+
+/// How many horizontal + vertical blur rounds bloom runs over its half-size
+/// target. Each round widens the glow by the kernel's reach (4 texels).
+pub const BLOOM_BLUR_ITERATIONS: usize = 2;
+
+/// The one-sided weights of bloom's 9-tap separable gaussian, center first.
+pub const GAUSSIAN_WEIGHTS: [f32; 5] = [0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216];
+
+/// How many taps depth of field gathers around each pixel (plus the center).
+pub const DOF_TAPS: usize = 16;
+
+/// FXAA's edge-direction tuning: the smallest and the luma-relative
+/// reduction of the direction normalizer, and the longest blur span in
+/// texels.
+const FXAA_REDUCE_MIN: f32 = 1.0 / 128.0;
+const FXAA_REDUCE_MUL: f32 = 1.0 / 8.0;
+const FXAA_SPAN_MAX: f32 = 8.0;
+
+/// The golden angle, in radians: successive depth-of-field taps turn by it so
+/// any prefix of the spiral covers the disc evenly.
+const GOLDEN_ANGLE: f32 = 2.39996;
+
+/// Hable's "Uncharted 2" curve constants and its linear white point.
+const FILMIC_A: f32 = 0.15;
+const FILMIC_B: f32 = 0.50;
+const FILMIC_C: f32 = 0.10;
+const FILMIC_D: f32 = 0.20;
+const FILMIC_E: f32 = 0.02;
+const FILMIC_F: f32 = 0.30;
+const FILMIC_WHITE: f32 = 11.2;
+
+/// The tonemapping curve of [`PostEffect::Tonemap`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Tonemap {
+    /// Narkowicz's fit of the ACES reference rendering transform: punchy
+    /// contrast with a soft shoulder.
+    Aces,
+    /// Hable's filmic curve: a gentler toe and a long shoulder.
+    Filmic,
+}
+
+/// One effect of a frame's post stack.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PostEffect {
+    /// Glow around bright pixels: everything whose brightest channel exceeds
+    /// `threshold` is blurred at half resolution and added back, scaled by
+    /// `intensity`.
+    Bloom { threshold: f32, intensity: f32 },
+    /// `curve(color * exposure)`.
+    Tonemap { curve: Tonemap, exposure: f32 },
+    /// Color grading through a lookup table: a horizontal strip of N tiles,
+    /// each N x N (an N² x N image), where red increases left to right within
+    /// a tile, green top to bottom, and blue from tile to tile. `strength`
+    /// mixes from the ungraded (0) to the graded (1) color. Grading waits for
+    /// a file LUT to load; the pass is skipped until then.
+    ColorGrade {
+        lut: TextureDescription,
+        strength: f32,
+    },
+    /// Darkened corners: `intensity` is the darkening at the corners and
+    /// `smoothness` the fraction of the center-to-corner distance the falloff
+    /// spans.
+    Vignette { intensity: f32, smoothness: f32 },
+    /// Fast approximate anti-aliasing: blurs along the luma edges the 3D pass
+    /// rasterized.
+    Fxaa,
+    /// Blurs what is away from the focal plane: a pixel `focus_range` world
+    /// units nearer or farther than `focus_distance` gets the full `max_blur`
+    /// radius (in pixels), in-focus pixels none. Depth comes from an extra
+    /// depth pass of the frame's scene, which skips translucent
+    /// (`Scene.opacity`) subtrees — those take the depth behind them.
+    DepthOfField {
+        focus_distance: f32,
+        focus_range: f32,
+        max_blur: f32,
+    },
+}
+
+impl PostEffect {
+    pub fn bloom(threshold: f32, intensity: f32) -> PostEffect {
+        PostEffect::Bloom {
+            threshold,
+            intensity,
+        }
+    }
+
+    pub fn aces(exposure: f32) -> PostEffect {
+        PostEffect::Tonemap {
+            curve: Tonemap::Aces,
+            exposure,
+        }
+    }
+
+    pub fn filmic(exposure: f32) -> PostEffect {
+        PostEffect::Tonemap {
+            curve: Tonemap::Filmic,
+            exposure,
+        }
+    }
+
+    pub fn color_grade(lut: TextureDescription, strength: f32) -> PostEffect {
+        PostEffect::ColorGrade { lut, strength }
+    }
+
+    pub fn vignette(intensity: f32, smoothness: f32) -> PostEffect {
+        PostEffect::Vignette {
+            intensity,
+            smoothness,
+        }
+    }
+
+    pub fn depth_of_field(focus_distance: f32, focus_range: f32, max_blur: f32) -> PostEffect {
+        PostEffect::DepthOfField {
+            focus_distance,
+            focus_range,
+            max_blur,
+        }
+    }
+}
+
+/// Whether any effect of `post` reads scene depth (and so needs the extra
+/// depth pass).
+pub fn needs_depth(post: &[PostEffect]) -> bool {
+    post.iter()
+        .any(|effect| matches!(effect, PostEffect::DepthOfField { .. }))
+}
+
+/// Bloom's bright pass: the part of `color` above `threshold`, measured on
+/// its brightest channel so saturated neon blooms as readily as white.
+pub fn bright_pass(color: [f32; 3], threshold: f32) -> [f32; 3] {
+    let brightest = color[0].max(color[1]).max(color[2]);
+    let keep = (brightest - threshold).max(0.0) / brightest.max(1e-4);
+    color.map(|channel| channel * keep)
+}
+
+/// Narkowicz's ACES fit of one channel.
+pub fn aces(x: f32) -> f32 {
+    ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)
+}
+
+fn hable(x: f32) -> f32 {
+    (x * (FILMIC_A * x + FILMIC_C * FILMIC_B) + FILMIC_D * FILMIC_E)
+        / (x * (FILMIC_A * x + FILMIC_B) + FILMIC_D * FILMIC_F)
+        - FILMIC_E / FILMIC_F
+}
+
+/// Hable's filmic curve of one channel, with the usual exposure bias of 2 and
+/// normalized so the white point maps to 1 (and anything past it clamps).
+pub fn filmic(x: f32) -> f32 {
+    (hable(x * 2.0) / hable(FILMIC_WHITE)).clamp(0.0, 1.0)
+}
+
+/// [`PostEffect::Tonemap`] of one color.
+pub fn tonemap(color: [f32; 3], curve: Tonemap, exposure: f32) -> [f32; 3] {
+    color.map(|channel| {
+        let exposed = channel * exposure;
+        match curve {
+            Tonemap::Aces => aces(exposed),
+            Tonemap::Filmic => filmic(exposed),
+        }
+    })
+}
+
+/// The brightness factor [`PostEffect::Vignette`] applies at `uv` of a
+/// `width` x `height` image: 1 at the center, `1 - intensity` at the corners.
+/// Distance is measured with the aspect ratio corrected, so the falloff is
+/// round rather than stretched to the frame.
+pub fn vignette_factor(
+    uv: [f32; 2],
+    width: f32,
+    height: f32,
+    intensity: f32,
+    smoothness: f32,
+) -> f32 {
+    let aspect = width / height.max(1.0);
+    let offset = [(uv[0] - 0.5) * aspect, uv[1] - 0.5];
+    let corner = (0.25 * aspect * aspect + 0.25).sqrt();
+    let r = (offset[0] * offset[0] + offset[1] * offset[1]).sqrt() / corner;
+    1.0 - intensity * smoothstep(1.0 - smoothness, 1.0, r)
+}
+
+/// GLSL's `smoothstep`, including its edge0 == edge1 behavior (a step).
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    if edge1 <= edge0 {
+        return if x < edge0 { 0.0 } else { 1.0 };
+    }
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Where a color reads a `width` x `height` lookup strip: the uv in the two
+/// blue slices that bracket it, and how far between them it lies.
+pub fn lut_lookup(color: [f32; 3], width: f32, height: f32) -> ([f32; 2], [f32; 2], f32) {
+    let n = height.max(1.0);
+    let [r, g, b] = color.map(|channel| channel.clamp(0.0, 1.0));
+    let blue = b * (n - 1.0);
+    let slice0 = blue.floor();
+    let slice1 = (slice0 + 1.0).min(n - 1.0);
+    let x = (r * (n - 1.0) + 0.5) / width;
+    let y = (g * (n - 1.0) + 0.5) / n;
+    (
+        [x + slice0 * n / width, y],
+        [x + slice1 * n / width, y],
+        blue - slice0,
+    )
+}
+
+/// Eye distance from a window-space depth of a perspective projection.
+pub fn linear_depth(window_z: f32, near: f32, far: f32) -> f32 {
+    let ndc = window_z * 2.0 - 1.0;
+    2.0 * near * far / (far + near - ndc * (far - near))
+}
+
+/// The blur radius, in pixels, of a pixel `depth` world units from the eye.
+pub fn circle_of_confusion(
+    depth: f32,
+    focus_distance: f32,
+    focus_range: f32,
+    max_blur: f32,
+) -> f32 {
+    ((depth - focus_distance).abs() / focus_range.max(1e-4)).clamp(0.0, 1.0) * max_blur
+}
+
+/// Depth of field's `i`th tap on the unit disc (a Vogel spiral) and its
+/// distance from the center.
+pub fn dof_tap(i: usize) -> ([f32; 2], f32) {
+    let r = ((i as f32 + 0.5) / DOF_TAPS as f32).sqrt();
+    let angle = i as f32 * GOLDEN_ANGLE;
+    ([angle.cos() * r, angle.sin() * r], r)
+}
+
+/// How much a depth-of-field tap `distance` pixels from the center counts:
+/// fully when its own blur reaches the center, fading out over a pixel when
+/// it does not — so sharp foreground texels do not smear into a blurred
+/// background around them.
+pub fn dof_tap_weight(tap_coc: f32, distance: f32) -> f32 {
+    (tap_coc - distance + 1.0).clamp(0.0, 1.0)
+}
+
+/// One direction of bloom's separable blur at `uv`: `sample` reads the
+/// image, `step` is one texel along the blur axis.
+pub fn gaussian_blur(
+    sample: impl Fn([f32; 2]) -> [f32; 3],
+    uv: [f32; 2],
+    step: [f32; 2],
+) -> [f32; 3] {
+    let mut sum = sample(uv).map(|channel| channel * GAUSSIAN_WEIGHTS[0]);
+    for (i, weight) in GAUSSIAN_WEIGHTS.iter().enumerate().skip(1) {
+        let reach = [step[0] * i as f32, step[1] * i as f32];
+        let ahead = sample([uv[0] + reach[0], uv[1] + reach[1]]);
+        let behind = sample([uv[0] - reach[0], uv[1] - reach[1]]);
+        for channel in 0..3 {
+            sum[channel] += (ahead[channel] + behind[channel]) * weight;
+        }
+    }
+    sum
+}
+
+fn luma(color: [f32; 3]) -> f32 {
+    color[0] * 0.299 + color[1] * 0.587 + color[2] * 0.114
+}
+
+/// [`PostEffect::Fxaa`] at `uv` of an image whose texels are `texel` uv
+/// apart; the GLSL's transcription.
+pub fn fxaa(sample: impl Fn([f32; 2]) -> [f32; 3], uv: [f32; 2], texel: [f32; 2]) -> [f32; 3] {
+    let at = |dx: f32, dy: f32| sample([uv[0] + dx, uv[1] + dy]);
+    let luma_nw = luma(at(-texel[0], -texel[1]));
+    let luma_ne = luma(at(texel[0], -texel[1]));
+    let luma_sw = luma(at(-texel[0], texel[1]));
+    let luma_se = luma(at(texel[0], texel[1]));
+    let center = at(0.0, 0.0);
+    let luma_m = luma(center);
+    let luma_min = luma_m.min(luma_nw.min(luma_ne).min(luma_sw.min(luma_se)));
+    let luma_max = luma_m.max(luma_nw.max(luma_ne).max(luma_sw.max(luma_se)));
+
+    let dir = [
+        -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+        (luma_nw + luma_sw) - (luma_ne + luma_se),
+    ];
+    let reduce =
+        ((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * FXAA_REDUCE_MUL).max(FXAA_REDUCE_MIN);
+    let scale = 1.0 / (dir[0].abs().min(dir[1].abs()) + reduce);
+    let dir = [
+        (dir[0] * scale).clamp(-FXAA_SPAN_MAX, FXAA_SPAN_MAX) * texel[0],
+        (dir[1] * scale).clamp(-FXAA_SPAN_MAX, FXAA_SPAN_MAX) * texel[1],
+    ];
+    let along = |t: f32| at(dir[0] * t, dir[1] * t);
+
+    let (a, b) = (along(1.0 / 3.0 - 0.5), along(2.0 / 3.0 - 0.5));
+    let (c, d) = (along(-0.5), along(0.5));
+    let narrow: [f32; 3] = std::array::from_fn(|i| 0.5 * (a[i] + b[i]));
+    let wide: [f32; 3] = std::array::from_fn(|i| narrow[i] * 0.5 + 0.25 * (c[i] + d[i]));
+    let luma_wide = luma(wide);
+    if luma_wide < luma_min || luma_wide > luma_max {
+        narrow
+    } else {
+        wide
+    }
+}
+
+/// [`PostEffect::DepthOfField`]'s gather at `uv`: `coc` is the blur radius
+/// in pixels at a uv, `texel` the uv size of one pixel.
+pub fn depth_of_field(
+    sample: impl Fn([f32; 2]) -> [f32; 3],
+    coc: impl Fn([f32; 2]) -> f32,
+    uv: [f32; 2],
+    texel: [f32; 2],
+) -> [f32; 3] {
+    let radius = coc(uv);
+    let mut sum = sample(uv);
+    let mut total = 1.0;
+    for i in 0..DOF_TAPS {
+        let ([x, y], r) = dof_tap(i);
+        let tap = [uv[0] + x * radius * texel[0], uv[1] + y * radius * texel[1]];
+        let weight = dof_tap_weight(coc(tap), r * radius);
+        let color = sample(tap);
+        for channel in 0..3 {
+            sum[channel] += color[channel] * weight;
+        }
+        total += weight;
+    }
+    sum.map(|channel| channel / total)
+}
+
+/// One fullscreen pass of the stack. An effect runs as one or more stages;
+/// each stage's GLSL reads `vUv` from `COMPOSITE_VERTEX_SHADER_SOURCE`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PostStage {
+    BrightPass,
+    Blur,
+    BloomCombine,
+    Tonemap,
+    ColorGrade,
+    Vignette,
+    Fxaa,
+    DepthOfField,
+}
+
+impl PostStage {
+    pub fn fragment_source(self) -> &'static str {
+        match self {
+            PostStage::BrightPass => BRIGHT_PASS_FRAGMENT_SHADER_SOURCE,
+            PostStage::Blur => BLUR_FRAGMENT_SHADER_SOURCE,
+            PostStage::BloomCombine => BLOOM_COMBINE_FRAGMENT_SHADER_SOURCE,
+            PostStage::Tonemap => TONEMAP_FRAGMENT_SHADER_SOURCE,
+            PostStage::ColorGrade => COLOR_GRADE_FRAGMENT_SHADER_SOURCE,
+            PostStage::Vignette => VIGNETTE_FRAGMENT_SHADER_SOURCE,
+            PostStage::Fxaa => FXAA_FRAGMENT_SHADER_SOURCE,
+            PostStage::DepthOfField => DEPTH_OF_FIELD_FRAGMENT_SHADER_SOURCE,
+        }
+    }
+
+    /// The stage's samplers, bound to texture units in this order. `source`
+    /// is always the previous image.
+    pub fn samplers(self) -> &'static [&'static str] {
+        match self {
+            PostStage::BloomCombine => &["source", "bloom"],
+            PostStage::ColorGrade => &["source", "lut"],
+            PostStage::DepthOfField => &["source", "depth"],
+            _ => &["source"],
+        }
+    }
+
+    /// The stage's float uniforms. Every one is statically used, so looking
+    /// them up never hits an inactive uniform.
+    pub fn uniforms(self) -> &'static [&'static str] {
+        match self {
+            PostStage::BrightPass => &["threshold"],
+            PostStage::Blur => &["horizontal"],
+            PostStage::BloomCombine => &["intensity"],
+            PostStage::Tonemap => &["exposure", "filmic"],
+            PostStage::ColorGrade => &["strength"],
+            PostStage::Vignette => &["intensity", "smoothness"],
+            PostStage::Fxaa => &[],
+            PostStage::DepthOfField => &[
+                "cameraNear",
+                "cameraFar",
+                "focusDistance",
+                "focusRange",
+                "maxBlur",
+            ],
+        }
+    }
+}
+
+const BRIGHT_PASS_FRAGMENT_SHADER_SOURCE: &str = r#"
+        in vec2 vUv;
+        out vec4 fragColor;
+
+        uniform sampler2D source;
+        uniform float threshold;
+
+        void main() {
+            vec3 color = texture(source, vUv).rgb;
+            float brightest = max(color.r, max(color.g, color.b));
+            float keep = max(brightest - threshold, 0.0) / max(brightest, 1e-4);
+            fragColor = vec4(color * keep, 1.0);
+        }
+"#;
+
+// The weights must match `GAUSSIAN_WEIGHTS`. Indexing a const array with the
+// loop counter is plain GLSL ES 3.00 (only sampler arrays need literals).
+const BLUR_FRAGMENT_SHADER_SOURCE: &str = r#"
+        in vec2 vUv;
+        out vec4 fragColor;
+
+        uniform sampler2D source;
+        uniform float horizontal;   // 1 = along x, 0 = along y
+
+        const float WEIGHTS[5] = float[5](0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+
+        void main() {
+            vec2 texel = 1.0 / vec2(textureSize(source, 0));
+            vec2 offset = horizontal > 0.5 ? vec2(texel.x, 0.0) : vec2(0.0, texel.y);
+            vec3 sum = texture(source, vUv).rgb * WEIGHTS[0];
+            for (int i = 1; i < 5; i++) {
+                vec2 reach = offset * float(i);
+                sum += texture(source, vUv + reach).rgb * WEIGHTS[i];
+                sum += texture(source, vUv - reach).rgb * WEIGHTS[i];
+            }
+            fragColor = vec4(sum, 1.0);
+        }
+"#;
+
+const BLOOM_COMBINE_FRAGMENT_SHADER_SOURCE: &str = r#"
+        in vec2 vUv;
+        out vec4 fragColor;
+
+        uniform sampler2D source;
+        uniform sampler2D bloom;
+        uniform float intensity;
+
+        void main() {
+            vec3 color = texture(source, vUv).rgb + texture(bloom, vUv).rgb * intensity;
+            fragColor = vec4(color, 1.0);
+        }
+"#;
+
+const TONEMAP_FRAGMENT_SHADER_SOURCE: &str = r#"
+        in vec2 vUv;
+        out vec4 fragColor;
+
+        uniform sampler2D source;
+        uniform float exposure;
+        uniform float filmic;       // 1 = Hable filmic, 0 = ACES
+
+        vec3 aces(vec3 x) {
+            return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), 0.0, 1.0);
+        }
+
+        vec3 hable(vec3 x) {
+            const float A = 0.15;
+            const float B = 0.50;
+            const float C = 0.10;
+            const float D = 0.20;
+            const float E = 0.02;
+            const float F = 0.30;
+            return (x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F) - E / F;
+        }
+
+        void main() {
+            vec3 color = texture(source, vUv).rgb * exposure;
+            if (filmic > 0.5) {
+                color = clamp(hable(color * 2.0) / hable(vec3(11.2)), 0.0, 1.0);
+            } else {
+                color = aces(color);
+            }
+            fragColor = vec4(color, 1.0);
+        }
+"#;
+
+// `lut_lookup` transcribed. `textureLod` level 0 keeps a mipmapped LUT from
+// blurring across tiles.
+const COLOR_GRADE_FRAGMENT_SHADER_SOURCE: &str = r#"
+        in vec2 vUv;
+        out vec4 fragColor;
+
+        uniform sampler2D source;
+        uniform sampler2D lut;
+        uniform float strength;
+
+        void main() {
+            vec3 color = clamp(texture(source, vUv).rgb, 0.0, 1.0);
+            vec2 size = vec2(textureSize(lut, 0));
+            float n = size.y;
+            float blue = color.b * (n - 1.0);
+            float slice0 = floor(blue);
+            float slice1 = min(slice0 + 1.0, n - 1.0);
+            float x = (color.r * (n - 1.0) + 0.5) / size.x;
+            float y = (color.g * (n - 1.0) + 0.5) / n;
+            vec3 low = textureLod(lut, vec2(x + slice0 * n / size.x, y), 0.0).rgb;
+            vec3 high = textureLod(lut, vec2(x + slice1 * n / size.x, y), 0.0).rgb;
+            vec3 graded = mix(low, high, blue - slice0);
+            fragColor = vec4(mix(color, graded, strength), 1.0);
+        }
+"#;
+
+const VIGNETTE_FRAGMENT_SHADER_SOURCE: &str = r#"
+        in vec2 vUv;
+        out vec4 fragColor;
+
+        uniform sampler2D source;
+        uniform float intensity;
+        uniform float smoothness;
+
+        void main() {
+            vec2 size = vec2(textureSize(source, 0));
+            float aspect = size.x / max(size.y, 1.0);
+            vec2 offset = (vUv - 0.5) * vec2(aspect, 1.0);
+            float corner = sqrt(0.25 * aspect * aspect + 0.25);
+            float r = length(offset) / corner;
+            float factor = 1.0 - intensity * smoothstep(1.0 - smoothness, 1.0, r);
+            fragColor = vec4(texture(source, vUv).rgb * factor, 1.0);
+        }
+"#;
+
+// The classic single-pass FXAA (Lottes' "lite" variant): find the luma edge
+// direction from the 2x2 diagonal neighbours, blur along it, and keep the
+// wider blur unless it strays outside the local luma range. The constants
+// match `FXAA_REDUCE_MIN`, `FXAA_REDUCE_MUL` and `FXAA_SPAN_MAX`.
+const FXAA_FRAGMENT_SHADER_SOURCE: &str = r#"
+        in vec2 vUv;
+        out vec4 fragColor;
+
+        uniform sampler2D source;
+
+        const float REDUCE_MIN = 1.0 / 128.0;
+        const float REDUCE_MUL = 1.0 / 8.0;
+        const float SPAN_MAX = 8.0;
+
+        float luma(vec3 color) {
+            return dot(color, vec3(0.299, 0.587, 0.114));
+        }
+
+        void main() {
+            vec2 texel = 1.0 / vec2(textureSize(source, 0));
+            float lumaNW = luma(texture(source, vUv + vec2(-1.0, -1.0) * texel).rgb);
+            float lumaNE = luma(texture(source, vUv + vec2(1.0, -1.0) * texel).rgb);
+            float lumaSW = luma(texture(source, vUv + vec2(-1.0, 1.0) * texel).rgb);
+            float lumaSE = luma(texture(source, vUv + vec2(1.0, 1.0) * texel).rgb);
+            vec3 center = texture(source, vUv).rgb;
+            float lumaM = luma(center);
+            float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+            float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+            vec2 dir = vec2(-((lumaNW + lumaNE) - (lumaSW + lumaSE)),
+                            (lumaNW + lumaSW) - (lumaNE + lumaSE));
+            float reduce = max((lumaNW + lumaNE + lumaSW + lumaSE) * 0.25 * REDUCE_MUL,
+                               REDUCE_MIN);
+            float scale = 1.0 / (min(abs(dir.x), abs(dir.y)) + reduce);
+            dir = clamp(dir * scale, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * texel;
+
+            vec3 narrow = 0.5 * (texture(source, vUv + dir * (1.0 / 3.0 - 0.5)).rgb
+                             + texture(source, vUv + dir * (2.0 / 3.0 - 0.5)).rgb);
+            vec3 wide = narrow * 0.5 + 0.25 * (texture(source, vUv - dir * 0.5).rgb
+                                           + texture(source, vUv + dir * 0.5).rgb);
+            float lumaWide = luma(wide);
+            fragColor = vec4((lumaWide < lumaMin || lumaWide > lumaMax) ? narrow : wide, 1.0);
+        }
+"#;
+
+// Depth comes packed by `DepthMaterial` (`packDepth`); `unpackDepth` is its
+// inverse, as in the lit shader's shadow lookup. `texelFetch`, not `texture`:
+// linear filtering would blend packed bytes. The taps and weights mirror
+// `dof_tap` and `dof_tap_weight`.
+const DEPTH_OF_FIELD_FRAGMENT_SHADER_SOURCE: &str = r#"
+        in vec2 vUv;
+        out vec4 fragColor;
+
+        uniform sampler2D source;
+        uniform sampler2D depth;
+        uniform float cameraNear;
+        uniform float cameraFar;
+        uniform float focusDistance;
+        uniform float focusRange;
+        uniform float maxBlur;
+
+        const int TAPS = 16;
+        const float GOLDEN_ANGLE = 2.39996;
+
+        float unpackDepth(vec4 rgba) {
+            return dot(rgba, vec4(1.0, 1.0 / 255.0, 1.0 / 65025.0, 1.0 / 16581375.0));
+        }
+
+        float circleOfConfusion(vec2 uv) {
+            ivec2 size = textureSize(depth, 0);
+            ivec2 texel = clamp(ivec2(uv * vec2(size)), ivec2(0), size - 1);
+            float z = unpackDepth(texelFetch(depth, texel, 0)) * 2.0 - 1.0;
+            float d = 2.0 * cameraNear * cameraFar
+                / (cameraFar + cameraNear - z * (cameraFar - cameraNear));
+            return clamp(abs(d - focusDistance) / max(focusRange, 1e-4), 0.0, 1.0) * maxBlur;
+        }
+
+        void main() {
+            vec2 texel = 1.0 / vec2(textureSize(source, 0));
+            float radius = circleOfConfusion(vUv);
+            vec3 sum = texture(source, vUv).rgb;
+            float total = 1.0;
+            for (int i = 0; i < TAPS; i++) {
+                float r = sqrt((float(i) + 0.5) / float(TAPS));
+                float angle = float(i) * GOLDEN_ANGLE;
+                vec2 uv = vUv + vec2(cos(angle), sin(angle)) * r * radius * texel;
+                float weight = clamp(circleOfConfusion(uv) - r * radius + 1.0, 0.0, 1.0);
+                sum += texture(source, uv).rgb * weight;
+                total += weight;
+            }
+            fragColor = vec4(sum / total, 1.0);
+        }
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn bright_pass_keeps_only_what_exceeds_the_threshold() {
+        assert_eq!(bright_pass([0.5, 0.4, 0.1], 0.6), [0.0, 0.0, 0.0]);
+        let kept = bright_pass([1.0, 0.5, 0.0], 0.5);
+        assert!(close(kept[0], 0.5) && close(kept[1], 0.25) && kept[2] == 0.0);
+        // Saturated colors bloom on their brightest channel, not their luma.
+        assert!(bright_pass([0.0, 0.0, 1.0], 0.8)[2] > 0.0);
+    }
+
+    #[test]
+    fn tonemap_curves_are_monotonic_and_bounded() {
+        for curve in [Tonemap::Aces, Tonemap::Filmic] {
+            let mut previous = -1.0;
+            for step in 0..=40 {
+                let [mapped, ..] = tonemap([step as f32 * 0.25, 0.0, 0.0], curve, 1.0);
+                assert!(mapped >= previous, "{curve:?} decreases at step {step}");
+                assert!((0.0..=1.0).contains(&mapped));
+                previous = mapped;
+            }
+            assert!(close(tonemap([0.0; 3], curve, 1.0)[0], 0.0));
+        }
+        assert!(close(filmic(FILMIC_WHITE / 2.0), 1.0));
+        // Exposure brightens before the curve.
+        assert!(
+            tonemap([0.2; 3], Tonemap::Aces, 2.0)[0] > tonemap([0.2; 3], Tonemap::Aces, 1.0)[0]
+        );
+    }
+
+    #[test]
+    fn vignette_darkens_the_corners_and_leaves_the_center() {
+        assert_eq!(vignette_factor([0.5, 0.5], 320.0, 240.0, 0.6, 0.5), 1.0);
+        assert!(close(
+            vignette_factor([0.0, 0.0], 320.0, 240.0, 0.6, 0.5),
+            0.4
+        ));
+        assert!(close(
+            vignette_factor([1.0, 1.0], 320.0, 240.0, 0.6, 0.5),
+            0.4
+        ));
+        let edge = vignette_factor([1.0, 0.5], 320.0, 240.0, 0.6, 0.5);
+        assert!(edge > 0.4 && edge < 1.0);
+    }
+
+    #[test]
+    fn lut_lookup_addresses_texel_centers_of_the_bracketing_slices() {
+        // A 4-slice strip (16 x 4): black reads slice 0's first texel.
+        let (low, high, t) = lut_lookup([0.0, 0.0, 0.0], 16.0, 4.0);
+        assert_eq!(
+            (low, high, t),
+            ([0.5 / 16.0, 0.5 / 4.0], [4.5 / 16.0, 0.5 / 4.0], 0.0)
+        );
+        // White reads the last texel of the last slice, with nothing past it.
+        let (low, high, t) = lut_lookup([1.0, 1.0, 1.0], 16.0, 4.0);
+        assert_eq!(
+            (low, high, t),
+            ([15.5 / 16.0, 3.5 / 4.0], [15.5 / 16.0, 3.5 / 4.0], 0.0)
+        );
+        // Blue between slices 1 and 2.
+        let (low, high, t) = lut_lookup([0.0, 0.0, 0.5], 16.0, 4.0);
+        assert_eq!((low[0], high[0]), (4.5 / 16.0, 8.5 / 16.0));
+        assert!(close(t, 0.5));
+    }
+
+    #[test]
+    fn circle_of_confusion_grows_away_from_the_focal_plane() {
+        assert_eq!(circle_of_confusion(10.0, 10.0, 4.0, 8.0), 0.0);
+        assert_eq!(circle_of_confusion(12.0, 10.0, 4.0, 8.0), 4.0);
+        assert_eq!(circle_of_confusion(2.0, 10.0, 4.0, 8.0), 8.0);
+        assert_eq!(circle_of_confusion(100.0, 10.0, 4.0, 8.0), 8.0);
+    }
+
+    #[test]
+    fn linear_depth_inverts_the_perspective_depth() {
+        let (near, far) = (0.1, 100.0);
+        assert!(close(linear_depth(0.0, near, far), near));
+        assert!((linear_depth(1.0, near, far) - far).abs() < 1e-2);
+        let camera = crate::Camera::default();
+        let projection = camera.projection_matrix(1.0);
+        let clip = projection * cgmath::Vector4::new(0.0, 0.0, -7.5, 1.0);
+        let window_z = (clip.z / clip.w) * 0.5 + 0.5;
+        assert!((linear_depth(window_z, camera.near, camera.far) - 7.5).abs() < 1e-2);
+    }
+
+    #[test]
+    fn dof_taps_cover_the_unit_disc() {
+        let taps: Vec<_> = (0..DOF_TAPS).map(dof_tap).collect();
+        assert!(taps.iter().all(|(_, r)| *r > 0.0 && *r < 1.0));
+        assert!(taps.iter().any(|([x, _], _)| *x < -0.5));
+        assert!(taps.iter().any(|([x, _], _)| *x > 0.5));
+        assert_eq!(dof_tap_weight(4.0, 2.0), 1.0);
+        assert_eq!(dof_tap_weight(0.0, 2.0), 0.0);
+    }
+
+    #[test]
+    fn blur_and_fxaa_leave_a_flat_image_flat() {
+        let flat = |_: [f32; 2]| [0.25, 0.5, 0.75];
+        assert!(close(
+            GAUSSIAN_WEIGHTS[0] + 2.0 * GAUSSIAN_WEIGHTS[1..].iter().sum::<f32>(),
+            1.0
+        ));
+        let blurred = gaussian_blur(flat, [0.5, 0.5], [0.1, 0.0]);
+        assert!(blurred
+            .iter()
+            .zip(flat([0.0; 2]))
+            .all(|(a, b)| close(*a, b)));
+        assert_eq!(fxaa(flat, [0.5, 0.5], [0.1, 0.1]), [0.25, 0.5, 0.75]);
+        // A straight axis-aligned edge has no stairs to smooth: FXAA blurs
+        // along it, so both sides keep their color.
+        let edge = |[u, _]: [f32; 2]| if u < 0.5 { [0.0; 3] } else { [1.0; 3] };
+        assert_eq!(fxaa(edge, [0.55, 0.5], [0.1, 0.1]), [1.0; 3]);
+        assert_eq!(fxaa(edge, [0.45, 0.5], [0.1, 0.1]), [0.0; 3]);
+    }
+
+    #[test]
+    fn every_stage_declares_the_uniforms_its_shader_uses() {
+        for stage in [
+            PostStage::BrightPass,
+            PostStage::Blur,
+            PostStage::BloomCombine,
+            PostStage::Tonemap,
+            PostStage::ColorGrade,
+            PostStage::Vignette,
+            PostStage::Fxaa,
+            PostStage::DepthOfField,
+        ] {
+            let source = stage.fragment_source();
+            for sampler in stage.samplers() {
+                assert!(source.contains(&format!("uniform sampler2D {sampler};")));
+            }
+            for uniform in stage.uniforms() {
+                assert!(source.contains(&format!("uniform float {uniform};")));
+            }
+            let declared = source.matches("uniform ").count();
+            assert_eq!(declared, stage.samplers().len() + stage.uniforms().len());
+        }
+        assert!(BLUR_FRAGMENT_SHADER_SOURCE.contains(
+            &GAUSSIAN_WEIGHTS
+                .iter()
+                .map(f32::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+}
@@ -71,6 +71,26 @@
 /// for now — nothing transmits or checks it; [`GameProducer`] impls all speak
 /// the current version.
 ///
+/// v20: post-processing — [`crate::Frame::post`], the ordered
+/// [`crate::PostEffect`] stack (bloom, tonemapping, LUT grading, vignette,
+/// FXAA, depth of field). Omitted while empty, so frames without one keep
+/// their v19 shape.
+///
+/// v19: metallic-roughness materials — [`crate::MaterialDescription::Pbr`]
+/// (glTF factors, alpha mode and double-sidedness, plus an optional texture
+/// per slot). A new variant, so frames without one keep their v18 shape.
+///
+/// v18: image-based lighting — [`crate::Frame::environment`] (a skybox or
+/// equirect source plus intensity) and [`crate::Frame::reflection_probes`]
+/// (probe positions). Both are omitted while unset, so frames that use
+/// neither keep their v17 shape.
+///
+/// v17: shadow layouts — [`crate::ShadowSettings`] (`shadow`: per-view
+/// resolution, directional cascade count and distance) on the casting
+/// [`crate::Light`] variants. Defaulted, and omitted while default, so only
+/// lights tuned through `Light.castShadowsAt` / `Light.castCascadedShadows`
+/// change shape.
+///
 /// v16: camera-facing billboards — the `SceneObject::Geometry(Shape::Billboard)`
 /// variant (a unit quad whose local XY maps to the active pass's camera
 /// right/up at draw time). Emitted only by `Scene.billboard`, so frames
@@ -129,7 +149,7 @@
 /// omitted when empty, so v1 frames read back and chainless frames stay v1-
 /// shaped) and the `TextureDescription::FileWhilePending` variant (a v1
 /// reader cannot decode a frame carrying one).
-pub const PROTOCOL_VERSION: u32 = 16;
+pub const PROTOCOL_VERSION: u32 = 20;
 
 /// The producer side of the protocol: one game logic instance as consumed by a
 /// runtime shell's frame loop. Every method carries a payload enumerated in
@@ -456,6 +476,37 @@ pub trait GameProducer {
         "{\"paused\":false,\"sources\":[],\"invocations\":[]}".to_string()
     }
 
+    /// Answer one source-level debugger command (`POST /debug`; see
+    /// [`crate::functor_lang_debug`]) with its reply and what the shell's
+    /// clock should do. The default refuses — only the interpreter producer
+    /// has source to stop in.
+    fn debug(
+        &mut self,
+        _request: &serde_json::Value,
+    ) -> Result<(serde_json::Value, crate::functor_lang_debug::DebugClock), String> {
+        Err("this runtime has no source-level debugger".to_string())
+    }
+
+    /// Called after each real frame: replay it under the debugger's
+    /// breakpoints. True when it stopped inside the frame — the shell then
+    /// pauses its clock until a `continue` runs the frame out.
+    fn debug_frame(&mut self) -> bool {
+        false
+    }
+
+    /// Arm the hook profiler (`--profile`, `GET /profile`; see
+    /// [`functor_lang::profile`]) if it is not already running. The default
+    /// refuses — only the interpreter producer has hooks to profile.
+    fn start_profiling(&mut self) -> Result<(), String> {
+        Err("this runtime has no hook profiler".to_string())
+    }
+
+    /// Everything the profiler aggregated since [`Self::start_profiling`],
+    /// rendered in `format`; `Err` while it is not running.
+    fn profile(&self, _format: functor_lang::profile::ProfileFormat) -> Result<String, String> {
+        Err("the hook profiler is not running".to_string())
+    }
+
     /// The shell delivered debug-injected input (`POST /input`) while the clock
     /// is PAUSED (visual-debugger PR2): no `tick` will run to sweep the
     /// journaled entry-point calls into the last-frame journal, so the producer
@@ -643,7 +694,7 @@ mod tests {
     fn sprite_atlas_material_wire_is_pinned() {
         use crate::{MaterialDescription, SpriteSampling, TextureDescription};
 
-        assert_eq!(PROTOCOL_VERSION, 16);
+        assert_eq!(PROTOCOL_VERSION, 20);
         let material = MaterialDescription::sprite_texture_tinted(
             TextureDescription::FileClamped("hero-atlas.png".to_string()),
             Some([96.0, 0.0, 96.0, 96.0]),
@@ -670,7 +721,7 @@ mod tests {
     fn convex_polygon_geometry_wire_is_pinned() {
         use crate::{Scene3D, SceneObject, Shape};
 
-        assert_eq!(PROTOCOL_VERSION, 16);
+        assert_eq!(PROTOCOL_VERSION, 20);
         let scene = Scene3D {
             obj: SceneObject::Geometry(Shape::ConvexPolygon {
                 points: vec![[0.0, 0.0], [2.0, 0.0], [1.0, 1.5]],
@@ -693,7 +744,7 @@ mod tests {
     fn billboard_geometry_wire_is_pinned() {
         use crate::{SceneObject, Shape};
 
-        assert_eq!(PROTOCOL_VERSION, 16);
+        assert_eq!(PROTOCOL_VERSION, 20);
         let obj = SceneObject::Geometry(Shape::Billboard);
         let json = serde_json::to_string(&obj).expect("serialize billboard geometry");
         assert_eq!(json, r#"{"Geometry":"Billboard"}"#);
@@ -708,7 +759,7 @@ mod tests {
     fn opacity_subtree_wire_is_pinned() {
         use crate::{Scene3D, SceneObject, Shape};
 
-        assert_eq!(PROTOCOL_VERSION, 16);
+        assert_eq!(PROTOCOL_VERSION, 20);
         let scene = SceneObject::Opacity(
             0.35,
             vec![Scene3D {
@@ -732,7 +783,7 @@ mod tests {
     fn instanced_wire_is_pinned() {
         use crate::{InstanceData, MaterialDescription, Scene3D, SceneObject};
 
-        assert_eq!(PROTOCOL_VERSION, 16);
+        assert_eq!(PROTOCOL_VERSION, 20);
         let template = Scene3D {
             obj: SceneObject::Material(
                 MaterialDescription::lit(1.0, 0.5, 0.25, 1.0),
@@ -758,11 +809,117 @@ mod tests {
         assert_eq!(back, scene.obj);
     }
 
+    /// Tuned shadow layouts ride on the light; untuned lights keep the v16
+    /// shape, which this pins too.
+    #[test]
+    fn light_shadow_settings_wire_is_pinned() {
+        use crate::{Light, ShadowSettings};
+
+        assert_eq!(PROTOCOL_VERSION, 20);
+        let plain = Light::point(0.0, 2.0, 0.0, 1.0, 1.0, 1.0, 1.0, 8.0).cast_shadows();
+        let json = serde_json::to_string(&plain).expect("serialize plain light");
+        assert_eq!(
+            json,
+            r#"{"Point":{"position":[0.0,2.0,0.0],"color":[1.0,1.0,1.0],"intensity":1.0,"range":8.0,"casts_shadows":true}}"#
+        );
+        let tuned = Light::directional(0.0, -1.0, 0.0, 1.0, 1.0, 1.0, 1.0).cast_shadows_with(
+            ShadowSettings {
+                resolution: 1024,
+                cascades: 3,
+                distance: 80.0,
+            },
+        );
+        let json = serde_json::to_string(&tuned).expect("serialize tuned light");
+        assert_eq!(
+            json,
+            r#"{"Directional":{"direction":[0.0,-1.0,0.0],"color":[1.0,1.0,1.0],"intensity":1.0,"casts_shadows":true,"shadow":{"resolution":1024,"cascades":3,"distance":80.0}}}"#
+        );
+        let back: Light = serde_json::from_str(&json).expect("deserialize tuned light");
+        assert_eq!(back, tuned);
+    }
+
+    #[test]
+    fn environment_wire_is_pinned() {
+        use crate::environment::{Environment, EnvironmentSource, ReflectionProbe};
+        use crate::skybox::SkyboxDescription;
+
+        assert_eq!(PROTOCOL_VERSION, 20);
+        let equirect = Environment {
+            source: EnvironmentSource::Equirect("studio.hdr".to_string()),
+            intensity: 1.5,
+        };
+        assert_eq!(
+            serde_json::to_string(&equirect).expect("serialize equirect environment"),
+            r#"{"source":{"Equirect":"studio.hdr"},"intensity":1.5}"#
+        );
+        let sky = Environment {
+            source: EnvironmentSource::Skybox(SkyboxDescription::new(
+                "px", "nx", "py", "ny", "pz", "nz",
+            )),
+            intensity: 1.0,
+        };
+        let json = serde_json::to_string(&sky).expect("serialize skybox environment");
+        assert_eq!(
+            json,
+            r#"{"source":{"Skybox":{"px":"px","nx":"nx","py":"py","ny":"ny","pz":"pz","nz":"nz"}},"intensity":1.0}"#
+        );
+        assert_eq!(serde_json::from_str::<Environment>(&json).unwrap(), sky);
+        let probe = ReflectionProbe {
+            position: [1.0, 2.0, 3.0],
+        };
+        assert_eq!(
+            serde_json::to_string(&probe).expect("serialize probe"),
+            r#"{"position":[1.0,2.0,3.0]}"#
+        );
+    }
+
+    #[test]
+    fn pbr_material_wire_is_pinned() {
+        use crate::pbr::{AlphaMode, PbrDescription};
+        use crate::{MaterialDescription, TextureDescription};
+
+        assert_eq!(PROTOCOL_VERSION, 20);
+        let mut pbr = PbrDescription::new([1.0, 0.5, 0.25, 1.0], 1.0, 0.5);
+        pbr.factors.alpha_mode = AlphaMode::Mask(0.5);
+        pbr.normal_texture = Some(TextureDescription::File("normal.png".to_string()));
+        let material = MaterialDescription::Pbr(Box::new(pbr));
+        let json = serde_json::to_string(&material).expect("serialize pbr material");
+        assert_eq!(
+            json,
+            r#"{"Pbr":{"factors":{"base_color":[1.0,0.5,0.25,1.0],"metallic":1.0,"roughness":0.5,"emissive":[0.0,0.0,0.0],"normal_scale":1.0,"occlusion_strength":1.0,"alpha_mode":{"Mask":0.5},"double_sided":true},"base_color_texture":null,"metallic_roughness_texture":null,"normal_texture":{"File":"normal.png"},"occlusion_texture":null,"emissive_texture":null}}"#
+        );
+        let back: MaterialDescription = serde_json::from_str(&json).expect("deserialize pbr");
+        assert_eq!(back, material);
+    }
+
+    #[test]
+    fn post_wire_is_pinned() {
+        use crate::post::PostEffect;
+        use crate::TextureDescription;
+
+        assert_eq!(PROTOCOL_VERSION, 20);
+        let post = vec![
+            PostEffect::bloom(0.7, 1.5),
+            PostEffect::aces(1.2),
+            PostEffect::color_grade(TextureDescription::File("lut.png".to_string()), 0.8),
+            PostEffect::vignette(0.4, 0.5),
+            PostEffect::Fxaa,
+            PostEffect::depth_of_field(6.0, 4.0, 8.0),
+        ];
+        let json = serde_json::to_string(&post).expect("serialize post");
+        assert_eq!(
+            json,
+            r#"[{"Bloom":{"threshold":0.7,"intensity":1.5}},{"Tonemap":{"curve":"Aces","exposure":1.2}},{"ColorGrade":{"lut":{"File":"lut.png"},"strength":0.8}},{"Vignette":{"intensity":0.4,"smoothness":0.5}},"Fxaa",{"DepthOfField":{"focus_distance":6.0,"focus_range":4.0,"max_blur":8.0}}]"#
+        );
+        let back: Vec<PostEffect> = serde_json::from_str(&json).expect("deserialize post");
+        assert_eq!(back, post);
+    }
+
     #[test]
     fn two_bone_reach_animation_wire_is_pinned() {
         use crate::anim::AnimExpr;
 
-        assert_eq!(PROTOCOL_VERSION, 16);
+        assert_eq!(PROTOCOL_VERSION, 20);
         let reach = AnimExpr::Reach {
             root: "upper".to_string(),
             middle: "lower".to_string(),
@@ -875,7 +1032,15 @@ mod tests {
             skybox: Some(crate::skybox::SkyboxDescription::new(
                 "px.jpg", "nx.jpg", "py.jpg", "ny.jpg", "pz.jpg", "nz.jpg",
             )),
+            environment: Some(crate::environment::Environment {
+                source: crate::environment::EnvironmentSource::Equirect("sky.hdr".to_string()),
+                intensity: 0.8,
+            }),
+            reflection_probes: vec![crate::environment::ReflectionProbe {
+                position: [0.0, 1.0, 0.0],
+            }],
             clear_color: Some([0.2, 0.4, 0.6]),
+            post: vec![crate::PostEffect::bloom(0.7, 1.5), crate::PostEffect::Fxaa],
             sprite_layers: vec![],
             pure_2d: false,
         };
@@ -893,7 +1058,10 @@ mod tests {
         assert!(legacy.ui_targets.is_empty());
         assert!(legacy.fog.is_none());
         assert!(legacy.skybox.is_none());
+        assert!(legacy.environment.is_none());
+        assert!(legacy.reflection_probes.is_empty());
         assert!(legacy.clear_color.is_none());
+        assert!(legacy.post.is_empty());
         assert!(legacy.sprite_layers.is_empty());
     }
 
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use cgmath::{Matrix4, Vector3};
 
+use crate::shadow::MAX_SHADOW_TILES;
 use crate::{asset::AssetCache, fog::Fog, FrameTime, Light};
 
 /// Which rendering pass is in flight. `DepthOnly` (e.g. filling a shadow map)
@@ -14,18 +15,52 @@ pub enum RenderPass {
     DepthOnly,
 }
 
-/// Shadow data made available to the forward pass: the directional light's depth
-/// map and the matrix that projects a world position into that light's clip
-/// space (for sampling the map).
+/// Shadow data made available to the forward pass: the casting light's depth
+/// atlas, and per view (tile) the matrix that projects a world position into
+/// that view's clip space plus where the view lives in the atlas.
 #[derive(Clone, Copy)]
 pub struct ShadowUniforms {
     pub depth_texture: glow::Texture,
-    pub light_space_matrix: Matrix4<f32>,
+    /// Each view's world→light-clip matrix, flattened column-major (16 floats
+    /// per view, the first `tile_count` set).
+    pub light_space_matrices: [f32; 16 * MAX_SHADOW_TILES],
+    /// Each view's atlas rectangle in uv: `[x, y, width, height]` per view.
+    pub tile_rects: [f32; 4 * MAX_SHADOW_TILES],
+    /// How many views the light rendered: 1, one per cascade, or 6 cube faces.
+    pub tile_count: i32,
     /// Index (into the packed light array) of the light that cast this map, so
     /// the lit shader applies the shadow to that light's contribution only.
     pub light_index: i32,
 }
 
+/// A prefiltered environment uploaded for the lit shaders: the specular cube
+/// (bound to `environment::ENVIRONMENT_TEXTURE_UNIT`) and its SH irradiance.
+#[derive(Clone, Copy)]
+pub struct EnvironmentUniforms {
+    pub specular_texture: glow::Texture,
+    /// The specular cube's last mip level (its roughest).
+    pub max_lod: f32,
+    /// Nine SH irradiance coefficients, three floats each.
+    pub irradiance: [f32; 27],
+    pub intensity: f32,
+}
+
+/// The image-based light of one pass: the frame environment plus any baked
+/// reflection probes by world position.
+#[derive(Default)]
+pub struct EnvironmentLighting {
+    pub environment: Option<EnvironmentUniforms>,
+    pub probes: Vec<(Vector3<f32>, EnvironmentUniforms)>,
+}
+
+impl EnvironmentLighting {
+    /// What a draw anchored at `position` samples: its nearest probe, else
+    /// the frame environment.
+    pub fn at(&self, position: Vector3<f32>) -> Option<&EnvironmentUniforms> {
+        crate::environment::nearest_probe(&self.probes, position).or(self.environment.as_ref())
+    }
+}
+
 /// Global override for how the scene is presented — a debug aid, not a
 /// per-material choice. `Default` uses each node's own material. Normal and
 /// tangent modes replace it with a diagnostic shader; transparent mode keeps
@@ -170,6 +205,10 @@ pub struct RenderContext<'a> {
     /// The directional shadow map + light matrix, when shadows are active.
     /// `None` during the depth pass and when no light casts shadows.
     pub shadow: Option<ShadowUniforms>,
+    /// The pass's environment and reflection probes (`Frame.withEnvironment`
+    /// / `Frame.withReflectionProbe`). `None` during depth passes and frames
+    /// without image-based light.
+    pub environment: Option<&'a EnvironmentLighting>,
     /// Frame-level distance fog (from `Frame.fog`), applied by every forward
     /// material. `None` during depth passes and fog-less frames. The
     /// normals/tangents debug materials ignore it (no fog block in their
@@ -4,16 +4,23 @@ use cgmath::{Matrix4, SquareMatrix};
 use glow::HasContext;
 
 use crate::asset::AssetCache;
-use crate::material::BasicMaterial;
+use crate::environment::{self, CubeImage, PrefilteredEnvironment, ReflectionProbe, PROBE_SIZE};
+use crate::material::{BasicMaterial, DepthMaterial};
+use crate::post;
+use crate::render_target::RenderTargetDescriptor;
+use crate::scene3d::{PostInputs, POST_DEPTH_TARGET_ID, POST_SCENE_TARGET_ID};
 use crate::shadow::{self, ShadowMap};
 use crate::{
-    Camera, Camera2D, DebugRenderMode, Frame, FrameTime, Light, OpacityStage, RenderContext,
-    RenderPass, Scene3D, SceneContext, ShadowUniforms, TransparentDraw, Viewport,
+    Camera, Camera2D, DebugRenderMode, EnvironmentLighting, Frame, FrameTime, Light, OpacityStage,
+    RenderContext, RenderPass, Scene3D, SceneContext, ShadowUniforms, TransparentDraw, Viewport,
 };
 
+/// The offscreen target reflection probes bake their faces through.
+const PROBE_TARGET_ID: &str = "__reflection_probe";
+
 /// Enough authored color to keep the scene readable while opaque diagnostic
 /// lines remain dominant through it.
-const TRANSPARENT_DEBUG_ALPHA: f32 = 0.2;
+pub(crate) const TRANSPARENT_DEBUG_ALPHA: f32 = 0.2;
 
 /// Render one `Frame` to the currently-bound (default) framebuffer.
 ///
@@ -30,9 +37,17 @@ const TRANSPARENT_DEBUG_ALPHA: f32 = 0.2;
 ///    its own lights' shadows). Double-buffered: the main pass samples the image
 ///    written *this* frame; a target sampling itself sees *last* frame's.
 /// 2. Shadow pass — render the scene into `shadow_map` from the first
-///    shadow-casting light (directional or spot), producing `ShadowUniforms`.
-/// 3. Forward pass — clear, then `Scene3D::render` with the lights + shadow map.
-/// 4. Sprite passes — ordered orthographic, alpha-blended layers above 3D.
+///    shadow-casting light (each of its views: the fixed box, directional
+///    cascades fitted to the frame's camera, a spot frustum, or a point
+///    light's six cube faces), producing `ShadowUniforms`.
+/// 3. Forward pass — clear, then `Scene3D::render` with the lights + shadow map
+///    and the frame's image-based light (environment and reflection probes,
+///    baking any probe that is new — see [`environment_lighting`]).
+/// 4. Post stack — when the frame has one, the forward pass renders into an
+///    offscreen target at the viewport's size instead, and the frame's
+///    `PostEffect`s run over it in order (plus a depth pass of the scene when
+///    depth of field asks for one) before the result lands in the pane.
+/// 5. Sprite passes — ordered orthographic, alpha-blended layers above 3D.
 ///
 /// Known MVP cost: a shell that calls `render_frame` more than once per game
 /// frame (stereo, one call per eye) re-renders the target passes each call —
@@ -229,7 +244,26 @@ are ignored — declare Frame.withUiTarget on the main frame",
                 ),
             );
         }
+        if !pass.frame.post.is_empty() {
+            scene_context.warn_once(
+                &format!("nested-post:{}", pass.target.id),
+                &format!(
+                    "[render-target] \"{}\": Frame.withPost inside a target \
+frame is ignored — post-processing applies to the main frame only",
+                    pass.target.id
+                ),
+            );
+        }
 
+        let lighting = environment_lighting(
+            gl,
+            shader_version,
+            asset_cache.clone(),
+            scene_context,
+            shadow_map,
+            &pass.frame,
+            frame_time,
+        );
         let shadow = shadow_pass(
             gl,
             shader_version,
@@ -239,6 +273,8 @@ are ignored — declare Frame.withUiTarget on the main frame",
             &pass.frame.scene,
             scene_context,
             shadow_map,
+            &pass.frame.camera,
+            pass.target.width.max(1) as f32 / pass.target.height.max(1) as f32,
         );
 
         // ensure_render_target above guarantees the entry exists. The handles
@@ -270,6 +306,7 @@ are ignored — declare Frame.withUiTarget on the main frame",
             shadow,
             pass.frame.fog.as_ref(),
             pass.frame.skybox.as_ref(),
+            Some(&lighting),
             width as f32 / height as f32,
             height as f32,
             &pass.frame.camera,
@@ -295,6 +332,18 @@ are ignored — declare Frame.withUiTarget on the main frame",
         scene_context.finish_render_target_write(&pass.target.id);
     }
 
+    let lighting = environment_lighting(
+        gl,
+        shader_version,
+        asset_cache.clone(),
+        scene_context,
+        shadow_map,
+        frame,
+        frame_time,
+    );
+
+    // Cascades fit the frame's culling camera rather than the per-eye one, so
+    // both stereo eyes sample the same splits.
     let shadow = shadow_pass(
         gl,
         shader_version,
@@ -304,6 +353,8 @@ are ignored — declare Frame.withUiTarget on the main frame",
         &frame.scene,
         scene_context,
         shadow_map,
+        lod_view.map_or(&frame.camera, |(camera, _, _, _)| camera),
+        viewport.aspect(),
     );
 
     // Main (forward) pass into the bound framebuffer, at the viewport's
@@ -330,6 +381,29 @@ are ignored — declare Frame.withUiTarget on the main frame",
         gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
     }
 
+    // A post stack reads the 3D pass as a texture: render it offscreen at the
+    // pane's size (same aspect and height, so LOD and projection match).
+    let post_stack = !frame.post.is_empty();
+    if post_stack {
+        let target = RenderTargetDescriptor {
+            id: POST_SCENE_TARGET_ID.to_string(),
+            width: viewport.width,
+            height: viewport.height,
+        };
+        scene_context.ensure_render_target(gl, &target, frame.resolved_clear_color());
+        let (fbo, width, height) = scene_context
+            .render_target_write(POST_SCENE_TARGET_ID)
+            .expect("post scene target was just ensured");
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+            gl.viewport(0, 0, width as i32, height as i32);
+            gl.disable(glow::SCISSOR_TEST);
+            let [r, g, b] = frame.resolved_clear_color();
+            gl.clear_color(r, g, b, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+        }
+    }
+
     forward_pass(
         gl,
         shader_version,
@@ -343,6 +417,7 @@ are ignored — declare Frame.withUiTarget on the main frame",
         shadow,
         frame.fog.as_ref(),
         frame.skybox.as_ref(),
+        Some(&lighting),
         viewport.aspect(),
         viewport.height as f32,
         lod_view.map_or(&frame.camera, |(camera, _, _, _)| camera),
@@ -353,6 +428,42 @@ are ignored — declare Frame.withUiTarget on the main frame",
         false,
     );
 
+    if post_stack {
+        scene_context.finish_render_target_write(POST_SCENE_TARGET_ID);
+        let scene = scene_context
+            .render_target_read_texture(POST_SCENE_TARGET_ID)
+            .expect("post scene target was just ensured");
+        let depth = post::needs_depth(&frame.post).then(|| {
+            post_depth_pass(
+                gl,
+                shader_version,
+                asset_cache.clone(),
+                frame_time,
+                scene_context,
+                &frame.scene,
+                camera,
+                &projection_matrix
+                    .copied()
+                    .unwrap_or_else(|| camera.projection_matrix(viewport.aspect())),
+                viewport,
+            )
+        });
+        scene_context.draw_post_stack(
+            gl,
+            shader_version,
+            &asset_cache,
+            &frame.post,
+            PostInputs {
+                scene,
+                depth,
+                near: camera.near,
+                far: camera.far,
+            },
+            previous_fbo,
+            viewport,
+        );
+    }
+
     render_sprite_layers(
         gl,
         shader_version,
@@ -431,6 +542,7 @@ fn render_sprite_layers(
             None,
             None,
             None,
+            None,
             fitted.aspect(),
             fitted.height as f32,
             &camera,
@@ -527,7 +639,6 @@ pub fn render_composited_frames_with_view(
     debug_render_mode: DebugRenderMode,
 ) {
     use crate::composite::{normalize_weights, MAX_COMPOSITE};
-    use crate::render_target::RenderTargetDescriptor;
 
     let n = frames.len().min(weights.len()).min(MAX_COMPOSITE);
     if n == 0 {
@@ -551,6 +662,15 @@ pub fn render_composited_frames_with_view(
             width: viewport.width,
             height: viewport.height,
         };
+        let lighting = environment_lighting(
+            gl,
+            shader_version,
+            asset_cache.clone(),
+            scene_context,
+            shadow_map,
+            frame,
+            *frame_time,
+        );
         scene_context.ensure_render_target(gl, &desc, clear);
 
         let shadow = shadow_pass(
@@ -562,6 +682,8 @@ pub fn render_composited_frames_with_view(
             &frame.scene,
             scene_context,
             shadow_map,
+            &frame.camera,
+            viewport.aspect(),
         );
 
         let (fbo, width, height) = scene_context
@@ -588,6 +710,7 @@ pub fn render_composited_frames_with_view(
             shadow,
             frame.fog.as_ref(),
             frame.skybox.as_ref(),
+            Some(&lighting),
             width as f32 / height as f32,
             height as f32,
             &frame.camera,
@@ -641,9 +764,10 @@ pub fn render_composited_frames_with_view(
 }
 
 /// Shadow pass: render `scene` into `shadow_map` from the first shadow-casting
-/// light (directional or spot), before a forward pass. Skinned casters come for
-/// free via the shared depth pass in `Scene3D::render`. Ends with the default
-/// framebuffer bound.
+/// light, before a forward pass. One light casts per pass — a second casting
+/// light renders unshadowed. `camera` and `aspect` are the view directional
+/// cascades fit. Skinned casters come for free via the shared depth pass in
+/// `Scene3D::render`. Ends with the default framebuffer bound.
 #[allow(clippy::too_many_arguments)]
 fn shadow_pass(
     gl: &glow::Context,
@@ -654,12 +778,14 @@ fn shadow_pass(
     scene: &Scene3D,
     scene_context: &SceneContext,
     shadow_map: &ShadowMap,
+    camera: &Camera,
+    aspect: f32,
 ) -> Option<ShadowUniforms> {
     lights
         .iter()
         .enumerate()
-        .find_map(|(i, l)| shadow::light_space_matrix(l).map(|m| (i, m)))
-        .map(|(light_index, light_space_matrix)| {
+        .find_map(|(i, l)| shadow::shadow_layout(l, camera, aspect).map(|layout| (i, layout)))
+        .map(|(light_index, layout)| {
             shadow::render_shadow_pass(
                 gl,
                 shader_version,
@@ -669,16 +795,233 @@ fn shadow_pass(
                 scene,
                 scene_context,
                 shadow_map,
-                light_space_matrix,
-            );
-            ShadowUniforms {
-                depth_texture: shadow_map.depth_texture,
-                light_space_matrix,
-                light_index: light_index as i32,
-            }
+                &layout,
+                light_index,
+            )
         })
 }
 
+/// Depth of the main pass's scene for the post stack, packed like a shadow
+/// map (`DepthMaterial`) into a target at the viewport's size and cleared to
+/// the far plane. Translucent subtrees are deferred and never drawn, as in
+/// the shadow pass. Leaves the target's framebuffer bound.
+#[allow(clippy::too_many_arguments)]
+fn post_depth_pass(
+    gl: &glow::Context,
+    shader_version: &str,
+    asset_cache: Arc<AssetCache>,
+    frame_time: FrameTime,
+    scene_context: &SceneContext,
+    scene: &Scene3D,
+    camera: &Camera,
+    projection_matrix: &Matrix4<f32>,
+    viewport: Viewport,
+) -> glow::Texture {
+    let target = RenderTargetDescriptor {
+        id: POST_DEPTH_TARGET_ID.to_string(),
+        width: viewport.width,
+        height: viewport.height,
+    };
+    scene_context.ensure_render_target(gl, &target, [1.0; 3]);
+    let (fbo, width, height) = scene_context
+        .render_target_write(POST_DEPTH_TARGET_ID)
+        .expect("post depth target was just ensured");
+    let camera_pos = cgmath::Vector3::new(camera.eye[0], camera.eye[1], camera.eye[2]);
+    let depth_ctx = RenderContext {
+        gl,
+        shader_version,
+        asset_cache,
+        frame_time,
+        debug_render_mode: DebugRenderMode::Default,
+        lights: &[],
+        render_pass: RenderPass::DepthOnly,
+        pass_blends: false,
+        blend_active: std::cell::Cell::new(false),
+        opacity_stage: OpacityStage::Defer,
+        opacity: std::cell::Cell::new(1.0),
+        shadow: None,
+        environment: None,
+        fog: None,
+        camera_pos,
+        lod_camera_pos: camera_pos,
+        lod_view_projections: [projection_matrix * camera.view_matrix(); 2],
+        lod_frustum_count: 1,
+        lod_projection_scale: projection_matrix.y.y.abs(),
+        viewport_height: height as f32,
+    };
+    let mut depth_material = DepthMaterial::create();
+    depth_material.initialize(&depth_ctx);
+    unsafe {
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+        gl.viewport(0, 0, width as i32, height as i32);
+        gl.disable(glow::SCISSOR_TEST);
+        gl.clear_color(1.0, 1.0, 1.0, 1.0);
+        gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+    }
+    scene.render(
+        &depth_ctx,
+        scene_context,
+        &Matrix4::identity(),
+        projection_matrix,
+        &camera.view_matrix(),
+        &depth_material,
+    );
+    scene_context.finish_render_target_write(POST_DEPTH_TARGET_ID);
+    scene_context
+        .render_target_read_texture(POST_DEPTH_TARGET_ID)
+        .expect("post depth target was just ensured")
+}
+
+/// The image-based light of `frame`'s passes: its environment (prefiltered
+/// and uploaded on first use) and its reflection probes. A probe not yet baked
+/// bakes here once the frame's assets have settled — a bake mid-load would
+/// capture fallbacks — and until then its draws use the environment.
+fn environment_lighting(
+    gl: &glow::Context,
+    shader_version: &str,
+    asset_cache: Arc<AssetCache>,
+    scene_context: &SceneContext,
+    shadow_map: &ShadowMap,
+    frame: &Frame,
+    frame_time: FrameTime,
+) -> EnvironmentLighting {
+    let mut lighting = EnvironmentLighting {
+        environment: frame.environment.as_ref().and_then(|environment| {
+            scene_context.environment_uniforms(gl, &asset_cache, environment)
+        }),
+        probes: vec![],
+    };
+    if frame.reflection_probes.is_empty() {
+        return lighting;
+    }
+    let intensity = frame
+        .environment
+        .as_ref()
+        .map_or(1.0, |environment| environment.intensity);
+    let progress = asset_cache.progress();
+    let settled = progress.loaded + progress.failed.len() >= progress.total;
+    for probe in &frame.reflection_probes {
+        let key = probe.key();
+        if settled && scene_context.reflection_probe(&key, intensity).is_none() {
+            let prefiltered = bake_reflection_probe(
+                gl,
+                shader_version,
+                asset_cache.clone(),
+                scene_context,
+                shadow_map,
+                frame,
+                frame_time,
+                probe,
+                &lighting,
+            );
+            scene_context.store_reflection_probe(gl, &key, &prefiltered);
+        }
+        if let Some(uniforms) = scene_context.reflection_probe(&key, intensity) {
+            lighting.probes.push((probe.position.into(), uniforms));
+        }
+    }
+    lighting
+}
+
+/// Render `frame` into the six faces of a cube around `probe` (each with its
+/// own shadow pass; sprite layers excluded), read them back and prefilter
+/// them. The capture is lit by the frame environment but by no probe, so
+/// probes never see each other. Restores the caller's framebuffer.
+#[allow(clippy::too_many_arguments)]
+fn bake_reflection_probe(
+    gl: &glow::Context,
+    shader_version: &str,
+    asset_cache: Arc<AssetCache>,
+    scene_context: &SceneContext,
+    shadow_map: &ShadowMap,
+    frame: &Frame,
+    frame_time: FrameTime,
+    probe: &ReflectionProbe,
+    lighting: &EnvironmentLighting,
+) -> PrefilteredEnvironment {
+    let previous_fbo = ambient_framebuffer(gl);
+    let desc = RenderTargetDescriptor {
+        id: PROBE_TARGET_ID.to_string(),
+        width: PROBE_SIZE,
+        height: PROBE_SIZE,
+    };
+    let clear = frame.resolved_clear_color();
+    scene_context.ensure_render_target(gl, &desc, clear);
+    let size = PROBE_SIZE as usize;
+    let mut faces: [Vec<u8>; 6] = Default::default();
+    for (face, pixels) in faces.iter_mut().enumerate() {
+        let camera = environment::probe_face_camera(
+            probe.position,
+            face,
+            frame.camera.near,
+            frame.camera.far,
+        );
+        let shadow = shadow_pass(
+            gl,
+            shader_version,
+            asset_cache.clone(),
+            frame_time,
+            &frame.lights,
+            &frame.scene,
+            scene_context,
+            shadow_map,
+            &camera,
+            1.0,
+        );
+        let (fbo, width, height) = scene_context
+            .render_target_write(PROBE_TARGET_ID)
+            .expect("probe render target was just ensured");
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+            gl.viewport(0, 0, width as i32, height as i32);
+            gl.disable(glow::SCISSOR_TEST);
+            let [r, g, b] = clear;
+            gl.clear_color(r, g, b, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+        }
+        forward_pass(
+            gl,
+            shader_version,
+            asset_cache.clone(),
+            scene_context,
+            &frame.scene,
+            &frame.lights,
+            &camera,
+            frame_time,
+            DebugRenderMode::Default,
+            shadow,
+            frame.fog.as_ref(),
+            frame.skybox.as_ref(),
+            Some(lighting),
+            1.0,
+            height as f32,
+            &camera,
+            None,
+            None,
+            None,
+            None,
+            false,
+        );
+        // Read back with row 0 at the bottom: with the probe cameras' up
+        // vectors that is the cube face's upload order.
+        *pixels = vec![0; size * size * 4];
+        unsafe {
+            gl.read_pixels(
+                0,
+                0,
+                size as i32,
+                size as i32,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(Some(pixels)),
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, previous_fbo);
+        }
+    }
+    let cube = CubeImage::from_rgba_faces(size, faces.each_ref().map(Vec::as_slice));
+    PrefilteredEnvironment::new(&cube, size)
+}
+
 /// The framebuffer currently bound — captured before an offscreen section and
 /// restored after it (the shadow-pass rule): XR shells render into per-eye
 /// swapchain FBOs, so resetting to the default would send the rest of the
@@ -756,6 +1099,7 @@ fn forward_pass(
     shadow: Option<ShadowUniforms>,
     fog: Option<&crate::fog::Fog>,
     skybox: Option<&crate::skybox::SkyboxDescription>,
+    environment: Option<&EnvironmentLighting>,
     aspect: f32,
     viewport_height: f32,
     lod_camera: &Camera,
@@ -792,6 +1136,7 @@ fn forward_pass(
         opacity_stage,
         opacity: std::cell::Cell::new(1.0),
         shadow,
+        environment,
         fog,
         camera_pos: cgmath::Vector3::new(camera.eye[0], camera.eye[1], camera.eye[2]),
         lod_camera_pos: cgmath::Vector3::new(
@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use glow::HasContext;
+
+use crate::{
+    asset::{AssetCache, AssetHandle, AssetPollState},
+    environment::{
+        CubeImage, Environment, EnvironmentSource, HdrImage, PrefilteredEnvironment,
+        MAX_SPECULAR_SIZE,
+    },
+    texture::TextureData,
+    EnvironmentUniforms,
+};
+
+use super::SceneContext;
+
+/// One environment source's progress through decode → prefilter → upload.
+pub(super) enum EnvironmentEntry {
+    /// Six pending skybox faces, in `SkyboxDescription::faces` order.
+    LoadingFaces(Vec<Arc<AssetHandle<TextureData>>>),
+    /// A pending equirect image.
+    LoadingEquirect(Arc<AssetHandle<HdrImage>>),
+    /// Prefiltered on the CPU; `texture` is filled by the first GL pass that
+    /// needs it (the software renderer never uploads).
+    Ready {
+        prefiltered: Arc<PrefilteredEnvironment>,
+        texture: Option<UploadedEnvironment>,
+    },
+    /// An input failed to load or validate; warned once, never retried.
+    Failed,
+}
+
+/// A prefiltered cube on the GPU, before a frame picks its intensity.
+#[derive(Clone, Copy)]
+pub(super) struct UploadedEnvironment {
+    texture: glow::Texture,
+    max_lod: f32,
+    irradiance: [f32; 27],
+}
+
+impl UploadedEnvironment {
+    fn upload(gl: &glow::Context, prefiltered: &PrefilteredEnvironment) -> UploadedEnvironment {
+        UploadedEnvironment {
+            texture: upload_prefiltered(gl, prefiltered),
+            max_lod: prefiltered.max_lod(),
+            irradiance: prefiltered.irradiance_uniform(),
+        }
+    }
+
+    fn uniforms(&self, intensity: f32) -> EnvironmentUniforms {
+        EnvironmentUniforms {
+            specular_texture: self.texture,
+            max_lod: self.max_lod,
+            irradiance: self.irradiance,
+            intensity,
+        }
+    }
+}
+
+impl SceneContext {
+    /// The prefiltered form of `source`, once its inputs have decoded. `None`
+    /// while they load (lit surfaces get no image-based light yet) or after a
+    /// failure (warned once, never retried). The prefilter runs once, on the
+    /// call that sees the last input arrive.
+    pub(crate) fn prefiltered_environment(
+        &self,
+        asset_cache: &Arc<AssetCache>,
+        source: &EnvironmentSource,
+    ) -> Option<Arc<PrefilteredEnvironment>> {
+        let key = source.key();
+        let mut environments = self.environments.borrow_mut();
+        let entry = environments
+            .entry(key.clone())
+            .or_insert_with(|| match source {
+                EnvironmentSource::Skybox(skybox) => EnvironmentEntry::LoadingFaces(
+                    skybox
+                        .faces()
+                        .iter()
+                        .map(|path| {
+                            asset_cache
+                                .load_asset_with_pipeline(self.raw_image_pipeline.clone(), path)
+                        })
+                        .collect(),
+                ),
+                EnvironmentSource::Equirect(path) => EnvironmentEntry::LoadingEquirect(
+                    asset_cache.load_asset_with_pipeline(self.hdr_image_pipeline.clone(), path),
+                ),
+            });
+
+        let prefiltered = match entry {
+            EnvironmentEntry::Ready { prefiltered, .. } => return Some(prefiltered.clone()),
+            EnvironmentEntry::Failed => return None,
+            // Poll every face each call, like the skybox: futures only
+            // advance when polled.
+            EnvironmentEntry::LoadingFaces(handles) => {
+                let mut faces: Vec<Arc<TextureData>> = Vec::with_capacity(6);
+                let mut pending = false;
+                let mut failed = false;
+                for handle in handles.iter() {
+                    match handle.poll_state() {
+                        AssetPollState::Loaded(data) => faces.push(data),
+                        AssetPollState::Loading => pending = true,
+                        AssetPollState::Failed => failed = true,
+                    }
+                }
+                if pending && !failed {
+                    return None;
+                }
+                let faces: Vec<&TextureData> = faces.iter().map(|face| face.as_ref()).collect();
+                (!failed)
+                    .then(|| CubeImage::from_faces(&faces))
+                    .flatten()
+                    .map(|cube| PrefilteredEnvironment::new(&cube, MAX_SPECULAR_SIZE))
+            }
+            EnvironmentEntry::LoadingEquirect(handle) => match handle.poll_state() {
+                AssetPollState::Loading => return None,
+                AssetPollState::Failed => None,
+                // 0x0 is the HDR pipeline's undecodable sentinel.
+                AssetPollState::Loaded(image) => (image.width > 0 && image.height > 0).then(|| {
+                    let size = (image.width as usize / 4).clamp(1, MAX_SPECULAR_SIZE);
+                    let cube = CubeImage::from_equirect(&image, size);
+                    PrefilteredEnvironment::new(&cube, MAX_SPECULAR_SIZE)
+                }),
+            },
+        };
+
+        match prefiltered {
+            Some(prefiltered) => {
+                let prefiltered = Arc::new(prefiltered);
+                *entry = EnvironmentEntry::Ready {
+                    prefiltered: prefiltered.clone(),
+                    texture: None,
+                };
+                Some(prefiltered)
+            }
+            None => {
+                *entry = EnvironmentEntry::Failed;
+                drop(environments);
+                self.warn_once(
+                    &key,
+                    &format!(
+                        "[environment] {} failed to load or is not a valid environment \
+(skybox faces must be square and the same size) — image-based light disabled",
+                        source.paths().join(", ")
+                    ),
+                );
+                None
+            }
+        }
+    }
+
+    /// The GPU form of `environment` for the lit shaders, uploading its
+    /// prefiltered cube on first use.
+    pub(crate) fn environment_uniforms(
+        &self,
+        gl: &glow::Context,
+        asset_cache: &Arc<AssetCache>,
+        environment: &Environment,
+    ) -> Option<EnvironmentUniforms> {
+        let prefiltered = self.prefiltered_environment(asset_cache, &environment.source)?;
+        let mut environments = self.environments.borrow_mut();
+        let Some(EnvironmentEntry::Ready { texture, .. }) =
+            environments.get_mut(&environment.source.key())
+        else {
+            return None;
+        };
+        let uploaded =
+            *texture.get_or_insert_with(|| UploadedEnvironment::upload(gl, &prefiltered));
+        Some(uploaded.uniforms(environment.intensity))
+    }
+
+    /// A reflection probe baked earlier, by `ReflectionProbe::key`.
+    pub(crate) fn reflection_probe(
+        &self,
+        key: &str,
+        intensity: f32,
+    ) -> Option<EnvironmentUniforms> {
+        self.reflection_probes
+            .borrow()
+            .get(key)
+            .map(|uploaded| uploaded.uniforms(intensity))
+    }
+
+    /// Upload a freshly baked probe under `key`.
+    pub(crate) fn store_reflection_probe(
+        &self,
+        gl: &glow::Context,
+        key: &str,
+        prefiltered: &PrefilteredEnvironment,
+    ) {
+        let uploaded = UploadedEnvironment::upload(gl, prefiltered);
+        self.reflection_probes
+            .borrow_mut()
+            .insert(key.to_string(), uploaded);
+    }
+}
+
+/// Upload a specular mip chain as an RGBA16F cube (float data, so `.hdr`
+/// values above 1.0 survive) with trilinear filtering across its levels.
+fn upload_prefiltered(gl: &glow::Context, prefiltered: &PrefilteredEnvironment) -> glow::Texture {
+    unsafe {
+        let texture = gl.create_texture().expect("environment cubemap");
+        crate::gpu_counters::gpu_counters().texture_created();
+        gl.bind_texture(glow::TEXTURE_CUBE_MAP, Some(texture));
+        for (level, cube) in prefiltered.specular.iter().enumerate() {
+            for (face, pixels) in cube.faces.iter().enumerate() {
+                let bytes: Vec<u8> = pixels
+                    .iter()
+                    .flat_map(|[r, g, b]| [*r, *g, *b, 1.0])
+                    .flat_map(f32::to_ne_bytes)
+                    .collect();
+                gl.tex_image_2d(
+                    glow::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+                    level as i32,
+                    glow::RGBA16F as i32,
+                    cube.size as i32,
+                    cube.size as i32,
+                    0,
+                    glow::RGBA,
+                    glow::FLOAT,
+                    glow::PixelUnpackData::Slice(Some(&bytes)),
+                );
+                crate::gpu_counters::gpu_counters().uploaded(bytes.len());
+            }
+        }
+        gl.tex_parameter_i32(
+            glow::TEXTURE_CUBE_MAP,
+            glow::TEXTURE_MIN_FILTER,
+            glow::LINEAR_MIPMAP_LINEAR as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_CUBE_MAP,
+            glow::TEXTURE_MAG_FILTER,
+            glow::LINEAR as i32,
+        );
+        // Every declared level is uploaded, so the chain is complete.
+        gl.tex_parameter_i32(glow::TEXTURE_CUBE_MAP, glow::TEXTURE_BASE_LEVEL, 0);
+        gl.tex_parameter_i32(
+            glow::TEXTURE_CUBE_MAP,
+            glow::TEXTURE_MAX_LEVEL,
+            prefiltered.specular.len() as i32 - 1,
+        );
+        for wrap in [
+            glow::TEXTURE_WRAP_S,
+            glow::TEXTURE_WRAP_T,
+            glow::TEXTURE_WRAP_R,
+        ] {
+            gl.tex_parameter_i32(glow::TEXTURE_CUBE_MAP, wrap, glow::CLAMP_TO_EDGE as i32);
+        }
+        gl.bind_texture(glow::TEXTURE_CUBE_MAP, None);
+        texture
+    }
+}
@@ -564,9 +564,9 @@ impl InstancedRenderer {
                 | MaterialDescription::Emissive { color, .. } => (*color, false),
                 // Recognition never accepts these; the arm exists only so
                 // the match stays exhaustive if the enum grows.
-                MaterialDescription::Texture(_) | MaterialDescription::SpriteTexture { .. } => {
-                    (cgmath::vec4(1.0, 1.0, 1.0, 1.0), false)
-                }
+                MaterialDescription::Texture(_)
+                | MaterialDescription::SpriteTexture { .. }
+                | MaterialDescription::Pbr(_) => (cgmath::vec4(1.0, 1.0, 1.0, 1.0), false),
             };
             set_forward_uniforms(
                 &self.forward,
@@ -1035,7 +1035,7 @@ fn set_forward_uniforms(
     };
     p.set_uniform_1i(ctx.gl, &u.debug_mode, debug_mode);
     p.set_uniform_1i(ctx.gl, &u.billboard_mode, billboard as i32);
-    u.lighting.set(p, ctx, view);
+    u.lighting.set(p, ctx, view, world.w.truncate());
     u.fog.set(p, ctx.gl, ctx.fog, &ctx.camera_pos);
 }
 
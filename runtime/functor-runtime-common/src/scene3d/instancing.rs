@@ -225,6 +225,12 @@ fn tint_material(material: MaterialDescription, tint: [f32; 3]) -> MaterialDescr
             source_pixels,
             sampling,
         },
+        MaterialDescription::Pbr(mut pbr) => {
+            for (channel, tint) in pbr.factors.base_color.iter_mut().zip(tint) {
+                *channel *= tint;
+            }
+            MaterialDescription::Pbr(pbr)
+        }
         // No color channel to multiply.
         texture @ MaterialDescription::Texture(_) => texture,
     }
@@ -345,9 +351,11 @@ pub(crate) fn recognize(template: &Scene3D) -> Option<RecognizedTemplate<'_>> {
                         normal_map,
                         ..
                     } => texture.is_none() && normal_map.is_none(),
-                    MaterialDescription::Texture(_) | MaterialDescription::SpriteTexture { .. } => {
-                        false
-                    }
+                    // The instanced shader has no metallic-roughness mode;
+                    // such templates expand on the CPU.
+                    MaterialDescription::Texture(_)
+                    | MaterialDescription::SpriteTexture { .. }
+                    | MaterialDescription::Pbr(_) => false,
                 };
                 if !recognized {
                     return None;
@@ -410,6 +418,7 @@ pub(crate) fn template_summary(template: &Scene3D) -> String {
                     MaterialDescription::Emissive { .. } => "emissive[",
                     MaterialDescription::Lit { .. } => "lit[",
                     MaterialDescription::SpriteTexture { .. } => "sprite[",
+                    MaterialDescription::Pbr(_) => "pbr[",
                 });
                 for (index, item) in items.iter().enumerate() {
                     if index > 0 {
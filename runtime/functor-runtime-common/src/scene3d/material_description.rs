@@ -3,7 +3,14 @@ use glow::HasContext;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    material::{BasicMaterial, ColorMaterial, EmissiveMaterial, LitMaterial, Material},
+    material::{
+        BasicMaterial, ColorMaterial, EmissiveMaterial, LitMaterial, Material, PbrMaterial,
+        PbrTextures,
+    },
+    pbr::{
+        PbrDescription, EMISSIVE_TEXTURE_UNIT, METALLIC_ROUGHNESS_TEXTURE_UNIT,
+        NORMAL_TEXTURE_UNIT, OCCLUSION_TEXTURE_UNIT,
+    },
     texture::RuntimeTexture,
     RenderContext, SceneContext, TextureDescription,
 };
@@ -62,6 +69,11 @@ pub enum MaterialDescription {
         source_pixels: Option<[f32; 4]>,
         sampling: SpriteSampling,
     },
+    /// A glTF metallic-roughness surface (`Scene.pbr`), shaded like an
+    /// imported model's materials. Procedural meshes are never culled — their
+    /// primitives don't share a winding — so `double_sided` only decides
+    /// whether back sides flip their normal.
+    Pbr(Box<PbrDescription>),
 }
 
 impl MaterialDescription {
@@ -85,10 +97,22 @@ impl MaterialDescription {
             | MaterialDescription::Emissive { color, .. }
             | MaterialDescription::Lit { color, .. }
             | MaterialDescription::SpriteTexture { color, .. } => Some(color.w),
+            MaterialDescription::Pbr(pbr) => Some(pbr.factors.base_color[3]),
             MaterialDescription::Texture(_) => None,
         }
     }
 
+    /// Whether the 3D forward pass blends this material's subtree: a
+    /// translucent constant color, or a metallic-roughness material in
+    /// `AlphaMode::Blend` (whose alpha may come from its texture). An opaque
+    /// or masked one ignores its alpha, as glTF specifies.
+    pub fn blends(&self) -> bool {
+        match self {
+            MaterialDescription::Pbr(pbr) => pbr.factors.blends(),
+            _ => self.color_alpha().is_some_and(|alpha| alpha < 1.0),
+        }
+    }
+
     /// A solid self-lit color (neon / UI), no texture.
     pub fn emissive(r: f32, g: f32, b: f32, a: f32) -> MaterialDescription {
         MaterialDescription::Emissive {
@@ -207,6 +231,43 @@ impl MaterialDescription {
                 material.initialize(&context);
                 material
             }
+            MaterialDescription::Pbr(pbr) => {
+                let textures = PbrTextures {
+                    base_color: bind_optional_texture(
+                        &pbr.base_color_texture,
+                        0,
+                        context,
+                        scene_context,
+                    ),
+                    normal: bind_optional_texture(
+                        &pbr.normal_texture,
+                        NORMAL_TEXTURE_UNIT,
+                        context,
+                        scene_context,
+                    ),
+                    metallic_roughness: bind_optional_texture(
+                        &pbr.metallic_roughness_texture,
+                        METALLIC_ROUGHNESS_TEXTURE_UNIT,
+                        context,
+                        scene_context,
+                    ),
+                    occlusion: bind_optional_texture(
+                        &pbr.occlusion_texture,
+                        OCCLUSION_TEXTURE_UNIT,
+                        context,
+                        scene_context,
+                    ),
+                    emissive: bind_optional_texture(
+                        &pbr.emissive_texture,
+                        EMISSIVE_TEXTURE_UNIT,
+                        context,
+                        scene_context,
+                    ),
+                };
+                let mut material = PbrMaterial::create(pbr.factors, textures);
+                material.initialize(context);
+                material
+            }
         }
     }
 }
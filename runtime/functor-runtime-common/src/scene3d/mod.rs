@@ -13,19 +13,25 @@ use crate::{
     asset::{
         self,
         pipelines::{
-            HeightmapData, HeightmapPipeline, ModelPipeline, RawImagePipeline, TexturePipeline,
+            HdrImagePipeline, HeightmapData, HeightmapPipeline, ModelPipeline, RawImagePipeline,
+            TexturePipeline,
         },
         AssetCache, AssetHandle, AssetPollState, BuiltAssetPipeline,
     },
     composite::{COMPOSITE_FRAGMENT_SHADER_SOURCE, COMPOSITE_VERTEX_SHADER_SOURCE, MAX_COMPOSITE},
+    environment::HdrImage,
     geometry::{self, Geometry},
     material::{
-        BasicMaterial, DepthMaterial, Material, NormalDebugMaterial, SkinnedDepthMaterial,
-        SkinnedMaterial, SkinnedNormalDebugMaterial, SkinnedTangentDebugMaterial,
+        BasicMaterial, DepthMaterial, Material, NormalDebugMaterial, PbrMaterial, PbrTextures,
+        SkinnedDepthMaterial, SkinnedNormalDebugMaterial, SkinnedTangentDebugMaterial,
         TangentDebugMaterial,
     },
     math::Angle,
-    model::{Model, Skeleton},
+    model::{Model, ModelMesh, Skeleton},
+    pbr::{
+        EMISSIVE_TEXTURE_UNIT, METALLIC_ROUGHNESS_TEXTURE_UNIT, NORMAL_TEXTURE_UNIT,
+        OCCLUSION_TEXTURE_UNIT,
+    },
     render_target::{warn_line, RenderTargetBuffers, RenderTargetDescriptor},
     shader::{Shader, ShaderType},
     shader_program::{ShaderProgram, UniformLocation},
@@ -35,10 +41,12 @@ use crate::{
     DebugRenderMode, RenderContext, RenderPass,
 };
 
+mod environment_maps;
 mod instanced_renderer;
 mod instancing;
 mod material_description;
 mod model_description;
+mod post_pass;
 mod texture_description;
 
 pub use instancing::InstanceData;
@@ -47,11 +55,12 @@ pub use material_description::*;
 use instanced_renderer::InstancedRenderer;
 pub(crate) use instancing::expand_instanced;
 pub use model_description::*;
+pub(crate) use post_pass::{PostInputs, POST_DEPTH_TARGET_ID, POST_SCENE_TARGET_ID};
 pub use texture_description::*;
 
 pub struct SceneContext {
-    model_pipeline: Arc<BuiltAssetPipeline<Model>>,
-    texture_pipeline: Arc<BuiltAssetPipeline<Texture2D>>,
+    pub(crate) model_pipeline: Arc<BuiltAssetPipeline<Model>>,
+    pub(crate) texture_pipeline: Arc<BuiltAssetPipeline<Texture2D>>,
     /// Terrain detail maps decode through their own pipeline so their reduced
     /// anisotropy and mean-color scan stay scoped to them.
     terrain_detail_pipeline: Arc<BuiltAssetPipeline<Texture2D>>,
@@ -93,12 +102,24 @@ pub struct SceneContext {
     // targets they persist across frames/hot reloads and are never evicted
     // (TODO). Faces decode through `raw_image_pipeline` (no GL hydration);
     // the cubemap uploads once when all six are ready.
-    raw_image_pipeline: Arc<BuiltAssetPipeline<TextureData>>,
+    pub(crate) raw_image_pipeline: Arc<BuiltAssetPipeline<TextureData>>,
     skyboxes: RefCell<HashMap<String, SkyboxEntry>>,
     skybox_program: RefCell<Option<(ShaderProgram, SkyboxUniforms)>>,
+    // Image-based light (`Frame.withEnvironment`), keyed by
+    // `EnvironmentSource::key`: skybox faces share `raw_image_pipeline`
+    // decodes, equirect images decode to float through `hdr_image_pipeline`,
+    // and each source is prefiltered on the CPU once, then uploaded once.
+    hdr_image_pipeline: Arc<BuiltAssetPipeline<HdrImage>>,
+    environments: RefCell<HashMap<String, environment_maps::EnvironmentEntry>>,
+    // Baked reflection probes by `ReflectionProbe::key`. Cleared on any asset
+    // reload so probes re-bake against the new content.
+    reflection_probes: RefCell<HashMap<String, environment_maps::UploadedEnvironment>>,
     // The screen-space compositor's fullscreen-average program, built lazily on
     // first use and cached like the skybox program (docs/time-travel.md T5).
     composite_program: RefCell<Option<(ShaderProgram, CompositeUniforms)>>,
+    // One program per post-processing stage (`Frame.withPost`), built lazily
+    // the first time a frame's stack uses the stage.
+    post_programs: RefCell<HashMap<crate::post::PostStage, post_pass::PostProgram>>,
     // In-flight `Effect.preload` loads (B.5), polled each frame by
     // `drive_preloads` until they settle — asset futures advance only when
     // polled, and nothing else polls an asset `draw` isn't referencing yet.
@@ -211,32 +232,117 @@ struct CompositeUniforms {
 /// Resolve a model asset for drawing: the per-frame cache poll (which is ALSO
 /// the liveness poll for `Asset.whilePending` chains, so it must run every
 /// frame) followed by placeholder resolution. Shared by the ordinary Model
-/// draw arm and the instanced-model arm so the two cannot drift.
-fn resolve_model_for_draw(
-    render_context: &RenderContext,
+/// draw arm and the instanced-model arm so the two cannot drift (and with the
+/// software renderer, which has no `RenderContext`).
+pub(crate) fn resolve_model_for_draw(
+    asset_cache: &Arc<AssetCache>,
     scene_context: &SceneContext,
     file: &str,
     while_pending: &[String],
 ) -> Arc<Model> {
-    let model: Arc<AssetHandle<Model>> = render_context
-        .asset_cache
-        .load_asset_with_pipeline(scene_context.model_pipeline.clone(), file);
+    let model: Arc<AssetHandle<Model>> =
+        asset_cache.load_asset_with_pipeline(scene_context.model_pipeline.clone(), file);
     crate::asset::resolve_while_pending(
-        &render_context.asset_cache,
+        asset_cache,
         &scene_context.model_pipeline,
         &model,
         while_pending,
     )
 }
 
+/// Draw one glTF primitive with its own metallic-roughness material: each
+/// texture on its `PbrMaterial` unit, back faces culled unless the material
+/// is double-sided (front faces are counter-clockwise, clockwise under a
+/// mirroring transform), and blend-mode materials blended without writing
+/// depth — unless the pass already owns the blend state. Alpha-mask cutouts
+/// only apply here; the depth pass still casts their full shadow.
+fn draw_pbr_model_mesh(
+    render_context: &RenderContext,
+    mesh: &ModelMesh,
+    is_skinned: bool,
+    projection_matrix: &Matrix4<f32>,
+    view_matrix: &Matrix4<f32>,
+    world_matrix: &Matrix4<f32>,
+    joints: &[Matrix4<f32>],
+) {
+    let material = &mesh.material;
+    let bind = |texture: &Option<Texture2D>, unit: u32| {
+        texture
+            .as_ref()
+            .map(|texture| texture.bind(unit, render_context))
+            .is_some()
+    };
+    mesh.base_color_texture.bind(0, render_context);
+    let textures = PbrTextures {
+        base_color: true,
+        normal: bind(&material.normal_texture, NORMAL_TEXTURE_UNIT),
+        metallic_roughness: bind(
+            &material.metallic_roughness_texture,
+            METALLIC_ROUGHNESS_TEXTURE_UNIT,
+        ),
+        occlusion: bind(&material.occlusion_texture, OCCLUSION_TEXTURE_UNIT),
+        emissive: bind(&material.emissive_texture, EMISSIVE_TEXTURE_UNIT),
+    };
+    let mut pbr_material = match is_skinned {
+        true => PbrMaterial::create_skinned(material.factors, textures),
+        false => PbrMaterial::create(material.factors, textures),
+    };
+    pbr_material.initialize(render_context);
+
+    let gl = render_context.gl;
+    let cull = !material.factors.double_sided;
+    let blend = material.factors.blends()
+        && !render_context.pass_blends
+        && !render_context.blend_active.get();
+    unsafe {
+        if cull {
+            gl.enable(glow::CULL_FACE);
+            gl.cull_face(glow::BACK);
+            gl.front_face(match world_matrix.determinant() < 0.0 {
+                true => glow::CW,
+                false => glow::CCW,
+            });
+        }
+        if blend {
+            gl.enable(glow::BLEND);
+            gl.blend_func_separate(
+                glow::SRC_ALPHA,
+                glow::ONE_MINUS_SRC_ALPHA,
+                glow::ONE,
+                glow::ONE_MINUS_SRC_ALPHA,
+            );
+            gl.depth_mask(false);
+        }
+    }
+    pbr_material.draw_opaque(
+        render_context,
+        projection_matrix,
+        view_matrix,
+        world_matrix,
+        joints,
+    );
+    mesh.mesh.draw(gl);
+    unsafe {
+        if cull {
+            gl.disable(glow::CULL_FACE);
+            gl.front_face(glow::CCW);
+        }
+        if blend {
+            gl.depth_mask(true);
+            gl.disable(glow::BLEND);
+        }
+    }
+}
+
 /// Evaluate a skinned model's pose ONCE — the declarative `Scene.animate`
 /// expression when attached (unknown clip/joint names warn once; a missing
 /// clip contributes the bind pose, a missing joint is ignored), else the
 /// zero-config default (the first clip auto-plays, looping on the game
 /// clock). Shared by the ordinary Model draw arm and the instanced
-/// shared-pose path, which uploads the result once for every copy.
-fn model_pose_joints(
-    render_context: &RenderContext,
+/// shared-pose path, which uploads the result once for every copy. `tts` is
+/// the pass's render time.
+pub(crate) fn model_pose_joints(
+    tts: f32,
     scene_context: &SceneContext,
     hydrated_model: &Arc<Model>,
     animation: &Option<crate::anim::AnimExpr>,
@@ -282,7 +388,7 @@ so the reach is ignored"
         // game clock.
         None => match hydrated_model.animations.first() {
             Some(animation) => {
-                let time = render_context.frame_time.tts % animation.duration;
+                let time = tts % animation.duration;
                 let animated_skeleton =
                     Skeleton::animate(&hydrated_model.skeleton, animation, time);
                 animated_skeleton.get_skinning_transforms()
@@ -299,7 +405,8 @@ impl SceneContext {
     /// Drop every cached decode of `path` so the next draw reloads it from
     /// disk — asset hot-reload (pair with `AssetCache::evict` for the bytes).
     /// A skybox using the path as a face rebuilds too (its cache key is the
-    /// six face paths joined with '\n'). GPU objects hydrated from the old
+    /// six face paths joined with '\n'), as does an environment reading it,
+    /// and every reflection probe re-bakes. GPU objects hydrated from the old
     /// decode are not freed (renderables have no Drop yet) — a dev-loop leak
     /// bounded by save count, the same class as the render-target TODO above.
     pub fn evict_asset(&self, path: &str) {
@@ -308,9 +415,14 @@ impl SceneContext {
         self.terrain_detail_pipeline.evict(path);
         self.raw_image_pipeline.evict(path);
         self.heightmap_pipeline.evict(path);
+        self.hdr_image_pipeline.evict(path);
         self.skyboxes
             .borrow_mut()
             .retain(|faces, _| !faces.split('\n').any(|face| face == path));
+        self.environments
+            .borrow_mut()
+            .retain(|key, _| !key.split('\n').skip(1).any(|input| input == path));
+        self.reflection_probes.borrow_mut().clear();
     }
 
     pub fn new() -> SceneContext {
@@ -340,7 +452,11 @@ impl SceneContext {
             raw_image_pipeline: asset::build_pipeline(Box::new(RawImagePipeline)),
             skyboxes: RefCell::new(HashMap::new()),
             skybox_program: RefCell::new(None),
+            hdr_image_pipeline: asset::build_pipeline(Box::new(HdrImagePipeline)),
+            environments: RefCell::new(HashMap::new()),
+            reflection_probes: RefCell::new(HashMap::new()),
             composite_program: RefCell::new(None),
+            post_programs: RefCell::new(HashMap::new()),
             preloads: RefCell::new(Vec::new()),
         }
     }
@@ -1625,7 +1741,7 @@ impl Scene3D {
                         // the empty fallback (chainless models resolve exactly
                         // like the old `get()`).
                         let hydrated_model = resolve_model_for_draw(
-                            render_context,
+                            &render_context.asset_cache,
                             scene_context,
                             str,
                             &model_description.while_pending,
@@ -1645,43 +1761,55 @@ impl Scene3D {
                                 | DebugRenderMode::Transparent
                                 | DebugRenderMode::Physics
                         );
+                        // Each mesh shades with its own glTF material (a
+                        // `PbrMaterial`) — except in a frame with no lights
+                        // and no environment, where static models stay
+                        // fullbright as they always have.
+                        let shaded = !render_context.lights.is_empty()
+                            || render_context.environment.is_some();
                         // In the depth pass, draw the model with a depth material
                         // that still skins (so animated models cast a correctly
-                        // deforming shadow), else the lit material or the matching
-                        // diagnostic material (skinned variants deform the
-                        // normal/tangent by the joint blend).
-                        let mut model_material: Box<dyn Material> = match (depth_pass, is_skinned) {
-                            (true, true) => SkinnedDepthMaterial::create(),
-                            (true, false) => DepthMaterial::create(),
-                            (false, _) => match render_context.debug_render_mode {
-                                DebugRenderMode::Default
-                                | DebugRenderMode::Transparent
-                                | DebugRenderMode::Physics
-                                    if is_skinned =>
-                                {
-                                    SkinnedMaterial::create()
-                                }
-                                DebugRenderMode::Default
-                                | DebugRenderMode::Transparent
-                                | DebugRenderMode::Physics => BasicMaterial::create(),
-                                DebugRenderMode::Normals if is_skinned => {
-                                    SkinnedNormalDebugMaterial::create()
-                                }
-                                DebugRenderMode::Normals => NormalDebugMaterial::create(),
-                                DebugRenderMode::Tangents if is_skinned => {
-                                    SkinnedTangentDebugMaterial::create()
-                                }
-                                DebugRenderMode::Tangents => TangentDebugMaterial::create(),
-                            },
-                        };
-                        model_material.initialize(&render_context);
+                        // deforming shadow), else the matching diagnostic
+                        // material (skinned variants deform the normal/tangent
+                        // by the joint blend), or `None` for the per-mesh
+                        // material.
+                        let mut model_material: Option<Box<dyn Material>> =
+                            match (depth_pass, is_skinned) {
+                                (true, true) => Some(SkinnedDepthMaterial::create()),
+                                (true, false) => Some(DepthMaterial::create()),
+                                (false, _) => match render_context.debug_render_mode {
+                                    DebugRenderMode::Default
+                                    | DebugRenderMode::Transparent
+                                    | DebugRenderMode::Physics
+                                        if is_skinned || shaded =>
+                                    {
+                                        None
+                                    }
+                                    DebugRenderMode::Default
+                                    | DebugRenderMode::Transparent
+                                    | DebugRenderMode::Physics => Some(BasicMaterial::create()),
+                                    DebugRenderMode::Normals if is_skinned => {
+                                        Some(SkinnedNormalDebugMaterial::create())
+                                    }
+                                    DebugRenderMode::Normals => Some(NormalDebugMaterial::create()),
+                                    DebugRenderMode::Tangents if is_skinned => {
+                                        Some(SkinnedTangentDebugMaterial::create())
+                                    }
+                                    DebugRenderMode::Tangents => {
+                                        Some(TangentDebugMaterial::create())
+                                    }
+                                },
+                            };
+                        if let Some(model_material) = model_material.as_mut() {
+                            model_material.initialize(render_context);
+                        }
 
                         // The pose depends only on the model + expression, so
                         // evaluate it once per model (a blend samples every
                         // clip in the expression) and share it across meshes.
                         let joints = if is_skinned {
                             model_pose_joints(
-                                render_context,
+                                render_context.frame_time.tts,
                                 scene_context,
                                 &hydrated_model,
                                 &model_description.animation,
@@ -1691,7 +1819,13 @@ impl Scene3D {
                             vec![]
                         };
 
-                        for mesh in hydrated_model.meshes.iter() {
+                        // glTF's blend-mode meshes draw after the model's
+                        // opaque ones (unsorted among themselves).
+                        let (blended, opaque): (Vec<&ModelMesh>, Vec<&ModelMesh>) =
+                            hydrated_model.meshes.iter().partition(|mesh| {
+                                model_material.is_none() && mesh.material.factors.blends()
+                            });
+                        for mesh in opaque.into_iter().chain(blended) {
                             // Go through selectors, and adjust
                             // let override_material_description = Some(MaterialDescription::Texture(
                             //     TextureDescription::File("vr_glove_color.jpg".to_string()),
@@ -1743,7 +1877,8 @@ impl Scene3D {
                                     &matrix,
                                     &[],
                                 );
-                            } else {
+                                mesh.mesh.draw(render_context.gl)
+                            } else if let Some(model_material) = &model_material {
                                 // Bind textures
                                 mesh.base_color_texture.bind(0, &render_context);
                                 model_material.draw_opaque(
@@ -1753,10 +1888,18 @@ impl Scene3D {
                                     &matrix,
                                     &joints,
                                 );
+                                mesh.mesh.draw(render_context.gl)
+                            } else {
+                                draw_pbr_model_mesh(
+                                    render_context,
+                                    mesh,
+                                    is_skinned,
+                                    projection_matrix,
+                                    view_matrix,
+                                    &matrix,
+                                    &joints,
+                                );
                             };
-
-                            // TODO: Bring back drawing
-                            mesh.mesh.draw(&render_context.gl)
                         }
                     }
                 }
@@ -1786,11 +1929,13 @@ impl Scene3D {
                 // whose constant color is translucent switches blending on for
                 // its own subtree and back off after.
                 //
-                // Only the runtime's own overlays reach this: the Functor Lang
-                // prelude cannot express a translucent 3D material (Color.rgb
-                // has no alpha channel), so `color_alpha() < 1` in the 3D pass
-                // means the extrapolation preview's presence ramp
-                // (`trajectory::scale_presence`) or its age fade. `pass_blends`
+                // Apart from `Scene.pbrAlphaBlend` (a metallic-roughness
+                // material in blend mode), only the runtime's own overlays
+                // reach this: the Functor Lang prelude cannot otherwise express
+                // a translucent 3D material (Color.rgb has no alpha channel),
+                // so `color_alpha() < 1` in the 3D pass means the extrapolation
+                // preview's presence ramp (`trajectory::scale_presence`) or its
+                // age fade. `pass_blends`
                 // covers the passes that already own the blend state (2D
                 // sprites, transparent debug) so the restore can't clobber them.
                 //
@@ -1819,9 +1964,7 @@ impl Scene3D {
                 let blend = !depth_pass
                     && !render_context.pass_blends
                     && !render_context.blend_active.get()
-                    && material_description
-                        .color_alpha()
-                        .is_some_and(|alpha| alpha < 1.0);
+                    && material_description.blends();
                 if blend {
                     render_context.blend_active.set(true);
                     unsafe {
@@ -1945,7 +2088,7 @@ impl Scene3D {
                     Some(instancing::RecognizedTemplate::Model(recognized)) => {
                         let ModelHandle::File(file) = &recognized.description.handle;
                         let hydrated_model = resolve_model_for_draw(
-                            render_context,
+                            &render_context.asset_cache,
                             scene_context,
                             file,
                             &recognized.description.while_pending,
@@ -1960,7 +2103,7 @@ impl Scene3D {
                         let is_skinned = hydrated_model.skeleton.get_joint_count() > 0;
                         let joints = if is_skinned {
                             model_pose_joints(
-                                render_context,
+                                render_context.frame_time.tts,
                                 scene_context,
                                 &hydrated_model,
                                 &recognized.description.animation,
@@ -0,0 +1,352 @@
+use std::sync::Arc;
+
+use glow::HasContext;
+
+use crate::{
+    asset::{AssetCache, AssetPollState},
+    composite::COMPOSITE_VERTEX_SHADER_SOURCE,
+    post::{PostEffect, PostStage, Tonemap, BLOOM_BLUR_ITERATIONS},
+    render_target::RenderTargetDescriptor,
+    shader::{Shader, ShaderType},
+    shader_program::{ShaderProgram, UniformLocation},
+    TextureDescription, Viewport,
+};
+
+use super::SceneContext;
+
+/// The offscreen target the main pass renders into when the frame has a post
+/// stack, at the viewport's size.
+pub(crate) const POST_SCENE_TARGET_ID: &str = "__post_scene";
+
+/// The depth pass depth of field reads, packed like a shadow map.
+pub(crate) const POST_DEPTH_TARGET_ID: &str = "__post_depth";
+
+/// Every full-size stage writes here; the pair's own double buffering lets a
+/// stage read the previous stage's output while writing the next.
+const POST_CHAIN_TARGET_ID: &str = "__post_chain";
+
+/// Bloom's bright pass and blur, at half the viewport's size.
+const POST_BLOOM_TARGET_ID: &str = "__post_bloom";
+
+/// One stage's linked program, with its sampler and float uniform locations
+/// in [`PostStage::samplers`] / [`PostStage::uniforms`] order.
+pub(super) struct PostProgram {
+    shader: ShaderProgram,
+    samplers: Vec<UniformLocation>,
+    floats: Vec<UniformLocation>,
+}
+
+/// What the main pass produced for the post stack to read.
+pub(crate) struct PostInputs {
+    pub scene: glow::Texture,
+    /// The packed depth pass, when an effect needs depth.
+    pub depth: Option<glow::Texture>,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl SceneContext {
+    /// Run `post` over the main pass's image, in order, then copy the result
+    /// onto `destination` at `viewport` (leaving that framebuffer bound, with
+    /// the viewport and scissor set to the pane). An effect whose input is
+    /// not ready — a lookup table still loading, or depth no pass rendered —
+    /// is skipped for the frame.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn draw_post_stack(
+        &self,
+        gl: &glow::Context,
+        shader_version: &str,
+        asset_cache: &Arc<AssetCache>,
+        post: &[PostEffect],
+        inputs: PostInputs,
+        destination: Option<glow::Framebuffer>,
+        viewport: Viewport,
+    ) {
+        let (width, height) = (viewport.width.max(1), viewport.height.max(1));
+        let chain = RenderTargetDescriptor {
+            id: POST_CHAIN_TARGET_ID.to_string(),
+            width,
+            height,
+        };
+        self.ensure_render_target(gl, &chain, [0.0; 3]);
+        if post
+            .iter()
+            .any(|effect| matches!(effect, PostEffect::Bloom { .. }))
+        {
+            let bloom = RenderTargetDescriptor {
+                id: POST_BLOOM_TARGET_ID.to_string(),
+                width: (width / 2).max(1),
+                height: (height / 2).max(1),
+            };
+            self.ensure_render_target(gl, &bloom, [0.0; 3]);
+        }
+        unsafe {
+            // The targets own their whole textures; the caller's pane scissor
+            // would clip them.
+            gl.disable(glow::SCISSOR_TEST);
+        }
+
+        let chain = POST_CHAIN_TARGET_ID;
+        let mut source = inputs.scene;
+        for effect in post {
+            match effect {
+                PostEffect::Bloom {
+                    threshold,
+                    intensity,
+                } => {
+                    let bloom = POST_BLOOM_TARGET_ID;
+                    let mut blurred = self.post_stage_into(
+                        gl,
+                        shader_version,
+                        bloom,
+                        PostStage::BrightPass,
+                        &[source],
+                        &[*threshold],
+                    );
+                    for _ in 0..BLOOM_BLUR_ITERATIONS {
+                        for horizontal in [1.0, 0.0] {
+                            blurred = self.post_stage_into(
+                                gl,
+                                shader_version,
+                                bloom,
+                                PostStage::Blur,
+                                &[blurred],
+                                &[horizontal],
+                            );
+                        }
+                    }
+                    source = self.post_stage_into(
+                        gl,
+                        shader_version,
+                        chain,
+                        PostStage::BloomCombine,
+                        &[source, blurred],
+                        &[*intensity],
+                    );
+                }
+                PostEffect::Tonemap { curve, exposure } => {
+                    let filmic = if *curve == Tonemap::Filmic { 1.0 } else { 0.0 };
+                    source = self.post_stage_into(
+                        gl,
+                        shader_version,
+                        chain,
+                        PostStage::Tonemap,
+                        &[source],
+                        &[*exposure, filmic],
+                    );
+                }
+                PostEffect::ColorGrade { lut, strength } => {
+                    if let Some(lut) = self.post_lut(gl, asset_cache, lut) {
+                        source = self.post_stage_into(
+                            gl,
+                            shader_version,
+                            chain,
+                            PostStage::ColorGrade,
+                            &[source, lut],
+                            &[*strength],
+                        );
+                    }
+                }
+                PostEffect::Vignette {
+                    intensity,
+                    smoothness,
+                } => {
+                    source = self.post_stage_into(
+                        gl,
+                        shader_version,
+                        chain,
+                        PostStage::Vignette,
+                        &[source],
+                        &[*intensity, *smoothness],
+                    );
+                }
+                PostEffect::Fxaa => {
+                    source = self.post_stage_into(
+                        gl,
+                        shader_version,
+                        chain,
+                        PostStage::Fxaa,
+                        &[source],
+                        &[],
+                    );
+                }
+                PostEffect::DepthOfField {
+                    focus_distance,
+                    focus_range,
+                    max_blur,
+                } => {
+                    if let Some(depth) = inputs.depth {
+                        source = self.post_stage_into(
+                            gl,
+                            shader_version,
+                            chain,
+                            PostStage::DepthOfField,
+                            &[source, depth],
+                            &[
+                                inputs.near,
+                                inputs.far,
+                                *focus_distance,
+                                *focus_range,
+                                *max_blur,
+                            ],
+                        );
+                    }
+                }
+            }
+        }
+
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, destination);
+            gl.viewport(
+                viewport.x as i32,
+                viewport.y as i32,
+                viewport.width as i32,
+                viewport.height as i32,
+            );
+            gl.scissor(
+                viewport.x as i32,
+                viewport.y as i32,
+                viewport.width as i32,
+                viewport.height as i32,
+            );
+            gl.enable(glow::SCISSOR_TEST);
+        }
+        self.draw_composite(gl, shader_version, &[source], &[1.0]);
+    }
+
+    /// Draw one stage into target `id` (ensured by the caller), publish it,
+    /// and return the texture the next stage reads.
+    fn post_stage_into(
+        &self,
+        gl: &glow::Context,
+        shader_version: &str,
+        id: &str,
+        stage: PostStage,
+        textures: &[glow::Texture],
+        values: &[f32],
+    ) -> glow::Texture {
+        let (fbo, width, height) = self
+            .render_target_write(id)
+            .expect("post targets were just ensured");
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+            gl.viewport(0, 0, width as i32, height as i32);
+        }
+        self.draw_post_stage(gl, shader_version, stage, textures, values);
+        self.finish_render_target_write(id);
+        self.render_target_read_texture(id)
+            .expect("post targets were just ensured")
+    }
+
+    /// A fullscreen quad through `stage`'s program (built on first use and
+    /// cached), with `textures` on units 0.. and `values` as its float
+    /// uniforms — the post counterpart of [`SceneContext::draw_composite`].
+    fn draw_post_stage(
+        &self,
+        gl: &glow::Context,
+        shader_version: &str,
+        stage: PostStage,
+        textures: &[glow::Texture],
+        values: &[f32],
+    ) {
+        let mut programs = self.post_programs.borrow_mut();
+        let program = programs.entry(stage).or_insert_with(|| {
+            let vertex = Shader::build(
+                gl,
+                ShaderType::Vertex,
+                COMPOSITE_VERTEX_SHADER_SOURCE,
+                shader_version,
+            );
+            let fragment = Shader::build(
+                gl,
+                ShaderType::Fragment,
+                stage.fragment_source(),
+                shader_version,
+            );
+            let shader = ShaderProgram::link(gl, &vertex, &fragment);
+            let samplers = stage
+                .samplers()
+                .iter()
+                .map(|name| shader.get_uniform_location(gl, name))
+                .collect();
+            let floats = stage
+                .uniforms()
+                .iter()
+                .map(|name| shader.get_uniform_location(gl, name))
+                .collect();
+            PostProgram {
+                shader,
+                samplers,
+                floats,
+            }
+        });
+        unsafe {
+            program.shader.use_program(gl);
+            for (unit, (location, texture)) in program.samplers.iter().zip(textures).enumerate() {
+                program.shader.set_uniform_1i(gl, location, unit as i32);
+                gl.active_texture(glow::TEXTURE0 + unit as u32);
+                gl.bind_texture(glow::TEXTURE_2D, Some(*texture));
+            }
+            for (location, value) in program.floats.iter().zip(values) {
+                program.shader.set_uniform_1f(gl, location, *value);
+            }
+            gl.disable(glow::DEPTH_TEST);
+        }
+        self.quad.borrow_mut().draw(gl);
+        unsafe {
+            gl.enable(glow::DEPTH_TEST);
+            for unit in 0..textures.len() {
+                gl.active_texture(glow::TEXTURE0 + unit as u32);
+                gl.bind_texture(glow::TEXTURE_2D, None);
+            }
+            gl.active_texture(glow::TEXTURE0);
+        }
+    }
+
+    /// A color-grading lookup table, once its file has loaded. Sampled
+    /// linearly and clamped, whatever the texture pipeline chose for it.
+    fn post_lut(
+        &self,
+        gl: &glow::Context,
+        asset_cache: &Arc<AssetCache>,
+        lut: &TextureDescription,
+    ) -> Option<glow::Texture> {
+        let file = match lut {
+            TextureDescription::File(file)
+            | TextureDescription::FileClamped(file)
+            | TextureDescription::FileWhilePending { file, .. }
+            | TextureDescription::FileClampedWhilePending { file, .. } => file,
+            TextureDescription::RenderTarget(_) | TextureDescription::Builtin(_) => {
+                self.warn_once(
+                    "post-lut-kind",
+                    "[post] Post.lut needs a file texture — color grading skipped",
+                );
+                return None;
+            }
+        };
+        let handle = asset_cache.load_asset_with_pipeline(self.texture_pipeline.clone(), file);
+        let texture = match handle.poll_state() {
+            AssetPollState::Loaded(texture) => texture.gl_texture(gl),
+            AssetPollState::Loading => return None,
+            AssetPollState::Failed => {
+                self.warn_once_with(&format!("post-lut:{file}"), || {
+                    format!("[post] LUT \"{file}\" failed to load — grading skipped")
+                });
+                return None;
+            }
+        };
+        unsafe {
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            for (parameter, value) in [
+                (glow::TEXTURE_MIN_FILTER, glow::LINEAR),
+                (glow::TEXTURE_MAG_FILTER, glow::LINEAR),
+                (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+                (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+            ] {
+                gl.tex_parameter_i32(glow::TEXTURE_2D, parameter, value as i32);
+            }
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+        Some(texture)
+    }
+}
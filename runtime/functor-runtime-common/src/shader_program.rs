@@ -84,6 +84,13 @@ impl ShaderProgram {
         }
     }
 
+    /// Upload a `vec4[]` uniform from a flattened slice (length = 4 × count).
+    pub fn set_uniform_vec4v(&self, gl: &glow::Context, uniform_location: &UniformLocation, v: &[f32]) {
+        unsafe {
+            gl.uniform_4_f32_slice(Some(&uniform_location.native_uniform_location), v);
+        }
+    }
+
     #[allow(dead_code)]
     pub fn set_uniform_vec3(
         &self,
@@ -1,11 +1,12 @@
+use std::cell::Cell;
 use std::sync::Arc;
 
-use cgmath::{ortho, perspective, vec3, InnerSpace, Matrix4, Point3, Rad, SquareMatrix};
+use cgmath::{ortho, perspective, vec3, InnerSpace, Matrix4, Point3, Rad, SquareMatrix, Vector3};
 use glow::HasContext;
 
 use crate::{
-    asset::AssetCache, material::DepthMaterial, FrameTime, Light, RenderContext, RenderPass,
-    Scene3D, SceneContext,
+    asset::AssetCache, material::DepthMaterial, Camera, FrameTime, Light, RenderContext,
+    RenderPass, Scene3D, SceneContext, ShadowSettings, ShadowUniforms,
 };
 
 /// An offscreen render target for shadow maps — the foundation later reused by
@@ -14,14 +15,59 @@ use crate::{
 /// decodes); a depth renderbuffer drives depth testing during the pass. RGBA8 is
 /// chosen for portability — `DEPTH_COMPONENT` sampled as a plain `sampler2D`,
 /// and even R32F, are unreliable on some drivers (notably macOS).
+///
+/// The texture is an atlas: each view of the casting light (the single map, a
+/// cascade, a cube face) renders into its own [`ShadowTile`]. It starts at the
+/// size the shell allocates and only grows — to what a [`ShadowLayout`] needs,
+/// capped at [`MAX_SHADOW_ATLAS_SIZE`] — so passes with different layouts in
+/// one frame never thrash the allocation.
 pub struct ShadowMap {
-    pub fbo: glow::Framebuffer,
-    pub depth_texture: glow::Texture,
-    pub size: u32,
+    atlas: Cell<ShadowAtlas>,
+}
+
+#[derive(Clone, Copy)]
+struct ShadowAtlas {
+    fbo: glow::Framebuffer,
+    depth_texture: glow::Texture,
+    depth_rbo: glow::Renderbuffer,
+    width: u32,
+    height: u32,
 }
 
 impl ShadowMap {
     pub fn new(gl: &glow::Context, size: u32) -> ShadowMap {
+        ShadowMap {
+            atlas: Cell::new(ShadowAtlas::new(gl, size, size)),
+        }
+    }
+
+    /// The packed-depth atlas texture the lit shader samples.
+    pub fn depth_texture(&self) -> glow::Texture {
+        self.atlas.get().depth_texture
+    }
+
+    /// The atlas size in texels, `(width, height)`.
+    pub fn size(&self) -> (u32, u32) {
+        let atlas = self.atlas.get();
+        (atlas.width, atlas.height)
+    }
+
+    /// Grow the atlas to hold `width` × `height` texels (a no-op if it
+    /// already does). Leaves no framebuffer bound when it reallocates.
+    fn ensure_size(&self, gl: &glow::Context, width: u32, height: u32) -> ShadowAtlas {
+        let atlas = self.atlas.get();
+        if atlas.width >= width && atlas.height >= height {
+            return atlas;
+        }
+        atlas.delete(gl);
+        let grown = ShadowAtlas::new(gl, atlas.width.max(width), atlas.height.max(height));
+        self.atlas.set(grown);
+        grown
+    }
+}
+
+impl ShadowAtlas {
+    fn new(gl: &glow::Context, width: u32, height: u32) -> ShadowAtlas {
         unsafe {
             let depth_texture = gl.create_texture().expect("shadow depth texture");
             crate::gpu_counters::gpu_counters().texture_created();
@@ -30,8 +76,8 @@ impl ShadowMap {
                 glow::TEXTURE_2D,
                 0,
                 glow::RGBA8 as i32,
-                size as i32,
-                size as i32,
+                width as i32,
+                height as i32,
                 0,
                 glow::RGBA,
                 glow::UNSIGNED_BYTE,
@@ -45,7 +91,8 @@ impl ShadowMap {
             gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_BASE_LEVEL, 0);
             gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAX_LEVEL, 0);
             // Clamp so samples outside the light frustum read the edge; the
-            // shader additionally treats out-of-range projections as lit.
+            // shader additionally treats out-of-range projections as lit, and
+            // keeps PCF taps inside their own tile.
             gl.tex_parameter_i32(
                 glow::TEXTURE_2D,
                 glow::TEXTURE_WRAP_S,
@@ -62,8 +109,8 @@ impl ShadowMap {
             gl.renderbuffer_storage(
                 glow::RENDERBUFFER,
                 glow::DEPTH_COMPONENT24,
-                size as i32,
-                size as i32,
+                width as i32,
+                height as i32,
             );
 
             let fbo = gl.create_framebuffer().expect("shadow fbo");
@@ -86,13 +133,93 @@ impl ShadowMap {
             gl.bind_renderbuffer(glow::RENDERBUFFER, None);
             gl.bind_texture(glow::TEXTURE_2D, None);
 
-            ShadowMap {
+            ShadowAtlas {
                 fbo,
                 depth_texture,
-                size,
+                depth_rbo,
+                width,
+                height,
             }
         }
     }
+
+    fn delete(&self, gl: &glow::Context) {
+        unsafe {
+            gl.delete_framebuffer(self.fbo);
+            gl.delete_texture(self.depth_texture);
+            crate::gpu_counters::gpu_counters().texture_deleted();
+            gl.delete_renderbuffer(self.depth_rbo);
+        }
+    }
+}
+
+/// The most views one casting light renders: a point light's six cube faces.
+/// Must match `MAX_SHADOW_TILES` in the lit shader.
+pub const MAX_SHADOW_TILES: usize = 6;
+
+/// The most cascades a directional light splits its view into.
+pub const MAX_SHADOW_CASCADES: u32 = 4;
+
+/// The largest shadow atlas side, in texels. Tile resolutions are clamped so
+/// a layout's grid fits it (a point light's 3 × 2 faces top out at 1365).
+pub const MAX_SHADOW_ATLAS_SIZE: u32 = 4096;
+
+/// Blend between uniform (0) and logarithmic (1) cascade split distances —
+/// the "practical split scheme": near cascades stay tight, far ones don't
+/// starve.
+const CASCADE_SPLIT_LAMBDA: f32 = 0.5;
+
+/// How far behind a cascade's bounding sphere (toward the light) casters are
+/// still captured, in world units — a tree outside the view still shadows it.
+const CASCADE_CASTER_DEPTH: f32 = 50.0;
+
+/// One light-space view of a shadow atlas: the world→light-clip matrix and the
+/// square texel rectangle (`origin` is its bottom-left corner) it renders into.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowTile {
+    pub light_space_matrix: Matrix4<f32>,
+    pub origin: [u32; 2],
+    pub size: u32,
+}
+
+/// Every view a casting light renders — one tile for the fixed directional box
+/// or a spot light, one per cascade (nearest first), or six cube faces for a
+/// point light — packed into a grid of `width` × `height` texels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShadowLayout {
+    pub tiles: Vec<ShadowTile>,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ShadowLayout {
+    /// Pack `matrices` into a grid of square tiles of (up to) `resolution`
+    /// texels: one column for a single view, two for cascades, three for the
+    /// cube faces.
+    fn packed(matrices: Vec<Matrix4<f32>>, resolution: u32) -> ShadowLayout {
+        let count = matrices.len() as u32;
+        let columns = match count {
+            0 | 1 => 1,
+            2..=4 => 2,
+            _ => 3,
+        };
+        let rows = count.div_ceil(columns).max(1);
+        let size = resolution.clamp(1, MAX_SHADOW_ATLAS_SIZE / columns.max(rows));
+        let tiles = matrices
+            .into_iter()
+            .enumerate()
+            .map(|(i, light_space_matrix)| ShadowTile {
+                light_space_matrix,
+                origin: [(i as u32 % columns) * size, (i as u32 / columns) * size],
+                size,
+            })
+            .collect();
+        ShadowLayout {
+            tiles,
+            width: columns * size,
+            height: rows * size,
+        }
+    }
 }
 
 /// World→light-clip matrix for a directional light: an orthographic box around
@@ -154,34 +281,149 @@ pub fn spot_light_space_matrix(
     proj * view
 }
 
-/// The world→light-clip matrix for a shadow-casting light (`None` if the light
-/// does not cast, or for types without a shadow path yet — point/ambient).
-pub fn light_space_matrix(light: &Light) -> Option<Matrix4<f32>> {
+/// World→light-clip matrices for a point light's six cube faces (+X, −X, +Y,
+/// −Y, +Z, −Z): 90° frusta from `position` out to `range`, widened by a small
+/// margin so PCF at a face edge stays inside that face's tile.
+pub fn point_light_space_matrices(position: [f32; 3], range: f32) -> [Matrix4<f32>; 6] {
+    let eye = Point3::new(position[0], position[1], position[2]);
+    let proj = perspective(
+        Rad(std::f32::consts::FRAC_PI_2 * 1.05),
+        1.0,
+        0.1,
+        range.max(1.0),
+    );
+    [
+        (vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)),
+        (vec3(-1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)),
+        (vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 1.0)),
+        (vec3(0.0, -1.0, 0.0), vec3(0.0, 0.0, -1.0)),
+        (vec3(0.0, 0.0, 1.0), vec3(0.0, 1.0, 0.0)),
+        (vec3(0.0, 0.0, -1.0), vec3(0.0, 1.0, 0.0)),
+    ]
+    .map(|(axis, up)| proj * Matrix4::look_to_rh(eye, axis, up))
+}
+
+/// Where the camera's view splits into `count` cascades between `near` and
+/// `distance`: `count + 1` ascending boundaries, the practical split scheme.
+pub fn cascade_splits(near: f32, distance: f32, count: u32) -> Vec<f32> {
+    let near = near.max(0.01);
+    let far = distance.max(near * 2.0);
+    (0..=count)
+        .map(|i| {
+            let t = i as f32 / count as f32;
+            let uniform = near + (far - near) * t;
+            let logarithmic = near * (far / near).powf(t);
+            uniform + (logarithmic - uniform) * CASCADE_SPLIT_LAMBDA
+        })
+        .collect()
+}
+
+/// World→light-clip matrices for a directional light's cascades, nearest
+/// first. Each fits an orthographic box around the bounding sphere of its
+/// slice of the camera frustum — a sphere, so the box keeps its size as the
+/// camera turns — with the center snapped to whole texels of a
+/// `resolution`-texel tile, so shadow edges don't shimmer as the camera moves.
+pub fn cascade_light_space_matrices(
+    direction: [f32; 3],
+    camera: &Camera,
+    aspect: f32,
+    settings: ShadowSettings,
+) -> Vec<Matrix4<f32>> {
+    let dir = vec3(direction[0], direction[1], direction[2]).normalize();
+    let up = if dir.y.abs() > 0.99 {
+        vec3(0.0, 0.0, 1.0)
+    } else {
+        vec3(0.0, 1.0, 0.0)
+    };
+    // Rotation only: the light "looks" along its rays from the origin.
+    let light_view = Matrix4::look_to_rh(Point3::new(0.0, 0.0, 0.0), dir, up);
+
+    let eye = vec3(camera.eye[0], camera.eye[1], camera.eye[2]);
+    let forward = (vec3(camera.target[0], camera.target[1], camera.target[2]) - eye).normalize();
+    let right = forward
+        .cross(vec3(camera.up[0], camera.up[1], camera.up[2]))
+        .normalize();
+    let camera_up = right.cross(forward);
+    let tan_half = (camera.fov_radians * 0.5).tan();
+
+    let count = settings.cascades.clamp(1, MAX_SHADOW_CASCADES);
+    let splits = cascade_splits(camera.near, settings.distance, count);
+    splits
+        .windows(2)
+        .map(|slice| {
+            let corners: Vec<Vector3<f32>> = slice
+                .iter()
+                .flat_map(|&d| {
+                    let (half_h, half_w) = (d * tan_half, d * tan_half * aspect);
+                    [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)].map(|(sx, sy)| {
+                        eye + forward * d + right * (sx * half_w) + camera_up * (sy * half_h)
+                    })
+                })
+                .collect();
+            let center = corners.iter().fold(vec3(0.0, 0.0, 0.0), |sum, c| sum + c) / 8.0;
+            let radius = corners
+                .iter()
+                .map(|c| (c - center).magnitude())
+                .fold(0.0_f32, f32::max);
+            // Quantize the radius too, so float noise can't resize the box.
+            let radius = (radius * 16.0).ceil() / 16.0;
+
+            let texel = 2.0 * radius / settings.resolution.max(1) as f32;
+            let c = (light_view * center.extend(1.0)).truncate();
+            let (cx, cy) = ((c.x / texel).floor() * texel, (c.y / texel).floor() * texel);
+            // View space looks down −z: the near plane sits `CASCADE_CASTER_DEPTH`
+            // past the sphere toward the light.
+            let proj = ortho(
+                cx - radius,
+                cx + radius,
+                cy - radius,
+                cy + radius,
+                -(c.z + radius + CASCADE_CASTER_DEPTH),
+                -(c.z - radius),
+            );
+            proj * light_view
+        })
+        .collect()
+}
+
+/// The shadow views of a casting light, seen from `camera` (whose view the
+/// directional cascades fit; `aspect` is its viewport's). `None` if the light
+/// does not cast (ambient never does).
+pub fn shadow_layout(light: &Light, camera: &Camera, aspect: f32) -> Option<ShadowLayout> {
     if !light.casts_shadows() {
         return None;
     }
-    match light {
-        Light::Directional { direction, .. } => Some(directional_light_space_matrix(*direction)),
+    let settings = light.shadow_settings();
+    let matrices = match light {
+        Light::Directional { direction, .. } if settings.cascades > 1 => {
+            cascade_light_space_matrices(*direction, camera, aspect, settings)
+        }
+        Light::Directional { direction, .. } => vec![directional_light_space_matrix(*direction)],
         Light::Spot {
             position,
             direction,
             cone_angle,
             range,
             ..
-        } => Some(spot_light_space_matrix(
+        } => vec![spot_light_space_matrix(
             *position,
             *direction,
             *cone_angle,
             *range,
-        )),
-        // Point shadows need a cube map (a later step); ambient never casts.
-        _ => None,
-    }
+        )],
+        Light::Point {
+            position, range, ..
+        } => point_light_space_matrices(*position, *range).to_vec(),
+        Light::Ambient { .. } => return None,
+    };
+    Some(ShadowLayout::packed(matrices, settings.resolution))
 }
 
-/// Render the scene into `shadow_map` from the light's viewpoint (a depth-only
-/// pass). Restores the framebuffer binding afterward; the caller restores the
-/// viewport for the main pass.
+/// Render the scene into `shadow_map` from each of the light's views (a
+/// depth-only pass per tile of `layout`), growing the atlas first if the
+/// layout needs it. Restores the framebuffer binding afterward; the caller
+/// restores the viewport for the main pass. Returns the forward pass's
+/// sampling uniforms, attributed to light `light_index`.
 #[allow(clippy::too_many_arguments)]
 pub fn render_shadow_pass(
     gl: &glow::Context,
@@ -192,8 +434,10 @@ pub fn render_shadow_pass(
     scene: &Scene3D,
     scene_context: &SceneContext,
     shadow_map: &ShadowMap,
-    light_space_matrix: Matrix4<f32>,
-) {
+    layout: &ShadowLayout,
+    light_index: usize,
+) -> ShadowUniforms {
+    let tile_size = layout.tiles.first().map_or(1, |tile| tile.size);
     let depth_ctx = RenderContext {
         gl,
         shader_version,
@@ -211,6 +455,7 @@ pub fn render_shadow_pass(
         opacity_stage: crate::OpacityStage::Defer,
         opacity: std::cell::Cell::new(1.0),
         shadow: None,
+        environment: None,
         // Fog is a forward-pass concern; the depth pass renders no color.
         fog: None,
         camera_pos: cgmath::Vector3::new(0.0, 0.0, 0.0),
@@ -221,7 +466,7 @@ pub fn render_shadow_pass(
         lod_view_projections: [Matrix4::identity(); 2],
         lod_frustum_count: 1,
         lod_projection_scale: 1.0,
-        viewport_height: shadow_map.size as f32,
+        viewport_height: tile_size as f32,
     };
 
     let mut depth_material = DepthMaterial::create();
@@ -245,8 +490,9 @@ pub fn render_shadow_pass(
         .map(glow::NativeFramebuffer);
         #[cfg(target_arch = "wasm32")]
         let previous_fbo: Option<glow::Framebuffer> = None;
-        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(shadow_map.fbo));
-        gl.viewport(0, 0, shadow_map.size as i32, shadow_map.size as i32);
+        let atlas = shadow_map.ensure_size(gl, layout.width, layout.height);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(atlas.fbo));
+        gl.viewport(0, 0, atlas.width as i32, atlas.height as i32);
         // Disable the scissor test for the duration of the pass: the forward pass
         // leaves SCISSOR_TEST enabled (clipped to the window's viewport pane), and
         // since the shadow pass runs first each frame it would otherwise inherit
@@ -255,22 +501,176 @@ pub fn render_shadow_pass(
         // that project past it. The shadow FBO owns the whole texture, so no
         // scissoring is wanted here; the forward pass re-enables it afterward.
         gl.disable(glow::SCISSOR_TEST);
-        // Clear the depth-color buffer to 1.0 (far) so untouched texels never
-        // shadow anything.
+        // Clear the whole depth-color atlas to 1.0 (far) so untouched texels
+        // never shadow anything.
         gl.clear_color(1.0, 1.0, 1.0, 1.0);
         gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
 
-        // Pass the light matrix as the "projection" with an identity view; the
-        // depth material multiplies projection * view * world.
-        scene.render(
-            &depth_ctx,
-            scene_context,
-            &identity,
-            &light_space_matrix,
-            &identity,
-            &depth_material,
-        );
+        for tile in &layout.tiles {
+            gl.viewport(
+                tile.origin[0] as i32,
+                tile.origin[1] as i32,
+                tile.size as i32,
+                tile.size as i32,
+            );
+            // Pass the light matrix as the "projection" with an identity view; the
+            // depth material multiplies projection * view * world.
+            scene.render(
+                &depth_ctx,
+                scene_context,
+                &identity,
+                &tile.light_space_matrix,
+                &identity,
+                &depth_material,
+            );
+        }
 
         gl.bind_framebuffer(glow::FRAMEBUFFER, previous_fbo);
     }
+
+    let mut uniforms = ShadowUniforms {
+        depth_texture: shadow_map.depth_texture(),
+        light_space_matrices: [0.0; 16 * MAX_SHADOW_TILES],
+        tile_rects: [0.0; 4 * MAX_SHADOW_TILES],
+        tile_count: layout.tiles.len().min(MAX_SHADOW_TILES) as i32,
+        light_index: light_index as i32,
+    };
+    let (width, height) = shadow_map.size();
+    for (i, tile) in layout.tiles.iter().take(MAX_SHADOW_TILES).enumerate() {
+        let matrix: &[f32; 16] = tile.light_space_matrix.as_ref();
+        uniforms.light_space_matrices[i * 16..i * 16 + 16].copy_from_slice(matrix);
+        uniforms.tile_rects[i * 4..i * 4 + 4].copy_from_slice(&[
+            tile.origin[0] as f32 / width as f32,
+            tile.origin[1] as f32 / height as f32,
+            tile.size as f32 / width as f32,
+            tile.size as f32 / height as f32,
+        ]);
+    }
+    uniforms
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{vec4, Vector4};
+
+    use super::{cascade_splits, shadow_layout, MAX_SHADOW_ATLAS_SIZE, MAX_SHADOW_CASCADES};
+    use crate::math::Angle;
+    use crate::{Camera, Light, ShadowSettings};
+
+    fn camera() -> Camera {
+        Camera::look_at(
+            [0.0, 2.0, -10.0],
+            [0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            Angle::from_degrees(60.0),
+        )
+    }
+
+    /// Whether `point` lands inside a view's light frustum (the lit shader's
+    /// test, before the tile lookup).
+    fn inside(matrix: &cgmath::Matrix4<f32>, point: [f32; 3]) -> bool {
+        let clip: Vector4<f32> = matrix * vec4(point[0], point[1], point[2], 1.0);
+        if clip.w <= 0.0 {
+            return false;
+        }
+        let ndc = clip.truncate() / clip.w;
+        ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0 && ndc.z <= 1.0
+    }
+
+    #[test]
+    fn default_directional_keeps_the_single_fixed_map() {
+        let light = Light::directional(0.5, -1.0, 0.3, 1.0, 1.0, 1.0, 1.0).cast_shadows();
+        let layout = shadow_layout(&light, &camera(), 1.5).expect("casting light");
+        assert_eq!(layout.tiles.len(), 1);
+        assert_eq!((layout.width, layout.height), (2048, 2048));
+        assert_eq!(layout.tiles[0].origin, [0, 0]);
+        assert_eq!(
+            layout.tiles[0].light_space_matrix,
+            super::directional_light_space_matrix([0.5, -1.0, 0.3])
+        );
+        assert!(shadow_layout(&Light::ambient(0.1, 0.1, 0.1), &camera(), 1.5).is_none());
+        let quiet = Light::point(0.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0, 5.0);
+        assert!(shadow_layout(&quiet, &camera(), 1.5).is_none());
+    }
+
+    #[test]
+    fn point_light_renders_six_faces_covering_every_direction() {
+        let light = Light::point(1.0, 2.0, 3.0, 1.0, 1.0, 1.0, 1.0, 10.0).cast_shadows();
+        let layout = shadow_layout(&light, &camera(), 1.5).expect("casting light");
+        assert_eq!(layout.tiles.len(), 6);
+        // 3 × 2 faces, each clamped so the atlas fits the cap.
+        let face = MAX_SHADOW_ATLAS_SIZE / 3;
+        assert!(layout.tiles.iter().all(|tile| tile.size == face));
+        assert_eq!((layout.width, layout.height), (3 * face, 2 * face));
+        assert_eq!(layout.tiles[5].origin, [2 * face, face]);
+        // Every direction from the light is held by at least one face.
+        for offset in [
+            [4.0, 0.0, 0.0],
+            [-4.0, 0.5, 0.0],
+            [0.0, 4.0, 0.2],
+            [0.3, -4.0, 0.0],
+            [0.0, 0.0, 4.0],
+            [0.0, -0.4, -4.0],
+            [2.0, 2.0, 2.0],
+        ] {
+            let point = [1.0 + offset[0], 2.0 + offset[1], 3.0 + offset[2]];
+            assert!(
+                layout
+                    .tiles
+                    .iter()
+                    .any(|tile| inside(&tile.light_space_matrix, point)),
+                "no face holds {point:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn cascades_split_the_view_nearest_first() {
+        let splits = cascade_splits(0.1, 60.0, 3);
+        assert_eq!(splits.len(), 4);
+        assert!((splits[0] - 0.1).abs() < 1e-5);
+        assert!((splits[3] - 60.0).abs() < 1e-3);
+        assert!(splits.windows(2).all(|pair| pair[0] < pair[1]));
+        // The practical scheme keeps the near cascade tighter than uniform.
+        assert!(splits[1] < 0.1 + (60.0 - 0.1) / 3.0);
+
+        let settings = ShadowSettings {
+            resolution: 1024,
+            cascades: 3,
+            distance: 60.0,
+        };
+        let light =
+            Light::directional(0.4, -1.0, 0.2, 1.0, 1.0, 1.0, 1.0).cast_shadows_with(settings);
+        let camera = camera();
+        let layout = shadow_layout(&light, &camera, 1.5).expect("casting light");
+        assert_eq!(layout.tiles.len(), 3);
+        assert_eq!((layout.width, layout.height), (2048, 2048));
+        // A point just ahead of the camera is in the first cascade; one far
+        // down the view only in a later one; one past `distance` in none.
+        let near_point = [0.0, 1.9, -8.0];
+        let far_point = [0.0, -2.0, 35.0];
+        let beyond = [0.0, -10.0, 200.0];
+        assert!(inside(&layout.tiles[0].light_space_matrix, near_point));
+        assert!(!inside(&layout.tiles[0].light_space_matrix, far_point));
+        assert!(layout.tiles[1..]
+            .iter()
+            .any(|tile| inside(&tile.light_space_matrix, far_point)));
+        assert!(!layout
+            .tiles
+            .iter()
+            .any(|tile| inside(&tile.light_space_matrix, beyond)));
+    }
+
+    #[test]
+    fn cascade_count_is_capped() {
+        let settings = ShadowSettings {
+            cascades: 9,
+            ..ShadowSettings::default()
+        };
+        let light =
+            Light::directional(0.0, -1.0, 0.0, 1.0, 1.0, 1.0, 1.0).cast_shadows_with(settings);
+        let layout = shadow_layout(&light, &camera(), 1.0).expect("casting light");
+        assert_eq!(layout.tiles.len(), MAX_SHADOW_CASCADES as usize);
+        assert!(layout.width <= MAX_SHADOW_ATLAS_SIZE && layout.height <= MAX_SHADOW_ATLAS_SIZE);
+    }
 }
@@ -0,0 +1,2629 @@
+//! A CPU rasterizer that renders a [`Frame`] with no GL context — the backend
+//! behind `--headless` capture, MCP screenshots on GPU-less machines, and the
+//! software golden images that run in plain `cargo test`.
+//!
+//! It walks the same `Frame` the GL renderer draws and mirrors it pass for
+//! pass (see `renderer::render_frame`): render-target passes, the shadow map
+//! of the first casting light, the skybox, the opaque walk with
+//! `Scene3D::render`'s node rules, the sorted `Scene.opacity` pass, and the
+//! ordered 2D sprite layers. Each material is its GLSL transcribed per
+//! fragment — color, texture, emissive (with sprite atlas rects), lit
+//! (Lambert + Blinn-Phong, normal maps, 3x3 PCF shadows, the environment's
+//! image-based light), glTF metallic-roughness (alpha masks and blending,
+//! back-face culling), skinned, and the normals/tangents debug views — and
+//! fog applies wherever the shaders apply it. The frame's post stack then
+//! runs stage for stage over the 3D pass. Texture units, like GL's, keep
+//! their last binding for the whole render.
+//!
+//! Deliberate differences from the GL path:
+//! - textures sample level 0 only (no mip chain, no anisotropy), so minified
+//!   textures alias where GL would blur;
+//! - nothing carries over between calls: a render target sampled before it is
+//!   written this frame (itself, or a later declaration) reads its clear
+//!   color, not last frame's image, and reflection probes re-bake every call;
+//! - terrain (a GPU LOD path) and `Frame.withUiTarget` painting are skipped
+//!   with a one-time warning, and the physics line overlay is not drawn.
+//!
+//! Textures and meshes are read from their CPU copies, which exist only until
+//! a GL renderer uploads them — a process renders through one backend.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::{Arc, OnceLock};
+
+use cgmath::{vec3, InnerSpace, Matrix3, Matrix4, SquareMatrix, Vector3, Vector4, Zero};
+
+use crate::asset::{AssetCache, AssetPollState};
+use crate::environment::{self, CubeImage, PrefilteredEnvironment, PROBE_SIZE};
+use crate::geometry;
+use crate::material::PbrTextures;
+use crate::math::normal_matrix;
+use crate::model::ModelMesh;
+use crate::pbr::{
+    AlphaMode, PbrFactors, EMISSIVE_TEXTURE_UNIT, METALLIC_ROUGHNESS_TEXTURE_UNIT,
+    OCCLUSION_TEXTURE_UNIT,
+};
+use crate::post::{self, PostEffect};
+use crate::render::{VertexPositionTexture, VertexPositionTextureSkinned};
+use crate::renderer::TRANSPARENT_DEBUG_ALPHA;
+use crate::scene3d::{
+    billboard_xform, expand_instanced, model_pose_joints, resolve_model_for_draw,
+};
+use crate::texture::{PixelFormat, Texture2D, TextureData};
+use crate::{
+    pack_lights, sort_back_to_front, BuiltinTexture, Camera, DebugRenderMode, Fog, Frame,
+    FrameTime, Light, LightUniforms, MaterialDescription, MeshOverride, ModelDescription,
+    ModelHandle, OpacityStage, Scene3D, SceneContext, SceneObject, Shape, SkyboxDescription,
+    SpriteSampling, TextureDescription, TransparentDraw, Viewport,
+};
+
+/// `MAX_JOINTS` in the skinned shaders: joint indices past it read nothing.
+const MAX_JOINTS: usize = 200;
+
+/// How many times [`capture_png`] re-renders while the asset cache still has
+/// loads in flight. Local files decode on their first poll; this covers
+/// assets that start further loads (a model's textures) one pass later.
+const MAX_SETTLE_PASSES: usize = 8;
+
+/// Per-vertex outputs interpolated across a triangle: world position (3),
+/// uv (2), world normal (3), world tangent (3), world bitangent (3).
+const VARYINGS: usize = 14;
+
+/// Render `frame` at `width` × `height` and return its pixels as top-down
+/// RGBA8 rows (the layout [`crate::frame_capture::encode_rgba_png`] takes).
+///
+/// The software counterpart of [`crate::render_frame`] for a full-surface
+/// viewport and the frame's own camera. Every call starts from scratch: the
+/// asset cache and `scene_context` supply models and textures (polling their
+/// loads, exactly like a GL frame does), but no GPU state exists to persist.
+pub fn render_frame(
+    asset_cache: Arc<AssetCache>,
+    scene_context: &SceneContext,
+    frame: &Frame,
+    frame_time: FrameTime,
+    width: u32,
+    height: u32,
+    debug_render_mode: DebugRenderMode,
+) -> Vec<u8> {
+    let renderer = Renderer {
+        asset_cache,
+        scene_context,
+        frame_time,
+        debug_render_mode,
+        units: RefCell::new(Default::default()),
+        textures: RefCell::new(HashMap::new()),
+        targets: RefCell::new(HashMap::new()),
+        font_atlas: RefCell::new(None),
+    };
+    renderer.render_target_passes(frame);
+
+    let lighting = renderer.image_lighting(frame);
+    let viewport = Viewport::new(width, height);
+    let mut canvas = Canvas::new(
+        width as usize,
+        height as usize,
+        frame.resolved_clear_color(),
+    );
+    let shadow = renderer.shadow_pass(
+        &frame.lights,
+        &frame.scene,
+        &frame.camera,
+        width as f32 / height.max(1) as f32,
+    );
+    renderer.forward_pass(
+        &mut canvas,
+        viewport,
+        ForwardInputs {
+            scene: &frame.scene,
+            lights: &frame.lights,
+            camera: &frame.camera,
+            shadow: shadow.as_ref(),
+            fog: frame.fog.as_ref(),
+            skybox: frame.skybox.as_ref(),
+            environment: Some(&lighting),
+            projection: None,
+        },
+        false,
+        debug_render_mode,
+    );
+    renderer.post_pass(&mut canvas, frame);
+    renderer.sprite_layers(&mut canvas, frame, viewport);
+    canvas.top_down_rgba()
+}
+
+/// Render `frame` in software and encode it as a PNG — the headless
+/// `/capture` and `--capture-frame` path. Re-renders (up to a small bound)
+/// while asset loads are still in flight, so a capture taken on the first
+/// frame that references an asset shows the asset rather than its fallback.
+pub fn capture_png(
+    asset_cache: Arc<AssetCache>,
+    scene_context: &SceneContext,
+    frame: &Frame,
+    frame_time: FrameTime,
+    width: u32,
+    height: u32,
+    debug_render_mode: DebugRenderMode,
+) -> Result<Vec<u8>, String> {
+    let mut rgba = Vec::new();
+    for _ in 0..MAX_SETTLE_PASSES {
+        rgba = render_frame(
+            asset_cache.clone(),
+            scene_context,
+            frame,
+            frame_time,
+            width,
+            height,
+            debug_render_mode,
+        );
+        let progress = asset_cache.progress();
+        if progress.loaded + progress.failed.len() >= progress.total {
+            break;
+        }
+    }
+    crate::frame_capture::encode_rgba_png(width, height, &rgba)
+}
+
+/// A texture as a sampler reads it: RGBA8 rows in upload order, so row 0 is
+/// `v = 0` — the top of a file image, the bottom of a render target.
+struct Texels {
+    width: usize,
+    height: usize,
+    rgba: Vec<[u8; 4]>,
+}
+
+impl Texels {
+    /// `None` for an empty or truncated image (the raw pipeline's 0x0
+    /// undecodable sentinel), which then samples like an unbound unit.
+    fn from_data(data: &TextureData) -> Option<Texels> {
+        let (width, height) = (data.width as usize, data.height as usize);
+        let channels = match data.format {
+            PixelFormat::RGB => 3,
+            PixelFormat::RGBA => 4,
+        };
+        if width == 0 || height == 0 || data.bytes.len() < width * height * channels {
+            return None;
+        }
+        let rgba = data
+            .bytes
+            .chunks_exact(channels)
+            .take(width * height)
+            .map(|p| [p[0], p[1], p[2], if channels == 4 { p[3] } else { 255 }])
+            .collect();
+        Some(Texels {
+            width,
+            height,
+            rgba,
+        })
+    }
+
+    fn solid(width: usize, height: usize, rgba: [u8; 4]) -> Texels {
+        Texels {
+            width,
+            height,
+            rgba: vec![rgba; width * height],
+        }
+    }
+
+    fn texel(&self, x: i64, y: i64, repeat: bool) -> Vector4<f32> {
+        let (w, h) = (self.width as i64, self.height as i64);
+        let (x, y) = if repeat {
+            (x.rem_euclid(w), y.rem_euclid(h))
+        } else {
+            (x.clamp(0, w - 1), y.clamp(0, h - 1))
+        };
+        let [r, g, b, a] = self.rgba[(y * w + x) as usize];
+        Vector4::new(r as f32, g as f32, b as f32, a as f32) / 255.0
+    }
+
+    /// GL's level-0 `NEAREST` / `LINEAR` filtering with `REPEAT` or
+    /// `CLAMP_TO_EDGE` wrapping.
+    fn sample(&self, u: f32, v: f32, repeat: bool, linear: bool) -> Vector4<f32> {
+        let x = u * self.width as f32;
+        let y = v * self.height as f32;
+        if !linear {
+            return self.texel(x.floor() as i64, y.floor() as i64, repeat);
+        }
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let top = self.texel(x0, y0, repeat) * (1.0 - fx) + self.texel(x0 + 1, y0, repeat) * fx;
+        let bottom =
+            self.texel(x0, y0 + 1, repeat) * (1.0 - fx) + self.texel(x0 + 1, y0 + 1, repeat) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+}
+
+/// One texture unit's binding: the texels plus the wrap/filter state GL keeps
+/// on the bound texture object. Unbound samples `(0, 0, 0, 1)`, as GL does.
+#[derive(Clone, Default)]
+struct Unit(Option<(Rc<Texels>, bool, bool)>);
+
+impl Unit {
+    fn sample(&self, u: f32, v: f32) -> Vector4<f32> {
+        match &self.0 {
+            Some((texels, repeat, linear)) => texels.sample(u, v, *repeat, *linear),
+            None => Vector4::new(0.0, 0.0, 0.0, 1.0),
+        }
+    }
+
+    /// `textureSize(unit, 0)`.
+    fn size(&self) -> (f32, f32) {
+        match &self.0 {
+            Some((texels, ..)) => (texels.width as f32, texels.height as f32),
+            None => (0.0, 0.0),
+        }
+    }
+}
+
+/// What a node draws with — the software `Box<dyn Material>`. Texture-reading
+/// variants sample whatever their unit holds at DRAW time, like the shaders.
+#[derive(Clone, Copy)]
+enum Shading {
+    Color(Vector4<f32>),
+    /// `BasicMaterial`: unit 0, unmodulated.
+    Basic,
+    Emissive {
+        color: Vector4<f32>,
+        use_texture: bool,
+        source_pixels: Option<[f32; 4]>,
+    },
+    /// `LitMaterial`, and `SkinnedMaterial` as white albedo over unit 0.
+    Lit {
+        color: Vector4<f32>,
+        use_texture: bool,
+        use_normal_map: bool,
+    },
+    /// `PbrMaterial`, with whichever units it was bound.
+    Pbr {
+        factors: PbrFactors,
+        textures: PbrTextures,
+    },
+    Normals,
+    Tangents,
+    Depth,
+}
+
+/// Which screen-space winding a draw culls (`GL_CULL_FACE` with the draw's
+/// `glFrontFace`).
+#[derive(Clone, Copy, PartialEq)]
+enum Cull {
+    Off,
+    Clockwise,
+    CounterClockwise,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DepthTest {
+    Less,
+    Equal,
+    /// `DEPTH_TEST` disabled — which also disables depth writes.
+    Off,
+}
+
+#[derive(Clone, Copy)]
+enum Blend {
+    Off,
+    /// `SRC_ALPHA, ONE_MINUS_SRC_ALPHA` (alpha: `ONE, ONE_MINUS_SRC_ALPHA`).
+    Alpha,
+    /// `CONSTANT_ALPHA, ONE_MINUS_CONSTANT_ALPHA` (alpha as above).
+    Constant(f32),
+}
+
+#[derive(Clone, Copy)]
+struct RasterState {
+    depth_test: DepthTest,
+    depth_write: bool,
+    color_write: bool,
+    blend: Blend,
+}
+
+/// A color + depth target with GL's bottom-up row order. A depth-only canvas
+/// (the shadow map) has no color storage.
+struct Canvas {
+    width: usize,
+    height: usize,
+    color: Vec<[u8; 4]>,
+    depth: Vec<f32>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize, clear: [f32; 3]) -> Canvas {
+        let [r, g, b] = clear;
+        Canvas {
+            width,
+            height,
+            color: vec![to_unorm8(Vector4::new(r, g, b, 1.0)); width * height],
+            depth: vec![1.0; width * height],
+        }
+    }
+
+    fn depth_only(size: usize) -> Canvas {
+        Canvas {
+            width: size,
+            height: size,
+            color: Vec::new(),
+            depth: vec![1.0; size * size],
+        }
+    }
+
+    fn clear_depth(&mut self, viewport: Viewport) {
+        for y in viewport.y as usize..(viewport.y + viewport.height) as usize {
+            let row = y * self.width;
+            self.depth[row + viewport.x as usize..row + (viewport.x + viewport.width) as usize]
+                .fill(1.0);
+        }
+    }
+
+    fn top_down_rgba(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.width * self.height * 4);
+        for row in self.color.chunks_exact(self.width.max(1)).rev() {
+            out.extend(row.iter().flatten());
+        }
+        out
+    }
+
+    /// The canvas as a sampled render-target texture: row 0 stays the bottom.
+    fn into_texels(self) -> Texels {
+        Texels {
+            width: self.width,
+            height: self.height,
+            rgba: self.color,
+        }
+    }
+}
+
+fn to_unorm8(color: Vector4<f32>) -> [u8; 4] {
+    // `as u8` saturates and maps NaN to 0 — GL's fixed-point conversion.
+    let c = |x: f32| (x.clamp(0.0, 1.0) * 255.0).round() as u8;
+    [c(color.x), c(color.y), c(color.z), c(color.w)]
+}
+
+/// The single casting light's depth maps (`renderer::shadow_pass`): one per
+/// view, in the order the lit shader tries them.
+struct ShadowMap {
+    views: Vec<ShadowView>,
+    light_index: usize,
+}
+
+/// One view's square depth map — a GL atlas tile.
+struct ShadowView {
+    depth: Vec<f32>,
+    size: usize,
+    light_space_matrix: Matrix4<f32>,
+}
+
+/// A vertex format the software vertex stage can read.
+trait SoftwareVertex {
+    fn position(&self) -> Vector3<f32>;
+    fn uv(&self) -> [f32; 2];
+    fn normal(&self) -> Vector3<f32>;
+    fn tangent(&self) -> Vector4<f32>;
+    /// `(joint_indices, weights)` for skinned formats.
+    fn skin(&self) -> Option<(Vector4<f32>, Vector4<f32>)> {
+        None
+    }
+}
+
+impl SoftwareVertex for VertexPositionTexture {
+    fn position(&self) -> Vector3<f32> {
+        self.position
+    }
+    fn uv(&self) -> [f32; 2] {
+        [self.uv.x, self.uv.y]
+    }
+    fn normal(&self) -> Vector3<f32> {
+        self.normal
+    }
+    fn tangent(&self) -> Vector4<f32> {
+        self.tangent
+    }
+}
+
+impl SoftwareVertex for VertexPositionTextureSkinned {
+    fn position(&self) -> Vector3<f32> {
+        self.position
+    }
+    fn uv(&self) -> [f32; 2] {
+        [self.uv.x, self.uv.y]
+    }
+    fn normal(&self) -> Vector3<f32> {
+        self.normal
+    }
+    fn tangent(&self) -> Vector4<f32> {
+        self.tangent
+    }
+    fn skin(&self) -> Option<(Vector4<f32>, Vector4<f32>)> {
+        Some((self.joint_indices, self.weights))
+    }
+}
+
+type MeshData = (Vec<VertexPositionTexture>, Vec<u32>);
+
+/// The shared primitive meshes, built once per process like the GL ones are
+/// built once per `SceneContext`.
+fn primitive_mesh(shape: &Shape) -> Option<&'static MeshData> {
+    static CUBE: OnceLock<MeshData> = OnceLock::new();
+    static SPHERE: OnceLock<MeshData> = OnceLock::new();
+    static CYLINDER: OnceLock<MeshData> = OnceLock::new();
+    static QUAD: OnceLock<MeshData> = OnceLock::new();
+    static PLANE: OnceLock<MeshData> = OnceLock::new();
+    Some(match shape {
+        Shape::Cube => CUBE.get_or_init(geometry::cube_mesh_data),
+        Shape::Sphere => SPHERE.get_or_init(geometry::sphere_mesh_data),
+        Shape::Cylinder => CYLINDER.get_or_init(geometry::cylinder_mesh_data),
+        Shape::Quad | Shape::Billboard => QUAD.get_or_init(geometry::quad_mesh_data),
+        Shape::Plane => PLANE.get_or_init(geometry::plane_mesh_data),
+        Shape::Heightmap { .. } | Shape::ConvexPolygon { .. } => return None,
+    })
+}
+
+fn mat3(m: &Matrix4<f32>) -> Matrix3<f32> {
+    Matrix3::from_cols(m.x.truncate(), m.y.truncate(), m.z.truncate())
+}
+
+fn clamp01(x: f32) -> f32 {
+    x.clamp(0.0, 1.0)
+}
+
+/// The inputs of one forward pass (`renderer::forward_pass`'s scene-side
+/// arguments).
+struct ForwardInputs<'f> {
+    scene: &'f Scene3D,
+    lights: &'f [Light],
+    camera: &'f Camera,
+    shadow: Option<&'f ShadowMap>,
+    fog: Option<&'f Fog>,
+    skybox: Option<&'f SkyboxDescription>,
+    environment: Option<&'f ImageLighting>,
+    /// An explicit projection (the sprite pass's ortho), else the camera's.
+    projection: Option<Matrix4<f32>>,
+}
+
+/// A frame's image-based light (`renderer::environment_lighting`): its
+/// prefiltered environment and baked probes, at the frame's intensity.
+struct ImageLighting {
+    environment: Option<Arc<PrefilteredEnvironment>>,
+    probes: Vec<(Vector3<f32>, Arc<PrefilteredEnvironment>)>,
+    intensity: f32,
+}
+
+impl ImageLighting {
+    /// `EnvironmentLighting::at`: the nearest probe, else the environment.
+    fn at(&self, position: Vector3<f32>) -> Option<&Arc<PrefilteredEnvironment>> {
+        environment::nearest_probe(&self.probes, position).or(self.environment.as_ref())
+    }
+}
+
+/// Per-render state — what GL keeps in the context between draws.
+struct Renderer<'a> {
+    asset_cache: Arc<AssetCache>,
+    scene_context: &'a SceneContext,
+    frame_time: FrameTime,
+    debug_render_mode: DebugRenderMode,
+    /// Texture units 0 (albedo) to 5 (emissive); unit 1 is the shadow map,
+    /// which the lit shading reads directly.
+    units: RefCell<[Unit; 6]>,
+    /// Converted textures, keyed by the source texture's address (the asset
+    /// pipelines keep every source alive for the whole render).
+    textures: RefCell<HashMap<usize, Rc<Texels>>>,
+    /// Render-target images by id, row 0 at the bottom.
+    targets: RefCell<HashMap<String, Rc<Texels>>>,
+    font_atlas: RefCell<Option<Rc<Texels>>>,
+}
+
+impl Renderer<'_> {
+    /// `renderer::render_frame_inner`'s target loop: first declaration wins,
+    /// nested targets are ignored, and each pass gets its own shadow map.
+    fn render_target_passes(&self, frame: &Frame) {
+        // Every declared target exists (as its clear color) before any pass
+        // runs, so a target sampled ahead of its own pass reads that instead
+        // of the magenta fallback.
+        let mut declared = HashSet::new();
+        for pass in &frame.render_targets {
+            if declared.insert(pass.target.id.as_str()) {
+                let [r, g, b] = pass.frame.resolved_clear_color();
+                let clear = to_unorm8(Vector4::new(r, g, b, 1.0));
+                let (width, height) = target_size(pass.target.width, pass.target.height);
+                self.targets.borrow_mut().insert(
+                    pass.target.id.clone(),
+                    Rc::new(Texels::solid(width, height, clear)),
+                );
+            } else {
+                self.scene_context.warn_once(
+                    &format!("duplicate:{}", pass.target.id),
+                    &format!(
+                        "[render-target] \"{}\" is declared more than once in a \
+frame — only the first declaration is rendered",
+                        pass.target.id
+                    ),
+                );
+            }
+        }
+        for ui in &frame.ui_targets {
+            self.scene_context.warn_once(
+                &format!("software-ui-target:{}", ui.target.id),
+                &format!(
+                    "[software-renderer] ui target \"{}\" is not painted by the \
+software renderer — sampling it binds the magenta fallback",
+                    ui.target.id
+                ),
+            );
+        }
+
+        let mut rendered = HashSet::new();
+        for pass in &frame.render_targets {
+            if !rendered.insert(pass.target.id.as_str()) {
+                continue;
+            }
+            if !pass.frame.render_targets.is_empty() {
+                self.scene_context.warn_once(
+                    &format!("nested:{}", pass.target.id),
+                    &format!(
+                        "[render-target] \"{}\": nested render targets inside a \
+target frame are ignored (depth 1 only)",
+                        pass.target.id
+                    ),
+                );
+            }
+            if !pass.frame.ui_targets.is_empty() {
+                self.scene_context.warn_once(
+                    &format!("nested-ui:{}", pass.target.id),
+                    &format!(
+                        "[render-target] \"{}\": ui targets inside a target frame \
+are ignored — declare Frame.withUiTarget on the main frame",
+                        pass.target.id
+                    ),
+                );
+            }
+            if !pass.frame.post.is_empty() {
+                self.scene_context.warn_once(
+                    &format!("nested-post:{}", pass.target.id),
+                    &format!(
+                        "[render-target] \"{}\": Frame.withPost inside a target \
+frame is ignored — post-processing applies to the main frame only",
+                        pass.target.id
+                    ),
+                );
+            }
+
+            let (width, height) = target_size(pass.target.width, pass.target.height);
+            let lighting = self.image_lighting(&pass.frame);
+            let shadow = self.shadow_pass(
+                &pass.frame.lights,
+                &pass.frame.scene,
+                &pass.frame.camera,
+                width as f32 / height as f32,
+            );
+            let mut canvas = Canvas::new(width, height, pass.frame.resolved_clear_color());
+            let viewport = Viewport::new(width as u32, height as u32);
+            self.forward_pass(
+                &mut canvas,
+                viewport,
+                ForwardInputs {
+                    scene: &pass.frame.scene,
+                    lights: &pass.frame.lights,
+                    camera: &pass.frame.camera,
+                    shadow: shadow.as_ref(),
+                    fog: pass.frame.fog.as_ref(),
+                    skybox: pass.frame.skybox.as_ref(),
+                    environment: Some(&lighting),
+                    projection: None,
+                },
+                false,
+                self.debug_render_mode,
+            );
+            self.sprite_layers(&mut canvas, &pass.frame, viewport);
+            self.targets
+                .borrow_mut()
+                .insert(pass.target.id.clone(), Rc::new(canvas.into_texels()));
+        }
+    }
+
+    /// Depth from the first shadow-casting light, one map per view of its
+    /// `shadow::shadow_layout` (the atlas tiles of the GL pass), each with the
+    /// view's light matrix as the projection and an identity view.
+    fn shadow_pass(
+        &self,
+        lights: &[Light],
+        scene: &Scene3D,
+        camera: &Camera,
+        aspect: f32,
+    ) -> Option<ShadowMap> {
+        let (light_index, layout) = lights.iter().enumerate().find_map(|(i, l)| {
+            crate::shadow::shadow_layout(l, camera, aspect).map(|layout| (i, layout))
+        })?;
+        let views = layout
+            .tiles
+            .iter()
+            .map(|tile| {
+                let size = tile.size as usize;
+                let mut canvas = Canvas::depth_only(size);
+                let mut pass = Pass {
+                    renderer: self,
+                    canvas: &mut canvas,
+                    viewport: Viewport::new(tile.size, tile.size),
+                    view: Matrix4::identity(),
+                    projection: tile.light_space_matrix,
+                    lights: pack_lights(&[]),
+                    shadow: None,
+                    environment: None,
+                    fog: None,
+                    camera_pos: vec3(0.0, 0.0, 0.0),
+                    depth_pass: true,
+                    debug_render_mode: DebugRenderMode::Default,
+                    pass_blends: false,
+                    blend_active: false,
+                    opacity_stage: OpacityStage::Defer,
+                    opacity: 1.0,
+                    state: RasterState {
+                        depth_test: DepthTest::Less,
+                        depth_write: true,
+                        color_write: false,
+                        blend: Blend::Off,
+                    },
+                };
+                pass.render(scene, &Matrix4::identity(), Shading::Depth);
+                ShadowView {
+                    depth: canvas.depth,
+                    size,
+                    light_space_matrix: tile.light_space_matrix,
+                }
+            })
+            .collect();
+        Some(ShadowMap { views, light_index })
+    }
+
+    /// `renderer::environment_lighting`: the frame environment once its
+    /// inputs have decoded, and every reflection probe baked afresh — six
+    /// square forward passes around it (lit by the environment alone) read
+    /// back and prefiltered.
+    fn image_lighting(&self, frame: &Frame) -> ImageLighting {
+        let mut lighting = ImageLighting {
+            environment: frame.environment.as_ref().and_then(|environment| {
+                self.scene_context
+                    .prefiltered_environment(&self.asset_cache, &environment.source)
+            }),
+            probes: vec![],
+            intensity: frame
+                .environment
+                .as_ref()
+                .map_or(1.0, |environment| environment.intensity),
+        };
+        let size = PROBE_SIZE as usize;
+        let probes = frame
+            .reflection_probes
+            .iter()
+            .map(|probe| {
+                let faces: [Vec<u8>; 6] = std::array::from_fn(|face| {
+                    let camera = environment::probe_face_camera(
+                        probe.position,
+                        face,
+                        frame.camera.near,
+                        frame.camera.far,
+                    );
+                    let shadow = self.shadow_pass(&frame.lights, &frame.scene, &camera, 1.0);
+                    let mut canvas = Canvas::new(size, size, frame.resolved_clear_color());
+                    self.forward_pass(
+                        &mut canvas,
+                        Viewport::new(PROBE_SIZE, PROBE_SIZE),
+                        ForwardInputs {
+                            scene: &frame.scene,
+                            lights: &frame.lights,
+                            camera: &camera,
+                            shadow: shadow.as_ref(),
+                            fog: frame.fog.as_ref(),
+                            skybox: frame.skybox.as_ref(),
+                            environment: Some(&lighting),
+                            projection: None,
+                        },
+                        false,
+                        DebugRenderMode::Default,
+                    );
+                    // Row 0 at the bottom, like the GL read-back.
+                    canvas.color.iter().flatten().copied().collect()
+                });
+                let cube = CubeImage::from_rgba_faces(size, faces.each_ref().map(Vec::as_slice));
+                let prefiltered = PrefilteredEnvironment::new(&cube, size);
+                (probe.position.into(), Arc::new(prefiltered))
+            })
+            .collect();
+        lighting.probes = probes;
+        lighting
+    }
+
+    /// `renderer::forward_pass`: skybox, the opaque walk (or the transparent
+    /// debug recipe), then the sorted `Scene.opacity` pass.
+    fn forward_pass(
+        &self,
+        canvas: &mut Canvas,
+        viewport: Viewport,
+        inputs: ForwardInputs<'_>,
+        caller_blends: bool,
+        debug_render_mode: DebugRenderMode,
+    ) {
+        let view = inputs.camera.view_matrix();
+        let projection = inputs
+            .projection
+            .unwrap_or_else(|| inputs.camera.projection_matrix(viewport.aspect()));
+
+        if let Some(desc) = inputs.skybox {
+            self.draw_skybox(canvas, viewport, desc, &projection, &view);
+        }
+
+        let transparent_debug = debug_render_mode == DebugRenderMode::Transparent;
+        let pass_blends = caller_blends || transparent_debug;
+        let eye = inputs.camera.eye;
+        let mut pass = Pass {
+            renderer: self,
+            canvas,
+            viewport,
+            view,
+            projection,
+            lights: pack_lights(inputs.lights),
+            shadow: inputs.shadow,
+            environment: inputs.environment,
+            fog: inputs.fog,
+            camera_pos: vec3(eye[0], eye[1], eye[2]),
+            depth_pass: false,
+            debug_render_mode,
+            pass_blends,
+            blend_active: false,
+            opacity_stage: if pass_blends {
+                OpacityStage::Ignore
+            } else {
+                OpacityStage::Defer
+            },
+            opacity: 1.0,
+            // The sprite pass: no depth test, straight-alpha over.
+            state: if caller_blends {
+                RasterState {
+                    depth_test: DepthTest::Off,
+                    depth_write: false,
+                    color_write: true,
+                    blend: Blend::Alpha,
+                }
+            } else {
+                RasterState {
+                    depth_test: DepthTest::Less,
+                    depth_write: true,
+                    color_write: true,
+                    blend: Blend::Off,
+                }
+            },
+        };
+        let identity = Matrix4::identity();
+
+        if transparent_debug {
+            // Depth first, then blend only the nearest surface, then clear
+            // depth (see the GL pass for why).
+            pass.state.color_write = false;
+            pass.render(inputs.scene, &identity, Shading::Basic);
+            pass.state = RasterState {
+                depth_test: DepthTest::Equal,
+                depth_write: false,
+                color_write: true,
+                blend: Blend::Constant(TRANSPARENT_DEBUG_ALPHA),
+            };
+            pass.render(inputs.scene, &identity, Shading::Basic);
+            pass.canvas.clear_depth(viewport);
+            return;
+        }
+
+        pass.render(inputs.scene, &identity, Shading::Basic);
+
+        if pass_blends || !inputs.scene.has_opacity() {
+            return;
+        }
+        let mut draws: Vec<TransparentDraw<'_>> = Vec::new();
+        inputs
+            .scene
+            .collect_transparent(&identity, None, &mut draws);
+        sort_back_to_front(&mut draws, &pass.view);
+        pass.pass_blends = true;
+        pass.opacity_stage = OpacityStage::Draw;
+        pass.state = RasterState {
+            depth_test: DepthTest::Less,
+            depth_write: false,
+            color_write: true,
+            blend: Blend::Constant(1.0),
+        };
+        for draw in &draws {
+            let material = draw
+                .material
+                .map(|description| pass.resolve_material(description))
+                .unwrap_or(Shading::Basic);
+            pass.render(draw.node, &draw.parent_world, material);
+        }
+    }
+
+    /// `SceneContext::draw_post_stack`: every stage evaluated at its target's
+    /// texel centers with linear, clamped sampling into RGBA8 images, as the
+    /// GL targets store them. Depth of field reads the canvas's own depth
+    /// where GL packs a separate depth pass (which, like this one, holds no
+    /// translucent subtrees).
+    fn post_pass(&self, canvas: &mut Canvas, frame: &Frame) {
+        if frame.post.is_empty() {
+            return;
+        }
+        let (width, height) = (canvas.width, canvas.height);
+        let mut source = Texels {
+            width,
+            height,
+            rgba: std::mem::take(&mut canvas.color),
+        };
+        for effect in &frame.post {
+            source = match effect {
+                PostEffect::Bloom {
+                    threshold,
+                    intensity,
+                } => {
+                    let (bloom_width, bloom_height) = ((width / 2).max(1), (height / 2).max(1));
+                    let mut bloom = post_image(bloom_width, bloom_height, |uv| {
+                        post::bright_pass(sample_rgb(&source, uv), *threshold)
+                    });
+                    for _ in 0..post::BLOOM_BLUR_ITERATIONS {
+                        for step in [
+                            [1.0 / bloom_width as f32, 0.0],
+                            [0.0, 1.0 / bloom_height as f32],
+                        ] {
+                            bloom = post_image(bloom_width, bloom_height, |uv| {
+                                post::gaussian_blur(|at| sample_rgb(&bloom, at), uv, step)
+                            });
+                        }
+                    }
+                    post_image(width, height, |uv| {
+                        let (color, glow) = (sample_rgb(&source, uv), sample_rgb(&bloom, uv));
+                        std::array::from_fn(|i| color[i] + glow[i] * intensity)
+                    })
+                }
+                PostEffect::Tonemap { curve, exposure } => post_image(width, height, |uv| {
+                    post::tonemap(sample_rgb(&source, uv), *curve, *exposure)
+                }),
+                PostEffect::ColorGrade { lut, strength } => match self.post_lut(lut) {
+                    Some(lut) => post_image(width, height, |uv| {
+                        let color = sample_rgb(&source, uv).map(clamp01);
+                        let (low, high, t) =
+                            post::lut_lookup(color, lut.width as f32, lut.height as f32);
+                        let (low, high) = (sample_rgb(&lut, low), sample_rgb(&lut, high));
+                        std::array::from_fn(|i| {
+                            let graded = low[i] + (high[i] - low[i]) * t;
+                            color[i] + (graded - color[i]) * strength
+                        })
+                    }),
+                    None => source,
+                },
+                PostEffect::Vignette {
+                    intensity,
+                    smoothness,
+                } => post_image(width, height, |uv| {
+                    let factor = post::vignette_factor(
+                        uv,
+                        width as f32,
+                        height as f32,
+                        *intensity,
+                        *smoothness,
+                    );
+                    sample_rgb(&source, uv).map(|channel| channel * factor)
+                }),
+                PostEffect::Fxaa => post_image(width, height, |uv| {
+                    let texel = [1.0 / width as f32, 1.0 / height as f32];
+                    post::fxaa(|at| sample_rgb(&source, at), uv, texel)
+                }),
+                PostEffect::DepthOfField {
+                    focus_distance,
+                    focus_range,
+                    max_blur,
+                } => post_image(width, height, |uv| {
+                    let coc = |[u, v]: [f32; 2]| {
+                        let x = ((u * width as f32) as i64).clamp(0, width as i64 - 1) as usize;
+                        let y = ((v * height as f32) as i64).clamp(0, height as i64 - 1) as usize;
+                        let depth = post::linear_depth(
+                            canvas.depth[y * width + x],
+                            frame.camera.near,
+                            frame.camera.far,
+                        );
+                        post::circle_of_confusion(depth, *focus_distance, *focus_range, *max_blur)
+                    };
+                    let texel = [1.0 / width as f32, 1.0 / height as f32];
+                    post::depth_of_field(|at| sample_rgb(&source, at), coc, uv, texel)
+                }),
+            };
+        }
+        canvas.color = source.rgba;
+    }
+
+    /// `SceneContext::post_lut`: a file lookup table once it has loaded.
+    fn post_lut(&self, lut: &TextureDescription) -> Option<Rc<Texels>> {
+        let file = match lut {
+            TextureDescription::File(file)
+            | TextureDescription::FileClamped(file)
+            | TextureDescription::FileWhilePending { file, .. }
+            | TextureDescription::FileClampedWhilePending { file, .. } => file,
+            TextureDescription::RenderTarget(_) | TextureDescription::Builtin(_) => {
+                self.scene_context.warn_once(
+                    "post-lut-kind",
+                    "[post] Post.lut needs a file texture — color grading skipped",
+                );
+                return None;
+            }
+        };
+        let handle = self
+            .asset_cache
+            .load_asset_with_pipeline(self.scene_context.texture_pipeline.clone(), file);
+        match handle.poll_state() {
+            AssetPollState::Loaded(texture) => {
+                self.texture_texels(&texture).map(|(texels, ..)| texels)
+            }
+            AssetPollState::Loading => None,
+            AssetPollState::Failed => {
+                self.scene_context
+                    .warn_once_with(&format!("post-lut:{file}"), || {
+                        format!("[post] LUT \"{file}\" failed to load — grading skipped")
+                    });
+                None
+            }
+        }
+    }
+
+    /// `renderer::render_sprite_layers`: each layer in its aspect-fitted
+    /// viewport with an orthographic camera, alpha-blended with no depth.
+    fn sprite_layers(&self, canvas: &mut Canvas, frame: &Frame, viewport: Viewport) {
+        for layer in &frame.sprite_layers {
+            let fitted = layer.camera.fitted_viewport(viewport);
+            let camera = layer.camera.render_camera();
+            self.forward_pass(
+                canvas,
+                fitted,
+                ForwardInputs {
+                    scene: &layer.scene,
+                    lights: &[],
+                    camera: &camera,
+                    shadow: None,
+                    fog: None,
+                    skybox: None,
+                    environment: None,
+                    projection: Some(layer.camera.projection_matrix()),
+                },
+                true,
+                DebugRenderMode::Default,
+            );
+        }
+    }
+
+    /// The skybox behind everything: each pixel's view ray (translation
+    /// stripped) samples the cubemap, with no depth write and no fog. Skipped
+    /// while faces load or after a face failure, like the GL draw.
+    fn draw_skybox(
+        &self,
+        canvas: &mut Canvas,
+        viewport: Viewport,
+        desc: &SkyboxDescription,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+    ) {
+        let Some(faces) = self.skybox_faces(desc) else {
+            return;
+        };
+        let mut rotation = *view;
+        rotation.w = Vector4::new(0.0, 0.0, 0.0, 1.0);
+        let Some(unproject) = (projection * rotation).invert() else {
+            return;
+        };
+        for y in viewport.y as usize..(viewport.y + viewport.height) as usize {
+            for x in viewport.x as usize..(viewport.x + viewport.width) as usize {
+                let ndc_x = ((x - viewport.x as usize) as f32 + 0.5) / viewport.width as f32;
+                let ndc_y = ((y - viewport.y as usize) as f32 + 0.5) / viewport.height as f32;
+                let far = unproject * Vector4::new(ndc_x * 2.0 - 1.0, ndc_y * 2.0 - 1.0, 1.0, 1.0);
+                let color = sample_cube(&faces, far.truncate() / far.w);
+                canvas.color[y * canvas.width + x] = to_unorm8(color);
+            }
+        }
+    }
+
+    /// The six faces (px, nx, py, ny, pz, nz) once all have loaded and
+    /// validated — the rules of `SceneContext::skybox_texture`.
+    fn skybox_faces(&self, desc: &SkyboxDescription) -> Option<Vec<Texels>> {
+        let key = desc.faces().join("\n");
+        let mut faces: Vec<Arc<TextureData>> = Vec::with_capacity(6);
+        let mut pending = false;
+        for path in desc.faces() {
+            let handle = self
+                .asset_cache
+                .load_asset_with_pipeline(self.scene_context.raw_image_pipeline.clone(), path);
+            match handle.poll_state() {
+                AssetPollState::Loaded(data) => faces.push(data),
+                AssetPollState::Loading => pending = true,
+                AssetPollState::Failed => {
+                    self.scene_context.warn_once(
+                        &key,
+                        &format!(
+                            "[skybox] face \"{path}\" failed to load — skybox \
+disabled for this set"
+                        ),
+                    );
+                    return None;
+                }
+            }
+        }
+        if pending {
+            return None;
+        }
+        let (w, h) = (faces[0].width, faces[0].height);
+        let texels: Vec<Texels> = faces.iter().filter_map(|f| Texels::from_data(f)).collect();
+        let valid = w > 0
+            && w == h
+            && texels.len() == 6
+            && faces.iter().all(|f| f.width == w && f.height == h);
+        if !valid {
+            self.scene_context.warn_once(
+                &key,
+                "[skybox] faces must all be square and the same size — \
+skybox disabled for this set",
+            );
+            return None;
+        }
+        Some(texels)
+    }
+
+    /// The converted texels of a pipeline texture, or `None` (an unbound
+    /// unit) once a GL renderer has consumed its CPU copy.
+    fn texture_texels(&self, texture: &Texture2D) -> Option<(Rc<Texels>, bool, bool)> {
+        let (data, options) = match texture.cpu_data() {
+            Some(cpu) => cpu,
+            None => {
+                self.scene_context.warn_once(
+                    "software-uploaded-texture",
+                    "[software-renderer] a texture was already uploaded to GL and has no \
+CPU copy — sampling it as unbound",
+                );
+                return None;
+            }
+        };
+        let key = texture as *const Texture2D as usize;
+        let cached = self.textures.borrow().get(&key).cloned();
+        let texels = match cached {
+            Some(texels) => texels,
+            None => {
+                let texels = Rc::new(Texels::from_data(&data)?);
+                self.textures.borrow_mut().insert(key, texels.clone());
+                texels
+            }
+        };
+        Some((texels, options.wrap, options.linear))
+    }
+
+    fn font_atlas(&self) -> Option<Rc<Texels>> {
+        let mut atlas = self.font_atlas.borrow_mut();
+        if atlas.is_none() {
+            *atlas = Texels::from_data(&crate::sprite_font::atlas_texture_data()).map(Rc::new);
+        }
+        atlas.clone()
+    }
+}
+
+/// `texture(source, uv).rgb` of a post image: linear and clamped, like the
+/// render targets the GL stack samples.
+fn sample_rgb(texels: &Texels, [u, v]: [f32; 2]) -> [f32; 3] {
+    let color = texels.sample(u, v, false, true);
+    [color.x, color.y, color.z]
+}
+
+/// One post stage's output: `shade` evaluated at each texel center (row 0 at
+/// the bottom, `v` up), stored as RGBA8.
+fn post_image(width: usize, height: usize, shade: impl Fn([f32; 2]) -> [f32; 3]) -> Texels {
+    let mut rgba = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let uv = [
+                (x as f32 + 0.5) / width as f32,
+                (y as f32 + 0.5) / height as f32,
+            ];
+            let [r, g, b] = shade(uv);
+            rgba.push(to_unorm8(Vector4::new(r, g, b, 1.0)));
+        }
+    }
+    Texels {
+        width,
+        height,
+        rgba,
+    }
+}
+
+/// A render target's texture size; GL allocates at least one texel.
+fn target_size(width: u32, height: u32) -> (usize, usize) {
+    (width.max(1) as usize, height.max(1) as usize)
+}
+
+/// `texture(samplerCube, dir)` with linear filtering and clamped face edges.
+fn sample_cube(faces: &[Texels], dir: Vector3<f32>) -> Vector4<f32> {
+    let (ax, ay, az) = (dir.x.abs(), dir.y.abs(), dir.z.abs());
+    // The GL cube-map face selection table: major axis, then (sc, tc).
+    let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+        if dir.x >= 0.0 {
+            (0, -dir.z, -dir.y, ax)
+        } else {
+            (1, dir.z, -dir.y, ax)
+        }
+    } else if ay >= az {
+        if dir.y >= 0.0 {
+            (2, dir.x, dir.z, ay)
+        } else {
+            (3, dir.x, -dir.z, ay)
+        }
+    } else if dir.z >= 0.0 {
+        (4, dir.x, -dir.y, az)
+    } else {
+        (5, -dir.x, -dir.y, az)
+    };
+    if ma == 0.0 || !ma.is_finite() {
+        return Vector4::new(0.0, 0.0, 0.0, 1.0);
+    }
+    let s = 0.5 * (sc / ma + 1.0);
+    let t = 0.5 * (tc / ma + 1.0);
+    faces[face].sample(s, t, false, true)
+}
+
+/// A vertex after the vertex stage: clip position plus varyings.
+#[derive(Clone, Copy)]
+struct ClipVertex {
+    clip: Vector4<f32>,
+    varyings: [f32; VARYINGS],
+}
+
+impl ClipVertex {
+    fn lerp(&self, other: &ClipVertex, t: f32) -> ClipVertex {
+        let mut varyings = [0.0; VARYINGS];
+        for (i, v) in varyings.iter_mut().enumerate() {
+            *v = self.varyings[i] + (other.varyings[i] - self.varyings[i]) * t;
+        }
+        ClipVertex {
+            clip: self.clip + (other.clip - self.clip) * t,
+            varyings,
+        }
+    }
+}
+
+/// The shading of one draw with its texture units captured at draw time.
+struct Program {
+    shading: Shading,
+    albedo: Unit,
+    normal_map: Unit,
+    metallic_roughness: Unit,
+    occlusion: Unit,
+    emissive: Unit,
+    cull: Cull,
+    /// The image-based light chosen for the draw's origin.
+    environment: Option<Arc<PrefilteredEnvironment>>,
+}
+
+/// One pass's walk over a scene — the software `RenderContext` plus the GL
+/// state `Scene3D::render` toggles along the way.
+struct Pass<'r, 'a> {
+    renderer: &'r Renderer<'a>,
+    canvas: &'r mut Canvas,
+    viewport: Viewport,
+    view: Matrix4<f32>,
+    projection: Matrix4<f32>,
+    lights: LightUniforms,
+    shadow: Option<&'r ShadowMap>,
+    environment: Option<&'r ImageLighting>,
+    fog: Option<&'r Fog>,
+    camera_pos: Vector3<f32>,
+    depth_pass: bool,
+    debug_render_mode: DebugRenderMode,
+    pass_blends: bool,
+    blend_active: bool,
+    opacity_stage: OpacityStage,
+    opacity: f32,
+    state: RasterState,
+}
+
+impl Pass<'_, '_> {
+    /// `Scene3D::render`, node for node.
+    fn render(&mut self, node: &Scene3D, world: &Matrix4<f32>, material: Shading) {
+        let geometry_material = if self.depth_pass {
+            Shading::Depth
+        } else {
+            match self.debug_render_mode {
+                DebugRenderMode::Normals => Shading::Normals,
+                DebugRenderMode::Tangents => Shading::Tangents,
+                DebugRenderMode::Default
+                | DebugRenderMode::Transparent
+                | DebugRenderMode::Physics => material,
+            }
+        };
+
+        match &node.obj {
+            SceneObject::Model(description) => {
+                self.render_model(description, &(world * node.xform))
+            }
+            SceneObject::Terrain(_) => {
+                if !self.depth_pass {
+                    self.renderer.scene_context.warn_once(
+                        "software-terrain",
+                        "[software-renderer] terrain is drawn by the GPU LOD path only — \
+the software renderer skips it",
+                    );
+                }
+            }
+            // A Material node ignores its own transform, as in the GL walk.
+            SceneObject::Material(description, items) => {
+                let material = self.resolve_material(description);
+                let blend = !self.depth_pass
+                    && !self.pass_blends
+                    && !self.blend_active
+                    && description.blends();
+                if blend {
+                    self.blend_active = true;
+                    self.state.blend = Blend::Alpha;
+                }
+                for item in items {
+                    self.render(item, world, material);
+                }
+                if blend {
+                    self.blend_active = false;
+                    self.state.blend = Blend::Off;
+                }
+            }
+            SceneObject::Group(items) => {
+                let world = world * node.xform;
+                for item in items {
+                    self.render(item, &world, material);
+                }
+            }
+            SceneObject::Opacity(alpha, items) => {
+                let stage = if self.depth_pass {
+                    OpacityStage::Defer
+                } else {
+                    self.opacity_stage
+                };
+                if stage == OpacityStage::Defer {
+                    return;
+                }
+                let world = world * node.xform;
+                let previous = self.opacity;
+                let blend = stage == OpacityStage::Draw;
+                if blend {
+                    let accumulated = previous * alpha;
+                    if accumulated == 0.0 {
+                        return;
+                    }
+                    self.opacity = accumulated;
+                    self.state.blend = Blend::Constant(accumulated);
+                }
+                for item in items {
+                    self.render(item, &world, material);
+                }
+                if blend {
+                    self.opacity = previous;
+                    self.state.blend = Blend::Constant(previous);
+                }
+            }
+            // The hardware-instanced paths are semantically the stamped
+            // group, which is what the software renderer draws.
+            SceneObject::Instanced {
+                template,
+                instances,
+            } => {
+                if instances.is_empty() {
+                    return;
+                }
+                let world = world * node.xform;
+                self.render(&expand_instanced(template, instances), &world, material);
+            }
+            SceneObject::Geometry(shape) => {
+                let acc = world * node.xform;
+                match shape {
+                    Shape::Billboard if self.depth_pass => {}
+                    Shape::Billboard => {
+                        let xform = billboard_xform(&acc, &self.view);
+                        let (vertices, indices) = primitive_mesh(shape).expect("quad mesh");
+                        self.draw_mesh(vertices, indices, &xform, &[], geometry_material);
+                    }
+                    Shape::Heightmap {
+                        rows,
+                        cols,
+                        heights,
+                    } => {
+                        let (vertices, indices) =
+                            geometry::heightmap_mesh_data(*rows as usize, *cols as usize, heights);
+                        self.draw_mesh(&vertices, &indices, &acc, &[], geometry_material);
+                    }
+                    Shape::ConvexPolygon { points } => {
+                        if points.len() < 3 {
+                            return;
+                        }
+                        let (vertices, indices) = geometry::polygon_mesh_data(points);
+                        self.draw_mesh(&vertices, &indices, &acc, &[], geometry_material);
+                    }
+                    Shape::Cube | Shape::Sphere | Shape::Cylinder | Shape::Quad | Shape::Plane => {
+                        let (vertices, indices) = primitive_mesh(shape).expect("primitive mesh");
+                        self.draw_mesh(vertices, indices, &acc, &[], geometry_material);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The Model arm of `Scene3D::render`: skinned meshes take only the scene
+    /// transform and the evaluated pose; static meshes keep their node
+    /// transform; a per-mesh material override draws unskinned.
+    fn render_model(&mut self, description: &ModelDescription, matrix: &Matrix4<f32>) {
+        let ModelHandle::File(file) = &description.handle;
+        let renderer = self.renderer;
+        let model = resolve_model_for_draw(
+            &renderer.asset_cache,
+            renderer.scene_context,
+            file,
+            &description.while_pending,
+        );
+        let is_skinned = model.skeleton.get_joint_count() > 0;
+        let debug_override = !matches!(
+            self.debug_render_mode,
+            DebugRenderMode::Default | DebugRenderMode::Transparent | DebugRenderMode::Physics
+        );
+        let shaded = self.lights.count > 0 || self.environment.is_some();
+        // `None` is each mesh's own `PbrMaterial`.
+        let model_material = if self.depth_pass {
+            Some(Shading::Depth)
+        } else {
+            match self.debug_render_mode {
+                DebugRenderMode::Normals => Some(Shading::Normals),
+                DebugRenderMode::Tangents => Some(Shading::Tangents),
+                _ if is_skinned || shaded => None,
+                _ => Some(Shading::Basic),
+            }
+        };
+        let joints = if is_skinned {
+            model_pose_joints(
+                renderer.frame_time.tts,
+                renderer.scene_context,
+                &model,
+                &description.animation,
+                file,
+            )
+        } else {
+            vec![]
+        };
+
+        let (blended, opaque): (Vec<&ModelMesh>, Vec<&ModelMesh>) = model
+            .meshes
+            .iter()
+            .partition(|mesh| model_material.is_none() && mesh.material.factors.blends());
+        for mesh in opaque.into_iter().chain(blended) {
+            let mut matrix = if is_skinned {
+                *matrix
+            } else {
+                matrix * mesh.transform
+            };
+            let mut override_material: Option<&MaterialDescription> = None;
+            for (_selector, override_) in &description.overrides {
+                match override_ {
+                    MeshOverride::Material(material) => override_material = Some(material),
+                    MeshOverride::Transform(xform) => matrix = matrix * xform,
+                }
+            }
+            if debug_override || self.depth_pass {
+                override_material = None;
+            }
+            let Some((vertices, indices)) = mesh.mesh.cpu_data() else {
+                renderer.scene_context.warn_once(
+                    "software-uploaded-mesh",
+                    "[software-renderer] a model mesh was already uploaded to GL and has no \
+CPU copy — skipping it",
+                );
+                continue;
+            };
+            match override_material {
+                Some(material) => {
+                    let shading = self.resolve_material(material);
+                    self.draw_mesh(&vertices, &indices, &matrix, &[], shading);
+                }
+                None => {
+                    renderer.units.borrow_mut()[0] =
+                        Unit(renderer.texture_texels(&mesh.base_color_texture));
+                    match model_material {
+                        Some(shading) => {
+                            self.draw_mesh(&vertices, &indices, &matrix, &joints, shading)
+                        }
+                        None => {
+                            self.draw_pbr_model_mesh(mesh, &vertices, &indices, &matrix, &joints)
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// `draw_pbr_model_mesh`: the primitive's textures on their units, back
+    /// faces culled unless double-sided, blend mode blended without depth
+    /// writes unless the pass owns the blend state.
+    fn draw_pbr_model_mesh(
+        &mut self,
+        mesh: &ModelMesh,
+        vertices: &[VertexPositionTextureSkinned],
+        indices: &[u32],
+        matrix: &Matrix4<f32>,
+        joints: &[Matrix4<f32>],
+    ) {
+        let renderer = self.renderer;
+        let material = &mesh.material;
+        let bind = |texture: &Option<Texture2D>, unit: u32| match texture {
+            Some(texture) => {
+                renderer.units.borrow_mut()[unit as usize] = Unit(renderer.texture_texels(texture));
+                true
+            }
+            None => false,
+        };
+        let textures = PbrTextures {
+            base_color: true,
+            normal: bind(&material.normal_texture, 2),
+            metallic_roughness: bind(
+                &material.metallic_roughness_texture,
+                METALLIC_ROUGHNESS_TEXTURE_UNIT,
+            ),
+            occlusion: bind(&material.occlusion_texture, OCCLUSION_TEXTURE_UNIT),
+            emissive: bind(&material.emissive_texture, EMISSIVE_TEXTURE_UNIT),
+        };
+        let shading = Shading::Pbr {
+            factors: material.factors,
+            textures,
+        };
+        let blend = material.factors.blends() && !self.pass_blends && !self.blend_active;
+        let previous = self.state;
+        if blend {
+            self.state.blend = Blend::Alpha;
+            self.state.depth_write = false;
+        }
+        let cull = match (material.factors.double_sided, matrix.determinant() < 0.0) {
+            (true, _) => Cull::Off,
+            (false, false) => Cull::Clockwise,
+            (false, true) => Cull::CounterClockwise,
+        };
+        self.draw_culled_mesh(vertices, indices, matrix, joints, shading, cull);
+        self.state = previous;
+    }
+
+    /// `MaterialDescription::get`: bind the material's textures to their
+    /// units and pick its shading.
+    fn resolve_material(&mut self, description: &MaterialDescription) -> Shading {
+        match description {
+            MaterialDescription::Color(color) => Shading::Color(*color),
+            MaterialDescription::Texture(texture) => {
+                self.bind(texture, 0, SpriteSampling::Linear);
+                Shading::Basic
+            }
+            MaterialDescription::Emissive { color, texture } => {
+                if let Some(texture) = texture {
+                    self.bind(texture, 0, SpriteSampling::Linear);
+                }
+                Shading::Emissive {
+                    color: *color,
+                    use_texture: texture.is_some(),
+                    source_pixels: None,
+                }
+            }
+            MaterialDescription::Lit {
+                color,
+                texture,
+                normal_map,
+            } => {
+                if let Some(texture) = texture {
+                    self.bind(texture, 0, SpriteSampling::Linear);
+                }
+                if let Some(normal_map) = normal_map {
+                    self.bind(normal_map, 2, SpriteSampling::Linear);
+                }
+                Shading::Lit {
+                    color: *color,
+                    use_texture: texture.is_some(),
+                    use_normal_map: normal_map.is_some(),
+                }
+            }
+            MaterialDescription::SpriteTexture {
+                color,
+                texture,
+                source_pixels,
+                sampling,
+            } => {
+                self.bind(texture, 0, *sampling);
+                Shading::Emissive {
+                    color: *color,
+                    use_texture: true,
+                    source_pixels: *source_pixels,
+                }
+            }
+            MaterialDescription::Pbr(pbr) => {
+                let mut bind = |texture: &Option<TextureDescription>, unit: u32| match texture {
+                    Some(texture) => {
+                        self.bind(texture, unit as usize, SpriteSampling::Linear);
+                        true
+                    }
+                    None => false,
+                };
+                let textures = PbrTextures {
+                    base_color: bind(&pbr.base_color_texture, 0),
+                    normal: bind(&pbr.normal_texture, 2),
+                    metallic_roughness: bind(
+                        &pbr.metallic_roughness_texture,
+                        METALLIC_ROUGHNESS_TEXTURE_UNIT,
+                    ),
+                    occlusion: bind(&pbr.occlusion_texture, OCCLUSION_TEXTURE_UNIT),
+                    emissive: bind(&pbr.emissive_texture, EMISSIVE_TEXTURE_UNIT),
+                };
+                Shading::Pbr {
+                    factors: pbr.factors,
+                    textures,
+                }
+            }
+        }
+    }
+
+    /// `bind_texture_description`: file textures repeat (clamped variants and
+    /// the built-in atlas clamp) with the material's filter; a render target
+    /// keeps its own linear/clamp state, and an undeclared id binds magenta.
+    fn bind(&mut self, texture: &TextureDescription, unit: usize, sampling: SpriteSampling) {
+        let renderer = self.renderer;
+        let linear = sampling == SpriteSampling::Linear;
+        let file = |file: &str, while_pending: &[String]| {
+            let pipeline = &renderer.scene_context.texture_pipeline;
+            let asset = renderer
+                .asset_cache
+                .load_asset_with_pipeline(pipeline.clone(), file);
+            let texture = if while_pending.is_empty() {
+                asset.get()
+            } else {
+                crate::asset::resolve_while_pending(
+                    &renderer.asset_cache,
+                    pipeline,
+                    &asset,
+                    while_pending,
+                )
+            };
+            renderer.texture_texels(&texture).map(|(texels, ..)| texels)
+        };
+        let bound = match texture {
+            TextureDescription::File(path) => file(path, &[]).map(|t| (t, true, linear)),
+            TextureDescription::FileClamped(path) => file(path, &[]).map(|t| (t, false, linear)),
+            TextureDescription::FileWhilePending {
+                file: path,
+                while_pending,
+            } => file(path, while_pending).map(|t| (t, true, linear)),
+            TextureDescription::FileClampedWhilePending {
+                file: path,
+                while_pending,
+            } => file(path, while_pending).map(|t| (t, false, linear)),
+            TextureDescription::Builtin(BuiltinTexture::FontAtlas) => {
+                renderer.font_atlas().map(|t| (t, false, linear))
+            }
+            TextureDescription::RenderTarget(id) => {
+                let target = renderer.targets.borrow().get(id).cloned();
+                Some(match target {
+                    Some(texels) => (texels, false, true),
+                    None => {
+                        renderer.scene_context.warn_once(
+                            id,
+                            &format!(
+                                "[render-target] a material samples \"{id}\" but no \
+Frame.withRenderTarget declares it — binding the magenta fallback"
+                            ),
+                        );
+                        (
+                            Rc::new(Texels::solid(1, 1, [255, 0, 255, 255])),
+                            false,
+                            true,
+                        )
+                    }
+                })
+            }
+        };
+        renderer.units.borrow_mut()[unit] = Unit(bound);
+    }
+
+    /// The vertex stage of every material (skinning when `joints` is
+    /// non-empty), then clipping and rasterization of each triangle.
+    fn draw_mesh<V: SoftwareVertex>(
+        &mut self,
+        vertices: &[V],
+        indices: &[u32],
+        world: &Matrix4<f32>,
+        joints: &[Matrix4<f32>],
+        shading: Shading,
+    ) {
+        self.draw_culled_mesh(vertices, indices, world, joints, shading, Cull::Off);
+    }
+
+    /// [`Pass::draw_mesh`] with `GL_CULL_FACE` set as `cull` says.
+    fn draw_culled_mesh<V: SoftwareVertex>(
+        &mut self,
+        vertices: &[V],
+        indices: &[u32],
+        world: &Matrix4<f32>,
+        joints: &[Matrix4<f32>],
+        shading: Shading,
+        cull: Cull,
+    ) {
+        let view_projection = self.projection * self.view;
+        let normal_matrix = normal_matrix(world);
+        let world3 = mat3(world);
+        let transformed: Vec<ClipVertex> = vertices
+            .iter()
+            .map(|vertex| {
+                let position = vertex.position().extend(1.0);
+                let tangent = vertex.tangent();
+                let skin = (!joints.is_empty())
+                    .then(|| vertex.skin())
+                    .flatten()
+                    .map(|(indices, weights)| skin_matrix(joints, indices, weights));
+                let (position, normal, tangent3) = match skin {
+                    Some(skin) => (
+                        skin * position,
+                        mat3(&skin) * vertex.normal(),
+                        mat3(&skin) * tangent.truncate(),
+                    ),
+                    None => (position, vertex.normal(), tangent.truncate()),
+                };
+                let world_position = world * position;
+                let n = normal_matrix * normal;
+                let t = world3 * tangent3;
+                let b = n.cross(t) * tangent.w;
+                let uv = vertex.uv();
+                ClipVertex {
+                    clip: view_projection * world_position,
+                    varyings: [
+                        world_position.x,
+                        world_position.y,
+                        world_position.z,
+                        uv[0],
+                        uv[1],
+                        n.x,
+                        n.y,
+                        n.z,
+                        t.x,
+                        t.y,
+                        t.z,
+                        b.x,
+                        b.y,
+                        b.z,
+                    ],
+                }
+            })
+            .collect();
+
+        let units = self.renderer.units.borrow();
+        let program = Program {
+            shading,
+            albedo: units[0].clone(),
+            normal_map: units[2].clone(),
+            metallic_roughness: units[METALLIC_ROUGHNESS_TEXTURE_UNIT as usize].clone(),
+            occlusion: units[OCCLUSION_TEXTURE_UNIT as usize].clone(),
+            emissive: units[EMISSIVE_TEXTURE_UNIT as usize].clone(),
+            cull,
+            environment: self
+                .environment
+                .and_then(|lighting| lighting.at(world.w.truncate()))
+                .cloned(),
+        };
+        drop(units);
+
+        for triangle in indices.chunks_exact(3) {
+            let corner = |i: u32| transformed.get(i as usize).copied();
+            let (Some(a), Some(b), Some(c)) = (
+                corner(triangle[0]),
+                corner(triangle[1]),
+                corner(triangle[2]),
+            ) else {
+                continue;
+            };
+            let polygon = clip_polygon(vec![a, b, c]);
+            for i in 1..polygon.len().saturating_sub(1) {
+                self.rasterize([&polygon[0], &polygon[i], &polygon[i + 1]], &program);
+            }
+        }
+    }
+
+    /// Scan-convert one clipped triangle: edge functions over the pixel
+    /// centers in its bounds (a top-left tie rule so shared edges cover each
+    /// pixel once), screen-linear depth, perspective-correct varyings.
+    fn rasterize(&mut self, triangle: [&ClipVertex; 3], program: &Program) {
+        let vp = self.viewport;
+        let screen = triangle.map(|v| {
+            let inv_w = 1.0 / v.clip.w;
+            [
+                vp.x as f32 + (v.clip.x * inv_w * 0.5 + 0.5) * vp.width as f32,
+                vp.y as f32 + (v.clip.y * inv_w * 0.5 + 0.5) * vp.height as f32,
+                v.clip.z * inv_w * 0.5 + 0.5,
+                inv_w,
+            ]
+        });
+        let edge = |a: [f32; 4], b: [f32; 4], px: f32, py: f32| {
+            (b[0] - a[0]) * (py - a[1]) - (b[1] - a[1]) * (px - a[0])
+        };
+        let mut order = [0, 1, 2];
+        let mut area = edge(screen[0], screen[1], screen[2][0], screen[2][1]);
+        if !area.is_finite() || area == 0.0 {
+            return;
+        }
+        // Screen y points up, as in GL window coordinates, so a positive area
+        // is counter-clockwise.
+        let culled = match program.cull {
+            Cull::Off => false,
+            Cull::Clockwise => area < 0.0,
+            Cull::CounterClockwise => area > 0.0,
+        };
+        if culled {
+            return;
+        }
+        // The triangle's world-space plane normal: the software `dFdx`/`dFdy`
+        // cross product the double-sided PBR shading orients by.
+        let corner = |v: &ClipVertex| vec3(v.varyings[0], v.varyings[1], v.varyings[2]);
+        let face = (corner(triangle[1]) - corner(triangle[0]))
+            .cross(corner(triangle[2]) - corner(triangle[0]));
+        if area < 0.0 {
+            order.swap(1, 2);
+            area = -area;
+        }
+        let [p0, p1, p2] = order.map(|i| screen[i]);
+        let [v0, v1, v2] = order.map(|i| triangle[i]);
+        let owns_edge = |a: [f32; 4], b: [f32; 4]| {
+            let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+            dy < 0.0 || (dy == 0.0 && dx < 0.0)
+        };
+        let owns = [owns_edge(p1, p2), owns_edge(p2, p0), owns_edge(p0, p1)];
+
+        let min_x = p0[0].min(p1[0]).min(p2[0]).floor().max(vp.x as f32) as usize;
+        let min_y = p0[1].min(p1[1]).min(p2[1]).floor().max(vp.y as f32) as usize;
+        let max_x = p0[0].max(p1[0]).max(p2[0]).ceil() as usize;
+        let max_y = p0[1].max(p1[1]).max(p2[1]).ceil() as usize;
+        let max_x = max_x.min((vp.x + vp.width) as usize).min(self.canvas.width);
+        let max_y = max_y
+            .min((vp.y + vp.height) as usize)
+            .min(self.canvas.height);
+
+        for y in min_y..max_y {
+            let py = y as f32 + 0.5;
+            for x in min_x..max_x {
+                let px = x as f32 + 0.5;
+                let w = [
+                    edge(p1, p2, px, py),
+                    edge(p2, p0, px, py),
+                    edge(p0, p1, px, py),
+                ];
+                let inside = (0..3).all(|i| w[i] > 0.0 || (w[i] == 0.0 && owns[i]));
+                if !inside {
+                    continue;
+                }
+                let l = [w[0] / area, w[1] / area, w[2] / area];
+                let depth = l[0] * p0[2] + l[1] * p1[2] + l[2] * p2[2];
+                let index = y * self.canvas.width + x;
+                let passes = match self.state.depth_test {
+                    DepthTest::Less => depth < self.canvas.depth[index],
+                    DepthTest::Equal => depth == self.canvas.depth[index],
+                    DepthTest::Off => true,
+                };
+                if !passes {
+                    continue;
+                }
+                let perspective = [l[0] * p0[3], l[1] * p1[3], l[2] * p2[3]];
+                let total = perspective[0] + perspective[1] + perspective[2];
+                let mut varyings = [0.0; VARYINGS];
+                for (i, v) in varyings.iter_mut().enumerate() {
+                    *v = (perspective[0] * v0.varyings[i]
+                        + perspective[1] * v1.varyings[i]
+                        + perspective[2] * v2.varyings[i])
+                        / total;
+                }
+                // `discard` skips the depth write too.
+                if discards(program, &varyings) {
+                    continue;
+                }
+                if self.state.depth_write && self.state.depth_test != DepthTest::Off {
+                    self.canvas.depth[index] = depth;
+                }
+                if !self.state.color_write {
+                    continue;
+                }
+                let source = self.shade(program, &varyings, face);
+                let destination = self.canvas.color[index];
+                self.canvas.color[index] = blend(self.state.blend, source, destination);
+            }
+        }
+    }
+
+    /// The fragment shaders. `face` is the triangle's world-space plane
+    /// normal, either orientation.
+    fn shade(
+        &self,
+        program: &Program,
+        varyings: &[f32; VARYINGS],
+        face: Vector3<f32>,
+    ) -> Vector4<f32> {
+        let world_pos = vec3(varyings[0], varyings[1], varyings[2]);
+        let (u, v) = (varyings[3], varyings[4]);
+        let normal = vec3(varyings[5], varyings[6], varyings[7]);
+        let tangent = vec3(varyings[8], varyings[9], varyings[10]);
+        let bitangent = vec3(varyings[11], varyings[12], varyings[13]);
+        let fogged = |c: Vector4<f32>| self.apply_fog(c.truncate(), world_pos).extend(c.w);
+        match program.shading {
+            Shading::Color(color) => fogged(color),
+            Shading::Basic => fogged(program.albedo.sample(u, v)),
+            Shading::Emissive {
+                color,
+                use_texture,
+                source_pixels,
+            } => {
+                if !use_texture {
+                    return fogged(color);
+                }
+                let (u, v) = match source_pixels {
+                    Some([x, y, w, h]) => {
+                        let (tw, th) = program.albedo.size();
+                        let px = (x + u * w).max(x + 0.5).min(x + w - 0.5);
+                        let py = (y + v * h).max(y + 0.5).min(y + h - 0.5);
+                        (px / tw, py / th)
+                    }
+                    None => (u, v),
+                };
+                let c = program.albedo.sample(u, v);
+                fogged(Vector4::new(
+                    c.x * color.x,
+                    c.y * color.y,
+                    c.z * color.z,
+                    c.w * color.w,
+                ))
+            }
+            Shading::Lit {
+                color,
+                use_texture,
+                use_normal_map,
+            } => {
+                let mut n = normal.normalize();
+                if use_normal_map {
+                    let m = program.normal_map.sample(u, v).truncate() * 2.0 - vec3(1.0, 1.0, 1.0);
+                    n = (tangent.normalize() * m.x + bitangent.normalize() * m.y + n * m.z)
+                        .normalize();
+                }
+                let (diffuse, specular) =
+                    self.accumulate_lights(n, world_pos, program.environment.as_deref());
+                let albedo = if use_texture {
+                    let c = program.albedo.sample(u, v);
+                    Vector4::new(c.x * color.x, c.y * color.y, c.z * color.z, c.w * color.w)
+                } else {
+                    color
+                };
+                let rgb = vec3(
+                    albedo.x * diffuse.x + specular.x,
+                    albedo.y * diffuse.y + specular.y,
+                    albedo.z * diffuse.z + specular.z,
+                );
+                self.apply_fog(rgb, world_pos).extend(albedo.w)
+            }
+            Shading::Pbr { factors, textures } => {
+                let base_color = pbr_base_color(program, &factors, &textures, u, v);
+                let mut n = normal.normalize();
+                let (mut t, mut b) = (tangent, bitangent);
+                if factors.double_sided {
+                    let face = if face.dot(self.camera_pos - world_pos) < 0.0 {
+                        -face
+                    } else {
+                        face
+                    };
+                    if n.dot(face) < 0.0 {
+                        (n, t, b) = (-n, -t, -b);
+                    }
+                }
+                if textures.normal {
+                    let m = program.normal_map.sample(u, v).truncate() * 2.0 - vec3(1.0, 1.0, 1.0);
+                    let (x, y) = (m.x * factors.normal_scale, m.y * factors.normal_scale);
+                    n = (t.normalize() * x + b.normalize() * y + n * m.z).normalize();
+                }
+                let (mut metallic, mut roughness) = (factors.metallic, factors.roughness);
+                if textures.metallic_roughness {
+                    let packed = program.metallic_roughness.sample(u, v);
+                    roughness *= packed.y;
+                    metallic *= packed.z;
+                }
+                let occlusion = if textures.occlusion {
+                    1.0 + factors.occlusion_strength * (program.occlusion.sample(u, v).x - 1.0)
+                } else {
+                    1.0
+                };
+                let [r, g, b] = factors.emissive;
+                let mut emissive = vec3(r, g, b);
+                if textures.emissive {
+                    let texel = program.emissive.sample(u, v);
+                    emissive = vec3(
+                        emissive.x * texel.x,
+                        emissive.y * texel.y,
+                        emissive.z * texel.z,
+                    );
+                }
+                let color = self.shade_pbr(
+                    n,
+                    world_pos,
+                    base_color.truncate(),
+                    metallic,
+                    roughness,
+                    occlusion,
+                    program.environment.as_deref(),
+                );
+                let alpha = match factors.alpha_mode {
+                    AlphaMode::Blend => base_color.w,
+                    AlphaMode::Opaque | AlphaMode::Mask(_) => 1.0,
+                };
+                self.apply_fog(color + emissive, world_pos).extend(alpha)
+            }
+            Shading::Normals => (normal.normalize() * 0.5 + vec3(0.5, 0.5, 0.5)).extend(1.0),
+            Shading::Tangents => (tangent.normalize() * 0.5 + vec3(0.5, 0.5, 0.5)).extend(1.0),
+            Shading::Depth => Vector4::new(0.0, 0.0, 0.0, 1.0),
+        }
+    }
+
+    /// `FOG_GLSL`'s `applyFog`.
+    fn apply_fog(&self, color: Vector3<f32>, world_pos: Vector3<f32>) -> Vector3<f32> {
+        let Some(fog) = self.fog else {
+            return color;
+        };
+        let dist = (world_pos - self.camera_pos).magnitude();
+        let factor = match fog {
+            Fog::Linear { near, far, .. } => clamp01((far - dist) / (far - near).max(1e-4)),
+            Fog::Exp { density, .. } => (-density * dist).exp(),
+        };
+        let [r, g, b] = fog.color();
+        vec3(r, g, b) + (color - vec3(r, g, b)) * factor
+    }
+
+    /// `lightToward`: the unit vector toward non-ambient light `i`, and its
+    /// attenuation at `world_pos`.
+    fn light_toward(&self, i: usize, world_pos: Vector3<f32>) -> (Vector3<f32>, f32) {
+        let lights = &self.lights;
+        let at = |array: &[f32], i: usize| vec3(array[i * 3], array[i * 3 + 1], array[i * 3 + 2]);
+        let kind = lights.types[i];
+        if kind == 1 {
+            return (-at(&lights.directions, i).normalize(), 1.0);
+        }
+        let to_light = at(&lights.positions, i) - world_pos;
+        let dist = to_light.magnitude();
+        let l = to_light / dist.max(1e-4);
+        let range = lights.ranges[i].max(1e-4);
+        let a = clamp01(1.0 - (dist * dist) / (range * range));
+        let mut atten = a * a;
+        if kind == 3 {
+            let cos_angle = (-l).dot(at(&lights.directions, i).normalize());
+            let outer = lights.cone_cos[i];
+            let inner = 1.0 + (outer - 1.0) * 0.85;
+            atten *= clamp01((cos_angle - outer) / (inner - outer).max(1e-4));
+        }
+        (l, atten)
+    }
+
+    /// `shadePbr` from the shared lighting GLSL: the metallic-roughness BRDF's
+    /// outgoing light, before emissive and fog.
+    #[allow(clippy::too_many_arguments)]
+    fn shade_pbr(
+        &self,
+        n: Vector3<f32>,
+        world_pos: Vector3<f32>,
+        albedo: Vector3<f32>,
+        metallic: f32,
+        roughness: f32,
+        occlusion: f32,
+        environment: Option<&PrefilteredEnvironment>,
+    ) -> Vector3<f32> {
+        let lights = &self.lights;
+        let view_dir = (self.camera_pos - world_pos).normalize();
+        let ndotv = n.dot(view_dir).max(1e-4);
+        let roughness = roughness.clamp(0.04, 1.0);
+        let metallic = clamp01(metallic);
+        let f0 = vec3(0.04, 0.04, 0.04) * (1.0 - metallic) + albedo * metallic;
+        let diffuse_color = albedo * (1.0 - metallic);
+        let alpha = roughness * roughness;
+        let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+        let (scale, bias) = environment_brdf(ndotv, roughness);
+        let indirect_specular = f0 * scale + vec3(bias, bias, bias);
+        let mul = |a: Vector3<f32>, b: Vector3<f32>| vec3(a.x * b.x, a.y * b.y, a.z * b.z);
+
+        let mut color = vec3(0.0, 0.0, 0.0);
+        for i in 0..lights.count as usize {
+            let light_color = vec3(
+                lights.colors[i * 3],
+                lights.colors[i * 3 + 1],
+                lights.colors[i * 3 + 2],
+            );
+            if lights.types[i] == 0 {
+                color += mul(diffuse_color + indirect_specular, light_color) * occlusion;
+                continue;
+            }
+            let (l, atten) = self.light_toward(i, world_pos);
+            let ndotl = n.dot(l).max(0.0);
+            if ndotl <= 0.0 {
+                continue;
+            }
+            let h = (l + view_dir).normalize();
+            let ndoth = n.dot(h).max(0.0);
+            let vdoth = view_dir.dot(h).max(0.0);
+            let d = ndoth * ndoth * (alpha * alpha - 1.0) + 1.0;
+            let distribution = alpha * alpha / (std::f32::consts::PI * d * d);
+            let visibility = 1.0 / (4.0 * (ndotl * (1.0 - k) + k) * (ndotv * (1.0 - k) + k));
+            let fresnel = f0 + (vec3(1.0, 1.0, 1.0) - f0) * (1.0 - vdoth).powf(5.0);
+            let brdf = mul(vec3(1.0, 1.0, 1.0) - fresnel, diffuse_color)
+                + fresnel * (std::f32::consts::PI * distribution * visibility);
+            let mut radiance = mul(brdf, light_color) * (ndotl * atten);
+            if let Some(shadow) = self.shadow.filter(|shadow| shadow.light_index == i) {
+                radiance *= 1.0 - sample_shadow(shadow, world_pos, ndotl);
+            }
+            color += radiance;
+        }
+        if let Some(environment) = environment {
+            let intensity = self.environment.map_or(1.0, |lighting| lighting.intensity);
+            let r = -view_dir - n * 2.0 * n.dot(-view_dir);
+            let reflection = environment.specular_at(r, roughness * environment.max_lod());
+            color += (mul(environment.irradiance_at(n), diffuse_color)
+                + mul(reflection, indirect_specular))
+                * (intensity * occlusion);
+        }
+        color
+    }
+
+    /// `accumulateLights` from the shared lighting GLSL: (diffuse, specular).
+    fn accumulate_lights(
+        &self,
+        n: Vector3<f32>,
+        world_pos: Vector3<f32>,
+        environment: Option<&PrefilteredEnvironment>,
+    ) -> (Vector3<f32>, Vector3<f32>) {
+        let lights = &self.lights;
+        let view_dir = (self.camera_pos - world_pos).normalize();
+        let mut diffuse_light = vec3(0.0, 0.0, 0.0);
+        let mut specular_light = vec3(0.0, 0.0, 0.0);
+        let at = |array: &[f32], i: usize| vec3(array[i * 3], array[i * 3 + 1], array[i * 3 + 2]);
+        for i in 0..lights.count as usize {
+            let color = at(&lights.colors, i);
+            // 0 = ambient, 1 = directional, 2 = point, 3 = spot.
+            let kind = lights.types[i];
+            if kind == 0 {
+                diffuse_light += color;
+                continue;
+            }
+            let (l, atten) = self.light_toward(i, world_pos);
+            let ndotl = n.dot(l).max(0.0);
+            let mut diffuse = color * ndotl * atten;
+            let spec = if ndotl > 0.0 {
+                n.dot((l + view_dir).normalize()).max(0.0).powf(32.0)
+            } else {
+                0.0
+            };
+            let mut specular = color * spec * 0.4 * atten;
+            if let Some(shadow) = self.shadow.filter(|shadow| shadow.light_index == i) {
+                let lit = 1.0 - sample_shadow(shadow, world_pos, ndotl);
+                diffuse *= lit;
+                specular *= lit;
+            }
+            diffuse_light += diffuse;
+            specular_light += specular;
+        }
+        if let Some(environment) = environment {
+            // `environmentLight` at `environmentRoughness`.
+            let roughness = 0.5;
+            let intensity = self.environment.map_or(1.0, |lighting| lighting.intensity);
+            let ndotv = n.dot(view_dir).max(0.0);
+            let fresnel = 0.04 + (f32::max(1.0 - roughness, 0.04) - 0.04) * (1.0 - ndotv).powf(5.0);
+            let r = -view_dir - n * 2.0 * n.dot(-view_dir);
+            diffuse_light += environment.irradiance_at(n) * intensity;
+            specular_light +=
+                environment.specular_at(r, roughness * environment.max_lod()) * fresnel * intensity;
+        }
+        (diffuse_light, specular_light)
+    }
+}
+
+/// `environmentBrdf`: Karis' analytic fit of the split-sum BRDF integral,
+/// as (scale, bias) of F0.
+fn environment_brdf(ndotv: f32, roughness: f32) -> (f32, f32) {
+    let r = Vector4::new(-1.0, -0.0275, -0.572, 0.022) * roughness
+        + Vector4::new(1.0, 0.0425, 1.04, -0.04);
+    let a004 = (r.x * r.x).min((-9.28 * ndotv).exp2()) * r.x + r.y;
+    (-1.04 * a004 + r.z, 1.04 * a004 + r.w)
+}
+
+/// `PbrMaterial`'s base color: the factor times the bound texture.
+fn pbr_base_color(
+    program: &Program,
+    factors: &PbrFactors,
+    textures: &PbrTextures,
+    u: f32,
+    v: f32,
+) -> Vector4<f32> {
+    let [r, g, b, a] = factors.base_color;
+    if !textures.base_color {
+        return Vector4::new(r, g, b, a);
+    }
+    let texel = program.albedo.sample(u, v);
+    Vector4::new(texel.x * r, texel.y * g, texel.z * b, texel.w * a)
+}
+
+/// Whether the fragment shader `discard`s: a masked PBR fragment under its
+/// alpha cutoff.
+fn discards(program: &Program, varyings: &[f32; VARYINGS]) -> bool {
+    match program.shading {
+        Shading::Pbr { factors, textures } => match factors.alpha_mode {
+            AlphaMode::Mask(cutoff) => {
+                pbr_base_color(program, &factors, &textures, varyings[3], varyings[4]).w < cutoff
+            }
+            AlphaMode::Opaque | AlphaMode::Blend => false,
+        },
+        _ => false,
+    }
+}
+
+/// `skinMatrix` in the skinned vertex shaders; an index past the palette (or
+/// `MAX_JOINTS`) contributes nothing.
+fn skin_matrix(
+    joints: &[Matrix4<f32>],
+    indices: Vector4<f32>,
+    weights: Vector4<f32>,
+) -> Matrix4<f32> {
+    let mut skin = Matrix4::zero();
+    for (index, weight) in [
+        (indices.x, weights.x),
+        (indices.y, weights.y),
+        (indices.z, weights.z),
+        (indices.w, weights.w),
+    ] {
+        if let Some(joint) = joints
+            .get(index as usize)
+            .filter(|_| (index as usize) < MAX_JOINTS)
+        {
+            skin += joint * weight;
+        }
+    }
+    skin
+}
+
+/// `sampleShadow`: 0 = lit, 1 = shadowed. The first view whose frustum holds
+/// the point wins; 3x3 PCF over nearest texels clamped to that view's map, and
+/// anything outside every view reads as lit.
+fn sample_shadow(shadow: &ShadowMap, world_pos: Vector3<f32>, ndotl: f32) -> f32 {
+    for view in &shadow.views {
+        let light_space = view.light_space_matrix * world_pos.extend(1.0);
+        if light_space.w <= 0.0 {
+            continue;
+        }
+        let proj = light_space.truncate() / light_space.w * 0.5 + vec3(0.5, 0.5, 0.5);
+        if proj.z > 1.0 || proj.x < 0.0 || proj.x > 1.0 || proj.y < 0.0 || proj.y > 1.0 {
+            continue;
+        }
+        let bias = (0.0015 * (1.0 - ndotl)).max(0.0008);
+        let size = view.size as f32;
+        let texel = |coord: f32| ((coord * size).floor() as i64).clamp(0, view.size as i64 - 1);
+        let mut shadowed = 0.0;
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                let x = texel(proj.x + dx as f32 / size) as usize;
+                let y = texel(proj.y + dy as f32 / size) as usize;
+                if proj.z - bias > view.depth[y * view.size + x] {
+                    shadowed += 1.0;
+                }
+            }
+        }
+        return shadowed / 9.0;
+    }
+    0.0
+}
+
+/// Clip a polygon in homogeneous space against the six frustum planes
+/// (Sutherland–Hodgman), interpolating varyings linearly in clip space.
+fn clip_polygon(mut polygon: Vec<ClipVertex>) -> Vec<ClipVertex> {
+    let planes: [fn(&Vector4<f32>) -> f32; 6] = [
+        |c| c.w + c.x,
+        |c| c.w - c.x,
+        |c| c.w + c.y,
+        |c| c.w - c.y,
+        |c| c.w + c.z,
+        |c| c.w - c.z,
+    ];
+    for plane in planes {
+        if polygon.is_empty() {
+            break;
+        }
+        let mut clipped = Vec::with_capacity(polygon.len() + 2);
+        for i in 0..polygon.len() {
+            let current = &polygon[i];
+            let next = &polygon[(i + 1) % polygon.len()];
+            let (dc, dn) = (plane(&current.clip), plane(&next.clip));
+            if dc >= 0.0 {
+                clipped.push(*current);
+            }
+            if (dc >= 0.0) != (dn >= 0.0) {
+                clipped.push(current.lerp(next, dc / (dc - dn)));
+            }
+        }
+        polygon = clipped;
+    }
+    polygon
+}
+
+/// Write one fragment through the blend unit into an RGBA8 texel. Sources
+/// clamp to `[0, 1]` first, as they do for a fixed-point target.
+fn blend(mode: Blend, source: Vector4<f32>, destination: [u8; 4]) -> [u8; 4] {
+    let s = source.map(clamp01);
+    let d = Vector4::new(
+        destination[0] as f32,
+        destination[1] as f32,
+        destination[2] as f32,
+        destination[3] as f32,
+    ) / 255.0;
+    let factor = match mode {
+        Blend::Off => return to_unorm8(s),
+        Blend::Alpha => s.w,
+        Blend::Constant(alpha) => alpha,
+    };
+    let rgb = s.truncate() * factor + d.truncate() * (1.0 - factor);
+    to_unorm8(rgb.extend(s.w + d.w * (1.0 - s.w)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Angle;
+    use crate::{Camera2D, SpriteLayer};
+
+    fn camera() -> Camera {
+        Camera::look_at(
+            [0.0, 0.0, 5.0],
+            [0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            Angle::from_degrees(45.0),
+        )
+    }
+
+    fn group(items: Vec<Scene3D>) -> Scene3D {
+        Scene3D {
+            obj: SceneObject::Group(items),
+            xform: Matrix4::identity(),
+        }
+    }
+
+    fn colored(r: f32, g: f32, b: f32, a: f32, scene: Scene3D) -> Scene3D {
+        Scene3D {
+            obj: SceneObject::Material(MaterialDescription::color(r, g, b, a), vec![scene]),
+            xform: Matrix4::identity(),
+        }
+    }
+
+    fn render(frame: &Frame, width: u32, height: u32) -> Vec<u8> {
+        render_frame(
+            Arc::new(AssetCache::new()),
+            &SceneContext::new(),
+            frame,
+            FrameTime { tts: 0.0, dts: 0.0 },
+            width,
+            height,
+            DebugRenderMode::Default,
+        )
+    }
+
+    fn pixel(rgba: &[u8], width: u32, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * width + x) * 4) as usize;
+        [rgba[i], rgba[i + 1], rgba[i + 2], rgba[i + 3]]
+    }
+
+    #[test]
+    fn an_empty_frame_is_the_clear_color() {
+        let frame = Frame::with_clear_color(Frame::new(camera(), group(vec![])), 1.0, 0.5, 0.0);
+        let rgba = render(&frame, 8, 6);
+        assert_eq!(rgba.len(), 8 * 6 * 4);
+        assert!(rgba.chunks_exact(4).all(|p| p == [255, 128, 0, 255]));
+    }
+
+    #[test]
+    fn a_color_cube_covers_the_center_and_not_the_corners() {
+        let frame = Frame::with_clear_color(
+            Frame::new(camera(), colored(1.0, 0.0, 0.0, 1.0, Scene3D::cube())),
+            0.0,
+            0.0,
+            0.0,
+        );
+        let rgba = render(&frame, 64, 48);
+        assert_eq!(pixel(&rgba, 64, 32, 24), [255, 0, 0, 255]);
+        assert_eq!(pixel(&rgba, 64, 0, 0), [0, 0, 0, 255]);
+        assert_eq!(pixel(&rgba, 64, 63, 47), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn the_nearer_surface_wins_the_depth_test() {
+        // Green is drawn second but sits behind red.
+        let scene = group(vec![
+            colored(1.0, 0.0, 0.0, 1.0, Scene3D::cube()),
+            colored(
+                0.0,
+                1.0,
+                0.0,
+                1.0,
+                Scene3D::cube().translate_z(-2.0).scale_x(4.0),
+            ),
+        ]);
+        let rgba = render(&Frame::new(camera(), scene), 64, 48);
+        assert_eq!(pixel(&rgba, 64, 32, 24), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn linear_fog_fully_covers_geometry_past_its_far_distance() {
+        let frame = Frame::with_fog(
+            Frame::new(camera(), colored(1.0, 0.0, 0.0, 1.0, Scene3D::cube())),
+            Fog::linear(0.0, 1.0, 0.0, 0.0, 1.0),
+        );
+        let rgba = render(&frame, 32, 24);
+        assert_eq!(pixel(&rgba, 32, 16, 12), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn lit_surfaces_take_only_ambient_light_without_direct_lights() {
+        let lit = Scene3D {
+            obj: SceneObject::Material(
+                MaterialDescription::lit(1.0, 1.0, 1.0, 1.0),
+                vec![Scene3D::cube()],
+            ),
+            xform: Matrix4::identity(),
+        };
+        let mut frame = Frame::new(camera(), lit);
+        frame.lights = vec![Light::ambient(0.25, 0.5, 1.0)];
+        let rgba = render(&frame, 32, 24);
+        assert_eq!(pixel(&rgba, 32, 16, 12), [64, 128, 255, 255]);
+    }
+
+    #[test]
+    fn a_reflection_probe_lights_surfaces_with_what_it_sees() {
+        let lit = Scene3D {
+            obj: SceneObject::Material(
+                MaterialDescription::lit(1.0, 1.0, 1.0, 1.0),
+                vec![Scene3D::cube()],
+            ),
+            xform: Matrix4::identity(),
+        };
+        // No lights: the cube is black until a probe between it and the
+        // camera sees the blue clear color around it.
+        let frame = Frame::with_clear_color(Frame::new(camera(), lit), 0.0, 0.25, 1.0);
+        let dark = pixel(&render(&frame, 32, 24), 32, 16, 12);
+        let probed = Frame::with_reflection_probe(
+            frame,
+            crate::environment::ReflectionProbe {
+                position: [0.0, 0.0, 3.0],
+            },
+        );
+        let lit = pixel(&render(&probed, 32, 24), 32, 16, 12);
+        assert_eq!(dark[..3], [0, 0, 0]);
+        assert!(
+            lit[2] > 100 && lit[2] > lit[1] && lit[1] > lit[0],
+            "{lit:?}"
+        );
+    }
+
+    #[test]
+    fn a_casting_point_light_shadows_the_floor_below_a_cube() {
+        let lit = |scene: Scene3D| Scene3D {
+            obj: SceneObject::Material(MaterialDescription::lit(1.0, 1.0, 1.0, 1.0), vec![scene]),
+            xform: Matrix4::identity(),
+        };
+        let scene = group(vec![
+            lit(Scene3D::plane().scale_x(12.0).scale_z(12.0)),
+            lit(Scene3D::cube().translate_y(1.5)),
+        ]);
+        let camera = Camera::look_at(
+            [0.0, 4.0, 8.0],
+            [0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            Angle::from_degrees(45.0),
+        );
+        let light = Light::point(0.0, 4.0, 0.0, 1.0, 1.0, 1.0, 1.0, 12.0);
+        let (width, height) = (64, 48);
+        // The floor just in front of the cube's footprint, as a pixel.
+        let clip = camera.projection_matrix(width as f32 / height as f32)
+            * camera.view_matrix()
+            * Vector4::new(0.0, 0.0, 0.6, 1.0);
+        let ndc = clip.truncate() / clip.w;
+        let x = ((ndc.x * 0.5 + 0.5) * width as f32) as u32;
+        let y = ((0.5 - ndc.y * 0.5) * height as f32) as u32;
+
+        let mut frame = Frame::new(camera, scene);
+        frame.lights = vec![light.clone()];
+        let unshadowed = pixel(&render(&frame, width, height), width, x, y);
+        frame.lights = vec![light.cast_shadows()];
+        let shadowed = pixel(&render(&frame, width, height), width, x, y);
+        assert!(unshadowed[0] > 100, "the floor is lit: {unshadowed:?}");
+        assert_eq!(shadowed[..3], [0, 0, 0], "the cube shadows it");
+    }
+
+    #[test]
+    fn a_translucent_material_blends_over_the_clear_color() {
+        let frame = Frame::with_clear_color(
+            Frame::new(camera(), colored(1.0, 1.0, 1.0, 0.5, Scene3D::cube())),
+            0.0,
+            0.0,
+            0.0,
+        );
+        let rgba = render(&frame, 32, 24);
+        assert_eq!(pixel(&rgba, 32, 16, 12), [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn pbr_alpha_masks_cut_out_and_blends_mix_with_what_is_behind() {
+        let half_white = |alpha_mode: AlphaMode| {
+            let mut pbr = crate::pbr::PbrDescription::new([1.0, 1.0, 1.0, 0.5], 0.0, 1.0);
+            pbr.factors.alpha_mode = alpha_mode;
+            let mut frame = Frame::with_clear_color(
+                Frame::new(
+                    camera(),
+                    Scene3D {
+                        obj: SceneObject::Material(
+                            MaterialDescription::Pbr(Box::new(pbr)),
+                            vec![Scene3D::cube()],
+                        ),
+                        xform: Matrix4::identity(),
+                    },
+                ),
+                0.0,
+                0.0,
+                1.0,
+            );
+            frame.lights = vec![Light::ambient(1.0, 1.0, 1.0)];
+            pixel(&render(&frame, 32, 24), 32, 16, 12)
+        };
+        // Alpha 0.5 falls below a 0.6 cutoff: the clear color shows through.
+        assert_eq!(half_white(AlphaMode::Mask(0.6)), [0, 0, 255, 255]);
+        // Above a 0.4 cutoff the surface is opaque and ambient-lit white.
+        let kept = half_white(AlphaMode::Mask(0.4));
+        assert!(kept[0] > 230 && kept[0] == kept[2], "{kept:?}");
+        // Blended, it lands halfway between white and the blue behind it.
+        let blended = half_white(AlphaMode::Blend);
+        assert!(
+            (100..160).contains(&blended[0]) && blended[2] > 230,
+            "{blended:?}"
+        );
+        // Opaque ignores the alpha entirely.
+        assert_eq!(half_white(AlphaMode::Opaque), kept);
+    }
+
+    #[test]
+    fn post_effects_run_over_the_3d_pass_and_not_the_sprite_layers() {
+        let red_cube = Frame::with_clear_color(
+            Frame::new(camera(), colored(1.0, 0.0, 0.0, 1.0, Scene3D::cube())),
+            0.0,
+            0.0,
+            0.0,
+        );
+        // Bloom spreads the cube's red a few pixels past its edge.
+        assert_eq!(
+            pixel(&render(&red_cube, 64, 48), 64, 42, 24),
+            [0, 0, 0, 255]
+        );
+        let bloomed = Frame::with_post(red_cube.clone(), vec![PostEffect::bloom(0.5, 1.0)]);
+        let rgba = render(&bloomed, 64, 48);
+        let glow = pixel(&rgba, 64, 42, 24);
+        assert!(glow[0] > 0 && glow[1] == 0, "{glow:?}");
+        assert_eq!(pixel(&rgba, 64, 0, 0), [0, 0, 0, 255]);
+
+        // A vignette darkens the corners of the 3D pass; the sprite layer
+        // drawn afterwards keeps its exact color.
+        let sprite = colored(
+            0.0,
+            1.0,
+            0.0,
+            1.0,
+            Scene3D::quad().scale_y(2.0).translate_x(0.75),
+        );
+        let frame = Frame::with_post(
+            Frame::with_2d(
+                Frame::with_clear_color(Frame::new(camera(), group(vec![])), 1.0, 1.0, 1.0),
+                SpriteLayer {
+                    camera: Camera2D::new(2.0, 2.0),
+                    scene: sprite,
+                },
+            ),
+            vec![PostEffect::vignette(0.5, 0.5)],
+        );
+        let rgba = render(&frame, 40, 40);
+        assert_eq!(pixel(&rgba, 40, 10, 20), [255, 255, 255, 255]);
+        assert_eq!(pixel(&rgba, 40, 0, 0)[0], 128);
+        assert_eq!(pixel(&rgba, 40, 39, 39), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn depth_of_field_blurs_only_what_is_out_of_focus() {
+        let red_cube = Frame::with_clear_color(
+            Frame::new(camera(), colored(1.0, 0.0, 0.0, 1.0, Scene3D::cube())),
+            0.0,
+            0.0,
+            0.0,
+        );
+        let edge = |focus_distance: f32| {
+            let frame = Frame::with_post(
+                red_cube.clone(),
+                vec![PostEffect::depth_of_field(focus_distance, 2.0, 6.0)],
+            );
+            let rgba = render(&frame, 64, 48);
+            (pixel(&rgba, 64, 32, 24), pixel(&rgba, 64, 37, 24))
+        };
+        // Focused on the cube's face, its edge stays crisp.
+        assert_eq!(edge(4.5), ([255, 0, 0, 255], [255, 0, 0, 255]));
+        // Focused far behind it, the black background bleeds into the edge
+        // more than into the center.
+        let (center, inside_edge) = edge(40.0);
+        assert!(inside_edge[0] < center[0], "{center:?} {inside_edge:?}");
+        assert_eq!((inside_edge[1], inside_edge[2]), (0, 0));
+    }
+
+    #[test]
+    fn sprite_layers_draw_over_the_3d_pass_with_a_y_up_camera() {
+        // A quad shifted into the top half of a 2x2 world.
+        let sprite = colored(0.0, 1.0, 0.0, 1.0, Scene3D::quad().translate_y(0.5));
+        let layer = SpriteLayer {
+            camera: Camera2D::new(2.0, 2.0),
+            scene: sprite,
+        };
+        let frame = Frame::with_2d(
+            Frame::with_clear_color(Frame::new(camera(), group(vec![])), 0.0, 0.0, 0.0),
+            layer,
+        );
+        let rgba = render(&frame, 20, 20);
+        assert_eq!(pixel(&rgba, 20, 10, 5), [0, 255, 0, 255]);
+        assert_eq!(pixel(&rgba, 20, 10, 15), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn skinning_blends_joints_by_weight_and_drops_out_of_range_indices() {
+        let joints = [
+            Matrix4::from_translation(vec3(2.0, 0.0, 0.0)),
+            Matrix4::from_translation(vec3(0.0, 4.0, 0.0)),
+        ];
+        let skin = skin_matrix(
+            &joints,
+            Vector4::new(0.0, 1.0, 7.0, 0.0),
+            Vector4::new(0.5, 0.25, 0.25, 0.0),
+        );
+        // Index 7 has no joint, so a quarter of the weight contributes nothing.
+        let moved = skin * Vector4::new(0.0, 0.0, 0.0, 1.0);
+        assert_eq!(moved, Vector4::new(1.0, 1.0, 0.0, 0.75));
+    }
+
+    #[test]
+    fn the_capture_is_a_png_of_the_requested_size() {
+        let frame = Frame::new(camera(), group(vec![]));
+        let png = capture_png(
+            Arc::new(AssetCache::new()),
+            &SceneContext::new(),
+            &frame,
+            FrameTime { tts: 0.0, dts: 0.0 },
+            12,
+            7,
+            DebugRenderMode::Default,
+        )
+        .expect("encodes");
+        let image = image::load_from_memory(&png).expect("decodes");
+        assert_eq!((image.width(), image.height()), (12, 7));
+    }
+
+    #[test]
+    fn shared_edges_cover_each_pixel_once() {
+        // A translucent quad is two triangles sharing a diagonal: any pixel
+        // covered twice would blend darker than its neighbors.
+        let frame = Frame::with_clear_color(
+            Frame::new(
+                camera(),
+                colored(1.0, 1.0, 1.0, 0.5, Scene3D::quad().scale_x(3.0)),
+            ),
+            0.0,
+            0.0,
+            0.0,
+        );
+        let rgba = render(&frame, 64, 64);
+        let covered: HashSet<[u8; 4]> = rgba
+            .chunks_exact(4)
+            .map(|p| [p[0], p[1], p[2], p[3]])
+            .filter(|p| *p != [0, 0, 0, 255])
+            .collect();
+        assert_eq!(covered, HashSet::from([[128, 128, 128, 255]]));
+    }
+}
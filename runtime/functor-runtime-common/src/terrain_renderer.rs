@@ -730,7 +730,7 @@ impl TerrainRenderer {
                     );
                 }
             }
-            u.lighting.set(p, ctx, view);
+            u.lighting.set(p, ctx, view, world.w.truncate());
             u.fog.set(p, gl, ctx.fog, &ctx.camera_pos);
 
             gl.active_texture(glow::TEXTURE0);
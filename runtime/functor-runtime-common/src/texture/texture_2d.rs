@@ -1,3 +1,5 @@
+use std::cell::Ref;
+
 use glow::HasContext;
 
 use crate::{
@@ -87,6 +89,18 @@ impl Texture2D {
         }
     }
 
+    /// The decoded pixels and sampling options, while the texture has never
+    /// been uploaded — what the software renderer samples.
+    pub(crate) fn cpu_data(&self) -> Option<(Ref<'_, TextureData>, Ref<'_, TextureOptions>)> {
+        self.ora.dehydrated()
+    }
+
+    /// The GL texture, uploading it on first use — for passes that bind a
+    /// texture outside a material (the post stack's lookup table).
+    pub(crate) fn gl_texture(&self, gl: &glow::Context) -> glow::Texture {
+        *self.ora.get(gl)
+    }
+
     /// The image's mean RGB, computed once at load, or white when
     /// `compute_average` was not requested.
     ///
@@ -909,6 +909,7 @@ impl SceneRecorder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use functor_lang::value::List;
     use functor_lang::{RunOutcome, Tracing};
     use std::rc::Rc;
 
@@ -1109,7 +1110,7 @@ mod tests {
 
     #[test]
     fn value_model_restores_exact() {
-        let shared = Value::List(Rc::new(vec![Value::Number(1.0), Value::Number(2.0)]));
+        let shared = Value::List(List::from(vec![Value::Number(1.0), Value::Number(2.0)]));
         let mut h = History::unbounded();
         // Simulate `{ model with n: n + 1 }` evolving each frame.
         for f in 0..8 {
@@ -1563,7 +1564,7 @@ mod tests {
     #[test]
     fn structural_sharing_survives_snapshots() {
         // One `shared` sub-tree referenced by every frame's model.
-        let shared = Value::List(Rc::new(vec![Value::Number(1.0), Value::Number(2.0)]));
+        let shared = Value::List(List::from(vec![Value::Number(1.0), Value::Number(2.0)]));
         let mut h = History::unbounded();
         h.record(0, &model(&shared, 0.0));
         h.record(1, &model(&shared, 1.0));
@@ -1575,7 +1576,7 @@ mod tests {
         let s1 = field(h.seek(1), "shared");
         match (s0, s1) {
             (Value::List(a), Value::List(b)) => {
-                assert!(Rc::ptr_eq(a, b), "the shared sub-tree was deep-copied");
+                assert!(List::ptr_eq(a, b), "the shared sub-tree was deep-copied");
             }
             _ => panic!("shared field is not a list"),
         }
@@ -708,6 +708,12 @@ fn faded_material(
             source_pixels: *source_pixels,
             sampling: *sampling,
         }),
+        Some(MaterialDescription::Pbr(pbr)) => {
+            let mut pbr = pbr.clone();
+            let faded = lerp(Vector4::from(pbr.factors.base_color));
+            pbr.factors.base_color = faded.into();
+            Some(MaterialDescription::Pbr(pbr))
+        }
         None => None,
     }
 }
@@ -762,6 +768,16 @@ fn alpha_faded_material(
             source_pixels: *source_pixels,
             sampling: *sampling,
         }),
+        // A metallic-roughness material only honors its alpha in blend mode,
+        // so a fading copy switches to it.
+        Some(MaterialDescription::Pbr(pbr)) => {
+            let mut pbr = pbr.clone();
+            pbr.factors.base_color = fade(Vector4::from(pbr.factors.base_color)).into();
+            if pbr.factors.base_color[3] < 1.0 {
+                pbr.factors.alpha_mode = crate::pbr::AlphaMode::Blend;
+            }
+            Some(MaterialDescription::Pbr(pbr))
+        }
         None => None,
     }
 }
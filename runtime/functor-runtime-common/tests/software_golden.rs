@@ -0,0 +1,228 @@
+//! Golden images for the CPU software renderer — the `"software"` target of
+//! `golden-scenarios.json` (the scenario list shared with the native and wasm
+//! harnesses; see `runtime/functor-runtime-desktop/tests/golden.rs`).
+//!
+//! Unlike those harnesses this needs no GL, no display, and no built CLI: it
+//! interprets each sample's `.fun` sources in-process, seeds the asset cache
+//! with the sample's files, drives a few frames at the scenario's fixed time,
+//! and rasterizes the last `Frame` with
+//! `functor_runtime_common::software_renderer`. It runs in plain
+//! `cargo test`, so only samples whose assets are checked in carry the
+//! target (the fetched `.glb` models are not).
+//!
+//! References live at `examples/<sample>/golden/software/<name>.png`. They
+//! are renderer-specific but not machine-specific (the rasterizer is plain
+//! f32 math). To regenerate them after an intended rendering change:
+//!
+//! ```sh
+//! UPDATE_GOLDENS=1 cargo test -p functor_runtime_common --test software_golden
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use functor_runtime_common::asset::AssetCache;
+use functor_runtime_common::functor_lang_game_embedded::{FunctorLangEmbeddedGame, NativePlatform};
+use functor_runtime_common::protocol::GameProducer;
+use functor_runtime_common::{software_renderer, DebugRenderMode, FrameTime, SceneContext};
+use serde::Deserialize;
+
+/// Software goldens render small: the rasterizer is unoptimized in test
+/// builds, and the references are compared exactly enough that resolution
+/// adds nothing. Changing this invalidates every software reference.
+const SOFTWARE_CAPTURE_SIZE: (u32, u32) = (320, 240);
+/// Frames driven before the capture, so a loading screen gated on asset
+/// progress (`Sub.assets`) has settled and the model reflects it.
+const WARMUP_FRAMES: usize = 3;
+// The same per-pixel tolerance and failing fraction as the GL harness.
+const TOLERANCE: u8 = 16;
+const MAX_DIFF_FRACTION: f64 = 0.01;
+
+#[derive(Debug, Deserialize)]
+struct Scenario {
+    name: String,
+    sample: String,
+    #[serde(rename = "fixedTime")]
+    fixed_time: f32,
+    #[serde(rename = "debugRender")]
+    debug_render: Option<String>,
+    targets: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    scenarios: Vec<Scenario>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Project {
+    entry: String,
+}
+
+fn repo_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../..")
+        .canonicalize()
+        .expect("resolve repo root")
+}
+
+fn debug_render_mode(name: Option<&str>) -> DebugRenderMode {
+    match name {
+        None => DebugRenderMode::Default,
+        Some("normals") => DebugRenderMode::Normals,
+        Some("tangents") => DebugRenderMode::Tangents,
+        Some("transparent") => DebugRenderMode::Transparent,
+        Some("physics") => DebugRenderMode::Physics,
+        Some(other) => panic!("unknown debugRender {other:?}"),
+    }
+}
+
+/// Every file under `dir` except the golden references, as paths relative to
+/// `dir` (how the sample's sources and `Asset.*` locators name them).
+fn sample_files(dir: &Path, relative: &Path, out: &mut Vec<PathBuf>) {
+    let mut entries: Vec<_> = std::fs::read_dir(dir.join(relative))
+        .unwrap_or_else(|e| panic!("read {}: {e}", dir.join(relative).display()))
+        .map(|entry| entry.expect("dir entry").file_name())
+        .collect();
+    entries.sort();
+    for name in entries {
+        let path = relative.join(&name);
+        if dir.join(&path).is_dir() {
+            if name != "golden" {
+                sample_files(dir, &path, out);
+            }
+        } else {
+            out.push(path);
+        }
+    }
+}
+
+/// Run `scenario`'s sample in-process and software-render its frame as a PNG.
+fn render_scenario(scenario: &Scenario) -> Vec<u8> {
+    let sample_dir = repo_root().join("examples").join(&scenario.sample);
+    let project: Project = serde_json::from_str(
+        &std::fs::read_to_string(sample_dir.join("functor.json")).expect("read functor.json"),
+    )
+    .expect("parse functor.json");
+
+    let asset_cache = Arc::new(AssetCache::new());
+    let mut files = Vec::new();
+    sample_files(&sample_dir, Path::new(""), &mut files);
+    let mut sources = Vec::new();
+    for file in files {
+        let locator = file.to_string_lossy().replace('\\', "/");
+        let bytes = std::fs::read(sample_dir.join(&file)).expect("read sample file");
+        if locator.ends_with(".fun") {
+            let source = String::from_utf8(bytes).expect("utf-8 source");
+            // The entry first, then its siblings.
+            let at = if locator == project.entry {
+                0
+            } else {
+                sources.len()
+            };
+            sources.insert(at, (locator, source));
+        } else {
+            asset_cache.replace_uploaded(&locator, bytes);
+        }
+    }
+
+    let mut game = FunctorLangEmbeddedGame::create(sources, Box::new(NativePlatform))
+        .unwrap_or_else(|e| panic!("load {}: {e}", scenario.sample));
+    let scene_context = SceneContext::new();
+    let time = FrameTime {
+        tts: scenario.fixed_time,
+        dts: 0.0,
+    };
+    let (width, height) = SOFTWARE_CAPTURE_SIZE;
+    let mode = debug_render_mode(scenario.debug_render.as_deref());
+    let mut png = Vec::new();
+    for _ in 0..WARMUP_FRAMES {
+        game.push_asset_progress(asset_cache.progress());
+        let commands = serde_json::from_str(&game.preload_drain_commands()).unwrap_or_default();
+        for token in scene_context.drive_preloads(&asset_cache, commands) {
+            game.preload_push_settled(token);
+        }
+        game.tick(time);
+        let frame = game.render(time);
+        // Rendering is what starts (and polls) the frame's asset loads.
+        png = software_renderer::capture_png(
+            asset_cache.clone(),
+            &scene_context,
+            &frame,
+            time,
+            width,
+            height,
+            mode,
+        )
+        .expect("encode capture");
+    }
+    png
+}
+
+fn assert_matches_golden(name: &str, png: &[u8], golden_path: &Path) {
+    if std::env::var_os("UPDATE_GOLDENS").is_some() {
+        std::fs::create_dir_all(golden_path.parent().unwrap()).expect("create golden dir");
+        std::fs::write(golden_path, png).expect("write golden");
+        println!("updated {}", golden_path.display());
+        return;
+    }
+    let actual = image::load_from_memory(png)
+        .expect("decode capture")
+        .to_rgba8();
+    let golden = image::open(golden_path)
+        .unwrap_or_else(|e| {
+            panic!(
+                "open golden {}: {e} — run with UPDATE_GOLDENS=1 to create it",
+                golden_path.display()
+            )
+        })
+        .to_rgba8();
+    assert_eq!(
+        actual.dimensions(),
+        golden.dimensions(),
+        "{name}: dimensions differ"
+    );
+
+    let differing = actual
+        .pixels()
+        .zip(golden.pixels())
+        .filter(|(a, g)| (0..4).any(|i| a[i].abs_diff(g[i]) > TOLERANCE))
+        .count();
+    let fraction = differing as f64 / (actual.width() * actual.height()) as f64;
+    assert!(
+        fraction <= MAX_DIFF_FRACTION,
+        "{name}: software rendering drifted from {}: {:.3}% of pixels exceed tolerance \
+         (max {:.3}%)",
+        golden_path.display(),
+        fraction * 100.0,
+        MAX_DIFF_FRACTION * 100.0
+    );
+}
+
+#[test]
+fn software_goldens_match() {
+    let manifest_path = repo_root().join("golden-scenarios.json");
+    let manifest: Manifest = serde_json::from_str(
+        &std::fs::read_to_string(&manifest_path).expect("read golden-scenarios.json"),
+    )
+    .expect("parse golden-scenarios.json");
+
+    let scenarios: Vec<_> = manifest
+        .scenarios
+        .iter()
+        .filter(|scenario| scenario.targets.iter().any(|target| target == "software"))
+        .collect();
+    assert!(
+        !scenarios.is_empty(),
+        "no scenario lists the \"software\" target"
+    );
+    for scenario in scenarios {
+        let png = render_scenario(scenario);
+        let golden = repo_root()
+            .join("examples")
+            .join(&scenario.sample)
+            .join("golden/software")
+            .join(format!("{}.png", scenario.name));
+        assert_matches_golden(&scenario.name, &png, &golden);
+    }
+}
@@ -29,11 +29,15 @@
 //! Per-frame errors print and keep the previous model/frame (a bad frame
 //! must not kill the session); load errors fail loud at startup.
 
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::time::Instant;
 
+use functor_lang::profile::{ProfileFormat, Profiler};
 use functor_lang::project::SourceMap;
 use functor_lang::{Session, Value};
 use functor_runtime_common::events::{self, RuntimeEvent};
+use functor_runtime_common::functor_lang_debug::{DebugClock, GameDebugger};
 use functor_runtime_common::functor_lang_prelude::{
     audio_scene_of, clear_audio_completions, clear_http_taggers, clear_preload_completions,
     frame_value, html_node_value, take_ui_handlers, view_value, EffectLog, EffectRunner,
@@ -221,6 +225,14 @@ pub struct FunctorLangGame {
     /// Invalidated when the frame advances (`tick`), the paused frame changes
     /// (rewind/seek), or the program reloads.
     cached_trace: Option<String>,
+    /// The source-level debugger behind `POST /debug` — breakpoints, and the
+    /// stop inside a replayed frame (functor_runtime_common::functor_lang_debug).
+    debugger: GameDebugger,
+    /// The hook profiler behind `--profile` / `GET /profile`
+    /// (functor_lang::profile), armed on first use. It outlives a hot reload:
+    /// the new session is re-armed with it, so one profile spans every
+    /// generation of the code.
+    profiler: Option<Rc<RefCell<Profiler>>>,
     /// Per-file sha256 of the loaded `.fun` source, computed at load /
     /// hot-reload (not per frame) — the wire contract's `sources`, and the
     /// per-file base→(file, local offset) map for binding spans.
@@ -500,6 +512,8 @@ impl FunctorLangGame {
             journal_ring: std::collections::VecDeque::new(),
             runnable,
             cached_trace: None,
+            debugger: GameDebugger::new(),
+            profiler: None,
             source_hashes,
             frames: 0,
             tick_ns: 0,
@@ -587,12 +601,14 @@ impl FunctorLangGame {
         self.journal_ring.clear(); // old program's spans
         self.runnable = functor_lang::coverage::runnable_offsets(&loaded.module);
         self.cached_trace = None;
+        self.debugger.reload();
         journal_swap(); // discard any partial current-frame journal
         self.reporter
             .set_source(SpanSource::Project(loaded.sources));
         self.names = loaded.names;
         self.module = loaded.module;
         self.session = loaded.session;
+        self.session.set_profiler(self.profiler.clone());
         self.has_input = loaded.has_input;
         self.has_sampled_input = loaded.has_sampled_input;
         self.pending_sampled_input = None;
@@ -688,12 +704,14 @@ impl FunctorLangGame {
         self.journal_ring.clear();
         self.runnable = functor_lang::coverage::runnable_offsets(&loaded.module);
         self.cached_trace = None;
+        self.debugger.reload();
         journal_swap();
         self.reporter
             .set_source(SpanSource::Project(loaded.sources));
         self.names = loaded.names;
         self.module = loaded.module;
         self.session = loaded.session;
+        self.session.set_profiler(self.profiler.clone());
         self.model = loaded.init;
         self.has_input = loaded.has_input;
         self.has_sampled_input = loaded.has_sampled_input;
@@ -1469,6 +1487,39 @@ AudioScene.empty), got {}",
         }
     }
 
+    fn debug(
+        &mut self,
+        request: &serde_json::Value,
+    ) -> Result<(serde_json::Value, DebugClock), String> {
+        self.debugger
+            .command(request, &self.last_frame_journal, &self.session, &self.sources)
+    }
+
+    /// Replay the frame that just ran under the debugger's breakpoints — a
+    /// no-op unless the client set some (or asked to pause).
+    fn debug_frame(&mut self) -> bool {
+        self.debugger.armed()
+            && self
+                .debugger
+                .scan(&self.last_frame_journal, &self.session, &self.sources)
+    }
+
+    fn start_profiling(&mut self) -> Result<(), String> {
+        if self.profiler.is_none() {
+            let profiler = Rc::new(RefCell::new(Profiler::new()));
+            self.session.set_profiler(Some(profiler.clone()));
+            self.profiler = Some(profiler);
+        }
+        Ok(())
+    }
+
+    fn profile(&self, format: ProfileFormat) -> Result<String, String> {
+        match &self.profiler {
+            Some(profiler) => Ok(profiler.borrow().render(format)),
+            None => Err("the hook profiler is not running".to_string()),
+        }
+    }
+
     fn net_drain_commands(&self) -> String {
         // HttpRequest commands (Effect.httpGet/httpPost), performed by the
         // shell's net_dispatch; the response returns via net_push_http_*.
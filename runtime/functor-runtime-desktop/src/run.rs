@@ -12,7 +12,9 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use functor_runtime_common::asset::AssetCache;
+use functor_runtime_common::functor_lang_debug::DebugClock;
 use functor_runtime_common::net::DeliveredEvent;
+use functor_runtime_common::software_renderer;
 use functor_runtime_common::viewer::{camera_frustum_lines, DebugCamera, DebugPresentation};
 use functor_runtime_common::{
     Frame, FrameTime, GameClock, InputEdges, InputSnapshot, MouseButtons, MouseSnapshot,
@@ -687,9 +689,9 @@ pub struct Args {
 
     /// Run without a GL window: drive the game loop + debug server headlessly
     /// (no GLFW/OpenGL). `/state`, `/scene`, `/input`, `/time` work; `/capture`
-    /// is unavailable (no rendering), and audio isn't played (so `Audio.playThen`
-    /// completions aren't delivered). Incompatible with `--capture-frame`. For
-    /// CI / scripted / LLM-driven runs.
+    /// and `--capture-frame` render through the CPU software renderer, and
+    /// audio isn't played (so `Audio.playThen` completions aren't delivered).
+    /// For CI / scripted / LLM-driven runs and machines with no GPU.
     #[arg(long)]
     headless: bool,
 
@@ -724,7 +726,8 @@ pub struct Args {
 
     /// Write a PNG of the rendered frame to this path, then exit. The capture
     /// happens on the first frame after --capture-time seconds of wall-clock
-    /// time, so assets have a chance to load. Implies --hidden.
+    /// time, so assets have a chance to load. Implies --hidden; with --headless
+    /// the frame is drawn by the CPU software renderer instead.
     #[arg(long)]
     capture_frame: Option<String>,
 
@@ -798,6 +801,16 @@ pub struct Args {
     #[arg(long, default_value = "127.0.0.1")]
     debug_bind: String,
 
+    /// Profile the game's hooks (`tick`, `update`, `draw`, and everything they
+    /// call, host externals like `Scene.*` as leaves) and write the profile
+    /// here when the run ends: a `.folded` / `.collapsed` / `.txt` path gets
+    /// collapsed stacks for flamegraph.pl, anything else a Chrome trace
+    /// (chrome://tracing, Perfetto). A `--headless` run, which ends by being
+    /// killed, rewrites it every 600 frames. `GET /profile` reads the same
+    /// profile live.
+    #[arg(long)]
+    profile: Option<String>,
+
     /// Override shading with a diagnostic view across the whole frame (e.g.
     /// `normals` to visualize surface normals as color). Primitives only for
     /// now; glTF models are unaffected until normal import lands.
@@ -1340,6 +1353,20 @@ fn service_debug_request(
         debug_server::DebugRequest::Time(cmd, resp) => {
             let _ = resp.send(clock.apply(cmd));
         }
+        debug_server::DebugRequest::Profile(format, resp) => {
+            let _ = resp.send(game.start_profiling().and_then(|()| game.profile(format)));
+        }
+        debug_server::DebugRequest::Debug(request, resp) => {
+            let result = game.debug(&request).map(|(body, clock_change)| {
+                match clock_change {
+                    DebugClock::Keep => {}
+                    DebugClock::Pause => clock.pause(),
+                    DebugClock::Resume => clock.resume(),
+                }
+                body
+            });
+            let _ = resp.send(result);
+        }
     }
     sampled_input_changed
 }
@@ -1347,9 +1374,12 @@ fn service_debug_request(
 /// Headless game loop: drives the game + debug server with no GL window, for CI /
 /// scripted / LLM-driven runs (`--headless`). Mirrors the windowed loop's game,
 /// networking, and debug handling, minus everything that needs GL — rendering,
-/// the framebuffer capture, and the GLFW event stream. `game.render` still runs
-/// (it returns a pure `Frame`, no GL) to power `GET /scene`; `POST /capture`
-/// returns an error since there is nothing rendered to read back.
+/// the GL renderer, and the GLFW event stream. `game.render` still runs (it
+/// returns a pure `Frame`, no GL) to power `GET /scene`, and `POST /capture` /
+/// `--capture-frame` draw that frame with the CPU software renderer
+/// (`functor_runtime_common::software_renderer`) — only when asked, so an
+/// uncaptured headless run costs no rasterization.
+#[allow(clippy::too_many_arguments)]
 fn run_headless(
     mut game: Box<dyn Game>,
     debug_requests: Option<std::sync::mpsc::Receiver<debug_server::DebugRequest>>,
@@ -1358,10 +1388,12 @@ fn run_headless(
     input_script: Option<HashMap<u64, Vec<RecordedInput>>>,
     script_dt: f32,
     net_transport: NetTransportArg,
+    profile: Option<&str>,
+    capture: HeadlessCapture,
 ) {
     // Stderr, not stdout: keep the CLI's `--json` ndjson stream (stdout) clean
     // even under `--headless`. This is an out-of-band notice, not an event.
-    eprintln!("[runtime] headless mode — no GL window; /capture unavailable");
+    eprintln!("[runtime] headless mode — no GL window; /capture uses the software renderer");
 
     let start_time = Instant::now();
     let mut last_time: f32 = 0.0;
@@ -1380,9 +1412,8 @@ fn run_headless(
     let mut xr_override: Option<XrInputSnapshot> = None;
     let mut gamepad_override: Option<GamepadSnapshot> = None;
     let mut touch_levels: Option<TouchSnapshot> = None;
-    // Keep the debug protocol target-isomorphic even without pixels: uploaded
-    // assets can be accepted now and are ready if headless rendering gains an
-    // asset path later.
+    // No GL ever hydrates these, so models and textures keep the CPU copies
+    // the software renderer draws from; uploaded assets resolve here too.
     let asset_cache = Arc::new(AssetCache::new());
     let scene_context = SceneContext::new();
 
@@ -1394,6 +1425,8 @@ fn run_headless(
     let (net_tx, net_rx) = std::sync::mpsc::channel::<net_dispatch::NetResult>();
     let (ws_tx, ws_rx) = std::sync::mpsc::channel::<ws_host::HostNetEvent>();
     let mut ws_manager = ws_host::WsManager::new(ws_tx);
+    let mut next_profile_write = HEADLESS_PROFILE_FRAMES;
+    let mut capture_due = false;
 
     loop {
         let elapsed = start_time.elapsed().as_secs_f32();
@@ -1454,15 +1487,66 @@ fn run_headless(
             }
             input_edges.clear();
             game.tick(sub.clone());
+            if capture.at_frame == Some(frame_count) {
+                capture_due = true;
+            }
             frame_count += 1;
+            // A breakpoint hit replaying this frame pauses the clock on it,
+            // the same pin `POST /time` sets; `continue` resumes it.
+            if game.debug_frame() {
+                clock.pause();
+                break;
+            }
         }
         dispatch_net_ws(&mut *game, &net_tx, &http_client, &mut ws_manager, net_transport);
+        if let Some(path) = profile.filter(|_| frame_count >= next_profile_write) {
+            if let Err(message) = write_profile(&*game, path) {
+                eprintln!("error: {message}");
+            }
+            next_profile_write = frame_count + HEADLESS_PROFILE_FRAMES;
+        }
 
         // The frame is pure data (no GL); it powers GET /scene. Drain and drop
         // audio commands so they do not pile up. Preloads and physics terrain
         // requests are driven above through the CPU-only asset cache.
         let frame = game.render(time.clone());
         let _ = game.audio_drain_commands();
+        let render_png = |width: u32, height: u32| {
+            software_renderer::capture_png(
+                asset_cache.clone(),
+                &scene_context,
+                &frame,
+                time.clone(),
+                width,
+                height,
+                capture.debug_render_mode,
+            )
+        };
+
+        if let Some(path) = &capture.frame {
+            // The same triggers as the windowed loop's --capture-frame.
+            let shoot = match capture.at_frame {
+                Some(_) => capture_due,
+                None => elapsed >= capture.time,
+            };
+            if shoot {
+                let (width, height) = capture.size;
+                let result = render_png(width, height)
+                    .and_then(|bytes| std::fs::write(path, bytes).map_err(|e| e.to_string()));
+                match result {
+                    Ok(()) => functor_runtime_common::events::emit(
+                        functor_runtime_common::events::RuntimeEvent::CaptureWritten {
+                            path: path.clone(),
+                        },
+                    ),
+                    Err(e) => {
+                        eprintln!("Failed to capture frame to {}: {}", path, e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+        }
 
         if let Some(rx) = &debug_requests {
             while let Ok(req) = rx.try_recv() {
@@ -1503,9 +1587,8 @@ fn run_headless(
                     None, // no webview overlay in headless
                     net_transport,
                     &|| {
-                        Err(debug_server::CaptureError::Unavailable(
-                            "capture is unavailable in --headless mode".to_string(),
-                        ))
+                        let (width, height) = capture.size;
+                        render_png(width, height).map_err(debug_server::CaptureError::Failed)
                     },
                 );
             }
@@ -1516,6 +1599,29 @@ fn run_headless(
     }
 }
 
+/// `--capture-frame` and `/capture` settings for the headless loop, which
+/// renders them in software.
+struct HeadlessCapture {
+    frame: Option<String>,
+    time: f32,
+    at_frame: Option<u64>,
+    /// `--capture-size`, else the default window size.
+    size: (u32, u32),
+    debug_render_mode: functor_runtime_common::DebugRenderMode,
+}
+
+/// How often (in frames) a `--headless --profile` run rewrites its profile:
+/// the headless loop has no exit of its own, so a killed run still leaves
+/// a recent one behind.
+const HEADLESS_PROFILE_FRAMES: u64 = 600;
+
+/// Write `--profile`'s file in the format its extension names.
+fn write_profile(game: &dyn Game, path: &str) -> Result<(), String> {
+    let format = functor_lang::profile::ProfileFormat::for_path(std::path::Path::new(path));
+    let profile = game.profile(format)?;
+    std::fs::write(path, profile).map_err(|e| format!("cannot write the profile to {path}: {e}"))
+}
+
 /// Run the desktop runtime to completion (windowed loop until the window
 /// closes / a `--capture-frame` shot is taken, or the headless loop until the
 /// process is killed).
@@ -1591,6 +1697,13 @@ then restart the runner"
         std::process::exit(1);
     }
 
+    if args.profile.is_some() {
+        if let Err(message) = game.start_profiling() {
+            eprintln!("error: --profile: {message}");
+            std::process::exit(1);
+        }
+    }
+
     // The game loaded and validated (incl. any scripted-input parse); the runtime
     // is up. One-shot lifecycle notice for the shell (replaces the old
     // game-path/working-dir debug prints).
@@ -1621,12 +1734,6 @@ sockets)."
 
     // Headless: drive the game + debug server with no GL window, and return.
     if args.headless {
-        if args.capture_frame.is_some() {
-            eprintln!(
-                "error: --capture-frame is not supported with --headless (no GL to read back)"
-            );
-            std::process::exit(1);
-        }
         if args.xreal_tracking {
             eprintln!(
                 "warning: --xreal-tracking has no effect in --headless mode (no view to rotate)"
@@ -1640,6 +1747,14 @@ sockets)."
             input_script,
             args.script_dt,
             args.net_transport,
+            args.profile.as_deref(),
+            HeadlessCapture {
+                frame: args.capture_frame.clone(),
+                time: args.capture_time,
+                at_frame: args.capture_at_frame,
+                size: args.capture_size.unwrap_or((SCR_WIDTH, SCR_HEIGHT)),
+                debug_render_mode: args.debug_render.into(),
+            },
         );
         return;
     }
@@ -2764,6 +2879,10 @@ Escape again to quit"
                     capture_due = true;
                 }
                 frame_count += 1;
+                if game.debug_frame() {
+                    clock.pause();
+                    break;
+                }
             }
 
             // Perform the HTTP + WebSocket commands this frame's tick queued
@@ -3638,6 +3757,13 @@ Escape again to quit"
         }
     }
 
+    // A failed write is reported, not fatal — the run itself went fine.
+    if let Some(path) = &args.profile {
+        match write_profile(&*game, path) {
+            Ok(()) => eprintln!("[runtime] profile written to {path}"),
+            Err(message) => eprintln!("error: {message}"),
+        }
+    }
     game.quit();
 }
 
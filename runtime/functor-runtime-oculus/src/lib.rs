@@ -836,6 +836,16 @@ its networking is real sockets"
                     .to_string(),
             ));
         }
+        DebugRequest::Profile(format, response) => {
+            let _ = response.send(game.start_profiling().and_then(|()| game.profile(format)));
+        }
+        // The source-level debugger replays frames through the desktop
+        // producer's journal; the device's embedded producer keeps none.
+        DebugRequest::Debug(_, response) => {
+            let _ = response.send(Err(
+                "the source-level debugger is not available on the device runtime".to_string(),
+            ));
+        }
     }
 }
 
@@ -1,7 +1,8 @@
 // Live language intelligence for the sandbox and IDE editors: loads the small
 // functor-lang analysis wasm (built by `npm run build:lang-wasm`, copied to
 // dist/pkg/ by build.mjs) and turns its type diagnostics into CodeMirror lint
-// underlines, plus hover types, inlay hints, and signature codelenses.
+// underlines, plus hover types, inlay hints, signature codelenses, parameter
+// hints inside calls, and an outline panel.
 // Both editor pages register a context provider (setLangContext) so every pass runs
 // over the whole file set with sibling modules resolved; without one (the hero
 // and demo editors) a pass analyzes the single buffer.
@@ -26,9 +27,11 @@ import {
   WidgetType,
   gutter,
   hoverTooltip,
+  keymap,
+  showPanel,
   showTooltip,
 } from "@codemirror/view";
-import type { DecorationSet, Tooltip, ViewUpdate } from "@codemirror/view";
+import type { DecorationSet, Panel, Tooltip, ViewUpdate } from "@codemirror/view";
 import { RangeSet, StateEffect, StateField } from "@codemirror/state";
 import type { EditorState, Extension, Range, Text } from "@codemirror/state";
 import type { CompletionContext } from "@codemirror/autocomplete";
@@ -54,6 +57,13 @@ type CompleteFn = (src: string, offset: number) => string;
 type CompleteProjectFn = (filesJson: string, active: string, offset: number) => string;
 type ExpectsProjectFn = (filesJson: string, active: string, budget: number) => string;
 type ResetFn = () => void;
+type FormatFn = (src: string) => string;
+type SignatureHelpFn = (src: string, offset: number) => string;
+type SignatureHelpProjectFn = (filesJson: string, active: string, offset: number) => string;
+type SymbolsFn = (src: string, isInterface: boolean) => string;
+type WorkspaceSymbolsFn = (filesJson: string, query: string) => string;
+type SemanticTokensFn = (src: string) => string;
+type SemanticTokensProjectFn = (filesJson: string, active: string) => string;
 
 /**
  * The wasm-bindgen glue's exports, as this module uses them. Everything but
@@ -71,6 +81,13 @@ interface LangWasm {
   functor_lang_complete_project?: CompleteProjectFn;
   functor_lang_expects_project?: ExpectsProjectFn;
   functor_lang_reset?: ResetFn;
+  functor_lang_format?: FormatFn;
+  functor_lang_signature_help?: SignatureHelpFn;
+  functor_lang_signature_help_project?: SignatureHelpProjectFn;
+  functor_lang_symbols?: SymbolsFn;
+  functor_lang_workspace_symbols?: WorkspaceSymbolsFn;
+  functor_lang_semantic_tokens?: SemanticTokensFn;
+  functor_lang_semantic_tokens_project?: SemanticTokensProjectFn;
 }
 
 /** One `analyze` diagnostic. The wasm only ever emits `"error"`. */
@@ -116,6 +133,71 @@ interface CompletionResponse {
   items: CompletionItem[];
 }
 
+/**
+ * The parsed `format` payload: the whole canonical buffer, or the parse error
+ * that stopped it (UTF-16 offsets; the lint pass already shows it).
+ */
+type FormatResult =
+  | { text: string }
+  | { error: { from: number; to: number; message: string } };
+
+/**
+ * The parsed `signature_help` payload (`""` — no call — never parses). `params`
+ * are UTF-16 ranges within `label`; `piped` is the one a `|>` supplies.
+ */
+interface SignatureHelp {
+  label: string;
+  params: [number, number][];
+  active: number | null;
+  piped: number | null;
+  doc: string | null;
+}
+
+type SymbolKind =
+  | "module"
+  | "function"
+  | "value"
+  | "record"
+  | "variant"
+  | "type"
+  | "constructor"
+  | "field"
+  | "test";
+
+/** One outline entry from `symbols`, UTF-16 offsets into the buffer. */
+interface OutlineSymbol {
+  name: string;
+  detail: string | null;
+  kind: SymbolKind;
+  from: number;
+  to: number;
+  nameFrom: number;
+  nameTo: number;
+  children: OutlineSymbol[];
+}
+
+/** One `workspace_symbols` match; `from`/`to` are UTF-16 in `path`'s source. */
+interface WorkspaceSymbol {
+  name: string;
+  kind: SymbolKind;
+  container: string;
+  path: string;
+  from: number;
+  to: number;
+}
+
+/**
+ * One `semantic_tokens` entry: a UTF-16 range, its LSP legend type
+ * (`"enumMember"`, `"function"`, `"unit"`, …) and modifiers
+ * (`"defaultLibrary"`, `"host"`, `"mutable"`, …).
+ */
+interface SemanticToken {
+  from: number;
+  to: number;
+  type: string;
+  modifiers: string[];
+}
+
 /** The states `expects_project` reports; `running` is this module's own. */
 type ExpectState = "pass" | "fail" | "error" | "unrunnable";
 type MarkerState = ExpectState | "running";
@@ -143,6 +225,17 @@ let hoverProjectFn: HoverProjectFn | null = null;
 let completeProjectFn: CompleteProjectFn | null = null;
 let resetFn: ResetFn | null = null; // clears the wasm completion cache — optional export
 let expectsProjectFn: ExpectsProjectFn | null = null; // optional export
+let formatFn: FormatFn | null = null; // optional export
+// Parameter hints and the outline are optional as a unit each: the single-file
+// and `_project` signature help arrive together, so a null check on the first
+// proves the second (the `!` below).
+let signatureHelpFn: SignatureHelpFn | null = null;
+let signatureHelpProjectFn: SignatureHelpProjectFn | null = null;
+let symbolsFn: SymbolsFn | null = null;
+let workspaceSymbolsFn: WorkspaceSymbolsFn | null = null;
+// Semantic highlighting, optional as a unit like parameter hints.
+let semanticTokensFn: SemanticTokensFn | null = null;
+let semanticTokensProjectFn: SemanticTokensProjectFn | null = null;
 let lastKey: string | null = null;
 let lastResult: AnalyzeResult | null = null;
 let lastExpectKey: string | null = null;
@@ -403,6 +496,234 @@ export const showHoverAtCursor = (view: EditorView): boolean => {
   return tooltip !== null;
 };
 
+// --- Format ---------------------------------------------------------------------
+// `functor fmt` in the editor (Shift-Alt-F, VS Code's binding): replace the
+// buffer with the wasm's canonical layout as ONE transaction, so a single undo
+// restores it. An unparseable buffer is left alone.
+
+/** Format the whole document. Returns false when formatting is unavailable. */
+export const formatDocument = (view: EditorView): boolean => {
+  if (!formatFn) return false;
+  const doc = view.state.doc.toString();
+  let result: FormatResult;
+  try {
+    result = JSON.parse(formatFn(doc));
+  } catch {
+    return false;
+  }
+  if ("text" in result && result.text !== doc) {
+    view.dispatch({
+      changes: { from: 0, to: doc.length, insert: result.text },
+      userEvent: "format",
+    });
+  }
+  return true;
+};
+
+// --- Parameter hints -------------------------------------------------------------
+// While the caret is inside a call, a tooltip above it shows the callee's
+// signature with the argument being typed highlighted (and the one a `|>`
+// supplies dimmed). Recomputed on every edit or caret move — one wasm call,
+// answered off the same last-good cache as completion, since the call being
+// typed never parses.
+
+const signatureAt = (state: EditorState): SignatureHelp | null => {
+  if (!signatureHelpFn) return null;
+  const pos = state.selection.main.head;
+  try {
+    const doc = state.doc.toString();
+    const args = projectArgs(doc);
+    const out = args
+      ? signatureHelpProjectFn!(args.filesJson, args.active, pos)
+      : signatureHelpFn(doc, pos);
+    return out ? JSON.parse(out) : null;
+  } catch {
+    return null;
+  }
+};
+
+const signatureTooltip = (state: EditorState): Tooltip | null => {
+  if (!state.selection.main.empty) return null;
+  const help = signatureAt(state);
+  if (!help) return null;
+  return {
+    pos: state.selection.main.head,
+    above: true,
+    create: () => {
+      const dom = document.createElement("div");
+      dom.className = "cm-signature-help";
+      const line = document.createElement("div");
+      let at = 0;
+      help.params.forEach(([from, to], i) => {
+        line.append(help.label.slice(at, from));
+        const param = document.createElement("span");
+        param.textContent = help.label.slice(from, to);
+        if (i === help.active) param.className = "cm-signature-active";
+        else if (i === help.piped) param.className = "cm-signature-piped";
+        line.append(param);
+        at = to;
+      });
+      line.append(help.label.slice(at));
+      dom.appendChild(line);
+      if (help.doc) {
+        const doc = document.createElement("div");
+        doc.className = "cm-signature-doc";
+        doc.textContent = help.doc;
+        dom.appendChild(doc);
+      }
+      return { dom };
+    },
+  };
+};
+
+const signatureHints = StateField.define<Tooltip | null>({
+  create: () => null,
+  update(value, tr) {
+    return tr.docChanged || tr.selection ? signatureTooltip(tr.state) : value;
+  },
+  provide: (field) => showTooltip.from(field),
+});
+
+// --- Outline -------------------------------------------------------------------
+// The buffer's lets, types (with their constructors and fields), inline
+// modules, and expects as a panel below the editor (Mod-Shift-O toggles it);
+// clicking an entry jumps to its name. An unparseable buffer keeps the last
+// outline rather than flashing empty mid-edit.
+
+// The raw outline for `src` — the test/introspection seam. Null when the wasm
+// (or its `symbols` export) isn't loaded or the buffer doesn't parse.
+export const outlineOf = (src: string, isInterface = false): OutlineSymbol[] | null => {
+  if (!symbolsFn) return null;
+  try {
+    return JSON.parse(symbolsFn(src, isInterface)).symbols;
+  } catch {
+    return null;
+  }
+};
+
+// Declarations matching `query` across the host's whole file set (the
+// registered context), or null in single-file mode / without the export.
+export const workspaceSymbols = (query: string): WorkspaceSymbol[] | null => {
+  if (!workspaceSymbolsFn || !contextFn) return null;
+  const { files } = contextFn() ?? ({} as Partial<LangContext>);
+  if (!Array.isArray(files)) return null;
+  try {
+    return JSON.parse(workspaceSymbolsFn(JSON.stringify(files), query)).symbols;
+  } catch {
+    return null;
+  }
+};
+
+const toggleOutline = StateEffect.define<void>();
+
+const outlineOpen = StateField.define<boolean>({
+  create: () => false,
+  update(open, tr) {
+    for (const effect of tr.effects) if (effect.is(toggleOutline)) open = !open;
+    return open;
+  },
+  provide: (field) => showPanel.from(field, (open) => (open ? outlinePanel : null)),
+});
+
+/** Show or hide the outline panel. Returns false when the outline is unavailable. */
+export const toggleOutlinePanel = (view: EditorView): boolean => {
+  if (!symbolsFn) return false;
+  view.dispatch({ effects: toggleOutline.of() });
+  return true;
+};
+
+const outlinePanel = (view: EditorView): Panel => {
+  const dom = document.createElement("div");
+  dom.className = "cm-outline";
+  const render = (state: EditorState) => {
+    const args = projectArgs(state.doc.toString());
+    const symbols = outlineOf(state.doc.toString(), !!args && args.active.endsWith(".funi"));
+    if (!symbols) return; // keep the last good outline
+    const list = document.createElement("ul");
+    const add = (items: OutlineSymbol[], depth: number) => {
+      for (const symbol of items) {
+        const row = document.createElement("li");
+        row.className = `cm-outline-${symbol.kind}`;
+        row.style.paddingLeft = `${0.5 + depth}em`;
+        row.textContent = symbol.name;
+        if (symbol.detail) {
+          const detail = document.createElement("span");
+          detail.className = "cm-outline-detail";
+          detail.textContent = ` ${symbol.detail}`;
+          row.appendChild(detail);
+        }
+        row.onclick = () => {
+          const len = view.state.doc.length;
+          const anchor = Math.min(symbol.nameFrom, len);
+          view.dispatch({
+            selection: { anchor, head: Math.min(symbol.nameTo, len) },
+            scrollIntoView: true,
+          });
+          view.focus();
+        };
+        list.appendChild(row);
+        add(symbol.children, depth + 1);
+      }
+    };
+    add(symbols, 0);
+    dom.replaceChildren(list);
+  };
+  render(view.state);
+  return {
+    dom,
+    update: (update: ViewUpdate) => {
+      if (update.docChanged) render(update.state);
+    },
+  };
+};
+
+// --- Semantic highlighting -------------------------------------------------------
+// The checker's view of each identifier — constructor, host value, builtin,
+// type parameter, unit suffix, assigned binding — as `cm-sem-<type>` marks
+// (plus `cm-sem-<modifier>` per modifier) over the grammar's highlighting.
+// Re-derived with the other decorations after each lint pass; a buffer that
+// doesn't load keeps the previous marks, mapped through the edits.
+
+// The raw tokens for `src` — the test/introspection seam. Null when the wasm
+// (or its export) isn't loaded or the buffer doesn't load.
+export const semanticTokensOf = (src: string): SemanticToken[] | null => {
+  if (!semanticTokensFn) return null;
+  try {
+    const args = projectArgs(src);
+    return JSON.parse(
+      args ? semanticTokensProjectFn!(args.filesJson, args.active) : semanticTokensFn(src)
+    ).tokens;
+  } catch {
+    return null;
+  }
+};
+
+const buildSemantic = (state: EditorState): DecorationSet | null => {
+  const tokens = semanticTokensOf(state.doc.toString());
+  if (!tokens) return null;
+  const len = state.doc.length;
+  const marks = tokens
+    .filter((token) => token.from < token.to && token.to <= len)
+    .map((token) => {
+      const classes = [token.type, ...token.modifiers].map((name) => `cm-sem-${name}`);
+      return Decoration.mark({ class: classes.join(" ") }).range(token.from, token.to);
+    });
+  return Decoration.set(marks, true);
+};
+
+const semanticField = StateField.define<DecorationSet>({
+  create: (state) => buildSemantic(state) || Decoration.none,
+  update(value, tr) {
+    let marks = tr.docChanged ? value.map(tr.changes) : value;
+    for (const effect of tr.effects) {
+      if (effect.is(refreshDecorations)) marks = buildSemantic(tr.state) || marks;
+      if (effect.is(clearDecorations)) marks = Decoration.none;
+    }
+    return marks;
+  },
+  provide: (f) => EditorView.decorations.from(f),
+});
+
 // --- Autocomplete -------------------------------------------------------------
 // A CodeMirror completion source backed by the wasm's scope-aware `complete`.
 // Registered via the language's `data` facet (below), so basicSetup's
@@ -1504,6 +1825,58 @@ const intelTheme = EditorView.theme({
     opacity: "0.9",
     marginRight: "0.4em",
   },
+  // Parameter hints: the hover panel's look, the active argument in the accent.
+  ".cm-signature-help": {
+    fontFamily: mono,
+    fontSize: "12.5px",
+    color: "#e9e6f2",
+    padding: "3px 7px",
+  },
+  ".cm-signature-active": {
+    color: "#c7f2f7",
+    fontWeight: "bold",
+    textDecoration: "underline",
+  },
+  ".cm-signature-piped": {
+    color: "#6c6685",
+  },
+  ".cm-signature-doc": {
+    color: "#9b94b3",
+    fontStyle: "italic",
+    marginTop: "2px",
+  },
+  // The outline panel: a short scrolling list under the editor.
+  ".cm-outline": {
+    backgroundColor: "#1e1833",
+    borderTop: "1px solid #2b2542",
+    maxHeight: "12em",
+    overflowY: "auto",
+  },
+  ".cm-outline ul": {
+    fontFamily: mono,
+    fontSize: "12.5px",
+    listStyle: "none",
+    margin: "0",
+    padding: "2px 0",
+  },
+  ".cm-outline li": {
+    color: "#e9e6f2",
+    cursor: "pointer",
+  },
+  ".cm-outline li:hover": {
+    backgroundColor: "#0e3b46",
+  },
+  ".cm-outline-detail": {
+    color: "#6c6685",
+    fontStyle: "italic",
+  },
+  // Semantic highlighting: only what the grammar can't tell apart.
+  ".cm-sem-enumMember": { color: "#f7c873" },
+  ".cm-sem-typeParameter": { color: "#7fd4c1", fontStyle: "italic" },
+  ".cm-sem-unit": { color: "#c7f2f7", fontStyle: "italic" },
+  ".cm-sem-host": { color: "#9fb7ff" },
+  ".cm-sem-defaultLibrary": { color: "#b9a3f7" },
+  ".cm-sem-mutable": { textDecoration: "underline dotted #6c6685" },
 });
 
 // Async setup: resolve to the full intel extension set, or [] on any failure so
@@ -1538,6 +1911,26 @@ export const setupLangIntel = async (): Promise<Extension[]> => {
       typeof mod.functor_lang_expects_project === "function"
         ? mod.functor_lang_expects_project
         : null;
+    formatFn = typeof mod.functor_lang_format === "function" ? mod.functor_lang_format : null;
+    if (
+      typeof mod.functor_lang_signature_help === "function" &&
+      typeof mod.functor_lang_signature_help_project === "function"
+    ) {
+      signatureHelpFn = mod.functor_lang_signature_help;
+      signatureHelpProjectFn = mod.functor_lang_signature_help_project;
+    }
+    symbolsFn = typeof mod.functor_lang_symbols === "function" ? mod.functor_lang_symbols : null;
+    workspaceSymbolsFn =
+      typeof mod.functor_lang_workspace_symbols === "function"
+        ? mod.functor_lang_workspace_symbols
+        : null;
+    if (
+      typeof mod.functor_lang_semantic_tokens === "function" &&
+      typeof mod.functor_lang_semantic_tokens_project === "function"
+    ) {
+      semanticTokensFn = mod.functor_lang_semantic_tokens;
+      semanticTokensProjectFn = mod.functor_lang_semantic_tokens_project;
+    }
   } catch {
     console.info(
       "[lang-intel] language analysis unavailable (pkg not built) — editor runs without diagnostics"
@@ -1549,12 +1942,19 @@ export const setupLangIntel = async (): Promise<Extension[]> => {
     hoverTypes,
     cursorHover,
     decorationField,
+    semanticField,
     liveField,
     coverageField,
     coverageGutter,
     expectField,
     expectGutter,
     initialRefresh,
+    keymap.of([
+      { key: "Shift-Alt-f", run: formatDocument },
+      { key: "Mod-Shift-o", run: toggleOutlinePanel },
+    ]),
+    signatureHints,
+    outlineOpen,
     // Register the completion source on the language's data facet — basicSetup's
     // autocompletion() picks it up via languageDataAt (no second popup).
     functorLangLanguage.data.of({ autocomplete: functorCompletions }),
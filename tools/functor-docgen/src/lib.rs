@@ -64,6 +64,7 @@ const CATEGORIES: &[(ApiGroup, &str, &[&str])] = &[
             "Skybox",
             "Texture",
             "Fog",
+            "Post",
             "RenderTarget",
         ],
     ),
@@ -86,7 +87,7 @@ const CATEGORIES: &[(ApiGroup, &str, &[&str])] = &[
     (ApiGroup::Engine, "Audio", &["AudioScene", "AudioSource"]),
     (ApiGroup::Engine, "UI", &["Ui", "Html", "Attr", "Style"]),
     (ApiGroup::Engine, "Assets", &["Asset"]),
-    (ApiGroup::Stdlib, "Collections", &["List", "Map"]),
+    (ApiGroup::Stdlib, "Collections", &["List", "Array", "Map", "Set"]),
     (ApiGroup::Stdlib, "Text", &["Text"]),
     (
         ApiGroup::Stdlib,
@@ -674,8 +675,8 @@ mod tests {
             let items: usize = modules.iter().map(|module| module.items.len()).sum();
             (modules.len(), items)
         };
-        assert_eq!(count(ApiGroup::Engine), (29, 325));
-        assert_eq!(count(ApiGroup::Stdlib), (10, 97));
+        assert_eq!(count(ApiGroup::Engine), (30, 349));
+        assert_eq!(count(ApiGroup::Stdlib), (12, 116));
         assert!(reference
             .modules
             .iter()
@@ -709,8 +710,8 @@ mod tests {
         assert_eq!(
             stdlib,
             [
-                "List", "Map", "Text", "Math", "Random", "Option", "Result", "Key", "Mouse",
-                "Debug"
+                "List", "Array", "Map", "Set", "Text", "Math", "Random", "Option", "Result",
+                "Key", "Mouse", "Debug"
             ]
         );
     }
@@ -0,0 +1,599 @@
+//! `functor-lang-dap` — a Debug Adapter Protocol server for `.fun` programs
+//! (docs/functor-lang.md Track D), over the same hand-rolled stdio framing
+//! as `functor-lang-lsp`.
+//!
+//! Two ways to debug:
+//!
+//! - **`launch {program, stopOnEntry}`** runs the program the way
+//!   `functor-lang run` does — hostless, `main` called when defined — under
+//!   [`functor_lang::debug::Debugger`], in this process.
+//! - **`attach {port, projectRoot}`** drives a running game's debugger over
+//!   the debug protocol's `POST /debug` (default port 8077, `functor
+//!   develop`'s). The game replays each frame under the breakpoints, and a
+//!   hit pauses its frame loop the way `POST /time` does; a poll of the
+//!   `status` command turns that into a `stopped` event here. The game
+//!   reports sources by file name, resolved against `projectRoot`.
+//!
+//! There is one thread (`id` 1). Line breakpoints with conditions, step
+//! in/over/out, scopes and variables rendered from values, and
+//! evaluate-in-frame all work in both modes; `Debug.log` lines from a launch
+//! arrive as `output` events.
+
+mod framing;
+
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+
+use functor_lang::debug::{Breakpoint, Debugger, Outcome, Run, Step, StopReason};
+use functor_lang::eval::DebugHook;
+use serde_json::{json, Value};
+
+use framing::{read_message, write_message};
+
+/// The only thread: a program runs on one.
+const THREAD_ID: i64 = 1;
+
+/// The debug protocol's default port — `functor develop`'s — mirrored from
+/// the runtime's `DEFAULT_DEVELOP_PORT`, which this crate does not link.
+const DEFAULT_DEVELOP_PORT: u16 = 8077;
+
+/// `Debug.log` lines of the current launch run (see [`Launch::run`]).
+static LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// A message arriving on the multiplexed channel: a framed request from the
+/// client, a `status` reply from the attach poll, or the client hitting EOF.
+enum Incoming {
+    Msg(Value),
+    Status(Value),
+    Eof,
+}
+
+fn main() {
+    let (tx, rx) = std::sync::mpsc::channel::<Incoming>();
+    let stdin_tx = tx.clone();
+    std::thread::spawn(move || {
+        let stdin = std::io::stdin();
+        let mut reader = BufReader::new(stdin.lock());
+        while let Some(message) = read_message(&mut reader) {
+            if stdin_tx.send(Incoming::Msg(message)).is_err() {
+                return;
+            }
+        }
+        let _ = stdin_tx.send(Incoming::Eof);
+    });
+    // stdout is the protocol: a launched program's `Debug.log` must not print
+    // to it.
+    functor_lang::set_trace_sink(Box::new(|line| LOG.lock().unwrap().push(line)));
+
+    let stdout = std::io::stdout();
+    let mut adapter = Adapter {
+        writer: stdout.lock(),
+        seq: 0,
+        mode: None,
+        tx,
+    };
+    while let Ok(incoming) = rx.recv() {
+        match incoming {
+            Incoming::Msg(message) => {
+                if !adapter.request(&message) {
+                    break;
+                }
+            }
+            Incoming::Status(status) => adapter.observe(&status),
+            Incoming::Eof => break,
+        }
+    }
+    if let Some(Mode::Attach(attach)) = &adapter.mode {
+        attach.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+/// What the session debugs, once `launch` or `attach` arrived.
+enum Mode {
+    Launch(Box<Launch>),
+    Attach(Attach),
+}
+
+struct Launch {
+    debugger: Debugger,
+    stop_on_entry: bool,
+    /// `Debug.log` lines already shown: a resume replays the run, and its
+    /// first lines are the ones the last run printed.
+    shown: usize,
+}
+
+struct Attach {
+    addr: String,
+    /// Where the game's file names resolve, for the editor to open them.
+    root: PathBuf,
+    /// The runtime's stop count last reported as a `stopped` event.
+    stops: u64,
+    stop: Arc<AtomicBool>,
+}
+
+struct Adapter<W: Write> {
+    writer: W,
+    seq: i64,
+    mode: Option<Mode>,
+    tx: Sender<Incoming>,
+}
+
+impl<W: Write> Adapter<W> {
+    /// Answer one client request. False once the session is over.
+    fn request(&mut self, message: &Value) -> bool {
+        if message["type"] != "request" {
+            return true;
+        }
+        let command = message["command"].as_str().unwrap_or_default().to_string();
+        let arguments = &message["arguments"];
+        let result = match command.as_str() {
+            "initialize" => {
+                let capabilities = json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsConditionalBreakpoints": true,
+                    "supportsEvaluateForHovers": true,
+                    "exceptionBreakpointFilters": [{
+                        "filter": "error",
+                        "label": "Runtime errors",
+                        "default": true,
+                    }],
+                });
+                self.respond(message, Ok(capabilities));
+                self.event("initialized", json!({}));
+                return true;
+            }
+            "launch" => self.launch(arguments),
+            "attach" => self.attach(arguments),
+            "configurationDone" => {
+                self.respond(message, Ok(json!({})));
+                if let Some(Mode::Launch(launch)) = &mut self.mode {
+                    let stop_on_entry = launch.stop_on_entry;
+                    let outcome = launch.run(|debugger, run| debugger.start(stop_on_entry, run));
+                    self.outcome(outcome);
+                }
+                return true;
+            }
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })),
+            "continue" | "next" | "stepIn" | "stepOut" => {
+                let step = match command.as_str() {
+                    "continue" => Step::Continue,
+                    "next" => Step::Over,
+                    "stepIn" => Step::In,
+                    _ => Step::Out,
+                };
+                return self.resume(message, step);
+            }
+            "pause" => match &self.mode {
+                // A launch runs to its next stop before answering anything,
+                // so there is never a running program to pause.
+                Some(Mode::Launch(_)) => Ok(json!({})),
+                _ => {
+                    let status = self.forward(message);
+                    self.respond(
+                        message,
+                        status.as_ref().map(|_| json!({})).map_err(Clone::clone),
+                    );
+                    if let Ok(status) = status {
+                        self.observe(&status);
+                    }
+                    return true;
+                }
+            },
+            "disconnect" => {
+                if let Some(Mode::Attach(attach)) = &self.mode {
+                    attach.stop.store(true, Ordering::SeqCst);
+                    let _ = post_debug(&attach.addr, message);
+                }
+                self.respond(message, Ok(json!({})));
+                return false;
+            }
+            _ => match &mut self.mode {
+                Some(Mode::Launch(launch)) => launch.request(&command, arguments),
+                Some(Mode::Attach(attach)) => {
+                    let root = attach.root.clone();
+                    let reply = post_debug(&attach.addr, message);
+                    reply.map(|mut body| {
+                        resolve_sources(&mut body, &root);
+                        body
+                    })
+                }
+                None => Err(format!("`{command}` before launch or attach")),
+            },
+        };
+        self.respond(message, result);
+        true
+    }
+
+    fn launch(&mut self, arguments: &Value) -> Result<Value, String> {
+        let program = arguments["program"]
+            .as_str()
+            .ok_or("launch needs a `program` (a .fun file)")?;
+        let project = functor_lang::project::load(Path::new(program)).map_err(|error| {
+            format!(
+                "{}:{}:{}: error: {}",
+                error.path.display(),
+                error.line,
+                error.col,
+                error.message
+            )
+        })?;
+        self.mode = Some(Mode::Launch(Box::new(Launch {
+            debugger: Debugger::new(project),
+            stop_on_entry: arguments["stopOnEntry"].as_bool().unwrap_or(false),
+            shown: 0,
+        })));
+        Ok(json!({}))
+    }
+
+    fn attach(&mut self, arguments: &Value) -> Result<Value, String> {
+        let port = arguments["port"]
+            .as_u64()
+            .unwrap_or(DEFAULT_DEVELOP_PORT as u64);
+        let addr = format!("127.0.0.1:{port}");
+        let status = post_debug(&addr, &json!({ "command": "status" })).map_err(|error| {
+            format!("no Functor runtime with a source-level debugger on {addr}: {error}")
+        })?;
+        let root = arguments["projectRoot"]
+            .as_str()
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        let stop = Arc::new(AtomicBool::new(false));
+        let (poll_addr, poll_stop, tx) = (addr.clone(), stop.clone(), self.tx.clone());
+        std::thread::spawn(move || poll_status(&poll_addr, tx, poll_stop));
+        self.mode = Some(Mode::Attach(Attach {
+            addr,
+            root,
+            // A game already stopped when we attach is a stop to show.
+            stops: status["stops"].as_u64().unwrap_or(0).saturating_sub(1),
+            stop,
+        }));
+        Ok(json!({}))
+    }
+
+    /// `continue` and the steps: answered at once, then the run goes on.
+    fn resume(&mut self, message: &Value, step: Step) -> bool {
+        match &mut self.mode {
+            Some(Mode::Launch(launch)) => {
+                if launch.debugger.stopped().is_none() {
+                    self.respond(message, Err("not stopped".to_string()));
+                    return true;
+                }
+                self.respond(message, Ok(json!({ "allThreadsContinued": true })));
+                let Some(Mode::Launch(launch)) = &mut self.mode else {
+                    unreachable!("matched above");
+                };
+                let outcome = launch.run(|debugger, run| debugger.resume(step, run));
+                self.outcome(outcome);
+            }
+            Some(Mode::Attach(_)) => {
+                let status = self.forward(message);
+                let body = status
+                    .as_ref()
+                    .map(|_| json!({ "allThreadsContinued": true }))
+                    .map_err(Clone::clone);
+                self.respond(message, body);
+                if let Ok(status) = status {
+                    self.observe(&status);
+                }
+            }
+            None => self.respond(message, Err("not launched or attached".to_string())),
+        }
+        true
+    }
+
+    /// Forward a request to the attached runtime.
+    fn forward(&self, message: &Value) -> Result<Value, String> {
+        match &self.mode {
+            Some(Mode::Attach(attach)) => post_debug(&attach.addr, message),
+            _ => Err("not attached".to_string()),
+        }
+    }
+
+    /// An attached runtime's `status`: a stop not yet shown becomes a
+    /// `stopped` event.
+    fn observe(&mut self, status: &Value) {
+        let Some(Mode::Attach(attach)) = &mut self.mode else {
+            return;
+        };
+        let stops = status["stops"].as_u64().unwrap_or(0);
+        let stopped = &status["stopped"];
+        if stops <= attach.stops || stopped.is_null() {
+            return;
+        }
+        attach.stops = stops;
+        let body = json!({
+            "reason": stopped["reason"],
+            "description": stopped["description"],
+            "text": stopped["text"],
+            "threadId": THREAD_ID,
+            "allThreadsStopped": true,
+        });
+        self.event("stopped", body);
+    }
+
+    /// Report a launch run's outcome: a stop, or the program's end.
+    fn outcome(&mut self, (outcome, output): (Outcome, Vec<String>)) {
+        for line in output {
+            self.output("stdout", format!("{line}\n"));
+        }
+        match outcome {
+            Outcome::Stopped(reason) => {
+                let (reason, text) = match reason {
+                    StopReason::Entry => ("entry", None),
+                    StopReason::Step => ("step", None),
+                    StopReason::Breakpoint => ("breakpoint", None),
+                    StopReason::Condition(message) => ("breakpoint", Some(message)),
+                    StopReason::Error(message) => ("exception", Some(message)),
+                };
+                let body = json!({
+                    "reason": reason,
+                    "text": text,
+                    "threadId": THREAD_ID,
+                    "allThreadsStopped": true,
+                });
+                self.event("stopped", body);
+            }
+            Outcome::Finished(result) => {
+                let exit_code = match result {
+                    Ok(value) => {
+                        if let Some(value) = value {
+                            self.output("stdout", format!("{value}\n"));
+                        }
+                        0
+                    }
+                    Err(error) => {
+                        let at = match &self.mode {
+                            Some(Mode::Launch(launch)) => {
+                                let (path, line, col) = launch.debugger.locate(error.span.start);
+                                format!("{}:{line}:{col}: ", path.display())
+                            }
+                            _ => String::new(),
+                        };
+                        self.output("stderr", format!("{at}error: {}\n", error.message));
+                        1
+                    }
+                };
+                self.event("exited", json!({ "exitCode": exit_code }));
+                self.event("terminated", json!({}));
+            }
+        }
+    }
+
+    fn output(&mut self, category: &str, output: String) {
+        self.event("output", json!({ "category": category, "output": output }));
+    }
+
+    fn respond(&mut self, request: &Value, result: Result<Value, String>) {
+        self.seq += 1;
+        let mut response = json!({
+            "seq": self.seq,
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = json!(message),
+        }
+        write_message(&mut self.writer, &response);
+    }
+
+    fn event(&mut self, event: &str, body: Value) {
+        self.seq += 1;
+        let message = json!({ "seq": self.seq, "type": "event", "event": event, "body": body });
+        write_message(&mut self.writer, &message);
+    }
+}
+
+impl Launch {
+    /// Start or resume the program, returning how it ended up and the
+    /// `Debug.log` lines it printed that were not shown before.
+    fn run(
+        &mut self,
+        go: impl FnOnce(&mut Debugger, &mut dyn Run) -> Outcome,
+    ) -> (Outcome, Vec<String>) {
+        LOG.lock().unwrap().clear();
+        let project = self.debugger.project();
+        let mut run = move |hook: &mut dyn DebugHook| {
+            functor_lang::debug::run_program(&project.module, &mut functor_lang::NoHost, hook)
+        };
+        let outcome = go(&mut self.debugger, &mut run);
+        let log = std::mem::take(&mut *LOG.lock().unwrap());
+        let output = log.iter().skip(self.shown).cloned().collect();
+        self.shown = self.shown.max(log.len());
+        (outcome, output)
+    }
+
+    /// The inspection requests, answered from the stop.
+    fn request(&mut self, command: &str, arguments: &Value) -> Result<Value, String> {
+        let frame = arguments["frameId"].as_u64().unwrap_or(0) as usize;
+        match command {
+            "setBreakpoints" => {
+                let path = arguments["source"]["path"]
+                    .as_str()
+                    .ok_or("setBreakpoints needs a source path")?;
+                let breakpoints: Vec<Breakpoint> = arguments["breakpoints"]
+                    .as_array()
+                    .map(Vec::as_slice)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|breakpoint| Breakpoint {
+                        line: breakpoint["line"].as_u64().unwrap_or(0) as usize,
+                        condition: breakpoint["condition"]
+                            .as_str()
+                            .filter(|condition| !condition.trim().is_empty())
+                            .map(str::to_string),
+                    })
+                    .collect();
+                let verified = self.debugger.set_breakpoints(Path::new(path), &breakpoints);
+                let body: Vec<Value> = breakpoints
+                    .iter()
+                    .zip(verified)
+                    .map(|(breakpoint, verified)| {
+                        json!({ "verified": verified, "line": breakpoint.line })
+                    })
+                    .collect();
+                Ok(json!({ "breakpoints": body }))
+            }
+            "setExceptionBreakpoints" => {
+                let filters = arguments["filters"]
+                    .as_array()
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                self.debugger
+                    .set_stop_on_errors(filters.iter().any(|filter| filter == "error"));
+                Ok(json!({}))
+            }
+            "stackTrace" => {
+                let stopped = self.debugger.stopped().ok_or("not stopped")?;
+                let frames: Vec<Value> = stopped
+                    .frames
+                    .iter()
+                    .enumerate()
+                    .map(|(id, frame)| {
+                        let (path, line, column) = self.debugger.locate(frame.span.start);
+                        json!({
+                            "id": id,
+                            "name": frame.name,
+                            "line": line,
+                            "column": column,
+                            "source": source(path),
+                        })
+                    })
+                    .collect();
+                let total = frames.len();
+                Ok(json!({ "stackFrames": frames, "totalFrames": total }))
+            }
+            "scopes" => {
+                let scopes: Vec<Value> = self
+                    .debugger
+                    .scopes(frame)
+                    .into_iter()
+                    .map(|scope| {
+                        json!({
+                            "name": scope.name,
+                            "variablesReference": scope.reference,
+                            "expensive": false,
+                        })
+                    })
+                    .collect();
+                Ok(json!({ "scopes": scopes }))
+            }
+            "variables" => {
+                let reference = arguments["variablesReference"].as_u64().unwrap_or(0);
+                let variables: Vec<Value> = self
+                    .debugger
+                    .variables(reference as usize)
+                    .into_iter()
+                    .map(|variable| {
+                        json!({
+                            "name": variable.name,
+                            "value": variable.value,
+                            "variablesReference": variable.reference,
+                        })
+                    })
+                    .collect();
+                Ok(json!({ "variables": variables }))
+            }
+            "evaluate" => {
+                let expression = arguments["expression"].as_str().unwrap_or_default();
+                let variable =
+                    self.debugger
+                        .evaluate(frame, expression, &mut functor_lang::NoHost)?;
+                Ok(json!({
+                    "result": variable.value,
+                    "variablesReference": variable.reference,
+                }))
+            }
+            other => Err(format!("unsupported request `{other}`")),
+        }
+    }
+}
+
+/// A DAP `Source` for a project file; bundled modules (`<prelude>/…`) have
+/// no file to open.
+fn source(path: &Path) -> Value {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned());
+    if path.to_string_lossy().starts_with('<') {
+        json!({ "name": name, "presentationHint": "deemphasize" })
+    } else {
+        json!({ "name": name, "path": path })
+    }
+}
+
+/// Resolve the game's file names in a forwarded reply (`stackTrace`'s
+/// frames) against the project root.
+fn resolve_sources(body: &mut Value, root: &Path) {
+    let Some(frames) = body["stackFrames"].as_array_mut() else {
+        return;
+    };
+    for frame in frames {
+        if let Some(name) = frame["source"]["path"].as_str() {
+            frame["source"]["path"] = json!(root.join(name));
+        }
+    }
+}
+
+/// `POST http://<addr>/debug` with a request, returning the reply body.
+/// Hand-rolled HTTP/1.1 like the LSP's trace fetch: `Connection: close`, so
+/// the body is everything after the header block. A non-200 answer is the
+/// runtime's refusal, carried as the error.
+fn post_debug(addr: &str, request: &Value) -> Result<Value, String> {
+    let command = json!({ "command": request["command"], "arguments": request["arguments"] });
+    let body = command.to_string();
+    let mut stream = std::net::TcpStream::connect(addr).map_err(|error| error.to_string())?;
+    stream
+        .set_read_timeout(Some(std::time::Duration::from_secs(10)))
+        .map_err(|error| error.to_string())?;
+    let request = format!(
+        "POST /debug HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\n\
+Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|error| error.to_string())?;
+    let mut response = Vec::new();
+    stream
+        .read_to_end(&mut response)
+        .map_err(|error| error.to_string())?;
+    let split = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or("malformed response")?;
+    let (head, reply) = (&response[..split], &response[split + 4..]);
+    if !head.starts_with(b"HTTP/1.1 200") {
+        let status = String::from_utf8_lossy(head.split(|&b| b == b'\r').next().unwrap_or(head));
+        let reply = String::from_utf8_lossy(reply);
+        return Err(if reply.is_empty() {
+            status.into_owned()
+        } else {
+            reply.into_owned()
+        });
+    }
+    serde_json::from_slice(reply).map_err(|error| error.to_string())
+}
+
+/// Poll the attached runtime's `status` ~4Hz until detach, injecting each
+/// changed reply — a frame that hit a breakpoint shows up as a new stop.
+fn poll_status(addr: &str, tx: Sender<Incoming>, stop: Arc<AtomicBool>) {
+    let mut last: Option<Value> = None;
+    while !stop.load(Ordering::SeqCst) {
+        if let Ok(status) = post_debug(addr, &json!({ "command": "status" })) {
+            if last.as_ref() != Some(&status) {
+                if tx.send(Incoming::Status(status.clone())).is_err() {
+                    return;
+                }
+                last = Some(status);
+            }
+        }
+        std::thread::sleep(std::time::Duration::from_millis(250));
+    }
+}
@@ -1,9 +1,11 @@
-//! Live `expect` test status — the editor half of inline tests.
+//! Live `expect` (and `property`, `expectSnapshot`) test status — the editor
+//! half of inline tests.
 //!
 //! On every buffer edit the server immediately pushes the project's expects
 //! as `running` (the in-flight state); once the debounce settles, a WORKER
 //! thread reloads the project from the live buffers and evaluates the
-//! expects with [`functor_lang::run_expects_budgeted`], and the results
+//! expects with [`functor_lang::run_expects_with`] under a step budget, and
+//! the results
 //! re-enter the server loop (as `$/functorExpects`, generation-tagged so a
 //! stale run never paints) to be relayed to the client as the custom
 //! notification [`STATUS`]:
@@ -13,11 +15,17 @@
 //!     { "line": 12, "state": "pass",       "detail": null },
 //!     { "line": 14, "state": "fail",       "detail": "left == right — left: 12, right: 12.5" },
 //!     { "line": 15, "state": "error",      "detail": "game.fun:3:5: no pattern matched 1" },
-//!     { "line": 16, "state": "unrunnable", "detail": "unknown external `Scene.cube` — engine calls need the runtime; run `functor test` or the game" }
+//!     { "line": 16, "state": "unrunnable", "detail": "unknown external `Scene.cube` — engine calls need the runtime; run `functor test` or the game" },
+//!     { "line": 18, "state": "fail",       "detail": "falsified by n = 10 (seed 24301, case 34, 4 shrinks): left < right — left: 10, right: 10" },
+//!     { "line": 20, "state": "fail",       "detail": "snapshot `hud` changed, 1 difference (`--update-snapshots` accepts it):\n  hud.score: expected 3, got 4" }
 //! ] } }
 //! ```
 //!
-//! `line` is 0-based (LSP convention), the line of the `expect` keyword.
+//! `line` is 0-based (LSP convention), the line of the `expect` (or
+//! `property`, `expectSnapshot`) keyword. A falsified property is a `fail`
+//! whose detail names the shrunk inputs and the seed that replays them. A
+//! snapshot compares against its stored `__snapshots__` value, which the
+//! editor only reads: recording and updating are `functor test`'s.
 //! States: `running` | `pass` | `fail` | `error` | `unrunnable` (an expect
 //! that calls an engine external — the plain evaluator has no host).
 //!
@@ -171,7 +179,19 @@ pub fn evaluate_rows(
         )
     };
     let uris = project_uris(&project, &path_to_uri);
-    let rows = match functor_lang::run_expects_budgeted(&project.module, &mut functor_lang::NoHost, Some(budget)) {
+    let file_of =
+        |span: functor_lang::Span| Some(project.sources.resolve(span.start).0.path.clone());
+    // An unreadable snapshot file leaves its snapshots passing as new; the
+    // CLI is where that error is reported.
+    let options = functor_lang::ExpectOptions {
+        budget: Some(budget),
+        snapshots: functor_lang::snapshot::load(&project.module, &file_of, false)
+            .unwrap_or_default(),
+        ..functor_lang::ExpectOptions::default()
+    };
+    let reports =
+        functor_lang::run_expects_with(&project.module, &mut functor_lang::NoHost, &options);
+    let rows = match reports {
         Ok(reports) => reports
             .iter()
             .map(|report| {
@@ -242,6 +262,46 @@ mod tests {
         assert!(rows[2].detail.as_deref().unwrap().contains("Scene.cube"));
     }
 
+    #[test]
+    fn a_falsified_property_reports_its_counterexample_and_seed() {
+        let src = "property (n: int) => n < 10\n";
+        let rows = rows_for(src, DEFAULT_BUDGET);
+        assert_eq!(rows[0].state, "fail");
+        let detail = rows[0].detail.as_deref().unwrap();
+        assert!(detail.starts_with("falsified by n = 10 (seed "), "{detail}");
+        assert!(detail.ends_with("left < right — left: 10, right: 10"), "{detail}");
+    }
+
+    /// The gutter compares against the stored snapshot, and never writes one.
+    #[test]
+    fn a_changed_snapshot_fails_against_the_stored_file() {
+        let dir =
+            std::env::temp_dir().join(format!("functor-lsp-snapshots-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("__snapshots__")).unwrap();
+        let stored = dir.join("__snapshots__").join("game.json");
+        std::fs::write(&stored, r#"{ "hud": { "Record": [["score", { "Int": 3 }]] } }"#).unwrap();
+        let path = dir.join("game.fun");
+        let src = "expectSnapshot hud = { score: 4 }\nexpectSnapshot fresh = 1\n";
+        std::fs::write(&path, src).unwrap();
+        let (rows, _uris) = evaluate_rows(
+            None,
+            Some((path, src.to_string())),
+            Default::default(),
+            DEFAULT_BUDGET,
+            uri,
+        )
+        .expect("loadable source");
+        assert_eq!(
+            rows.iter().map(|r| r.state).collect::<Vec<_>>(),
+            ["fail", "pass"]
+        );
+        let detail = rows[0].detail.as_deref().unwrap();
+        assert!(detail.ends_with("hud.score: expected 3, got 4"), "{detail}");
+        assert!(!std::fs::read_to_string(&stored).unwrap().contains("fresh"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn budget_exhaustion_is_an_error_row() {
         let src = "let sum = (n) => List.range(n) |> List.fold((a, x) => a + x, 0.0)\n\
@@ -0,0 +1,48 @@
+//! `Content-Length` message framing over a byte stream — the base protocol
+//! the Language Server Protocol and the Debug Adapter Protocol share, so
+//! `functor-lang-lsp` and `functor-lang-dap` frame their stdio the same way.
+
+use std::io::{BufRead, Write};
+
+use serde_json::Value;
+
+/// Read one `Content-Length`-framed message. Returns `None` only when the
+/// stream is done (EOF, short read, missing `Content-Length`); a frame whose
+/// body is valid length but invalid JSON is skipped — the stream is still
+/// framed-in-sync, so one garbage message must not kill diagnostics for the
+/// whole session.
+pub fn read_message(reader: &mut impl BufRead) -> Option<Value> {
+    loop {
+        let mut content_length: Option<usize> = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).ok()? == 0 {
+                return None; // EOF
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break; // blank line ends the header block
+            }
+            // Header names are case-insensitive (RFC 9110, and clients vary).
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().ok();
+                }
+            }
+            // Other headers (Content-Type) are ignored.
+        }
+        let mut body = vec![0; content_length?];
+        reader.read_exact(&mut body).ok()?;
+        match serde_json::from_slice(&body) {
+            Ok(message) => return Some(message),
+            Err(_) => continue, // skip the garbage frame, keep serving
+        }
+    }
+}
+
+pub fn write_message(writer: &mut impl Write, message: &Value) {
+    let body = message.to_string();
+    // A write failure means the client is gone; exiting quietly beats a panic.
+    let _ = write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len());
+    let _ = writer.flush();
+}
@@ -13,10 +13,23 @@
 //! to answer `textDocument/hover` (quick info: `name : Type` from the
 //! gradual checker, via `functor_lang::hover`), `textDocument/definition`
 //! (go-to-definition via `functor_lang::goto`; Functor Lang is single-file, so the answer is
-//! always a `Location` in the same document), `textDocument/inlayHint`
+//! always a `Location` in the same document), `textDocument/references` and
+//! `textDocument/rename` / `prepareRename` (project-wide occurrences and
+//! checked renames, via `functor_lang::references`),
+//! `textDocument/signatureHelp` (the enclosing call's signature with the
+//! argument under the cursor, via `functor_lang::signature`),
+//! `textDocument/documentSymbol` and `workspace/symbol` (the file outline and
+//! a project-wide name search, via `functor_lang::symbols`), `textDocument/inlayHint`
 //! (inferred `: Type` ghost text on unannotated lambda params, via
-//! `functor_lang::inlay`), and `textDocument/codeLens` (each top-level def's inferred
-//! signature above it, via `functor_lang::codelens`). Diagnostics cover parse,
+//! `functor_lang::inlay`), `textDocument/codeLens` (each top-level def's inferred
+//! signature above it, via `functor_lang::codelens`),
+//! `textDocument/formatting` / `rangeFormatting` (the canonical `functor fmt`
+//! layout, via `functor_lang::format`), `textDocument/codeAction` (quick
+//! fixes for the checker's diagnostics plus extract and return-annotation
+//! refactors, via `functor_lang::actions`), and
+//! `textDocument/semanticTokens/full` / `full/delta` (constructors, host
+//! values, builtins, type parameters, unit suffixes, and assigned bindings
+//! told apart, via `functor_lang::semantic`). Diagnostics cover parse,
 //! lowering, and every `functor_lang::check` type diagnostic.
 //!
 //! On top of that it hosts the **paused-scene inspector** (see [`inspector`]):
@@ -30,10 +43,11 @@
 //! `workspace/codeLens/refresh` requests.
 
 mod expects;
+mod framing;
 mod inspector;
 
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader, Read, Write};
+use std::io::{BufReader, Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
@@ -41,12 +55,15 @@ use std::sync::Arc;
 
 use serde_json::{json, Value};
 
+use framing::{read_message, write_message};
 use inspector::TraceDoc;
 
 /// JSON-RPC "method not found" (LSP inherits JSON-RPC 2.0 error codes).
 const METHOD_NOT_FOUND: i64 = -32601;
 /// JSON-RPC "invalid request" — requests after `shutdown`.
 const INVALID_REQUEST: i64 = -32600;
+/// LSP "request failed" — a rename refused, with the reason as its message.
+const REQUEST_FAILED: i64 = -32803;
 
 /// A message arriving on the multiplexed channel: a framed message (from stdin
 /// or an attach-poll thread), or the stdin reader hitting EOF. `Eof` ends the
@@ -153,7 +170,7 @@ fn debounce_publisher(rx: Receiver<String>, flush: Sender<Incoming>, delay: std:
 /// exit code: per the LSP spec, `exit` after `shutdown` is 0, `exit` without
 /// it is 1 (EOF — the client vanished — is a quiet 0).
 #[cfg(test)]
-fn serve(reader: &mut impl BufRead, writer: &mut impl Write) -> i32 {
+fn serve(reader: &mut impl std::io::BufRead, writer: &mut impl Write) -> i32 {
     // The synchronous single-source loop used by the tests: stdin only. Attach
     // polling is a `main`-only concern (its trace notifications arrive on the
     // channel `main` drains), so the receiver is dropped here.
@@ -179,6 +196,10 @@ fn run(
     // `None`; keeping the previous good load lets us still answer. A failed
     // refresh retains the previous entry — that IS "last good".
     let mut projects: HashMap<String, functor_lang::project::Project> = HashMap::new();
+    // The semantic tokens last sent per URI, with their `resultId`, so a
+    // `full/delta` request can answer with just what changed.
+    let mut semantic: HashMap<String, (String, Vec<u32>)> = HashMap::new();
+    let mut next_result_id: u64 = 0;
     // Inspector overlay state: the latest trace (parsed + its raw params, for
     // change detection), the per-entry selected execution index (raw; reduced
     // mod count at display), a monotonic id for server→client requests, and
@@ -231,10 +252,24 @@ fn run(
                         "textDocumentSync": 1,
                         "hoverProvider": true,
                         "definitionProvider": true,
+                        "referencesProvider": true,
+                        "renameProvider": { "prepareProvider": true },
                         "inlayHintProvider": true,
                         "foldingRangeProvider": true,
                         "codeLensProvider": { "resolveProvider": false },
                         "completionProvider": { "triggerCharacters": ["."] },
+                        "signatureHelpProvider": { "triggerCharacters": ["(", ","] },
+                        "documentSymbolProvider": true,
+                        "workspaceSymbolProvider": true,
+                        "documentFormattingProvider": true,
+                        "documentRangeFormattingProvider": true,
+                        "semanticTokensProvider": {
+                            "legend": semantic_tokens_legend(),
+                            "full": { "delta": true },
+                        },
+                        "codeActionProvider": {
+                            "codeActionKinds": ["quickfix", "refactor.extract", "refactor.rewrite"],
+                        },
                         "executeCommandProvider": {
                             "commands": ["functor.inspector.cycleExecution"],
                         },
@@ -295,6 +330,35 @@ fn run(
                     &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
                 );
             }
+            ("textDocument/references", Some(id)) => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                let result = documents
+                    .contains_key(uri)
+                    .then(|| references(uri, &documents, params))
+                    .flatten()
+                    .unwrap_or(Value::Null);
+                write_message(
+                    writer,
+                    &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                );
+            }
+            ("textDocument/prepareRename" | "textDocument/rename", Some(id)) => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                let answer = if method == "textDocument/rename" {
+                    let new_name = params["newName"].as_str().unwrap_or("");
+                    rename(uri, &documents, &params["position"], Some(new_name))
+                } else {
+                    rename(uri, &documents, &params["position"], None)
+                };
+                let response = match answer {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err(message) => {
+                        let error = json!({ "code": REQUEST_FAILED, "message": message });
+                        json!({ "jsonrpc": "2.0", "id": id, "error": error })
+                    }
+                };
+                write_message(writer, &response);
+            }
             ("textDocument/inlayHint", Some(id)) => {
                 let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
                 // Type hints (project-derived) merged with live-value hints
@@ -379,6 +443,102 @@ fn run(
                     &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
                 );
             }
+            ("textDocument/signatureHelp", Some(id)) => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                // Same footing as completion: the call being typed never
+                // parses, so the callee resolves against the last-good load.
+                let result = projects
+                    .get(uri)
+                    .and_then(|project| {
+                        signature_help(project, uri, &documents, &params["position"])
+                    })
+                    .unwrap_or(Value::Null);
+                write_message(
+                    writer,
+                    &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                );
+            }
+            ("textDocument/documentSymbol", Some(id)) => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                let result = document_symbols(uri, &documents).unwrap_or(Value::Null);
+                write_message(
+                    writer,
+                    &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                );
+            }
+            ("workspace/symbol", Some(id)) => {
+                let query = params["query"].as_str().unwrap_or("");
+                let result = workspace_symbols(&projects, query);
+                write_message(
+                    writer,
+                    &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                );
+            }
+            (
+                "textDocument/semanticTokens/full" | "textDocument/semanticTokens/full/delta",
+                Some(id),
+            ) => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                let data = documents
+                    .contains_key(uri)
+                    .then(|| semantic_token_data(uri, &documents))
+                    .flatten();
+                // A buffer that does not load has no tokens to give; the
+                // editor keeps what it last painted.
+                let result = match data {
+                    None => Value::Null,
+                    Some(data) => {
+                        next_result_id += 1;
+                        let result_id = next_result_id.to_string();
+                        let previous = params["previousResultId"].as_str();
+                        let result = match semantic.get(uri) {
+                            Some((known, old))
+                                if method.ends_with("/delta") && previous == Some(known) =>
+                            {
+                                json!({ "resultId": result_id, "edits": token_edits(old, &data) })
+                            }
+                            _ => json!({ "resultId": result_id, "data": data }),
+                        };
+                        semantic.insert(uri.to_string(), (result_id, data));
+                        result
+                    }
+                };
+                write_message(
+                    writer,
+                    &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                );
+            }
+            ("textDocument/codeAction", Some(id)) => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                let result = documents
+                    .contains_key(uri)
+                    .then(|| code_actions(uri, &documents, &params["range"]))
+                    .flatten()
+                    .unwrap_or(Value::Null);
+                write_message(
+                    writer,
+                    &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                );
+            }
+            ("textDocument/formatting", Some(id)) => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                // An unparseable buffer formats to no edits (the diagnostics
+                // already say why).
+                let result = formatting(uri, &documents, None).unwrap_or_else(|| json!([]));
+                write_message(
+                    writer,
+                    &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                );
+            }
+            ("textDocument/rangeFormatting", Some(id)) => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                let result = formatting(uri, &documents, Some(&params["range"]))
+                    .unwrap_or_else(|| json!([]));
+                write_message(
+                    writer,
+                    &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                );
+            }
             (_, Some(id)) => {
                 let error = json!({
                     "code": METHOD_NOT_FOUND,
@@ -453,6 +613,7 @@ fn run(
                 let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
                 documents.remove(uri);
                 projects.remove(uri);
+                semantic.remove(uri);
                 // Clear stale squiggles for the closed file.
                 write_diagnostics(writer, uri, vec![]);
                 // And its expect gutter/problems (an authoritative empty list).
@@ -547,8 +708,8 @@ fn push_expects_running(
     generation: u64,
 ) {
     let Some(project) = project else { return };
-    let rows = expects::running_rows(project, |path| path_to_uri(path));
-    let uris = expects::project_uris(project, |path| path_to_uri(path));
+    let rows = expects::running_rows(project, path_to_uri);
+    let uris = expects::project_uris(project, path_to_uri);
     let params = expects::status_params(generation, &rows, &uris);
     write_message(
         writer,
@@ -589,7 +750,7 @@ fn spawn_expect_worker(
             // diagnostics say why). Loadable: authoritative per-file lists —
             // a project with zero expects clears gutters via empty lists.
             let Some((rows, uris)) =
-                expects::evaluate_rows(entry, single, overrides, budget, |p| path_to_uri(p))
+                expects::evaluate_rows(entry, single, overrides, budget, path_to_uri)
             else {
                 return;
             };
@@ -813,6 +974,198 @@ fn definition(uri: &str, documents: &HashMap<String, String>, position: &Value)
     Some(json!({ "uri": target_uri, "range": range }))
 }
 
+/// Answer a references request via `functor_lang::references`: every
+/// occurrence of the symbol under the cursor across the project's files,
+/// without its declaration when the client's `context` asks for that.
+fn references(uri: &str, documents: &HashMap<String, String>, params: &Value) -> Option<Value> {
+    let project = load_project(uri, documents)?;
+    let file = project.sources.file_by_path(&uri_to_path(uri)?)?;
+    let offset = file.base + position_to_offset(&file.src, &params["position"])?;
+    let declarations = params["context"]["includeDeclaration"]
+        .as_bool()
+        .unwrap_or(true);
+    let (_, types) = project.check_with_types();
+    let locations: Vec<Value> = functor_lang::references::references(&project, &types, offset)
+        .into_iter()
+        .filter(|occurrence| declarations || !occurrence.declaration)
+        .filter_map(|occurrence| localize(&project, occurrence.span))
+        .map(|(uri, range)| json!({ "uri": uri, "range": range }))
+        .collect();
+    Some(Value::Array(locations))
+}
+
+/// Answer `prepareRename` (`new_name` absent: the name's range, or why it
+/// cannot be renamed) or `rename` (a `WorkspaceEdit`: per-file text edits,
+/// plus a file move when a file module is renamed). A refusal — an invalid
+/// name, a collision, a shadowed use — is the `Err` message the editor
+/// shows.
+fn rename(
+    uri: &str,
+    documents: &HashMap<String, String>,
+    position: &Value,
+    new_name: Option<&str>,
+) -> Result<Value, String> {
+    let not_loaded = || "the project does not load; fix its errors first".to_string();
+    if !documents.contains_key(uri) {
+        return Err(not_loaded());
+    }
+    let project = load_project(uri, documents).ok_or_else(not_loaded)?;
+    let file = uri_to_path(uri)
+        .and_then(|path| project.sources.file_by_path(&path))
+        .ok_or_else(not_loaded)?;
+    let offset = file.base + position_to_offset(&file.src, position).ok_or_else(not_loaded)?;
+    let (_, types) = project.check_with_types();
+    let Some(new_name) = new_name else {
+        let span = functor_lang::references::prepare_rename(&project, &types, offset)?;
+        return Ok(json!({ "range": local_range(file, span) }));
+    };
+    let renamed = functor_lang::references::rename(&project, &types, offset, new_name)?;
+    let changes = text_edits_by_uri(&project, &renamed.edits);
+    let Some((from, to)) = renamed.moved else {
+        return Ok(json!({ "changes": changes_map(changes) }));
+    };
+    // Text edits address the files by their current URIs, so they go
+    // before the move.
+    let mut document_changes: Vec<Value> = changes
+        .into_iter()
+        .map(|(uri, edits)| {
+            json!({ "textDocument": { "uri": uri, "version": null }, "edits": edits })
+        })
+        .collect();
+    document_changes.push(json!({
+        "kind": "rename",
+        "oldUri": path_to_uri(&from),
+        "newUri": path_to_uri(&to),
+    }));
+    Ok(json!({ "documentChanges": document_changes }))
+}
+
+/// Project-wide edits → LSP `TextEdit`s grouped by the file they land in,
+/// in first-touched order.
+fn text_edits_by_uri(
+    project: &functor_lang::project::Project,
+    edits: &[functor_lang::references::Edit],
+) -> Vec<(String, Vec<Value>)> {
+    let mut changes: Vec<(String, Vec<Value>)> = Vec::new();
+    for edit in edits {
+        let owner = project.sources.file_at(edit.span.start);
+        let uri = path_to_uri(&owner.path);
+        let text_edit = json!({ "range": local_range(owner, edit.span), "newText": edit.text });
+        match changes.iter_mut().find(|(known, _)| *known == uri) {
+            Some((_, edits)) => edits.push(text_edit),
+            None => changes.push((uri, vec![text_edit])),
+        }
+    }
+    changes
+}
+
+/// A `WorkspaceEdit`'s `changes` map.
+fn changes_map(changes: Vec<(String, Vec<Value>)>) -> serde_json::Map<String, Value> {
+    changes
+        .into_iter()
+        .map(|(uri, edits)| (uri, Value::Array(edits)))
+        .collect()
+}
+
+/// Answer a code-action request via `functor_lang::actions`: the quick
+/// fixes for the diagnostics the range touches (each naming the diagnostic
+/// it resolves) and the refactors available there, as `CodeAction`s with
+/// their `WorkspaceEdit`s. `None` when the project does not load.
+fn code_actions(uri: &str, documents: &HashMap<String, String>, range: &Value) -> Option<Value> {
+    use functor_lang::actions::ActionKind;
+    let project = load_project(uri, documents)?;
+    let file = project.sources.file_by_path(&uri_to_path(uri)?)?;
+    let start = file.base + position_to_offset(&file.src, &range["start"])?;
+    let end = file.base + position_to_offset(&file.src, &range["end"])?;
+    let span = functor_lang::Span::new(start, end);
+    let actions: Vec<Value> = functor_lang::actions::code_actions(&project, span)
+        .into_iter()
+        .map(|action| {
+            let kind = match action.kind {
+                ActionKind::QuickFix => "quickfix",
+                ActionKind::Rewrite => "refactor.rewrite",
+                ActionKind::Extract => "refactor.extract",
+            };
+            let changes = changes_map(text_edits_by_uri(&project, &action.edits));
+            let mut value = json!({
+                "title": action.title,
+                "kind": kind,
+                "edit": { "changes": changes },
+            });
+            if let Some(fixes) = action.fixes {
+                let owner = project.sources.file_at(fixes.span.start);
+                value["diagnostics"] = json!([{
+                    "range": local_range(owner, fixes.span),
+                    "severity": 1, // Error
+                    "source": "functor-lang",
+                    "message": fixes.message,
+                }]);
+                value["isPreferred"] = json!(true);
+            }
+            value
+        })
+        .collect();
+    Some(Value::Array(actions))
+}
+
+/// The semantic-token legend: `functor_lang::semantic`'s types and
+/// modifiers, in the order the encoded tokens index them.
+fn semantic_tokens_legend() -> Value {
+    use functor_lang::semantic::{TokenModifier, TokenType};
+    let types: Vec<&str> = TokenType::ALL.iter().map(|ty| ty.name()).collect();
+    let modifiers: Vec<&str> = TokenModifier::ALL.iter().map(|m| m.name()).collect();
+    json!({ "tokenTypes": types, "tokenModifiers": modifiers })
+}
+
+/// The open file's semantic tokens via `functor_lang::semantic`, in the
+/// LSP's relative encoding: five integers per token (line delta, start
+/// delta, UTF-16 length, type index, modifier bits). `None` when the project
+/// does not load.
+fn semantic_token_data(uri: &str, documents: &HashMap<String, String>) -> Option<Vec<u32>> {
+    use functor_lang::semantic::{TokenModifier, TokenType};
+    let project = load_project(uri, documents)?;
+    let file = project.sources.file_by_path(&uri_to_path(uri)?)?;
+    let (_, types) = project.check_with_types();
+    let mut data = Vec::new();
+    let (mut last_line, mut last_start) = (0, 0);
+    for token in functor_lang::semantic::semantic_tokens(&project, &types, file) {
+        let start = token.span.start - file.base;
+        let prefix = &file.src[..start];
+        let line = prefix.matches('\n').count() as u32;
+        let line_start = prefix.rfind('\n').map_or(0, |i| i + 1);
+        let character = prefix[line_start..].encode_utf16().count() as u32;
+        let length = file.src[start..token.span.end - file.base].encode_utf16().count() as u32;
+        let ty = TokenType::ALL.iter().position(|t| *t == token.ty).unwrap_or(0) as u32;
+        let modifiers = token.modifiers.iter().fold(0u32, |bits, modifier| {
+            bits | 1 << TokenModifier::ALL.iter().position(|m| m == modifier).unwrap_or(0)
+        });
+        let delta_start = if line == last_line { character - last_start } else { character };
+        data.extend([line - last_line, delta_start, length, ty, modifiers]);
+        (last_line, last_start) = (line, character);
+    }
+    Some(data)
+}
+
+/// The `SemanticTokensEdit`s turning `old` into `new`: the one splice
+/// between their common prefix and suffix (none when they are equal).
+fn token_edits(old: &[u32], new: &[u32]) -> Value {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if prefix == old.len() && prefix == new.len() {
+        return json!([]);
+    }
+    json!([{
+        "start": prefix,
+        "deleteCount": old.len() - prefix - suffix,
+        "data": &new[prefix..new.len() - suffix],
+    }])
+}
+
 /// Answer a code-lens request: load the project and return one lens per
 /// top-level def **in the open file** with a known inferred signature
 /// (`name : Type`), anchored on the line above the def. The command is inert
@@ -1155,6 +1508,40 @@ fn folding_ranges(
     Some(Value::Array(ranges))
 }
 
+/// Answer a formatting request via `functor_lang::format`: one `TextEdit`
+/// replacing the whole buffer, or with a `range` just the top-level items it
+/// touches. No edits when the text is already canonical; `None` when the
+/// buffer does not parse.
+fn formatting(
+    uri: &str,
+    documents: &HashMap<String, String>,
+    range: Option<&Value>,
+) -> Option<Value> {
+    let text = documents.get(uri)?;
+    let interface = uri_to_path(uri)
+        .and_then(|path| path.extension().map(|extension| extension == "funi"))
+        .unwrap_or(false);
+    let (span, new_text) = match range {
+        None => {
+            let formatted = functor_lang::format::format_source(text, interface).ok()?;
+            (functor_lang::Span::new(0, text.len()), formatted)
+        }
+        Some(range) => {
+            let start = position_to_offset(text, &range["start"])?;
+            let end = position_to_offset(text, &range["end"])?.max(start);
+            let selected = functor_lang::Span::new(start, end);
+            match functor_lang::format::format_range(text, interface, selected).ok()? {
+                Some(edit) => edit,
+                None => return Some(json!([])),
+            }
+        }
+    };
+    if text[span.start..span.end] == new_text {
+        return Some(json!([]));
+    }
+    Some(json!([{ "range": span_to_range(text, span), "newText": new_text }]))
+}
+
 /// Answer a completion request. Unlike hover/definition, the offset stays
 /// **local** to the live buffer (no `file.base +`): `functor_lang::complete`
 /// derives context textually from that buffer, while candidates come from the
@@ -1206,6 +1593,133 @@ fn completion(
     Some(Value::Array(items))
 }
 
+/// Answer a signature-help request via `functor_lang::signature`, with the
+/// same local-offset, last-good-project footing as [`completion`]. Parameter
+/// labels are `[start, end]` offsets into the signature label, in UTF-16 code
+/// units like every LSP position; the parameter a `|>` pipeline supplies is
+/// documented as such.
+fn signature_help(
+    project: &functor_lang::project::Project,
+    uri: &str,
+    documents: &HashMap<String, String>,
+    position: &Value,
+) -> Option<Value> {
+    let text = documents.get(uri)?;
+    let offset = position_to_offset(text, position)?;
+    let file = uri_to_path(uri).and_then(|path| project.sources.file_by_path(&path));
+    let current_module = file.map_or_else(|| project.entry.clone(), |file| file.module.clone());
+    let inline = file
+        .and_then(|file| {
+            project
+                .inline_module_at(file.base + offset)
+                .filter(|module| module.file == file.module)
+        })
+        .map(|module| module.path.clone());
+    let help = functor_lang::signature::signature_help(
+        project,
+        &current_module,
+        inline.as_deref(),
+        text,
+        offset,
+    )?;
+    let utf16 = |end: usize| help.label[..end].encode_utf16().count();
+    let parameters: Vec<Value> = help
+        .params
+        .iter()
+        .enumerate()
+        .map(|(i, &(start, end))| {
+            let mut parameter = json!({ "label": [utf16(start), utf16(end)] });
+            if help.piped == Some(i) {
+                parameter["documentation"] = json!("supplied by the `|>` pipeline");
+            }
+            parameter
+        })
+        .collect();
+    let mut signature = json!({ "label": help.label, "parameters": parameters });
+    if let Some(doc) = &help.doc {
+        signature["documentation"] = json!({ "kind": "markdown", "value": doc });
+    }
+    Some(json!({
+        "signatures": [signature],
+        "activeSignature": 0,
+        "activeParameter": help.active,
+    }))
+}
+
+/// Answer a document-symbol request via `functor_lang::symbols`: the live
+/// buffer's outline as nested `DocumentSymbol`s. `None` when it does not
+/// parse.
+fn document_symbols(uri: &str, documents: &HashMap<String, String>) -> Option<Value> {
+    let text = documents.get(uri)?;
+    let interface = uri_to_path(uri)
+        .and_then(|path| path.extension().map(|extension| extension == "funi"))
+        .unwrap_or(false);
+    let symbols = functor_lang::symbols::document_symbols(text, interface).ok()?;
+    fn convert(text: &str, symbols: &[functor_lang::symbols::DocumentSymbol]) -> Vec<Value> {
+        symbols
+            .iter()
+            .map(|symbol| {
+                json!({
+                    "name": symbol.name,
+                    "detail": symbol.detail,
+                    "kind": symbol_kind_code(symbol.kind),
+                    "range": span_to_range(text, symbol.span),
+                    "selectionRange": span_to_range(text, symbol.selection),
+                    "children": convert(text, &symbol.children),
+                })
+            })
+            .collect()
+    }
+    Some(Value::Array(convert(text, &symbols)))
+}
+
+/// Answer a workspace-symbol request via `functor_lang::symbols` over every
+/// project the open documents belong to (their last-good loads; two open
+/// files of one project report its symbols once).
+fn workspace_symbols(
+    projects: &HashMap<String, functor_lang::project::Project>,
+    query: &str,
+) -> Value {
+    let mut uris: Vec<&String> = projects.keys().collect();
+    uris.sort();
+    let mut seen = std::collections::HashSet::new();
+    let mut found = Vec::new();
+    for uri in uris {
+        let project = &projects[uri];
+        for symbol in functor_lang::symbols::workspace_symbols(project, query) {
+            let Some((uri, range)) = localize(project, symbol.span) else {
+                continue;
+            };
+            if !seen.insert((uri.clone(), range.to_string())) {
+                continue;
+            }
+            found.push(json!({
+                "name": symbol.name,
+                "kind": symbol_kind_code(symbol.kind),
+                "containerName": symbol.container,
+                "location": { "uri": uri, "range": range },
+            }));
+        }
+    }
+    Value::Array(found)
+}
+
+/// The LSP `SymbolKind` code for a symbol kind.
+fn symbol_kind_code(kind: functor_lang::symbols::SymbolKind) -> i64 {
+    use functor_lang::symbols::SymbolKind;
+    match kind {
+        SymbolKind::Module => 2,
+        SymbolKind::Record => 23,
+        SymbolKind::Variant => 10,
+        SymbolKind::Type => 5,
+        SymbolKind::Field => 8,
+        SymbolKind::Constructor => 22,
+        SymbolKind::Function => 12,
+        SymbolKind::Value => 13,
+        SymbolKind::Test => 24,
+    }
+}
+
 /// The LSP `CompletionItemKind` code for a completion kind.
 fn kind_code(kind: functor_lang::complete::CompletionKind) -> i64 {
     use functor_lang::complete::CompletionKind;
@@ -1439,47 +1953,6 @@ fn lsp_position(text: &str, offset: usize) -> Value {
     json!({ "line": line, "character": character })
 }
 
-/// Read one `Content-Length`-framed message. Returns `None` only when the
-/// stream is done (EOF, short read, missing `Content-Length`); a frame whose
-/// body is valid length but invalid JSON is skipped — the stream is still
-/// framed-in-sync, so one garbage message must not kill diagnostics for the
-/// whole session.
-fn read_message(reader: &mut impl BufRead) -> Option<Value> {
-    loop {
-        let mut content_length: Option<usize> = None;
-        loop {
-            let mut line = String::new();
-            if reader.read_line(&mut line).ok()? == 0 {
-                return None; // EOF
-            }
-            let line = line.trim_end();
-            if line.is_empty() {
-                break; // blank line ends the header block
-            }
-            // Header names are case-insensitive (RFC 9110, and clients vary).
-            if let Some((name, value)) = line.split_once(':') {
-                if name.eq_ignore_ascii_case("content-length") {
-                    content_length = value.trim().parse().ok();
-                }
-            }
-            // Other headers (Content-Type) are ignored.
-        }
-        let mut body = vec![0; content_length?];
-        reader.read_exact(&mut body).ok()?;
-        match serde_json::from_slice(&body) {
-            Ok(message) => return Some(message),
-            Err(_) => continue, // skip the garbage frame, keep serving
-        }
-    }
-}
-
-fn write_message(writer: &mut impl Write, message: &Value) {
-    let body = message.to_string();
-    // A write failure means the client is gone; exiting quietly beats a panic.
-    let _ = write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len());
-    let _ = writer.flush();
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,268 @@
+//! End-to-end test: spawn the real `functor-lang-dap` binary and speak framed
+//! DAP to it — launch a program to a breakpoint, inspect and evaluate in the
+//! stopped frame, step, and run it out; and attach to a (fake) runtime's
+//! `POST /debug`, turning its polled stop into a `stopped` event.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use serde_json::{json, Value};
+
+struct Adapter {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    seq: i64,
+}
+
+impl Adapter {
+    fn spawn() -> Adapter {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_functor-lang-dap"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("spawn functor-lang-dap");
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Adapter {
+            child,
+            stdin,
+            stdout,
+            seq: 0,
+        }
+    }
+
+    /// Send a request and return its response, skipping events.
+    fn request(&mut self, command: &str, arguments: Value) -> Value {
+        self.seq += 1;
+        let message = json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        });
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        self.stdin.flush().unwrap();
+        let seq = self.seq;
+        self.recv_until(&format!("the {command} response"), |message| {
+            message["type"] == "response" && message["request_seq"] == seq
+        })
+    }
+
+    /// Read messages until the `event` event.
+    fn event(&mut self, event: &str) -> Value {
+        self.recv_until(event, |message| {
+            message["type"] == "event" && message["event"] == event
+        })["body"]
+            .clone()
+    }
+
+    fn recv_until(&mut self, what: &str, want: impl Fn(&Value) -> bool) -> Value {
+        for _ in 0..50 {
+            let message = self.recv();
+            if want(&message) {
+                return message;
+            }
+        }
+        panic!("never received {what}");
+    }
+
+    fn recv(&mut self) -> Value {
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            self.stdout.read_line(&mut line).expect("read header");
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("Content-Length:") {
+                content_length = value.trim().parse().expect("content length");
+            }
+        }
+        let mut body = vec![0; content_length];
+        self.stdout.read_exact(&mut body).expect("read body");
+        serde_json::from_slice(&body).expect("parse body")
+    }
+}
+
+impl Drop for Adapter {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+const PROGRAM: &str = "let double = (n) =>\n\
+\x20 let twice = n * 2.0 in\n\
+\x20 twice\n\
+\n\
+let main = () =>\n\
+\x20 let logged = Debug.log(\"first\", double(1.0)) in\n\
+\x20 logged + double(20.0)\n";
+
+#[test]
+fn launch_stops_at_a_breakpoint_inspects_steps_and_runs_out() {
+    let dir = std::env::temp_dir().join(format!("functor-lang-dap-e2e-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("scratch dir");
+    let program = dir.join("main.fun");
+    std::fs::write(&program, PROGRAM).unwrap();
+
+    let mut adapter = Adapter::spawn();
+    let initialize = adapter.request("initialize", json!({ "adapterID": "functor" }));
+    assert_eq!(initialize["body"]["supportsConditionalBreakpoints"], true);
+    adapter.event("initialized");
+    let launch = adapter.request("launch", json!({ "program": program }));
+    assert_eq!(launch["success"], true, "{launch}");
+    let set = adapter.request(
+        "setBreakpoints",
+        json!({
+            "source": { "path": program },
+            "breakpoints": [{ "line": 3, "condition": "n < 10.0" }, { "line": 4 }],
+        }),
+    );
+    // Line 4 is blank: nothing can stop there.
+    assert_eq!(set["body"]["breakpoints"][0]["verified"], true);
+    assert_eq!(set["body"]["breakpoints"][1]["verified"], false);
+    adapter.request("configurationDone", json!({}));
+
+    // The first `double` call (n = 1) stops; the second would not.
+    let stopped = adapter.event("stopped");
+    assert_eq!(stopped["reason"], "breakpoint");
+
+    let stack = adapter.request("stackTrace", json!({ "threadId": 1 }));
+    let frames = stack["body"]["stackFrames"].as_array().unwrap();
+    let names: Vec<&str> = frames.iter().map(|f| f["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["double", "main"]);
+    assert_eq!(frames[0]["line"], 3);
+    assert_eq!(
+        frames[0]["source"]["path"],
+        program.to_string_lossy().as_ref()
+    );
+
+    let scopes = adapter.request("scopes", json!({ "frameId": 0 }));
+    let locals = scopes["body"]["scopes"][0]["variablesReference"].clone();
+    let variables = adapter.request("variables", json!({ "variablesReference": locals }));
+    let shown: Vec<(&str, &str)> = variables["body"]["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| (v["name"].as_str().unwrap(), v["value"].as_str().unwrap()))
+        .collect();
+    assert_eq!(shown, [("n", "1"), ("twice", "2")]);
+
+    let evaluated = adapter.request(
+        "evaluate",
+        json!({ "frameId": 0, "expression": "twice + n" }),
+    );
+    assert_eq!(evaluated["body"]["result"], "3");
+
+    // Stepping out runs `Debug.log` and lands on main's next line.
+    adapter.request("stepOut", json!({ "threadId": 1 }));
+    assert_eq!(adapter.event("output")["output"], "first: 2\n");
+    assert_eq!(adapter.event("stopped")["reason"], "step");
+    let stack = adapter.request("stackTrace", json!({ "threadId": 1 }));
+    assert_eq!(stack["body"]["stackFrames"][0]["name"], "main");
+    assert_eq!(stack["body"]["stackFrames"][0]["line"], 7);
+
+    // The replay to the end prints the log line once, not again.
+    adapter.request("continue", json!({ "threadId": 1 }));
+    let result = adapter.event("output");
+    assert_eq!(result["output"], "42\n");
+    assert_eq!(adapter.event("exited")["exitCode"], 0);
+    adapter.event("terminated");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// A runtime stand-in: answers each `POST /debug` from `reply`, by command.
+fn fake_runtime(reply: fn(&str) -> Value) -> u16 {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { return };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(value) = line.strip_prefix("Content-Length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let request: Value = serde_json::from_slice(&body).unwrap();
+            let answer = reply(request["command"].as_str().unwrap()).to_string();
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+Content-Length: {}\r\n\r\n{answer}",
+                answer.len()
+            );
+        }
+    });
+    port
+}
+
+#[test]
+fn attach_reports_the_runtimes_stop_and_resolves_its_sources() {
+    let port = fake_runtime(|command| match command {
+        "status" => json!({
+            "stopped": { "reason": "breakpoint", "description": "update (Jump)", "text": null },
+            "stops": 1,
+        }),
+        "stackTrace" => json!({
+            "stackFrames": [{
+                "id": 0,
+                "name": "update",
+                "line": 4,
+                "column": 3,
+                "source": { "name": "game.fun", "path": "game.fun" },
+            }],
+            "totalFrames": 1,
+        }),
+        _ => json!({}),
+    });
+    let mut adapter = Adapter::spawn();
+    adapter.request("initialize", json!({ "adapterID": "functor" }));
+    let attach = adapter.request(
+        "attach",
+        json!({ "port": port, "projectRoot": "/work/game" }),
+    );
+    assert_eq!(attach["success"], true, "{attach}");
+    let stopped = adapter.event("stopped");
+    assert_eq!(stopped["reason"], "breakpoint");
+    assert_eq!(stopped["description"], "update (Jump)");
+    let stack = adapter.request("stackTrace", json!({ "threadId": 1 }));
+    assert_eq!(
+        stack["body"]["stackFrames"][0]["source"]["path"],
+        "/work/game/game.fun"
+    );
+    let disconnect = adapter.request("disconnect", json!({}));
+    assert_eq!(disconnect["success"], true);
+}
+
+#[test]
+fn attach_without_a_runtime_fails_the_request() {
+    // Bind then drop: nothing listens on the port.
+    let port = TcpListener::bind(("127.0.0.1", 0))
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut adapter = Adapter::spawn();
+    adapter.request("initialize", json!({ "adapterID": "functor" }));
+    let attach = adapter.request("attach", json!({ "port": port }));
+    assert_eq!(attach["success"], false);
+    assert!(attach["message"]
+        .as_str()
+        .unwrap()
+        .contains("no Functor runtime"));
+}
@@ -155,7 +155,7 @@ fn diagnostics_over_real_stdio() {
         "jsonrpc": "2.0", "method": "textDocument/didChange",
         "params": {
             "textDocument": { "uri": URI, "version": 2 },
-            "contentChanges": [ { "text": "let x = 3" } ],
+            "contentChanges": [ { "text": "let x = 3.0" } ],
         },
     }));
     let publish = server.recv();
@@ -931,3 +931,518 @@ fn prelude_type_annotations_do_not_squiggle() {
     server.send(json!({ "jsonrpc": "2.0", "method": "exit" }));
     server.child.wait().expect("wait for exit");
 }
+
+#[test]
+fn formatting_and_range_formatting_over_real_stdio() {
+    const TEXT: &str = "let a   = 1\nlet b   = [1,2]\n";
+    let mut server = Server::spawn();
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "capabilities": {} },
+    }));
+    let capabilities = server.recv()["result"]["capabilities"].clone();
+    assert_eq!(capabilities["documentFormattingProvider"], json!(true));
+    assert_eq!(capabilities["documentRangeFormattingProvider"], json!(true));
+    server.send(json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }));
+    server.send(json!({
+        "jsonrpc": "2.0", "method": "textDocument/didOpen",
+        "params": { "textDocument": {
+            "uri": URI, "languageId": "functor-lang", "version": 1, "text": TEXT,
+        } },
+    }));
+    server.recv(); // publishDiagnostics (clean)
+
+    // The whole document: one edit replacing everything.
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 2, "method": "textDocument/formatting",
+        "params": {
+            "textDocument": { "uri": URI },
+            "options": { "tabSize": 2, "insertSpaces": true },
+        },
+    }));
+    assert_eq!(
+        server.recv()["result"],
+        json!([{
+            "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 2, "character": 0 } },
+            "newText": "let a = 1\nlet b = [1, 2]\n",
+        }]),
+    );
+
+    // A selection inside line 2 reformats only that item.
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 3, "method": "textDocument/rangeFormatting",
+        "params": {
+            "textDocument": { "uri": URI },
+            "range": { "start": { "line": 1, "character": 4 }, "end": { "line": 1, "character": 5 } },
+            "options": { "tabSize": 2, "insertSpaces": true },
+        },
+    }));
+    assert_eq!(
+        server.recv()["result"],
+        json!([{
+            "range": { "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 15 } },
+            "newText": "let b = [1, 2]",
+        }]),
+    );
+
+    // An unparseable buffer gets no edits rather than an error.
+    server.send(json!({
+        "jsonrpc": "2.0", "method": "textDocument/didChange",
+        "params": {
+            "textDocument": { "uri": URI },
+            "contentChanges": [{ "text": "let = 3\n" }],
+        },
+    }));
+    server.recv(); // publishDiagnostics (the parse error)
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 4, "method": "textDocument/formatting",
+        "params": { "textDocument": { "uri": URI }, "options": {} },
+    }));
+    assert_eq!(server.recv()["result"], json!([]));
+
+    server.send(json!({ "jsonrpc": "2.0", "id": 9, "method": "shutdown" }));
+    server.recv();
+    server.send(json!({ "jsonrpc": "2.0", "method": "exit" }));
+    server.child.wait().expect("wait for exit");
+}
+
+/// Find-references and rename across a `functor.json` project: references
+/// to a sibling's def come back from both files, a rename edits both, a
+/// collision is refused with a message, and renaming a file module moves
+/// its file.
+#[test]
+fn references_and_rename_across_files_over_real_stdio() {
+    let dir = std::env::temp_dir().join(format!(
+        "functor-lang-lsp-e2e-rename-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("scratch dir");
+    std::fs::write(
+        dir.join("functor.json"),
+        r#"{"language": "functor-lang","entry":"game.fun"}"#,
+    )
+    .unwrap();
+    let game = "let apply = (n) => Utils.double(n)\n";
+    std::fs::write(dir.join("game.fun"), game).unwrap();
+    std::fs::write(
+        dir.join("utils.fun"),
+        "let double = (x: float): float => x * 2.0\nlet twice = double\n",
+    )
+    .unwrap();
+    let game_uri = format!("file://{}/game.fun", dir.display());
+    let utils_uri = format!("file://{}/utils.fun", dir.display());
+
+    let mut server = Server::spawn();
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "capabilities": {} },
+    }));
+    let capabilities = server.recv()["result"]["capabilities"].clone();
+    assert_eq!(capabilities["referencesProvider"], json!(true));
+    assert_eq!(capabilities["renameProvider"], json!({ "prepareProvider": true }));
+    server.send(json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }));
+    server.send(json!({
+        "jsonrpc": "2.0", "method": "textDocument/didOpen",
+        "params": { "textDocument": {
+            "uri": game_uri, "languageId": "functor-lang", "version": 1, "text": game,
+        } },
+    }));
+    server.recv(); // publishDiagnostics (clean)
+
+    let double = json!({ "line": 0, "character": game.find("double").unwrap() });
+    let range = |line: i64, start: i64, end: i64| {
+        json!({
+            "start": { "line": line, "character": start },
+            "end": { "line": line, "character": end },
+        })
+    };
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 2, "method": "textDocument/references",
+        "params": {
+            "textDocument": { "uri": game_uri },
+            "position": double,
+            "context": { "includeDeclaration": false },
+        },
+    }));
+    assert_eq!(
+        server.recv()["result"],
+        json!([
+            { "uri": game_uri, "range": range(0, 25, 31) },
+            { "uri": utils_uri, "range": range(1, 12, 18) },
+        ]),
+    );
+
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 3, "method": "textDocument/prepareRename",
+        "params": { "textDocument": { "uri": game_uri }, "position": double },
+    }));
+    assert_eq!(server.recv()["result"], json!({ "range": range(0, 25, 31) }));
+
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 4, "method": "textDocument/rename",
+        "params": { "textDocument": { "uri": game_uri }, "position": double, "newName": "dbl" },
+    }));
+    let changes = server.recv()["result"]["changes"].clone();
+    assert_eq!(
+        changes[&game_uri],
+        json!([{ "range": range(0, 25, 31), "newText": "dbl" }]),
+    );
+    assert_eq!(changes[&utils_uri].as_array().map(Vec::len), Some(2));
+
+    // `twice` already exists in Utils: refused, with the reason.
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 5, "method": "textDocument/rename",
+        "params": { "textDocument": { "uri": game_uri }, "position": double, "newName": "twice" },
+    }));
+    let response = server.recv();
+    assert_eq!(response["error"]["code"], -32803, "{response}");
+    assert!(
+        response["error"]["message"]
+            .as_str()
+            .is_some_and(|m| m.contains("`Utils.twice` is already defined")),
+        "{response}"
+    );
+
+    // The `Utils` qualifier renames the module: edits plus a file move.
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 6, "method": "textDocument/rename",
+        "params": {
+            "textDocument": { "uri": game_uri },
+            "position": { "line": 0, "character": 20 },
+            "newName": "Maths",
+        },
+    }));
+    let document_changes = server.recv()["result"]["documentChanges"].clone();
+    assert_eq!(
+        document_changes,
+        json!([
+            {
+                "textDocument": { "uri": game_uri, "version": null },
+                "edits": [{ "range": range(0, 19, 24), "newText": "Maths" }],
+            },
+            {
+                "kind": "rename",
+                "oldUri": utils_uri,
+                "newUri": format!("file://{}/maths.fun", dir.display()),
+            },
+        ]),
+    );
+
+    server.send(json!({ "jsonrpc": "2.0", "id": 9, "method": "shutdown" }));
+    server.recv();
+    server.send(json!({ "jsonrpc": "2.0", "method": "exit" }));
+    server.child.wait().expect("wait for exit");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Signature help inside a call and a pipeline stage, the file outline, and
+/// a project-wide symbol search, over a two-file project.
+#[test]
+fn signature_help_and_symbols_over_real_stdio() {
+    let dir = std::env::temp_dir().join(format!(
+        "functor-lang-lsp-e2e-symbols-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("scratch dir");
+    std::fs::write(
+        dir.join("functor.json"),
+        r#"{"language": "functor-lang","entry":"game.fun"}"#,
+    )
+    .unwrap();
+    let game =
+        "type Shape = | Circle(radius: float)\nlet apply = (n) => Utils.clamp(n, 0.0, 1.0)\n";
+    std::fs::write(dir.join("game.fun"), game).unwrap();
+    std::fs::write(
+        dir.join("utils.fun"),
+        "// Clamp v into [lo, hi].\nlet clamp = (v: float, lo: float, hi: float): float => v\n",
+    )
+    .unwrap();
+    let game_uri = format!("file://{}/game.fun", dir.display());
+    let utils_uri = format!("file://{}/utils.fun", dir.display());
+
+    let mut server = Server::spawn();
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "capabilities": {} },
+    }));
+    let capabilities = server.recv()["result"]["capabilities"].clone();
+    assert_eq!(
+        capabilities["signatureHelpProvider"],
+        json!({ "triggerCharacters": ["(", ","] })
+    );
+    assert_eq!(capabilities["documentSymbolProvider"], json!(true));
+    assert_eq!(capabilities["workspaceSymbolProvider"], json!(true));
+    server.send(json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }));
+    server.send(json!({
+        "jsonrpc": "2.0", "method": "textDocument/didOpen",
+        "params": { "textDocument": {
+            "uri": game_uri, "languageId": "functor-lang", "version": 1, "text": game,
+        } },
+    }));
+    server.recv(); // publishDiagnostics (clean)
+
+    let range = |line: i64, start: i64, end: i64| {
+        json!({
+            "start": { "line": line, "character": start },
+            "end": { "line": line, "character": end },
+        })
+    };
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 2, "method": "textDocument/documentSymbol",
+        "params": { "textDocument": { "uri": game_uri } },
+    }));
+    assert_eq!(
+        server.recv()["result"],
+        json!([
+            {
+                "name": "Shape", "detail": null, "kind": 10,
+                "range": range(0, 0, 36), "selectionRange": range(0, 5, 10),
+                "children": [{
+                    "name": "Circle", "detail": "(float)", "kind": 22,
+                    "range": range(0, 15, 36), "selectionRange": range(0, 15, 21),
+                    "children": [],
+                }],
+            },
+            {
+                "name": "apply", "detail": "(n)", "kind": 12,
+                "range": range(1, 0, 43), "selectionRange": range(1, 4, 9),
+                "children": [],
+            },
+        ]),
+    );
+
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 3, "method": "workspace/symbol",
+        "params": { "query": "clmp" },
+    }));
+    assert_eq!(
+        server.recv()["result"],
+        json!([{
+            "name": "clamp", "kind": 12, "containerName": "Utils",
+            "location": { "uri": utils_uri, "range": range(1, 4, 9) },
+        }]),
+    );
+
+    // Mid-edit the call no longer parses; the last good load still answers.
+    let broken = "type Shape = | Circle(radius: float)\nlet apply = (n) => n |> Utils.clamp(0.0, ";
+    server.send(json!({
+        "jsonrpc": "2.0", "method": "textDocument/didChange",
+        "params": {
+            "textDocument": { "uri": game_uri, "version": 2 },
+            "contentChanges": [ { "text": broken } ],
+        },
+    }));
+    server.recv(); // publishDiagnostics (a parse error)
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 4, "method": "textDocument/signatureHelp",
+        "params": {
+            "textDocument": { "uri": game_uri },
+            "position": { "line": 1, "character": 41 },
+        },
+    }));
+    assert_eq!(
+        server.recv()["result"],
+        json!({
+            "signatures": [{
+                "label": "Utils.clamp : (v: float, lo: float, hi: float) => float",
+                "parameters": [
+                    { "label": [15, 23] },
+                    { "label": [25, 34] },
+                    {
+                        "label": [36, 45],
+                        "documentation": "supplied by the `|>` pipeline",
+                    },
+                ],
+                "documentation": { "kind": "markdown", "value": "Clamp v into [lo, hi]." },
+            }],
+            "activeSignature": 0,
+            "activeParameter": 1,
+        }),
+    );
+
+    server.send(json!({ "jsonrpc": "2.0", "id": 9, "method": "shutdown" }));
+    server.recv();
+    server.send(json!({ "jsonrpc": "2.0", "method": "exit" }));
+    server.child.wait().expect("wait for exit");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn code_actions_over_real_stdio() {
+    let dir = std::env::temp_dir().join(format!(
+        "functor-lang-lsp-e2e-actions-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("scratch dir");
+    std::fs::write(
+        dir.join("functor.json"),
+        r#"{"language": "functor-lang","entry":"game.fun"}"#,
+    )
+    .unwrap();
+    let game =
+        "type Dir = | Up | Down\nlet f = (d) => match d with | Up => 1.0\nlet g = (x: Float) => x\n";
+    std::fs::write(dir.join("game.fun"), game).unwrap();
+    let game_uri = format!("file://{}/game.fun", dir.display());
+
+    let mut server = Server::spawn();
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "capabilities": {} },
+    }));
+    let capabilities = server.recv()["result"]["capabilities"].clone();
+    assert_eq!(
+        capabilities["codeActionProvider"],
+        json!({ "codeActionKinds": ["quickfix", "refactor.extract", "refactor.rewrite"] })
+    );
+    server.send(json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }));
+    server.send(json!({
+        "jsonrpc": "2.0", "method": "textDocument/didOpen",
+        "params": { "textDocument": {
+            "uri": game_uri, "languageId": "functor-lang", "version": 1, "text": game,
+        } },
+    }));
+    let diagnostics = server.recv()["params"]["diagnostics"].clone();
+    assert_eq!(diagnostics.as_array().map(Vec::len), Some(2), "{diagnostics}");
+
+    let range = |line: i64, start: i64, end: i64| {
+        json!({
+            "start": { "line": line, "character": start },
+            "end": { "line": line, "character": end },
+        })
+    };
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 2, "method": "textDocument/codeAction",
+        "params": {
+            "textDocument": { "uri": game_uri },
+            "range": range(1, 20, 20),
+            "context": { "diagnostics": [] },
+        },
+    }));
+    let actions = server.recv()["result"].clone();
+    assert_eq!(
+        actions[0],
+        json!({
+            "title": "Add missing arm `Down`",
+            "kind": "quickfix",
+            "edit": { "changes": { game_uri.clone(): [
+                { "range": range(1, 39, 39), "newText": " | Down => 0.0" },
+            ] } },
+            "diagnostics": [diagnostics[0].clone()],
+            "isPreferred": true,
+        }),
+    );
+
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 3, "method": "textDocument/codeAction",
+        "params": {
+            "textDocument": { "uri": game_uri },
+            "range": range(2, 12, 12),
+            "context": { "diagnostics": [] },
+        },
+    }));
+    let actions = server.recv()["result"].clone();
+    assert_eq!(actions[0]["title"], json!("Change to `float`"));
+    assert_eq!(
+        actions[0]["edit"]["changes"][&game_uri],
+        json!([{ "range": range(2, 12, 17), "newText": "float" }]),
+    );
+
+    server.send(json!({ "jsonrpc": "2.0", "id": 9, "method": "shutdown" }));
+    server.recv();
+    server.send(json!({ "jsonrpc": "2.0", "method": "exit" }));
+    server.child.wait().expect("wait for exit");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn semantic_tokens_over_real_stdio() {
+    let dir = std::env::temp_dir().join(format!(
+        "functor-lang-lsp-e2e-semantic-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("scratch dir");
+    std::fs::write(
+        dir.join("functor.json"),
+        r#"{"language": "functor-lang","entry":"game.fun"}"#,
+    )
+    .unwrap();
+    let game = "type Shape = | Circle(radius: float)\nlet c = Circle(1.0)\n";
+    std::fs::write(dir.join("game.fun"), game).unwrap();
+    let game_uri = format!("file://{}/game.fun", dir.display());
+
+    let mut server = Server::spawn();
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "capabilities": {} },
+    }));
+    let provider = server.recv()["result"]["capabilities"]["semanticTokensProvider"].clone();
+    assert_eq!(provider["full"], json!({ "delta": true }));
+    let legend = &provider["legend"];
+    let index = |list: &str, name: &str| {
+        legend[list]
+            .as_array()
+            .unwrap()
+            .iter()
+            .position(|entry| entry == name)
+            .unwrap_or_else(|| panic!("{name} missing from the legend")) as u64
+    };
+    let (ty, member) = (index("tokenTypes", "type"), index("tokenTypes", "enumMember"));
+    let (property, variable) = (index("tokenTypes", "property"), index("tokenTypes", "variable"));
+    let declaration = 1 << index("tokenModifiers", "declaration");
+    server.send(json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }));
+    server.send(json!({
+        "jsonrpc": "2.0", "method": "textDocument/didOpen",
+        "params": { "textDocument": {
+            "uri": game_uri, "languageId": "functor-lang", "version": 1, "text": game,
+        } },
+    }));
+    server.recv(); // publishDiagnostics (clean)
+
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 2, "method": "textDocument/semanticTokens/full",
+        "params": { "textDocument": { "uri": game_uri } },
+    }));
+    let full = server.recv()["result"].clone();
+    assert_eq!(
+        full["data"],
+        json!([
+            0, 5, 5, ty, declaration,
+            0, 10, 6, member, declaration,
+            0, 7, 6, property, declaration,
+            0, 8, 5, ty, 0,
+            1, 4, 1, variable, declaration,
+            0, 4, 6, member, 0,
+        ]),
+    );
+
+    // Renaming `c` to `cc` shifts one length and one start: the delta is a
+    // single splice over them.
+    server.send(json!({
+        "jsonrpc": "2.0", "method": "textDocument/didChange",
+        "params": {
+            "textDocument": { "uri": game_uri, "version": 2 },
+            "contentChanges": [{
+                "text": "type Shape = | Circle(radius: float)\nlet cc = Circle(1.0)\n",
+            }],
+        },
+    }));
+    server.recv(); // publishDiagnostics (clean)
+    server.send(json!({
+        "jsonrpc": "2.0", "id": 3, "method": "textDocument/semanticTokens/full/delta",
+        "params": {
+            "textDocument": { "uri": game_uri },
+            "previousResultId": full["resultId"],
+        },
+    }));
+    let delta = server.recv()["result"].clone();
+    assert_ne!(delta["resultId"], full["resultId"]);
+    assert_eq!(
+        delta["edits"],
+        json!([{ "start": 22, "deleteCount": 5, "data": [2, variable, declaration, 0, 5] }]),
+    );
+
+    server.send(json!({ "jsonrpc": "2.0", "id": 9, "method": "shutdown" }));
+    server.recv();
+    server.send(json!({ "jsonrpc": "2.0", "method": "exit" }));
+    server.child.wait().expect("wait for exit");
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -106,6 +106,23 @@ function activate(context) {
     fs.existsSync
   );
   elog(`language server command: ${serverCommand}`);
+  // The debug adapter (`functor-lang-dap`) resolves like the server; VS Code
+  // spawns it per debug session and speaks DAP over its stdio.
+  context.subscriptions.push(
+    vscode.debug.registerDebugAdapterDescriptorFactory("functor", {
+      createDebugAdapterDescriptor() {
+        const command = resolveServerCommand(
+          vscode.workspace.getConfiguration("functor").get("debugAdapterPath"),
+          context.extensionPath,
+          process.platform,
+          fs.existsSync,
+          "functor-lang-dap"
+        );
+        elog(`debug adapter command: ${command}`);
+        return new vscode.DebugAdapterExecutable(command);
+      },
+    })
+  );
   client = new LanguageClient(
     "functor-lang",
     "Functor Lang Language Server",
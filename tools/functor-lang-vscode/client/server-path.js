@@ -1,23 +1,31 @@
-// Resolve the command used to launch the functor-lang-lsp language server:
+// Resolve the command used to launch the functor-lang-lsp language server
+// (or, with `binary`, its sibling functor-lang-dap debug adapter):
 //
-//   1. the `functor.serverPath` setting, when set — explicit always wins
+//   1. the `functor.serverPath` (`functor.debugAdapterPath`) setting, when
+//      set — explicit always wins
 //   2. the binary bundled inside a platform-specific VSIX
-//      (server/functor-lang-lsp[.exe], staged by the release pipeline)
-//   3. bare "functor-lang-lsp", resolved from PATH (dev checkouts — see
+//      (server/<binary>[.exe], staged by the release pipeline)
+//   3. the bare binary name, resolved from PATH (dev checkouts — see
 //      ../README.md for how to get it there)
 //
 // Pure decision logic with fs injected, node-tested like inspector.js.
 const path = require("path");
 
-function resolveServerCommand(configured, extensionPath, platform, existsSync) {
+function resolveServerCommand(
+  configured,
+  extensionPath,
+  platform,
+  existsSync,
+  binary = "functor-lang-lsp"
+) {
   if (configured) return configured;
   const bundled = path.join(
     extensionPath,
     "server",
-    platform === "win32" ? "functor-lang-lsp.exe" : "functor-lang-lsp"
+    platform === "win32" ? `${binary}.exe` : binary
   );
   if (existsSync(bundled)) return bundled;
-  return "functor-lang-lsp";
+  return binary;
 }
 
 module.exports = { resolveServerCommand };
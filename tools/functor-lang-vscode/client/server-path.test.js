@@ -31,3 +31,12 @@ test("an empty setting is treated as unset", () => {
   const cmd = resolveServerCommand("", EXT, "linux", () => false);
   assert.strictEqual(cmd, "functor-lang-lsp");
 });
+
+test("the debug adapter resolves by the same rules", () => {
+  const bundledDap = path.join(EXT, "server", "functor-lang-dap");
+  const dap = (configured, exists) =>
+    resolveServerCommand(configured, EXT, "linux", exists, "functor-lang-dap");
+  assert.strictEqual(dap("/opt/functor-lang-dap", () => true), "/opt/functor-lang-dap");
+  assert.strictEqual(dap(undefined, (p) => p === bundledDap), bundledDap);
+  assert.strictEqual(dap(undefined, () => false), "functor-lang-dap");
+});
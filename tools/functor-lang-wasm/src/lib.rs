@@ -12,6 +12,16 @@
 //!   diagnostics, inlay hints, and code lenses.
 //! - [`functor_lang_hover`] answers a single hover at an offset.
 //! - [`functor_lang_complete`] answers completion candidates at an offset.
+//! - [`functor_lang_format`] rewrites the buffer into the canonical
+//!   `functor fmt` layout.
+//! - [`functor_lang_signature_help`] answers the enclosing call's signature
+//!   and active parameter at an offset (parameter hints).
+//! - [`functor_lang_symbols`] outlines the buffer (lets, types, constructors,
+//!   inline modules, expects); [`functor_lang_workspace_symbols`] searches
+//!   declaration names across the whole file set.
+//! - [`functor_lang_semantic_tokens`] classifies the buffer's identifiers
+//!   (constructors, host values, builtins, type parameters, unit suffixes,
+//!   assigned bindings) for semantic highlighting.
 //! - The `*_project` variants ([`functor_lang_analyze_project`], …) take the
 //!   WHOLE file set (`[{ "path", "source" }]`, entry first — the IDE's
 //!   multi-file case) plus the active file's path, so cross-module references
@@ -133,6 +143,86 @@ pub fn functor_lang_expects_project(files_json: &str, active: &str, budget: f64)
     expects_project_json(files_json, active, budget.max(0.0) as u64)
 }
 
+/// Format `src` into the canonical `functor fmt` layout (comments kept).
+/// Returns `{"text": str}` — the whole new buffer, equal to `src` when it is
+/// already formatted — or, when `src` doesn't parse,
+/// `{"error": {"from": u16, "to": u16, "message": str}}` (leave the buffer as
+/// it is; the diagnostics already show the error).
+#[wasm_bindgen]
+pub fn functor_lang_format(src: &str) -> String {
+    format_json(src)
+}
+
+/// Signature help at `offset` (a UTF-16 code-unit position): the call the
+/// cursor is inside. Returns `""` outside a call, otherwise
+///
+/// ```json
+/// { "label": str, "params": [[u16, u16]], "active": int|null, "piped": int|null, "doc": str|null }
+/// ```
+///
+/// `params` are each parameter's UTF-16 range WITHIN `label`; `piped` is the
+/// parameter a `|>` pipeline supplies. Shares the last-good cache with
+/// [`functor_lang_complete`] — the call being typed never parses.
+#[wasm_bindgen]
+pub fn functor_lang_signature_help(src: &str, offset: f64) -> String {
+    signature_help_json(src, offset.max(0.0) as usize)
+}
+
+/// Project-aware [`functor_lang_signature_help`]: `offset` is UTF-16, local
+/// to `active` (whose source in `files_json` is the LIVE buffer).
+#[wasm_bindgen]
+pub fn functor_lang_signature_help_project(files_json: &str, active: &str, offset: f64) -> String {
+    signature_help_project_json(files_json, active, offset.max(0.0) as usize)
+}
+
+/// The outline of `src` (a `.fun`, or a `.funi` when `interface`):
+///
+/// ```json
+/// { "symbols": [{ "name": str, "detail": str|null, "kind": str,
+///                 "from": u16, "to": u16, "nameFrom": u16, "nameTo": u16,
+///                 "children": [...] }] }
+/// ```
+///
+/// `kind` is lowercase (`"function"`, `"record"`, `"constructor"`, `"test"`,
+/// …). `{"symbols": null}` means the buffer doesn't parse (keep the previous
+/// outline).
+#[wasm_bindgen]
+pub fn functor_lang_symbols(src: &str, interface: bool) -> String {
+    symbols_json(src, interface)
+}
+
+/// Declarations across the whole file set (`[{ "path", "source" }]`, entry
+/// first) whose name matches `query` (case-insensitive subsequence; empty
+/// matches all): `{"symbols": [{ "name", "kind", "container", "path",
+/// "from", "to" }]}`, `from`/`to` UTF-16 in `path`'s source. `{"symbols":
+/// null}` when the set doesn't load.
+#[wasm_bindgen]
+pub fn functor_lang_workspace_symbols(files_json: &str, query: &str) -> String {
+    workspace_symbols_json(files_json, query)
+}
+
+/// The buffer's semantic tokens, in source order:
+///
+/// ```json
+/// { "tokens": [{ "from": u16, "to": u16, "type": str, "modifiers": [str] }] }
+/// ```
+///
+/// `type` and `modifiers` are the LSP legend's names (`"enumMember"`,
+/// `"function"`, `"unit"`; `"defaultLibrary"`, `"host"`, `"mutable"`, …).
+/// `{"tokens": null}` means the buffer doesn't load (keep the previous
+/// highlighting).
+#[wasm_bindgen]
+pub fn functor_lang_semantic_tokens(src: &str) -> String {
+    semantic_tokens_json(src)
+}
+
+/// Project-aware [`functor_lang_semantic_tokens`]: the tokens of `active`,
+/// in its local UTF-16 offsets.
+#[wasm_bindgen]
+pub fn functor_lang_semantic_tokens_project(files_json: &str, active: &str) -> String {
+    semantic_tokens_project_json(files_json, active)
+}
+
 /// See [`functor_lang_analyze`]. Pure — the tested seam.
 pub fn analyze_json(src: &str) -> String {
     analyze_impl(single(src), Path::new(USER_FILE))
@@ -323,30 +413,41 @@ pub fn complete_project_json(files_json: &str, active: &str, offset: usize) -> S
 }
 
 fn complete_impl(sources: Vec<(PathBuf, String)>, active: &Path, offset: usize) -> String {
+    with_last_good(sources, active, offset, |project, module, inline, live, byte| {
+        let items = functor_lang::complete::complete(project, module, inline, live, byte);
+        completion_json(&items)
+    })
+    .unwrap_or_else(empty_completion)
+}
+
+/// The live-buffer footing completion and signature help share: refresh the
+/// last-good cache when `sources` load cleanly, then run `answer` against it
+/// with the active file's module scope, the inline `module` block the cursor
+/// sits in (found by span), the live text, and the LOCAL byte offset. `None`
+/// when `active` isn't in the set or nothing has loaded yet.
+fn with_last_good(
+    sources: Vec<(PathBuf, String)>,
+    active: &Path,
+    offset: usize,
+    answer: impl FnOnce(&Project, &str, Option<&str>, &str, usize) -> String,
+) -> Option<String> {
     // The active file's source IS the live buffer (the offset contract:
     // context from the live text, candidates from a possibly-stale project).
-    // An `active` outside the set is a caller bug — empty items, not
+    // An `active` outside the set is a caller bug — the empty answer, not
     // entry-scope candidates against an empty buffer.
-    let Some(live) = sources
+    let live = sources
         .iter()
         .find(|(path, _)| path == active)
-        .map(|(_, src)| src.clone())
-    else {
-        return empty_completion();
-    };
+        .map(|(_, src)| src.clone())?;
     let byte = from_u16(&live, offset);
     if let Ok(project) = load_sources(sources) {
         LAST_GOOD.with(|cell| *cell.borrow_mut() = Some(project));
     }
     LAST_GOOD.with(|cell| {
         let borrow = cell.borrow();
-        let Some(project) = borrow.as_ref() else {
-            return empty_completion();
-        };
-        // Complete in the active file's module scope — plus the inline
-        // `module` block the cursor sits in, found by span. A file the cached
-        // project doesn't know (just created, buffer still broken) falls back
-        // to the entry module.
+        let project = borrow.as_ref()?;
+        // A file the cached project doesn't know (just created, buffer still
+        // broken) falls back to the entry module.
         let file = project.sources.file_by_path(active);
         let module = file.map_or_else(|| project.entry.clone(), |file| file.module.clone());
         let inline = file
@@ -358,10 +459,145 @@ fn complete_impl(sources: Vec<(PathBuf, String)>, active: &Path, offset: usize)
                     .filter(|module| module.file == file.module)
             })
             .map(|module| module.path.clone());
-        let items =
-            functor_lang::complete::complete(project, &module, inline.as_deref(), &live, byte);
-        completion_json(&items)
+        Some(answer(project, &module, inline.as_deref(), &live, byte))
+    })
+}
+
+/// See [`functor_lang_signature_help`]. Pure — the tested seam.
+pub fn signature_help_json(src: &str, offset: usize) -> String {
+    signature_help_impl(single(src), Path::new(USER_FILE), offset)
+}
+
+/// See [`functor_lang_signature_help_project`]. Pure — the tested seam.
+pub fn signature_help_project_json(files_json: &str, active: &str, offset: usize) -> String {
+    let Some(sources) = parse_files(files_json) else {
+        return String::new();
+    };
+    signature_help_impl(sources, Path::new(active), offset)
+}
+
+fn signature_help_impl(sources: Vec<(PathBuf, String)>, active: &Path, offset: usize) -> String {
+    with_last_good(sources, active, offset, |project, module, inline, live, byte| {
+        let Some(help) =
+            functor_lang::signature::signature_help(project, module, inline, live, byte)
+        else {
+            return String::new();
+        };
+        let params: Vec<Value> = help
+            .params
+            .iter()
+            .map(|&(start, end)| {
+                json!([utf16_len(&help.label[..start]), utf16_len(&help.label[..end])])
+            })
+            .collect();
+        json!({
+            "label": help.label,
+            "params": params,
+            "active": help.active,
+            "piped": help.piped,
+            "doc": help.doc,
+        })
+        .to_string()
     })
+    .unwrap_or_default()
+}
+
+/// See [`functor_lang_symbols`]. Pure — the tested seam.
+pub fn symbols_json(src: &str, interface: bool) -> String {
+    let Ok(symbols) = functor_lang::symbols::document_symbols(src, interface) else {
+        return json!({ "symbols": Value::Null }).to_string();
+    };
+    fn convert(src: &str, symbols: &[functor_lang::symbols::DocumentSymbol]) -> Vec<Value> {
+        let at = |offset: usize| utf16_len(&src[..offset.min(src.len())]);
+        symbols
+            .iter()
+            .map(|symbol| {
+                json!({
+                    "name": symbol.name,
+                    "detail": symbol.detail,
+                    "kind": symbol_kind_str(symbol.kind),
+                    "from": at(symbol.span.start),
+                    "to": at(symbol.span.end),
+                    "nameFrom": at(symbol.selection.start),
+                    "nameTo": at(symbol.selection.end),
+                    "children": convert(src, &symbol.children),
+                })
+            })
+            .collect()
+    }
+    json!({ "symbols": convert(src, &symbols) }).to_string()
+}
+
+/// See [`functor_lang_workspace_symbols`]. Pure — the tested seam.
+pub fn workspace_symbols_json(files_json: &str, query: &str) -> String {
+    let project = parse_files(files_json).and_then(|sources| load_sources(sources).ok());
+    let Some(project) = project else {
+        return json!({ "symbols": Value::Null }).to_string();
+    };
+    let symbols: Vec<Value> = functor_lang::symbols::workspace_symbols(&project, query)
+        .into_iter()
+        .map(|symbol| {
+            let file = project.sources.file_at(symbol.span.start);
+            json!({
+                "name": symbol.name,
+                "kind": symbol_kind_str(symbol.kind),
+                "container": symbol.container,
+                "path": file.path.to_string_lossy(),
+                "from": to_u16(file, symbol.span.start),
+                "to": to_u16(file, symbol.span.end),
+            })
+        })
+        .collect();
+    json!({ "symbols": symbols }).to_string()
+}
+
+/// See [`functor_lang_semantic_tokens`]. Pure — the tested seam.
+pub fn semantic_tokens_json(src: &str) -> String {
+    semantic_tokens_impl(single(src), Path::new(USER_FILE))
+}
+
+/// See [`functor_lang_semantic_tokens_project`]. Pure — the tested seam.
+pub fn semantic_tokens_project_json(files_json: &str, active: &str) -> String {
+    let Some(sources) = parse_files(files_json) else {
+        return json!({ "tokens": Value::Null }).to_string();
+    };
+    semantic_tokens_impl(sources, Path::new(active))
+}
+
+fn semantic_tokens_impl(sources: Vec<(PathBuf, String)>, active: &Path) -> String {
+    let project = load_sources(sources).ok();
+    let Some((project, file)) = project
+        .as_ref()
+        .and_then(|project| Some((project, project.sources.file_by_path(active)?)))
+    else {
+        return json!({ "tokens": Value::Null }).to_string();
+    };
+    let (_, types) = project.check_with_types();
+    let tokens: Vec<Value> = functor_lang::semantic::semantic_tokens(project, &types, file)
+        .into_iter()
+        .map(|token| {
+            let modifiers: Vec<&str> = token.modifiers.iter().map(|m| m.name()).collect();
+            json!({
+                "from": to_u16(file, token.span.start),
+                "to": to_u16(file, token.span.end),
+                "type": token.ty.name(),
+                "modifiers": modifiers,
+            })
+        })
+        .collect();
+    json!({ "tokens": tokens }).to_string()
+}
+
+/// See [`functor_lang_format`]. Pure — the tested seam.
+pub fn format_json(src: &str) -> String {
+    match functor_lang::format::format_source(src, false) {
+        Ok(text) => json!({ "text": text }).to_string(),
+        Err(err) => {
+            let from = utf16_len(&src[..err.span.start.min(src.len())]);
+            let to = utf16_len(&src[..err.span.end.min(src.len())]);
+            json!({ "error": { "from": from, "to": to, "message": err.message } }).to_string()
+        }
+    }
 }
 
 /// See [`functor_lang_reset`]. Pure — the tested seam. Drops the last-good
@@ -404,6 +640,22 @@ fn kind_str(kind: CompletionKind) -> &'static str {
     }
 }
 
+/// A symbol kind as a lowercase string (the outline's icon class).
+fn symbol_kind_str(kind: functor_lang::symbols::SymbolKind) -> &'static str {
+    use functor_lang::symbols::SymbolKind;
+    match kind {
+        SymbolKind::Module => "module",
+        SymbolKind::Function => "function",
+        SymbolKind::Value => "value",
+        SymbolKind::Record => "record",
+        SymbolKind::Variant => "variant",
+        SymbolKind::Type => "type",
+        SymbolKind::Constructor => "constructor",
+        SymbolKind::Field => "field",
+        SymbolKind::Test => "test",
+    }
+}
+
 /// The single-file wrappers' file set: `src` as the one `game.fun`.
 fn single(src: &str) -> Vec<(PathBuf, String)> {
     vec![(PathBuf::from(USER_FILE), src.to_string())]
@@ -979,4 +1231,108 @@ mod tests {
         assert_eq!(&src[from_u16(src, from)..from_u16(src, to)], "spin");
         assert!(v["text"].as_str().unwrap().contains("float"), "{out}");
     }
+
+    // Format returns the canonical text, or a UTF-16 error span when the
+    // buffer doesn't parse (after a non-ASCII literal, so units ≠ bytes).
+    #[test]
+    fn format_returns_text_or_a_utf16_error() {
+        let out = parse(&format_json("let   x=[1,2] // keep\n"));
+        assert_eq!(out["text"], "let x = [1, 2] // keep\n");
+
+        let src = "let label = \"café→\"\nlet = 3\n";
+        let out = parse(&format_json(src));
+        let from = out["error"]["from"].as_u64().unwrap() as usize;
+        assert_eq!(from_u16(src, from), src.rfind('=').unwrap());
+        assert!(out.get("text").is_none(), "{out}");
+    }
+
+    // Signature help answers off the last-good cache while the call is
+    // mid-edit, with parameter ranges in UTF-16 units of the label.
+    #[test]
+    fn signature_help_inside_a_broken_call() {
+        reset_cache();
+        let prime =
+            "// Spin by turns.\nlet spin = (turns: float, speed: float): float => turns * speed\n";
+        assert_eq!(signature_help_json(prime, 0), "");
+        let live = format!("{prime}let x = spin(1.0, ");
+        let out = parse(&signature_help_json(&live, utf16_len(&live)));
+        assert_eq!(out["label"], "spin : (turns: float, speed: float) => float");
+        assert_eq!(out["params"], json!([[8, 20], [22, 34]]));
+        assert_eq!(out["active"], 1);
+        assert_eq!(out["piped"], Value::Null);
+        assert_eq!(out["doc"], "Spin by turns.");
+
+        let live = format!("{prime}let x = 2.0 |> spin(");
+        let out = parse(&signature_help_json(&live, utf16_len(&live)));
+        assert_eq!((out["active"].clone(), out["piped"].clone()), (json!(0), json!(1)));
+    }
+
+    // The outline nests constructors under their type, in UTF-16 offsets.
+    #[test]
+    fn symbols_outline_the_buffer() {
+        let src = "let label = \"café→\"\ntype Shape = | Circle(radius: float)\n";
+        let out = parse(&symbols_json(src, false));
+        let shape = &out["symbols"][1];
+        assert_eq!(shape["name"], "Shape");
+        assert_eq!(shape["kind"], "variant");
+        let from = shape["nameFrom"].as_u64().unwrap() as usize;
+        assert_eq!(from_u16(src, from), src.find("Shape").unwrap());
+        assert_eq!(shape["children"][0]["name"], "Circle");
+        assert_eq!(shape["children"][0]["kind"], "constructor");
+        assert_eq!(parse(&symbols_json("let = 3", false))["symbols"], Value::Null);
+    }
+
+    // Workspace search spans the whole file set, skipping bundled modules.
+    #[test]
+    fn workspace_symbols_search_every_file() {
+        let files = json!([
+            { "path": "game.fun", "source": "let glow = Palette.base\n" },
+            { "path": "palette.fun", "source": "let base = 1.0\nlet bright = 2.0\n" },
+        ])
+        .to_string();
+        let out = parse(&workspace_symbols_json(&files, "b"));
+        assert_eq!(
+            out["symbols"],
+            json!([
+                {
+                    "name": "base", "kind": "value", "container": "Palette",
+                    "path": "palette.fun", "from": 4, "to": 8,
+                },
+                {
+                    "name": "bright", "kind": "value", "container": "Palette",
+                    "path": "palette.fun", "from": 19, "to": 25,
+                },
+            ])
+        );
+    }
+
+    // Tokens come from the checked program: a host value and a builtin are
+    // told apart, and offsets are UTF-16.
+    #[test]
+    fn semantic_tokens_classify_the_buffer() {
+        let src = "let label = \"café→\"\nlet c = Scene.cube()\nlet n = Math.sin(1.0)\n";
+        let out = parse(&semantic_tokens_json(src));
+        let tokens: Vec<(String, &str, Vec<&str>)> = out["tokens"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|token| {
+                let from = from_u16(src, token["from"].as_u64().unwrap() as usize);
+                let to = from_u16(src, token["to"].as_u64().unwrap() as usize);
+                let modifiers = token["modifiers"].as_array().unwrap();
+                (
+                    src[from..to].to_string(),
+                    token["type"].as_str().unwrap(),
+                    modifiers.iter().map(|m| m.as_str().unwrap()).collect(),
+                )
+            })
+            .collect();
+        assert!(tokens.contains(&("cube".to_string(), "function", vec!["host"])));
+        assert!(tokens.contains(&("sin".to_string(), "function", vec!["defaultLibrary"])));
+        assert!(tokens.contains(&("Scene".to_string(), "namespace", vec![])));
+        assert_eq!(
+            parse(&semantic_tokens_json("let = 3"))["tokens"],
+            Value::Null
+        );
+    }
 }
@@ -216,6 +216,10 @@ export interface Scene {
   camera: Camera;
   scene: unknown;
   lights: unknown;
+  /** The `Frame.withPost` stack in application order, e.g.
+   * `{ "Bloom": { "threshold": 0.6, "intensity": 1.2 } }` or `"Fxaa"`.
+   * Absent when the frame has no post effects. */
+  post?: unknown[];
 }
 
 /** An input event for `POST /input`, tagged by `type`. */